@@ -1,4 +1,4 @@
-use crate::types::{AttrValue, Attrs, Span};
+use crate::types::{AttrSpan, AttrSpans, AttrValue, Attrs, FileId, Span};
 use crate::error::ParseError;
 
 /// Parse a SurfDoc attribute string into an ordered map.
@@ -11,10 +11,22 @@ use crate::error::ParseError;
 /// Numeric values are stored as `AttrValue::Number`. Everything else is
 /// `AttrValue::String`.
 pub fn parse_attrs(input: &str) -> Result<Attrs, ParseError> {
+    parse_attrs_spanned(input).map(|(attrs, _)| attrs)
+}
+
+/// [`parse_attrs`], also returning where each key and value sits in `input`.
+///
+/// Spans are byte ranges into `input` with zero line numbers; callers that
+/// know where `input` came from rebase them. A value span covers the value as
+/// written, quotes included. When a key repeats, the last occurrence wins in
+/// both maps.
+pub fn parse_attrs_spanned(input: &str) -> Result<(Attrs, AttrSpans), ParseError> {
     let trimmed = input.trim();
+    let mut base = input.len() - input.trim_start().len();
 
     // Strip surrounding brackets if present.
     let inner = if trimmed.starts_with('[') && trimmed.ends_with(']') {
+        base += 1;
         &trimmed[1..trimmed.len() - 1]
     } else {
         trimmed
@@ -22,8 +34,22 @@ pub fn parse_attrs(input: &str) -> Result<Attrs, ParseError> {
 
     let chars: Vec<char> = inner.chars().collect();
     let len = chars.len();
+    // Byte offset in `input` of each char index (and of the end).
+    let offsets: Vec<usize> = inner
+        .char_indices()
+        .map(|(b, _)| base + b)
+        .chain(std::iter::once(base + inner.len()))
+        .collect();
+    let span_of = |from: usize, to: usize| Span {
+        start_line: 0,
+        end_line: 0,
+        start_offset: offsets[from],
+        end_offset: offsets[to],
+        file: FileId::ROOT,
+    };
     let mut pos = 0;
     let mut attrs = Attrs::new();
+    let mut spans = AttrSpans::new();
 
     while pos < len {
         // Skip whitespace.
@@ -49,15 +75,18 @@ pub fn parse_attrs(input: &str) -> Result<Attrs, ParseError> {
                     end_line: 0,
                     start_offset: pos,
                     end_offset: pos + 1,
+                    file: FileId::ROOT,
                 },
             });
         }
 
         let key: String = chars[key_start..pos].iter().collect();
+        let key_span = span_of(key_start, pos);
 
         // Check for `=`.
         if pos < len && chars[pos] == '=' {
             pos += 1; // consume `=`
+            let val_start = pos;
 
             if pos >= len {
                 return Err(ParseError::InvalidAttrs {
@@ -67,6 +96,7 @@ pub fn parse_attrs(input: &str) -> Result<Attrs, ParseError> {
                         end_line: 0,
                         start_offset: pos,
                         end_offset: pos,
+                        file: FileId::ROOT,
                     },
                 });
             }
@@ -103,26 +133,73 @@ pub fn parse_attrs(input: &str) -> Result<Attrs, ParseError> {
                             end_line: 0,
                             start_offset: key_start,
                             end_offset: pos,
+                            file: FileId::ROOT,
                         },
                     });
                 }
+                spans.insert(
+                    key.clone(),
+                    AttrSpan {
+                        key: key_span,
+                        value: Some(span_of(val_start, pos)),
+                    },
+                );
                 attrs.insert(key, AttrValue::String(value));
             } else {
                 // Unquoted value: read until whitespace or `]`.
-                let val_start = pos;
                 while pos < len && !chars[pos].is_whitespace() && chars[pos] != ']' {
                     pos += 1;
                 }
                 let raw: String = chars[val_start..pos].iter().collect();
+                spans.insert(
+                    key.clone(),
+                    AttrSpan {
+                        key: key_span,
+                        value: Some(span_of(val_start, pos)),
+                    },
+                );
                 attrs.insert(key, coerce_value(&raw));
             }
         } else {
             // No `=` — boolean flag.
+            spans.insert(
+                key.clone(),
+                AttrSpan {
+                    key: key_span,
+                    value: None,
+                },
+            );
             attrs.insert(key, AttrValue::Bool(true));
         }
     }
 
-    Ok(attrs)
+    Ok((attrs, spans))
+}
+
+/// [`parse_attrs_spanned`] for an attribute group found at byte `offset` on
+/// 1-based source line `line`: the returned spans are absolute.
+pub(crate) fn parse_attrs_at(
+    input: &str,
+    offset: usize,
+    line: usize,
+) -> Result<(Attrs, AttrSpans), ParseError> {
+    let (attrs, mut spans) = parse_attrs_spanned(input)?;
+    let rebase = |s: &mut Span| {
+        *s = Span {
+            start_line: line,
+            end_line: line,
+            start_offset: offset + s.start_offset,
+            end_offset: offset + s.end_offset,
+            file: FileId::ROOT,
+        }
+    };
+    for attr in spans.values_mut() {
+        rebase(&mut attr.key);
+        if let Some(value) = attr.value.as_mut() {
+            rebase(value);
+        }
+    }
+    Ok((attrs, spans))
 }
 
 /// Coerce an unquoted value string into the most specific `AttrValue`:
@@ -214,6 +291,29 @@ mod tests {
         assert_eq!(attrs["val"], AttrValue::Null);
     }
 
+    #[test]
+    fn spanned_points_at_key_and_value() {
+        let input = r#"[id=x sortable  title="a b"]"#;
+        let (attrs, spans) = parse_attrs_spanned(input).unwrap();
+        assert_eq!(attrs.len(), 3);
+        let text = |s: Span| &input[s.start_offset..s.end_offset];
+        assert_eq!(text(spans["id"].key), "id");
+        assert_eq!(text(spans["id"].value.unwrap()), "x");
+        assert_eq!(text(spans["sortable"].key), "sortable");
+        assert_eq!(spans["sortable"].value, None);
+        assert_eq!(text(spans["title"].value.unwrap()), r#""a b""#);
+    }
+
+    #[test]
+    fn spanned_offsets_are_bytes_into_input() {
+        let input = r#"  [note="héllo" k=v]"#;
+        let (_, spans) = parse_attrs_spanned(input).unwrap();
+        let k = spans["k"].key;
+        assert_eq!(&input[k.start_offset..k.end_offset], "k");
+        let v = spans["note"].value.unwrap();
+        assert_eq!(&input[v.start_offset..v.end_offset], r#""héllo""#);
+    }
+
     #[test]
     fn parse_float_number() {
         let attrs = parse_attrs("[ratio=3.14]").unwrap();
@@ -0,0 +1,112 @@
+//! `.surflint.toml` loading and discovery, shared by the `surf-lint` and
+//! `surf-lsp` binaries (`#[path]`-included, so both resolve configuration
+//! identically). The lint core only ever sees the resulting [`LintConfig`].
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use surf_parse::lint::{CustomRule, validate_custom_rules};
+use surf_parse::{LintConfig, ParseOptions, Severity};
+
+/// `.surflint.toml` schema v1.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SurfLintToml {
+    /// Rule id → "error" | "warning" | "info" | "off".
+    #[serde(default)]
+    severity: BTreeMap<String, String>,
+    /// Extra allowed front matter enum values (suppress P005 for them).
+    #[serde(default)]
+    frontmatter: FrontMatterToml,
+    /// Parse resource bounds, shared with the wasm/uniffi entry points.
+    #[serde(default)]
+    limits: ParseOptions,
+    /// Project-local declarative rules (`[[rules]]`).
+    #[serde(default)]
+    rules: Vec<CustomRule>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FrontMatterToml {
+    #[serde(default)]
+    extra_types: Vec<String>,
+    #[serde(default)]
+    extra_statuses: Vec<String>,
+    #[serde(default)]
+    extra_scopes: Vec<String>,
+}
+
+impl SurfLintToml {
+    fn into_lint_config(self, origin: &Path) -> Result<LintConfig, String> {
+        let mut cfg = LintConfig::default();
+        for (rule, value) in self.severity {
+            match value.as_str() {
+                "error" => cfg.severity_overrides.insert(rule, Severity::Error),
+                "warning" => cfg.severity_overrides.insert(rule, Severity::Warning),
+                "info" => cfg.severity_overrides.insert(rule, Severity::Info),
+                "off" => {
+                    cfg.disabled_rules.insert(rule);
+                    None
+                }
+                other => {
+                    return Err(format!(
+                        "invalid config '{}': [severity] {rule} = {other:?} — expected \"error\", \"warning\", \"info\", or \"off\"",
+                        origin.display()
+                    ));
+                }
+            };
+        }
+        let mut extend = |field: &str, values: Vec<String>| {
+            if !values.is_empty() {
+                cfg.extra_frontmatter_values
+                    .entry(field.to_string())
+                    .or_insert_with(BTreeSet::new)
+                    .extend(values);
+            }
+        };
+        extend("type", self.frontmatter.extra_types);
+        extend("status", self.frontmatter.extra_statuses);
+        extend("scope", self.frontmatter.extra_scopes);
+        cfg.parse_options = self.limits;
+        validate_custom_rules(&self.rules)
+            .map_err(|e| format!("invalid config '{}': [[rules]] {e}", origin.display()))?;
+        cfg.custom_rules = self.rules;
+        Ok(cfg)
+    }
+}
+
+/// Load and translate a `.surflint.toml`. Any read/parse/value error names
+/// the offending path; surf-lint treats it as an internal error (exit 3).
+pub fn load_config_file(path: &Path) -> Result<LintConfig, String> {
+    let raw = fs::read_to_string(path)
+        .map_err(|e| format!("cannot read config '{}': {e}", path.display()))?;
+    let parsed: SurfLintToml =
+        toml::from_str(&raw).map_err(|e| format!("invalid config '{}': {e}", path.display()))?;
+    parsed.into_lint_config(path)
+}
+
+/// First `.surflint.toml` found from `start` walking up to the root.
+pub fn discover_config_path(start: &Path) -> Option<PathBuf> {
+    let mut dir = Some(start);
+    while let Some(d) = dir {
+        let candidate = d.join(".surflint.toml");
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        dir = d.parent();
+    }
+    None
+}
+
+/// Config for an input whose discovery starts at `start` (a file's parent
+/// directory, or the current directory for stdin): the nearest
+/// `.surflint.toml`, or defaults when there is none.
+pub fn discover_config(start: &Path) -> Result<LintConfig, String> {
+    match discover_config_path(start) {
+        Some(path) => load_config_file(&path),
+        None => Ok(LintConfig::default()),
+    }
+}
@@ -12,27 +12,35 @@
 //! | Code | Meaning |
 //! |------|---------|
 //! | 0    | clean — no errors (and no warnings, or warnings not denied) |
-//! | 1    | errors found; for `fix`: errors remain after fixing, or the fix loop did not converge |
+//! | 1    | errors found; for `fix`: errors remain after fixing, or the fix loop did not converge; for `fmt --check`: a file is not formatted |
 //! | 2    | warnings present AND `--deny-warnings` |
 //! | 3    | internal/IO error (unreadable path, bad usage, write failure) |
 //!
 //! Multi-file runs aggregate to the MAX code across files. `fix --dry-run`
 //! does NOT exit non-zero merely because changes would be applied — it exits
 //! per check semantics on the issues that would REMAIN after the simulated
-//! fix (documented contract).
+//! fix (documented contract). `fmt` without `--check` exits 0 after
+//! rewriting files; `--check` exits 1 when any file would change.
 
-use std::collections::{BTreeMap, BTreeSet};
+#[path = "shared/config.rs"]
+mod config;
+
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::{IsTerminal, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
 use clap::{Parser, Subcommand, ValueEnum};
-use serde::Deserialize;
 use surf_parse::lint::{
-    JSON_SCHEMA_VERSION, MAX_FIX_ITERATIONS, apply_fixes_with, reports_to_json, rule_registry,
+    CustomRule, JSON_SCHEMA_VERSION, MAX_FIX_ITERATIONS, apply_fixes_with, reports_to_json,
+    reports_to_junit, reports_to_sarif, rule_registry,
+};
+use surf_parse::{
+    CheckReport, Diagnostic, FixSafety, FormatOptions, LintConfig, Severity, check_with, format,
 };
-use surf_parse::{CheckReport, Diagnostic, FixSafety, LintConfig, Severity, check_with};
+
+use config::{discover_config, load_config_file};
 
 const EXIT_CLEAN: u8 = 0;
 const EXIT_ERRORS: u8 = 1;
@@ -48,10 +56,11 @@ const DIFF_CONTEXT: usize = 3;
     version,
     about = "Deterministic check/diagnose/fix for SurfDoc (.surf) files",
     long_about = "Deterministic check/diagnose/fix for SurfDoc (.surf) files.\n\n\
-        Exit codes: 0 clean; 1 errors found (fix: errors remain or no convergence);\n\
+        Exit codes: 0 clean; 1 errors found (fix: errors remain or no convergence;\n\
+        fmt --check: a file is not formatted);\n\
         2 warnings present with --deny-warnings; 3 internal/IO error.\n\
         Multi-file runs exit with the MAX code across files.\n\n\
-        Configuration (.surflint.toml, schema v1): check/fix look for a\n\
+        Configuration (.surflint.toml, schema v1): check/fix/fmt look for a\n\
         .surflint.toml next to each target file, walking parent directories up\n\
         to the filesystem root (first found wins; stdin searches from the\n\
         current directory). --config <path> overrides discovery; --no-config\n\
@@ -62,7 +71,17 @@ const DIFF_CONTEXT: usize = 3;
         \x20   [frontmatter]             # extra allowed enum values (suppress P005)\n\
         \x20   extra_types = [\"checkpoint\", \"spec\"]\n\
         \x20   extra_statuses = [\"superseded\"]\n\
-        \x20   extra_scopes = [\"org\"]"
+        \x20   extra_scopes = [\"org\"]\n\n\
+        \x20   [limits]                  # parse resource bounds (exceeded: P007 error)\n\
+        \x20   max_depth = 16            # also max_blocks, max_source_bytes, max_attr_len\n\n\
+        \x20   [[rules]]                 # project-local rule (id must not be P/V/L + digits)\n\
+        \x20   id = \"X001\"\n\
+        \x20   block = \"decision\"        # omit for a whole-document rule\n\
+        \x20   require_attrs = [\"deciders\"]\n\
+        \x20   message = \"::{block} must have `{attr}`\"\n\
+        \x20   # also: severity, front_matter = { type = \"incident\" }, forbid_attrs,\n\
+        \x20   # attr_prefix = { src = \"https://\" }, attr_values, require_children,\n\
+        \x20   # forbid_children, max_heading_depth"
 )]
 struct Cli {
     #[command(subcommand)]
@@ -110,6 +129,21 @@ enum Command {
         #[arg(long)]
         no_config: bool,
     },
+    /// Rewrite files into canonical layout ('-' formats stdin to stdout).
+    Fmt {
+        /// Files, directories (recursed for *.surf), or '-' for stdin.
+        #[arg(required = true)]
+        paths: Vec<String>,
+        /// Write nothing; print a diff and exit 1 if any file would change.
+        #[arg(long)]
+        check: bool,
+        /// Use this .surflint.toml instead of per-file discovery.
+        #[arg(long, conflicts_with = "no_config")]
+        config: Option<PathBuf>,
+        /// Ignore .surflint.toml files entirely.
+        #[arg(long)]
+        no_config: bool,
+    },
     /// Print the lint rule registry (spec/rules.toml).
     Rules {
         /// Output format.
@@ -126,6 +160,10 @@ enum Format {
     Json,
     /// GitHub Actions workflow annotations.
     Github,
+    /// SARIF 2.1.0 log (code-scanning dashboards), with rule metadata and fixes.
+    Sarif,
+    /// JUnit XML (test-report widgets): one test case per diagnostic.
+    Junit,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
@@ -188,97 +226,27 @@ fn run(command: &Command) -> Result<u8, String> {
             *diff,
             &ConfigSource::new(config.as_deref(), *no_config)?,
         ),
+        Command::Fmt {
+            paths,
+            check,
+            config,
+            no_config,
+        } => run_fmt(
+            paths,
+            *check,
+            &ConfigSource::new(config.as_deref(), *no_config)?,
+        ),
         Command::Rules { format } => run_rules(*format),
     }
 }
 
 // ------------------------------------------------------------------
 // .surflint.toml configuration (CLI-side only — the lint core takes the
-// resulting LintConfig struct and never touches the filesystem)
+// resulting LintConfig struct and never touches the filesystem). The file
+// format and discovery live in `shared/config.rs`, shared with surf-lsp.
 // ------------------------------------------------------------------
 
-/// `.surflint.toml` schema v1.
-#[derive(Debug, Default, Deserialize)]
-#[serde(deny_unknown_fields)]
-struct SurfLintToml {
-    /// Rule id → "error" | "warning" | "info" | "off".
-    #[serde(default)]
-    severity: BTreeMap<String, String>,
-    /// Extra allowed front matter enum values (suppress P005 for them).
-    #[serde(default)]
-    frontmatter: FrontMatterToml,
-}
-
-#[derive(Debug, Default, Deserialize)]
-#[serde(deny_unknown_fields)]
-struct FrontMatterToml {
-    #[serde(default)]
-    extra_types: Vec<String>,
-    #[serde(default)]
-    extra_statuses: Vec<String>,
-    #[serde(default)]
-    extra_scopes: Vec<String>,
-}
-
-impl SurfLintToml {
-    fn into_lint_config(self, origin: &Path) -> Result<LintConfig, String> {
-        let mut cfg = LintConfig::default();
-        for (rule, value) in self.severity {
-            match value.as_str() {
-                "error" => cfg.severity_overrides.insert(rule, Severity::Error),
-                "warning" => cfg.severity_overrides.insert(rule, Severity::Warning),
-                "info" => cfg.severity_overrides.insert(rule, Severity::Info),
-                "off" => {
-                    cfg.disabled_rules.insert(rule);
-                    None
-                }
-                other => {
-                    return Err(format!(
-                        "invalid config '{}': [severity] {rule} = {other:?} — expected \"error\", \"warning\", \"info\", or \"off\"",
-                        origin.display()
-                    ));
-                }
-            };
-        }
-        let mut extend = |field: &str, values: Vec<String>| {
-            if !values.is_empty() {
-                cfg.extra_frontmatter_values
-                    .entry(field.to_string())
-                    .or_insert_with(BTreeSet::new)
-                    .extend(values);
-            }
-        };
-        extend("type", self.frontmatter.extra_types);
-        extend("status", self.frontmatter.extra_statuses);
-        extend("scope", self.frontmatter.extra_scopes);
-        Ok(cfg)
-    }
-}
-
-/// Load and translate a `.surflint.toml`. Any read/parse/value error is an
-/// internal error (exit 3) with the offending path in the message.
-fn load_config_file(path: &Path) -> Result<LintConfig, String> {
-    let raw = fs::read_to_string(path)
-        .map_err(|e| format!("cannot read config '{}': {e}", path.display()))?;
-    let parsed: SurfLintToml =
-        toml::from_str(&raw).map_err(|e| format!("invalid config '{}': {e}", path.display()))?;
-    parsed.into_lint_config(path)
-}
-
-/// First `.surflint.toml` found from `start` walking up to the root.
-fn discover_config_path(start: &Path) -> Option<PathBuf> {
-    let mut dir = Some(start);
-    while let Some(d) = dir {
-        let candidate = d.join(".surflint.toml");
-        if candidate.is_file() {
-            return Some(candidate);
-        }
-        dir = d.parent();
-    }
-    None
-}
-
-/// How check/fix resolve the [`LintConfig`] for each input.
+/// How check/fix/fmt resolve the [`LintConfig`] for each input.
 enum ConfigSource {
     /// `--no-config`: defaults everywhere.
     Disabled,
@@ -326,10 +294,7 @@ impl ConfigSource {
         if let Some(cfg) = cache.borrow().get(&start) {
             return Ok(cfg.clone());
         }
-        let cfg = match discover_config_path(&start) {
-            Some(path) => load_config_file(&path)?,
-            None => LintConfig::default(),
-        };
+        let cfg = discover_config(&start)?;
         cache.borrow_mut().insert(start, cfg.clone());
         Ok(cfg)
     }
@@ -428,7 +393,10 @@ fn run_check(
     let mut exit = EXIT_CLEAN;
     let mut files_checked = 0usize;
     let mut totals = CheckTotals::default();
-    let mut json_reports: Vec<(String, CheckReport)> = Vec::new();
+    // Whole-run formats (json/sarif/junit) render after the last file.
+    let mut collected: Vec<(String, String, CheckReport)> = Vec::new();
+    // Custom rules seen across every input's config, for SARIF descriptors.
+    let mut custom_rules: Vec<CustomRule> = Vec::new();
 
     for input in inputs {
         let label = input.label();
@@ -445,6 +413,11 @@ fn run_check(
         // works on; render excerpts/offsets against the same text.
         let source = content.replace("\r\n", "\n");
         let report = check_with(&source, &cfg);
+        for rule in cfg.custom_rules {
+            if !custom_rules.iter().any(|r| r.id == rule.id) {
+                custom_rules.push(rule);
+            }
+        }
         files_checked += 1;
         totals.add(&report);
         exit = exit.max(check_exit_code(&report, deny_warnings));
@@ -454,9 +427,13 @@ fn run_check(
                 render_human_file(&mut out, &label, &source, &report, color).map_err(stdout_err)?
             }
             Format::Github => render_github_file(&mut out, &label, &report).map_err(stdout_err)?,
-            Format::Json => json_reports.push((label, report)),
+            Format::Json | Format::Sarif | Format::Junit => collected.push((label, source, report)),
         }
     }
+    let labeled: Vec<(&str, &str, &CheckReport)> = collected
+        .iter()
+        .map(|(label, source, report)| (label.as_str(), source.as_str(), report))
+        .collect();
 
     match format {
         Format::Human => {
@@ -465,15 +442,21 @@ fn run_check(
         Format::Json => {
             // Shared envelope construction (surf_parse::lint) — identical
             // structure to the wasm `check_json` export.
-            let pairs: Vec<(Option<&str>, &CheckReport)> = json_reports
+            let pairs: Vec<(Option<&str>, &CheckReport)> = labeled
                 .iter()
-                .map(|(label, report)| (Some(label.as_str()), report))
+                .map(|(label, _, report)| (Some(*label), *report))
                 .collect();
             let envelope = reports_to_json(&pairs);
             let rendered = serde_json::to_string_pretty(&envelope)
                 .map_err(|e| format!("cannot serialize JSON output: {e}"))?;
             writeln!(out, "{rendered}").map_err(stdout_err)?;
         }
+        Format::Sarif => {
+            let rendered = serde_json::to_string_pretty(&reports_to_sarif(&labeled, &custom_rules))
+                .map_err(|e| format!("cannot serialize SARIF output: {e}"))?;
+            writeln!(out, "{rendered}").map_err(stdout_err)?;
+        }
+        Format::Junit => write!(out, "{}", reports_to_junit(&labeled)).map_err(stdout_err)?,
         Format::Github => {}
     }
     Ok(exit)
@@ -871,6 +854,60 @@ fn report_fix(
     Ok(())
 }
 
+// ------------------------------------------------------------------
+// fmt
+// ------------------------------------------------------------------
+
+fn run_fmt(paths: &[String], check: bool, config: &ConfigSource) -> Result<u8, String> {
+    let inputs = collect_inputs(paths)?;
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let mut exit = EXIT_CLEAN;
+
+    for input in inputs {
+        let label = input.label();
+        let cfg = config.for_input(&input)?;
+        let content = match input.read() {
+            Ok(c) => c,
+            Err(msg) => {
+                eprintln!("surf-lint: error: {msg}");
+                exit = exit.max(EXIT_INTERNAL);
+                continue;
+            }
+        };
+        let options = FormatOptions {
+            parse_options: cfg.parse_options,
+            ..FormatOptions::default()
+        };
+        let formatted = format(&content, &options);
+        let changed = formatted != content;
+
+        if check {
+            // Nothing is written: the diff goes to stdout either way.
+            if changed {
+                let rendered = unified_diff(&content.replace("\r\n", "\n"), &formatted, &label);
+                write!(out, "{rendered}").map_err(stdout_err)?;
+                exit = exit.max(EXIT_ERRORS);
+            }
+            continue;
+        }
+        match &input {
+            Input::Stdin => write!(out, "{formatted}").map_err(stdout_err)?,
+            Input::File(path) => {
+                if changed && let Err(e) = fs::write(path, &formatted) {
+                    eprintln!("surf-lint: error: cannot write '{label}': {e}");
+                    exit = exit.max(EXIT_INTERNAL);
+                    continue;
+                }
+                if changed {
+                    writeln!(out, "{label}: formatted").map_err(stdout_err)?;
+                }
+            }
+        }
+    }
+    Ok(exit)
+}
+
 // ------------------------------------------------------------------
 // Hand-rolled unified-style line diff (no extra dependency)
 // ------------------------------------------------------------------
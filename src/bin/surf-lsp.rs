@@ -0,0 +1,665 @@
+//! `surf-lsp` — Language Server Protocol front end for the SurfDoc
+//! lint/check/fix pipeline, speaking JSON-RPC over stdio.
+//!
+//! Built only with the non-default `lsp` feature:
+//!
+//! ```text
+//! cargo run --features lsp --bin surf-lsp
+//! cargo test --features lsp            # includes tests/lsp.rs
+//! ```
+//!
+//! # Capabilities
+//!
+//! | LSP feature | Source |
+//! |-------------|--------|
+//! | `publishDiagnostics` | [`check_with`] on every open/change (full sync) |
+//! | `codeAction` (quickfix) | each [`Diagnostic::fix`]; `Safe` fixes are preferred |
+//! | `completion` | block names after `::` ([`known_block_names`]); attribute names inside `::name[...]` ([`block_attribute_names`]) |
+//! | `hover` | [`RuleMeta::description`] of the diagnostics under the cursor |
+//! | `documentSymbol` | markdown headings and `::page` routes (page headings nest under their page) |
+//!
+//! # Configuration
+//!
+//! `.surflint.toml` is resolved exactly like `surf-lint check` does without
+//! `--config`: discovery starts at a `file:` document's directory (any other
+//! URI scheme starts at the server's current directory, like stdin) and walks
+//! up to the filesystem root. The config is re-read on every check, so edits
+//! to it apply on the next document change. A malformed config — an exit-3
+//! error for the CLI — clears the document's diagnostics and is reported once
+//! via `window/showMessage`.
+//!
+//! [`RuleMeta::description`]: surf_parse::lint::RuleMeta::description
+
+#[path = "shared/config.rs"]
+mod config;
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _,
+    PublishDiagnostics, ShowMessage,
+};
+use lsp_types::request::{
+    CodeActionRequest, Completion, DocumentSymbolRequest, HoverRequest, Request as _,
+};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOptions, CodeActionOrCommand, CodeActionParams,
+    CodeActionProviderCapability, CompletionItem, CompletionItemKind, CompletionOptions,
+    CompletionParams, CompletionResponse, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, DocumentSymbol, DocumentSymbolParams, DocumentSymbolResponse, Hover,
+    HoverContents, HoverParams, HoverProviderCapability, MarkupContent, MarkupKind, MessageType,
+    NumberOrString, OneOf, Position, PublishDiagnosticsParams, Range, ServerCapabilities,
+    ShowMessageParams, SymbolKind, TextDocumentSyncCapability, TextDocumentSyncKind, Uri,
+    WorkspaceEdit,
+};
+use surf_parse::lint::{block_attribute_names, known_block_names, rule_registry};
+use surf_parse::{
+    Block, CheckReport, Diagnostic, FixSafety, LintConfig, Severity, check_with, parse_with,
+};
+
+type BoxError = Box<dyn Error + Send + Sync>;
+
+/// `source` of every published diagnostic, matching the CLI's name.
+const DIAGNOSTIC_SOURCE: &str = "surf-lint";
+
+fn main() -> ExitCode {
+    let (connection, io_threads) = Connection::stdio();
+    let served = serve(connection);
+    match served.and_then(|()| io_threads.join().map_err(BoxError::from)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("surf-lsp: error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+            code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+            ..CodeActionOptions::default()
+        })),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![":".into(), "[".into(), " ".into()]),
+            ..CompletionOptions::default()
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        ..ServerCapabilities::default()
+    }
+}
+
+/// Run the initialize handshake, then the main loop until `shutdown`.
+fn serve(connection: Connection) -> Result<(), BoxError> {
+    let (id, _params) = connection.initialize_start()?;
+    connection.initialize_finish(
+        id,
+        serde_json::json!({
+            "capabilities": capabilities(),
+            "serverInfo": { "name": "surf-lsp", "version": env!("CARGO_PKG_VERSION") },
+        }),
+    )?;
+
+    let mut server = Server {
+        connection: &connection,
+        documents: BTreeMap::new(),
+    };
+    for message in &connection.receiver {
+        match message {
+            Message::Request(req) => {
+                if connection.handle_shutdown(&req)? {
+                    return Ok(());
+                }
+                server.handle_request(req)?;
+            }
+            Message::Notification(note) => server.handle_notification(note)?,
+            Message::Response(_) => {}
+        }
+    }
+    Err("client disconnected without a shutdown request".into())
+}
+
+// ------------------------------------------------------------------
+// Document state
+// ------------------------------------------------------------------
+
+/// One open document and the results of its latest check.
+struct Document {
+    /// CRLF-normalised text — the source every span is anchored to.
+    source: String,
+    /// `None` when the document's `.surflint.toml` failed to load.
+    config: Option<LintConfig>,
+    report: Option<CheckReport>,
+    /// Last config error shown to the user, so a broken config is reported
+    /// once rather than on every keystroke.
+    config_error: Option<String>,
+}
+
+struct Server<'c> {
+    connection: &'c Connection,
+    documents: BTreeMap<Uri, Document>,
+}
+
+impl Server<'_> {
+    fn handle_notification(&mut self, note: Notification) -> Result<(), BoxError> {
+        match note.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams = serde_json::from_value(note.params)?;
+                let doc = params.text_document;
+                self.update(doc.uri, doc.version, &doc.text)
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams = serde_json::from_value(note.params)?;
+                // Full sync: the last change carries the whole text.
+                match params.content_changes.into_iter().last() {
+                    Some(change) => self.update(
+                        params.text_document.uri,
+                        params.text_document.version,
+                        &change.text,
+                    ),
+                    None => Ok(()),
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams = serde_json::from_value(note.params)?;
+                self.documents.remove(&params.text_document.uri);
+                self.publish(params.text_document.uri, Vec::new(), None)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn handle_request(&self, req: Request) -> Result<(), BoxError> {
+        let id = req.id.clone();
+        let result = match req.method.as_str() {
+            CodeActionRequest::METHOD => {
+                self.respond(req, |p: CodeActionParams| self.code_actions(&p))
+            }
+            Completion::METHOD => self.respond(req, |p: CompletionParams| {
+                let pos = p.text_document_position;
+                self.documents
+                    .get(&pos.text_document.uri)
+                    .map(|doc| CompletionResponse::Array(completions(&doc.source, pos.position)))
+            }),
+            HoverRequest::METHOD => self.respond(req, |p: HoverParams| {
+                let pos = p.text_document_position_params;
+                self.documents
+                    .get(&pos.text_document.uri)
+                    .and_then(|doc| hover(doc, pos.position))
+            }),
+            DocumentSymbolRequest::METHOD => self.respond(req, |p: DocumentSymbolParams| {
+                self.documents
+                    .get(&p.text_document.uri)
+                    .map(|doc| DocumentSymbolResponse::Nested(document_symbols(doc)))
+            }),
+            _ => Response::new_err(
+                id,
+                ErrorCode::MethodNotFound as i32,
+                format!("unsupported request: {}", req.method),
+            ),
+        };
+        self.connection.sender.send(Message::Response(result))?;
+        Ok(())
+    }
+
+    /// Decode `req`'s params and answer with `handler`'s result.
+    fn respond<P, R>(&self, req: Request, handler: impl FnOnce(P) -> R) -> Response
+    where
+        P: serde::de::DeserializeOwned,
+        R: serde::Serialize,
+    {
+        match serde_json::from_value::<P>(req.params) {
+            Ok(params) => Response::new_ok(req.id, handler(params)),
+            Err(e) => Response::new_err(req.id, ErrorCode::InvalidParams as i32, e.to_string()),
+        }
+    }
+
+    /// Re-check `uri` with freshly discovered config and publish the result.
+    fn update(&mut self, uri: Uri, version: i32, text: &str) -> Result<(), BoxError> {
+        let source = text.replace("\r\n", "\n");
+        let previous_error = self.documents.remove(&uri).and_then(|doc| doc.config_error);
+        let (config, report, config_error) = match config_for(&uri) {
+            Ok(cfg) => {
+                let report = check_with(&source, &cfg);
+                (Some(cfg), Some(report), None)
+            }
+            Err(msg) => (None, None, Some(msg)),
+        };
+        if let Some(msg) = &config_error
+            && previous_error.as_ref() != Some(msg)
+        {
+            self.notify::<ShowMessage>(ShowMessageParams {
+                typ: MessageType::ERROR,
+                message: format!("surf-lsp: {msg}"),
+            })?;
+        }
+        let diagnostics = match &report {
+            Some(report) => {
+                let index = LineIndex::new(&source);
+                report
+                    .diagnostics
+                    .iter()
+                    .map(|d| to_lsp_diagnostic(d, &index))
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+        self.documents.insert(
+            uri.clone(),
+            Document {
+                source,
+                config,
+                report,
+                config_error,
+            },
+        );
+        self.publish(uri, diagnostics, Some(version))
+    }
+
+    fn publish(
+        &self,
+        uri: Uri,
+        diagnostics: Vec<lsp_types::Diagnostic>,
+        version: Option<i32>,
+    ) -> Result<(), BoxError> {
+        self.notify::<PublishDiagnostics>(PublishDiagnosticsParams {
+            uri,
+            diagnostics,
+            version,
+        })
+    }
+
+    fn notify<N: lsp_types::notification::Notification>(
+        &self,
+        params: N::Params,
+    ) -> Result<(), BoxError> {
+        let note = Notification::new(N::METHOD.to_string(), params);
+        self.connection.sender.send(Message::Notification(note))?;
+        Ok(())
+    }
+
+    /// One quickfix per fixable diagnostic overlapping the requested range.
+    fn code_actions(&self, params: &CodeActionParams) -> Option<Vec<CodeActionOrCommand>> {
+        let uri = &params.text_document.uri;
+        let doc = self.documents.get(uri)?;
+        let report = doc.report.as_ref()?;
+        let index = LineIndex::new(&doc.source);
+        let actions = report
+            .diagnostics
+            .iter()
+            .filter(|d| {
+                let range = diagnostic_range(d, &index);
+                range.start <= params.range.end && params.range.start <= range.end
+            })
+            .filter_map(|d| {
+                let fix = d.fix.as_ref()?;
+                let edits = fix
+                    .edits
+                    .iter()
+                    .map(|edit| lsp_types::TextEdit {
+                        range: index.range(edit.span.start_offset, edit.span.end_offset),
+                        new_text: edit.replacement.clone(),
+                    })
+                    .collect();
+                Some(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: fix.description.clone(),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![to_lsp_diagnostic(d, &index)]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(std::collections::HashMap::from([(uri.clone(), edits)])),
+                        ..WorkspaceEdit::default()
+                    }),
+                    is_preferred: Some(fix.safety == FixSafety::Safe),
+                    ..CodeAction::default()
+                }))
+            })
+            .collect();
+        Some(actions)
+    }
+}
+
+// ------------------------------------------------------------------
+// Configuration
+// ------------------------------------------------------------------
+
+/// The document's [`LintConfig`], discovered like the CLI does for a file
+/// (non-`file:` URIs discover from the current directory, like stdin).
+fn config_for(uri: &Uri) -> Result<LintConfig, String> {
+    let start = match file_path(uri).and_then(|p| p.parent().map(PathBuf::from)) {
+        Some(dir) => dir,
+        None => std::env::current_dir()
+            .map_err(|e| format!("cannot determine current directory: {e}"))?,
+    };
+    config::discover_config(&start)
+}
+
+/// Filesystem path of a `file:` URI, percent-decoded.
+fn file_path(uri: &Uri) -> Option<PathBuf> {
+    if !uri.scheme()?.as_str().eq_ignore_ascii_case("file") {
+        return None;
+    }
+    let raw = uri.path().as_str().as_bytes();
+    let mut bytes = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        let hex = raw
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (raw[i], hex) {
+            (b'%', Some(byte)) => {
+                bytes.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                bytes.push(byte);
+                i += 1;
+            }
+        }
+    }
+    let path = String::from_utf8(bytes).ok()?;
+    // `file:///C:/dir` carries the drive after the leading slash.
+    #[cfg(windows)]
+    let path = match path.strip_prefix('/') {
+        Some(rest) if rest.as_bytes().get(1) == Some(&b':') => rest.to_string(),
+        _ => path,
+    };
+    Some(PathBuf::from(path))
+}
+
+// ------------------------------------------------------------------
+// Positions
+// ------------------------------------------------------------------
+
+/// Byte offset ↔ LSP position over a CRLF-normalised source. Lines are the
+/// same as in the client's (possibly CRLF) text; columns are UTF-16 code
+/// units, the protocol's default position encoding.
+struct LineIndex<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            source,
+            line_starts,
+        }
+    }
+
+    fn position(&self, offset: usize) -> Position {
+        let mut offset = offset.min(self.source.len());
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let character = self.source[self.line_starts[line]..offset]
+            .encode_utf16()
+            .count();
+        Position::new(line as u32, character as u32)
+    }
+
+    fn offset(&self, position: Position) -> usize {
+        let line = position.line as usize;
+        let Some(&start) = self.line_starts.get(line) else {
+            return self.source.len();
+        };
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .map_or(self.source.len(), |next| next - 1);
+        let mut units = 0;
+        for (i, c) in self.source[start..end].char_indices() {
+            if units >= position.character as usize {
+                return start + i;
+            }
+            units += c.len_utf16();
+        }
+        end
+    }
+
+    fn range(&self, start: usize, end: usize) -> Range {
+        Range::new(self.position(start), self.position(end))
+    }
+}
+
+fn diagnostic_range(d: &Diagnostic, index: &LineIndex) -> Range {
+    match d.span {
+        Some(span) => index.range(span.start_offset, span.end_offset),
+        None => Range::default(),
+    }
+}
+
+fn to_lsp_diagnostic(d: &Diagnostic, index: &LineIndex) -> lsp_types::Diagnostic {
+    lsp_types::Diagnostic {
+        range: diagnostic_range(d, index),
+        severity: Some(match d.severity {
+            Severity::Error => lsp_types::DiagnosticSeverity::ERROR,
+            Severity::Warning => lsp_types::DiagnosticSeverity::WARNING,
+            Severity::Info => lsp_types::DiagnosticSeverity::INFORMATION,
+        }),
+        code: d.code.clone().map(NumberOrString::String),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        message: d.message.clone(),
+        ..lsp_types::Diagnostic::default()
+    }
+}
+
+// ------------------------------------------------------------------
+// Completion
+// ------------------------------------------------------------------
+
+/// Block names while typing a directive name (`::cal|`), attribute names
+/// inside its bracket group (`::callout[ty|`). Attributes already present are
+/// not offered again; nothing is offered inside a value.
+fn completions(source: &str, position: Position) -> Vec<CompletionItem> {
+    let index = LineIndex::new(source);
+    let line_start = index.line_starts[(position.line as usize).min(index.line_starts.len() - 1)];
+    let cursor = index.offset(position).max(line_start);
+    let prefix = source[line_start..cursor].trim_start();
+    let colons = prefix.chars().take_while(|&c| c == ':').count();
+    if colons < 2 {
+        return Vec::new();
+    }
+    let rest = &prefix[colons..];
+    let Some(bracket) = rest.find('[') else {
+        if !rest
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Vec::new();
+        }
+        return known_block_names()
+            .iter()
+            .map(|name| CompletionItem {
+                label: name.clone(),
+                kind: Some(CompletionItemKind::KEYWORD),
+                ..CompletionItem::default()
+            })
+            .collect();
+    };
+    let name = rest[..bracket].trim();
+    let group = &rest[bracket + 1..];
+    let in_value = group.matches('"').count() % 2 == 1
+        || group
+            .rsplit(char::is_whitespace)
+            .next()
+            .is_some_and(|word| word.contains('='));
+    if group.contains(']') || in_value {
+        return Vec::new();
+    }
+    let present: Vec<&str> = group
+        .split_whitespace()
+        .filter_map(|word| word.split_once('=').map(|(key, _)| key))
+        .collect();
+    block_attribute_names(name)
+        .unwrap_or_default()
+        .iter()
+        .filter(|attr| !present.contains(&attr.as_str()))
+        .map(|attr| CompletionItem {
+            label: attr.clone(),
+            kind: Some(CompletionItemKind::PROPERTY),
+            detail: Some(format!("::{name} attribute")),
+            insert_text: Some(format!("{attr}=")),
+            ..CompletionItem::default()
+        })
+        .collect()
+}
+
+// ------------------------------------------------------------------
+// Hover
+// ------------------------------------------------------------------
+
+/// The registry description of every diagnostic whose span covers the cursor.
+fn hover(doc: &Document, position: Position) -> Option<Hover> {
+    let index = LineIndex::new(&doc.source);
+    let offset = index.offset(position);
+    let registry = rule_registry();
+    let mut lines = Vec::new();
+    let mut range = None;
+    for d in &doc.report.as_ref()?.diagnostics {
+        let Some(span) = d.span else { continue };
+        if !(span.start_offset <= offset && offset <= span.end_offset) {
+            continue;
+        }
+        let Some((code, meta)) = d.code.as_deref().and_then(|c| Some((c, registry.get(c)?))) else {
+            continue;
+        };
+        let line = format!("**{code}** — {}", meta.description);
+        if !lines.contains(&line) {
+            lines.push(line);
+        }
+        range.get_or_insert(index.range(span.start_offset, span.end_offset));
+    }
+    if lines.is_empty() {
+        return None;
+    }
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: lines.join("\n\n"),
+        }),
+        range,
+    })
+}
+
+// ------------------------------------------------------------------
+// Document symbols
+// ------------------------------------------------------------------
+
+/// Top-level headings and `::page` routes, each page holding its headings.
+fn document_symbols(doc: &Document) -> Vec<DocumentSymbol> {
+    let options = doc
+        .config
+        .as_ref()
+        .map(|cfg| cfg.parse_options)
+        .unwrap_or_default();
+    let parsed = parse_with(&doc.source, &options);
+    let index = LineIndex::new(&doc.source);
+    block_symbols(&parsed.doc.blocks, &doc.source, &index)
+}
+
+fn block_symbols(blocks: &[Block], source: &str, index: &LineIndex) -> Vec<DocumentSymbol> {
+    let mut symbols = Vec::new();
+    for block in blocks {
+        match block {
+            Block::Markdown { span, .. } => {
+                symbols.extend(heading_symbols(
+                    source,
+                    span.start_offset,
+                    span.end_offset,
+                    index,
+                ));
+            }
+            // Page children carry synthetic spans, so the page body is
+            // scanned in place instead.
+            Block::Page {
+                route, title, span, ..
+            } => {
+                let opener_end = source[span.start_offset..span.end_offset]
+                    .find('\n')
+                    .map_or(span.end_offset, |i| span.start_offset + i);
+                symbols.push(symbol(
+                    route.clone(),
+                    title.clone(),
+                    SymbolKind::NAMESPACE,
+                    index.range(span.start_offset, span.end_offset),
+                    index.range(span.start_offset, opener_end),
+                    heading_symbols(source, opener_end, span.end_offset, index),
+                ));
+            }
+            _ => {}
+        }
+    }
+    symbols
+}
+
+/// ATX headings in `source[start..end]`, skipping fenced code.
+fn heading_symbols(
+    source: &str,
+    start: usize,
+    end: usize,
+    index: &LineIndex,
+) -> Vec<DocumentSymbol> {
+    let Some(text) = source.get(start..end) else {
+        return Vec::new();
+    };
+    let mut symbols = Vec::new();
+    let mut offset = start;
+    let mut in_fence = false;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        let level = trimmed.chars().take_while(|&c| c == '#').count();
+        if !in_fence && (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            let title = trimmed[level..].trim().trim_end_matches('#').trim_end();
+            if !title.is_empty() {
+                let range = index.range(offset, offset + line.trim_end_matches('\n').len());
+                symbols.push(symbol(
+                    title.to_string(),
+                    Some("#".repeat(level)),
+                    SymbolKind::STRING,
+                    range,
+                    range,
+                    Vec::new(),
+                ));
+            }
+        }
+        offset += line.len();
+    }
+    symbols
+}
+
+fn symbol(
+    name: String,
+    detail: Option<String>,
+    kind: SymbolKind,
+    range: Range,
+    selection_range: Range,
+    children: Vec<DocumentSymbol>,
+) -> DocumentSymbol {
+    // `deprecated` is itself deprecated in favour of `tags`, but it is
+    // still a required struct field.
+    #[allow(deprecated)]
+    DocumentSymbol {
+        name,
+        detail,
+        kind,
+        tags: None,
+        deprecated: None,
+        range,
+        selection_range,
+        children: (!children.is_empty()).then_some(children),
+    }
+}
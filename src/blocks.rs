@@ -3,13 +3,16 @@
 //! `resolve_block` converts a `Block::Unknown` into a typed variant based on
 //! the block name. Unknown block names pass through unchanged.
 
-use crate::citation::{parse_authors, Reference, RefType};
+use crate::citation::{
+    BibFormat, RefType, Reference, import_references, normalize_month, parse_authors,
+};
 use crate::types::{
-    AttrValue, Attrs, AuthProvider, BeforeAfterItem, BindingEvent, Block, BookingDay,
-    BookingService, CalloutType, ChartData, ChartSeries, ChartType, ChatMessage, ChatReaction,
+    AttrSpans, AttrValue, Attrs, AuthProvider, BeforeAfterItem, BindingEvent, Block, BookingDay,
+    BookingService, CalloutType, ChartAggregate, ChartBinding, ChartData, ChartSeries, ChartSort,
+    ChartType, ChatMessage, ChatReaction,
     Format, StoreItem,
-    ColumnContent, CommandItem, CrateDep, CrateEntry, DataFormat, DecisionStatus, DomainEntry, DropdownOption,
-    EmbedType, EnvEntry, EnvVar, FaqItem, FeatureCard, FieldConstraint, FilterField, FooterSection,
+    ColumnContent, CommandItem, CrateDep, CrateEntry, DataFormat, DecisionStatus, DiagramDirection, DomainEntry, DropdownOption,
+    EmbedType, EnvEntry, EnvVar, FaqItem, FeatureCard, FieldConstraint, FileId, FilterField, FooterSection,
     FormField, FormFieldType, GalleryItem, HeroButton, HttpMethod, ListDisplay, ListFilter,
     ModelField, ModelFieldType, NavGroup, NavItem, PipelineStep, PostItem, ProductGroup, ProductItem, ProgressStep,
     RowAction, RowState, SchemaField, SegmentItem,
@@ -20,10 +23,22 @@ use crate::types::{
 /// Resolve a `Block::Unknown` into a typed variant, if the name matches a known
 /// block type. Unrecognised names are returned unchanged.
 pub fn resolve_block(block: Block) -> Block {
-    let block = adopt_own_line_attrs(block);
+    resolve(block, LineSpans::default())
+}
+
+/// [`resolve_block`] for a top-level block scanned from `source`: list items
+/// (task lines, FAQ entries, nav links) keep their own source spans.
+pub(crate) fn resolve_block_in(block: Block, source: &str) -> Block {
+    let lines = LineSpans::locate(&block, source);
+    resolve(block, lines)
+}
+
+fn resolve(block: Block, lines: LineSpans) -> Block {
+    let (block, lines) = adopt_own_line_attrs(block, lines);
     let Block::Unknown {
         name,
         attrs,
+        attr_spans,
         content,
         span,
     } = &block
@@ -35,11 +50,12 @@ pub fn resolve_block(block: Block) -> Block {
         "callout" => parse_callout(attrs, content, *span),
         "data" => parse_data(attrs, content, *span),
         "code" => parse_code(attrs, content, *span),
-        "tasks" | "action-items" => parse_tasks(content, *span),
+        "tasks" | "action-items" => parse_tasks(content, *span, &lines),
         "decision" => parse_decision(attrs, content, *span),
         "metric" => parse_metric(attrs, *span),
         "summary" => parse_summary(content, *span),
         "cite" | "reference-def" => parse_cite(attrs, content, *span),
+        "bibliography-data" => parse_bibliography_data(attrs, content, *span),
         "bibliography" | "references" => parse_bibliography(attrs, *span),
         "figure" => parse_figure(attrs, *span),
         "diagram" => parse_diagram(attrs, content, *span),
@@ -50,13 +66,13 @@ pub fn resolve_block(block: Block) -> Block {
         "hero-image" => parse_hero_image(attrs, *span),
         "testimonial" => parse_testimonial(attrs, content, *span),
         "style" => parse_style(content, *span),
-        "faq" => parse_faq(content, *span),
+        "faq" => parse_faq(content, *span, &lines),
         "pricing-table" => parse_pricing_table(content, *span),
         "site" => parse_site(attrs, content, *span),
-        "page" => parse_page(attrs, content, *span),
+        "page" => parse_page(attrs, attr_spans, content, *span, &lines),
         "deck" => parse_deck(attrs, content, *span),
-        "slide" => parse_slide(attrs, content, *span),
-        "nav" => parse_nav(attrs, content, *span),
+        "slide" => parse_slide(attrs, content, *span, &lines),
+        "nav" => parse_nav(attrs, content, *span, &lines),
         "embed" => parse_embed(attrs, *span),
         "form" => parse_form(attrs, content, *span),
         "banner" => parse_banner(attrs, content, *span),
@@ -76,7 +92,7 @@ pub fn resolve_block(block: Block) -> Block {
         "toc" => parse_toc(attrs, *span),
         "before-after" => parse_before_after(attrs, content, *span),
         "pipeline" => parse_pipeline(content, *span),
-        "section" => parse_section(attrs, content, *span),
+        "section" => parse_section(attrs, content, *span, &lines),
         "product-card" => parse_product_card(attrs, content, *span),
         // App description blocks
         "list" => parse_list(attrs, content, *span),
@@ -92,15 +108,15 @@ pub fn resolve_block(block: Block) -> Block {
         // Compound widget mount points
         "editor" => parse_editor(attrs, *span),
         "chart" => parse_chart(attrs, content, *span),
-        "split-pane" => parse_split_pane(attrs, content, *span),
+        "split-pane" => parse_split_pane(attrs, content, *span, &lines),
         // Infrastructure manifest blocks
-        "app" => parse_app(attrs, content, *span),
+        "app" => parse_app(attrs, content, *span, &lines),
         "build" => parse_build(attrs, content, *span),
         "database" => parse_infra_database(attrs, content, *span),
-        "deploy" => parse_deploy(attrs, content, *span),
-        "env" => parse_infra_env(attrs, content, *span),
+        "deploy" => parse_deploy(attrs, attr_spans, content, *span),
+        "env" => parse_infra_env(attrs, attr_spans, content, *span),
         "health" => parse_health(attrs, *span),
-        "concurrency" => parse_concurrency(attrs, *span),
+        "concurrency" => parse_concurrency(attrs, attr_spans, *span),
         "cicd" => parse_cicd(attrs, content, *span),
         "smoke" => parse_smoke(attrs, content, *span),
         "domains" => parse_domains(content, *span),
@@ -109,7 +125,7 @@ pub fn resolve_block(block: Block) -> Block {
         "volumes" => parse_volumes(content, *span),
         // App spec blocks (data layer + API)
         "model" => parse_model(attrs, content, *span),
-        "route" => parse_route(attrs, content, *span),
+        "route" => parse_route(attrs, attr_spans, content, *span),
         "auth" => parse_auth(attrs, content, *span),
         "binding" => parse_binding(attrs, content, *span),
         // App format blocks (schema, deps, config, deploy)
@@ -120,15 +136,15 @@ pub fn resolve_block(block: Block) -> Block {
         "row" => parse_row(attrs, content, *span),
         "infocard" | "info-card" => parse_infocard(attrs, content, *span),
         // Interactive / application blocks
-        "app-shell" => parse_app_shell(attrs, content, *span),
-        "sidebar" => parse_sidebar(attrs, content, *span),
-        "panel" => parse_panel(attrs, content, *span),
+        "app-shell" => parse_app_shell(attrs, content, *span, &lines),
+        "sidebar" => parse_sidebar(attrs, content, *span, &lines),
+        "panel" => parse_panel(attrs, content, *span, &lines),
         "tab-bar" => parse_tab_bar(attrs, content, *span),
         "segmented-control" => parse_segmented_control(attrs, content, *span),
-        "tab-content" => parse_tab_content(attrs, content, *span),
+        "tab-content" => parse_tab_content(attrs, content, *span, &lines),
         "toolbar" => parse_toolbar(attrs, content, *span),
-        "drawer" => parse_drawer(attrs, content, *span),
-        "modal" => parse_modal(attrs, content, *span),
+        "drawer" => parse_drawer(attrs, content, *span, &lines),
+        "modal" => parse_modal(attrs, content, *span, &lines),
         "command-palette" => parse_command_palette(attrs, content, *span),
         "dropdown-select" => parse_dropdown_select(attrs, content, *span),
         "code-editor" => parse_code_editor(attrs, content, *span),
@@ -149,6 +165,79 @@ pub fn resolve_block(block: Block) -> Block {
     }
 }
 
+/// Trimmed source spans of a block's content lines, indexed like
+/// `content.lines()`. Empty for built blocks (and anything nested in them),
+/// so every item span resolves to `None`.
+#[derive(Default)]
+struct LineSpans(Vec<Span>);
+
+impl LineSpans {
+    /// Map `block`'s content back onto `source`. The content of a scanned
+    /// block is the verbatim slice after its opener line; anything else maps
+    /// to nothing.
+    fn locate(block: &Block, source: &str) -> Self {
+        let Block::Unknown { content, span, .. } = block else {
+            return Self::default();
+        };
+        let Some(opener_end) = source
+            .get(span.start_offset..span.end_offset)
+            .and_then(|text| text.find('\n'))
+        else {
+            return Self::default();
+        };
+        let mut offset = span.start_offset + opener_end + 1;
+        if !source[offset..].starts_with(content.as_str()) {
+            return Self::default();
+        }
+        let spans = content
+            .split_inclusive('\n')
+            .enumerate()
+            .map(|(i, raw)| {
+                let lead = raw.len() - raw.trim_start().len();
+                let start_offset = offset + lead;
+                offset += raw.len();
+                Span {
+                    start_line: span.start_line + 1 + i,
+                    end_line: span.start_line + 1 + i,
+                    start_offset,
+                    end_offset: start_offset + raw.trim().len(),
+                    file: FileId::ROOT,
+                }
+            })
+            .collect();
+        Self(spans)
+    }
+
+    fn remove(&mut self, idx: usize) {
+        if idx < self.0.len() {
+            self.0.remove(idx);
+        }
+    }
+
+    /// The spans of lines `from..to` (clamped to the end), for a nested
+    /// block whose content is exactly those lines.
+    fn slice(&self, from: usize, to: usize) -> Self {
+        let to = to.min(self.0.len());
+        Self(self.0.get(from..to).unwrap_or_default().to_vec())
+    }
+
+    fn line(&self, idx: usize) -> Option<Span> {
+        self.0.get(idx).copied()
+    }
+
+    /// Span from the start of line `first` through the end of line `last`.
+    fn range(&self, first: usize, last: usize) -> Option<Span> {
+        let (start, end) = (self.line(first)?, self.line(last)?);
+        Some(Span {
+            start_line: start.start_line,
+            end_line: end.end_line,
+            start_offset: start.start_offset,
+            end_offset: end.end_offset,
+            file: FileId::ROOT,
+        })
+    }
+}
+
 // ------------------------------------------------------------------
 // Attribute extraction helpers
 // ------------------------------------------------------------------
@@ -160,15 +249,18 @@ pub fn resolve_block(block: Block) -> Block {
 /// own, the first non-blank content line is exactly a bracket group, and
 /// `parse_attrs` accepts it with at least one attribute — which keeps markdown
 /// link references (`[1]: url`), task boxes, and prose brackets out.
-fn adopt_own_line_attrs(block: Block) -> Block {
+/// The adopted line is dropped from both the content and `lines`, and its
+/// attribute spans are recorded when `lines` locates it.
+fn adopt_own_line_attrs(block: Block, mut lines: LineSpans) -> (Block, LineSpans) {
     let Block::Unknown {
         name,
         attrs,
+        attr_spans,
         content,
         span,
     } = block
     else {
-        return block;
+        return (block, lines);
     };
 
     if attrs.is_empty() {
@@ -179,7 +271,7 @@ fn adopt_own_line_attrs(block: Block) -> Block {
         {
             let trimmed = line.trim();
             if trimmed.starts_with('[') && trimmed.ends_with(']') {
-                if let Ok(adopted) = crate::attrs::parse_attrs(trimmed) {
+                if let Ok((adopted, adopted_spans)) = opener_attrs(trimmed, lines.line(idx), 0) {
                     if !adopted.is_empty() {
                         let remaining: String = content
                             .lines()
@@ -188,23 +280,46 @@ fn adopt_own_line_attrs(block: Block) -> Block {
                             .map(|(_, l)| l)
                             .collect::<Vec<_>>()
                             .join("\n");
-                        return Block::Unknown {
-                            name,
-                            attrs: adopted,
-                            content: remaining,
-                            span,
-                        };
+                        lines.remove(idx);
+                        return (
+                            Block::Unknown {
+                                name,
+                                attrs: adopted,
+                                attr_spans: adopted_spans,
+                                content: remaining,
+                                span,
+                            },
+                            lines,
+                        );
                     }
                 }
             }
         }
     }
 
-    Block::Unknown {
-        name,
-        attrs,
-        content,
-        span,
+    (
+        Block::Unknown {
+            name,
+            attrs,
+            attr_spans,
+            content,
+            span,
+        },
+        lines,
+    )
+}
+
+/// Parse the attribute group `attrs_str`, which starts `col` bytes into the
+/// trimmed line located by `line`. Spans are absolute when the line is known
+/// and empty otherwise.
+fn opener_attrs(
+    attrs_str: &str,
+    line: Option<Span>,
+    col: usize,
+) -> Result<(Attrs, AttrSpans), crate::error::ParseError> {
+    match line {
+        Some(at) => crate::attrs::parse_attrs_at(attrs_str, at.start_offset + col, at.start_line),
+        None => crate::attrs::parse_attrs(attrs_str).map(|attrs| (attrs, AttrSpans::new())),
     }
 }
 
@@ -258,11 +373,15 @@ fn parse_diagram(attrs: &Attrs, content: &str, span: Span) -> Block {
         .map(|s| s.to_lowercase())
         .unwrap_or_default();
 
+    let id = attr_string(attrs, "id");
     let title = attr_string(attrs, "title");
+    let direction = attr_string(attrs, "direction").and_then(|s| DiagramDirection::from_name(&s));
 
     Block::Diagram {
+        id,
         diagram_type,
         title,
+        direction,
         content: content.to_string(),
         span,
     }
@@ -332,7 +451,7 @@ fn parse_table_content(content: &str) -> (Vec<String>, Vec<Vec<String>>) {
 }
 
 /// Check whether a line is a markdown table separator (e.g. `|---|---|`).
-fn is_table_separator(line: &str) -> bool {
+pub(crate) fn is_table_separator(line: &str) -> bool {
     let stripped = line.trim().trim_matches('|').trim();
     if stripped.is_empty() {
         return false;
@@ -344,7 +463,7 @@ fn is_table_separator(line: &str) -> bool {
 
 /// Split a pipe-delimited row into trimmed cell strings, stripping leading and
 /// trailing pipes.
-fn split_pipe_row(line: &str) -> Vec<String> {
+pub(crate) fn split_pipe_row(line: &str) -> Vec<String> {
     let trimmed = line.trim();
     // Remove leading/trailing pipes.
     let inner = trimmed
@@ -413,6 +532,7 @@ fn parse_csv_content(content: &str) -> (Vec<String>, Vec<Vec<String>>) {
 }
 
 fn parse_code(attrs: &Attrs, content: &str, span: Span) -> Block {
+    let id = attr_string(attrs, "id");
     let lang = attr_string(attrs, "lang");
     let file = attr_string(attrs, "file");
     let highlight = attr_string(attrs, "highlight")
@@ -420,6 +540,7 @@ fn parse_code(attrs: &Attrs, content: &str, span: Span) -> Block {
         .unwrap_or_default();
 
     Block::Code {
+        id,
         lang,
         file,
         highlight,
@@ -428,10 +549,10 @@ fn parse_code(attrs: &Attrs, content: &str, span: Span) -> Block {
     }
 }
 
-fn parse_tasks(content: &str, span: Span) -> Block {
+fn parse_tasks(content: &str, span: Span, lines: &LineSpans) -> Block {
     let mut items = Vec::new();
 
-    for line in content.lines() {
+    for (idx, line) in content.lines().enumerate() {
         let trimmed = line.trim();
 
         let (done, rest) = if let Some(rest) = trimmed.strip_prefix("- [x] ") {
@@ -456,6 +577,7 @@ fn parse_tasks(content: &str, span: Span) -> Block {
             done,
             text,
             assignee,
+            span: lines.line(idx),
         });
     }
 
@@ -584,7 +706,15 @@ fn parse_summary(content: &str, span: Span) -> Block {
 /// `field: value` (or `field = value`) lines; recognised fields map onto the
 /// reference (author/title/journal/year/volume/issue/pages/url/doi/…). Multiple
 /// authors use `author = "Last, First; Last2, First2"`.
+///
+/// A `format=` attr naming an interchange format, or a body that is plainly
+/// BibTeX / CSL-JSON / RIS, makes the block a [`Block::BibliographyData`].
 fn parse_cite(attrs: &Attrs, content: &str, span: Span) -> Block {
+    let format = attr_string(attrs, "format").and_then(|f| BibFormat::parse(&f));
+    if format.or_else(|| BibFormat::sniff(content)).is_some() {
+        return parse_bibliography_data(attrs, content, span);
+    }
+
     let mut reference = Reference::default();
 
     // Key + type may be supplied as attributes.
@@ -624,6 +754,7 @@ fn parse_cite(attrs: &Attrs, content: &str, span: Span) -> Block {
             }
             "publisher" => reference.publisher = v(),
             "year" | "date" => reference.year = v(),
+            "month" => reference.month = normalize_month(value),
             "volume" | "vol" => reference.volume = v(),
             "issue" | "number" | "no" => reference.issue = v(),
             "pages" | "page" | "pp" => reference.pages = v(),
@@ -638,6 +769,21 @@ fn parse_cite(attrs: &Attrs, content: &str, span: Span) -> Block {
     Block::Cite { reference, span }
 }
 
+/// Parse a `::bibliography-data` block: a BibTeX (default), CSL-JSON or RIS
+/// body imported into references. Without `format=` the body is sniffed.
+fn parse_bibliography_data(attrs: &Attrs, content: &str, span: Span) -> Block {
+    let format = attr_string(attrs, "format")
+        .and_then(|f| BibFormat::parse(&f))
+        .or_else(|| BibFormat::sniff(content))
+        .unwrap_or_default();
+    Block::BibliographyData {
+        format,
+        references: import_references(format, content),
+        raw_content: content.to_string(),
+        span,
+    }
+}
+
 /// Strip a single pair of surrounding double or single quotes.
 fn strip_quotes(s: &str) -> &str {
     let s = s.trim();
@@ -787,12 +933,14 @@ fn parse_quote(attrs: &Attrs, content: &str, span: Span) -> Block {
 }
 
 fn parse_figure(attrs: &Attrs, span: Span) -> Block {
+    let id = attr_string(attrs, "id");
     let src = attr_string(attrs, "src").unwrap_or_default();
     let caption = attr_string(attrs, "caption");
     let alt = attr_string(attrs, "alt");
     let width = attr_string(attrs, "width");
 
     Block::Figure {
+        id,
         src,
         caption,
         alt,
@@ -861,45 +1009,44 @@ fn parse_style(content: &str, span: Span) -> Block {
     Block::Style { properties, span }
 }
 
-fn parse_faq(content: &str, span: Span) -> Block {
+fn parse_faq(content: &str, span: Span, lines: &LineSpans) -> Block {
     let mut items = Vec::new();
-    let mut current_question: Option<String> = None;
+    // (question, question line, last non-blank line)
+    let mut current_question: Option<(String, usize, usize)> = None;
     let mut current_lines: Vec<&str> = Vec::new();
 
-    for line in content.lines() {
+    let faq_item = |(question, first, last): (String, usize, usize), answer: &[&str]| FaqItem {
+        question,
+        answer: answer.join("\n").trim().to_string(),
+        span: lines.range(first, last),
+    };
+
+    for (idx, line) in content.lines().enumerate() {
         let trimmed = line.trim();
-        // FAQ questions: `### Question` inside faq block
-        if let Some(rest) = trimmed.strip_prefix("### ") {
+        // FAQ questions: `### Question` inside faq block; also accept `##`.
+        if let Some(rest) = trimmed
+            .strip_prefix("### ")
+            .or_else(|| trimmed.strip_prefix("## "))
+        {
             // Flush previous item
             if let Some(question) = current_question.take() {
-                items.push(FaqItem {
-                    question,
-                    answer: current_lines.join("\n").trim().to_string(),
-                });
-                current_lines.clear();
-            }
-            current_question = Some(rest.trim().to_string());
-        } else if let Some(rest) = trimmed.strip_prefix("## ") {
-            // Also accept ## headers
-            if let Some(question) = current_question.take() {
-                items.push(FaqItem {
-                    question,
-                    answer: current_lines.join("\n").trim().to_string(),
-                });
+                items.push(faq_item(question, &current_lines));
                 current_lines.clear();
             }
-            current_question = Some(rest.trim().to_string());
+            current_question = Some((rest.trim().to_string(), idx, idx));
         } else {
+            if !trimmed.is_empty()
+                && let Some((_, _, last)) = current_question.as_mut()
+            {
+                *last = idx;
+            }
             current_lines.push(line);
         }
     }
 
     // Flush final item
     if let Some(question) = current_question {
-        items.push(FaqItem {
-            question,
-            answer: current_lines.join("\n").trim().to_string(),
-        });
+        items.push(faq_item(question, &current_lines));
     }
 
     Block::Faq { items, span }
@@ -940,14 +1087,20 @@ fn parse_site(attrs: &Attrs, content: &str, span: Span) -> Block {
     }
 }
 
-fn parse_page(attrs: &Attrs, content: &str, span: Span) -> Block {
+fn parse_page(
+    attrs: &Attrs,
+    attr_spans: &AttrSpans,
+    content: &str,
+    span: Span,
+    lines: &LineSpans,
+) -> Block {
     let route = attr_string(attrs, "route").unwrap_or_default();
     let layout = attr_string(attrs, "layout");
     let title = attr_string(attrs, "title");
     let sidebar = attr_bool(attrs, "sidebar");
 
     // Scan content for leaf directives, interleaving with markdown.
-    let children = parse_page_children(content);
+    let children = parse_page_children(content, lines);
 
     Block::Page {
         route,
@@ -956,6 +1109,7 @@ fn parse_page(attrs: &Attrs, content: &str, span: Span) -> Block {
         sidebar,
         content: content.to_string(),
         children,
+        attr_spans: attr_spans.clone(),
         span,
     }
 }
@@ -1000,14 +1154,14 @@ fn parse_deck(attrs: &Attrs, content: &str, span: Span) -> Block {
 ///
 /// Children reuse the existing content block families via
 /// [`parse_page_children`], so a slide is an arrangement of existing blocks.
-fn parse_slide(attrs: &Attrs, content: &str, span: Span) -> Block {
+fn parse_slide(attrs: &Attrs, content: &str, span: Span, lines: &LineSpans) -> Block {
     let layout = attr_string(attrs, "layout")
         .as_deref()
         .and_then(SlideLayout::from_name);
     let kicker = attr_string(attrs, "kicker");
     let mut notes = attr_string(attrs, "notes");
 
-    let mut children = parse_page_children(content);
+    let mut children = parse_page_children(content, lines);
 
     // Extract a `::notes` / `::speaker-notes` child block into the slide's
     // `notes` field (presenter notes), removing it from the rendered children.
@@ -1057,11 +1211,21 @@ fn attr_value_string(value: &AttrValue) -> Option<String> {
 
 /// Scan page content for both leaf directives and container blocks.
 ///
-/// Container blocks (`::name\ncontent\n::`) are collected and resolved via
-/// `resolve_block()`. Leaf directives (`::name[attrs]` with no matching closer)
+/// Container blocks (`::name\ncontent\n::`) are collected and resolved like
+/// top-level blocks. Leaf directives (`::name[attrs]` with no matching closer)
 /// are handled as before. Consecutive non-directive lines are collected as
-/// `Block::Markdown`.
-fn parse_page_children(content: &str) -> Vec<Block> {
+/// `Block::Markdown`. `spans` locates `content`'s lines, so nested attributes
+/// and list items keep their source spans.
+fn parse_page_children(content: &str, spans: &LineSpans) -> Vec<Block> {
+    scan_page_children(content, spans)
+        .into_iter()
+        .map(|(block, lines)| resolve(block, lines))
+        .collect()
+}
+
+/// [`parse_page_children`] before resolution: each child paired with the
+/// spans of its own content lines.
+fn scan_page_children(content: &str, spans: &LineSpans) -> Vec<(Block, LineSpans)> {
     let lines: Vec<&str> = content.lines().collect();
     let mut children = Vec::new();
     let mut md_lines: Vec<&str> = Vec::new();
@@ -1095,29 +1259,33 @@ fn parse_page_children(content: &str) -> Vec<Block> {
                 // Container block — flush markdown, resolve, advance past closer
                 flush_md_lines(&mut md_lines, &mut children);
 
-                let attrs = crate::attrs::parse_attrs(&attrs_str).unwrap_or_default();
+                let col = crate::parse::directive_name_start(trimmed, depth) + name.len();
+                let (attrs, attr_spans) =
+                    opener_attrs(&attrs_str, spans.line(i), col).unwrap_or_default();
                 let dummy_span = Span {
                     start_line: 0,
                     end_line: 0,
                     start_offset: 0,
                     end_offset: 0,
+                    file: FileId::ROOT,
                 };
 
                 let block = Block::Unknown {
                     name,
                     attrs,
+                    attr_spans,
                     content: content_str,
                     span: dummy_span,
                 };
-                children.push(resolve_block(block));
+                children.push((block, spans.slice(i + 1, end_idx)));
 
                 i = end_idx + 1; // skip past closing ::
                 continue;
             } else {
                 // No matching closer — treat as leaf directive
-                if let Some(block) = try_parse_leaf_directive(lines[i]) {
+                if let Some(block) = try_parse_leaf_directive(lines[i], spans.line(i)) {
                     flush_md_lines(&mut md_lines, &mut children);
-                    children.push(block);
+                    children.push((block, LineSpans::default()));
                     i += 1;
                     continue;
                 }
@@ -1173,10 +1341,10 @@ fn parse_nav_item_line(trimmed: &str) -> Option<NavItem> {
             }
         }
     }
-    Some(NavItem { label, href, icon, image, external })
+    Some(NavItem { label, href, icon, image, external, span: None })
 }
 
-fn parse_nav(attrs: &Attrs, content: &str, span: Span) -> Block {
+fn parse_nav(attrs: &Attrs, content: &str, span: Span, lines: &LineSpans) -> Block {
     let logo = attr_string(attrs, "logo");
     let brand = attr_string(attrs, "brand");
     let brand_reg = attr_bool(attrs, "reg");
@@ -1190,6 +1358,7 @@ fn parse_nav(attrs: &Attrs, content: &str, span: Span) -> Block {
             icon: attr_string(attrs, "cta-icon"),
             image: None,
             external: false,
+            span: None,
         }),
         _ => None,
     };
@@ -1201,7 +1370,7 @@ fn parse_nav(attrs: &Attrs, content: &str, span: Span) -> Block {
 
     // Parse link lines, grouping by `## Heading` / `### Heading` markers. Items
     // before any heading stay in the flat `items` list (legacy behaviour).
-    for line in content.lines() {
+    for (idx, line) in content.lines().enumerate() {
         let trimmed = line.trim();
         if let Some(rest) = trimmed.strip_prefix("## ").or_else(|| trimmed.strip_prefix("### ")) {
             // Flush the previous group (or lead items) before starting a new one.
@@ -1217,7 +1386,8 @@ fn parse_nav(attrs: &Attrs, content: &str, span: Span) -> Block {
         if !trimmed.starts_with("- [") {
             continue;
         }
-        if let Some(item) = parse_nav_item_line(trimmed) {
+        if let Some(mut item) = parse_nav_item_line(trimmed) {
+            item.span = lines.line(idx);
             if current_label.is_some() {
                 current_items.push(item);
             } else {
@@ -1521,6 +1691,7 @@ fn parse_footer(attrs: &Attrs, content: &str, span: Span) -> Block {
                         icon: None,
                         image: None,
                         external: false,
+                        span: None,
                     });
                 }
             }
@@ -1535,6 +1706,7 @@ fn parse_footer(attrs: &Attrs, content: &str, span: Span) -> Block {
                 icon: None,
                 image: None,
                 external: false,
+                span: None,
             });
         }
     }
@@ -1637,8 +1809,9 @@ fn scan_container_close(lines: &[&str], start: usize, open_depth: usize) -> Opti
 
 /// Try to parse a single line as a leaf directive (`::name[attrs]`).
 ///
-/// Returns `Some(resolved_block)` if the line matches, `None` otherwise.
-fn try_parse_leaf_directive(line: &str) -> Option<Block> {
+/// Returns `Some(unresolved_block)` if the line matches, `None` otherwise.
+/// `at` locates the trimmed line, for attribute spans.
+fn try_parse_leaf_directive(line: &str, at: Option<Span>) -> Option<Block> {
     let trimmed = line.trim();
     if !trimmed.starts_with("::") {
         return None;
@@ -1681,38 +1854,41 @@ fn try_parse_leaf_directive(line: &str) -> Option<Block> {
         ""
     };
 
-    let attrs = crate::attrs::parse_attrs(attrs_str).unwrap_or_default();
+    let col = trimmed.len() - remainder.len();
+    let (attrs, attr_spans) = opener_attrs(attrs_str, at, col).unwrap_or_default();
     let dummy_span = Span {
         start_line: 0,
         end_line: 0,
         start_offset: 0,
         end_offset: 0,
+        file: FileId::ROOT,
     };
 
-    let block = Block::Unknown {
+    Some(Block::Unknown {
         name: name.to_string(),
         attrs,
+        attr_spans,
         content: String::new(),
         span: dummy_span,
-    };
-
-    Some(resolve_block(block))
+    })
 }
 
 /// Flush accumulated markdown lines into a `Block::Markdown` if non-empty.
-fn flush_md_lines(lines: &mut Vec<&str>, children: &mut Vec<Block>) {
+fn flush_md_lines(lines: &mut Vec<&str>, children: &mut Vec<(Block, LineSpans)>) {
     let text = lines.join("\n");
     let trimmed = text.trim();
     if !trimmed.is_empty() {
-        children.push(Block::Markdown {
+        let block = Block::Markdown {
             content: text.trim().to_string(),
             span: Span {
                 start_line: 0,
                 end_line: 0,
                 start_offset: 0,
                 end_offset: 0,
+                file: FileId::ROOT,
             },
-        });
+        };
+        children.push((block, LineSpans::default()));
     }
     lines.clear();
 }
@@ -2545,7 +2721,7 @@ fn parse_pipeline(content: &str, span: Span) -> Block {
     Block::Pipeline { steps, span }
 }
 
-fn parse_section(attrs: &Attrs, content: &str, span: Span) -> Block {
+fn parse_section(attrs: &Attrs, content: &str, span: Span, lines: &LineSpans) -> Block {
     let bg = attr_string(attrs, "bg");
     let mut headline: Option<String> = None;
     let mut subtitle: Option<String> = None;
@@ -2586,8 +2762,15 @@ fn parse_section(attrs: &Attrs, content: &str, span: Span) -> Block {
         .collect::<Vec<_>>()
         .join("\n");
     let remaining = remaining.trim().to_string();
+    // `trim` drops the blank lines before the body, so its lines start there.
+    let first = body_start
+        + content
+            .lines()
+            .skip(body_start)
+            .take_while(|l| l.trim().is_empty())
+            .count();
 
-    let children = parse_page_children(&remaining);
+    let children = parse_page_children(&remaining, &lines.slice(first, usize::MAX));
 
     Block::Section {
         bg,
@@ -3126,20 +3309,74 @@ fn parse_chart(attrs: &Attrs, content: &str, span: Span) -> Block {
     let source = attr_string(attrs, "source")
         .and_then(|s| validate_source_path(&s))
         .unwrap_or_default();
+    let id = attr_string(attrs, "id");
     let period = attr_string(attrs, "period");
     let title = attr_string(attrs, "title");
-    let data = parse_chart_data(content);
+    let binding = parse_chart_binding(attrs);
+    // A binding is filled from its `::data` block once the whole document is
+    // parsed (`chart::bind_chart_data`); any inline body is ignored.
+    let data = if binding.is_some() {
+        None
+    } else {
+        parse_chart_data(content)
+    };
 
     Block::Chart {
+        id,
         chart_type,
         source,
         period,
         title,
         data,
+        binding,
         span,
     }
 }
 
+/// Parse the `data=#id x=… y=a,b aggregate=… sort=[-]col` attributes of a
+/// `::chart` into a [`ChartBinding`]. `None` without a `data=` reference.
+/// Unknown `aggregate` values are ignored, like unknown chart types.
+fn parse_chart_binding(attrs: &Attrs) -> Option<ChartBinding> {
+    let data = attr_string(attrs, "data")?;
+    let data = data.trim().trim_start_matches('#').trim();
+    if data.is_empty() {
+        return None;
+    }
+    let y = attr_string(attrs, "y")
+        .map(|s| {
+            s.split(',')
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default();
+    let aggregate = attr_string(attrs, "aggregate").and_then(|s| match s.to_lowercase().as_str() {
+        "sum" => Some(ChartAggregate::Sum),
+        "avg" | "average" | "mean" => Some(ChartAggregate::Avg),
+        "count" => Some(ChartAggregate::Count),
+        _ => None,
+    });
+    let sort = attr_string(attrs, "sort").and_then(|s| {
+        let s = s.trim();
+        let (column, descending) = match s.strip_prefix('-') {
+            Some(column) => (column.trim(), true),
+            None => (s, false),
+        };
+        (!column.is_empty()).then(|| ChartSort {
+            column: column.to_string(),
+            descending,
+        })
+    });
+    Some(ChartBinding {
+        data: data.to_string(),
+        x: attr_string(attrs, "x").filter(|x| !x.trim().is_empty()),
+        y,
+        aggregate,
+        sort,
+    })
+}
+
 /// Parse a `::chart` block body into an inline [`ChartData`] dataset.
 ///
 /// The body is a pipe-delimited table consistent with `::data[format=table]`:
@@ -3178,7 +3415,7 @@ pub(crate) fn parse_chart_data(content: &str) -> Option<ChartData> {
 
 /// Parse a numeric chart cell. Strips common formatting (`$`, `%`, `,`,
 /// surrounding whitespace); non-numeric cells become `0.0`.
-fn parse_chart_number(cell: &str) -> f64 {
+pub(crate) fn parse_chart_number(cell: &str) -> f64 {
     let cleaned: String = cell
         .chars()
         .filter(|c| !matches!(c, '$' | '%' | ',' | ' ' | '_'))
@@ -3186,7 +3423,7 @@ fn parse_chart_number(cell: &str) -> f64 {
     cleaned.parse::<f64>().unwrap_or(0.0)
 }
 
-fn parse_split_pane(attrs: &Attrs, content: &str, span: Span) -> Block {
+fn parse_split_pane(attrs: &Attrs, content: &str, span: Span, lines: &LineSpans) -> Block {
     let ratio = attr_string(attrs, "ratio").unwrap_or_else(|| "50:50".to_string());
     let back_label = attr_string(attrs, "back-label");
     let back_action = attr_string(attrs, "back-action");
@@ -3199,7 +3436,8 @@ fn parse_split_pane(attrs: &Attrs, content: &str, span: Span) -> Block {
     let mut right: Vec<Block> = Vec::new();
     if !content.trim().is_empty() {
         let mut pane_index = 0usize;
-        for child in parse_page_children(content) {
+        for (child, child_lines) in scan_page_children(content, lines) {
+            let (child, child_lines) = adopt_own_line_attrs(child, child_lines);
             match child {
                 Block::Unknown { ref name, ref attrs, content: ref body, .. }
                     if name == "pane" =>
@@ -3211,10 +3449,10 @@ fn parse_split_pane(attrs: &Attrs, content: &str, span: Span) -> Block {
                         _ if pane_index == 0 => &mut left,
                         _ => &mut right,
                     };
-                    target.extend(parse_page_children(body));
+                    target.extend(parse_page_children(body, &child_lines));
                     pane_index += 1;
                 }
-                other => left.push(other),
+                other => left.push(resolve(other, child_lines)),
             }
         }
     }
@@ -3227,7 +3465,7 @@ fn parse_split_pane(attrs: &Attrs, content: &str, span: Span) -> Block {
 // ------------------------------------------------------------------
 
 /// Container block — recursively parse children (like `parse_page`).
-fn parse_app(attrs: &Attrs, content: &str, span: Span) -> Block {
+fn parse_app(attrs: &Attrs, content: &str, span: Span, lines: &LineSpans) -> Block {
     let name = attr_string(attrs, "name").unwrap_or_default();
     let binary = attr_string(attrs, "binary");
     let region = attr_string(attrs, "region");
@@ -3235,7 +3473,7 @@ fn parse_app(attrs: &Attrs, content: &str, span: Span) -> Block {
     let platform = attr_string(attrs, "platform");
     let auth = attr_string(attrs, "auth");
 
-    let children = parse_page_children(content);
+    let children = parse_page_children(content, lines);
 
     Block::App {
         name,
@@ -3308,7 +3546,7 @@ fn parse_infra_database(attrs: &Attrs, content: &str, span: Span) -> Block {
     }
 }
 
-fn parse_deploy(attrs: &Attrs, content: &str, span: Span) -> Block {
+fn parse_deploy(attrs: &Attrs, attr_spans: &AttrSpans, content: &str, span: Span) -> Block {
     let env = attr_string(attrs, "env");
     let app = attr_string(attrs, "app");
     let machines = attr_u32(attrs, "machines");
@@ -3341,12 +3579,13 @@ fn parse_deploy(attrs: &Attrs, content: &str, span: Span) -> Block {
         min_machines,
         strategy,
         properties,
+        attr_spans: attr_spans.clone(),
         span,
     }
 }
 
 /// Parse `NAME` or `NAME = default_value` lines.
-fn parse_infra_env(attrs: &Attrs, content: &str, span: Span) -> Block {
+fn parse_infra_env(attrs: &Attrs, attr_spans: &AttrSpans, content: &str, span: Span) -> Block {
     let tier = attr_string(attrs, "tier");
 
     let mut entries = Vec::new();
@@ -3373,6 +3612,7 @@ fn parse_infra_env(attrs: &Attrs, content: &str, span: Span) -> Block {
     Block::InfraEnv {
         tier,
         entries,
+        attr_spans: attr_spans.clone(),
         span,
     }
 }
@@ -3388,12 +3628,13 @@ fn parse_health(attrs: &Attrs, span: Span) -> Block {
     }
 }
 
-fn parse_concurrency(attrs: &Attrs, span: Span) -> Block {
+fn parse_concurrency(attrs: &Attrs, attr_spans: &AttrSpans, span: Span) -> Block {
     Block::Concurrency {
         concurrency_type: attr_string(attrs, "type"),
         hard_limit: attr_u32(attrs, "hard_limit"),
         soft_limit: attr_u32(attrs, "soft_limit"),
         force_https: attr_bool(attrs, "force_https"),
+        attr_spans: attr_spans.clone(),
         span,
     }
 }
@@ -3703,7 +3944,7 @@ fn parse_field_constraints(s: &str) -> Vec<FieldConstraint> {
 ///
 /// If a fenced code block (` ``` `rust ... ``` `) appears in the content, the
 /// code inside is extracted as the `handler` field.
-fn parse_route(attrs: &Attrs, content: &str, span: Span) -> Block {
+fn parse_route(attrs: &Attrs, attr_spans: &AttrSpans, content: &str, span: Span) -> Block {
     let method_str = attr_string(attrs, "method").unwrap_or_else(|| "get".to_string());
     let method = match method_str.to_lowercase().as_str() {
         "get" => HttpMethod::Get,
@@ -3768,6 +4009,7 @@ fn parse_route(attrs: &Attrs, content: &str, span: Span) -> Block {
         body,
         handler,
         content: extra_lines.join("\n"),
+        attr_spans: attr_spans.clone(),
         span,
     }
 }
@@ -4318,10 +4560,10 @@ fn parse_app_deploy(attrs: &Attrs, content: &str, span: Span) -> Block {
 // Interactive / application block parsers
 // ------------------------------------------------------------------
 
-fn parse_app_shell(attrs: &Attrs, content: &str, span: Span) -> Block {
+fn parse_app_shell(attrs: &Attrs, content: &str, span: Span, lines: &LineSpans) -> Block {
     let layout = attr_string(attrs, "layout").unwrap_or_else(|| "sidebar-main-panel".to_string());
     let height = attr_u32(attrs, "height");
-    let children = parse_page_children(content);
+    let children = parse_page_children(content, lines);
     Block::AppShell {
         layout,
         height,
@@ -4330,11 +4572,11 @@ fn parse_app_shell(attrs: &Attrs, content: &str, span: Span) -> Block {
     }
 }
 
-fn parse_sidebar(attrs: &Attrs, content: &str, span: Span) -> Block {
+fn parse_sidebar(attrs: &Attrs, content: &str, span: Span, lines: &LineSpans) -> Block {
     let position = attr_string(attrs, "position").unwrap_or_else(|| "left".to_string());
     let collapsible = attr_bool(attrs, "collapsible");
     let width = attr_u32(attrs, "width");
-    let children = parse_page_children(content);
+    let children = parse_page_children(content, lines);
     Block::Sidebar {
         position,
         collapsible,
@@ -4344,12 +4586,12 @@ fn parse_sidebar(attrs: &Attrs, content: &str, span: Span) -> Block {
     }
 }
 
-fn parse_panel(attrs: &Attrs, content: &str, span: Span) -> Block {
+fn parse_panel(attrs: &Attrs, content: &str, span: Span, lines: &LineSpans) -> Block {
     let position = attr_string(attrs, "position").unwrap_or_else(|| "bottom".to_string());
     let resizable = attr_bool(attrs, "resizable");
     let height = attr_u32(attrs, "height");
     let desktop_only = attr_bool(attrs, "desktop-only");
-    let children = parse_page_children(content);
+    let children = parse_page_children(content, lines);
     Block::Panel {
         position,
         resizable,
@@ -4442,12 +4684,12 @@ fn parse_segmented_control(attrs: &Attrs, content: &str, span: Span) -> Block {
     }
 }
 
-fn parse_tab_content(attrs: &Attrs, content: &str, span: Span) -> Block {
+fn parse_tab_content(attrs: &Attrs, content: &str, span: Span, lines: &LineSpans) -> Block {
     let tab = attr_string(attrs, "tab").unwrap_or_default();
     // 0.13: ruled centered-column idiom (`width=880 align=center`).
     let width = attr_u32(attrs, "width");
     let align = attr_string(attrs, "align");
-    let children = parse_page_children(content);
+    let children = parse_page_children(content, lines);
     Block::TabContent {
         tab,
         width,
@@ -4533,12 +4775,12 @@ fn parse_toolbar(attrs: &Attrs, content: &str, span: Span) -> Block {
     }
 }
 
-fn parse_drawer(attrs: &Attrs, content: &str, span: Span) -> Block {
+fn parse_drawer(attrs: &Attrs, content: &str, span: Span, lines: &LineSpans) -> Block {
     let name = attr_string(attrs, "name").unwrap_or_default();
     let position = attr_string(attrs, "position").unwrap_or_else(|| "right".to_string());
     let width = attr_u32(attrs, "width");
     let trigger = attr_string(attrs, "trigger");
-    let children = parse_page_children(content);
+    let children = parse_page_children(content, lines);
     Block::Drawer {
         name,
         position,
@@ -4549,7 +4791,7 @@ fn parse_drawer(attrs: &Attrs, content: &str, span: Span) -> Block {
     }
 }
 
-fn parse_modal(attrs: &Attrs, content: &str, span: Span) -> Block {
+fn parse_modal(attrs: &Attrs, content: &str, span: Span, lines: &LineSpans) -> Block {
     let name = attr_string(attrs, "name").unwrap_or_default();
     let title = attr_string(attrs, "title");
     let width = attr_u32(attrs, "width");
@@ -4558,7 +4800,7 @@ fn parse_modal(attrs: &Attrs, content: &str, span: Span) -> Block {
         .get("dismissible")
         .map(|v| matches!(v, AttrValue::Bool(true)))
         .unwrap_or(true);
-    let children = parse_page_children(content);
+    let children = parse_page_children(content, lines);
     Block::Modal {
         name,
         title,
@@ -4900,11 +5142,13 @@ mod tests {
             name: name.to_string(),
             attrs,
             content: content.to_string(),
+            attr_spans: Default::default(),
             span: Span {
                 start_line: 1,
                 end_line: 3,
                 start_offset: 0,
                 end_offset: 100,
+                file: FileId::ROOT,
             },
         }
     }
@@ -5450,6 +5694,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn resolve_diagram_reads_direction() {
+        let block = unknown(
+            "diagram",
+            attrs(&[
+                ("type", AttrValue::String("flowchart".into())),
+                ("direction", AttrValue::String("lr".into())),
+            ]),
+            "a -> b",
+        );
+        match resolve_block(block) {
+            Block::Diagram { direction, .. } => {
+                assert_eq!(direction, Some(DiagramDirection::LeftRight));
+            }
+            other => panic!("Expected Diagram, got {other:?}"),
+        }
+        let block = unknown(
+            "diagram",
+            attrs(&[("direction", AttrValue::String("diagonal".into()))]),
+            "",
+        );
+        match resolve_block(block) {
+            Block::Diagram { direction, .. } => assert!(direction.is_none()),
+            other => panic!("Expected Diagram, got {other:?}"),
+        }
+    }
+
     #[test]
     fn resolve_diagram_unknown_type_round_trips_raw() {
         // Unknown types stay a String so they survive losslessly.
@@ -5861,6 +6132,94 @@ mod tests {
         }
     }
 
+    // -- Item and attribute spans ----------------------------------
+
+    fn text(source: &str, span: Option<Span>) -> &str {
+        let span = span.expect("span");
+        &source[span.start_offset..span.end_offset]
+    }
+
+    #[test]
+    fn parsed_task_items_carry_line_spans() {
+        let src = "intro\n\n::tasks\n  - [ ] Write spec @ann\n\n- [x] Ship it\n::\n";
+        let doc = crate::parse::parse(src).doc;
+        let Block::Tasks { items, .. } = &doc.blocks[1] else {
+            panic!("Expected Tasks, got {:?}", doc.blocks[1]);
+        };
+        assert_eq!(text(src, items[0].span), "- [ ] Write spec @ann");
+        assert_eq!(items[0].span.unwrap().start_line, 4);
+        assert_eq!(text(src, items[1].span), "- [x] Ship it");
+        assert_eq!(items[1].span.unwrap().start_line, 6);
+    }
+
+    #[test]
+    fn parsed_items_skip_adopted_attr_line() {
+        let src = "::nav\n[brand=Acme]\n- [Home](/)\n- [Docs](/docs)\n::\n";
+        let doc = crate::parse::parse(src).doc;
+        let Block::Nav { items, .. } = &doc.blocks[0] else {
+            panic!("Expected Nav, got {:?}", doc.blocks[0]);
+        };
+        assert_eq!(text(src, items[0].span), "- [Home](/)");
+        assert_eq!(text(src, items[1].span), "- [Docs](/docs)");
+    }
+
+    #[test]
+    fn parsed_faq_item_spans_cover_question_and_answer() {
+        let src = "::faq\n### One?\nYes.\n\n### Two?\nNo.\nReally.\n::\n";
+        let doc = crate::parse::parse(src).doc;
+        let Block::Faq { items, .. } = &doc.blocks[0] else {
+            panic!("Expected Faq, got {:?}", doc.blocks[0]);
+        };
+        assert_eq!(text(src, items[0].span), "### One?\nYes.");
+        assert_eq!(text(src, items[1].span), "### Two?\nNo.\nReally.");
+    }
+
+    #[test]
+    fn resolve_block_without_source_has_no_item_spans() {
+        let block = unknown("tasks", Attrs::new(), "- [ ] A");
+        match resolve_block(block) {
+            Block::Tasks { items, .. } => assert_eq!(items[0].span, None),
+            other => panic!("Expected Tasks, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parsed_blocks_record_opener_and_own_line_attr_spans() {
+        let src = "::page[route=/x title=\"Hi\"]\nBody\n::\n\n::deploy\n\n[env=staging]\n::\n";
+        let doc = crate::parse::parse(src).doc;
+        let spans = doc.blocks[0].attr_spans().unwrap();
+        assert_eq!(text(src, Some(spans["route"].key)), "route");
+        assert_eq!(text(src, spans["route"].value), "/x");
+        assert_eq!(text(src, spans["title"].value), "\"Hi\"");
+        assert_eq!(spans["route"].key.start_line, 1);
+
+        let spans = doc.blocks[1].attr_spans().unwrap();
+        assert_eq!(text(src, spans["env"].value), "staging");
+        assert_eq!(spans["env"].key.start_line, 7);
+    }
+
+    #[test]
+    fn nested_blocks_record_attr_and_item_spans() {
+        let src = "::page[route=/]\n:::route[path=api]\n:::\n:::section\n## Plan\n\n::::tasks\n- [ ] Draft\n::::\n:::\n::\n";
+        let doc = crate::parse::parse(src).doc;
+        let children = doc.blocks[0].children().unwrap();
+        let spans = children[0].attr_spans().unwrap();
+        assert_eq!(text(src, spans["path"].value), "api");
+        assert_eq!(spans["path"].key.start_line, 2);
+
+        let Block::Tasks { items, .. } = &children[1].children().unwrap()[0] else {
+            panic!("Expected Tasks, got {:?}", children[1]);
+        };
+        assert_eq!(text(src, items[0].span), "- [ ] Draft");
+        assert_eq!(items[0].span.unwrap().start_line, 8);
+    }
+
+    #[test]
+    fn resolve_block_without_source_has_no_attr_spans() {
+        let block = unknown("page", attrs(&[("route", AttrValue::String("/".into()))]), "");
+        assert!(resolve_block(block).attr_spans().unwrap().is_empty());
+    }
+
     // -- PricingTable ----------------------------------------------
 
     #[test]
@@ -7766,6 +8125,38 @@ Saturday 7am-4pm, Sunday 8am-2pm.
         }
     }
 
+    /// `data=#id` parses into a binding; the inline body is ignored and
+    /// `data` stays None until the parse-level binding pass resolves it.
+    #[test]
+    fn resolve_chart_data_binding() {
+        let a = attrs(&[
+            ("type", AttrValue::String("bar".into())),
+            ("data", AttrValue::String("#sales".into())),
+            ("x", AttrValue::String("month".into())),
+            ("y", AttrValue::String("revenue, cost".into())),
+            ("aggregate", AttrValue::String("mean".into())),
+            ("sort", AttrValue::String("-revenue".into())),
+        ]);
+        match resolve_block(unknown("chart", a, "Q | A\nQ1 | 1")) {
+            Block::Chart { data, binding, .. } => {
+                assert!(data.is_none(), "a bound chart ignores its inline body");
+                let b = binding.expect("binding parsed");
+                assert_eq!(b.data, "sales");
+                assert_eq!(b.x.as_deref(), Some("month"));
+                assert_eq!(b.y, vec!["revenue", "cost"]);
+                assert_eq!(b.aggregate, Some(ChartAggregate::Avg));
+                assert_eq!(
+                    b.sort,
+                    Some(ChartSort {
+                        column: "revenue".into(),
+                        descending: true
+                    })
+                );
+            }
+            other => panic!("Expected Chart, got {other:?}"),
+        }
+    }
+
     /// L2: an empty body (or <2 columns) leaves `data` None → mount-point fallback.
     #[test]
     fn resolve_chart_missing_data_falls_back() {
@@ -9695,4 +10086,48 @@ Note
             other => panic!("Expected Callout, got {other:?}"),
         }
     }
+
+    #[test]
+    fn resolve_bibliography_data() {
+        let a = attrs(&[("format", AttrValue::String("ris".into()))]);
+        let body = "TY  - JOUR\nAU  - Smith, John\nTI  - A Title\nPY  - 2020\nER  - ";
+        match resolve_block(unknown("bibliography-data", a, body)) {
+            Block::BibliographyData {
+                format, references, ..
+            } => {
+                assert_eq!(format, BibFormat::Ris);
+                assert_eq!(references.len(), 1);
+                assert_eq!(references[0].key, "smith2020");
+            }
+            other => panic!("Expected BibliographyData, got {other:?}"),
+        }
+        // No `format=`: the body is sniffed.
+        let body = "@book{jones2019, author = {Jones, Alice}, year = 2019}";
+        match resolve_block(unknown("bibliography-data", Attrs::new(), body)) {
+            Block::BibliographyData { format, .. } => assert_eq!(format, BibFormat::Bibtex),
+            other => panic!("Expected BibliographyData, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn resolve_cite_with_interchange_body() {
+        // `::cite[format=csl-json]` imports instead of reading field lines.
+        let a = attrs(&[("format", AttrValue::String("csl-json".into()))]);
+        let body = r#"[{"id": "lee2021", "type": "webpage", "title": "Page"}]"#;
+        match resolve_block(unknown("cite", a, body)) {
+            Block::BibliographyData {
+                format, references, ..
+            } => {
+                assert_eq!(format, BibFormat::CslJson);
+                assert_eq!(references[0].ref_type, RefType::Web);
+            }
+            other => panic!("Expected BibliographyData, got {other:?}"),
+        }
+        // A plain field list stays a `::cite`; `month` is normalized.
+        let a = attrs(&[("key", AttrValue::String("smith2020".into()))]);
+        match resolve_block(unknown("cite", a, "author = Smith, John\nmonth = Sept.")) {
+            Block::Cite { reference, .. } => assert_eq!(reference.month, Some(9)),
+            other => panic!("Expected Cite, got {other:?}"),
+        }
+    }
 }
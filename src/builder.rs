@@ -7,8 +7,8 @@
 
 use crate::citation::{Author, Reference, RefType};
 use crate::types::{
-    Block, CalloutType, ChartType, ColumnContent, DataFormat, DecisionStatus, EmbedType, FaqItem,
-    FeatureCard, Format, FooterSection, FormField, FormFieldType, FrontMatter, GalleryItem, HeroButton,
+    AttrSpans, Block, CalloutType, ChartAggregate, ChartType, ColumnContent, DataFormat, DecisionStatus,
+    EmbedType, FaqItem, FeatureCard, Format, FooterSection, FormField, FormFieldType, FrontMatter, GalleryItem, HeroButton,
     HttpMethod, ListDisplay, NavItem, RowState, SocialLink, Span, StatItem, StepItem,
     StyleProperty, SurfDoc, TabPanel, TaskItem, Trend,
 };
@@ -148,6 +148,7 @@ impl SurfDocBuilder {
     /// Add a code block with optional language.
     pub fn code(mut self, content: &str, lang: Option<&str>) -> Self {
         self.blocks.push(Block::Code {
+            id: None,
             lang: lang.map(|s| s.to_string()),
             file: None,
             highlight: vec![],
@@ -160,6 +161,7 @@ impl SurfDocBuilder {
     /// Add a code block with language and file path.
     pub fn code_file(mut self, content: &str, lang: &str, file: &str) -> Self {
         self.blocks.push(Block::Code {
+            id: None,
             lang: Some(lang.to_string()),
             file: Some(file.to_string()),
             highlight: vec![],
@@ -203,6 +205,7 @@ impl SurfDocBuilder {
                 done,
                 text: text.to_string(),
                 assignee: None,
+                span: None,
             });
         } else {
             self.blocks.push(Block::Tasks {
@@ -210,6 +213,7 @@ impl SurfDocBuilder {
                     done,
                     text: text.to_string(),
                     assignee: None,
+                    span: None,
                 }],
                 span: Span::SYNTHETIC,
             });
@@ -280,6 +284,7 @@ impl SurfDocBuilder {
     /// Add a figure block.
     pub fn figure(mut self, src: &str) -> Self {
         self.blocks.push(Block::Figure {
+            id: None,
             src: src.to_string(),
             caption: None,
             alt: None,
@@ -292,6 +297,7 @@ impl SurfDocBuilder {
     /// Add a figure block with caption and optional alt text.
     pub fn figure_with_caption(mut self, src: &str, caption: &str, alt: Option<&str>) -> Self {
         self.blocks.push(Block::Figure {
+            id: None,
             src: src.to_string(),
             caption: Some(caption.to_string()),
             alt: alt.map(|s| s.to_string()),
@@ -418,6 +424,7 @@ impl SurfDocBuilder {
             sidebar: false,
             content: content.to_string(),
             children,
+            attr_spans: AttrSpans::new(),
             span: Span::SYNTHETIC,
         });
         self
@@ -705,6 +712,7 @@ impl SurfDocBuilder {
             min_machines: None,
             strategy: None,
             properties: Vec::new(),
+            attr_spans: AttrSpans::new(),
             span: Span::SYNTHETIC,
         });
         self
@@ -826,6 +834,13 @@ fn serialize_front_matter(fm: &FrontMatter) -> String {
     if let Some(decision) = &fm.decision {
         lines.push(format!("decision: \"{}\"", escape_yaml_string(decision)));
     }
+    if let Some(csl) = &fm.csl {
+        // A YAML literal block keeps the style XML verbatim.
+        lines.push("csl: |".to_string());
+        for line in csl.trim_end().lines() {
+            lines.push(format!("  {line}"));
+        }
+    }
     if let Some(related) = &fm.related
         && !related.is_empty() {
             lines.push("related:".to_string());
@@ -963,18 +978,26 @@ fn serialize_block(block: &Block) -> String {
         }
 
         Block::Diagram {
+            id,
             diagram_type,
             title,
+            direction,
             content,
             ..
         } => {
             let mut attr_parts = Vec::new();
+            if let Some(id) = id {
+                attr_parts.push(format!("id=\"{}\"", escape_attr(id)));
+            }
             if !diagram_type.is_empty() {
                 attr_parts.push(format!("type={diagram_type}"));
             }
             if let Some(t) = title {
                 attr_parts.push(format!("title=\"{}\"", escape_attr(t)));
             }
+            if let Some(d) = direction {
+                attr_parts.push(format!("direction={}", d.as_str()));
+            }
             let attrs = if attr_parts.is_empty() {
                 String::new()
             } else {
@@ -993,6 +1016,7 @@ fn serialize_block(block: &Block) -> String {
             sortable,
             headers,
             rows,
+            raw_content,
             ..
         } => {
             let mut attr_parts = Vec::new();
@@ -1010,16 +1034,31 @@ fn serialize_block(block: &Block) -> String {
             }
             let attrs = format!("[{}]", attr_parts.join(" "));
 
-            let mut content_lines = Vec::new();
-            if !headers.is_empty() {
-                content_lines.push(format!("| {} |", headers.join(" | ")));
-                let sep: Vec<&str> = headers.iter().map(|_| "---").collect();
-                content_lines.push(format!("| {} |", sep.join(" | ")));
-            }
-            for row in rows {
-                content_lines.push(format!("| {} |", row.join(" | ")));
-            }
-            let content = content_lines.join("\n");
+            // CSV and JSON bodies re-emit their source text: a pipe table
+            // under `format=csv`/`format=json` would not parse back.
+            let content = match format {
+                DataFormat::Csv | DataFormat::Json if !raw_content.trim().is_empty() => {
+                    raw_content.trim().to_string()
+                }
+                DataFormat::Csv => std::iter::once(headers)
+                    .filter(|h| !h.is_empty())
+                    .chain(rows)
+                    .map(|row| row.join(","))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                _ => {
+                    let mut content_lines = Vec::new();
+                    if !headers.is_empty() {
+                        content_lines.push(format!("| {} |", headers.join(" | ")));
+                        let sep: Vec<&str> = headers.iter().map(|_| "---").collect();
+                        content_lines.push(format!("| {} |", sep.join(" | ")));
+                    }
+                    for row in rows {
+                        content_lines.push(format!("| {} |", row.join(" | ")));
+                    }
+                    content_lines.join("\n")
+                }
+            };
             if content.is_empty() {
                 format!("::data{attrs}\n::")
             } else {
@@ -1028,6 +1067,7 @@ fn serialize_block(block: &Block) -> String {
         }
 
         Block::Code {
+            id,
             lang,
             file,
             content,
@@ -1035,6 +1075,9 @@ fn serialize_block(block: &Block) -> String {
             ..
         } => {
             let mut attr_parts = Vec::new();
+            if let Some(id) = id {
+                attr_parts.push(format!("id=\"{}\"", escape_attr(id)));
+            }
             if let Some(l) = lang {
                 attr_parts.push(format!("lang={l}"));
             }
@@ -1122,12 +1165,31 @@ fn serialize_block(block: &Block) -> String {
 
         Block::Cite { reference, .. } => cite_to_surf(reference),
 
+        Block::BibliographyData {
+            format,
+            references,
+            raw_content,
+            ..
+        } => {
+            let body = if raw_content.trim().is_empty() {
+                crate::citation::export_references(*format, references)
+            } else {
+                raw_content.clone()
+            };
+            format!(
+                "::bibliography-data[format={}]\n{}\n::",
+                format.as_str(),
+                body.trim_end()
+            )
+        }
+
         Block::Bibliography { style, .. } => match style {
             Some(s) => format!("::bibliography[style={}]\n::", citation_format_slug(*s)),
             None => "::bibliography\n::".to_string(),
         },
 
         Block::Figure {
+            id,
             src,
             caption,
             alt,
@@ -1135,6 +1197,9 @@ fn serialize_block(block: &Block) -> String {
             ..
         } => {
             let mut attr_parts = Vec::new();
+            if let Some(id) = id {
+                attr_parts.push(format!("id=\"{}\"", escape_attr(id)));
+            }
             attr_parts.push(format!("src=\"{}\"", escape_attr(src)));
             if let Some(c) = caption {
                 attr_parts.push(format!("caption=\"{}\"", escape_attr(c)));
@@ -2269,7 +2334,7 @@ fn serialize_block(block: &Block) -> String {
             format!("::editor{attrs_str}\n::")
         }
 
-        Block::Chart { chart_type, source, period, title, data, .. } => {
+        Block::Chart { id, chart_type, source, period, title, data, binding, .. } => {
             let type_str = match chart_type {
                 ChartType::Line => "line",
                 ChartType::Bar => "bar",
@@ -2281,6 +2346,9 @@ fn serialize_block(block: &Block) -> String {
                 ChartType::Radar => "radar",
             };
             let mut attrs_parts = vec![format!("type={type_str}")];
+            if let Some(id) = id {
+                attrs_parts.push(format!("id=\"{}\"", escape_attr(id)));
+            }
             if !source.is_empty() {
                 attrs_parts.push(format!("source=\"{}\"", escape_attr(source)));
             }
@@ -2290,6 +2358,32 @@ fn serialize_block(block: &Block) -> String {
             if let Some(t) = title {
                 attrs_parts.push(format!("title=\"{}\"", escape_attr(t)));
             }
+            // A `data=#id` binding round-trips as the reference, not the
+            // dataset it resolved to.
+            if let Some(b) = binding {
+                attrs_parts.push(format!("data=\"#{}\"", escape_attr(&b.data)));
+                if let Some(x) = &b.x {
+                    attrs_parts.push(format!("x=\"{}\"", escape_attr(x)));
+                }
+                if !b.y.is_empty() {
+                    attrs_parts.push(format!("y=\"{}\"", escape_attr(&b.y.join(","))));
+                }
+                if let Some(agg) = b.aggregate {
+                    attrs_parts.push(format!(
+                        "aggregate={}",
+                        match agg {
+                            ChartAggregate::Sum => "sum",
+                            ChartAggregate::Avg => "avg",
+                            ChartAggregate::Count => "count",
+                        }
+                    ));
+                }
+                if let Some(sort) = &b.sort {
+                    let dir = if sort.descending { "-" } else { "" };
+                    attrs_parts.push(format!("sort=\"{dir}{}\"", escape_attr(&sort.column)));
+                }
+                return format!("::chart[{}]\n::", attrs_parts.join(" "));
+            }
             // Inline data → emit a pipe table body so the chart round-trips.
             if let Some(d) = data {
                 let mut body = String::new();
@@ -3342,6 +3436,7 @@ fn cite_to_surf(r: &Reference) -> String {
         field("container", &r.container),
         field("publisher", &r.publisher),
         field("year", &r.year),
+        field("month", &r.month.map(|m| m.to_string())),
         field("volume", &r.volume),
         field("issue", &r.issue),
         field("pages", &r.pages),
@@ -3681,11 +3776,13 @@ mod tests {
                 done: true,
                 text: "Done".into(),
                 assignee: Some("brady".into()),
+                span: None,
             },
             TaskItem {
                 done: false,
                 text: "Todo".into(),
                 assignee: None,
+                span: None,
             },
         ];
         let doc = SurfDocBuilder::new().tasks(items).build();
@@ -3860,11 +3957,13 @@ mod tests {
                     done: true,
                     text: "Done".into(),
                     assignee: Some("brady".into()),
+                    span: None,
                 },
                 TaskItem {
                     done: false,
                     text: "Todo".into(),
                     assignee: None,
+                    span: None,
                 },
             ])
             .build();
@@ -4053,6 +4152,7 @@ mod tests {
             .faq(vec![FaqItem {
                 question: "Is it free?".into(),
                 answer: "Yes.".into(),
+                span: None,
             }])
             .build();
         let source = to_surf_source(&doc);
@@ -4083,6 +4183,7 @@ mod tests {
                     href: "/".into(),
                     icon: None,
                     image: None, external: false,
+                    span: None,
                 }],
                 None,
             )
@@ -4130,6 +4231,7 @@ mod tests {
                         href: "/".into(),
                         icon: None,
                         image: None, external: false,
+                        span: None,
                     }],
                 }],
                 Some("(c) 2026 CloudSurf"),
@@ -4211,6 +4313,89 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_roundtrip_chart_binding() {
+        // A bound chart serializes its `data=#id` reference, not the
+        // resolved dataset, so the source stays tied to the `::data` block.
+        let source = "::data[id=sales format=csv]\nmonth,revenue\nJan,10\nJan,5\n::\n\n::chart[type=bar data=#sales x=month y=revenue aggregate=sum sort=-revenue]\n::";
+        let parsed = parse::parse(source);
+        let out = to_surf_source(&parsed.doc);
+        assert!(
+            !out.contains("| label |"),
+            "resolved data must not be inlined: {out}"
+        );
+        let reparsed = parse::parse(&out);
+        match &reparsed.doc.blocks[1] {
+            Block::Chart {
+                binding: Some(b),
+                data: Some(d),
+                ..
+            } => {
+                assert_eq!(b.data, "sales");
+                assert_eq!(d.series[0].values, vec![15.0]);
+            }
+            other => panic!("Expected bound Chart, got {:?}\n{out}", other),
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_bibliography_data() {
+        // The authored body is kept; a synthesized block (no body) is
+        // serialized through the exporter for its format.
+        let source = "::bibliography-data[format=ris]\nTY  - BOOK\nID  - jones2019\nAU  - Jones, Alice\nTI  - Foundations\nPY  - 2019\nER  -\n::";
+        let mut parsed = parse::parse(source);
+        assert_eq!(to_surf_source(&parsed.doc), format!("{source}\n"));
+        if let Block::BibliographyData { raw_content, .. } = &mut parsed.doc.blocks[0] {
+            raw_content.clear();
+        }
+        let out = to_surf_source(&parsed.doc);
+        let reparsed = parse::parse(&out);
+        match (&parsed.doc.blocks[0], &reparsed.doc.blocks[0]) {
+            (
+                Block::BibliographyData { references: a, .. },
+                Block::BibliographyData { references: b, .. },
+            ) => assert_eq!(a, b, "{out}"),
+            other => panic!("Expected BibliographyData, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_cross_reference_ids() {
+        let source = "::figure[id=fig:arch src=\"a.png\"]\n::\n\n::chart[type=bar id=fig:sales]\n| | A |\n| x | 1 |\n::\n\n::diagram[id=fig:flow type=flowchart]\na -> b\n::\n\n::code[id=eq:e lang=math]\nE = mc^2\n::";
+        let parsed = parse::parse(source);
+        let out = to_surf_source(&parsed.doc);
+        let reparsed = parse::parse(&out);
+        let labels: Vec<&str> = reparsed
+            .doc
+            .blocks
+            .iter()
+            .filter_map(crate::xref::block_label)
+            .collect();
+        assert_eq!(
+            labels,
+            ["fig:arch", "fig:sales", "fig:flow", "eq:e"],
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_front_matter_csl() {
+        let csl = "<style version=\"1.0\">\n  <citation><layout><text variable=\"title\"/></layout></citation>\n</style>";
+        let doc = SurfDoc {
+            front_matter: Some(FrontMatter {
+                title: Some("Paper".into()),
+                csl: Some(csl.into()),
+                ..Default::default()
+            }),
+            blocks: vec![],
+            source: String::new(),
+        };
+        let out = to_surf_source(&doc);
+        let reparsed = parse::parse(&out);
+        let fm = reparsed.doc.front_matter.expect("front matter");
+        assert_eq!(fm.csl.as_deref(), Some(csl), "{out}");
+    }
+
     #[test]
     fn test_roundtrip_toolbar_button_toggled() {
         let source = "::toolbar\n- button[label=\"Panel\" action=toggle_panel toggled=true]\n- button[label=\"Save\" action=save]\n::";
@@ -4414,11 +4599,13 @@ mod tests {
                     done: true,
                     text: "Implement parser".into(),
                     assignee: None,
+                    span: None,
                 },
                 TaskItem {
                     done: false,
                     text: "Write tests".into(),
                     assignee: Some("brady".into()),
+                    span: None,
                 },
             ])
             .decision(DecisionStatus::Accepted, "We chose Rust.")
@@ -4557,10 +4744,12 @@ mod tests {
                 FaqItem {
                     question: "Is it free?".into(),
                     answer: "Yes.".into(),
+                    span: None,
                 },
                 FaqItem {
                     question: "Can I export?".into(),
                     answer: "PDF and HTML.".into(),
+                    span: None,
                 },
             ])
             .pricing_table(
@@ -4745,12 +4934,14 @@ mod tests {
                         href: "/".into(),
                         icon: None,
                         image: None, external: false,
+                        span: None,
                     },
                     NavItem {
                         label: "About".into(),
                         href: "/about".into(),
                         icon: None,
                         image: None, external: false,
+                        span: None,
                     },
                 ],
                 None,
@@ -4777,6 +4968,7 @@ mod tests {
                         href: "/".into(),
                         icon: None,
                         image: None, external: false,
+                        span: None,
                     }],
                 }],
                 Some("(c) 2026 Test"),
@@ -4897,6 +5089,8 @@ mod tests {
             "::diagram[type=architecture]\na -> b\n::",
             // Empty body.
             "::diagram[type=erd title=\"Empty\"]\n::",
+            // Layout direction.
+            "::diagram[type=flowchart direction=LR]\na -> b\n::",
         ];
 
         for case in cases {
@@ -4909,8 +5103,8 @@ mod tests {
             let source = to_surf_source(&first.doc);
             let second = parse::parse(&source);
 
-            let (Block::Diagram { diagram_type: t1, title: ti1, content: c1, .. },
-                 Block::Diagram { diagram_type: t2, title: ti2, content: c2, .. }) =
+            let (Block::Diagram { diagram_type: t1, title: ti1, direction: d1, content: c1, .. },
+                 Block::Diagram { diagram_type: t2, title: ti2, direction: d2, content: c2, .. }) =
                 (&first.doc.blocks[0], &second.doc.blocks[0])
             else {
                 panic!(
@@ -4920,6 +5114,7 @@ mod tests {
             };
             assert_eq!(t1, t2, "diagram_type drifted for {case:?}");
             assert_eq!(ti1, ti2, "title drifted for {case:?}");
+            assert_eq!(d1, d2, "direction drifted for {case:?}");
             assert_eq!(c1, c2, "content drifted for {case:?}");
         }
     }
@@ -4930,8 +5125,10 @@ mod tests {
         let titled = SurfDoc {
             front_matter: None,
             blocks: vec![Block::Diagram {
+                id: None,
                 diagram_type: "erd".to_string(),
                 title: Some("Data \"v2\"".to_string()),
+                direction: None,
                 content: "users: id pk".to_string(),
                 span: Span::SYNTHETIC,
             }],
@@ -4945,8 +5142,10 @@ mod tests {
         let bare = SurfDoc {
             front_matter: None,
             blocks: vec![Block::Diagram {
+                id: None,
                 diagram_type: String::new(),
                 title: None,
+                direction: None,
                 content: "a -> b".to_string(),
                 span: Span::SYNTHETIC,
             }],
@@ -12,7 +12,10 @@
 //! consumers pin exact substrings / element counts in tests.
 
 use crate::render_html::escape_html;
-use crate::types::{ChartData, ChartType};
+use crate::text_metrics::TextMeasurer;
+use crate::types::{
+    Block, ChartAggregate, ChartBinding, ChartData, ChartSeries, ChartSort, ChartType, DataFormat,
+};
 
 /// Deterministic categorical palette (stable order). Used when the renderer has
 /// no theme accent to key off of. Index `i % len` per series / slice.
@@ -39,6 +42,13 @@ const PAD_R: i64 = 24;
 const PAD_T: i64 = 44;
 const PAD_B: i64 = 64;
 
+/// Legend labels are 11px, axis labels 10px.
+const LEGEND_FONT: TextMeasurer = TextMeasurer::new(11);
+const AXIS_FONT: TextMeasurer = TextMeasurer::new(10);
+/// Baseline-to-baseline distance of a wrapped x-axis label; two lines fit
+/// above the legend.
+const AXIS_LINE_H: f64 = 12.0;
+
 /// Render a chart of `chart_type` from inline `data` as deterministic inline
 /// SVG. `title` (the block's `title=` attribute) becomes the SVG `<title>` and
 /// the on-canvas heading.
@@ -138,10 +148,39 @@ fn push_title(svg: &mut String, title: Option<&str>) {
     }
 }
 
+/// Width of one horizontal legend entry: swatch, label and trailing gap.
+fn legend_item_w(name: &str) -> i64 {
+    18 + LEGEND_FONT.width(name) + 18
+}
+
+/// An x-axis category label wrapped to its `slot` width: at most two lines,
+/// the second truncated with an ellipsis when the label runs longer.
+fn x_label_lines(cat: &str, slot: f64) -> Vec<String> {
+    let max_w = (slot as i64 - 4).max(1);
+    let mut lines = AXIS_FONT.wrap(cat, max_w);
+    if lines.len() > 2 {
+        let rest = lines[1..].join(" ");
+        lines.truncate(1);
+        lines.push(AXIS_FONT.truncate(&rest, max_w));
+    }
+    lines
+}
+
+/// Centred x-axis category label at `(x, y)`, wrapped to `slot` width.
+fn push_x_label(svg: &mut String, x: f64, y: f64, cat: &str, slot: f64) {
+    for (i, line) in x_label_lines(cat, slot).iter().enumerate() {
+        svg.push_str(&format!(
+            "<text class=\"surfdoc-chart-xtick\" x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"10\" fill=\"{MUTED}\">{}</text>",
+            fnum(x),
+            fnum(y + i as f64 * AXIS_LINE_H),
+            escape_html(line),
+        ));
+    }
+}
+
 /// Horizontal legend centred on the canvas at vertical `y`.
 fn push_legend(svg: &mut String, names: &[String], y: i64) {
-    let item_w = |n: &str| 18 + n.chars().count() as i64 * 7 + 18;
-    let total: i64 = names.iter().map(|n| item_w(n)).sum();
+    let total: i64 = names.iter().map(|n| legend_item_w(n)).sum();
     let mut x = (W - total) / 2;
     for (i, n) in names.iter().enumerate() {
         svg.push_str(&format!(
@@ -154,7 +193,7 @@ fn push_legend(svg: &mut String, names: &[String], y: i64) {
             x + 16,
             escape_html(n),
         ));
-        x += item_w(n);
+        x += legend_item_w(n);
     }
 }
 
@@ -290,14 +329,10 @@ fn render_line(data: &ChartData, title: Option<&str>, fill: bool) -> String {
     };
     let y_at = |v: f64| -> f64 { y_bottom - (v - lo) / span * plot_h };
 
-    // x category labels
+    // x category labels, wrapped to the spacing between points
+    let slot = if n == 1 { plot_w } else { plot_w / (n - 1) as f64 };
     for (i, cat) in data.categories.iter().enumerate() {
-        svg.push_str(&format!(
-            "<text class=\"surfdoc-chart-xtick\" x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"10\" fill=\"{MUTED}\">{}</text>",
-            fnum(x_at(i)),
-            fnum(y_bottom + 16.0),
-            escape_html(cat),
-        ));
+        push_x_label(&mut svg, x_at(i), y_bottom + 16.0, cat, slot);
     }
 
     for (si, s) in data.series.iter().enumerate() {
@@ -391,12 +426,7 @@ fn render_bar(data: &ChartData, title: Option<&str>, stacked: bool) -> String {
     for (i, cat) in data.categories.iter().enumerate() {
         let slot_left = x0 + i as f64 * slot;
         // x label
-        svg.push_str(&format!(
-            "<text class=\"surfdoc-chart-xtick\" x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"10\" fill=\"{MUTED}\">{}</text>",
-            fnum(slot_left + slot / 2.0),
-            fnum(y_bottom + 16.0),
-            escape_html(cat),
-        ));
+        push_x_label(&mut svg, slot_left + slot / 2.0, y_bottom + 16.0, cat, slot);
 
         if stacked {
             let bw = slot * 0.6;
@@ -707,6 +737,309 @@ fn render_radar(data: &ChartData, title: Option<&str>) -> String {
     svg
 }
 
+// ------------------------------------------------------------------
+// Data binding (`::chart[data=#id]`)
+// ------------------------------------------------------------------
+//
+// A bound chart names a `::data[id=…]` table instead of carrying an inline
+// body. Binding is a parse-time pass over the whole document: every
+// renderer (HTML, Typst, native scenes) then sees an ordinary inline
+// `ChartData`. Grouping keeps first-appearance order and sorting is stable,
+// so the resolved dataset is a pure function of the document.
+
+/// Fill the `data` of every bound `::chart` in `blocks` (containers
+/// included) from the `::data` block it references. Idempotent: a chart
+/// whose reference does not resolve gets `data = None` and keeps its
+/// mount-point fallback (lint `L042` reports why).
+pub(crate) fn bind_chart_data(blocks: &mut [Block]) {
+    bind_after(&[], blocks);
+}
+
+/// [`bind_chart_data`] for a batch of blocks that follows `committed` (the
+/// streaming parser's already-emitted prefix), whose `::data` blocks are
+/// visible to the batch.
+pub(crate) fn bind_after(committed: &[Block], batch: &mut [Block]) {
+    if !has_binding(batch) {
+        return;
+    }
+    let mut tables = collect_tables(committed);
+    collect_tables_rec(batch, &mut tables);
+    bind_rec(batch, &tables);
+}
+
+/// Streaming commit limit: the number of leading `batch` blocks whose chart
+/// bindings all name a `::data` id already seen in `committed` or `batch`.
+/// A chart past that point must wait — a later block may still supply its
+/// table, and the first table with an id wins.
+pub(crate) fn bindable_prefix(committed: &[Block], batch: &[Block]) -> usize {
+    if !has_binding(batch) {
+        return batch.len();
+    }
+    let mut tables = collect_tables(committed);
+    collect_tables_rec(batch, &mut tables);
+    batch
+        .iter()
+        .position(|b| has_unknown_source(b, &tables))
+        .unwrap_or(batch.len())
+}
+
+fn has_unknown_source(block: &Block, tables: &[DataTable]) -> bool {
+    match block {
+        Block::Chart {
+            binding: Some(binding),
+            ..
+        } => !tables.iter().any(|(id, ..)| *id == binding.data),
+        _ => block
+            .children()
+            .is_some_and(|children| children.iter().any(|c| has_unknown_source(c, tables))),
+    }
+}
+
+/// Resolve one binding against the document's `::data` blocks. The error
+/// is a human-readable reason for the `L042` lint.
+pub(crate) fn resolve_binding(
+    binding: &ChartBinding,
+    blocks: &[Block],
+) -> Result<ChartData, String> {
+    resolve_in(binding, &collect_tables(blocks))
+}
+
+/// `(id, headers, rows)` of every `::data` block with an id, in document
+/// order. JSON bodies are tabulated here; table and CSV bodies are already.
+type DataTable = (String, Vec<String>, Vec<Vec<String>>);
+
+fn collect_tables(blocks: &[Block]) -> Vec<DataTable> {
+    let mut out = Vec::new();
+    collect_tables_rec(blocks, &mut out);
+    out
+}
+
+fn collect_tables_rec(blocks: &[Block], out: &mut Vec<DataTable>) {
+    for block in blocks {
+        if let Block::Data {
+            id: Some(id),
+            format,
+            headers,
+            rows,
+            raw_content,
+            ..
+        } = block
+        {
+            let (headers, rows) = match format {
+                DataFormat::Json => json_table(raw_content),
+                _ => (headers.clone(), rows.clone()),
+            };
+            out.push((id.clone(), headers, rows));
+        }
+        if let Some(children) = block.children() {
+            collect_tables_rec(children, out);
+        }
+    }
+}
+
+fn has_binding(blocks: &[Block]) -> bool {
+    blocks.iter().any(|b| {
+        matches!(
+            b,
+            Block::Chart {
+                binding: Some(_),
+                ..
+            }
+        ) || b.children().is_some_and(has_binding)
+    })
+}
+
+fn bind_rec(blocks: &mut [Block], tables: &[DataTable]) {
+    for block in blocks {
+        if let Block::Chart {
+            binding: Some(binding),
+            data,
+            ..
+        } = block
+        {
+            *data = resolve_in(binding, tables).ok();
+        }
+        if let Some(children) = block.children_mut() {
+            bind_rec(children, tables);
+        }
+    }
+}
+
+/// Tabulate a JSON `::data` body: an array of arrays (first row = headers)
+/// or an array of objects (headers = keys in first-seen order; each object's
+/// keys come in sorted order, so name `x`/`y` explicitly for JSON). Anything
+/// else is an empty table.
+fn json_table(raw: &str) -> (Vec<String>, Vec<Vec<String>>) {
+    let Ok(serde_json::Value::Array(items)) = serde_json::from_str::<serde_json::Value>(raw) else {
+        return (Vec::new(), Vec::new());
+    };
+    let cell = |v: &serde_json::Value| match v {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    if items.iter().all(|i| i.is_array()) {
+        let mut rows = items
+            .iter()
+            .filter_map(|i| i.as_array())
+            .map(|r| r.iter().map(cell).collect::<Vec<_>>());
+        let headers = rows.next().unwrap_or_default();
+        return (headers, rows.collect());
+    }
+    let mut headers: Vec<String> = Vec::new();
+    for obj in items.iter().filter_map(|i| i.as_object()) {
+        for key in obj.keys() {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+    }
+    let rows = items
+        .iter()
+        .filter_map(|i| i.as_object())
+        .map(|obj| {
+            headers
+                .iter()
+                .map(|h| obj.get(h).map(cell).unwrap_or_default())
+                .collect()
+        })
+        .collect();
+    (headers, rows)
+}
+
+fn resolve_in(binding: &ChartBinding, tables: &[DataTable]) -> Result<ChartData, String> {
+    let Some((id, headers, rows)) = tables.iter().find(|(id, ..)| *id == binding.data) else {
+        return Err(format!("no `::data` block with id \"{}\"", binding.data));
+    };
+    if headers.is_empty() || rows.is_empty() {
+        return Err(format!("`::data[id={id}]` has no rows"));
+    }
+    let column = |name: &str| {
+        headers.iter().position(|h| h == name).ok_or_else(|| {
+            format!(
+                "`::data[id={id}]` has no column \"{name}\" (columns: {})",
+                headers.join(", ")
+            )
+        })
+    };
+    let x = match &binding.x {
+        Some(name) => column(name)?,
+        None => 0,
+    };
+    // No `y` with `aggregate=count` counts rows under one `count` series.
+    let row_count = binding.y.is_empty() && binding.aggregate == Some(ChartAggregate::Count);
+    let y: Vec<usize> = if !binding.y.is_empty() {
+        binding
+            .y
+            .iter()
+            .map(|name| column(name))
+            .collect::<Result<_, _>>()?
+    } else if row_count {
+        Vec::new()
+    } else {
+        (0..headers.len()).filter(|&i| i != x).collect()
+    };
+    if y.is_empty() && !row_count {
+        return Err(format!(
+            "`::data[id={id}]` has no series column besides \"{}\"",
+            headers[x]
+        ));
+    }
+
+    let mut categories: Vec<String> = Vec::new();
+    let mut series: Vec<ChartSeries> = if row_count {
+        vec![ChartSeries {
+            name: "count".into(),
+            values: Vec::new(),
+        }]
+    } else {
+        y.iter()
+            .map(|&i| ChartSeries {
+                name: headers[i].clone(),
+                values: Vec::new(),
+            })
+            .collect()
+    };
+    // Per category: rows merged so far (for `avg`).
+    let mut merged: Vec<usize> = Vec::new();
+    for row in rows {
+        let cat = table_cell(row, x);
+        let k = match binding.aggregate {
+            Some(_) => categories.iter().position(|c| c == cat),
+            None => None,
+        };
+        let k = k.unwrap_or_else(|| {
+            categories.push(cat.to_string());
+            merged.push(0);
+            for s in &mut series {
+                s.values.push(0.0);
+            }
+            categories.len() - 1
+        });
+        merged[k] += 1;
+        if row_count {
+            series[0].values[k] += 1.0;
+            continue;
+        }
+        for (s, &i) in series.iter_mut().zip(&y) {
+            let raw = table_cell(row, i);
+            s.values[k] += match binding.aggregate {
+                Some(ChartAggregate::Count) => f64::from(u8::from(!raw.trim().is_empty())),
+                _ => crate::blocks::parse_chart_number(raw),
+            };
+        }
+    }
+    if binding.aggregate == Some(ChartAggregate::Avg) {
+        for s in &mut series {
+            for (v, n) in s.values.iter_mut().zip(&merged) {
+                *v /= *n as f64;
+            }
+        }
+    }
+
+    let mut data = ChartData { categories, series };
+    if let Some(sort) = &binding.sort {
+        sort_data(&mut data, sort, &headers[x])?;
+    }
+    Ok(data)
+}
+
+/// Cell `i` of a table row; short rows read as empty.
+fn table_cell(row: &[String], i: usize) -> &str {
+    row.get(i).map(String::as_str).unwrap_or("")
+}
+
+/// Reorder categories (and every series with them) by the x column or one
+/// series. Numeric categories compare as numbers; the sort is stable.
+fn sort_data(data: &mut ChartData, sort: &ChartSort, x_name: &str) -> Result<(), String> {
+    let mut order: Vec<usize> = (0..data.categories.len()).collect();
+    if sort.column == x_name {
+        let cats = &data.categories;
+        order.sort_by(|&a, &b| {
+            match (cats[a].trim().parse::<f64>(), cats[b].trim().parse::<f64>()) {
+                (Ok(l), Ok(r)) => l.total_cmp(&r),
+                _ => cats[a].cmp(&cats[b]),
+            }
+        });
+    } else {
+        let Some(s) = data.series.iter().find(|s| s.name == sort.column) else {
+            return Err(format!(
+                "cannot sort by \"{}\": not the x column or a charted series",
+                sort.column
+            ));
+        };
+        order.sort_by(|&a, &b| s.values[a].total_cmp(&s.values[b]));
+    }
+    if sort.descending {
+        order.reverse();
+    }
+    data.categories = order.iter().map(|&i| data.categories[i].clone()).collect();
+    for s in &mut data.series {
+        s.values = order.iter().map(|&i| s.values[i]).collect();
+    }
+    Ok(())
+}
+
 // ------------------------------------------------------------------
 // Geometry scenes (native feature)
 // ------------------------------------------------------------------
@@ -796,6 +1129,7 @@ impl SceneOut {
             fill,
             stroke: fill,
             stroke_width: 1.0,
+            href: None,
         });
     }
     fn dot(&mut self, cx: f64, cy: f64, r: f64, fill: NativeRole) {
@@ -809,7 +1143,12 @@ impl SceneOut {
         });
     }
     fn polygon(&mut self, points: Vec<NativePoint>, fill: NativeRole, stroke: NativeRole) {
-        self.shapes.push(NativeShape::Polygon { points, fill, stroke });
+        self.shapes.push(NativeShape::Polygon {
+            points,
+            fill,
+            stroke,
+            href: None,
+        });
     }
 }
 
@@ -863,13 +1202,12 @@ fn scene_y_axis(out: &mut SceneOut, x0: f64, y0: f64, plot_w: f64, plot_h: f64,
 /// Scene twin of [`push_legend`] (horizontal, centred at `y`).
 #[cfg(feature = "native")]
 fn scene_legend(out: &mut SceneOut, names: &[String], y: f64, series_count: usize) {
-    let item_w = |n: &str| 18 + n.chars().count() as i64 * 7 + 18;
-    let total: i64 = names.iter().map(|n| item_w(n)).sum();
+    let total: i64 = names.iter().map(|n| legend_item_w(n)).sum();
     let mut x = ((W - total) / 2) as f64;
     for (i, n) in names.iter().enumerate() {
         out.rect(x, y - 10.0, 12.0, 12.0, 2.0, series_role(i, series_count));
         out.label(x + 16.0, y, n, NativeRole::TextPrimary, 11.0, false, NativeAnchor::Start);
-        x += item_w(n) as f64;
+        x += legend_item_w(n) as f64;
     }
 }
 
@@ -919,8 +1257,12 @@ fn scene_line(out: &mut SceneOut, data: &ChartData, fill: bool) {
     };
     let y_at = |v: f64| -> f64 { y_bottom - (v - lo) / span * plot_h };
 
+    let slot = if n == 1 { plot_w } else { plot_w / (n - 1) as f64 };
     for (i, cat) in data.categories.iter().enumerate() {
-        out.label(x_at(i), y_bottom + 16.0, cat, NativeRole::TextSecondary, 10.0, false, NativeAnchor::Middle);
+        for (k, line) in x_label_lines(cat, slot).iter().enumerate() {
+            let y = y_bottom + 16.0 + k as f64 * AXIS_LINE_H;
+            out.label(x_at(i), y, line, NativeRole::TextSecondary, 10.0, false, NativeAnchor::Middle);
+        }
     }
 
     let m = data.series.len();
@@ -987,7 +1329,10 @@ fn scene_bar(out: &mut SceneOut, data: &ChartData, stacked: bool) {
 
     for (i, cat) in data.categories.iter().enumerate() {
         let slot_left = x0 + i as f64 * slot;
-        out.label(slot_left + slot / 2.0, y_bottom + 16.0, cat, NativeRole::TextSecondary, 10.0, false, NativeAnchor::Middle);
+        for (k, line) in x_label_lines(cat, slot).iter().enumerate() {
+            let y = y_bottom + 16.0 + k as f64 * AXIS_LINE_H;
+            out.label(slot_left + slot / 2.0, y, line, NativeRole::TextSecondary, 10.0, false, NativeAnchor::Middle);
+        }
 
         if stacked {
             let bw = slot * 0.6;
@@ -1335,6 +1680,18 @@ mod tests {
         assert!(svg.contains("Product B"));
     }
 
+    #[test]
+    fn legend_and_axis_labels_use_measured_widths() {
+        let mut d = data();
+        d.categories[0] = "Quarterly revenue across all regions, channels and product families worldwide".into();
+        let svg = render_svg(ChartType::Bar, &d, None);
+        // The long category wraps to two lines, the second ellipsized.
+        assert_eq!(svg.matches("surfdoc-chart-xtick").count(), 5);
+        assert!(svg.contains('\u{2026}'));
+        assert_eq!(legend_item_w("Product A"), 36 + LEGEND_FONT.width("Product A"));
+        assert!(legend_item_w("iiii") < legend_item_w("WWWW"));
+    }
+
     // ── geometry scenes (native) ────────────────────────────────────
 
     #[cfg(feature = "native")]
@@ -1405,4 +1762,121 @@ mod tests {
             assert_eq!(before, render_svg(t, &d, Some("T")));
         }
     }
+
+    // --- data binding ---
+
+    fn bound(src: &str) -> Vec<Option<ChartData>> {
+        crate::parse(src)
+            .doc
+            .blocks
+            .into_iter()
+            .filter_map(|b| match b {
+                Block::Chart { data, .. } => Some(data),
+                _ => None,
+            })
+            .collect()
+    }
+
+    const SALES: &str = "::data[id=sales format=csv]\nmonth,region,revenue,cost\nJan,north,100,60\nFeb,north,80,50\nJan,south,40,30\n::\n\n";
+
+    #[test]
+    fn binding_selects_x_and_y_columns() {
+        let src = format!("{SALES}::chart[type=bar data=#sales x=month y=revenue,cost]\n::\n");
+        let d = bound(&src).remove(0).expect("binding resolved");
+        assert_eq!(d.categories, vec!["Jan", "Feb", "Jan"]);
+        assert_eq!(d.series.len(), 2);
+        assert_eq!(d.series[0].name, "revenue");
+        assert_eq!(d.series[0].values, vec![100.0, 80.0, 40.0]);
+        assert_eq!(d.series[1].values, vec![60.0, 50.0, 30.0]);
+    }
+
+    #[test]
+    fn binding_defaults_to_first_column_and_remaining_series() {
+        let src = "::data[id=t]\n| q | a | b |\n|---|---|---|\n| Q1 | 1 | 2 |\n::\n\n::chart[type=line data=#t]\n::\n";
+        let d = bound(src).remove(0).expect("binding resolved");
+        assert_eq!(d.categories, vec!["Q1"]);
+        assert_eq!(
+            d.series.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+    }
+
+    #[test]
+    fn binding_aggregates_grouped_by_x() {
+        let src = format!(
+            "{SALES}::chart[type=bar data=#sales x=month y=revenue aggregate=sum]\n::\n\n\
+             ::chart[type=bar data=#sales x=month y=revenue aggregate=avg]\n::\n\n\
+             ::chart[type=bar data=#sales x=region aggregate=count]\n::\n"
+        );
+        let charts = bound(&src);
+        let sum = charts[0].as_ref().unwrap();
+        assert_eq!(sum.categories, vec!["Jan", "Feb"]);
+        assert_eq!(sum.series[0].values, vec![140.0, 80.0]);
+        let avg = charts[1].as_ref().unwrap();
+        assert_eq!(avg.series[0].values, vec![70.0, 80.0]);
+        let count = charts[2].as_ref().unwrap();
+        assert_eq!(count.categories, vec!["north", "south"]);
+        assert_eq!(count.series[0].name, "count");
+        assert_eq!(count.series[0].values, vec![2.0, 1.0]);
+    }
+
+    #[test]
+    fn binding_sorts_by_series_or_category() {
+        let src = format!(
+            "{SALES}::chart[type=bar data=#sales x=month y=revenue aggregate=sum sort=-revenue]\n::\n\n\
+             ::chart[type=bar data=#sales x=month y=revenue aggregate=sum sort=month]\n::\n"
+        );
+        let charts = bound(&src);
+        let by_value = charts[0].as_ref().unwrap();
+        assert_eq!(by_value.categories, vec!["Jan", "Feb"]);
+        assert_eq!(by_value.series[0].values, vec![140.0, 80.0]);
+        let by_x = charts[1].as_ref().unwrap();
+        assert_eq!(by_x.categories, vec!["Feb", "Jan"]);
+        assert_eq!(by_x.series[0].values, vec![80.0, 140.0]);
+    }
+
+    #[test]
+    fn binding_reads_json_sources() {
+        let src = "::data[id=j format=json]\n[{\"k\": \"a\", \"v\": 3}, {\"k\": \"b\", \"v\": 5}]\n::\n\n::chart[type=pie data=#j x=k y=v]\n::\n";
+        let d = bound(src).remove(0).expect("binding resolved");
+        assert_eq!(d.categories, vec!["a", "b"]);
+        assert_eq!(d.series[0].values, vec![3.0, 5.0]);
+    }
+
+    #[test]
+    fn unresolved_binding_leaves_data_empty() {
+        let src = format!(
+            "::chart[type=bar data=#missing]\n::\n\n{SALES}::chart[type=bar data=#sales y=profit]\n::\n"
+        );
+        assert!(bound(&src).iter().all(Option::is_none));
+        let err = resolve_binding(
+            &ChartBinding {
+                data: "sales".into(),
+                x: None,
+                y: vec!["profit".into()],
+                aggregate: None,
+                sort: None,
+            },
+            &crate::parse(&src).doc.blocks,
+        )
+        .unwrap_err();
+        assert!(err.contains("no column \"profit\""), "{err}");
+    }
+
+    #[test]
+    fn bound_chart_renders_in_html() {
+        let src =
+            format!("{SALES}::chart[type=bar data=#sales x=month y=revenue aggregate=sum]\n::\n");
+        let html = crate::parse(&src).doc.to_html();
+        assert!(
+            html.contains("surfdoc-chart-bar"),
+            "bound chart renders bars: {html}"
+        );
+    }
+
+    #[test]
+    fn binding_applies_to_charts_before_their_data() {
+        let src = format!("::chart[type=bar data=#sales y=revenue]\n::\n\n{SALES}");
+        assert!(bound(&src)[0].is_some());
+    }
 }
@@ -17,13 +17,27 @@
 //! a [`CiteContext`] (the active style + the document's references + IEEE
 //! numbering). Renderers install a context via [`install_context`] and read it
 //! through [`with_active`] / [`substitute_text_cites`].
+//!
+//! Reference libraries exported from reference managers are imported and
+//! exported as BibTeX, CSL-JSON or RIS ([`from_bibtex`], [`to_bibtex`], …);
+//! `::bibliography-data` blocks carry the imported references.
+//!
+//! Beyond the built-in styles, a document may supply any CSL 1.0 style as
+//! front matter `csl:` (the style XML itself — the engine never touches the
+//! filesystem). [`document_context`] binds it into the [`CiteContext`], and the
+//! context's [`CiteContext::in_text`] / [`CiteContext::bibliography`] format
+//! through [`crate::csl`] instead of the `Format` styles.
 
 use std::collections::{BTreeMap, BTreeSet};
 use std::cell::RefCell;
+use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 
-use crate::types::{Block, Format};
+use crate::csl::{CslProcessor, CslStyle};
+use crate::footnote::Notes;
+use crate::types::{Block, Format, SurfDoc};
+use crate::xref::Xrefs;
 
 // ───────────────────────────────────────────────────────────────────────────
 // Data model
@@ -92,6 +106,9 @@ pub struct Reference {
     pub publisher: Option<String>,
     /// Year of publication (kept as a string to preserve `n.d.`/`2020a`).
     pub year: Option<String>,
+    /// Month of publication (`1`–`12`), normalized from names, abbreviations
+    /// or numbers. Styles format the year only; imports and exports keep it.
+    pub month: Option<u8>,
     /// Volume number.
     pub volume: Option<String>,
     /// Issue number.
@@ -213,13 +230,15 @@ fn strip_page_prefix(s: &str) -> String {
     t.to_string()
 }
 
+/// English month names, January first.
+pub(crate) const MONTH_NAMES: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June", "July", "August",
+    "September", "October", "November", "December",
+];
+
 /// MLA access-date format: `2021-05-01` → `1 May 2021`. Falls back to the input
 /// when it is not an ISO date.
 fn fmt_date_mla(iso: &str) -> String {
-    const MONTHS: [&str; 12] = [
-        "January", "February", "March", "April", "May", "June", "July", "August",
-        "September", "October", "November", "December",
-    ];
     let parts: Vec<&str> = iso.split('-').collect();
     if parts.len() == 3 {
         if let (Ok(y), Ok(m), Ok(d)) = (
@@ -228,7 +247,7 @@ fn fmt_date_mla(iso: &str) -> String {
             parts[2].parse::<i32>(),
         ) {
             if (1..=12).contains(&m) {
-                return format!("{} {} {}", d, MONTHS[m - 1], y);
+                return format!("{} {} {}", d, MONTH_NAMES[m - 1], y);
             }
         }
         // fall through to raw on out-of-range month
@@ -723,6 +742,1235 @@ pub fn reference_list(refs: &[Reference], style: Format) -> Vec<String> {
         .collect()
 }
 
+// ───────────────────────────────────────────────────────────────────────────
+// Import / export: BibTeX, CSL-JSON, RIS
+// ───────────────────────────────────────────────────────────────────────────
+//
+// Reference managers (Zotero, JabRef, Mendeley) exchange bibliographies in
+// these three formats. Parsing is lenient like the `::cite` body: malformed
+// entries are skipped, unknown fields ignored. Entries without a key get a
+// generated `family` + `year` key (`smith2020`, then `smith2020a`, …) so
+// inline `[@key]` citations can still reach them.
+
+/// Interchange format of a `::bibliography-data` body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum BibFormat {
+    /// BibTeX / BibLaTeX (`@article{key, …}`).
+    #[default]
+    Bibtex,
+    /// CSL-JSON, as exported by Zotero and pandoc (`[{"id": …}]`).
+    CslJson,
+    /// RIS tagged records (`TY  - JOUR` … `ER  - `).
+    Ris,
+}
+
+impl BibFormat {
+    /// Parse a `format=` value (case-insensitive). `None` when unrecognised.
+    pub fn parse(s: &str) -> Option<BibFormat> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "bibtex" | "bib" | "biblatex" => Some(BibFormat::Bibtex),
+            "csl-json" | "csljson" | "csl" | "json" => Some(BibFormat::CslJson),
+            "ris" => Some(BibFormat::Ris),
+            _ => None,
+        }
+    }
+
+    /// Guess the format of an unlabelled body from its first significant
+    /// character: `@` is BibTeX, `[`/`{` is CSL-JSON, a `TY  -` tag is RIS.
+    /// `None` for anything else (e.g. a `::cite` field list).
+    pub fn sniff(body: &str) -> Option<BibFormat> {
+        let t = body.trim_start();
+        if t.starts_with('@') {
+            Some(BibFormat::Bibtex)
+        } else if t.starts_with('[') || t.starts_with('{') {
+            Some(BibFormat::CslJson)
+        } else if t.starts_with("TY  -") {
+            Some(BibFormat::Ris)
+        } else {
+            None
+        }
+    }
+
+    /// The canonical `format=` value.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BibFormat::Bibtex => "bibtex",
+            BibFormat::CslJson => "csl-json",
+            BibFormat::Ris => "ris",
+        }
+    }
+}
+
+/// Parse a bibliography body in `format` into references.
+pub fn import_references(format: BibFormat, src: &str) -> Vec<Reference> {
+    match format {
+        BibFormat::Bibtex => from_bibtex(src),
+        BibFormat::CslJson => from_csl_json(src),
+        BibFormat::Ris => from_ris(src),
+    }
+}
+
+/// Serialize references in `format`. Output is deterministic.
+pub fn export_references(format: BibFormat, refs: &[Reference]) -> String {
+    match format {
+        BibFormat::Bibtex => to_bibtex(refs),
+        BibFormat::CslJson => to_csl_json(refs),
+        BibFormat::Ris => to_ris(refs),
+    }
+}
+
+/// Normalize a month given as a number (`3`, `03`), an English name or
+/// abbreviation (`March`, `mar`, `Mar.`) to `1`–`12`.
+pub(crate) fn normalize_month(s: &str) -> Option<u8> {
+    let t = s.trim().trim_end_matches('.').to_ascii_lowercase();
+    if let Ok(n) = t.parse::<u8>() {
+        return (1..=12).contains(&n).then_some(n);
+    }
+    if t.len() < 3 {
+        return None;
+    }
+    MONTH_NAMES
+        .iter()
+        .position(|m| m.to_ascii_lowercase().starts_with(&t))
+        .map(|i| i as u8 + 1)
+}
+
+/// Split a loosely formatted date (`2020`, `2020-03-15`, `2020/03//`,
+/// `March 2020`, `15 Mar 2020`) into year, month and day. The year is the
+/// first four-digit number; a month name anywhere, or the number after the
+/// year, is the month; the number after that is the day.
+pub(crate) fn normalize_date(s: &str) -> (Option<String>, Option<u8>, Option<u8>) {
+    let tokens: Vec<&str> = s
+        .split(|c: char| c == '-' || c == '/' || c == ',' || c.is_whitespace())
+        .filter(|t| !t.is_empty())
+        .collect();
+    let Some(yi) = tokens
+        .iter()
+        .position(|t| t.len() == 4 && t.chars().all(|c| c.is_ascii_digit()))
+    else {
+        return (None, None, None);
+    };
+    let year = Some(tokens[yi].to_string());
+    let named = tokens
+        .iter()
+        .position(|t| t.chars().all(char::is_alphabetic) && normalize_month(t).is_some());
+    match named {
+        Some(mi) => {
+            let day = tokens
+                .iter()
+                .enumerate()
+                .find(|&(i, t)| {
+                    i != yi && t.len() <= 2 && t.parse::<u8>().is_ok_and(|d| (1..=31).contains(&d))
+                })
+                .and_then(|(_, t)| t.parse().ok());
+            (year, normalize_month(tokens[mi]), day)
+        }
+        None => {
+            let month = tokens.get(yi + 1).and_then(|t| normalize_month(t));
+            let day = month
+                .and(tokens.get(yi + 2))
+                .and_then(|t| t.parse::<u8>().ok())
+                .filter(|d| (1..=31).contains(d));
+            (year, month, day)
+        }
+    }
+}
+
+/// A date as ISO `YYYY-MM-DD` when it has all three parts, else the input.
+fn iso_date(s: &str) -> String {
+    match normalize_date(s) {
+        (Some(y), Some(m), Some(d)) => format!("{y}-{m:02}-{d:02}"),
+        _ => s.trim().to_string(),
+    }
+}
+
+/// A DOI without its resolver prefix.
+fn bare_doi(s: &str) -> String {
+    let t = s.trim();
+    [
+        "https://doi.org/",
+        "http://doi.org/",
+        "https://dx.doi.org/",
+        "http://dx.doi.org/",
+        "doi:",
+    ]
+    .iter()
+    .find_map(|p| t.strip_prefix(p))
+    .unwrap_or(t)
+    .to_string()
+}
+
+/// A page range with its en/em dash (or BibTeX `--`) as a plain hyphen.
+fn plain_pages(s: &str) -> String {
+    s.trim().replace("--", "-").replace(['–', '—'], "-")
+}
+
+/// Give every reference without a key a unique `family` + `year` key.
+fn fill_missing_keys(refs: &mut [Reference]) {
+    let mut taken: BTreeSet<String> = refs
+        .iter()
+        .filter(|r| !r.key.is_empty())
+        .map(|r| r.key.clone())
+        .collect();
+    for (i, r) in refs.iter_mut().enumerate() {
+        if !r.key.is_empty() {
+            continue;
+        }
+        let family: String = r
+            .authors
+            .first()
+            .map(|a| {
+                a.family
+                    .to_lowercase()
+                    .chars()
+                    .filter(|c| c.is_alphanumeric())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let year: String = r
+            .year
+            .as_deref()
+            .unwrap_or("")
+            .chars()
+            .filter(char::is_ascii_digit)
+            .collect();
+        let base = match format!("{family}{year}") {
+            b if b.is_empty() => format!("ref{}", i + 1),
+            b => b,
+        };
+        let mut key = base.clone();
+        let mut suffix = b'a';
+        while taken.contains(&key) && suffix <= b'z' {
+            key = format!("{base}{}", suffix as char);
+            suffix += 1;
+        }
+        taken.insert(key.clone());
+        r.key = key;
+    }
+}
+
+// ── LaTeX decoding ──────────────────────────────────────────────────────────
+
+/// Accent commands: `(command, combining mark, bases, precomposed)`. A base
+/// letter found in `bases` maps to the character at the same position of
+/// `precomposed`; any other base takes the combining mark.
+const LATEX_ACCENTS: &[(char, char, &str, &str)] = &[
+    ('`', '\u{300}', "aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+    (
+        '\'',
+        '\u{301}',
+        "aeiouyAEIOUYcnszCNSZ",
+        "áéíóúýÁÉÍÓÚÝćńśźĆŃŚŹ",
+    ),
+    ('^', '\u{302}', "aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+    ('"', '\u{308}', "aeiouyAEIOUY", "äëïöüÿÄËÏÖÜŸ"),
+    ('~', '\u{303}', "anoANO", "ãñõÃÑÕ"),
+    ('=', '\u{304}', "aeiouAEIOU", "āēīōūĀĒĪŌŪ"),
+    ('.', '\u{307}', "zeZEI", "żėŻĖİ"),
+    ('c', '\u{327}', "cstCST", "çşţÇŞŢ"),
+    ('v', '\u{30c}', "cszrnedtCSZRNEDT", "čšžřňěďťČŠŽŘŇĚĎŤ"),
+    ('u', '\u{306}', "agAG", "ăğĂĞ"),
+    ('H', '\u{30b}', "ouOU", "őűŐŰ"),
+    ('r', '\u{30a}', "auAU", "åůÅŮ"),
+    ('k', '\u{328}', "aeAE", "ąęĄĘ"),
+    ('d', '\u{323}', "", ""),
+    ('b', '\u{331}', "", ""),
+];
+
+/// Letter-like control words (`\ss`, `\o`, …) and their characters.
+const LATEX_LETTERS: &[(&str, &str)] = &[
+    ("ss", "ß"),
+    ("o", "ø"),
+    ("O", "Ø"),
+    ("aa", "å"),
+    ("AA", "Å"),
+    ("ae", "æ"),
+    ("AE", "Æ"),
+    ("oe", "œ"),
+    ("OE", "Œ"),
+    ("l", "ł"),
+    ("L", "Ł"),
+    ("i", "ı"),
+    ("j", "ȷ"),
+    ("dh", "ð"),
+    ("DH", "Ð"),
+    ("th", "þ"),
+    ("TH", "Þ"),
+    ("textendash", "–"),
+    ("textemdash", "—"),
+    ("ldots", "…"),
+    ("dots", "…"),
+];
+
+/// Decode the LaTeX markup of a BibTeX value into plain text: accent
+/// commands (`\"o`, `{\'e}`, `\c{c}`), letter commands (`\ss`, `\o`),
+/// escaped specials (`\&`, `\%`), `--`/`---` dashes and `~` ties. Grouping
+/// braces are dropped, other commands (`\emph{x}`) keep only their argument,
+/// and runs of whitespace collapse to one space.
+fn decode_latex(s: &str) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '{' | '}' => i += 1,
+            '~' => {
+                out.push(' ');
+                i += 1;
+            }
+            '-' if chars.get(i + 1) == Some(&'-') => {
+                if chars.get(i + 2) == Some(&'-') {
+                    out.push('—');
+                    i += 3;
+                } else {
+                    out.push('–');
+                    i += 2;
+                }
+            }
+            '\\' => i = decode_latex_command(&chars, i + 1, &mut out),
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Decode one command starting just after its backslash; returns the index
+/// after the command and its argument.
+fn decode_latex_command(chars: &[char], i: usize, out: &mut String) -> usize {
+    let Some(&c) = chars.get(i) else {
+        return i;
+    };
+    if !c.is_ascii_alphabetic() {
+        if let Some(accent) = LATEX_ACCENTS.iter().find(|a| a.0 == c) {
+            let (arg, next) = latex_argument(chars, i + 1);
+            push_accented(out, &arg, accent);
+            return next;
+        }
+        // Control symbol: `\&`, `\%`, `\{`, `\ ` … stand for themselves.
+        out.push(if c == '\\' { ' ' } else { c });
+        return i + 1;
+    }
+    let mut end = i;
+    while chars.get(end).is_some_and(char::is_ascii_alphabetic) {
+        end += 1;
+    }
+    let name: String = chars[i..end].iter().collect();
+    // A control word swallows the spaces after it.
+    let mut next = end;
+    while chars.get(next).is_some_and(|c| *c == ' ') {
+        next += 1;
+    }
+    let mut name_chars = name.chars();
+    if let (Some(letter), None) = (name_chars.next(), name_chars.next())
+        && let Some(accent) = LATEX_ACCENTS.iter().find(|a| a.0 == letter)
+        && !LATEX_LETTERS.iter().any(|(n, _)| *n == name)
+    {
+        let (arg, after) = latex_argument(chars, next);
+        push_accented(out, &arg, accent);
+        return after;
+    }
+    if let Some((_, letter)) = LATEX_LETTERS.iter().find(|(n, _)| *n == name) {
+        out.push_str(letter);
+    }
+    // Unknown commands (`\emph`, `\textit`) vanish; their braced argument
+    // is decoded as ordinary text by the caller.
+    next
+}
+
+/// The decoded argument of an accent command: a braced group, another
+/// command (`\'\i`), or the next character.
+fn latex_argument(chars: &[char], i: usize) -> (String, usize) {
+    match chars.get(i) {
+        Some('{') => {
+            let mut depth = 0usize;
+            let mut end = i;
+            while end < chars.len() {
+                match chars[end] {
+                    '{' => depth += 1,
+                    '}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+                end += 1;
+            }
+            let inner: String = chars[i + 1..end.min(chars.len())].iter().collect();
+            (decode_latex(&inner), (end + 1).min(chars.len()))
+        }
+        Some('\\') => {
+            let mut arg = String::new();
+            let next = decode_latex_command(chars, i + 1, &mut arg);
+            (arg, next)
+        }
+        Some(&c) => (c.to_string(), i + 1),
+        None => (String::new(), i),
+    }
+}
+
+/// Apply an accent to the first character of `arg` (dotless `ı`/`ȷ` take
+/// accents as `i`/`j`).
+fn push_accented(out: &mut String, arg: &str, accent: &(char, char, &str, &str)) {
+    let (_, mark, bases, composed) = *accent;
+    let mut it = arg.chars();
+    let Some(first) = it.next() else {
+        return;
+    };
+    let base = match first {
+        'ı' => 'i',
+        'ȷ' => 'j',
+        c => c,
+    };
+    match bases.chars().position(|b| b == base) {
+        Some(k) => out.extend(composed.chars().nth(k)),
+        None => {
+            out.push(base);
+            out.push(mark);
+        }
+    }
+    out.extend(it);
+}
+
+/// Escape the characters BibTeX treats specially inside a braced value.
+fn encode_latex(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' | '%' | '#' | '_' | '$' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Brace every word of an encoded title that carries a capital, so BibTeX
+/// styles that lower-case titles keep `{DNA}` and `{Darwin}` as written. The
+/// title's leading capital needs no braces: styles keep its case. Import
+/// drops the braces again, so titles round-trip.
+fn protect_case(encoded: &str) -> String {
+    encoded
+        .split(' ')
+        .enumerate()
+        .map(|(i, word)| {
+            let skip = usize::from(i == 0);
+            if word.chars().skip(skip).any(char::is_uppercase) {
+                format!("{{{word}}}")
+            } else {
+                word.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// ── BibTeX ──────────────────────────────────────────────────────────────────
+
+/// BibTeX's predefined month macros.
+const BIBTEX_MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// Cursor over a BibTeX source.
+struct BibtexScanner<'a> {
+    chars: &'a [char],
+    pos: usize,
+    macros: &'a BTreeMap<String, String>,
+}
+
+impl BibtexScanner<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn ident(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | ':' | '.' | '+' | '/'))
+        {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// A `{…}` group with balanced inner braces, returned without the outer
+    /// pair. The cursor is on the opening brace.
+    fn braced(&mut self) -> String {
+        let start = self.pos + 1;
+        let mut depth = 0usize;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return self.chars[start..self.pos - 1].iter().collect();
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.chars[start.min(self.pos)..].iter().collect()
+    }
+
+    /// A `"…"` string (quotes inside braces do not end it).
+    fn quoted(&mut self) -> String {
+        self.pos += 1;
+        let start = self.pos;
+        let mut depth = 0usize;
+        while let Some(c) = self.peek() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth = depth.saturating_sub(1),
+                '"' if depth == 0 => {
+                    let s = self.chars[start..self.pos].iter().collect();
+                    self.pos += 1;
+                    return s;
+                }
+                _ => {}
+            }
+            self.pos += 1;
+        }
+        self.chars[start..].iter().collect()
+    }
+
+    /// A field value: `#`-concatenated braced groups, strings, numbers and
+    /// macro names (expanded from `@string` definitions and month macros).
+    fn value(&mut self) -> String {
+        let mut out = String::new();
+        loop {
+            self.skip_ws();
+            match self.peek() {
+                Some('{') => out.push_str(&self.braced()),
+                Some('"') => out.push_str(&self.quoted()),
+                Some(_) => {
+                    let word = self.ident();
+                    if word.is_empty() {
+                        break;
+                    }
+                    let lower = word.to_ascii_lowercase();
+                    match self.macros.get(&lower) {
+                        Some(v) => out.push_str(v),
+                        None => out.push_str(&word),
+                    }
+                }
+                None => break,
+            }
+            self.skip_ws();
+            if self.peek() == Some('#') {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        out
+    }
+
+    /// `name = value` pairs up to the entry's closing delimiter.
+    fn fields(&mut self, close: char) -> Vec<(String, String)> {
+        let mut fields = Vec::new();
+        loop {
+            self.skip_ws();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(c) if c == close => {
+                    self.pos += 1;
+                    break;
+                }
+                None => break,
+                Some(_) => {
+                    let name = self.ident().to_ascii_lowercase();
+                    self.skip_ws();
+                    if name.is_empty() || self.peek() != Some('=') {
+                        // Malformed: resume at the next entry.
+                        while self.peek().is_some_and(|c| c != '@') {
+                            self.pos += 1;
+                        }
+                        break;
+                    }
+                    self.pos += 1;
+                    let value = self.value();
+                    fields.push((name, value));
+                }
+            }
+        }
+        fields
+    }
+}
+
+/// Parse BibTeX / BibLaTeX entries into references. `@string` macros are
+/// expanded, `@comment` and `@preamble` skipped, and text outside entries
+/// ignored. Values are LaTeX-decoded; `date`/`month` are normalized.
+pub fn from_bibtex(src: &str) -> Vec<Reference> {
+    let mut macros: BTreeMap<String, String> = BIBTEX_MONTHS
+        .iter()
+        .map(|m| (m.to_string(), m.to_string()))
+        .collect();
+    let mut refs = Vec::new();
+    let mut pos = 0;
+    let chars: Vec<char> = src.chars().collect();
+    while let Some(at) = chars[pos.min(chars.len())..].iter().position(|&c| c == '@') {
+        let mut sc = BibtexScanner {
+            chars: &chars,
+            pos: pos + at + 1,
+            macros: &macros,
+        };
+        let entry_type = sc.ident().to_ascii_lowercase();
+        sc.skip_ws();
+        let close = match sc.peek() {
+            Some('{') => '}',
+            Some('(') => ')',
+            _ => {
+                pos = sc.pos;
+                continue;
+            }
+        };
+        sc.pos += 1;
+        match entry_type.as_str() {
+            "comment" | "preamble" => {
+                sc.pos -= 1;
+                if close == '}' {
+                    sc.braced();
+                } else {
+                    while sc.peek().is_some_and(|c| c != ')') {
+                        sc.pos += 1;
+                    }
+                }
+                pos = sc.pos;
+            }
+            "string" => {
+                let defs = sc.fields(close);
+                pos = sc.pos;
+                macros.extend(defs);
+            }
+            _ => {
+                sc.skip_ws();
+                let start = sc.pos;
+                while sc
+                    .peek()
+                    .is_some_and(|c| c != ',' && c != close && !c.is_whitespace())
+                {
+                    sc.pos += 1;
+                }
+                let key: String = sc.chars[start..sc.pos].iter().collect();
+                let fields = sc.fields(close);
+                pos = sc.pos;
+                refs.push(bibtex_reference(&entry_type, key, fields));
+            }
+        }
+    }
+    fill_missing_keys(&mut refs);
+    refs
+}
+
+/// Build a reference from one BibTeX entry's raw fields.
+fn bibtex_reference(entry_type: &str, key: String, fields: Vec<(String, String)>) -> Reference {
+    let mut r = Reference {
+        key,
+        ..Reference::default()
+    };
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    };
+    let text = |name: &str| field(name).map(decode_latex).filter(|v| !v.is_empty());
+    // URLs and DOIs are verbatim apart from escaped specials.
+    let verbatim = |name: &str| {
+        field(name)
+            .map(|v| {
+                v.trim()
+                    .replace("\\_", "_")
+                    .replace("\\%", "%")
+                    .replace("\\&", "&")
+                    .replace("\\#", "#")
+            })
+            .filter(|v| !v.is_empty())
+    };
+
+    r.ref_type = match entry_type {
+        "book" | "mvbook" | "booklet" => RefType::Book,
+        "incollection" | "inbook" | "inreference" => RefType::Chapter,
+        "online" | "electronic" | "www" | "webpage" => RefType::Web,
+        "techreport" | "report" => RefType::Report,
+        "inproceedings" | "conference" | "proceedings" => RefType::Conference,
+        "misc" if field("url").is_some() => RefType::Web,
+        _ => RefType::Article,
+    };
+    r.authors = field("author").map(bibtex_names).unwrap_or_default();
+    r.editors = field("editor").map(bibtex_names).unwrap_or_default();
+    r.title = text("title");
+    r.container = ["journal", "journaltitle", "booktitle", "howpublished"]
+        .iter()
+        .find_map(|f| text(f));
+    r.publisher = ["publisher", "institution", "organization", "school"]
+        .iter()
+        .find_map(|f| text(f));
+    let (date_year, date_month, _) = field("date").map(normalize_date).unwrap_or_default();
+    r.year = text("year").or(date_year);
+    r.month = field("month").and_then(normalize_month).or(date_month);
+    r.volume = text("volume");
+    r.issue = text("number").or_else(|| text("issue"));
+    r.pages = text("pages").map(|p| plain_pages(&p));
+    r.url = verbatim("url");
+    r.doi = verbatim("doi").map(|d| bare_doi(&d));
+    r.accessed = text("urldate").map(|d| iso_date(&d));
+    r.edition = text("edition");
+    r
+}
+
+/// Split a BibTeX name list on top-level ` and `, dropping `others`.
+fn bibtex_names(list: &str) -> Vec<Author> {
+    let mut names = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let bytes = list.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' => depth += 1,
+            b'}' => depth = depth.saturating_sub(1),
+            b' ' | b'\t' | b'\n' if depth == 0 => {
+                let rest = &list[i + 1..];
+                let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                if rest[..word_end].eq_ignore_ascii_case("and") && word_end < rest.len() {
+                    names.push(&list[start..i]);
+                    i += 1 + word_end;
+                    start = i;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    names.push(&list[start..]);
+    names
+        .into_iter()
+        .map(str::trim)
+        .filter(|n| !n.is_empty() && *n != "others")
+        .map(bibtex_name)
+        .filter(|a| !a.family.is_empty())
+        .collect()
+}
+
+/// Split `s` on `sep` outside braces.
+fn split_top_level(s: &str, sep: impl Fn(char) -> bool) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            c if depth == 0 && sep(c) => {
+                parts.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// One BibTeX name: `{Organisation}`, `Last, First`, `Last, Jr, First`, or
+/// `First von Last` (lower-case particles join the family name).
+fn bibtex_name(name: &str) -> Author {
+    let commas: Vec<&str> = split_top_level(name, |c| c == ',')
+        .into_iter()
+        .map(str::trim)
+        .collect();
+    if commas.len() >= 2 {
+        return Author {
+            family: decode_latex(&commas[..commas.len() - 1].join(", ")),
+            given: opt(&decode_latex(commas[commas.len() - 1])),
+        };
+    }
+    let words: Vec<&str> = split_top_level(name, char::is_whitespace)
+        .into_iter()
+        .filter(|w| !w.is_empty())
+        .collect();
+    if words.len() <= 1 {
+        return Author {
+            family: decode_latex(name),
+            given: None,
+        };
+    }
+    let last = words.len() - 1;
+    let von = words[..last]
+        .iter()
+        .skip(1)
+        .position(|w| w.starts_with(|c: char| c.is_lowercase()))
+        .map_or(last, |k| k + 1);
+    Author {
+        family: decode_latex(&words[von..].join(" ")),
+        given: opt(&decode_latex(&words[..von].join(" "))),
+    }
+}
+
+/// Serialize references as BibTeX entries.
+pub fn to_bibtex(refs: &[Reference]) -> String {
+    let mut out = String::new();
+    for r in refs {
+        let (entry_type, container_field, publisher_field) = match r.ref_type {
+            RefType::Article => ("article", "journal", "publisher"),
+            RefType::Book => ("book", "howpublished", "publisher"),
+            RefType::Chapter => ("incollection", "booktitle", "publisher"),
+            RefType::Web => ("online", "howpublished", "organization"),
+            RefType::Report => ("techreport", "howpublished", "institution"),
+            RefType::Conference => ("inproceedings", "booktitle", "publisher"),
+        };
+        let mut fields: Vec<(&str, String)> = Vec::new();
+        let names = |list: &[Author]| {
+            list.iter()
+                .map(|a| match &a.given {
+                    Some(g) => format!("{}, {}", encode_latex(&a.family), encode_latex(g)),
+                    // Braces keep a multi-word organisation name whole.
+                    None if a.family.contains(' ') => format!("{{{}}}", encode_latex(&a.family)),
+                    None => encode_latex(&a.family),
+                })
+                .collect::<Vec<_>>()
+                .join(" and ")
+        };
+        if !r.authors.is_empty() {
+            fields.push(("author", names(&r.authors)));
+        }
+        if !r.editors.is_empty() {
+            fields.push(("editor", names(&r.editors)));
+        }
+        let mut text = |name, v: &Option<String>| {
+            if let Some(v) = v {
+                fields.push((name, encode_latex(v)));
+            }
+        };
+        text("title", &r.title);
+        text(container_field, &r.container);
+        text(publisher_field, &r.publisher);
+        text("year", &r.year);
+        text("volume", &r.volume);
+        text("number", &r.issue);
+        text("pages", &r.pages.as_ref().map(|p| p.replace('-', "--")));
+        text("edition", &r.edition);
+        text("urldate", &r.accessed);
+        for (name, v) in [("url", &r.url), ("doi", &r.doi)] {
+            if let Some(v) = v {
+                fields.push((name, v.clone()));
+            }
+        }
+        out.push_str(&format!("@{entry_type}{{{},\n", r.key));
+        for (name, v) in &fields {
+            // Styles change the case of titles and book titles only.
+            if matches!(*name, "title" | "booktitle") {
+                out.push_str(&format!("  {name} = {{{}}},\n", protect_case(v)));
+            } else {
+                out.push_str(&format!("  {name} = {{{v}}},\n"));
+            }
+        }
+        if let Some(m) = r.month.filter(|m| (1..=12).contains(m)) {
+            out.push_str(&format!("  month = {},\n", BIBTEX_MONTHS[m as usize - 1]));
+        }
+        out.push_str("}\n\n");
+    }
+    out.truncate(out.trim_end().len());
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+// ── CSL-JSON ────────────────────────────────────────────────────────────────
+
+/// Parse CSL-JSON (an array of items, or a single item) into references.
+/// Invalid JSON yields no references.
+pub fn from_csl_json(src: &str) -> Vec<Reference> {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(src) else {
+        return Vec::new();
+    };
+    let items = match value {
+        serde_json::Value::Array(items) => items,
+        item @ serde_json::Value::Object(_) => vec![item],
+        _ => Vec::new(),
+    };
+    let mut refs: Vec<Reference> = items
+        .iter()
+        .filter(|i| i.is_object())
+        .map(csl_reference)
+        .collect();
+    fill_missing_keys(&mut refs);
+    refs
+}
+
+/// A CSL scalar (string or number) as trimmed text.
+fn csl_text(item: &serde_json::Value, name: &str) -> Option<String> {
+    match item.get(name)? {
+        serde_json::Value::String(s) => opt(s),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// A CSL date object (`date-parts`, `raw` or `literal`) as year, month, day.
+fn csl_date(item: &serde_json::Value, name: &str) -> (Option<String>, Option<u8>, Option<u8>) {
+    let Some(date) = item.get(name) else {
+        return (None, None, None);
+    };
+    if let Some(parts) = date
+        .get("date-parts")
+        .and_then(|p| p.get(0))
+        .and_then(|p| p.as_array())
+    {
+        let part = |k: usize| {
+            parts.get(k).and_then(|v| match v {
+                serde_json::Value::Number(n) => Some(n.to_string()),
+                serde_json::Value::String(s) => opt(s),
+                _ => None,
+            })
+        };
+        let year = part(0);
+        let month = part(1).and_then(|m| normalize_month(&m));
+        let day = part(2)
+            .and_then(|d| d.parse::<u8>().ok())
+            .filter(|d| (1..=31).contains(d));
+        if year.is_some() {
+            return (year, month, day);
+        }
+    }
+    ["raw", "literal"]
+        .iter()
+        .find_map(|k| date.get(k).and_then(|v| v.as_str()))
+        .map(|s| match normalize_date(s) {
+            (None, ..) => (opt(s), None, None),
+            parts => parts,
+        })
+        .unwrap_or_default()
+}
+
+/// CSL name objects (`family`/`given`, particles, or `literal`).
+fn csl_names(item: &serde_json::Value, name: &str) -> Vec<Author> {
+    let Some(list) = item.get(name).and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+    list.iter()
+        .filter_map(|n| {
+            if let Some(literal) = csl_text(n, "literal") {
+                return Some(Author {
+                    family: literal,
+                    given: None,
+                });
+            }
+            let family = csl_text(n, "family")?;
+            let family = match csl_text(n, "non-dropping-particle") {
+                Some(particle) => format!("{particle} {family}"),
+                None => family,
+            };
+            let given = match (csl_text(n, "given"), csl_text(n, "dropping-particle")) {
+                (Some(g), Some(p)) => Some(format!("{g} {p}")),
+                (g, _) => g,
+            };
+            Some(Author { family, given })
+        })
+        .collect()
+}
+
+fn csl_reference(item: &serde_json::Value) -> Reference {
+    let (year, month, _) = csl_date(item, "issued");
+    let accessed = match csl_date(item, "accessed") {
+        (Some(y), Some(m), Some(d)) => Some(format!("{y}-{m:02}-{d:02}")),
+        (Some(y), Some(m), None) => Some(format!("{y}-{m:02}")),
+        (y, ..) => y,
+    };
+    Reference {
+        key: csl_text(item, "id").unwrap_or_default(),
+        ref_type: match csl_text(item, "type").as_deref() {
+            Some("book") => RefType::Book,
+            Some("chapter" | "entry-encyclopedia" | "entry-dictionary") => RefType::Chapter,
+            Some("webpage" | "post" | "post-weblog") => RefType::Web,
+            Some("report") => RefType::Report,
+            Some("paper-conference") => RefType::Conference,
+            _ => RefType::Article,
+        },
+        authors: csl_names(item, "author"),
+        editors: csl_names(item, "editor"),
+        title: csl_text(item, "title"),
+        container: csl_text(item, "container-title"),
+        publisher: csl_text(item, "publisher"),
+        year,
+        month,
+        volume: csl_text(item, "volume"),
+        issue: csl_text(item, "issue"),
+        pages: csl_text(item, "page").map(|p| plain_pages(&p)),
+        url: csl_text(item, "URL"),
+        doi: csl_text(item, "DOI").map(|d| bare_doi(&d)),
+        accessed,
+        edition: csl_text(item, "edition"),
+    }
+}
+
+/// Serialize references as a CSL-JSON array (pretty-printed).
+pub fn to_csl_json(refs: &[Reference]) -> String {
+    use serde_json::{Map, Value, json};
+    let names = |list: &[Author]| {
+        Value::Array(
+            list.iter()
+                .map(|a| match &a.given {
+                    Some(g) => json!({ "family": a.family, "given": g }),
+                    None => json!({ "literal": a.family }),
+                })
+                .collect(),
+        )
+    };
+    let items: Vec<Value> = refs
+        .iter()
+        .map(|r| {
+            let mut item = Map::new();
+            item.insert("id".into(), json!(r.key));
+            let csl_type = match r.ref_type {
+                RefType::Article => "article-journal",
+                RefType::Book => "book",
+                RefType::Chapter => "chapter",
+                RefType::Web => "webpage",
+                RefType::Report => "report",
+                RefType::Conference => "paper-conference",
+            };
+            item.insert("type".into(), json!(csl_type));
+            if !r.authors.is_empty() {
+                item.insert("author".into(), names(&r.authors));
+            }
+            if !r.editors.is_empty() {
+                item.insert("editor".into(), names(&r.editors));
+            }
+            for (name, v) in [
+                ("title", &r.title),
+                ("container-title", &r.container),
+                ("publisher", &r.publisher),
+                ("volume", &r.volume),
+                ("issue", &r.issue),
+                ("page", &r.pages),
+                ("URL", &r.url),
+                ("DOI", &r.doi),
+                ("edition", &r.edition),
+            ] {
+                if let Some(v) = v {
+                    item.insert(name.into(), json!(v));
+                }
+            }
+            if let Some(year) = &r.year {
+                let issued = match year.parse::<i64>() {
+                    Ok(y) => match r.month {
+                        Some(m) => json!({ "date-parts": [[y, m]] }),
+                        None => json!({ "date-parts": [[y]] }),
+                    },
+                    // `n.d.`, `2020a`: keep the text as authored.
+                    Err(_) => json!({ "literal": year }),
+                };
+                item.insert("issued".into(), issued);
+            }
+            if let Some(accessed) = &r.accessed {
+                let date = match normalize_date(accessed) {
+                    (Some(y), Some(m), Some(d)) if y.parse::<i64>().is_ok() => {
+                        json!({ "date-parts": [[y.parse::<i64>().unwrap_or(0), m, d]] })
+                    }
+                    _ => json!({ "raw": accessed }),
+                };
+                item.insert("accessed".into(), date);
+            }
+            Value::Object(item)
+        })
+        .collect();
+    serde_json::to_string_pretty(&items).unwrap_or_else(|_| "[]".into())
+}
+
+// ── RIS ─────────────────────────────────────────────────────────────────────
+
+/// Parse RIS records (`TY  - ` … `ER  - `) into references.
+pub fn from_ris(src: &str) -> Vec<Reference> {
+    let mut refs = Vec::new();
+    let mut tags: Vec<(String, String)> = Vec::new();
+    let mut open = false;
+    for raw in src.lines() {
+        let line = raw.trim_end();
+        let Some((tag, value)) = line
+            .get(..2)
+            .filter(|_| line.get(2..5).is_some_and(|sep| sep.starts_with("  -")))
+            .map(|tag| (tag, line.get(5..).unwrap_or("").trim()))
+        else {
+            continue;
+        };
+        match tag {
+            "TY" => {
+                if open {
+                    refs.push(ris_reference(&tags));
+                }
+                tags.clear();
+                tags.push((tag.to_string(), value.to_string()));
+                open = true;
+            }
+            "ER" => {
+                if open {
+                    refs.push(ris_reference(&tags));
+                }
+                tags.clear();
+                open = false;
+            }
+            _ if open => tags.push((tag.to_string(), value.to_string())),
+            _ => {}
+        }
+    }
+    if open {
+        refs.push(ris_reference(&tags));
+    }
+    fill_missing_keys(&mut refs);
+    refs
+}
+
+fn ris_reference(tags: &[(String, String)]) -> Reference {
+    let all = |names: &[&str]| -> Vec<&str> {
+        tags.iter()
+            .filter(|(t, v)| names.contains(&t.as_str()) && !v.is_empty())
+            .map(|(_, v)| v.as_str())
+            .collect()
+    };
+    let first = |names: &[&str]| all(names).first().map(|v| v.to_string());
+    let ref_type = match first(&["TY"]).as_deref() {
+        Some("BOOK" | "EBOOK") => RefType::Book,
+        Some("CHAP" | "ECHAP") => RefType::Chapter,
+        Some("ELEC" | "WEB" | "BLOG" | "ICOMM") => RefType::Web,
+        Some("RPRT") => RefType::Report,
+        Some("CONF" | "CPAPER") => RefType::Conference,
+        _ => RefType::Article,
+    };
+    let mut title = first(&["TI", "T1"]);
+    let mut container = first(&["T2", "JF", "JO", "JA", "BT"]);
+    // A book's `BT` is its own title when there is no `TI`.
+    if ref_type == RefType::Book && title.is_none() {
+        title = container.take();
+    }
+    let (py_year, py_month, _) = first(&["PY", "Y1"])
+        .map(|d| normalize_date(&d))
+        .unwrap_or_default();
+    let (da_year, da_month, _) = first(&["DA"])
+        .map(|d| normalize_date(&d))
+        .unwrap_or_default();
+    let pages = match (first(&["SP"]), first(&["EP"])) {
+        (Some(sp), Some(ep)) => Some(format!("{sp}-{ep}")),
+        (sp, _) => sp.map(|p| plain_pages(&p)),
+    };
+    Reference {
+        key: first(&["ID"]).unwrap_or_default(),
+        ref_type,
+        authors: all(&["AU", "A1"]).into_iter().map(ris_author).collect(),
+        editors: all(&["ED", "A2"]).into_iter().map(ris_author).collect(),
+        title,
+        container,
+        publisher: first(&["PB"]),
+        year: py_year.or(da_year),
+        month: py_month.or(da_month),
+        volume: first(&["VL"]),
+        issue: first(&["IS"]),
+        pages,
+        url: first(&["UR"]),
+        doi: first(&["DO"]).map(|d| bare_doi(&d)),
+        accessed: first(&["Y2"]).map(|d| iso_date(&d)),
+        edition: first(&["ET"]),
+    }
+}
+
+/// RIS names are always `Last, First`; a name without a comma is an
+/// organisation and stays whole.
+fn ris_author(s: &str) -> Author {
+    if s.contains(',') {
+        parse_author(s)
+    } else {
+        Author {
+            family: s.trim().to_string(),
+            given: None,
+        }
+    }
+}
+
+/// Serialize references as RIS records.
+pub fn to_ris(refs: &[Reference]) -> String {
+    let mut out = String::new();
+    for r in refs {
+        let ty = match r.ref_type {
+            RefType::Article => "JOUR",
+            RefType::Book => "BOOK",
+            RefType::Chapter => "CHAP",
+            RefType::Web => "ELEC",
+            RefType::Report => "RPRT",
+            RefType::Conference => "CPAPER",
+        };
+        let mut line = |tag: &str, v: &str| out.push_str(&format!("{tag}  - {v}\n"));
+        line("TY", ty);
+        line("ID", &r.key);
+        for a in &r.authors {
+            line("AU", &inverted(a));
+        }
+        for a in &r.editors {
+            line("ED", &inverted(a));
+        }
+        for (tag, v) in [
+            ("TI", &r.title),
+            ("T2", &r.container),
+            ("PB", &r.publisher),
+            ("VL", &r.volume),
+            ("IS", &r.issue),
+        ] {
+            if let Some(v) = v {
+                line(tag, v);
+            }
+        }
+        if let Some(year) = &r.year {
+            match r.month {
+                Some(m) => line("PY", &format!("{year}/{m:02}//")),
+                None => line("PY", year),
+            }
+        }
+        if let Some(pages) = &r.pages {
+            match pages.split_once('-') {
+                Some((sp, ep)) => {
+                    line("SP", sp.trim());
+                    line("EP", ep.trim());
+                }
+                None => line("SP", pages),
+            }
+        }
+        for (tag, v) in [("UR", &r.url), ("DO", &r.doi), ("ET", &r.edition)] {
+            if let Some(v) = v {
+                line(tag, v);
+            }
+        }
+        if let Some(accessed) = &r.accessed {
+            line("Y2", &accessed.replace('-', "/"));
+        }
+        line("ER", "");
+        out.push('\n');
+    }
+    out.truncate(out.trim_end().len());
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
 // ───────────────────────────────────────────────────────────────────────────
 // Document context: collect references + citation order + numbering
 // ───────────────────────────────────────────────────────────────────────────
@@ -733,32 +1981,115 @@ pub fn reference_list(refs: &[Reference], style: Format) -> Vec<String> {
 pub struct CiteContext {
     /// Active citation style.
     pub style: Format,
-    /// All references defined via `::cite`, in definition order.
+    /// All references defined via `::cite` or imported via
+    /// `::bibliography-data`, in definition order.
     pub references: Vec<Reference>,
     /// Key → citation number (cited keys first in citation order, then any
     /// defined-but-uncited references in definition order). Used by IEEE/ACM.
     pub numbers: BTreeMap<String, usize>,
+    /// CSL style from front matter `csl:`, bound to `references`. When set it
+    /// replaces `style` for in-text citations and the default bibliography.
+    pub csl: Option<Arc<CslProcessor>>,
+    /// The document's footnotes (and, under a note style, citation notes).
+    pub notes: Notes,
+    /// The document's cross-reference targets.
+    pub xrefs: Xrefs,
 }
 
-/// Child-block accessor for the container variants citations can nest inside.
-fn children_of(b: &Block) -> Option<&[Block]> {
-    match b {
-        Block::Page { children, .. }
-        | Block::Section { children, .. }
-        | Block::Slide { children, .. }
-        | Block::App { children, .. }
-        | Block::AppShell { children, .. }
-        | Block::Sidebar { children, .. }
-        | Block::Panel { children, .. }
-        | Block::TabContent { children, .. }
-        | Block::Drawer { children, .. }
-        | Block::Modal { children, .. } => Some(children),
-        _ => None,
+/// A formatted reference list, ready for a renderer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BibliographyList {
+    /// Section heading (`References`, `Works Cited`, `Bibliography`).
+    pub heading: String,
+    /// Built-in numbered style: entries start with `[n] ` and read as an
+    /// ordered list. CSL styles render their own numbering, so are never
+    /// `numbered`.
+    pub numbered: bool,
+    /// `(key, entry)` pairs in display order; entries use markdown italics.
+    pub entries: Vec<(String, String)>,
+    /// The built-in style used, or `None` for the document's CSL style.
+    pub style: Option<Format>,
+}
+
+impl CiteContext {
+    /// Bind a CSL style to this context's references and numbering.
+    pub fn with_csl(mut self, style: CslStyle) -> CiteContext {
+        self.csl = Some(Arc::new(CslProcessor::new(
+            style,
+            &self.references,
+            &self.numbers,
+        )));
+        self
+    }
+
+    /// Format an inline citation group with the CSL style if one is bound,
+    /// else the built-in style. Note styles produce the note text.
+    pub fn in_text(&self, cite: &CiteRef) -> String {
+        match &self.csl {
+            Some(p) => p.cite(cite),
+            None => format_in_text(&self.references, cite, self.style, &self.numbers),
+        }
+    }
+
+    /// Inline citations become footnotes: a note-class CSL style is bound
+    /// and no note body is being rendered (notes cite in line).
+    pub fn note_citations(&self) -> bool {
+        self.csl.as_ref().is_some_and(|p| p.style().is_note_style()) && !self.notes.in_note()
+    }
+
+    /// In-text citations are set as superscripts (numbered built-in styles,
+    /// or a CSL style with `vertical-align="sup"`).
+    pub fn superscript(&self) -> bool {
+        match &self.csl {
+            Some(p) => p.style().superscript_citations(),
+            None => is_numbered(self.style),
+        }
+    }
+
+    /// Format one reference as a standalone entry (no number).
+    pub fn format_entry(&self, r: &Reference) -> String {
+        match &self.csl {
+            Some(p) => p.entry(r),
+            None => format_reference(r, self.style, None),
+        }
+    }
+
+    /// The reference list. `style_override` (a `::bibliography[style=…]`)
+    /// selects a built-in style over both `style` and the CSL style; entries
+    /// then follow definition order, numbered styles renumbering from 1.
+    pub fn bibliography(&self, style_override: Option<Format>) -> BibliographyList {
+        if style_override.is_none()
+            && let Some(p) = &self.csl
+        {
+            let heading = if p.style().is_note_style() {
+                "Bibliography"
+            } else {
+                "References"
+            };
+            return BibliographyList {
+                heading: heading.to_string(),
+                numbered: false,
+                entries: p.bibliography(),
+                style: None,
+            };
+        }
+        let style = style_override.unwrap_or(self.style);
+        let refs = if style_override.is_some() {
+            self.references.clone()
+        } else {
+            ordered_references(self)
+        };
+        BibliographyList {
+            heading: bibliography_heading(style).to_string(),
+            numbered: is_numbered(style),
+            entries: reference_list_keyed(&refs, style),
+            style: Some(style),
+        }
     }
 }
 
 /// Prose text of a block that may contain inline `[@key]` citations.
-fn cite_text_of(b: &Block) -> Option<&str> {
+pub(crate) fn cite_text_of(b: &Block) -> Option<&str> {
     match b {
         Block::Markdown { content, .. }
         | Block::Callout { content, .. }
@@ -772,17 +2103,19 @@ fn cite_text_of(b: &Block) -> Option<&str> {
 
 fn collect_refs_rec(blocks: &[Block], out: &mut Vec<Reference>) {
     for b in blocks {
-        if let Block::Cite { reference, .. } = b {
-            out.push(reference.clone());
+        match b {
+            Block::Cite { reference, .. } => out.push(reference.clone()),
+            Block::BibliographyData { references, .. } => out.extend(references.iter().cloned()),
+            _ => {}
         }
-        if let Some(children) = children_of(b) {
+        if let Some(children) = b.children() {
             collect_refs_rec(children, out);
         }
     }
 }
 
-/// Collect every `::cite`-defined reference in document order (recursing into
-/// container blocks).
+/// Collect every `::cite`-defined and `::bibliography-data`-imported reference
+/// in document order (recursing into container blocks).
 pub fn collect_references(blocks: &[Block]) -> Vec<Reference> {
     let mut out = Vec::new();
     collect_refs_rec(blocks, &mut out);
@@ -800,7 +2133,7 @@ fn collect_keys_rec(blocks: &[Block], order: &mut Vec<String>, seen: &mut BTreeS
                 }
             }
         }
-        if let Some(children) = children_of(b) {
+        if let Some(children) = b.children() {
             collect_keys_rec(children, order, seen);
         }
     }
@@ -843,6 +2176,25 @@ pub fn build_context(blocks: &[Block], format: Option<Format>) -> CiteContext {
         style: active_style(format),
         references,
         numbers,
+        csl: None,
+        notes: Notes::collect(blocks),
+        xrefs: Xrefs::collect(blocks),
+    }
+}
+
+/// Build the [`CiteContext`] for a whole document: its blocks, front matter
+/// `format:`, and front matter `csl:` style. A `csl:` style that does not
+/// parse is ignored here (lint rule L043 reports it) so the document still
+/// renders with the built-in style.
+pub fn document_context(doc: &SurfDoc) -> CiteContext {
+    let fm = doc.front_matter.as_ref();
+    let ctx = build_context(&doc.blocks, fm.and_then(|fm| fm.format));
+    match fm
+        .and_then(|fm| fm.csl.as_deref())
+        .and_then(|xml| CslStyle::parse(xml).ok())
+    {
+        Some(style) => ctx.with_csl(style),
+        None => ctx,
     }
 }
 
@@ -892,8 +2244,10 @@ pub fn with_active<R>(f: impl FnOnce(Option<&CiteContext>) -> R) -> R {
 }
 
 /// Replace inline `[@key]` citations in `text` with their formatted in-text
-/// strings using the ambient context. No-op when there is no context, no
-/// references, or no cites. Used by text-oriented renderers (markdown/native).
+/// strings using the ambient context; under a note style, with `[^@n]`
+/// references to new citation notes (see [`crate::footnote`]). No-op when
+/// there is no context, no references, or no cites. Used by text-oriented
+/// renderers (markdown/native).
 pub fn substitute_text_cites(text: &str) -> String {
     with_active(|ctx| match ctx {
         Some(ctx) if !ctx.references.is_empty() => {
@@ -905,7 +2259,12 @@ pub fn substitute_text_cites(text: &str) -> String {
             let mut last = 0;
             for (s, e, cr) in cites {
                 out.push_str(&text[last..s]);
-                out.push_str(&format_in_text(&ctx.references, &cr, ctx.style, &ctx.numbers));
+                if ctx.note_citations() {
+                    let label = ctx.notes.add(ctx.in_text(&cr));
+                    out.push_str(&format!("[^{label}]"));
+                } else {
+                    out.push_str(&ctx.in_text(&cr));
+                }
                 last = e;
             }
             out.push_str(&text[last..]);
@@ -1120,4 +2479,240 @@ mod tests {
         assert_eq!(active_style(None), Format::Apa);
         assert_eq!(active_style(Some(Format::Ieee)), Format::Ieee);
     }
+
+    // ── Import / export ─────────────────────────────────────────────────────
+
+    #[test]
+    fn latex_accent_tables_align() {
+        for (cmd, _, bases, composed) in LATEX_ACCENTS {
+            assert_eq!(bases.chars().count(), composed.chars().count(), "\\{cmd}");
+        }
+    }
+
+    #[test]
+    fn decode_latex_accents_and_markup() {
+        assert_eq!(decode_latex(r#"G{\"o}del"#), "Gödel");
+        assert_eq!(decode_latex(r"Erd\H{o}s"), "Erdős");
+        assert_eq!(decode_latex(r"{\'E}mile Durkheim"), "Émile Durkheim");
+        assert_eq!(decode_latex(r"Fran\c{c}ois"), "François");
+        assert_eq!(decode_latex(r"Dvo\v{r}\'ak"), "Dvořák");
+        assert_eq!(decode_latex(r"Bl{\aa}b{\ae}r \ss"), "Blåbær ß");
+        assert_eq!(decode_latex(r#"Na\"{\i}ve"#), "Naïve");
+        assert_eq!(decode_latex(r"\d{a}"), "a\u{323}");
+        assert_eq!(
+            decode_latex(r"R\&D: \emph{Deep} {Learning}"),
+            "R&D: Deep Learning"
+        );
+        assert_eq!(decode_latex("pp.~1--10 --- done"), "pp. 1–10 — done");
+    }
+
+    #[test]
+    fn month_and_date_normalization() {
+        assert_eq!(normalize_month("mar"), Some(3));
+        assert_eq!(normalize_month("March"), Some(3));
+        assert_eq!(normalize_month("Sept."), Some(9));
+        assert_eq!(normalize_month("03"), Some(3));
+        assert_eq!(normalize_month("13"), None);
+        assert_eq!(normalize_month("ma"), None);
+        assert_eq!(
+            normalize_date("2020-03-15"),
+            (Some("2020".into()), Some(3), Some(15))
+        );
+        assert_eq!(
+            normalize_date("2020/03//"),
+            (Some("2020".into()), Some(3), None)
+        );
+        assert_eq!(
+            normalize_date("15 Mar 2020"),
+            (Some("2020".into()), Some(3), Some(15))
+        );
+        assert_eq!(normalize_date("n.d."), (None, None, None));
+    }
+
+    #[test]
+    fn bibtex_import() {
+        let src = r#"
+@string{jcai = "Journal of Climate {AI}"}
+@comment{ignored @article{nope, title={x}} }
+
+@Article{smith2020,
+  author  = {Smith, John and M{\"u}ller, J{\"o}rg and {World Health Organization}},
+  title   = {Deep Learning for {Climate} Models},
+  journal = jcai # " Letters",
+  year    = 2020,
+  month   = mar,
+  volume  = "12",
+  number  = {3},
+  pages   = {45--67},
+  doi     = {https://doi.org/10.1000/jcai\_2020},
+}
+
+@inproceedings{garcia2022,
+  author = {David Garcia and Ludwig van Beethoven},
+  title = {Scalable Inference},
+  booktitle = {Proceedings of NeurIPS},
+  date = {2022-12-01},
+}
+
+@misc{,
+  author = {Lee, Carol},
+  title = {Understanding Transformers},
+  year = {2021},
+  url = {https://aiweekly.example/a_b},
+  urldate = {2021-05-01},
+}
+"#;
+        let refs = from_bibtex(src);
+        assert_eq!(refs.len(), 3);
+        let smith = &refs[0];
+        assert_eq!(smith.key, "smith2020");
+        assert_eq!(smith.ref_type, RefType::Article);
+        assert_eq!(smith.authors.len(), 3);
+        assert_eq!(smith.authors[1].family, "Müller");
+        assert_eq!(smith.authors[1].given.as_deref(), Some("Jörg"));
+        assert_eq!(smith.authors[2].family, "World Health Organization");
+        assert_eq!(smith.authors[2].given, None);
+        assert_eq!(
+            smith.title.as_deref(),
+            Some("Deep Learning for Climate Models")
+        );
+        assert_eq!(
+            smith.container.as_deref(),
+            Some("Journal of Climate AI Letters")
+        );
+        assert_eq!(smith.month, Some(3));
+        assert_eq!(smith.pages.as_deref(), Some("45-67"));
+        assert_eq!(smith.doi.as_deref(), Some("10.1000/jcai_2020"));
+
+        let garcia = &refs[1];
+        assert_eq!(garcia.ref_type, RefType::Conference);
+        assert_eq!(garcia.authors[0].family, "Garcia");
+        assert_eq!(garcia.authors[1].family, "van Beethoven");
+        assert_eq!(garcia.authors[1].given.as_deref(), Some("Ludwig"));
+        assert_eq!(
+            (garcia.year.as_deref(), garcia.month),
+            (Some("2022"), Some(12))
+        );
+
+        let lee = &refs[2];
+        assert_eq!(lee.key, "lee2021");
+        assert_eq!(lee.ref_type, RefType::Web);
+        assert_eq!(lee.url.as_deref(), Some("https://aiweekly.example/a_b"));
+        assert_eq!(lee.accessed.as_deref(), Some("2021-05-01"));
+    }
+
+    #[test]
+    fn csl_json_import() {
+        let src = r#"[
+  {"id": "smith2020", "type": "article-journal",
+   "author": [{"family": "Smith", "given": "John"}, {"literal": "OpenAI"}],
+   "title": "Deep Learning", "container-title": "JCAI",
+   "issued": {"date-parts": [[2020, 3]]}, "volume": 12, "page": "45–67",
+   "DOI": "10.1000/x"},
+  {"type": "webpage", "author": [{"family": "Beethoven", "given": "Ludwig",
+   "non-dropping-particle": "van"}], "title": "Page",
+   "issued": {"raw": "2021-05"}, "URL": "https://example.com",
+   "accessed": {"date-parts": [["2021", "5", "1"]]}}
+]"#;
+        let refs = from_csl_json(src);
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].authors[1].family, "OpenAI");
+        assert_eq!(refs[0].volume.as_deref(), Some("12"));
+        assert_eq!(refs[0].pages.as_deref(), Some("45-67"));
+        assert_eq!(refs[0].month, Some(3));
+        assert_eq!(refs[1].key, "vanbeethoven2021");
+        assert_eq!(refs[1].ref_type, RefType::Web);
+        assert_eq!(refs[1].month, Some(5));
+        assert_eq!(refs[1].accessed.as_deref(), Some("2021-05-01"));
+        assert!(from_csl_json("not json").is_empty());
+    }
+
+    #[test]
+    fn ris_import() {
+        let src = "TY  - JOUR\nID  - smith2020\nAU  - Smith, John\nAU  - Doe, Jane\nTI  - Deep Learning\nJO  - JCAI\nPY  - 2020/03//\nVL  - 12\nIS  - 3\nSP  - 45\nEP  - 67\nDO  - 10.1000/x\nER  - \n\nTY  - BOOK\nAU  - Jones, Alice\nBT  - Foundations\nPB  - MIT Press\nPY  - 2019\nER  - \n";
+        let refs = from_ris(src);
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].authors.len(), 2);
+        assert_eq!(refs[0].container.as_deref(), Some("JCAI"));
+        assert_eq!(refs[0].month, Some(3));
+        assert_eq!(refs[0].pages.as_deref(), Some("45-67"));
+        assert_eq!(refs[1].key, "jones2019");
+        assert_eq!(refs[1].ref_type, RefType::Book);
+        assert_eq!(refs[1].title.as_deref(), Some("Foundations"));
+        assert_eq!(refs[1].container, None);
+    }
+
+    #[test]
+    fn generated_keys_are_unique() {
+        let src = "@book{, author={Smith, A}, year={2020}}\n@book{, author={Smith, B}, year={2020}}\n@book{, title={Anon}}";
+        let keys: Vec<String> = from_bibtex(src).into_iter().map(|r| r.key).collect();
+        assert_eq!(keys, ["smith2020", "smith2020a", "ref3"]);
+    }
+
+    #[test]
+    fn exports_round_trip() {
+        let mut refs = fixtures();
+        refs[0].month = Some(3);
+        refs[0].title = Some("R&D at 100% — Müller's #1_model".into());
+        refs.push(Reference {
+            key: "who2023".into(),
+            ref_type: RefType::Report,
+            authors: vec![Author {
+                family: "World Health Organization".into(),
+                given: None,
+            }],
+            title: Some("Annual Report".into()),
+            publisher: Some("WHO Press".into()),
+            year: Some("2023".into()),
+            ..Default::default()
+        });
+        for format in [BibFormat::Bibtex, BibFormat::CslJson, BibFormat::Ris] {
+            let text = export_references(format, &refs);
+            assert_eq!(
+                import_references(format, &text),
+                refs,
+                "{}",
+                format.as_str()
+            );
+            assert_eq!(BibFormat::sniff(&text), Some(format));
+        }
+        let bib = to_bibtex(&refs);
+        assert!(bib.contains("@article{smith2020,"));
+        assert!(bib.contains("  month = mar,"));
+        assert!(bib.contains("  pages = {45--67},"));
+        assert!(bib.contains(r"{R\&D} at 100\% — {Müller's} \#1\_model"));
+        assert!(bib.contains("  author = {{World Health Organization}},"));
+        assert!(to_ris(&refs).contains("PY  - 2020/03//\n"));
+    }
+
+    #[test]
+    fn bibtex_export_protects_capitalised_title_words() {
+        let src = "@incollection{k, title={{DNA} repair in {Escherichia} coli}, \
+                   booktitle={Methods in {RNA} Biology}, publisher={Cold Spring Press}}";
+        let refs = from_bibtex(src);
+        assert_eq!(
+            refs[0].title.as_deref(),
+            Some("DNA repair in Escherichia coli")
+        );
+        let bib = to_bibtex(&refs);
+        assert!(
+            bib.contains("  title = {{DNA} repair in {Escherichia} coli},"),
+            "{bib}"
+        );
+        assert!(
+            bib.contains("  booktitle = {Methods in {RNA} {Biology}},"),
+            "{bib}"
+        );
+        assert!(bib.contains("  publisher = {Cold Spring Press},"), "{bib}");
+        assert_eq!(from_bibtex(&bib), refs);
+    }
+
+    #[test]
+    fn bib_format_lookup() {
+        assert_eq!(BibFormat::parse("BibTeX"), Some(BibFormat::Bibtex));
+        assert_eq!(BibFormat::parse("csl-json"), Some(BibFormat::CslJson));
+        assert_eq!(BibFormat::parse("ris"), Some(BibFormat::Ris));
+        assert_eq!(BibFormat::parse("endnote"), None);
+        assert_eq!(BibFormat::sniff("author: Smith"), None);
+    }
 }
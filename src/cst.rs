@@ -0,0 +1,834 @@
+//! Lossless concrete syntax tree.
+//!
+//! The typed [`Block`] tree keeps only a [`Span`] per block and the parsed
+//! fields, so exact layout (colon counts, attribute spelling and order,
+//! whitespace, blank lines) is gone once a document is resolved. The CST
+//! keeps all of it: every byte of `doc.source` belongs to exactly one leaf
+//! token, so concatenating the leaves in order reproduces the source.
+//!
+//! ```text
+//! Document
+//! ├── FrontMatter
+//! ├── Trivia                      blank lines between blocks
+//! ├── Markdown                    ↔ Block::Markdown
+//! └── Directive                   ↔ any other top-level Block
+//!     ├── Opener
+//!     │   ├── Whitespace? Colons Whitespace? Name
+//!     │   ├── AttrList
+//!     │   │   └── LBracket (Attr (AttrKey Equals? AttrValue?) | Whitespace)* RBracket
+//!     │   └── Whitespace? Text? Whitespace?   same-line trailing content
+//!     ├── Newline
+//!     ├── Body
+//!     │   └── (Text | Directive Newline?)*    ↔ a container's child blocks
+//!     └── Closer
+//!         └── Whitespace? Colons Whitespace?
+//! ```
+//!
+//! Top-level nodes are cut along the parser's own block spans, so each
+//! typed top-level block links to its node ([`Cst::block_node`],
+//! [`Cst::node_for`]). Nested directives follow the container matching of
+//! `blocks.rs` (a container owns the first surplus closer at its depth inside
+//! its parent's body; otherwise it is a leaf). Bodies of literal blocks
+//! (`::code`, `::output`) and fenced code inside bodies are plain `Text`,
+//! bar the strict `::name` openers the parser still reads there.
+//! A container's [`Block::children`] link to its body's nodes in order —
+//! markdown children to `Text` runs, the rest to nested `Directive`s — so
+//! every typed block reaches its node through [`Cst::node_at`].
+//!
+//! Token spans are exact byte ranges into the CRLF-normalised source, so lint
+//! fixes can target an attribute key or a closer's colons directly.
+
+use serde::Serialize;
+
+use crate::parse::{
+    self, LeafLookahead, closing_directive_depth, directive_name_start, opening_directive,
+};
+use crate::types::{Block, FileId, Span, SurfDoc};
+
+/// Block names whose body is literal text: directive-looking lines inside
+/// them are content, not structure.
+pub(crate) const LITERAL_BLOCK_NAMES: &[&str] = &["code", "output"];
+
+/// Node and token kinds. Tokens (`Colons` … `Text`) never have children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CstKind {
+    /// The whole source.
+    Document,
+    /// `---` … `---` front matter, both fences included.
+    FrontMatter,
+    /// A top-level markdown gap (one `Block::Markdown`).
+    Markdown,
+    /// Top-level bytes outside every block: blank lines and line breaks
+    /// between blocks, and any source a parse bound cut off.
+    Trivia,
+    /// A top-level `::` that matched no open block (P006).
+    OrphanCloser,
+    /// A block directive: opener, then body and closer when it has them.
+    Directive,
+    /// The directive's opening line, without its line break.
+    Opener,
+    /// The `[...]` attribute string on an opener.
+    AttrList,
+    /// One `key`, `key=value`, or `key="value"` entry.
+    Attr,
+    /// Lines between the opener and the closer (or the end of the block).
+    Body,
+    /// The directive's closing `::` line, without its line break.
+    Closer,
+    /// A run of `:`.
+    Colons,
+    /// Directive name.
+    Name,
+    /// `[`
+    LBracket,
+    /// `]`
+    RBracket,
+    /// Attribute key.
+    AttrKey,
+    /// `=`
+    Equals,
+    /// Attribute value as written (quotes included).
+    AttrValue,
+    /// Attribute text `parse_attrs` rejects (P003); the typed block dropped it.
+    InvalidAttrs,
+    /// Spaces or tabs inside a line.
+    Whitespace,
+    /// A single `\n`.
+    Newline,
+    /// Any other text: body lines, same-line trailing content.
+    Text,
+}
+
+/// A CST node. `span` covers exactly the node's bytes; a node's children
+/// tile its span without gaps or overlap.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CstNode {
+    pub kind: CstKind,
+    pub span: Span,
+    pub children: Vec<CstNode>,
+    /// For linked nodes: index of the typed block in `doc.blocks` (top-level
+    /// nodes) or in the enclosing container's [`Block::children`] (nodes in
+    /// its `Body`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block: Option<usize>,
+}
+
+impl CstNode {
+    /// The node's source text.
+    pub fn text<'s>(&self, source: &'s str) -> &'s str {
+        &source[self.span.start_offset..self.span.end_offset]
+    }
+
+    /// First direct child of `kind`.
+    pub fn child(&self, kind: CstKind) -> Option<&CstNode> {
+        self.children.iter().find(|c| c.kind == kind)
+    }
+
+    /// This node and everything below it, in source order.
+    pub fn descendants(&self) -> Descendants<'_> {
+        Descendants { stack: vec![self] }
+    }
+
+    /// For a `Directive`: its name.
+    pub fn directive_name<'s>(&self, source: &'s str) -> Option<&'s str> {
+        Some(
+            self.child(CstKind::Opener)?
+                .child(CstKind::Name)?
+                .text(source),
+        )
+    }
+
+    /// For a `Directive`: its colon count.
+    pub fn directive_depth(&self) -> Option<usize> {
+        let colons = self.child(CstKind::Opener)?.child(CstKind::Colons)?;
+        Some(colons.span.end_offset - colons.span.start_offset)
+    }
+
+    /// For a `Directive`: the `Attr` node whose key is `key`.
+    pub fn attr(&self, key: &str, source: &str) -> Option<&CstNode> {
+        self.child(CstKind::Opener)?
+            .child(CstKind::AttrList)?
+            .children
+            .iter()
+            .filter(|c| c.kind == CstKind::Attr)
+            .find(|a| {
+                a.child(CstKind::AttrKey)
+                    .is_some_and(|k| k.text(source) == key)
+            })
+    }
+}
+
+/// Pre-order iterator returned by [`CstNode::descendants`].
+pub struct Descendants<'a> {
+    stack: Vec<&'a CstNode>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = &'a CstNode;
+
+    fn next(&mut self) -> Option<&'a CstNode> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children.iter().rev());
+        Some(node)
+    }
+}
+
+/// Lossless syntax tree of one parsed document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Cst {
+    root: CstNode,
+}
+
+impl Cst {
+    /// Build the CST for a parsed document. `doc.source` and the top-level
+    /// block spans must come from the same parse; blocks whose span does not
+    /// fit the source (e.g. builder-made blocks) are left unlinked and their
+    /// bytes fall into the surrounding nodes.
+    pub fn from_doc(doc: &SurfDoc) -> Self {
+        Builder::new(&doc.source).document(doc)
+    }
+
+    /// The `Document` node.
+    pub fn root(&self) -> &CstNode {
+        &self.root
+    }
+
+    /// The node for `doc.blocks[index]`.
+    pub fn block_node(&self, index: usize) -> Option<&CstNode> {
+        self.root.children.iter().find(|n| n.block == Some(index))
+    }
+
+    /// The node for a block nested in containers: `path` indexes
+    /// `doc.blocks`, then each container's [`Block::children`]. `&[i]` is
+    /// [`block_node`](Self::block_node)`(i)`.
+    pub fn node_at(&self, path: &[usize]) -> Option<&CstNode> {
+        let (first, rest) = path.split_first()?;
+        let mut node = self.block_node(*first)?;
+        for index in rest {
+            node = node
+                .child(CstKind::Body)?
+                .children
+                .iter()
+                .find(|n| n.block == Some(*index))?;
+        }
+        Some(node)
+    }
+
+    /// The node for a top-level block, matched by span.
+    pub fn node_for(&self, block: &Block) -> Option<&CstNode> {
+        let span = block.span();
+        self.root
+            .children
+            .iter()
+            .find(|n| n.block.is_some() && n.span == span)
+    }
+
+    /// Every `Directive` node, nested ones included, in source order.
+    pub fn directives(&self) -> impl Iterator<Item = &CstNode> {
+        self.root
+            .descendants()
+            .filter(|n| n.kind == CstKind::Directive)
+    }
+
+    /// The leaf tokens in source order; their texts concatenate to the
+    /// source.
+    pub fn leaves(&self) -> impl Iterator<Item = &CstNode> {
+        self.root.descendants().filter(|n| n.children.is_empty())
+    }
+}
+
+/// Link `block`'s child blocks to the items of `node`'s body, in order:
+/// each markdown child to the next non-blank `Text` run, every other child
+/// to the next `Directive`. Children past the first that finds no match stay
+/// unlinked.
+fn link_children(node: &mut CstNode, block: &Block, source: &str) {
+    let (Some(children), Some(body)) = (
+        block.children(),
+        node.children.iter_mut().find(|c| c.kind == CstKind::Body),
+    ) else {
+        return;
+    };
+    let mut items = body.children.iter_mut();
+    for (index, child) in children.iter().enumerate() {
+        let markdown = matches!(child, Block::Markdown { .. });
+        let item = items.find(|n| {
+            if markdown {
+                n.kind == CstKind::Text && !n.text(source).trim().is_empty()
+            } else {
+                n.kind == CstKind::Directive
+            }
+        });
+        let Some(item) = item else {
+            return;
+        };
+        item.block = Some(index);
+        link_children(item, child, source);
+    }
+}
+
+struct Builder<'a> {
+    source: &'a str,
+    lines: Vec<&'a str>,
+    /// Byte offset of each line start.
+    starts: Vec<usize>,
+}
+
+impl<'a> Builder<'a> {
+    fn new(source: &'a str) -> Self {
+        let lines: Vec<&str> = source.split('\n').collect();
+        let mut starts = Vec::with_capacity(lines.len());
+        let mut offset = 0;
+        for line in &lines {
+            starts.push(offset);
+            offset += line.len() + 1;
+        }
+        Builder {
+            source,
+            lines,
+            starts,
+        }
+    }
+
+    fn line_of(&self, offset: usize) -> usize {
+        self.starts
+            .partition_point(|&s| s <= offset)
+            .saturating_sub(1)
+    }
+
+    fn line_end(&self, idx: usize) -> usize {
+        self.starts[idx] + self.lines[idx].len()
+    }
+
+    fn span(&self, start: usize, end: usize) -> Span {
+        Span {
+            start_line: self.line_of(start) + 1,
+            end_line: self.line_of(end.max(start + 1) - 1) + 1,
+            start_offset: start,
+            end_offset: end,
+            file: FileId::ROOT,
+        }
+    }
+
+    fn leaf(&self, kind: CstKind, start: usize, end: usize) -> CstNode {
+        self.node(kind, start, end, Vec::new())
+    }
+
+    fn node(&self, kind: CstKind, start: usize, end: usize, children: Vec<CstNode>) -> CstNode {
+        CstNode {
+            kind,
+            span: self.span(start, end),
+            children,
+            block: None,
+        }
+    }
+
+    fn document(&self, doc: &SurfDoc) -> Cst {
+        let mut children = Vec::new();
+        let mut cursor = 0;
+
+        let (_, body_start) =
+            parse::extract_front_matter(&self.lines, self.source, &mut Vec::new());
+        if body_start > 0 {
+            let end = self.line_end(body_start - 1);
+            children.push(self.leaf(CstKind::FrontMatter, 0, end));
+            cursor = end;
+        }
+
+        for (index, block) in doc.blocks.iter().enumerate() {
+            let span = block.span();
+            if !span.file.is_root()
+                || span.start_offset < cursor
+                || span.end_offset > self.source.len()
+            {
+                continue;
+            }
+            let mut node = match block {
+                Block::Markdown { .. } => {
+                    self.leaf(CstKind::Markdown, span.start_offset, span.end_offset)
+                }
+                _ => match self.top_level_opener(span) {
+                    Some(idx) => self.top_level_directive(idx, span.end_offset),
+                    None => continue,
+                },
+            };
+            // The parser's span verbatim (its end line convention for
+            // blocks force-closed at end of input differs from ours).
+            node.span = span;
+            node.block = Some(index);
+            link_children(&mut node, block, self.source);
+            self.gap(cursor, span.start_offset, &mut children);
+            children.push(node);
+            cursor = span.end_offset;
+        }
+        self.gap(cursor, self.source.len(), &mut children);
+
+        Cst {
+            root: self.node(CstKind::Document, 0, self.source.len(), children),
+        }
+    }
+
+    /// Line index of the opener a top-level block span starts at, if the
+    /// span really starts on an opener line.
+    fn top_level_opener(&self, span: Span) -> Option<usize> {
+        let idx = span.start_line.checked_sub(1)?;
+        (self.starts.get(idx) == Some(&span.start_offset)
+            && span.end_offset >= self.line_end(idx).min(self.source.len())
+            && opening_directive(self.lines[idx].trim()).is_some())
+        .then_some(idx)
+    }
+
+    /// Top-level bytes between blocks: trivia, with orphan closer lines
+    /// split out.
+    fn gap(&self, from: usize, to: usize, out: &mut Vec<CstNode>) {
+        let mut trivia_start = from;
+        let mut idx = self.line_of(from);
+        while idx < self.lines.len() && self.starts[idx] < to {
+            let (start, end) = (self.starts[idx], self.line_end(idx));
+            if start >= from
+                && end <= to
+                && closing_directive_depth(self.lines[idx].trim()).is_some()
+            {
+                if trivia_start < start {
+                    out.push(self.leaf(CstKind::Trivia, trivia_start, start));
+                }
+                let closer = self.closer_tokens(idx);
+                out.push(self.node(CstKind::OrphanCloser, start, end, closer));
+                trivia_start = end;
+            }
+            idx += 1;
+        }
+        if trivia_start < to {
+            out.push(self.leaf(CstKind::Trivia, trivia_start, to));
+        }
+    }
+
+    /// A top-level directive ending at `end` (the parser's span end), with
+    /// the closer decided exactly as the parser's stack decides it.
+    fn top_level_directive(&self, open_idx: usize, end: usize) -> CstNode {
+        let depth = opening_directive(self.lines[open_idx].trim()).map_or(2, |(d, _, _)| d);
+        let last_idx = self.line_of(end.max(self.starts[open_idx] + 1) - 1);
+        let closer = self
+            .parser_closer(open_idx, depth, last_idx)
+            .filter(|&c| self.line_end(c) == end);
+        let body_end = match closer {
+            Some(c) => self.starts[c],
+            None => end,
+        };
+        self.directive(open_idx, body_end, closer, end)
+    }
+
+    /// The line closing the block opened at `open_idx`, mirroring the
+    /// parser's nesting stack (every nested opener owes a closer; a closer
+    /// pops the innermost open block of its depth).
+    fn parser_closer(&self, open_idx: usize, depth: usize, last_idx: usize) -> Option<usize> {
+        let mut stack = vec![depth];
+        let mut in_fence = false;
+        for idx in open_idx + 1..=last_idx.min(self.lines.len() - 1) {
+            let trimmed = self.lines[idx].trim();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+            }
+            if let Some(d) = closing_directive_depth(trimmed)
+                && let Some(pos) = stack.iter().rposition(|&s| s == d)
+            {
+                stack.truncate(pos);
+                if stack.is_empty() {
+                    return Some(idx);
+                }
+                continue;
+            }
+            let opener = opening_directive(trimmed)
+                .filter(|(d, _, _)| !in_fence || directive_name_start(trimmed, *d) == *d);
+            if let Some((d, _, _)) = opener {
+                stack.push(d);
+            }
+        }
+        None
+    }
+
+    /// A directive whose body runs to `body_end` and whose node ends at
+    /// `end` (the closer line's end when `closer` is set).
+    fn directive(
+        &self,
+        open_idx: usize,
+        body_end: usize,
+        closer: Option<usize>,
+        end: usize,
+    ) -> CstNode {
+        let start = self.starts[open_idx];
+        let opener_end = self.line_end(open_idx).min(end);
+        let mut children = vec![self.node(
+            CstKind::Opener,
+            start,
+            opener_end,
+            self.opener_tokens(open_idx),
+        )];
+        if opener_end < body_end.max(end) {
+            children.push(self.leaf(CstKind::Newline, opener_end, opener_end + 1));
+        }
+        let body_start = (opener_end + 1).min(body_end);
+        if body_start < body_end {
+            let literal = opening_directive(self.lines[open_idx].trim())
+                .is_some_and(|(_, name, _)| LITERAL_BLOCK_NAMES.contains(&name.as_str()));
+            let body = if literal {
+                vec![self.leaf(CstKind::Text, body_start, body_end)]
+            } else {
+                self.body(body_start, body_end)
+            };
+            children.push(self.node(CstKind::Body, body_start, body_end, body));
+        }
+        if let Some(c) = closer {
+            children.push(self.node(
+                CstKind::Closer,
+                self.starts[c],
+                self.line_end(c),
+                self.closer_tokens(c),
+            ));
+        }
+        self.node(CstKind::Directive, start, end, children)
+    }
+
+    /// Body content between two line starts (or to the end of the source):
+    /// text runs and nested directives.
+    fn body(&self, from: usize, to: usize) -> Vec<CstNode> {
+        let first = self.line_of(from);
+        // Lines wholly inside the body: the container look-ahead must not see
+        // the parent's closer.
+        let limit = if to == self.source.len() {
+            self.lines.len()
+        } else {
+            self.line_of(to)
+        };
+        let scope = &self.lines[..limit];
+
+        let mut out = Vec::new();
+        let mut text_start = from;
+        let mut in_fence = false;
+        let mut idx = first;
+        while idx < limit {
+            let trimmed = self.lines[idx].trim();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+            }
+            // Inside a fence only the strict `::name` form opens, as in
+            // `blocks.rs`.
+            let opener = opening_directive(trimmed)
+                .filter(|(d, _, _)| !in_fence || directive_name_start(trimmed, *d) == *d);
+            let Some((depth, _, _)) = opener else {
+                idx += 1;
+                continue;
+            };
+            let start = self.starts[idx];
+            if text_start < start {
+                out.push(self.leaf(CstKind::Text, text_start, start));
+            }
+            let (node, last) = match parse::leaf_lookahead(scope, idx + 1, depth) {
+                LeafLookahead::Closed { closer } => {
+                    let end = self.line_end(closer);
+                    (
+                        self.directive(idx, self.starts[closer], Some(closer), end),
+                        closer,
+                    )
+                }
+                LeafLookahead::Open { .. } => {
+                    let end = self.line_end(idx).min(to);
+                    (self.directive(idx, end, None, end), idx)
+                }
+            };
+            let mut after = node.span.end_offset;
+            out.push(node);
+            if after < to {
+                out.push(self.leaf(CstKind::Newline, after, after + 1));
+                after += 1;
+            }
+            text_start = after;
+            idx = last + 1;
+        }
+        if text_start < to {
+            out.push(self.leaf(CstKind::Text, text_start, to));
+        }
+        out
+    }
+
+    fn opener_tokens(&self, idx: usize) -> Vec<CstNode> {
+        let base = self.starts[idx];
+        let raw = self.lines[idx];
+        let trimmed = raw.trim();
+        let indent = raw.len() - raw.trim_start().len();
+        let Some((depth, name, attrs_str)) = opening_directive(trimmed) else {
+            return vec![self.leaf(CstKind::Text, base, base + raw.len())];
+        };
+
+        let mut out = Vec::new();
+        let mut pos = base;
+        self.push_token(&mut out, CstKind::Whitespace, &mut pos, indent);
+        self.push_token(&mut out, CstKind::Colons, &mut pos, depth);
+        let gap = directive_name_start(trimmed, depth) - depth;
+        self.push_token(&mut out, CstKind::Whitespace, &mut pos, gap);
+        self.push_token(&mut out, CstKind::Name, &mut pos, name.len());
+        if !attrs_str.is_empty() {
+            out.push(self.node(
+                CstKind::AttrList,
+                pos,
+                pos + attrs_str.len(),
+                self.attr_tokens(&attrs_str, pos),
+            ));
+            pos += attrs_str.len();
+        }
+        let tail = &raw[pos - base..];
+        let content = tail.trim();
+        if !content.is_empty() {
+            let gap = tail.len() - tail.trim_start().len();
+            self.push_token(&mut out, CstKind::Whitespace, &mut pos, gap);
+            self.push_token(&mut out, CstKind::Text, &mut pos, content.len());
+        }
+        let rest = base + raw.len() - pos;
+        self.push_token(&mut out, CstKind::Whitespace, &mut pos, rest);
+        out
+    }
+
+    /// Push a `len`-byte token at `*pos` (nothing when empty) and advance.
+    fn push_token(&self, out: &mut Vec<CstNode>, kind: CstKind, pos: &mut usize, len: usize) {
+        if len > 0 {
+            out.push(self.leaf(kind, *pos, *pos + len));
+            *pos += len;
+        }
+    }
+
+    fn closer_tokens(&self, idx: usize) -> Vec<CstNode> {
+        let base = self.starts[idx];
+        let raw = self.lines[idx];
+        let indent = raw.len() - raw.trim_start().len();
+        let colons = raw.trim().len();
+        let mut out = Vec::new();
+        if indent > 0 {
+            out.push(self.leaf(CstKind::Whitespace, base, base + indent));
+        }
+        out.push(self.leaf(CstKind::Colons, base + indent, base + indent + colons));
+        if indent + colons < raw.len() {
+            out.push(self.leaf(
+                CstKind::Whitespace,
+                base + indent + colons,
+                base + raw.len(),
+            ));
+        }
+        out
+    }
+
+    /// Tokens of an opener's `[...]` string at byte offset `base`, following
+    /// `attrs::parse_attrs` exactly; text it rejects becomes `InvalidAttrs`.
+    fn attr_tokens(&self, attrs: &str, base: usize) -> Vec<CstNode> {
+        let bracketed = attrs.len() >= 2 && attrs.starts_with('[') && attrs.ends_with(']');
+        if !bracketed {
+            return vec![self.leaf(CstKind::InvalidAttrs, base, base + attrs.len())];
+        }
+        let inner_start = base + 1;
+        let inner = &attrs[1..attrs.len() - 1];
+        let mut out = vec![self.leaf(CstKind::LBracket, base, inner_start)];
+        match self.attr_entries(inner, inner_start) {
+            Some(tokens) => out.extend(tokens),
+            None => out.push(self.leaf(
+                CstKind::InvalidAttrs,
+                inner_start,
+                inner_start + inner.len(),
+            )),
+        }
+        out.push(self.leaf(
+            CstKind::RBracket,
+            base + attrs.len() - 1,
+            base + attrs.len(),
+        ));
+        out
+    }
+
+    fn attr_entries(&self, inner: &str, base: usize) -> Option<Vec<CstNode>> {
+        let chars: Vec<(usize, char)> = inner.char_indices().collect();
+        let at = |i: usize| chars.get(i).map_or(inner.len(), |&(b, _)| b) + base;
+        let is_key = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+        let mut out = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let ws = i;
+            while i < chars.len() && chars[i].1.is_whitespace() {
+                i += 1;
+            }
+            if i > ws {
+                out.push(self.leaf(CstKind::Whitespace, at(ws), at(i)));
+            }
+            if i >= chars.len() {
+                break;
+            }
+
+            let key = i;
+            while i < chars.len() && is_key(chars[i].1) {
+                i += 1;
+            }
+            if i == key {
+                return None;
+            }
+            let mut attr = vec![self.leaf(CstKind::AttrKey, at(key), at(i))];
+            if i < chars.len() && chars[i].1 == '=' {
+                attr.push(self.leaf(CstKind::Equals, at(i), at(i + 1)));
+                i += 1;
+                let value = i;
+                match chars.get(i).map(|&(_, c)| c) {
+                    None => return None,
+                    Some('"') => {
+                        i += 1;
+                        while i < chars.len() && chars[i].1 != '"' {
+                            let escape = chars[i].1 == '\\'
+                                && matches!(chars.get(i + 1), Some((_, '"' | '\\')));
+                            i += if escape { 2 } else { 1 };
+                        }
+                        if i >= chars.len() {
+                            return None;
+                        }
+                        i += 1;
+                    }
+                    Some(_) => {
+                        while i < chars.len() && !chars[i].1.is_whitespace() && chars[i].1 != ']' {
+                            i += 1;
+                        }
+                    }
+                }
+                attr.push(self.leaf(CstKind::AttrValue, at(value), at(i)));
+            }
+            out.push(self.node(CstKind::Attr, at(key), at(i), attr));
+        }
+        Some(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cst(source: &str) -> (String, Cst) {
+        let doc = crate::parse(source).doc;
+        let cst = Cst::from_doc(&doc);
+        (doc.source, cst)
+    }
+
+    fn kinds(node: &CstNode) -> Vec<CstKind> {
+        node.children.iter().map(|c| c.kind).collect()
+    }
+
+    #[test]
+    fn leaves_reproduce_the_source() {
+        let source = "---\ntitle: T\n---\n\n# Heading\n\n::callout[type=info title=\"Hi there\"]  \nBody\n:::columns\n::::column\nA\n::::\n:::\n::\n::\n\n::metric[label=x value=1]\n";
+        let (normalised, cst) = cst(source);
+        let rebuilt: String = cst.leaves().map(|l| l.text(&normalised)).collect();
+        assert_eq!(rebuilt, normalised);
+        assert_eq!(
+            kinds(cst.root()),
+            vec![
+                CstKind::FrontMatter,
+                CstKind::Trivia,
+                CstKind::Markdown,
+                CstKind::Trivia,
+                CstKind::Directive,
+                CstKind::Trivia,
+                CstKind::OrphanCloser,
+                CstKind::Trivia,
+                CstKind::Directive,
+            ]
+        );
+    }
+
+    #[test]
+    fn directive_has_opener_body_and_closer_tokens() {
+        let source = "::callout[type=warning title=\"Mind the gap\" dismissible]\nCareful.\n::\n";
+        let (src, cst) = cst(source);
+        let node = cst.block_node(0).unwrap();
+        assert_eq!(
+            kinds(node),
+            vec![
+                CstKind::Opener,
+                CstKind::Newline,
+                CstKind::Body,
+                CstKind::Closer
+            ]
+        );
+        assert_eq!(node.directive_name(&src), Some("callout"));
+        assert_eq!(node.directive_depth(), Some(2));
+        let title = node.attr("title", &src).unwrap();
+        assert_eq!(title.text(&src), "title=\"Mind the gap\"");
+        assert_eq!(
+            title.child(CstKind::AttrValue).unwrap().text(&src),
+            "\"Mind the gap\""
+        );
+        let flag = node.attr("dismissible", &src).unwrap();
+        assert_eq!(kinds(flag), vec![CstKind::AttrKey]);
+        assert_eq!(node.child(CstKind::Body).unwrap().text(&src), "Careful.\n");
+        assert_eq!(node.child(CstKind::Closer).unwrap().text(&src), "::");
+    }
+
+    #[test]
+    fn typed_blocks_link_to_their_nodes() {
+        let source =
+            "Intro.\n\n::summary\nS\n::\n\n::metric[label=a value=1]\n::metric[label=b value=2]\n";
+        let doc = crate::parse(source).doc;
+        let cst = Cst::from_doc(&doc);
+        for (i, block) in doc.blocks.iter().enumerate() {
+            let node = cst.node_for(block).unwrap();
+            assert_eq!(node.block, Some(i));
+            assert_eq!(node.span, block.span());
+        }
+        // Leaf directives are a bare opener.
+        assert_eq!(kinds(cst.block_node(2).unwrap()), vec![CstKind::Opener]);
+    }
+
+    #[test]
+    fn nested_blocks_link_to_their_nodes() {
+        let source =
+            "::section\n## Title\n\nIntro.\n\n:::callout[type=note]\nHi\n:::\n\nOutro.\n::\n";
+        let doc = crate::parse(source).doc;
+        let cst = Cst::from_doc(&doc);
+        let children = doc.blocks[0].children().unwrap();
+        assert_eq!(children.len(), 3);
+        let src = &doc.source;
+        assert_eq!(
+            cst.node_at(&[0, 0]).unwrap().text(src),
+            "## Title\n\nIntro.\n\n"
+        );
+        let callout = cst.node_at(&[0, 1]).unwrap();
+        assert_eq!(callout.directive_name(src), Some("callout"));
+        assert_eq!(cst.node_at(&[0, 2]).unwrap().text(src), "\nOutro.\n");
+        assert!(cst.node_at(&[0, 3]).is_none());
+    }
+
+    #[test]
+    fn nested_directives_and_literal_bodies() {
+        let source = "::columns\n:::column\nA\n:::\n:::column\nB\n:::\n::\n::code[lang=md]\n:::callout\nnot a block\n:::\n::\n";
+        let (src, cst) = cst(source);
+        let names: Vec<&str> = cst
+            .directives()
+            .filter_map(|d| d.directive_name(&src))
+            .collect();
+        assert_eq!(names, vec!["columns", "column", "column", "code"]);
+        let code = cst.block_node(1).unwrap();
+        assert_eq!(
+            kinds(code.child(CstKind::Body).unwrap()),
+            vec![CstKind::Text]
+        );
+    }
+
+    #[test]
+    fn rejected_attrs_are_one_invalid_token() {
+        let source = "::callout[type=\"open]\nX\n::\n";
+        let (src, cst) = cst(source);
+        let list = cst
+            .block_node(0)
+            .unwrap()
+            .child(CstKind::Opener)
+            .unwrap()
+            .child(CstKind::AttrList)
+            .unwrap();
+        assert_eq!(
+            kinds(list),
+            vec![CstKind::LBracket, CstKind::InvalidAttrs, CstKind::RBracket]
+        );
+        assert_eq!(list.children[1].text(&src), "type=\"open");
+    }
+}
@@ -1,10 +1,10 @@
 //! Native diagram DSL (`::diagram`) — parsing, geometry layout and SVG
 //! rendering.
 //!
-//! Seventeen diagram kinds are supported: `architecture`, `erd`, `flowchart`,
+//! Nineteen diagram kinds are supported: `architecture`, `erd`, `flowchart`,
 //! `sequence`, `gantt`, `state`, `mindmap`, `class`, `timeline`, `journey`,
-//! `quadrant`, `kanban`, `usecase`, `gitgraph`, `c4`, `requirement` and
-//! `sankey`. Four further chart-alias types (`pie`, `donut`, `radar`,
+//! `quadrant`, `kanban`, `usecase`, `gitgraph`, `c4`, `requirement`,
+//! `sankey`, `packet` and `block`. Four further chart-alias types (`pie`, `donut`, `radar`,
 //! `xychart`) are recognized by [`chart_alias`] and render through the
 //! `::chart` pipeline instead of this module's geometry scenes. Bodies
 //! written in mermaid syntax are translated to this DSL up front by
@@ -21,20 +21,27 @@
 //! The same scene crosses the FFI so native clients draw identical layouts
 //! from typed shapes.
 //!
-//! DETERMINISM: [`render_svg`] is a pure function of the model. Layout uses
-//! only declaration-ordered `Vec`s (no `HashMap` iteration), no randomness and
-//! no time, all geometry is integer arithmetic, so output is byte-stable
-//! across runs — consumers pin exact substrings in tests.
+//! DETERMINISM: [`render_svg`] is a pure function of the model and the
+//! caller's [`install_diagram_today`] date. Layout uses only
+//! declaration-ordered `Vec`s (no `HashMap` iteration), no randomness and no
+//! clock, all geometry is integer arithmetic, so output is byte-stable across
+//! runs — consumers pin exact substrings in tests.
+
+use std::cell::Cell;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 
 use crate::diagram_scene::{NativeAnchor, NativeMarker, NativePoint, NativeRole, NativeShape};
 use crate::render_html::escape_html;
+use crate::text_metrics::TextMeasurer;
+use crate::types::DiagramDirection;
 
 // ------------------------------------------------------------------
 // Model types
 // ------------------------------------------------------------------
 
-/// A DSL parse failure. Carries the offending line for future diagnostics;
-/// in v1 it is used only to trigger the prose fallback at render time.
+/// A DSL parse failure. Triggers the prose fallback at render time, and is
+/// surfaced at its source line by the `L041` lint.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct DiagramError {
     /// 1-based line number of the offending statement (0 = whole block,
@@ -44,11 +51,66 @@ pub(crate) struct DiagramError {
     pub(crate) message: String,
 }
 
+/// A named node group (`group id: Label {` … `}`) of an architecture
+/// diagram or flowchart, drawn as a labelled cluster rectangle around its
+/// members.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DiagramGroup {
+    pub(crate) id: String,
+    pub(crate) label: String,
+}
+
+/// Semantic emphasis of a flowchart or state node/edge (`style a, b:
+/// danger`). Scenes paint it through the matching [`NativeRole`], so the
+/// theme decides the actual color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Emphasis {
+    Highlight,
+    Muted,
+    Danger,
+    Success,
+}
+
+impl Emphasis {
+    /// Map a `style` keyword to its emphasis.
+    fn parse(tok: &str) -> Option<Self> {
+        match tok {
+            "highlight" => Some(Emphasis::Highlight),
+            "muted" => Some(Emphasis::Muted),
+            "danger" => Some(Emphasis::Danger),
+            "success" => Some(Emphasis::Success),
+            _ => None,
+        }
+    }
+
+    /// Paint role for the outline of an emphasized node or edge.
+    fn role(self) -> NativeRole {
+        match self {
+            Emphasis::Highlight => NativeRole::Highlight,
+            Emphasis::Muted => NativeRole::Muted,
+            Emphasis::Danger => NativeRole::Danger,
+            Emphasis::Success => NativeRole::Success,
+        }
+    }
+}
+
+/// A clickable node target (`link id: url "tooltip"`). The url has passed
+/// the [`LINK_SCHEMES`] allowlist at parse time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DiagramLink {
+    pub(crate) url: String,
+    /// Hover text; the SVG falls back to the url when absent.
+    pub(crate) tooltip: Option<String>,
+}
+
 /// A node in an architecture diagram (`id: Label text`).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct ArchNode {
     pub(crate) id: String,
     pub(crate) label: String,
+    /// Index of the enclosing group, if the node was declared inside one.
+    pub(crate) group: Option<usize>,
+    pub(crate) link: Option<DiagramLink>,
 }
 
 /// An edge in an architecture diagram (`a -> b`, `a <-> b`, `a -> b: Label`).
@@ -122,6 +184,10 @@ pub(crate) struct FlowNode {
     pub(crate) id: String,
     pub(crate) label: String,
     pub(crate) shape: FlowShape,
+    /// Index of the enclosing group, if the node was declared inside one.
+    pub(crate) group: Option<usize>,
+    pub(crate) emphasis: Option<Emphasis>,
+    pub(crate) link: Option<DiagramLink>,
 }
 
 /// A flowchart edge (`a -> b`, optional `: Label`).
@@ -130,6 +196,7 @@ pub(crate) struct FlowEdge {
     pub(crate) from: String,
     pub(crate) to: String,
     pub(crate) label: Option<String>,
+    pub(crate) emphasis: Option<Emphasis>,
 }
 
 // ── sequence ───────────────────────────────────────────────────────
@@ -155,16 +222,102 @@ pub(crate) enum SeqEvent {
     Activate(String),
     /// End the most recent activation bar on an actor's lifeline.
     Deactivate(String),
+    /// A note beside or over one or two lifelines (`over` only for two).
+    Note {
+        placement: NotePlacement,
+        actors: Vec<String>,
+        text: String,
+    },
+    /// A combined fragment (`alt`, `loop`, …): one section per operand, the
+    /// first carrying the opener's guard. Fragments nest.
+    Fragment {
+        kind: FragmentKind,
+        sections: Vec<SeqSection>,
+    },
+}
+
+/// Where a sequence note sits relative to its lifeline(s).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NotePlacement {
+    LeftOf,
+    RightOf,
+    Over,
+}
+
+/// The operator of a sequence-diagram combined fragment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FragmentKind {
+    Loop,
+    Alt,
+    Opt,
+    Par,
+    Critical,
+    Break,
+    /// Highlighted region: a shaded background, no operator tab.
+    Rect,
+}
+
+impl FragmentKind {
+    /// All kinds with their DSL keyword (also the frame's tab text).
+    const ALL: [(FragmentKind, &'static str); 7] = [
+        (FragmentKind::Loop, "loop"),
+        (FragmentKind::Alt, "alt"),
+        (FragmentKind::Opt, "opt"),
+        (FragmentKind::Par, "par"),
+        (FragmentKind::Critical, "critical"),
+        (FragmentKind::Break, "break"),
+        (FragmentKind::Rect, "rect"),
+    ];
+
+    fn word(self) -> &'static str {
+        FragmentKind::ALL
+            .iter()
+            .find(|(k, _)| *k == self)
+            .map(|(_, w)| *w)
+            .expect("every kind is listed")
+    }
+
+    /// The keyword that starts a further section, for kinds that have them.
+    fn separator(self) -> Option<&'static str> {
+        match self {
+            FragmentKind::Alt => Some("else"),
+            FragmentKind::Par => Some("and"),
+            FragmentKind::Critical => Some("option"),
+            _ => None,
+        }
+    }
+}
+
+/// One operand of a combined fragment: an optional guard (`[x > 0]`) and
+/// the events inside it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SeqSection {
+    pub(crate) guard: Option<String>,
+    pub(crate) events: Vec<SeqEvent>,
 }
 
 // ── gantt ──────────────────────────────────────────────────────────
 
+/// Progress status of a Gantt task (`done`, `active` or `crit` tag).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GanttStatus {
+    Done,
+    Active,
+    Crit,
+}
+
 /// One bar of a Gantt chart (`Label: start, duration`), optionally in a
-/// section. `start` is a numeric unit or a day-number (when dates are used).
+/// section. `start` is a numeric unit or a day-number (when dates are used);
+/// for a task declared `after a b` it is resolved from the latest end of
+/// its dependencies. A milestone is a zero-duration point.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct GanttTask {
     pub(crate) section: Option<String>,
     pub(crate) label: String,
+    pub(crate) id: Option<String>,
+    pub(crate) after: Vec<String>,
+    pub(crate) milestone: bool,
+    pub(crate) status: Option<GanttStatus>,
     pub(crate) start: i64,
     pub(crate) duration: i64,
 }
@@ -179,6 +332,8 @@ pub(crate) struct StateNode {
     pub(crate) label: String,
     pub(crate) initial: bool,
     pub(crate) final_: bool,
+    pub(crate) emphasis: Option<Emphasis>,
+    pub(crate) link: Option<DiagramLink>,
 }
 
 /// A state transition (`A -> B`, optional `: Label`).
@@ -187,6 +342,7 @@ pub(crate) struct StateTransition {
     pub(crate) from: String,
     pub(crate) to: String,
     pub(crate) label: Option<String>,
+    pub(crate) emphasis: Option<Emphasis>,
 }
 
 // ── mindmap ────────────────────────────────────────────────────────
@@ -420,12 +576,44 @@ pub(crate) struct SankeyFlow {
     pub(crate) value_cs: i64,
 }
 
+// ── packet ─────────────────────────────────────────────────────────
+
+/// One field of a packet diagram (`start-end: Label`): `bits` consecutive
+/// bits from bit `start`. Fields tile the packet without gaps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PacketField {
+    pub(crate) start: i64,
+    pub(crate) bits: i64,
+    pub(crate) label: String,
+}
+
+// ── block ──────────────────────────────────────────────────────────
+
+/// One grid cell of a block diagram: a block (`id: Label`) or, with no id,
+/// an empty `space`. `span` is the number of grid columns it covers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BlockCell {
+    pub(crate) id: Option<String>,
+    pub(crate) label: String,
+    pub(crate) span: usize,
+}
+
+/// An edge of a block diagram (`a -> b`, optional `: label`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BlockEdge {
+    pub(crate) from: String,
+    pub(crate) to: String,
+    pub(crate) label: Option<String>,
+}
+
 /// Parsed diagram body, per diagram kind. Declaration order is preserved in
 /// every `Vec` — layout and SVG output depend on it.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum DiagramModel {
     Architecture {
         nodes: Vec<ArchNode>,
+        /// Groups in declaration order.
+        groups: Vec<DiagramGroup>,
         edges: Vec<ArchEdge>,
     },
     Erd {
@@ -434,6 +622,8 @@ pub(crate) enum DiagramModel {
     },
     Flowchart {
         nodes: Vec<FlowNode>,
+        /// Groups in declaration order.
+        groups: Vec<DiagramGroup>,
         edges: Vec<FlowEdge>,
     },
     Sequence {
@@ -445,6 +635,8 @@ pub(crate) enum DiagramModel {
         /// True when `start` values are day-numbers from ISO dates (axis ticks
         /// render as dates); false for plain numeric units.
         dated: bool,
+        /// The `today` marker position, in the same units as `start`.
+        today: Option<i64>,
     },
     State {
         nodes: Vec<StateNode>,
@@ -502,12 +694,29 @@ pub(crate) enum DiagramModel {
         nodes: Vec<String>,
         flows: Vec<SankeyFlow>,
     },
+    Packet {
+        fields: Vec<PacketField>,
+    },
+    Block {
+        /// `columns N`, when declared; otherwise every cell shares one row.
+        columns: Option<usize>,
+        cells: Vec<BlockCell>,
+        edges: Vec<BlockEdge>,
+    },
 }
 
 // ------------------------------------------------------------------
 // DSL parsing
 // ------------------------------------------------------------------
 
+/// Every accepted `type` value: the native geometry types, then the
+/// chart-alias types, then the explicit `mermaid` marker.
+pub(crate) const DIAGRAM_TYPES: &[&str] = &[
+    "architecture", "erd", "flowchart", "sequence", "gantt", "state", "mindmap", "class", "timeline",
+    "journey", "quadrant", "kanban", "usecase", "gitgraph", "c4", "requirement", "sankey", "packet",
+    "block", "pie", "donut", "radar", "xychart", "mermaid",
+];
+
 /// Parse a diagram body into a [`DiagramModel`].
 ///
 /// `diagram_type` is the (already lowercased) `type` attribute value. Unknown
@@ -534,6 +743,8 @@ pub(crate) fn parse_diagram_source(
         "c4" => parse_c4(content),
         "requirement" => parse_requirement(content),
         "sankey" => parse_sankey(content),
+        "packet" => parse_packet(content),
+        "block" => parse_block(content),
         other => Err(DiagramError {
             line: 0,
             message: format!("unknown diagram type \"{other}\""),
@@ -557,6 +768,97 @@ pub(crate) fn chart_alias(diagram_type: &str) -> Option<crate::types::ChartType>
     }
 }
 
+/// Canonical statement forms of a native diagram type, used by the `L041`
+/// lint to point at the closest valid line. Each entry is `(keyword, form)`
+/// where `keyword` is the statement's leading word, or `""` for statements
+/// that start with an id or label. Empty for unknown types and `mindmap`
+/// (whose only statement is an indented label).
+pub(crate) fn statement_forms(diagram_type: &str) -> &'static [(&'static str, &'static str)] {
+    match diagram_type {
+        "architecture" => &[
+            ("", "id: Label"),
+            ("", "a -> b: Label"),
+            ("group", "group id: Label {"),
+            ("link", "link id: url \"tooltip\""),
+        ],
+        "erd" => &[("", "name: field1, field2 pk"), ("", "a 1--* b: label")],
+        "flowchart" => &[
+            ("", "id: Label"),
+            ("", "id [diamond]: Label"),
+            ("", "a -> b: Label"),
+            ("group", "group id: Label {"),
+            ("style", "style a, b: highlight"),
+            ("link", "link id: url \"tooltip\""),
+        ],
+        "sequence" => &[
+            ("actor", "actor id: Label"),
+            ("participant", "participant id: Label"),
+            ("", "a -> b: text"),
+            ("activate", "activate id"),
+            ("deactivate", "deactivate id"),
+            ("note", "note over a: text"),
+            ("loop", "loop guard {"),
+            ("alt", "alt guard {"),
+            ("opt", "opt guard {"),
+            ("par", "par guard {"),
+            ("critical", "critical guard {"),
+            ("break", "break guard {"),
+            ("rect", "rect {"),
+        ],
+        "gantt" => &[
+            ("section", "section Name"),
+            ("", "Label: start, duration"),
+            ("", "Label: crit, id, after a b, duration"),
+            ("", "Label: milestone, id, start, 0"),
+            ("today", "today start"),
+        ],
+        "state" => &[
+            ("", "id: Label"),
+            ("", "a -> b: event"),
+            ("style", "style a, b: highlight"),
+            ("link", "link id: url \"tooltip\""),
+        ],
+        "class" => &[("", "Name: member, member()"), ("", "A -> B: label")],
+        "timeline" => &[("", "2026-01: Label")],
+        "journey" => &[("section", "section Name"), ("", "Label: score")],
+        "quadrant" => &[
+            ("x-axis", "x-axis Low --> High"),
+            ("y-axis", "y-axis Low --> High"),
+            ("quadrant-1", "quadrant-1: Label"),
+            ("", "Name: 0.3, 0.7"),
+        ],
+        "kanban" => &[("column", "column Name")],
+        "usecase" => &[("actor", "actor id: Label"), ("usecase", "usecase id: Label"), ("", "actor -> id")],
+        "gitgraph" => &[
+            ("commit", "commit: label"),
+            ("branch", "branch name"),
+            ("checkout", "checkout name"),
+            ("merge", "merge name"),
+        ],
+        "c4" => &[
+            ("person", "person id: Label"),
+            ("system", "system id: Label"),
+            ("container", "container id: Label: tech"),
+            ("boundary", "boundary Name {"),
+            ("", "a -> b: label"),
+        ],
+        "requirement" => &[
+            ("requirement", "requirement id: Label"),
+            ("element", "element id: Label"),
+            ("", "a -> b: satisfies"),
+        ],
+        "sankey" => &[("", "Source -> Target: value")],
+        "packet" => &[("", "0-15: Label"), ("", "+8: Label")],
+        "block" => &[
+            ("columns", "columns 3"),
+            ("space", "space [2]"),
+            ("", "id [2]: Label"),
+            ("", "a -> b: Label"),
+        ],
+        _ => &[],
+    }
+}
+
 /// True for characters allowed in node/entity ids: `[A-Za-z0-9_-]`.
 fn is_id_char(c: char) -> bool {
     c.is_ascii_alphanumeric() || c == '_' || c == '-'
@@ -597,12 +899,248 @@ fn parse_label_suffix(rest: &str, line_no: usize) -> Result<Option<String>, Diag
     }
 }
 
-/// Parse an `architecture` body: node lines (`id: Label`) and edge lines
-/// (`a -> b`, `a <-> b`, optional `: Label`). Ids referenced in edges without
-/// a node line are auto-declared with label = id.
+/// Is `line` a `style targets: emphasis` statement? `style` is also a valid
+/// node id, so `style: Label`, `style [diamond]: Label` and `style -> b` stay
+/// node and edge lines.
+fn is_style_line(line: &str) -> bool {
+    strip_keyword(line, "style").is_some_and(|rest| !rest.trim_start().starts_with([':', '-', '<', '[']))
+}
+
+/// A `style a, b -> c: danger` statement: the nodes and edges it names, its
+/// emphasis, and its line. Rules apply once the body is parsed, so a style
+/// may precede the declarations it refers to.
+struct StyleRule {
+    nodes: Vec<String>,
+    edges: Vec<(String, String)>,
+    emphasis: Emphasis,
+    line_no: usize,
+}
+
+/// Parse a `style targets: emphasis` line. Targets are comma-separated node
+/// ids or `a -> b` edge references.
+fn parse_style_rule(line: &str, line_no: usize) -> Result<StyleRule, DiagramError> {
+    let rest = strip_keyword(line, "style").unwrap_or_default();
+    let Some((targets, emphasis)) = rest.rsplit_once(':') else {
+        return Err(err(line_no, "expected `style a, b: highlight|muted|danger|success`"));
+    };
+    let emphasis = emphasis.trim();
+    let Some(emphasis) = Emphasis::parse(emphasis) else {
+        return Err(err(
+            line_no,
+            format!("unknown emphasis \"{emphasis}\" (expected highlight, muted, danger or success)"),
+        ));
+    };
+    let mut rule = StyleRule { nodes: Vec::new(), edges: Vec::new(), emphasis, line_no };
+    for target in targets.split(',') {
+        let target = target.trim();
+        let (from, after) = split_leading_id(target);
+        let after = after.trim_start();
+        if from.is_empty() {
+            return Err(err(line_no, format!("expected node id or `a -> b` to style, found \"{target}\"")));
+        }
+        if after.is_empty() {
+            rule.nodes.push(from.to_string());
+            continue;
+        }
+        let (to, tail) = match after.strip_prefix("->") {
+            Some(after_arrow) => split_leading_id(after_arrow.trim_start()),
+            None => ("", after),
+        };
+        if to.is_empty() || !tail.trim().is_empty() {
+            return Err(err(line_no, format!("expected node id or `a -> b` to style, found \"{target}\"")));
+        }
+        rule.edges.push((from.to_string(), to.to_string()));
+    }
+    Ok(rule)
+}
+
+/// Resolve `style` rules to per-node and per-edge emphasis (later rules win;
+/// an edge reference styles every parallel `a -> b` edge). Naming a node or
+/// edge the body never declares is an error at the rule's line.
+#[allow(clippy::type_complexity)]
+fn resolve_styles(
+    rules: &[StyleRule],
+    node_ids: &[&str],
+    edge_ends: &[(&str, &str)],
+) -> Result<(Vec<Option<Emphasis>>, Vec<Option<Emphasis>>), DiagramError> {
+    let mut nodes = vec![None; node_ids.len()];
+    let mut edges = vec![None; edge_ends.len()];
+    for rule in rules {
+        for id in &rule.nodes {
+            let Some(i) = node_ids.iter().position(|n| n == id) else {
+                return Err(err(rule.line_no, format!("style names unknown node \"{id}\"")));
+            };
+            nodes[i] = Some(rule.emphasis);
+        }
+        for (from, to) in &rule.edges {
+            let mut found = false;
+            for (k, _) in edge_ends.iter().enumerate().filter(|(_, (f, t))| f == from && t == to) {
+                edges[k] = Some(rule.emphasis);
+                found = true;
+            }
+            if !found {
+                return Err(err(rule.line_no, format!("style names unknown edge \"{from} -> {to}\"")));
+            }
+        }
+    }
+    Ok((nodes, edges))
+}
+
+/// URL schemes a `link` may use. Scheme-less urls (`/docs/auth`,
+/// `#section`) are relative and always allowed; anything else — notably
+/// `javascript:` and `data:` — is rejected at parse time.
+const LINK_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+/// Is `line` a `link id: url` statement? `link` is also a valid node id, so
+/// `link: Label`, `link [diamond]: Label` and `link -> b` stay node and edge
+/// lines.
+fn is_link_line(line: &str) -> bool {
+    strip_keyword(line, "link").is_some_and(|rest| !rest.trim_start().starts_with([':', '-', '<', '[']))
+}
+
+/// Parse a `link id: url "tooltip"` line (tooltip optional) into the node id,
+/// its link and the line number, for resolution once the body is parsed.
+fn parse_link(line: &str, line_no: usize) -> Result<(String, DiagramLink, usize), DiagramError> {
+    let rest = strip_keyword(line, "link").unwrap_or_default().trim_start();
+    let (id, after) = split_leading_id(rest);
+    let Some(target) = after.trim_start().strip_prefix(':') else {
+        return Err(err(line_no, "expected `link id: url \"tooltip\"`"));
+    };
+    if id.is_empty() {
+        return Err(err(line_no, "expected node id after `link`"));
+    }
+    let target = target.trim();
+    let (url, tooltip) = match target.split_once(char::is_whitespace) {
+        Some((url, tail)) => {
+            let tail = tail.trim();
+            match tail.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+                Some(tooltip) if !tooltip.is_empty() => (url, Some(tooltip.to_string())),
+                _ => return Err(err(line_no, format!("expected a quoted tooltip after the url, found \"{tail}\""))),
+            }
+        }
+        None => (target, None),
+    };
+    if url.is_empty() {
+        return Err(err(line_no, format!("expected url after \"{id}:\"")));
+    }
+    if !is_safe_link(url) {
+        return Err(err(
+            line_no,
+            format!("unsupported link url \"{url}\" (expected http, https, mailto or a relative path)"),
+        ));
+    }
+    Ok((id.to_string(), DiagramLink { url: url.to_string(), tooltip }, line_no))
+}
+
+/// True when `url` is relative or uses a [`LINK_SCHEMES`] scheme. A scheme
+/// is the text before a `:` that precedes any `/`, `?` or `#`.
+pub(crate) fn is_safe_link(url: &str) -> bool {
+    if url.chars().any(|c| c.is_control() || c.is_whitespace()) {
+        return false;
+    }
+    let scheme_end = url.find([':', '/', '?', '#']);
+    match scheme_end {
+        Some(i) if url[i..].starts_with(':') => LINK_SCHEMES.iter().any(|s| s.eq_ignore_ascii_case(&url[..i])),
+        _ => true,
+    }
+}
+
+/// Resolve parsed `link` statements to per-node links (a later link for the
+/// same node wins). Naming a node the body never declares is an error at
+/// the link's line.
+fn resolve_links(
+    links: Vec<(String, DiagramLink, usize)>,
+    node_ids: &[&str],
+) -> Result<Vec<Option<DiagramLink>>, DiagramError> {
+    let mut resolved = vec![None; node_ids.len()];
+    for (id, link, line_no) in links {
+        let Some(i) = node_ids.iter().position(|n| *n == id) else {
+            return Err(err(line_no, format!("link names unknown node \"{id}\"")));
+        };
+        resolved[i] = Some(link);
+    }
+    Ok(resolved)
+}
+
+/// Is `line` a `group id: Label {` opener? `group` is also a valid node id,
+/// so `group: Label` and `group -> b` stay node and edge lines.
+fn is_group_line(line: &str) -> bool {
+    strip_keyword(line, "group").is_some_and(|rest| !rest.trim_start().starts_with([':', '-', '<']))
+}
+
+/// Parse a `group id: Label {` opener (label optional, defaults to the id)
+/// and push it onto `groups`, returning its index. Groups do not nest and
+/// ids must be unique.
+fn parse_group_open(
+    line: &str,
+    line_no: usize,
+    groups: &mut Vec<DiagramGroup>,
+    open: Option<usize>,
+) -> Result<usize, DiagramError> {
+    if open.is_some() {
+        return Err(err(line_no, "groups cannot nest"));
+    }
+    let rest = strip_keyword(line, "group").expect("checked by is_group_line");
+    let Some(head) = rest.trim().strip_suffix('{') else {
+        return Err(err(line_no, "expected `{` at end of group line"));
+    };
+    let (id, after) = split_leading_id(head.trim());
+    if id.is_empty() {
+        return Err(err(line_no, "expected group id"));
+    }
+    let after = after.trim();
+    let label = if after.is_empty() {
+        ""
+    } else {
+        match after.strip_prefix(':') {
+            Some(label) => label.trim(),
+            None => return Err(err(line_no, format!("expected `: Label` or `{{` after \"{id}\""))),
+        }
+    };
+    if groups.iter().any(|g| g.id == id) {
+        return Err(err(line_no, format!("duplicate group \"{id}\"")));
+    }
+    groups.push(DiagramGroup {
+        id: id.to_string(),
+        label: if label.is_empty() { id.to_string() } else { label.to_string() },
+    });
+    Ok(groups.len() - 1)
+}
+
+/// Move an already-declared node into the open group for a bare `id` member
+/// line. A node belongs to at most one group.
+fn join_group(
+    current: &mut Option<usize>,
+    open: usize,
+    id: &str,
+    groups: &[DiagramGroup],
+    line_no: usize,
+) -> Result<(), DiagramError> {
+    match *current {
+        Some(g) if g != open => Err(err(
+            line_no,
+            format!("node \"{id}\" is already in group \"{}\"", groups[g].id),
+        )),
+        _ => {
+            *current = Some(open);
+            Ok(())
+        }
+    }
+}
+
+/// Parse an `architecture` body: node lines (`id: Label`), edge lines
+/// (`a -> b`, `a <-> b`, optional `: Label`) and groups. Ids referenced in
+/// edges without a node line are auto-declared with label = id.
+///
+/// A group (`group id: Label {` … `}`, one nesting level) clusters the
+/// nodes declared inside it; a bare `id` line inside a group adds a node
+/// declared elsewhere (or auto-declares it). Edges may appear anywhere.
 fn parse_architecture(content: &str) -> Result<DiagramModel, DiagramError> {
     let mut nodes: Vec<ArchNode> = Vec::new();
+    let mut groups: Vec<DiagramGroup> = Vec::new();
     let mut edges: Vec<ArchEdge> = Vec::new();
+    let mut links: Vec<(String, DiagramLink, usize)> = Vec::new();
+    let mut open_group: Option<usize> = None;
 
     for (idx, raw) in content.lines().enumerate() {
         let line_no = idx + 1;
@@ -611,12 +1149,43 @@ fn parse_architecture(content: &str) -> Result<DiagramModel, DiagramError> {
             continue;
         }
 
+        if is_group_line(line) {
+            open_group = Some(parse_group_open(line, line_no, &mut groups, open_group)?);
+            continue;
+        }
+        if is_link_line(line) {
+            links.push(parse_link(line, line_no)?);
+            continue;
+        }
+        if line == "}" {
+            if open_group.take().is_none() {
+                return Err(err(line_no, "`}` without an open group"));
+            }
+            continue;
+        }
+
         let (id, rest) = split_leading_id(line);
         if id.is_empty() {
             return Err(err(line_no, format!("expected node or edge, found \"{line}\"")));
         }
         let rest = rest.trim_start();
 
+        // A bare id inside a group is a membership line.
+        if rest.is_empty()
+            && let Some(open) = open_group
+        {
+            match nodes.iter_mut().find(|n| n.id == id) {
+                Some(node) => join_group(&mut node.group, open, id, &groups, line_no)?,
+                None => nodes.push(ArchNode {
+                    id: id.to_string(),
+                    label: id.to_string(),
+                    group: Some(open),
+                    link: None,
+                }),
+            }
+            continue;
+        }
+
         // Classifier: `:` right after the id makes it a node; an arrow makes
         // it an edge. Node labels are free text, so `a: Uses -> b` is a node.
         if let Some(label) = rest.strip_prefix(':') {
@@ -625,6 +1194,8 @@ fn parse_architecture(content: &str) -> Result<DiagramModel, DiagramError> {
                 id: id.to_string(),
                 // Empty label falls back to the id so every box has text.
                 label: if label.is_empty() { id.to_string() } else { label.to_string() },
+                group: open_group,
+                link: None,
             });
             continue;
         }
@@ -662,12 +1233,20 @@ fn parse_architecture(content: &str) -> Result<DiagramModel, DiagramError> {
                 nodes.push(ArchNode {
                     id: id.clone(),
                     label: id.clone(),
+                    group: None,
+                    link: None,
                 });
             }
         }
     }
 
-    Ok(DiagramModel::Architecture { nodes, edges })
+    let node_ids: Vec<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+    let node_links = resolve_links(links, &node_ids)?;
+    for (node, link) in nodes.iter_mut().zip(node_links) {
+        node.link = link;
+    }
+
+    Ok(DiagramModel::Architecture { nodes, groups, edges })
 }
 
 /// Strip a leading cardinality token (`1--1`, `1--*`, `*--1`, `*--*`) off `s`.
@@ -802,6 +1381,10 @@ fn parse_erd_fields(list: &str, line_no: usize) -> Result<Vec<ErdField>, Diagram
 /// - node:  `id [diamond]: Label`  — decision; shapes: `box`, `diamond`,
 ///   `decision` (=diamond), `rounded`, `terminator` (=rounded), `round`
 /// - edge:  `a -> b`  or  `a -> b: Label`
+/// - group: `group id: Label {` … `}` — clusters the nodes declared inside
+///   (one nesting level); a bare `id` line inside adds an existing node
+/// - style: `style a, b: danger` / `style a -> b: muted` — emphasis
+///   `highlight`, `muted`, `danger` or `success` on nodes and edges
 ///
 /// Edge endpoints with no node line are auto-declared (label = id, box shape)
 /// in first-reference order, so layout stays deterministic.
@@ -809,15 +1392,21 @@ fn parse_erd_fields(list: &str, line_no: usize) -> Result<Vec<ErdField>, Diagram
 /// Example:
 /// ```text
 /// start [terminator]: Start
-/// check [diamond]: Valid?
-/// save: Persist
+/// group review: Review {
+///   check [diamond]: Valid?
+///   save: Persist
+/// }
 /// start -> check
 /// check -> save: yes
 /// check -> start: no
 /// ```
 fn parse_flowchart(content: &str) -> Result<DiagramModel, DiagramError> {
     let mut nodes: Vec<FlowNode> = Vec::new();
+    let mut groups: Vec<DiagramGroup> = Vec::new();
     let mut edges: Vec<FlowEdge> = Vec::new();
+    let mut styles: Vec<StyleRule> = Vec::new();
+    let mut links: Vec<(String, DiagramLink, usize)> = Vec::new();
+    let mut open_group: Option<usize> = None;
 
     for (idx, raw) in content.lines().enumerate() {
         let line_no = idx + 1;
@@ -826,12 +1415,49 @@ fn parse_flowchart(content: &str) -> Result<DiagramModel, DiagramError> {
             continue;
         }
 
+        if is_group_line(line) {
+            open_group = Some(parse_group_open(line, line_no, &mut groups, open_group)?);
+            continue;
+        }
+        if is_style_line(line) {
+            styles.push(parse_style_rule(line, line_no)?);
+            continue;
+        }
+        if is_link_line(line) {
+            links.push(parse_link(line, line_no)?);
+            continue;
+        }
+        if line == "}" {
+            if open_group.take().is_none() {
+                return Err(err(line_no, "`}` without an open group"));
+            }
+            continue;
+        }
+
         let (id, rest) = split_leading_id(line);
         if id.is_empty() {
             return Err(err(line_no, format!("expected node or edge, found \"{line}\"")));
         }
         let rest = rest.trim_start();
 
+        // A bare id inside a group is a membership line.
+        if rest.is_empty()
+            && let Some(open) = open_group
+        {
+            match nodes.iter_mut().find(|n| n.id == id) {
+                Some(node) => join_group(&mut node.group, open, id, &groups, line_no)?,
+                None => nodes.push(FlowNode {
+                    id: id.to_string(),
+                    label: id.to_string(),
+                    shape: FlowShape::Box,
+                    group: Some(open),
+                    emphasis: None,
+                    link: None,
+                }),
+            }
+            continue;
+        }
+
         // Optional `[shape]` between id and `:` makes this a node declaration.
         if let Some(after_bracket) = rest.strip_prefix('[') {
             let Some(close) = after_bracket.find(']') else {
@@ -849,6 +1475,7 @@ fn parse_flowchart(content: &str) -> Result<DiagramModel, DiagramError> {
                 id,
                 if label.is_empty() { id } else { label },
                 shape,
+                open_group,
             );
             continue;
         }
@@ -861,6 +1488,7 @@ fn parse_flowchart(content: &str) -> Result<DiagramModel, DiagramError> {
                 id,
                 if label.is_empty() { id } else { label },
                 FlowShape::Box,
+                open_group,
             );
             continue;
         }
@@ -878,6 +1506,7 @@ fn parse_flowchart(content: &str) -> Result<DiagramModel, DiagramError> {
             from: id.to_string(),
             to: to.to_string(),
             label,
+            emphasis: None,
         });
     }
 
@@ -888,12 +1517,29 @@ fn parse_flowchart(content: &str) -> Result<DiagramModel, DiagramError> {
                     id: id.clone(),
                     label: id.clone(),
                     shape: FlowShape::Box,
+                    group: None,
+                    emphasis: None,
+                    link: None,
                 });
             }
         }
     }
 
-    Ok(DiagramModel::Flowchart { nodes, edges })
+    let node_ids: Vec<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+    let edge_ends: Vec<(&str, &str)> = edges.iter().map(|e| (e.from.as_str(), e.to.as_str())).collect();
+    let (node_emphasis, edge_emphasis) = resolve_styles(&styles, &node_ids, &edge_ends)?;
+    let node_links = resolve_links(links, &node_ids)?;
+    for (node, emphasis) in nodes.iter_mut().zip(node_emphasis) {
+        node.emphasis = emphasis;
+    }
+    for (edge, emphasis) in edges.iter_mut().zip(edge_emphasis) {
+        edge.emphasis = emphasis;
+    }
+    for (node, link) in nodes.iter_mut().zip(node_links) {
+        node.link = link;
+    }
+
+    Ok(DiagramModel::Flowchart { nodes, groups, edges })
 }
 
 /// Map a shape keyword to a [`FlowShape`].
@@ -911,15 +1557,22 @@ fn parse_flow_shape(tok: &str, line_no: usize) -> Result<FlowShape, DiagramError
 
 /// Insert a flowchart node, or update the shape/label of an existing
 /// auto-declared one (re-declaration wins, keeping first-reference order).
-fn push_flow_node(nodes: &mut Vec<FlowNode>, id: &str, label: &str, shape: FlowShape) {
+/// A re-declaration inside a group moves the node into it.
+fn push_flow_node(nodes: &mut Vec<FlowNode>, id: &str, label: &str, shape: FlowShape, group: Option<usize>) {
     if let Some(existing) = nodes.iter_mut().find(|n| n.id == id) {
         existing.label = label.to_string();
         existing.shape = shape;
+        if group.is_some() {
+            existing.group = group;
+        }
     } else {
         nodes.push(FlowNode {
             id: id.to_string(),
             label: label.to_string(),
             shape,
+            group,
+            emphasis: None,
+            link: None,
         });
     }
 }
@@ -931,13 +1584,19 @@ fn push_flow_node(nodes: &mut Vec<FlowNode>, id: &str, label: &str, shape: FlowS
 /// Parse a `sequence` body.
 ///
 /// DSL (line-oriented, top-down timeline):
-/// - actor:   `actor id: Display Name`  (alias: `participant`)
-/// - message: `a -> b: text`   — solid (sync)
-/// - message: `a --> b: text`  — dashed (async / return)
-/// - lifebar: `activate id` / `deactivate id`
+/// - actor:    `actor id: Display Name`  (alias: `participant`)
+/// - message:  `a -> b: text`   — solid (sync)
+/// - message:  `a --> b: text`  — dashed (async / return)
+/// - lifebar:  `activate id` / `deactivate id`
+/// - note:     `note left of a: text`, `note right of a: text`,
+///   `note over a: text`, `note over a, b: text`
+/// - fragment: `alt guard {` … `}` (also `loop`, `opt`, `par`, `critical`,
+///   `break`, `rect`); the guard is optional free text. Further sections
+///   start with `else guard` (alt), `and guard` (par) or `option guard`
+///   (critical). Fragments nest.
 ///
-/// Actors used in messages without an `actor` line are auto-declared in
-/// first-reference order.
+/// Actors used in messages, lifebars or notes without an `actor` line are
+/// auto-declared in first-reference order.
 ///
 /// Example:
 /// ```text
@@ -945,12 +1604,19 @@ fn push_flow_node(nodes: &mut Vec<FlowNode>, id: &str, label: &str, shape: FlowS
 /// actor api: API
 /// user -> api: POST /login
 /// activate api
-/// api --> user: 200 OK
+/// alt valid password {
+///   api --> user: 200 OK
+/// else
+///   api --> user: 401
+///   note over user, api: retry allowed
+/// }
 /// deactivate api
 /// ```
 fn parse_sequence(content: &str) -> Result<DiagramModel, DiagramError> {
     let mut actors: Vec<SeqActor> = Vec::new();
     let mut events: Vec<SeqEvent> = Vec::new();
+    // Open fragments, innermost last: opener line, kind, sections so far.
+    let mut open: Vec<(usize, FragmentKind, Vec<SeqSection>)> = Vec::new();
 
     let declare = |actors: &mut Vec<SeqActor>, id: &str| {
         if !actors.iter().any(|a| a.id == id) {
@@ -960,6 +1626,13 @@ fn parse_sequence(content: &str) -> Result<DiagramModel, DiagramError> {
             });
         }
     };
+    // Events land in the innermost open fragment's last section.
+    let push = |events: &mut Vec<SeqEvent>, open: &mut Vec<(usize, FragmentKind, Vec<SeqSection>)>, ev: SeqEvent| {
+        match open.last_mut().and_then(|(_, _, sections)| sections.last_mut()) {
+            Some(section) => section.events.push(ev),
+            None => events.push(ev),
+        }
+    };
 
     for (idx, raw) in content.lines().enumerate() {
         let line_no = idx + 1;
@@ -968,8 +1641,50 @@ fn parse_sequence(content: &str) -> Result<DiagramModel, DiagramError> {
             continue;
         }
 
+        // Fragment structure: `kind guard {`, section separators, `}`.
+        if line.ends_with('{')
+            && let Some((kind, rest)) = FragmentKind::ALL
+                .iter()
+                .find_map(|(kind, word)| Some((*kind, frame_keyword(line, word)?)))
+        {
+            let guard = rest.strip_suffix('{').expect("checked above").trim();
+            open.push((
+                line_no,
+                kind,
+                vec![SeqSection {
+                    guard: (!guard.is_empty()).then(|| guard.to_string()),
+                    events: Vec::new(),
+                }],
+            ));
+            continue;
+        }
+        if let Some((word, rest)) = ["else", "and", "option"]
+            .iter()
+            .find_map(|word| Some((*word, frame_keyword(line, word)?)))
+        {
+            let Some((_, kind, sections)) = open.last_mut() else {
+                return Err(err(line_no, format!("`{word}` outside a fragment")));
+            };
+            if kind.separator() != Some(word) {
+                return Err(err(line_no, format!("`{word}` is not valid inside `{}`", kind.word())));
+            }
+            let guard = rest.trim();
+            sections.push(SeqSection {
+                guard: (!guard.is_empty()).then(|| guard.to_string()),
+                events: Vec::new(),
+            });
+            continue;
+        }
+        if line == "}" {
+            let Some((_, kind, sections)) = open.pop() else {
+                return Err(err(line_no, "`}` without an open fragment"));
+            };
+            push(&mut events, &mut open, SeqEvent::Fragment { kind, sections });
+            continue;
+        }
+
         // Keyword lines: `actor`/`participant id: Label`, `activate id`,
-        // `deactivate id`.
+        // `deactivate id`, `note … : text`.
         if let Some(rest) = strip_keyword(line, "actor").or_else(|| strip_keyword(line, "participant")) {
             let (id, after) = split_leading_id(rest.trim_start());
             if id.is_empty() {
@@ -992,7 +1707,7 @@ fn parse_sequence(content: &str) -> Result<DiagramModel, DiagramError> {
                 return Err(err(line_no, "expected actor id after `activate`"));
             }
             declare(&mut actors, id);
-            events.push(SeqEvent::Activate(id.to_string()));
+            push(&mut events, &mut open, SeqEvent::Activate(id.to_string()));
             continue;
         }
         if let Some(rest) = strip_keyword(line, "deactivate") {
@@ -1001,7 +1716,17 @@ fn parse_sequence(content: &str) -> Result<DiagramModel, DiagramError> {
                 return Err(err(line_no, "expected actor id after `deactivate`"));
             }
             declare(&mut actors, id);
-            events.push(SeqEvent::Deactivate(id.to_string()));
+            push(&mut events, &mut open, SeqEvent::Deactivate(id.to_string()));
+            continue;
+        }
+        if let Some(rest) = frame_keyword(line, "note") {
+            let note = parse_seq_note(rest, line_no)?;
+            if let SeqEvent::Note { actors: ids, .. } = &note {
+                for id in ids {
+                    declare(&mut actors, id);
+                }
+            }
+            push(&mut events, &mut open, note);
             continue;
         }
 
@@ -1025,17 +1750,71 @@ fn parse_sequence(content: &str) -> Result<DiagramModel, DiagramError> {
         let label = parse_label_suffix(after_to, line_no)?;
         declare(&mut actors, from);
         declare(&mut actors, to);
-        events.push(SeqEvent::Message {
-            from: from.to_string(),
-            to: to.to_string(),
-            label,
-            dashed,
-        });
+        push(
+            &mut events,
+            &mut open,
+            SeqEvent::Message {
+                from: from.to_string(),
+                to: to.to_string(),
+                label,
+                dashed,
+            },
+        );
+    }
+
+    if let Some((opener, kind, _)) = open.last() {
+        return Err(err(*opener, format!("unclosed `{}` fragment", kind.word())));
     }
 
     Ok(DiagramModel::Sequence { actors, events })
 }
 
+/// Match a sequence statement keyword (`alt`, `else`, `note`, …): the bare
+/// keyword, or the keyword + whitespace, returning the remainder. Actor ids
+/// may reuse these words, so a remainder starting with an arrow is a message.
+fn frame_keyword<'a>(line: &'a str, keyword: &str) -> Option<&'a str> {
+    if line == keyword {
+        return Some("");
+    }
+    strip_keyword(line, keyword).filter(|rest| !rest.trim_start().starts_with('-'))
+}
+
+/// Parse the remainder of a `note` line: `left of a: text`,
+/// `right of a: text`, `over a: text` or `over a, b: text`.
+fn parse_seq_note(rest: &str, line_no: usize) -> Result<SeqEvent, DiagramError> {
+    let rest = rest.trim_start();
+    let (placement, targets) = if let Some(r) = rest.strip_prefix("left of ") {
+        (NotePlacement::LeftOf, r)
+    } else if let Some(r) = rest.strip_prefix("right of ") {
+        (NotePlacement::RightOf, r)
+    } else if let Some(r) = rest.strip_prefix("over ") {
+        (NotePlacement::Over, r)
+    } else {
+        return Err(err(line_no, "expected `left of`, `right of` or `over` after `note`"));
+    };
+    let Some((targets, text)) = targets.split_once(':') else {
+        return Err(err(line_no, "expected `: text` after the note's actors"));
+    };
+    let mut ids: Vec<String> = Vec::new();
+    for target in targets.split(',') {
+        let target = target.trim();
+        let (id, after) = split_leading_id(target);
+        if id.is_empty() || !after.is_empty() {
+            return Err(err(line_no, format!("expected actor id in note, found \"{target}\"")));
+        }
+        ids.push(id.to_string());
+    }
+    let max = if placement == NotePlacement::Over { 2 } else { 1 };
+    if ids.len() > max {
+        return Err(err(line_no, "only `note over` may span two actors"));
+    }
+    Ok(SeqEvent::Note {
+        placement,
+        actors: ids,
+        text: text.trim().to_string(),
+    })
+}
+
 /// Strip a leading whitespace-delimited keyword (e.g. `actor`) returning the
 /// remainder, or `None` if `line` does not start with `keyword` + space.
 fn strip_keyword<'a>(line: &'a str, keyword: &str) -> Option<&'a str> {
@@ -1047,6 +1826,39 @@ fn strip_keyword<'a>(line: &'a str, keyword: &str) -> Option<&'a str> {
     }
 }
 
+// ------------------------------------------------------------------
+// Ambient "today" for gantt charts (thread-local, set by the caller)
+// ------------------------------------------------------------------
+
+thread_local! {
+    static TODAY: Cell<Option<i64>> = const { Cell::new(None) };
+}
+
+/// RAII guard that clears the ambient diagram date when dropped.
+pub struct TodayScope {
+    _private: (),
+}
+
+impl Drop for TodayScope {
+    fn drop(&mut self) {
+        TODAY.with(|t| t.set(None));
+    }
+}
+
+/// Install `date` (`YYYY-MM-DD`) as "today" for gantt charts rendered on the
+/// current thread for the lifetime of the returned guard. Dated charts draw
+/// the today marker there; a `today` line in the body overrides it, and
+/// charts in plain numeric units ignore it. `None` when `date` is not a valid
+/// date. The module has no clock of its own, so without a scope (and without
+/// a `today` line) no marker is drawn.
+pub fn install_diagram_today(date: &str) -> Option<TodayScope> {
+    let (day, true) = parse_gantt_value(date.trim(), 0).ok()? else {
+        return None;
+    };
+    TODAY.with(|t| t.set(Some(day)));
+    Some(TodayScope { _private: () })
+}
+
 // ------------------------------------------------------------------
 // DSL parsing — gantt
 // ------------------------------------------------------------------
@@ -1057,22 +1869,45 @@ fn strip_keyword<'a>(line: &'a str, keyword: &str) -> Option<&'a str> {
 /// - section: `section Name`           — groups following tasks (optional)
 /// - task:    `Label: start, duration` — numeric units, or
 /// - task:    `Label: 2026-01-05, 7`   — ISO start date + duration in days
+/// - task:    `Label: [tags,] [id,] start, duration` — `tags` are any of
+///   `done`, `active`, `crit` and `milestone`; `id` names the task for
+///   dependencies; `start` may be `after a b` (the latest end of tasks `a`
+///   and `b`)
+/// - today:   `today 2026-01-12`       — draws a "today" line, overriding
+///   the caller's [`install_diagram_today`] date
 ///
-/// All tasks must use the same convention (numeric or dated); mixing is a
-/// parse error. `Label` may contain spaces (it ends at the last `:`).
+/// All explicit starts (and `today`) must use the same convention (numeric
+/// or dated); mixing is a parse error. A milestone is drawn as a diamond at
+/// its start and ends there, whatever its duration. When several status tags
+/// are given the most urgent wins (`crit`, then `active`, then `done`).
+/// Unknown `after` ids and dependency cycles are parse errors. `Label` ends
+/// at the first `:`.
 ///
 /// Example:
 /// ```text
 /// section Planning
-/// Research: 2026-01-01, 5
-/// Design: 2026-01-06, 3
+/// Research: done, research, 2026-01-01, 5
+/// Design: active, design, after research, 3
 /// section Build
-/// Implementation: 2026-01-09, 10
+/// Implementation: crit, build, after design, 10
+/// Launch: milestone, after build, 0
+/// today 2026-01-07
 /// ```
 fn parse_gantt(content: &str) -> Result<DiagramModel, DiagramError> {
     let mut tasks: Vec<GanttTask> = Vec::new();
+    // Source line of each task, for dependency errors reported after parsing.
+    let mut task_lines: Vec<usize> = Vec::new();
     let mut section: Option<String> = None;
     let mut dated: Option<bool> = None;
+    let mut today: Option<i64> = None;
+    let mut check_units = |is_date: bool, line_no: usize| match dated {
+        None => {
+            dated = Some(is_date);
+            Ok(())
+        }
+        Some(d) if d != is_date => Err(err(line_no, "cannot mix numeric and date start values")),
+        _ => Ok(()),
+    };
 
     for (idx, raw) in content.lines().enumerate() {
         let line_no = idx + 1;
@@ -1087,9 +1922,22 @@ fn parse_gantt(content: &str) -> Result<DiagramModel, DiagramError> {
             continue;
         }
 
-        // `Label: start, duration` — split label at the LAST colon so labels
-        // may contain colons; here we split at the first colon for the label
-        // and parse the remainder as `start, duration`.
+        // `today <start>` has no colon, so it never shadows a task label
+        // that happens to begin with "today".
+        if !line.contains(':')
+            && let Some(rest) = strip_keyword(line, "today")
+        {
+            if today.is_some() {
+                return Err(err(line_no, "duplicate `today` line"));
+            }
+            let (value, is_date) = parse_gantt_value(rest.trim(), line_no)?;
+            check_units(is_date, line_no)?;
+            today = Some(value);
+            continue;
+        }
+
+        // `Label: [tags,] [id,] start, duration` — the label ends at the
+        // first colon; the remainder is a comma-separated field list.
         let Some((label, spec)) = line.split_once(':') else {
             return Err(err(line_no, format!("expected `Label: start, duration`, found \"{line}\"")));
         };
@@ -1097,53 +1945,171 @@ fn parse_gantt(content: &str) -> Result<DiagramModel, DiagramError> {
         if label.is_empty() {
             return Err(err(line_no, "task label must not be empty"));
         }
-        let Some((start_s, dur_s)) = spec.split_once(',') else {
-            return Err(err(line_no, "expected `start, duration`"));
+        let mut fields: Vec<&str> = spec.split(',').map(str::trim).collect();
+        let mut milestone = false;
+        let mut status: Option<GanttStatus> = None;
+        while let Some(&tag) = fields.first() {
+            let tagged = match tag {
+                "milestone" => {
+                    milestone = true;
+                    None
+                }
+                "crit" => Some(GanttStatus::Crit),
+                "active" => Some(GanttStatus::Active),
+                "done" => Some(GanttStatus::Done),
+                _ => break,
+            };
+            if let Some(tagged) = tagged {
+                status = Some(status.map_or(tagged, |s| gantt_urgent(s, tagged)));
+            }
+            fields.remove(0);
+        }
+        let (id, start_s, dur_s) = match fields[..] {
+            [start_s, dur_s] => (None, start_s, dur_s),
+            [id, start_s, dur_s] => {
+                if !is_gantt_id(id) {
+                    return Err(err(line_no, format!("invalid task id \"{id}\"")));
+                }
+                if tasks.iter().any(|t| t.id.as_deref() == Some(id)) {
+                    return Err(err(line_no, format!("duplicate task id \"{id}\"")));
+                }
+                (Some(id.to_string()), start_s, dur_s)
+            }
+            _ => return Err(err(line_no, "expected `start, duration`")),
+        };
+        let (start, after) = match strip_keyword(start_s, "after") {
+            Some(rest) => {
+                let after: Vec<String> = rest.split_whitespace().map(String::from).collect();
+                if after.is_empty() {
+                    return Err(err(line_no, "`after` needs at least one task id"));
+                }
+                (0, after)
+            }
+            None => {
+                let (start, is_date) = parse_gantt_value(start_s, line_no)?;
+                check_units(is_date, line_no)?;
+                (start, Vec::new())
+            }
         };
-        let (start, is_date) = parse_gantt_value(start_s.trim(), line_no)?;
         // Duration is always a numeric count of units/days.
         let duration: i64 = dur_s
-            .trim()
             .parse()
-            .map_err(|_| err(line_no, format!("invalid duration \"{}\"", dur_s.trim())))?;
+            .map_err(|_| err(line_no, format!("invalid duration \"{dur_s}\"")))?;
         if duration < 0 {
             return Err(err(line_no, "duration must be non-negative"));
         }
         if duration > GANTT_VALUE_MAX {
-            return Err(err(line_no, format!("duration \"{}\" is out of range", dur_s.trim())));
-        }
-        match dated {
-            None => dated = Some(is_date),
-            Some(d) if d != is_date => {
-                return Err(err(line_no, "cannot mix numeric and date start values"));
-            }
-            _ => {}
+            return Err(err(line_no, format!("duration \"{dur_s}\" is out of range")));
         }
         tasks.push(GanttTask {
             section: section.clone(),
             label: label.to_string(),
+            id,
+            after,
+            milestone,
+            status,
             start,
-            duration,
+            duration: if milestone { 0 } else { duration },
         });
+        task_lines.push(line_no);
     }
 
+    resolve_gantt_starts(&mut tasks, &task_lines)?;
     Ok(DiagramModel::Gantt {
         tasks,
         dated: dated.unwrap_or(false),
+        today,
     })
 }
 
-/// Layout-safe bound for gantt starts and durations (units/days). Values
-/// beyond this would overflow the integer pixel arithmetic in the scene
-/// stage; a bound this generous (±1 billion units ≈ 2.7 million years of
-/// days) rejects only pathological input.
-const GANTT_VALUE_MAX: i64 = 1_000_000_000;
+/// The more urgent of two status tags on one task.
+fn gantt_urgent(a: GanttStatus, b: GanttStatus) -> GanttStatus {
+    let rank = |s| match s {
+        GanttStatus::Done => 0,
+        GanttStatus::Active => 1,
+        GanttStatus::Crit => 2,
+    };
+    if rank(b) > rank(a) { b } else { a }
+}
 
-/// Parse a Gantt start value: either a plain integer (numeric units) or an
-/// ISO `YYYY-MM-DD` date (converted to a day-number). Returns `(value,
-/// is_date)`.
-fn parse_gantt_value(s: &str, line_no: usize) -> Result<(i64, bool), DiagramError> {
-    if let Ok(n) = s.parse::<i64>() {
+/// Task ids are word characters and `-`, and never parse as a start value.
+fn is_gantt_id(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        && s.parse::<i64>().is_err()
+}
+
+/// Resolve every `after` start to the latest end of its dependencies, in
+/// dependency order (Kahn's algorithm). An unknown id or a cycle is an
+/// error on the line of the offending task.
+fn resolve_gantt_starts(tasks: &mut [GanttTask], lines: &[usize]) -> Result<(), DiagramError> {
+    let index = |id: &str| tasks.iter().position(|t| t.id.as_deref() == Some(id));
+    let mut deps: Vec<Vec<usize>> = Vec::with_capacity(tasks.len());
+    for (i, t) in tasks.iter().enumerate() {
+        let mut d = Vec::new();
+        for id in &t.after {
+            match index(id) {
+                Some(j) => d.push(j),
+                None => return Err(err(lines[i], format!("unknown task id \"{id}\" in `after`"))),
+            }
+        }
+        deps.push(d);
+    }
+
+    let mut pending: Vec<usize> = deps.iter().map(Vec::len).collect();
+    let mut ready: Vec<usize> = (0..tasks.len()).filter(|&i| pending[i] == 0).collect();
+    let mut done = 0;
+    while let Some(j) = ready.pop() {
+        done += 1;
+        for i in 0..tasks.len() {
+            for _ in deps[i].iter().filter(|&&d| d == j) {
+                pending[i] -= 1;
+                if pending[i] == 0 {
+                    let start = deps[i].iter().map(|&d| tasks[d].start + tasks[d].duration).max().unwrap_or(0);
+                    if start > GANTT_VALUE_MAX {
+                        return Err(err(lines[i], "start after dependencies is out of range"));
+                    }
+                    tasks[i].start = start;
+                    ready.push(i);
+                }
+            }
+        }
+    }
+    if done == tasks.len() {
+        return Ok(());
+    }
+
+    // Every unresolved task waits on another unresolved one, so walking
+    // unresolved dependencies from the first of them must revisit a task.
+    let first = (0..tasks.len()).find(|&i| pending[i] > 0).unwrap_or(0);
+    let mut path = vec![first];
+    let mut cur = first;
+    while let Some(&next) = deps[cur].iter().find(|&&d| pending[d] > 0) {
+        if let Some(pos) = path.iter().position(|&p| p == next) {
+            let cycle: Vec<&str> = path[pos..]
+                .iter()
+                .chain(std::iter::once(&next))
+                .map(|&k| tasks[k].id.as_deref().unwrap_or(&tasks[k].label))
+                .collect();
+            return Err(err(lines[path[pos]], format!("dependency cycle: {}", cycle.join(" after "))));
+        }
+        path.push(next);
+        cur = next;
+    }
+    Err(err(lines[first], "dependency cycle"))
+}
+
+/// Layout-safe bound for gantt starts and durations (units/days). Values
+/// beyond this would overflow the integer pixel arithmetic in the scene
+/// stage; a bound this generous (±1 billion units ≈ 2.7 million years of
+/// days) rejects only pathological input.
+const GANTT_VALUE_MAX: i64 = 1_000_000_000;
+
+/// Parse a Gantt start value: either a plain integer (numeric units) or an
+/// ISO `YYYY-MM-DD` date (converted to a day-number). Returns `(value,
+/// is_date)`.
+fn parse_gantt_value(s: &str, line_no: usize) -> Result<(i64, bool), DiagramError> {
+    if let Ok(n) = s.parse::<i64>() {
         if !(-GANTT_VALUE_MAX..=GANTT_VALUE_MAX).contains(&n) {
             return Err(err(line_no, format!("start value \"{s}\" is out of range")));
         }
@@ -1200,6 +2166,8 @@ fn civil_from_days(z: i64) -> (i64, i64, i64) {
 /// DSL (line-oriented, top-down layout):
 /// - state:      `id: Label`        — optional; defaults label = id
 /// - transition: `a -> b`  or  `a -> b: event`
+/// - style:      `style a, b: danger` / `style a -> b: muted` — emphasis
+///   `highlight`, `muted`, `danger` or `success`
 /// - `[*]` is the special initial (as a source) / final (as a target) marker.
 ///
 /// States used in transitions without a state line are auto-declared.
@@ -1214,6 +2182,8 @@ fn civil_from_days(z: i64) -> (i64, i64, i64) {
 fn parse_state(content: &str) -> Result<DiagramModel, DiagramError> {
     let mut nodes: Vec<StateNode> = Vec::new();
     let mut transitions: Vec<StateTransition> = Vec::new();
+    let mut styles: Vec<StyleRule> = Vec::new();
+    let mut links: Vec<(String, DiagramLink, usize)> = Vec::new();
 
     // `[*]` maps to a synthetic node id; a `[*]` as a source means initial,
     // as a target means final. We use distinct ids so both can coexist.
@@ -1228,6 +2198,8 @@ fn parse_state(content: &str) -> Result<DiagramModel, DiagramError> {
                 label: if initial || final_ { String::new() } else { id.to_string() },
                 initial,
                 final_,
+                emphasis: None,
+                link: None,
             });
         }
     };
@@ -1239,6 +2211,15 @@ fn parse_state(content: &str) -> Result<DiagramModel, DiagramError> {
             continue;
         }
 
+        if is_style_line(line) {
+            styles.push(parse_style_rule(line, line_no)?);
+            continue;
+        }
+        if is_link_line(line) {
+            links.push(parse_link(line, line_no)?);
+            continue;
+        }
+
         // `[*]` token (source position).
         if let Some(rest) = line.strip_prefix("[*]") {
             let rest = rest.trim_start();
@@ -1257,6 +2238,7 @@ fn parse_state(content: &str) -> Result<DiagramModel, DiagramError> {
                 from: INITIAL.to_string(),
                 to: to.to_string(),
                 label,
+                emphasis: None,
             });
             continue;
         }
@@ -1292,9 +2274,24 @@ fn parse_state(content: &str) -> Result<DiagramModel, DiagramError> {
             from: id.to_string(),
             to: to.to_string(),
             label,
+            emphasis: None,
         });
     }
 
+    let node_ids: Vec<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+    let edge_ends: Vec<(&str, &str)> = transitions.iter().map(|t| (t.from.as_str(), t.to.as_str())).collect();
+    let (node_emphasis, edge_emphasis) = resolve_styles(&styles, &node_ids, &edge_ends)?;
+    let node_links = resolve_links(links, &node_ids)?;
+    for (node, emphasis) in nodes.iter_mut().zip(node_emphasis) {
+        node.emphasis = emphasis;
+    }
+    for (transition, emphasis) in transitions.iter_mut().zip(edge_emphasis) {
+        transition.emphasis = emphasis;
+    }
+    for (node, link) in nodes.iter_mut().zip(node_links) {
+        node.link = link;
+    }
+
     Ok(DiagramModel::State { nodes, transitions })
 }
 
@@ -2408,13 +3405,239 @@ fn parse_sankey(content: &str) -> Result<DiagramModel, DiagramError> {
     Ok(DiagramModel::Sankey { nodes, flows })
 }
 
+// ------------------------------------------------------------------
+// DSL parsing — packet
+// ------------------------------------------------------------------
+
+/// Largest packet accepted, in bits — bounds the canvas at 128 rows.
+const PACKET_MAX_BITS: i64 = 4096;
+
+/// Parse a `packet` body (a bit-field layout, 32 bits per row).
+///
+/// DSL (one field per line, in bit order):
+/// - range:    `start-end: Label` — bits `start..=end`
+/// - one bit:  `bit: Label`
+/// - relative: `+n: Label` — the next `n` bits
+///
+/// Fields must tile the packet from bit 0: each starts where the previous
+/// one ended, so gaps and overlaps are errors. At most 4096 bits.
+///
+/// Example:
+/// ```text
+/// 0-15: Source port
+/// 16-31: Destination port
+/// 32-63: Sequence number
+/// +4: Data offset
+/// ```
+fn parse_packet(content: &str) -> Result<DiagramModel, DiagramError> {
+    let mut fields: Vec<PacketField> = Vec::new();
+    let mut next = 0i64;
+
+    for (idx, raw) in content.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = raw.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some((range, label)) = line.split_once(':') else {
+            return Err(err(line_no, format!("expected `start-end: Label`, found \"{line}\"")));
+        };
+        let range = range.trim();
+        let bit = |s: &str| -> Result<i64, DiagramError> {
+            s.trim()
+                .parse::<i64>()
+                .ok()
+                .filter(|&b| (0..PACKET_MAX_BITS).contains(&b))
+                .ok_or_else(|| err(line_no, format!("invalid bit \"{}\" (expected 0..{PACKET_MAX_BITS})", s.trim())))
+        };
+        let (start, bits) = if let Some(count) = range.strip_prefix('+') {
+            let count = bit(count)?;
+            if count == 0 {
+                return Err(err(line_no, "field must span at least one bit"));
+            }
+            (next, count)
+        } else if let Some((a, b)) = range.split_once('-') {
+            let (a, b) = (bit(a)?, bit(b)?);
+            if b < a {
+                return Err(err(line_no, format!("field ends at bit {b} before it starts at bit {a}")));
+            }
+            (a, b - a + 1)
+        } else {
+            (bit(range)?, 1)
+        };
+        if start != next {
+            return Err(err(line_no, format!("field starts at bit {start}, expected bit {next}")));
+        }
+        if start + bits > PACKET_MAX_BITS {
+            return Err(err(line_no, format!("packet exceeds {PACKET_MAX_BITS} bits")));
+        }
+        let label = label.trim();
+        fields.push(PacketField {
+            start,
+            bits,
+            label: if label.is_empty() { range.to_string() } else { label.to_string() },
+        });
+        next = start + bits;
+    }
+
+    Ok(DiagramModel::Packet { fields })
+}
+
+// ------------------------------------------------------------------
+// DSL parsing — block
+// ------------------------------------------------------------------
+
+/// Widest grid (and widest span) a block diagram accepts.
+const BLOCK_MAX_COLUMNS: usize = 16;
+
+/// Parse an optional `[n]` span suffix after a block id or `space`,
+/// returning the span (1 when absent) and the remainder.
+fn block_span(rest: &str, line_no: usize) -> Result<(usize, &str), DiagramError> {
+    let Some(inner) = rest.trim_start().strip_prefix('[') else {
+        return Ok((1, rest));
+    };
+    let Some((n, after)) = inner.split_once(']') else {
+        return Err(err(line_no, "expected `]` after span"));
+    };
+    match n.trim().parse::<usize>() {
+        Ok(n) if (1..=BLOCK_MAX_COLUMNS).contains(&n) => Ok((n, after)),
+        _ => Err(err(line_no, format!("invalid span \"{}\" (expected 1..={BLOCK_MAX_COLUMNS})", n.trim()))),
+    }
+}
+
+/// Parse a `block` body (blocks laid out on a grid, row by row).
+///
+/// DSL (line-oriented; cells fill the grid in declaration order):
+/// - columns: `columns N` — grid width (default: one row of every cell)
+/// - block:   `id: Label`, bare `id`, or `id [n]: Label` to span `n` columns
+/// - space:   `space` or `space [n]` — an empty cell
+/// - edge:    `a -> b`  or  `a -> b: label`
+///
+/// A cell that does not fit the rest of its row starts the next one. Edge
+/// endpoints never declared are appended as blocks in first-reference order.
+///
+/// Example:
+/// ```text
+/// columns 3
+/// web: Web
+/// api [2]: API gateway
+/// space
+/// db: Database
+/// web -> api
+/// api -> db: SQL
+/// ```
+fn parse_block(content: &str) -> Result<DiagramModel, DiagramError> {
+    let mut columns: Option<usize> = None;
+    let mut cells: Vec<BlockCell> = Vec::new();
+    let mut edges: Vec<BlockEdge> = Vec::new();
+
+    for (idx, raw) in content.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = raw.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(rest) = strip_keyword(line, "columns") {
+            if columns.is_some() {
+                return Err(err(line_no, "duplicate `columns`"));
+            }
+            match rest.trim().parse::<usize>() {
+                Ok(n) if (1..=BLOCK_MAX_COLUMNS).contains(&n) => columns = Some(n),
+                _ => {
+                    return Err(err(
+                        line_no,
+                        format!("invalid column count \"{}\" (expected 1..={BLOCK_MAX_COLUMNS})", rest.trim()),
+                    ));
+                }
+            }
+            continue;
+        }
+        // `space` is also a valid block id, so `space: Label` and
+        // `space -> b` stay block and edge lines.
+        if let Some(rest) = line.strip_prefix("space")
+            && (rest.is_empty() || rest.trim_start().starts_with('['))
+        {
+            let (span, after) = block_span(rest, line_no)?;
+            if !after.trim().is_empty() {
+                return Err(err(line_no, format!("unexpected \"{}\" after space", after.trim())));
+            }
+            cells.push(BlockCell { id: None, label: String::new(), span });
+            continue;
+        }
+
+        let (id, rest) = split_leading_id(line);
+        if id.is_empty() {
+            return Err(err(line_no, format!("expected block, space or edge, found \"{line}\"")));
+        }
+        // Edge line: `a -> b`, optional `: label`.
+        if let Some(after_arrow) = rest.trim_start().strip_prefix("->") {
+            let (to, after_to) = split_leading_id(after_arrow.trim_start());
+            if to.is_empty() {
+                return Err(err(line_no, "expected target id after arrow"));
+            }
+            let label = parse_label_suffix(after_to, line_no)?;
+            edges.push(BlockEdge {
+                from: id.to_string(),
+                to: to.to_string(),
+                label,
+            });
+            continue;
+        }
+        // Block line: `id`, `id: Label`, `id [n]: Label`.
+        let (span, after) = block_span(rest, line_no)?;
+        let after = after.trim();
+        let label = if after.is_empty() {
+            ""
+        } else {
+            match after.strip_prefix(':') {
+                Some(label) => label.trim(),
+                None => return Err(err(line_no, format!("expected `: Label` or `->` after \"{id}\""))),
+            }
+        };
+        if cells.iter().any(|c| c.id.as_deref() == Some(id)) {
+            return Err(err(line_no, format!("duplicate block \"{id}\"")));
+        }
+        cells.push(BlockCell {
+            id: Some(id.to_string()),
+            label: if label.is_empty() { id.to_string() } else { label.to_string() },
+            span,
+        });
+    }
+
+    // Auto-declare edge endpoints without a block line, in first-reference
+    // order so layout stays deterministic.
+    for edge in &edges {
+        for id in [&edge.from, &edge.to] {
+            if !cells.iter().any(|c| c.id.as_ref() == Some(id)) {
+                cells.push(BlockCell {
+                    id: Some(id.clone()),
+                    label: id.clone(),
+                    span: 1,
+                });
+            }
+        }
+    }
+
+    Ok(DiagramModel::Block { columns, cells, edges })
+}
+
 // ------------------------------------------------------------------
 // SVG rendering — shared geometry
 // ------------------------------------------------------------------
 
-/// Approximate character width in px at the 13px UI font — no text measuring
-/// is available, so boxes are sized by label length.
-const CHAR_W: i64 = 8;
+/// The 13px UI font node labels are drawn in; the smaller sizes measure the
+/// secondary text (field rows, annotations, axis labels).
+const LABEL_FONT: TextMeasurer = TextMeasurer::new(13);
+const SMALL_FONT: TextMeasurer = TextMeasurer::new(12);
+const NOTE_FONT: TextMeasurer = TextMeasurer::new(11);
+const TINY_FONT: TextMeasurer = TextMeasurer::new(10);
+/// Widest a wrapping node label's text runs before it breaks onto another
+/// line (architecture, flowchart, state and sequence-actor boxes).
+const LABEL_MAX_W: i64 = 200;
+/// Baseline-to-baseline distance between wrapped label lines.
+const LABEL_LINE_H: i64 = 16;
 /// Outer canvas margin.
 const MARGIN: i64 = 20;
 
@@ -2453,8 +3676,11 @@ fn svg_open(w: i64, h: i64, title: Option<&str>) -> String {
 /// Two stages: [`build_scene`] computes the typed geometry scene, then
 /// [`emit_svg`] serializes it. `title` is the block's `title` attribute;
 /// when present it becomes the SVG `<title>` child for accessibility.
-pub(crate) fn render_svg(model: &DiagramModel, title: Option<&str>) -> String {
-    emit_svg(&build_scene(model), title)
+/// `direction` is its `direction` attribute: layered diagrams
+/// (architecture, flowchart, state, class) lay their layers out along it,
+/// and `None` keeps each type's default.
+pub(crate) fn render_svg(model: &DiagramModel, title: Option<&str>, direction: Option<DiagramDirection>) -> String {
+    emit_svg(&build_scene(model, direction), title)
 }
 
 /// Build the FFI-facing geometry scene for a diagram body, or `None` when
@@ -2471,18 +3697,20 @@ pub(crate) fn native_scene(
     diagram_type: &str,
     content: &str,
     title: Option<&str>,
+    direction: Option<DiagramDirection>,
 ) -> Option<crate::diagram_scene::NativeDiagramScene> {
     let translated = crate::mermaid_compat::translate(diagram_type, content);
     let (eff_type, eff_content) = match &translated {
         Some(t) => (t.diagram_type, t.content.as_str()),
         None => (diagram_type, content),
     };
+    let direction = direction.or(translated.as_ref().and_then(|t| t.direction));
     if let Some(chart_type) = chart_alias(eff_type) {
         let data = crate::blocks::parse_chart_data(eff_content)?;
         return Some(crate::chart::build_scene(chart_type, &data, title));
     }
     let model = parse_diagram_source(eff_type, eff_content).ok()?;
-    let scene = build_scene(&model);
+    let scene = build_scene(&model, direction);
     Some(crate::diagram_scene::NativeDiagramScene {
         width: scene.w as f64,
         height: scene.h as f64,
@@ -2532,6 +3760,9 @@ enum SvgItem {
     ClassDefs,
     GroupOpen(&'static str),
     GroupClose,
+    /// `<a href>` (with a `<title>` tooltip) around a linked node's group.
+    LinkOpen(DiagramLink),
+    LinkClose,
     Shape { shape: NativeShape, chrome: Chrome },
 }
 
@@ -2560,20 +3791,40 @@ impl SceneBuild {
     fn close_group(&mut self) {
         self.items.push(SvgItem::GroupClose);
     }
+    /// Open a link around the next node group, if the node has one.
+    fn open_link(&mut self, link: Option<&DiagramLink>) {
+        if let Some(link) = link {
+            self.items.push(SvgItem::LinkOpen(link.clone()));
+        }
+    }
+    /// Close the link opened by [`SceneBuild::open_link`] for the same node.
+    fn close_link(&mut self, link: Option<&DiagramLink>) {
+        if link.is_some() {
+            self.items.push(SvgItem::LinkClose);
+        }
+    }
 }
 
 /// Lay a parsed model out into a scene. Pure and deterministic: all
 /// coordinates come from integer arithmetic, widened to `f64` at the edge.
-fn build_scene(model: &DiagramModel) -> SceneBuild {
+fn build_scene(model: &DiagramModel, direction: Option<DiagramDirection>) -> SceneBuild {
+    use DiagramDirection::{LeftRight, TopBottom};
     match model {
-        DiagramModel::Architecture { nodes, edges } => scene_architecture(nodes, edges),
+        DiagramModel::Architecture { nodes, groups, edges } => {
+            scene_architecture(nodes, groups, edges, direction.unwrap_or(LeftRight))
+        }
         DiagramModel::Erd { entities, relations } => scene_erd(entities, relations),
-        DiagramModel::Flowchart { nodes, edges } => scene_flowchart(nodes, edges),
+        DiagramModel::Flowchart { nodes, groups, edges } => {
+            scene_flowchart(nodes, groups, edges, direction.unwrap_or(TopBottom))
+        }
         DiagramModel::Sequence { actors, events } => scene_sequence(actors, events),
-        DiagramModel::Gantt { tasks, dated } => scene_gantt(tasks, *dated),
-        DiagramModel::State { nodes, transitions } => scene_state(nodes, transitions),
+        DiagramModel::Gantt { tasks, dated, today } => {
+            let today = today.or_else(|| dated.then(|| TODAY.with(Cell::get)).flatten());
+            scene_gantt(tasks, *dated, today)
+        }
+        DiagramModel::State { nodes, transitions } => scene_state(nodes, transitions, direction.unwrap_or(TopBottom)),
         DiagramModel::Mindmap { nodes } => scene_mindmap(nodes),
-        DiagramModel::Class { classes, relations } => scene_class(classes, relations),
+        DiagramModel::Class { classes, relations } => scene_class(classes, relations, direction.unwrap_or(TopBottom)),
         DiagramModel::Timeline { events } => scene_timeline(events),
         DiagramModel::Journey { tasks } => scene_journey(tasks),
         DiagramModel::Quadrant { x_axis, y_axis, labels, points } => {
@@ -2585,6 +3836,8 @@ fn build_scene(model: &DiagramModel) -> SceneBuild {
         DiagramModel::C4 { nodes, boundaries, edges } => scene_c4(nodes, boundaries, edges),
         DiagramModel::Requirement { nodes, edges } => scene_requirement(nodes, edges),
         DiagramModel::Sankey { nodes, flows } => scene_sankey(nodes, flows),
+        DiagramModel::Packet { fields } => scene_packet(fields),
+        DiagramModel::Block { columns, cells, edges } => scene_block(*columns, cells, edges),
     }
 }
 
@@ -2607,6 +3860,7 @@ fn rect_at(x: i64, y: i64, w: i64, h: i64, corner: i64, fill: NativeRole, stroke
         fill,
         stroke,
         stroke_width: 1.0,
+        href: None,
     }
 }
 
@@ -2687,6 +3941,10 @@ fn role_color(role: NativeRole) -> &'static str {
         NativeRole::TextPrimary => "currentColor",
         NativeRole::TextSecondary => "#64748b",
         NativeRole::OnAccent => "#ffffff",
+        NativeRole::Cluster => "#f1f5f9",
+        NativeRole::Highlight => "#d97706",
+        NativeRole::Danger => "#dc2626",
+        NativeRole::Success => "#16a34a",
     }
 }
 
@@ -2734,6 +3992,16 @@ fn emit_svg(scene: &SceneBuild, title: Option<&str>) -> String {
                 svg.push_str("\">");
             }
             SvgItem::GroupClose => svg.push_str("</g>"),
+            SvgItem::LinkOpen(link) => {
+                // The url passed the scheme allowlist at parse time; escaping
+                // keeps it (and the tooltip) inside the attribute and element.
+                svg.push_str(&format!(
+                    "<a href=\"{}\" class=\"surfdoc-diagram-link\"><title>{}</title>",
+                    escape_html(&link.url),
+                    escape_html(link.tooltip.as_deref().unwrap_or(&link.url)),
+                ));
+            }
+            SvgItem::LinkClose => svg.push_str("</a>"),
             SvgItem::Shape { shape, chrome } => emit_shape(&mut svg, shape, chrome),
         }
     }
@@ -2754,6 +4022,7 @@ fn emit_shape(svg: &mut String, shape: &NativeShape, chrome: &Chrome) {
             fill,
             stroke,
             stroke_width,
+            href: _, // SVG links wrap the node group instead
         } => {
             let rx = if *corner != 0.0 {
                 format!(" rx=\"{}\"", fnum(*corner))
@@ -2824,7 +4093,7 @@ fn emit_shape(svg: &mut String, shape: &NativeShape, chrome: &Chrome) {
             }
         }
 
-        NativeShape::Polygon { points, fill, stroke } => {
+        NativeShape::Polygon { points, fill, stroke, .. } => {
             let pts = points
                 .iter()
                 .map(|p| format!("{},{}", fnum(p.x), fnum(p.y)))
@@ -2913,8 +4182,8 @@ fn emit_shape(svg: &mut String, shape: &NativeShape, chrome: &Chrome) {
 }
 
 /// Longest-path layering over an arbitrary node count + index edge list.
-/// Mirrors [`arch_layers`]: cycle-closing edges (in declaration order) and
-/// self-loops are dropped so the relaxation always terminates.
+/// Cycle-closing edges (in declaration order) and self-loops are dropped so
+/// the relaxation always terminates — never panics or loops.
 fn longest_path_layers(n: usize, edges: &[(usize, usize)]) -> Vec<usize> {
     let mut adj: Vec<Vec<usize>> = vec![Vec::new(); n];
     for &(f, t) in edges {
@@ -2936,56 +4205,823 @@ fn longest_path_layers(n: usize, edges: &[(usize, usize)]) -> Vec<usize> {
     layer
 }
 
-/// A top-down layered placement: each node placed in a row by its layer,
-/// centered left-to-right within the canvas in declaration order.
+/// A layered placement: one rect per node, plus one cluster rectangle per
+/// non-empty group.
 struct Placed {
     rects: Vec<Rect>,
+    /// `(group index, cluster rect)` in band order.
+    clusters: Vec<(usize, Rect)>,
     w: i64,
     h: i64,
 }
 
-/// Place `widths.len()` uniform-height nodes top-down by `longest_path_layers`.
-fn layered_layout(widths: &[i64], node_h: i64, row_gap: i64, hgap: i64, edges: &[(usize, usize)]) -> Placed {
-    let n = widths.len();
-    let layer = longest_path_layers(n, edges);
-    let n_rows = layer.iter().map(|l| l + 1).max().unwrap_or(1);
-    let mut rows: Vec<Vec<usize>> = vec![Vec::new(); n_rows];
-    for (i, &l) in layer.iter().enumerate() {
-        rows[l].push(i);
+/// Cluster padding around a group's members: sides / bottom, and top (room
+/// for the group label).
+const CLUSTER_PAD: i64 = 14;
+const CLUSTER_PAD_TOP: i64 = 30;
+
+/// Bands of a grouped layout: one per group plus one shared band for the
+/// ungrouped nodes (`None`), ordered by each band's first member in
+/// declaration order. `groups` is indexed by node; an empty slice (or all
+/// `None`) yields the single band `[None]`, i.e. the ungrouped layout.
+fn group_bands(n: usize, groups: &[Option<usize>]) -> Vec<Option<usize>> {
+    let mut bands: Vec<Option<usize>> = Vec::new();
+    for i in 0..n {
+        let g = groups.get(i).copied().flatten();
+        if !bands.contains(&g) {
+            bands.push(g);
+        }
     }
-    let row_width = |row: &[usize]| -> i64 {
-        if row.is_empty() {
-            0
-        } else {
-            row.iter().map(|&i| widths[i]).sum::<i64>() + hgap * (row.len() as i64 - 1)
+    if bands.is_empty() {
+        bands.push(None);
+    }
+    bands
+}
+
+/// Ordering sweeps (alternating down/up) before the crossing count settles.
+const ORDER_SWEEPS: usize = 12;
+/// Down/up rounds of parent/child centering in coordinate assignment.
+const COORD_ROUNDS: usize = 4;
+
+/// Place nodes of per-index `sizes` in layers, Sugiyama style.
+///
+/// 1. **Layers** from [`longest_path_layers`]; layers stack top to bottom
+///    (`TopBottom`) or left to right (`LeftRight`), `layer_gap` apart.
+/// 2. **Dummy nodes** split every edge spanning more than one layer, so a
+///    long edge claims a slot (and a channel) in each layer it crosses.
+/// 3. **Ordering**: starting from declaration order, alternating down/up
+///    sweeps sort each layer by the median (barycenter on ties) position
+///    of its neighbours in the previous layer; the ordering with the
+///    fewest edge crossings wins.
+/// 4. **Coordinates**: each node is pulled toward the mean position of its
+///    neighbours in the adjacent layer — ending with an upward pass, so
+///    parents center over their children — while keeping its order and
+///    `node_gap` clearance.
+///
+/// `groups` maps node index → group index (empty slice = no groups). Each
+/// band from [`group_bands`] owns its own strip across the layers, so a
+/// group's members stay contiguous and cluster rectangles never overlap;
+/// ordering and centering work within a band.
+///
+/// Deterministic: every step is a stable sort or integer arithmetic over
+/// declaration-ordered vectors.
+fn layered_layout(
+    sizes: &[(i64, i64)],
+    layer_gap: i64,
+    node_gap: i64,
+    edges: &[(usize, usize)],
+    groups: &[Option<usize>],
+    direction: DiagramDirection,
+) -> Placed {
+    let n = sizes.len();
+    let across = direction == DiagramDirection::LeftRight;
+    // Extent along the order axis, and thickness along the layer axis.
+    let extent = |i: usize| if across { sizes[i].1 } else { sizes[i].0 };
+    let thickness = |i: usize| if across { sizes[i].0 } else { sizes[i].1 };
+
+    let layer = longest_path_layers(n, edges);
+    let n_layers = layer.iter().map(|l| l + 1).max().unwrap_or(1);
+    let bands = group_bands(n, groups);
+    let band_of: Vec<usize> = (0..n)
+        .map(|i| {
+            let g = groups.get(i).copied().flatten();
+            bands.iter().position(|&band| band == g).expect("every node has a band")
+        })
+        .collect();
+
+    // Items are nodes (`< n`) then dummies; `links` join items in adjacent
+    // layers, upper item first.
+    let mut item_layer = layer.clone();
+    let mut item_band = band_of.clone();
+    let mut links: Vec<(usize, usize)> = Vec::new();
+    for &(f, t) in edges {
+        if f >= n || t >= n || layer[f] == layer[t] {
+            continue;
+        }
+        let (u, v) = if layer[f] < layer[t] { (f, t) } else { (t, f) };
+        if layer[v] - layer[u] > 1 && band_of[u] != band_of[v] {
+            continue; // a long edge between bands has no strip to run in
+        }
+        let mut prev = u;
+        for l in layer[u] + 1..layer[v] {
+            item_layer.push(l);
+            item_band.push(band_of[u]);
+            links.push((prev, item_layer.len() - 1));
+            prev = item_layer.len() - 1;
+        }
+        links.push((prev, v));
+    }
+    let n_items = item_layer.len();
+    let item_extent = |i: usize| if i < n { extent(i) } else { 0 };
+    let mut ups: Vec<Vec<usize>> = vec![Vec::new(); n_items];
+    let mut downs: Vec<Vec<usize>> = vec![Vec::new(); n_items];
+    for &(u, v) in &links {
+        downs[u].push(v);
+        ups[v].push(u);
+    }
+
+    // cells[band][layer], in declaration order to start.
+    let mut cells: Vec<Vec<Vec<usize>>> = vec![vec![Vec::new(); n_layers]; bands.len()];
+    for i in 0..n_items {
+        cells[item_band[i]][item_layer[i]].push(i);
+    }
+    order_layers(&mut cells, &ups, &downs, &links, &item_layer);
+
+    // Coordinates along the order axis, per band, relative to the band strip.
+    let mut pos = vec![0i64; n_items];
+    let mut band_w: Vec<i64> = Vec::with_capacity(bands.len());
+    for rows in &cells {
+        for row in rows {
+            let mut x = 0;
+            for &i in row {
+                pos[i] = x;
+                x += item_extent(i) + node_gap;
+            }
         }
+        let same_band = |list: &[usize], i: usize| -> Vec<usize> {
+            list.iter().copied().filter(|&j| item_band[j] == item_band[i]).collect()
+        };
+        for _ in 0..COORD_ROUNDS {
+            for (neighbours, layers) in [(&ups, (1..n_layers).collect::<Vec<_>>()), (&downs, (0..n_layers).rev().collect())] {
+                for l in layers {
+                    let row = &rows[l];
+                    let desired: Vec<i64> = row
+                        .iter()
+                        .map(|&i| {
+                            let near = same_band(&neighbours[i], i);
+                            if near.is_empty() {
+                                return pos[i];
+                            }
+                            let centre = near.iter().map(|&j| pos[j] + item_extent(j) / 2).sum::<i64>() / near.len() as i64;
+                            centre - item_extent(i) / 2
+                        })
+                        .collect();
+                    let placed = pack_in_order(row, &desired, |i| item_extent(i) + node_gap);
+                    for (&i, x) in row.iter().zip(placed) {
+                        pos[i] = x;
+                    }
+                }
+            }
+        }
+        let items = rows.iter().flatten();
+        let lo = items.clone().map(|&i| pos[i]).min().unwrap_or(0);
+        for &i in items.clone() {
+            pos[i] -= lo;
+        }
+        band_w.push(items.map(|&i| pos[i] + item_extent(i)).max().unwrap_or(0));
+    }
+
+    // Layer thickness and offsets along the layer axis.
+    let grouped = bands.iter().any(Option::is_some);
+    // Cluster pads: the label needs room at the top, on whichever axis.
+    let (layer_pad, order_pad) = match (grouped, across) {
+        (false, _) => ((0, 0), (0, 0)),
+        (true, false) => ((CLUSTER_PAD_TOP, CLUSTER_PAD), (CLUSTER_PAD, CLUSTER_PAD)),
+        (true, true) => ((CLUSTER_PAD, CLUSTER_PAD), (CLUSTER_PAD_TOP, CLUSTER_PAD)),
     };
-    let max_row_w = rows.iter().map(|r| row_width(r)).max().unwrap_or(0);
+    let layer_t: Vec<i64> = (0..n_layers)
+        .map(|l| (0..n).filter(|&i| layer[i] == l).map(thickness).max().unwrap_or(0))
+        .collect();
+    let mut layer_at = Vec::with_capacity(n_layers);
+    let mut at = MARGIN + layer_pad.0;
+    for t in &layer_t {
+        layer_at.push(at);
+        at += t + layer_gap;
+    }
+    let layer_end = at - layer_gap + layer_pad.1 + MARGIN;
+
     let mut rects = vec![Rect { x: 0, y: 0, w: 0, h: 0 }; n];
-    for (r, row) in rows.iter().enumerate() {
-        let y = MARGIN + r as i64 * (node_h + row_gap);
-        let mut x = MARGIN + (max_row_w - row_width(row)) / 2;
-        for &i in row {
-            rects[i] = Rect { x, y, w: widths[i], h: node_h };
-            x += widths[i] + hgap;
+    let mut clusters: Vec<(usize, Rect)> = Vec::new();
+    let mut strip = MARGIN;
+    for (b, (band, rows)) in bands.iter().zip(&cells).enumerate() {
+        let pad = if band.is_some() { order_pad } else { (0, 0) };
+        for &i in rows.iter().flatten().filter(|&&i| i < n) {
+            let l = layer[i];
+            let (o, d) = (strip + pad.0 + pos[i], layer_at[l] + (layer_t[l] - thickness(i)) / 2);
+            let (x, y) = if across { (d, o) } else { (o, d) };
+            rects[i] = Rect { x, y, w: sizes[i].0, h: sizes[i].1 };
+        }
+        let first = rows.iter().position(|r| r.iter().any(|&i| i < n));
+        let last = rows.iter().rposition(|r| r.iter().any(|&i| i < n));
+        if let (Some(g), Some(first), Some(last)) = (*band, first, last) {
+            let (o, o_len) = (strip, band_w[b] + pad.0 + pad.1);
+            let d = layer_at[first] - layer_pad.0;
+            let d_len = layer_at[last] + layer_t[last] + layer_pad.1 - d;
+            let r = if across { Rect { x: d, y: o, w: d_len, h: o_len } } else { Rect { x: o, y: d, w: o_len, h: d_len } };
+            clusters.push((g, r));
+        }
+        strip += band_w[b] + pad.0 + pad.1 + node_gap;
+    }
+    let order_end = (strip - node_gap + MARGIN).max(2 * MARGIN);
+    let thickest = (0..n).map(thickness).max().unwrap_or(0);
+    let layer_end = layer_end.max(2 * MARGIN + thickest);
+    let (w, h) = if across { (layer_end, order_end) } else { (order_end, layer_end) };
+    Placed { rects, clusters, w, h }
+}
+
+/// Reorder every `cells[band][layer]` to reduce edge crossings: alternating
+/// down/up sweeps sort each cell by neighbour median (then barycenter),
+/// keeping the best ordering seen. Items with no neighbour on the swept
+/// side keep their place.
+fn order_layers(
+    cells: &mut [Vec<Vec<usize>>],
+    ups: &[Vec<usize>],
+    downs: &[Vec<usize>],
+    links: &[(usize, usize)],
+    item_layer: &[usize],
+) {
+    let n_layers = cells.first().map_or(0, Vec::len);
+    // Position of every item within its whole layer (bands side by side).
+    let positions = |cells: &[Vec<Vec<usize>>]| -> Vec<i64> {
+        let mut pos = vec![0i64; item_layer.len()];
+        for l in 0..n_layers {
+            for (k, &i) in cells.iter().flat_map(|band| &band[l]).enumerate() {
+                pos[i] = k as i64;
+            }
+        }
+        pos
+    };
+    let crossings = |pos: &[i64]| -> usize {
+        let mut count = 0;
+        for (k, &(u1, v1)) in links.iter().enumerate() {
+            for &(u2, v2) in &links[k + 1..] {
+                if item_layer[u1] == item_layer[u2] && (pos[u1] - pos[u2]) * (pos[v1] - pos[v2]) < 0 {
+                    count += 1;
+                }
+            }
+        }
+        count
+    };
+    let mut best = cells.to_vec();
+    let mut best_crossings = crossings(&positions(cells));
+    for sweep in 0..ORDER_SWEEPS {
+        if best_crossings == 0 {
+            break;
+        }
+        let down = sweep % 2 == 0;
+        let layers: Vec<usize> = if down { (1..n_layers).collect() } else { (0..n_layers.saturating_sub(1)).rev().collect() };
+        for l in layers {
+            let pos = positions(cells);
+            for band in cells.iter_mut() {
+                let key = |i: usize| {
+                    let mut near: Vec<i64> = (if down { &ups[i] } else { &downs[i] }).iter().map(|&j| pos[j]).collect();
+                    if near.is_empty() {
+                        return (2 * pos[i], 2 * pos[i]);
+                    }
+                    near.sort_unstable();
+                    let m = near.len() / 2;
+                    // Doubled so an even count's median stays integral.
+                    let median = if near.len() % 2 == 1 { 2 * near[m] } else { near[m - 1] + near[m] };
+                    (median, 2 * near.iter().sum::<i64>() / near.len() as i64)
+                };
+                band[l].sort_by_key(|&i| key(i));
+            }
+        }
+        let count = crossings(&positions(cells));
+        if count < best_crossings {
+            best_crossings = count;
+            best = cells.to_vec();
+        }
+    }
+    cells.clone_from_slice(&best);
+}
+
+/// Positions for `row` (in order) as close as possible to `desired` while
+/// each item starts at least `advance(previous)` after the previous one —
+/// pool-adjacent-violators over the offset targets, in integers.
+fn pack_in_order(row: &[usize], desired: &[i64], advance: impl Fn(usize) -> i64) -> Vec<i64> {
+    // Shift out the minimum spacing so the constraint becomes "non-decreasing".
+    let mut offset = Vec::with_capacity(row.len());
+    let mut acc = 0;
+    for &i in row {
+        offset.push(acc);
+        acc += advance(i);
+    }
+    // Blocks of (sum, count); a block's value is its mean.
+    let mut blocks: Vec<(i64, i64)> = Vec::new();
+    for (k, &d) in desired.iter().enumerate() {
+        blocks.push((d - offset[k], 1));
+        while blocks.len() >= 2 {
+            let (s2, c2) = blocks[blocks.len() - 1];
+            let (s1, c1) = blocks[blocks.len() - 2];
+            if s1.div_euclid(c1) <= s2.div_euclid(c2) {
+                break;
+            }
+            blocks.pop();
+            *blocks.last_mut().expect("two blocks") = (s1 + s2, c1 + c2);
         }
     }
-    let total_w = (MARGIN * 2 + max_row_w).max(2 * MARGIN);
-    let total_h = (MARGIN * 2 + n_rows as i64 * (node_h + row_gap) - row_gap).max(2 * MARGIN + node_h);
-    Placed { rects, w: total_w, h: total_h }
+    let mut out = Vec::with_capacity(row.len());
+    for (sum, count) in blocks {
+        for _ in 0..count {
+            out.push(sum.div_euclid(count) + offset[out.len()]);
+        }
+    }
+    out
+}
+
+/// Paint a group's cluster: a tinted rectangle behind its members with the
+/// group label at top-left. Shared by architecture and flowchart scenes.
+fn push_cluster(sc: &mut SceneBuild, r: &Rect, label: &str) {
+    sc.push(
+        Chrome::class("surfdoc-diagram-cluster"),
+        rect_at(r.x, r.y, r.w, r.h, 6, NativeRole::Cluster, NativeRole::AccentSoft),
+    );
+    sc.push(
+        Chrome::class("surfdoc-diagram-cluster-label"),
+        text_at(r.x + 10, r.y + 18, label, NativeRole::TextSecondary, 11, true, NativeAnchor::Start),
+    );
+}
+
+// ------------------------------------------------------------------
+// SVG rendering — orthogonal edge routing
+// ------------------------------------------------------------------
+
+/// Which side of a box an edge leaves or enters by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right,
 }
 
-/// Choose the two connection points between two boxes for a top-down layout:
-/// bottom→top when descending, top→bottom when ascending, side→side same row.
-fn vert_edge_points(a: &Rect, b: &Rect) -> (i64, i64, i64, i64) {
+impl Side {
+    /// Unit step pointing away from the box.
+    fn outward(self) -> (i64, i64) {
+        match self {
+            Side::Top => (0, -1),
+            Side::Bottom => (0, 1),
+            Side::Left => (-1, 0),
+            Side::Right => (1, 0),
+        }
+    }
+
+    /// Grid direction index of [`Side::outward`] (see [`ROUTE_DIRS`]).
+    fn dir(self) -> usize {
+        match self {
+            Side::Top => 0,
+            Side::Bottom => 1,
+            Side::Left => 2,
+            Side::Right => 3,
+        }
+    }
+}
+
+/// Grid step per direction index: up, down, left, right.
+const ROUTE_DIRS: [(i64, i64); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+
+/// Sides for a top-down layout: bottom→top when descending, top→bottom when
+/// ascending, side→side within one row.
+fn vert_sides(a: &Rect, b: &Rect) -> (Side, Side) {
     if b.cy() > a.cy() {
-        (a.cx(), a.y + a.h, b.cx(), b.y)
+        (Side::Bottom, Side::Top)
     } else if b.cy() < a.cy() {
-        (a.cx(), a.y, b.cx(), b.y + b.h)
+        (Side::Top, Side::Bottom)
     } else if b.cx() >= a.cx() {
-        (a.x + a.w, a.cy(), b.x, b.cy())
+        (Side::Right, Side::Left)
     } else {
-        (a.x, a.cy(), b.x + b.w, b.cy())
+        (Side::Left, Side::Right)
+    }
+}
+
+/// Sides for a left-to-right column layout: facing sides across columns,
+/// bottom/top within one column.
+fn horiz_sides(a: &Rect, b: &Rect) -> (Side, Side) {
+    if b.cx() > a.cx() {
+        (Side::Right, Side::Left)
+    } else if b.cx() < a.cx() {
+        (Side::Left, Side::Right)
+    } else if b.cy() >= a.cy() {
+        (Side::Bottom, Side::Top)
+    } else {
+        (Side::Top, Side::Bottom)
+    }
+}
+
+/// The drawn outline inside a node's box, which decides where its ports sit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outline {
+    /// Fills the box: ports spread along the whole side.
+    Box,
+    /// A diamond: ports spread over the middle half of a side, set onto the
+    /// sloped outline.
+    Diamond,
+    /// A small marker: every edge uses the centre of the side.
+    Point,
+}
+
+/// Sides for a layered layout: [`vert_sides`] when layers stack top to
+/// bottom, [`horiz_sides`] when they run left to right.
+fn layered_sides(direction: DiagramDirection, a: &Rect, b: &Rect) -> (Side, Side) {
+    match direction {
+        DiagramDirection::TopBottom => vert_sides(a, b),
+        DiagramDirection::LeftRight => horiz_sides(a, b),
+    }
+}
+
+/// One edge to route between node boxes `from` and `to`.
+struct RouteReq {
+    from: usize,
+    to: usize,
+    exit: Side,
+    entry: Side,
+}
+
+/// Straight run out of (and into) a box before a route may turn.
+const ROUTE_STUB: i64 = 10;
+/// Clearance kept between a route and every box it does not end at.
+const ROUTE_CLEAR: i64 = 4;
+/// Extra cost of a turn, in px of route length.
+const ROUTE_BEND: i64 = 24;
+/// Extra cost of running along a grid segment an earlier route already
+/// uses — spreads parallel edges onto separate channels.
+const ROUTE_REUSE: i64 = 40;
+
+/// Route every edge as an orthogonal polyline that avoids the node boxes.
+///
+/// Ports: edges sharing a side of a box are spread evenly along it (ordered
+/// by where the other end sits), as the node's [`Outline`] allows. Each
+/// route leaves its side perpendicular to it for [`ROUTE_STUB`] px, then
+/// follows the
+/// cheapest path on a channel grid (box-gap midpoints, port lines and one
+/// lane around the whole drawing) by length + bends + reuse of channels
+/// earlier edges took. Self-loops get a fixed loop off the top-right
+/// corner; an unroutable pair falls back to a straight segment.
+///
+/// Deterministic: requests route in order, the grid is built from sorted
+/// integer coordinates and ties break on grid index.
+fn route_edges(rects: &[Rect], outlines: &[Outline], reqs: &[RouteReq]) -> Vec<Vec<(i64, i64)>> {
+    let ports = route_ports(rects, outlines, reqs);
+    let mut xs: Vec<i64> = Vec::new();
+    let mut ys: Vec<i64> = Vec::new();
+    let mut bx: Vec<i64> = rects.iter().flat_map(|r| [r.x, r.x + r.w]).collect();
+    let mut by: Vec<i64> = rects.iter().flat_map(|r| [r.y, r.y + r.h]).collect();
+    bx.sort_unstable();
+    bx.dedup();
+    by.sort_unstable();
+    by.dedup();
+    channel_lanes(&bx, &mut xs);
+    channel_lanes(&by, &mut ys);
+    for (_, &((_, s), (_, t))) in reqs.iter().zip(&ports).filter(|(req, _)| req.from != req.to) {
+        xs.extend([s.0, t.0]);
+        ys.extend([s.1, t.1]);
+    }
+    xs.sort_unstable();
+    xs.dedup();
+    ys.sort_unstable();
+    ys.dedup();
+
+    let grid = RouteGrid::new(xs, ys, rects);
+    let mut used = vec![0u32; grid.xs.len() * grid.ys.len() * 2];
+    let mut out = Vec::with_capacity(reqs.len());
+    for (req, &((p, s), (q, t))) in reqs.iter().zip(&ports) {
+        if req.from == req.to {
+            let r = &rects[req.from];
+            let (right, top) = (r.x + r.w + ROUTE_STUB, r.y - ROUTE_STUB);
+            out.push(vec![
+                (r.x + r.w, r.cy()),
+                (right, r.cy()),
+                (right, top),
+                (r.x + r.w * 3 / 4, top),
+                (r.x + r.w * 3 / 4, r.y),
+            ]);
+            continue;
+        }
+        // Arriving at the target stub we must then head back into the box.
+        let arrive = Side::dir(match req.entry {
+            Side::Top => Side::Bottom,
+            Side::Bottom => Side::Top,
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        });
+        let mut points = vec![p];
+        // No channel path: fall back to the straight segment.
+        if let Some(path) = grid.path(s, req.exit.dir(), t, arrive, &used) {
+            for w in path.windows(2) {
+                grid.mark(w[0], w[1], &mut used);
+            }
+            points.extend(path);
+        }
+        points.push(q);
+        out.push(simplify_route(points));
+    }
+    out
+}
+
+/// Channel lanes along one axis from the sorted box boundaries: the midpoint
+/// of every gap (plus a lane either side of it when the gap is wide enough
+/// for three), and two lanes inside the margin beyond the outermost boxes.
+fn channel_lanes(bounds: &[i64], lanes: &mut Vec<i64>) {
+    for pair in bounds.windows(2) {
+        let (mid, gap) = ((pair[0] + pair[1]) / 2, pair[1] - pair[0]);
+        lanes.push(mid);
+        if gap >= 24 {
+            lanes.extend([mid - gap / 4, mid + gap / 4]);
+        }
+    }
+    if let (Some(&lo), Some(&hi)) = (bounds.first(), bounds.last()) {
+        lanes.extend([lo - 6, lo - 14, hi + 6, hi + 14]);
+    }
+}
+
+/// One end of a route: the point on the node outline and the stub point
+/// [`ROUTE_STUB`] px out from the box side.
+type RoutePort = ((i64, i64), (i64, i64));
+
+/// Both ends of every request: `(source, target)`.
+fn route_ports(rects: &[Rect], outlines: &[Outline], reqs: &[RouteReq]) -> Vec<(RoutePort, RoutePort)> {
+    // Every (box, side) attachment in request order: (request, is_source).
+    let mut sides: Vec<(usize, Side)> = Vec::new();
+    let mut attached: Vec<Vec<(usize, bool)>> = Vec::new();
+    for (k, req) in reqs.iter().enumerate().filter(|(_, req)| req.from != req.to) {
+        for (node, side, is_source) in [(req.from, req.exit, true), (req.to, req.entry, false)] {
+            match sides.iter().position(|&key| key == (node, side)) {
+                Some(i) => attached[i].push((k, is_source)),
+                None => {
+                    sides.push((node, side));
+                    attached.push(vec![(k, is_source)]);
+                }
+            }
+        }
+    }
+    let mut ports = vec![(((0, 0), (0, 0)), ((0, 0), (0, 0))); reqs.len()];
+    for ((node, side), mut list) in sides.into_iter().zip(attached) {
+        let r = &rects[node];
+        let along = matches!(side, Side::Top | Side::Bottom);
+        // Order by where the far end sits along this side (stable on ties).
+        list.sort_by_key(|&(k, is_source)| {
+            let far = &rects[if is_source { reqs[k].to } else { reqs[k].from }];
+            if along { far.cx() } else { far.cy() }
+        });
+        let outline = outlines.get(node).copied().unwrap_or(Outline::Box);
+        let (len, depth) = if along { (r.w, r.h) } else { (r.h, r.w) };
+        let n = list.len() as i64;
+        for (i, &(k, is_source)) in list.iter().enumerate() {
+            // Offset along the side, and how far the outline sits inside it.
+            let (pos, inset) = match outline {
+                Outline::Box => (len * (i as i64 + 1) / (n + 1), 0),
+                Outline::Diamond => {
+                    let pos = len / 4 + len / 2 * (i as i64 + 1) / (n + 1);
+                    (pos, (pos - len / 2).abs() * depth / len.max(1))
+                }
+                Outline::Point => (len / 2, 0),
+            };
+            let (dx, dy) = side.outward();
+            let edge = match side {
+                Side::Top => (r.x + pos, r.y),
+                Side::Bottom => (r.x + pos, r.y + r.h),
+                Side::Left => (r.x, r.y + pos),
+                Side::Right => (r.x + r.w, r.y + pos),
+            };
+            let port = (edge.0 - dx * inset, edge.1 - dy * inset);
+            let stub = (edge.0 + dx * ROUTE_STUB, edge.1 + dy * ROUTE_STUB);
+            if is_source {
+                ports[k].0 = (port, stub);
+            } else {
+                ports[k].1 = (port, stub);
+            }
+        }
+    }
+    ports
+}
+
+/// Drop repeated points and the middle of every collinear triple.
+fn simplify_route(points: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    let mut out: Vec<(i64, i64)> = Vec::with_capacity(points.len());
+    for p in points {
+        if out.last() == Some(&p) {
+            continue;
+        }
+        if out.len() >= 2 {
+            let (a, b) = (out[out.len() - 2], out[out.len() - 1]);
+            if (a.0 == b.0 && b.0 == p.0) || (a.1 == b.1 && b.1 == p.1) {
+                out.pop();
+            }
+        }
+        out.push(p);
+    }
+    out
+}
+
+/// The routing channel grid: every `(x, y)` of the sorted coordinate lists,
+/// minus points inside a box grown by [`ROUTE_CLEAR`].
+struct RouteGrid {
+    xs: Vec<i64>,
+    ys: Vec<i64>,
+    free: Vec<bool>,
+}
+
+impl RouteGrid {
+    fn new(xs: Vec<i64>, ys: Vec<i64>, rects: &[Rect]) -> Self {
+        let mut free = vec![true; xs.len() * ys.len()];
+        for r in rects {
+            // Grid lines strictly inside the grown box.
+            let cols = xs.partition_point(|&x| x <= r.x - ROUTE_CLEAR)..xs.partition_point(|&x| x < r.x + r.w + ROUTE_CLEAR);
+            let rows = ys.partition_point(|&y| y <= r.y - ROUTE_CLEAR)..ys.partition_point(|&y| y < r.y + r.h + ROUTE_CLEAR);
+            for j in rows {
+                free[j * xs.len() + cols.start..j * xs.len() + cols.end].fill(false);
+            }
+        }
+        RouteGrid { xs, ys, free }
+    }
+
+    fn index(&self, p: (i64, i64)) -> Option<usize> {
+        let i = self.xs.binary_search(&p.0).ok()?;
+        let j = self.ys.binary_search(&p.1).ok()?;
+        Some(j * self.xs.len() + i)
+    }
+
+    fn point(&self, idx: usize) -> (i64, i64) {
+        (self.xs[idx % self.xs.len()], self.ys[idx / self.xs.len()])
+    }
+
+    /// Neighbour of `idx` one grid step in direction `d`, if free.
+    fn step(&self, idx: usize, d: usize) -> Option<usize> {
+        let (nx, ny) = (self.xs.len(), self.ys.len());
+        let (i, j) = (idx % nx, idx / nx);
+        let (di, dj) = ROUTE_DIRS[d];
+        let i = usize::try_from(i as i64 + di).ok().filter(|&i| i < nx)?;
+        let j = usize::try_from(j as i64 + dj).ok().filter(|&j| j < ny)?;
+        let next = j * nx + i;
+        self.free[next].then_some(next)
+    }
+
+    /// Slot in the reuse table for the segment between adjacent points.
+    fn segment(&self, a: usize, b: usize) -> usize {
+        let lo = a.min(b);
+        if a.abs_diff(b) == 1 { lo * 2 } else { lo * 2 + 1 }
+    }
+
+    /// Count every grid segment between consecutive path corners as used.
+    fn mark(&self, from: (i64, i64), to: (i64, i64), used: &mut [u32]) {
+        let (Some(mut a), Some(b)) = (self.index(from), self.index(to)) else {
+            return;
+        };
+        let d = if to.0 > from.0 {
+            3
+        } else if to.0 < from.0 {
+            2
+        } else if to.1 > from.1 {
+            1
+        } else {
+            0
+        };
+        while a != b {
+            let Some(next) = self.step(a, d) else { return };
+            used[self.segment(a, next)] += 1;
+            a = next;
+        }
+    }
+
+    /// Cheapest path from `s` (already heading `start_dir`) to `t`, finishing
+    /// by heading `end_dir`. Returns the corner points, `s` and `t` included.
+    fn path(&self, s: (i64, i64), start_dir: usize, t: (i64, i64), end_dir: usize, used: &[u32]) -> Option<Vec<(i64, i64)>> {
+        let (s, t) = (self.index(s)?, self.index(t)?);
+        if !self.free[s] || !self.free[t] {
+            return None;
+        }
+        let n = self.free.len();
+        let mut dist = vec![i64::MAX; n * 4];
+        let mut prev = vec![usize::MAX; n * 4];
+        // A* on (point, heading); route length never undercuts the
+        // Manhattan distance left to the target.
+        let target = self.point(t);
+        let remaining = |at: usize| {
+            let p = self.point(at);
+            (p.0 - target.0).abs() + (p.1 - target.1).abs()
+        };
+        let mut best: Option<(i64, usize)> = None;
+        let mut heap = BinaryHeap::new();
+        dist[s * 4 + start_dir] = 0;
+        heap.push(Reverse((remaining(s), s, start_dir)));
+        while let Some(Reverse((estimate, at, d))) = heap.pop() {
+            if best.is_some_and(|(b, _)| estimate >= b) {
+                break;
+            }
+            let cost = dist[at * 4 + d];
+            if estimate > cost + remaining(at) {
+                continue;
+            }
+            if at == t {
+                // Arriving off the entry heading costs one more turn.
+                let total = cost + if d == end_dir { 0 } else { ROUTE_BEND };
+                if best.is_none_or(|(b, _)| total < b) {
+                    best = Some((total, d));
+                }
+                continue;
+            }
+            for nd in 0..4 {
+                // Never double back on the current heading.
+                if ROUTE_DIRS[nd].0 == -ROUTE_DIRS[d].0 && ROUTE_DIRS[nd].1 == -ROUTE_DIRS[d].1 {
+                    continue;
+                }
+                let Some(next) = self.step(at, nd) else { continue };
+                let (a, b) = (self.point(at), self.point(next));
+                let mut c = cost + (a.0 - b.0).abs() + (a.1 - b.1).abs();
+                c += ROUTE_REUSE * i64::from(used[self.segment(at, next)]);
+                if nd != d {
+                    c += ROUTE_BEND;
+                }
+                if c < dist[next * 4 + nd] {
+                    dist[next * 4 + nd] = c;
+                    prev[next * 4 + nd] = at * 4 + d;
+                    heap.push(Reverse((c + remaining(next), next, nd)));
+                }
+            }
+        }
+        let (_, end) = best?;
+        let mut state = t * 4 + end;
+        let mut corners = vec![self.point(t)];
+        while state != s * 4 + start_dir {
+            state = prev[state];
+            if state == usize::MAX {
+                return None;
+            }
+            corners.push(self.point(state / 4));
+        }
+        corners.reverse();
+        Some(simplify_route(corners))
+    }
+}
+
+/// Where the label of `routes[k]` goes: beside the longest segment of the
+/// route that keeps the label inside the `w`×`h` canvas and clear of every
+/// box, every other route and every label already placed (`taken`). When
+/// no spot is that clear, crossing another route is allowed; failing that
+/// too, the label sits at the longest segment. Returns the baseline point and
+/// anchor, and records the label's box in `taken`.
+fn place_edge_label(
+    routes: &[Vec<(i64, i64)>],
+    k: usize,
+    label: &str,
+    rects: &[Rect],
+    (w_max, h_max): (i64, i64),
+    taken: &mut Vec<Rect>,
+) -> (i64, i64, NativeAnchor) {
+    let w = NOTE_FONT.width(label);
+    let mut segs: Vec<((i64, i64), (i64, i64))> = routes[k].windows(2).map(|p| (p[0], p[1])).collect();
+    segs.sort_by_key(|(a, b)| Reverse((a.0 - b.0).abs() + (a.1 - b.1).abs()));
+    let mut candidates: Vec<(i64, i64, NativeAnchor, Rect)> = Vec::new();
+    for (a, b) in segs {
+        let (mx, my) = ((a.0 + b.0) / 2, (a.1 + b.1) / 2);
+        // The midpoint first, then slid either way along a long segment.
+        let len = (a.0 - b.0).abs() + (a.1 - b.1).abs();
+        for shift in [0i64, -16, 16] {
+            // A slid label must still sit beside the segment.
+            let room = if a.0 == b.0 { 2 * (shift.abs() + 7) } else { 4 * shift.abs() + w };
+            if shift != 0 && len < room {
+                continue;
+            }
+            if a.0 == b.0 {
+                let my = my + shift;
+                candidates.push((mx + 6, my + 4, NativeAnchor::Start, Rect { x: mx + 6, y: my - 7, w, h: 14 }));
+                candidates.push((mx - 6, my + 4, NativeAnchor::End, Rect { x: mx - 6 - w, y: my - 7, w, h: 14 }));
+            } else {
+                let mx = mx + shift * 2;
+                candidates.push((mx, my - 5, NativeAnchor::Middle, Rect { x: mx - w / 2, y: my - 16, w, h: 14 }));
+                candidates.push((mx, my + 14, NativeAnchor::Middle, Rect { x: mx - w / 2, y: my + 3, w, h: 14 }));
+            }
+        }
+    }
+    // Other routes count as hairline boxes.
+    let strokes: Vec<Rect> = routes
+        .iter()
+        .enumerate()
+        .filter(|&(j, _)| j != k)
+        .flat_map(|(_, route)| route.windows(2))
+        .map(|p| {
+            let (x, y) = (p[0].0.min(p[1].0), p[0].1.min(p[1].1));
+            Rect { x: x - 1, y: y - 1, w: (p[0].0 - p[1].0).abs() + 2, h: (p[0].1 - p[1].1).abs() + 2 }
+        })
+        .collect();
+    let overlaps = |a: &Rect, b: &Rect| a.x < b.x + b.w && b.x < a.x + a.w && a.y < b.y + b.h && b.y < a.y + a.h;
+    let fits = |r: &Rect| r.x >= 0 && r.y >= 0 && r.x + r.w <= w_max && r.y + r.h <= h_max;
+    // Clear of everything if possible; else allow crossing another edge.
+    let clear = |r: &Rect, strokes: &[Rect]| fits(r) && !rects.iter().chain(strokes).chain(taken.iter()).any(|o| overlaps(r, o));
+    let pick = candidates
+        .iter()
+        .find(|(_, _, _, r)| clear(r, &strokes))
+        .or_else(|| candidates.iter().find(|(_, _, _, r)| clear(r, &[])))
+        .or(candidates.first());
+    match pick {
+        Some(&(x, y, anchor, r)) => {
+            taken.push(r);
+            (x, y, anchor)
+        }
+        None => (0, 0, NativeAnchor::Middle),
+    }
+}
+
+/// An open polyline edge shape through integer route points.
+fn route_line(points: &[(i64, i64)], marker_start: NativeMarker) -> NativeShape {
+    NativeShape::Line {
+        points: points.iter().map(|&(x, y)| pt(x, y)).collect(),
+        stroke: NativeRole::Stroke,
+        stroke_width: 1.5,
+        dashed: false,
+        marker_start,
+        marker_end: NativeMarker::Arrow,
     }
 }
 
@@ -3022,116 +5058,127 @@ fn reachable(adj: &[Vec<usize>], from: usize, to: usize) -> bool {
     false
 }
 
-/// Longest-path layering from roots. Cycles are broken by declaration order
-/// (an edge that would close a cycle is ignored for layout — never panics or
-/// loops), self-loops are skipped.
-fn arch_layers(nodes: &[ArchNode], edges: &[ArchEdge]) -> Vec<usize> {
-    let index_of = |id: &str| nodes.iter().position(|n| n.id == id);
+/// A node label broken into lines of at most [`LABEL_MAX_W`] px.
+fn label_lines(label: &str) -> Vec<String> {
+    LABEL_FONT.wrap(label, LABEL_MAX_W)
+}
 
-    // Build a DAG in edge-declaration order, dropping cycle-closing edges.
-    let mut adj: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
-    for edge in edges {
-        let (Some(f), Some(t)) = (index_of(&edge.from), index_of(&edge.to)) else {
-            continue; // unreachable: endpoints are auto-declared at parse time
-        };
-        if f == t || reachable(&adj, t, f) {
-            continue;
-        }
-        adj[f].push(t);
+/// Box width for a wrapping label: widest line + padding, min 80.
+fn label_width(label: &str) -> i64 {
+    let text_w = label_lines(label).iter().map(|l| LABEL_FONT.width(l)).max().unwrap_or(0);
+    (text_w + 24).max(80)
+}
+
+/// Box height for a wrapping label: `base` plus one line per extra line.
+fn label_height(label: &str, base: i64) -> i64 {
+    base + (label_lines(label).len() as i64 - 1) * LABEL_LINE_H
+}
+
+/// Box width for a single-line label: measured text + padding, min 80.
+fn line_width(label: &str) -> i64 {
+    (LABEL_FONT.width(label) + 24).max(80)
+}
+
+/// Push a wrapping node label centred on `(cx, cy)`: one `Label` per line,
+/// the block vertically centred (a single line sits on the `cy + 4`
+/// optical baseline).
+fn push_label(sc: &mut SceneBuild, cx: i64, cy: i64, label: &str) {
+    push_label_as(sc, cx, cy, label, NativeRole::TextPrimary);
+}
+
+/// [`push_label`] in a given text role (muted nodes use secondary text).
+fn push_label_as(sc: &mut SceneBuild, cx: i64, cy: i64, label: &str, role: NativeRole) {
+    let lines = label_lines(label);
+    let top = cy + 4 - (lines.len() as i64 - 1) * LABEL_LINE_H / 2;
+    for (i, line) in lines.iter().enumerate() {
+        sc.push(
+            Chrome::default(),
+            text_at(cx, top + i as i64 * LABEL_LINE_H, line, role, 13, false, NativeAnchor::Middle),
+        );
     }
+}
 
-    // Longest path from roots: n relaxation passes converge on a DAG.
-    let mut layer = vec![0usize; nodes.len()];
-    for _ in 0..nodes.len() {
-        for (f, outs) in adj.iter().enumerate() {
-            for &t in outs {
-                if layer[t] < layer[f] + 1 {
-                    layer[t] = layer[f] + 1;
-                }
+/// Repaint a node outline or edge stroke with its `style` emphasis: the
+/// emphasis role, widened to 2 (`muted` keeps the plain width, so it reads
+/// as de-emphasis rather than a second accent).
+fn emphasize(mut shape: NativeShape, emphasis: Option<Emphasis>) -> NativeShape {
+    let Some(emphasis) = emphasis else {
+        return shape;
+    };
+    let widen = emphasis != Emphasis::Muted;
+    match &mut shape {
+        NativeShape::Rect { stroke, stroke_width, .. } | NativeShape::Line { stroke, stroke_width, .. } => {
+            *stroke = emphasis.role();
+            if widen {
+                *stroke_width = 2.0;
             }
         }
+        NativeShape::Polygon { stroke, .. } => *stroke = emphasis.role(),
+        NativeShape::Ellipse { stroke: Some(stroke), .. } => *stroke = emphasis.role(),
+        NativeShape::Ellipse { .. } | NativeShape::Label { .. } => {}
     }
-    layer
+    shape
 }
 
-/// Box width for a label: ~8px per char + padding, min 80.
-fn label_width(label: &str) -> i64 {
-    (label.chars().count() as i64 * CHAR_W + 24).max(80)
+/// Attach a node's link url to its outline shape, so native clients can
+/// hit-test it.
+fn with_href(mut shape: NativeShape, link: Option<&DiagramLink>) -> NativeShape {
+    if let NativeShape::Rect { href, .. } | NativeShape::Polygon { href, .. } = &mut shape {
+        *href = link.map(|l| l.url.clone());
+    }
+    shape
 }
 
-fn scene_architecture(nodes: &[ArchNode], edges: &[ArchEdge]) -> SceneBuild {
-    let layer = arch_layers(nodes, edges);
-    let n_cols = layer.iter().map(|l| l + 1).max().unwrap_or(1);
-
-    // Group node indices by layer (declaration order within a column).
-    let mut columns: Vec<Vec<usize>> = vec![Vec::new(); n_cols];
-    for (i, &l) in layer.iter().enumerate() {
-        columns[l].push(i);
-    }
+/// Text role for a node label under its emphasis.
+fn emphasis_text(emphasis: Option<Emphasis>) -> NativeRole {
+    if emphasis == Some(Emphasis::Muted) { NativeRole::TextSecondary } else { NativeRole::TextPrimary }
+}
 
-    // Column widths and x offsets.
-    let col_w: Vec<i64> = columns
+fn scene_architecture(
+    nodes: &[ArchNode],
+    groups: &[DiagramGroup],
+    edges: &[ArchEdge],
+    direction: DiagramDirection,
+) -> SceneBuild {
+    // Columns by dependency depth (rows when `direction=TB`); groups split
+    // the canvas into bands (see `group_bands`). One row height for every
+    // node, tall enough for the most-wrapped label.
+    let node_h = nodes.iter().map(|n| label_height(&n.label, NODE_H)).max().unwrap_or(NODE_H);
+    let sizes: Vec<(i64, i64)> = nodes.iter().map(|n| (label_width(&n.label), node_h)).collect();
+    let node_groups: Vec<Option<usize>> = nodes.iter().map(|n| n.group).collect();
+    let edge_idx: Vec<(usize, usize)> = edges
         .iter()
-        .map(|col| {
-            col.iter()
-                .map(|&i| label_width(&nodes[i].label))
-                .max()
-                .unwrap_or(80)
+        .filter_map(|e| {
+            Some((
+                nodes.iter().position(|n| n.id == e.from)?,
+                nodes.iter().position(|n| n.id == e.to)?,
+            ))
         })
         .collect();
-    let mut col_x = Vec::with_capacity(n_cols);
-    let mut x = MARGIN;
-    for w in &col_w {
-        col_x.push(x);
-        x += w + COL_GAP;
-    }
-
-    // Place each node, centered within its column.
-    let mut rects: Vec<Rect> = vec![
-        Rect { x: 0, y: 0, w: 0, h: 0 };
-        nodes.len()
-    ];
-    let mut max_rows = 0usize;
-    for (c, col) in columns.iter().enumerate() {
-        max_rows = max_rows.max(col.len());
-        for (row, &i) in col.iter().enumerate() {
-            let w = label_width(&nodes[i].label);
-            rects[i] = Rect {
-                x: col_x[c] + (col_w[c] - w) / 2,
-                y: MARGIN + row as i64 * (NODE_H + NODE_VGAP),
-                w,
-                h: NODE_H,
-            };
-        }
-    }
-
-    let total_w = if n_cols > 0 { x - COL_GAP + MARGIN } else { 2 * MARGIN };
-    let total_h = MARGIN * 2 + (max_rows.max(1) as i64) * (NODE_H + NODE_VGAP) - NODE_VGAP;
+    let Placed { rects, clusters, w: total_w, h: total_h } =
+        layered_layout(&sizes, COL_GAP, NODE_VGAP, &edge_idx, &node_groups, direction);
 
     let mut sc = SceneBuild::new(total_w, total_h);
     sc.items.push(SvgItem::ArrowDefs);
 
-    // Edges first so node boxes paint over the line ends.
-    for edge in edges {
-        let (Some(f), Some(t)) = (
-            nodes.iter().position(|n| n.id == edge.from),
-            nodes.iter().position(|n| n.id == edge.to),
-        ) else {
-            continue;
-        };
-        let (a, b) = (&rects[f], &rects[t]);
-
-        // Connect facing sides: left/right across columns, top/bottom within one.
-        let (x1, y1, x2, y2) = if b.cx() > a.cx() {
-            (a.x + a.w, a.cy(), b.x, b.cy())
-        } else if b.cx() < a.cx() {
-            (a.x, a.cy(), b.x + b.w, b.cy())
-        } else if b.cy() >= a.cy() {
-            (a.cx(), a.y + a.h, b.cx(), b.y)
-        } else {
-            (a.cx(), a.y, b.cx(), b.y + b.h)
-        };
-
+    // Clusters behind everything, then edges so node boxes paint over the
+    // line ends.
+    for (g, r) in &clusters {
+        push_cluster(&mut sc, r, &groups[*g].label);
+    }
+    // Connect facing sides: across layers, or side by side within one.
+    let (routed, reqs): (Vec<&ArchEdge>, Vec<RouteReq>) = edges
+        .iter()
+        .filter_map(|edge| {
+            let f = nodes.iter().position(|n| n.id == edge.from)?;
+            let t = nodes.iter().position(|n| n.id == edge.to)?;
+            let (exit, entry) = layered_sides(direction, &rects[f], &rects[t]);
+            Some((edge, RouteReq { from: f, to: t, exit, entry }))
+        })
+        .unzip();
+    let routes = route_edges(&rects, &[], &reqs);
+    let mut labels_taken: Vec<Rect> = Vec::new();
+    for (k, (edge, route)) in routed.iter().zip(&routes).enumerate() {
         let marker_start = if edge.bidirectional {
             NativeMarker::Arrow
         } else {
@@ -3143,21 +5190,14 @@ fn scene_architecture(nodes: &[ArchNode], edges: &[ArchEdge]) -> SceneBuild {
                 fill_none: true,
                 dash: None,
             },
-            line2(x1, y1, x2, y2, NativeRole::Stroke, 1.5, false, marker_start, NativeMarker::Arrow),
+            route_line(route, marker_start),
         );
 
         if let Some(label) = &edge.label {
+            let (lx, ly, anchor) = place_edge_label(&routes, k, label, &rects, (sc.w, sc.h), &mut labels_taken);
             sc.push(
                 Chrome::class("surfdoc-diagram-edge-label"),
-                text_at(
-                    (x1 + x2) / 2,
-                    (y1 + y2) / 2 - 5,
-                    label,
-                    NativeRole::TextSecondary,
-                    11,
-                    false,
-                    NativeAnchor::Middle,
-                ),
+                text_at(lx, ly, label, NativeRole::TextSecondary, 11, false, anchor),
             );
         }
     }
@@ -3165,24 +5205,15 @@ fn scene_architecture(nodes: &[ArchNode], edges: &[ArchEdge]) -> SceneBuild {
     // Node boxes with centered labels.
     for (i, node) in nodes.iter().enumerate() {
         let r = &rects[i];
+        sc.open_link(node.link.as_ref());
         sc.open_group("surfdoc-diagram-node");
         sc.push(
             Chrome::default(),
-            rect_at(r.x, r.y, r.w, r.h, 8, NativeRole::Surface, NativeRole::Stroke),
-        );
-        sc.push(
-            Chrome::default(),
-            text_at(
-                r.cx(),
-                r.cy() + 4, // optical baseline centering
-                &node.label,
-                NativeRole::TextPrimary,
-                13,
-                false,
-                NativeAnchor::Middle,
-            ),
+            with_href(rect_at(r.x, r.y, r.w, r.h, 8, NativeRole::Surface, NativeRole::Stroke), node.link.as_ref()),
         );
+        push_label(&mut sc, r.cx(), r.cy(), &node.label);
         sc.close_group();
+        sc.close_link(node.link.as_ref());
     }
 
     sc
@@ -3218,14 +5249,14 @@ fn erd_badges(field: &ErdField) -> String {
 
 /// Box width for an entity: widest of name / field rows, min 120.
 fn entity_width(entity: &ErdEntity) -> i64 {
-    let mut chars = entity.name.chars().count();
+    let mut text_w = LABEL_FONT.bold().width(&entity.name);
     for field in &entity.fields {
         let badges = erd_badges(field);
-        let row = field.name.chars().count()
-            + if badges.is_empty() { 0 } else { badges.chars().count() + 3 };
-        chars = chars.max(row);
+        let row = SMALL_FONT.width(&field.name)
+            + if badges.is_empty() { 0 } else { TINY_FONT.width(&badges) + 16 };
+        text_w = text_w.max(row);
     }
-    (chars as i64 * CHAR_W + 24).max(120)
+    (text_w + 24).max(120)
 }
 
 /// Box height for an entity: title bar + one row per field.
@@ -3413,8 +5444,13 @@ fn flow_node_width(node: &FlowNode) -> i64 {
     }
 }
 
-fn scene_flowchart(nodes: &[FlowNode], edges: &[FlowEdge]) -> SceneBuild {
-    let widths: Vec<i64> = nodes.iter().map(flow_node_width).collect();
+fn scene_flowchart(
+    nodes: &[FlowNode],
+    groups: &[DiagramGroup],
+    edges: &[FlowEdge],
+    direction: DiagramDirection,
+) -> SceneBuild {
+    let node_groups: Vec<Option<usize>> = nodes.iter().map(|n| n.group).collect();
     let edge_idx: Vec<(usize, usize)> = edges
         .iter()
         .filter_map(|e| {
@@ -3424,58 +5460,46 @@ fn scene_flowchart(nodes: &[FlowNode], edges: &[FlowEdge]) -> SceneBuild {
             ))
         })
         .collect();
-    let placed = layered_layout(&widths, FLOW_NODE_H, FLOW_ROW_GAP, FLOW_HGAP, &edge_idx);
+    let node_h = nodes.iter().map(|n| label_height(&n.label, FLOW_NODE_H)).max().unwrap_or(FLOW_NODE_H);
+    let sizes: Vec<(i64, i64)> = nodes.iter().map(|n| (flow_node_width(n), node_h)).collect();
+    let placed = layered_layout(&sizes, FLOW_ROW_GAP, FLOW_HGAP, &edge_idx, &node_groups, direction);
 
     let mut sc = SceneBuild::new(placed.w, placed.h);
     sc.items.push(SvgItem::ArrowDefs);
 
-    for edge in edges {
-        let (Some(f), Some(t)) = (
-            nodes.iter().position(|n| n.id == edge.from),
-            nodes.iter().position(|n| n.id == edge.to),
-        ) else {
-            continue;
-        };
-        let (a, b) = (&placed.rects[f], &placed.rects[t]);
-        let (mut x1, y1, mut x2, y2) = vert_edge_points(a, b);
-        // Opposite edges between the same two nodes trace the same vertical
-        // segment; nudge each member of the pair sideways (mirrors state).
-        let vertical = x1 == x2;
-        let reverse = f != t
-            && edges
-                .iter()
-                .any(|o| o.from == edge.to && o.to == edge.from);
-        let dx = if vertical && reverse {
-            if f < t {
-                -10
-            } else {
-                10
-            }
-        } else {
-            0
-        };
-        x1 += dx;
-        x2 += dx;
+    // Clusters first: everything else paints on top.
+    for (g, r) in &placed.clusters {
+        push_cluster(&mut sc, r, &groups[*g].label);
+    }
+
+    // Edges route around the nodes; opposite edges between one pair land on
+    // separate ports, so both arrows (and both labels) stay distinguishable.
+    let (routed, reqs): (Vec<&FlowEdge>, Vec<RouteReq>) = edges
+        .iter()
+        .filter_map(|edge| {
+            let f = nodes.iter().position(|n| n.id == edge.from)?;
+            let t = nodes.iter().position(|n| n.id == edge.to)?;
+            let (exit, entry) = layered_sides(direction, &placed.rects[f], &placed.rects[t]);
+            Some((edge, RouteReq { from: f, to: t, exit, entry }))
+        })
+        .unzip();
+    let outlines: Vec<Outline> = nodes
+        .iter()
+        .map(|n| if n.shape == FlowShape::Diamond { Outline::Diamond } else { Outline::Box })
+        .collect();
+    let routes = route_edges(&placed.rects, &outlines, &reqs);
+    let mut labels_taken: Vec<Rect> = Vec::new();
+    for (k, (edge, route)) in routed.iter().zip(&routes).enumerate() {
         sc.push(
             Chrome {
                 class: Some("surfdoc-diagram-edge"),
                 fill_none: true,
                 dash: None,
             },
-            line2(x1, y1, x2, y2, NativeRole::Stroke, 1.5, false, NativeMarker::None, NativeMarker::Arrow),
+            emphasize(route_line(route, NativeMarker::None), edge.emphasis),
         );
         if let Some(label) = &edge.label {
-            // Vertical edges get their label beside the line (a centered
-            // label would sit on the stroke); paired edges label outward.
-            let (lx, ly, anchor) = if vertical {
-                if dx < 0 {
-                    (x1 - 6, (y1 + y2) / 2 + 4, NativeAnchor::End)
-                } else {
-                    (x1 + 6, (y1 + y2) / 2 + 4, NativeAnchor::Start)
-                }
-            } else {
-                ((x1 + x2) / 2, (y1 + y2) / 2 - 5, NativeAnchor::Middle)
-            };
+            let (lx, ly, anchor) = place_edge_label(&routes, k, label, &placed.rects, (sc.w, sc.h), &mut labels_taken);
             sc.push(
                 Chrome::class("surfdoc-diagram-edge-label"),
                 text_at(lx, ly, label, NativeRole::TextSecondary, 11, false, anchor),
@@ -3485,6 +5509,7 @@ fn scene_flowchart(nodes: &[FlowNode], edges: &[FlowEdge]) -> SceneBuild {
 
     for (i, node) in nodes.iter().enumerate() {
         let r = &placed.rects[i];
+        sc.open_link(node.link.as_ref());
         sc.open_group("surfdoc-diagram-node");
         let shape = match node.shape {
             FlowShape::Box => rect_at(r.x, r.y, r.w, r.h, 4, NativeRole::Surface, NativeRole::Stroke),
@@ -3498,54 +5523,222 @@ fn scene_flowchart(nodes: &[FlowNode], edges: &[FlowEdge]) -> SceneBuild {
                 ],
                 fill: NativeRole::Surface,
                 stroke: NativeRole::Stroke,
+                href: None,
             },
         };
-        sc.push(Chrome::default(), shape);
-        sc.push(
-            Chrome::default(),
-            text_at(r.cx(), r.cy() + 4, &node.label, NativeRole::TextPrimary, 13, false, NativeAnchor::Middle),
-        );
+        sc.push(Chrome::default(), with_href(emphasize(shape, node.emphasis), node.link.as_ref()));
+        push_label_as(&mut sc, r.cx(), r.cy(), &node.label, emphasis_text(node.emphasis));
         sc.close_group();
+        sc.close_link(node.link.as_ref());
+    }
+
+    sc
+}
+
+// ------------------------------------------------------------------
+// SVG rendering — sequence
+// ------------------------------------------------------------------
+
+/// Actor header box height.
+const SEQ_ACTOR_H: i64 = 30;
+/// Vertical spacing between timeline event rows.
+const SEQ_MSG_GAP: i64 = 38;
+/// Horizontal gap between actor columns.
+const SEQ_COL_GAP: i64 = 60;
+/// Width of an activation bar.
+const SEQ_ACT_W: i64 = 10;
+/// Row heights of fragment structure: the opener (frame top + tab), a
+/// section divider, and the closer (frame bottom).
+const SEQ_FRAME_OPEN_H: i64 = 24;
+const SEQ_FRAME_DIVIDER_H: i64 = 24;
+const SEQ_FRAME_CLOSE_H: i64 = 16;
+/// Height of the operator tab in a fragment's top-left corner.
+const SEQ_TAB_H: i64 = 18;
+/// Horizontal inset per nesting level, so nested frame borders never meet.
+const SEQ_FRAME_INSET: i64 = 6;
+/// Note box height.
+const SEQ_NOTE_H: i64 = 24;
+
+/// One row of the flattened sequence timeline: a plain event, or the
+/// opener / section divider / closer of fragment `frame`.
+enum SeqRow<'a> {
+    Event(&'a SeqEvent),
+    Open(usize),
+    Divider(usize, usize),
+    Close(usize),
+}
+
+/// A combined fragment found while flattening: its operator, sections and
+/// nesting depth (0 = top level).
+struct SeqFrame<'a> {
+    kind: FragmentKind,
+    sections: &'a [SeqSection],
+    depth: i64,
+}
+
+/// Flatten nested fragments into timeline rows (declaration order).
+fn flatten_sequence<'a>(events: &'a [SeqEvent], depth: i64, rows: &mut Vec<SeqRow<'a>>, frames: &mut Vec<SeqFrame<'a>>) {
+    for ev in events {
+        if let SeqEvent::Fragment { kind, sections } = ev {
+            let f = frames.len();
+            frames.push(SeqFrame { kind: *kind, sections, depth });
+            rows.push(SeqRow::Open(f));
+            for (k, section) in sections.iter().enumerate() {
+                if k > 0 {
+                    rows.push(SeqRow::Divider(f, k));
+                }
+                flatten_sequence(&section.events, depth + 1, rows, frames);
+            }
+            rows.push(SeqRow::Close(f));
+        } else {
+            rows.push(SeqRow::Event(ev));
+        }
+    }
+}
+
+/// The leftmost and rightmost actor columns an event list touches
+/// (recursing into nested fragments), or `None` when it touches none.
+fn seq_actor_span(events: &[SeqEvent], idx: &dyn Fn(&str) -> Option<usize>) -> Option<(usize, usize)> {
+    let mut span: Option<(usize, usize)> = None;
+    let mut add = |k: Option<usize>| {
+        if let Some(k) = k {
+            span = Some(span.map_or((k, k), |(lo, hi)| (lo.min(k), hi.max(k))));
+        }
+    };
+    for ev in events {
+        match ev {
+            SeqEvent::Message { from, to, .. } => {
+                add(idx(from));
+                add(idx(to));
+            }
+            SeqEvent::Activate(id) | SeqEvent::Deactivate(id) => add(idx(id)),
+            SeqEvent::Note { actors, .. } => actors.iter().for_each(|id| add(idx(id))),
+            SeqEvent::Fragment { sections, .. } => {
+                for section in sections {
+                    if let Some((lo, hi)) = seq_actor_span(&section.events, idx) {
+                        add(Some(lo));
+                        add(Some(hi));
+                    }
+                }
+            }
+        }
     }
-
-    sc
+    span
 }
 
-// ------------------------------------------------------------------
-// SVG rendering — sequence
-// ------------------------------------------------------------------
-
-/// Actor header box height.
-const SEQ_ACTOR_H: i64 = 30;
-/// Vertical spacing between timeline event rows.
-const SEQ_MSG_GAP: i64 = 38;
-/// Horizontal gap between actor columns.
-const SEQ_COL_GAP: i64 = 60;
-/// Width of an activation bar.
-const SEQ_ACT_W: i64 = 10;
+/// Width of a note box for its (11px) text.
+fn seq_note_width(text: &str) -> i64 {
+    (NOTE_FONT.width(text) + 16).max(60)
+}
 
 fn scene_sequence(actors: &[SeqActor], events: &[SeqEvent]) -> SceneBuild {
     let n = actors.len();
     let col_w = actors.iter().map(|a| label_width(&a.label)).max().unwrap_or(80);
     let spacing = col_w + SEQ_COL_GAP;
-    let cx = |k: usize| MARGIN + col_w / 2 + k as i64 * spacing;
     let idx = |id: &str| actors.iter().position(|a| a.id == id);
 
-    let lifeline_top = MARGIN + SEQ_ACTOR_H;
-    let ey = |i: i64| lifeline_top + (i + 1) * SEQ_MSG_GAP;
-    let n_events = events.len() as i64;
-    let bottom = lifeline_top + (n_events + 1) * SEQ_MSG_GAP;
+    let mut rows: Vec<SeqRow> = Vec::new();
+    let mut frames: Vec<SeqFrame> = Vec::new();
+    flatten_sequence(events, 0, &mut rows, &mut frames);
 
-    let total_w = if n > 0 {
-        MARGIN * 2 + n as i64 * col_w + (n as i64 - 1) * SEQ_COL_GAP
+    // Note boxes in unshifted coordinates: `(row, x, w)`. A note left of
+    // the first lifeline shifts the whole diagram right.
+    let base_cx = |k: usize| MARGIN + col_w / 2 + k as i64 * spacing;
+    let mut note_boxes: Vec<(usize, i64, i64)> = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        let SeqRow::Event(SeqEvent::Note { placement, actors: ids, text }) = row else {
+            continue;
+        };
+        let cols: Vec<usize> = ids.iter().filter_map(|id| idx(id)).collect();
+        let (Some(&lo), Some(&hi)) = (cols.iter().min(), cols.iter().max()) else {
+            continue;
+        };
+        let w = seq_note_width(text);
+        let (x, w) = match placement {
+            NotePlacement::LeftOf => (base_cx(lo) - 10 - w, w),
+            NotePlacement::RightOf => (base_cx(lo) + 10, w),
+            NotePlacement::Over => {
+                let w = w.max(base_cx(hi) - base_cx(lo) + 40);
+                ((base_cx(lo) + base_cx(hi)) / 2 - w / 2, w)
+            }
+        };
+        note_boxes.push((i, x, w));
+    }
+    let shift = note_boxes.iter().map(|&(_, x, _)| MARGIN - x).max().unwrap_or(0).max(0);
+    let cx = |k: usize| base_cx(k) + shift;
+
+    // Row baselines: plain events keep the uniform message gap.
+    let actor_h = actors.iter().map(|a| label_height(&a.label, SEQ_ACTOR_H)).max().unwrap_or(SEQ_ACTOR_H);
+    let lifeline_top = MARGIN + actor_h;
+    let mut row_y: Vec<i64> = Vec::with_capacity(rows.len());
+    let mut y = lifeline_top;
+    for row in &rows {
+        y += match row {
+            SeqRow::Event(_) => SEQ_MSG_GAP,
+            SeqRow::Open(_) => SEQ_FRAME_OPEN_H,
+            SeqRow::Divider(..) => SEQ_FRAME_DIVIDER_H,
+            SeqRow::Close(_) => SEQ_FRAME_CLOSE_H,
+        };
+        row_y.push(y);
+    }
+    let ey = |i: usize| row_y[i];
+    let bottom = y + SEQ_MSG_GAP;
+
+    let mut total_w = if n > 0 {
+        MARGIN * 2 + n as i64 * col_w + (n as i64 - 1) * SEQ_COL_GAP + shift
     } else {
         2 * MARGIN
     };
+    for &(_, x, w) in &note_boxes {
+        total_w = total_w.max(x + shift + w + MARGIN);
+    }
     let total_h = bottom + MARGIN;
 
     let mut sc = SceneBuild::new(total_w, total_h);
     sc.items.push(SvgItem::ArrowDefs);
 
+    // Fragment geometry: horizontal extent over the lifelines involved
+    // (all of them when none are), vertical extent from the rows.
+    let frame_x = |f: &SeqFrame| -> (i64, i64) {
+        let (lo, hi) = f
+            .sections
+            .iter()
+            .filter_map(|section| seq_actor_span(&section.events, &idx))
+            .reduce(|(a, b), (c, d)| (a.min(c), b.max(d)))
+            .unwrap_or((0, n.saturating_sub(1)));
+        let inset = SEQ_FRAME_INSET * f.depth;
+        (cx(lo) - col_w / 2 + inset, cx(hi) + col_w / 2 - inset)
+    };
+    let frame_top = |f: usize| {
+        let i = rows.iter().position(|r| matches!(r, SeqRow::Open(g) if *g == f)).expect("opener row");
+        ey(i) - SEQ_FRAME_OPEN_H / 2
+    };
+    let frame_bottom = |f: usize| {
+        let i = rows.iter().position(|r| matches!(r, SeqRow::Close(g) if *g == f)).expect("closer row");
+        ey(i) - SEQ_FRAME_CLOSE_H / 4
+    };
+
+    // `rect` highlights shade the background under everything else.
+    if n > 0 {
+        for (f, frame) in frames.iter().enumerate() {
+            if frame.kind != FragmentKind::Rect {
+                continue;
+            }
+            let (l, r) = frame_x(frame);
+            let (top, bot) = (frame_top(f), frame_bottom(f));
+            sc.push(
+                Chrome::class("surfdoc-diagram-highlight"),
+                rect_at(l, top, r - l, bot - top, 4, NativeRole::Cluster, NativeRole::Cluster),
+            );
+            if let Some(guard) = &frame.sections[0].guard {
+                sc.push(
+                    Chrome::class("surfdoc-diagram-frame-guard"),
+                    text_at(l + 6, top + 13, guard, NativeRole::TextSecondary, 11, false, NativeAnchor::Start),
+                );
+            }
+        }
+    }
+
     // Lifelines.
     for k in 0..n {
         sc.push(
@@ -3558,25 +5751,101 @@ fn scene_sequence(actors: &[SeqActor], events: &[SeqEvent]) -> SceneBuild {
         );
     }
 
+    // Fragment frames: outline, operator tab + guard, section dividers.
+    if n > 0 {
+        for (f, frame) in frames.iter().enumerate() {
+            if frame.kind == FragmentKind::Rect {
+                continue;
+            }
+            let (l, r) = frame_x(frame);
+            let (top, bot) = (frame_top(f), frame_bottom(f));
+            sc.push(
+                Chrome {
+                    class: Some("surfdoc-diagram-frame"),
+                    fill_none: true,
+                    dash: None,
+                },
+                NativeShape::Line {
+                    points: vec![pt(l, top), pt(r, top), pt(r, bot), pt(l, bot), pt(l, top)],
+                    stroke: NativeRole::Stroke,
+                    stroke_width: 1.0,
+                    dashed: false,
+                    marker_start: NativeMarker::None,
+                    marker_end: NativeMarker::None,
+                },
+            );
+            let word = frame.kind.word();
+            let tab_w = NOTE_FONT.bold().width(word) + 18;
+            sc.push(
+                Chrome::class("surfdoc-diagram-frame-tab"),
+                NativeShape::Polygon {
+                    points: vec![
+                        pt(l, top),
+                        pt(l + tab_w, top),
+                        pt(l + tab_w, top + SEQ_TAB_H - 6),
+                        pt(l + tab_w - 6, top + SEQ_TAB_H),
+                        pt(l, top + SEQ_TAB_H),
+                    ],
+                    fill: NativeRole::SurfaceAlt,
+                    stroke: NativeRole::Stroke,
+                    href: None,
+                },
+            );
+            sc.push(
+                Chrome::class("surfdoc-diagram-frame-label"),
+                text_at(l + 6, top + 13, word, NativeRole::TextPrimary, 11, true, NativeAnchor::Start),
+            );
+            if let Some(guard) = &frame.sections[0].guard {
+                sc.push(
+                    Chrome::class("surfdoc-diagram-frame-guard"),
+                    text_at(l + tab_w + 8, top + 13, &format!("[{guard}]"), NativeRole::TextSecondary, 11, false, NativeAnchor::Start),
+                );
+            }
+            for (i, row) in rows.iter().enumerate() {
+                let SeqRow::Divider(g, k) = row else {
+                    continue;
+                };
+                if *g != f {
+                    continue;
+                }
+                let yd = ey(i) - SEQ_FRAME_DIVIDER_H / 2;
+                sc.push(
+                    Chrome {
+                        class: Some("surfdoc-diagram-frame-divider"),
+                        fill_none: false,
+                        dash: Some("4 4"),
+                    },
+                    line2(l, yd, r, yd, NativeRole::Stroke, 1.0, true, NativeMarker::None, NativeMarker::None),
+                );
+                if let Some(guard) = &frame.sections[*k].guard {
+                    sc.push(
+                        Chrome::class("surfdoc-diagram-frame-guard"),
+                        text_at(l + 8, yd + 13, &format!("[{guard}]"), NativeRole::TextSecondary, 11, false, NativeAnchor::Start),
+                    );
+                }
+            }
+        }
+    }
+
     // Activation bars (computed from activate/deactivate events).
     let mut stacks: Vec<Vec<i64>> = vec![Vec::new(); n];
     let mut bars: Vec<(usize, i64, i64)> = Vec::new();
-    for (i, ev) in events.iter().enumerate() {
-        let y = ey(i as i64);
-        match ev {
-            SeqEvent::Activate(id) => {
+    for (i, row) in rows.iter().enumerate() {
+        let y = ey(i);
+        match row {
+            SeqRow::Event(SeqEvent::Activate(id)) => {
                 if let Some(k) = idx(id) {
                     stacks[k].push(y);
                 }
             }
-            SeqEvent::Deactivate(id) => {
+            SeqRow::Event(SeqEvent::Deactivate(id)) => {
                 if let Some(k) = idx(id) {
                     if let Some(start) = stacks[k].pop() {
                         bars.push((k, start, y));
                     }
                 }
             }
-            SeqEvent::Message { .. } => {}
+            _ => {}
         }
     }
     for (k, stack) in stacks.iter().enumerate() {
@@ -3600,12 +5869,12 @@ fn scene_sequence(actors: &[SeqActor], events: &[SeqEvent]) -> SceneBuild {
     }
 
     // Messages.
-    for (i, ev) in events.iter().enumerate() {
-        if let SeqEvent::Message { from, to, label, dashed } = ev {
+    for (i, row) in rows.iter().enumerate() {
+        if let SeqRow::Event(SeqEvent::Message { from, to, label, dashed }) = row {
             let (Some(a), Some(b)) = (idx(from), idx(to)) else {
                 continue;
             };
-            let y = ey(i as i64);
+            let y = ey(i);
             let dash = if *dashed { Some("6 4") } else { None };
             if a == b {
                 // Self-message: a small out-and-back loop.
@@ -3651,26 +5920,33 @@ fn scene_sequence(actors: &[SeqActor], events: &[SeqEvent]) -> SceneBuild {
         }
     }
 
+    // Notes, above messages and frames.
+    for &(i, x, w) in &note_boxes {
+        let SeqRow::Event(SeqEvent::Note { text, .. }) = &rows[i] else {
+            continue;
+        };
+        let (x, y) = (x + shift, ey(i));
+        sc.open_group("surfdoc-diagram-note");
+        sc.push(
+            Chrome::default(),
+            rect_at(x, y - SEQ_NOTE_H + 6, w, SEQ_NOTE_H, 2, NativeRole::SurfaceAlt, NativeRole::Stroke),
+        );
+        sc.push(
+            Chrome::default(),
+            text_at(x + w / 2, y - 2, text, NativeRole::TextPrimary, 11, false, NativeAnchor::Middle),
+        );
+        sc.close_group();
+    }
+
     // Actor header boxes (drawn last so they sit above lifelines).
     for (k, actor) in actors.iter().enumerate() {
         let w = label_width(&actor.label);
         sc.open_group("surfdoc-diagram-actor");
         sc.push(
             Chrome::default(),
-            rect_at(cx(k) - w / 2, MARGIN, w, SEQ_ACTOR_H, 4, NativeRole::Surface, NativeRole::Stroke),
-        );
-        sc.push(
-            Chrome::default(),
-            text_at(
-                cx(k),
-                MARGIN + SEQ_ACTOR_H / 2 + 4,
-                &actor.label,
-                NativeRole::TextPrimary,
-                13,
-                false,
-                NativeAnchor::Middle,
-            ),
+            rect_at(cx(k) - w / 2, MARGIN, w, actor_h, 4, NativeRole::Surface, NativeRole::Stroke),
         );
+        push_label(&mut sc, cx(k), MARGIN + actor_h / 2, &actor.label);
         sc.close_group();
     }
 
@@ -3705,7 +5981,37 @@ fn gantt_row_count(tasks: &[GanttTask]) -> i64 {
     rows
 }
 
-fn scene_gantt(tasks: &[GanttTask], dated: bool) -> SceneBuild {
+/// Half-width of a milestone diamond.
+const GANTT_MILESTONE_R: i64 = GANTT_BAR_H / 2 + 1;
+
+/// Bar / milestone fill for a task status.
+fn gantt_fill(status: Option<GanttStatus>) -> NativeRole {
+    match status {
+        None => NativeRole::Muted,
+        Some(GanttStatus::Done) => NativeRole::Success,
+        Some(GanttStatus::Active) => NativeRole::Highlight,
+        Some(GanttStatus::Crit) => NativeRole::Danger,
+    }
+}
+
+/// Finish-to-start dependency route from the end of one task (`from`, at
+/// row-center `fy`) to the start of another (`to`, row-center `ty`). With
+/// room between them the line drops straight down (or up) and runs into the
+/// task; otherwise it doubles back along the row boundary next to the
+/// dependent task.
+fn gantt_dependency_route(from: i64, fy: i64, to: i64, ty: i64) -> Vec<(i64, i64)> {
+    let turn = from + 8;
+    if to >= turn + 8 {
+        return vec![(from, fy), (turn, fy), (turn, ty), (to, ty)];
+    }
+    let boundary = if ty > fy { ty - GANTT_ROW_H / 2 } else { ty + GANTT_ROW_H / 2 };
+    vec![(from, fy), (turn, fy), (turn, boundary), (to - 8, boundary), (to - 8, ty), (to, ty)]
+}
+
+/// Lay out a Gantt chart. `today` (from the `today` line, else the caller's
+/// [`install_diagram_today`] date) draws a dashed marker when it falls on
+/// the axis.
+fn scene_gantt(tasks: &[GanttTask], dated: bool, today: Option<i64>) -> SceneBuild {
     if tasks.is_empty() {
         return SceneBuild::new(2 * MARGIN, 2 * MARGIN);
     }
@@ -3714,7 +6020,7 @@ fn scene_gantt(tasks: &[GanttTask], dated: bool) -> SceneBuild {
     let t1 = tasks.iter().map(|t| t.start + t.duration).max().unwrap_or(0);
     let span = (t1 - t0).max(1);
     let unit_w = (600 / span).clamp(6, 40);
-    let label_col_w = tasks.iter().map(|t| label_width(&t.label)).max().unwrap_or(80);
+    let label_col_w = tasks.iter().map(|t| line_width(&t.label)).max().unwrap_or(80);
     let chart_x = MARGIN + label_col_w + 12;
     let chart_w = span * unit_w;
 
@@ -3753,7 +6059,9 @@ fn scene_gantt(tasks: &[GanttTask], dated: bool) -> SceneBuild {
         tick += stride;
     }
 
-    // Rows: section headers + task bars.
+    // Rows: section headers + task bars. Each task's horizontal extent and
+    // row center are kept for the dependency arrows.
+    let mut extents: Vec<(i64, i64, i64)> = Vec::with_capacity(tasks.len());
     let mut y = GANTT_TOP;
     let mut prev: Option<&str> = None;
     for (i, t) in tasks.iter().enumerate() {
@@ -3773,20 +6081,74 @@ fn scene_gantt(tasks: &[GanttTask], dated: bool) -> SceneBuild {
             text_at(MARGIN, y + GANTT_ROW_H / 2 + 4, &t.label, NativeRole::TextPrimary, 12, false, NativeAnchor::Start),
         );
         let bx = chart_x + (t.start - t0) * unit_w;
-        let bw = (t.duration * unit_w).max(2);
+        let cy = y + GANTT_ROW_H / 2;
+        if t.milestone {
+            let r = GANTT_MILESTONE_R;
+            sc.push(
+                Chrome::class("surfdoc-diagram-milestone"),
+                NativeShape::Polygon {
+                    points: vec![pt(bx, cy - r), pt(bx + r, cy), pt(bx, cy + r), pt(bx - r, cy)],
+                    fill: gantt_fill(t.status),
+                    stroke: NativeRole::Stroke,
+                    href: None,
+                },
+            );
+            extents.push((bx - r, bx + r, cy));
+        } else {
+            let bw = (t.duration * unit_w).max(2);
+            sc.push(
+                Chrome::class("surfdoc-diagram-bar"),
+                rect_at(
+                    bx,
+                    y + (GANTT_ROW_H - GANTT_BAR_H) / 2,
+                    bw,
+                    GANTT_BAR_H,
+                    3,
+                    gantt_fill(t.status),
+                    NativeRole::Stroke,
+                ),
+            );
+            extents.push((bx, bx + bw, cy));
+        }
+        y += GANTT_ROW_H;
+    }
+
+    // Dependency arrows, end of each `after` task to the dependent's start.
+    if tasks.iter().any(|t| !t.after.is_empty()) {
+        sc.items.push(SvgItem::ArrowDefs);
+    }
+    for (i, t) in tasks.iter().enumerate() {
+        for id in &t.after {
+            let Some(j) = tasks.iter().position(|d| d.id.as_deref() == Some(id.as_str())) else {
+                continue;
+            };
+            let (_, from, fy) = extents[j];
+            let (to, _, ty) = extents[i];
+            sc.push(
+                Chrome {
+                    class: Some("surfdoc-diagram-dependency"),
+                    fill_none: true,
+                    dash: None,
+                },
+                route_line(&gantt_dependency_route(from, fy, to, ty), NativeMarker::None),
+            );
+        }
+    }
+
+    if let Some(today) = today.filter(|d| (t0..=t1).contains(d)) {
+        let x = chart_x + (today - t0) * unit_w;
         sc.push(
-            Chrome::class("surfdoc-diagram-bar"),
-            rect_at(
-                bx,
-                y + (GANTT_ROW_H - GANTT_BAR_H) / 2,
-                bw,
-                GANTT_BAR_H,
-                3,
-                NativeRole::Muted,
-                NativeRole::Stroke,
-            ),
+            Chrome {
+                class: Some("surfdoc-diagram-today"),
+                fill_none: false,
+                dash: Some("4 3"),
+            },
+            line2(x, GANTT_TOP, x, bottom, NativeRole::Danger, 1.5, true, NativeMarker::None, NativeMarker::None),
+        );
+        sc.push(
+            Chrome::class("surfdoc-diagram-today"),
+            text_at(x, bottom + 12, "today", NativeRole::Danger, 10, false, NativeAnchor::Middle),
         );
-        y += GANTT_ROW_H;
     }
 
     sc
@@ -3799,11 +6161,7 @@ fn scene_gantt(tasks: &[GanttTask], dated: bool) -> SceneBuild {
 /// Uniform state node height.
 const STATE_NODE_H: i64 = 40;
 
-fn scene_state(nodes: &[StateNode], transitions: &[StateTransition]) -> SceneBuild {
-    let widths: Vec<i64> = nodes
-        .iter()
-        .map(|n| if n.initial || n.final_ { 24 } else { label_width(&n.label) })
-        .collect();
+fn scene_state(nodes: &[StateNode], transitions: &[StateTransition], direction: DiagramDirection) -> SceneBuild {
     let edge_idx: Vec<(usize, usize)> = transitions
         .iter()
         .filter_map(|tr| {
@@ -3813,59 +6171,42 @@ fn scene_state(nodes: &[StateNode], transitions: &[StateTransition]) -> SceneBui
             ))
         })
         .collect();
-    let placed = layered_layout(&widths, STATE_NODE_H, 50, 40, &edge_idx);
+    let node_h = nodes.iter().map(|n| label_height(&n.label, STATE_NODE_H)).max().unwrap_or(STATE_NODE_H);
+    let sizes: Vec<(i64, i64)> = nodes
+        .iter()
+        .map(|n| (if n.initial || n.final_ { 24 } else { label_width(&n.label) }, node_h))
+        .collect();
+    let placed = layered_layout(&sizes, 50, 40, &edge_idx, &[], direction);
 
     let mut sc = SceneBuild::new(placed.w, placed.h);
     sc.items.push(SvgItem::ArrowDefs);
 
-    for tr in transitions {
-        let (Some(f), Some(t)) = (
-            nodes.iter().position(|n| n.id == tr.from),
-            nodes.iter().position(|n| n.id == tr.to),
-        ) else {
-            continue;
-        };
-        let (a, b) = (&placed.rects[f], &placed.rects[t]);
-        let (mut x1, y1, mut x2, y2) = vert_edge_points(a, b);
-        // An opposite transition between the same two states traces the
-        // exact same vertical segment; nudge each member of the pair
-        // sideways so both arrows (and both labels) stay distinguishable.
-        let vertical = x1 == x2;
-        let reverse = f != t
-            && transitions
-                .iter()
-                .any(|o| o.from == tr.to && o.to == tr.from);
-        let dx = if vertical && reverse {
-            if f < t {
-                -10
-            } else {
-                10
-            }
-        } else {
-            0
-        };
-        x1 += dx;
-        x2 += dx;
+    let (routed, reqs): (Vec<&StateTransition>, Vec<RouteReq>) = transitions
+        .iter()
+        .filter_map(|tr| {
+            let f = nodes.iter().position(|n| n.id == tr.from)?;
+            let t = nodes.iter().position(|n| n.id == tr.to)?;
+            let (exit, entry) = layered_sides(direction, &placed.rects[f], &placed.rects[t]);
+            Some((tr, RouteReq { from: f, to: t, exit, entry }))
+        })
+        .unzip();
+    let outlines: Vec<Outline> = nodes
+        .iter()
+        .map(|n| if n.initial || n.final_ { Outline::Point } else { Outline::Box })
+        .collect();
+    let routes = route_edges(&placed.rects, &outlines, &reqs);
+    let mut labels_taken: Vec<Rect> = Vec::new();
+    for (k, (tr, route)) in routed.iter().zip(&routes).enumerate() {
         sc.push(
             Chrome {
                 class: Some("surfdoc-diagram-transition"),
                 fill_none: true,
                 dash: None,
             },
-            line2(x1, y1, x2, y2, NativeRole::Stroke, 1.5, false, NativeMarker::None, NativeMarker::Arrow),
+            emphasize(route_line(route, NativeMarker::None), tr.emphasis),
         );
         if let Some(label) = &tr.label {
-            // Vertical edges get their label beside the line (a centered
-            // label would sit on the stroke); paired edges label outward.
-            let (lx, ly, anchor) = if vertical {
-                if dx < 0 {
-                    (x1 - 6, (y1 + y2) / 2 + 4, NativeAnchor::End)
-                } else {
-                    (x1 + 6, (y1 + y2) / 2 + 4, NativeAnchor::Start)
-                }
-            } else {
-                ((x1 + x2) / 2, (y1 + y2) / 2 - 5, NativeAnchor::Middle)
-            };
+            let (lx, ly, anchor) = place_edge_label(&routes, k, label, &placed.rects, (sc.w, sc.h), &mut labels_taken);
             sc.push(
                 Chrome::class("surfdoc-diagram-transition-label"),
                 text_at(lx, ly, label, NativeRole::TextSecondary, 11, false, anchor),
@@ -3884,7 +6225,7 @@ fn scene_state(nodes: &[StateNode], transitions: &[StateTransition]) -> SceneBui
             sc.open_group("surfdoc-diagram-final");
             sc.push(
                 Chrome::default(),
-                circle_at(r.cx(), r.cy(), 10, None, Some(NativeRole::Stroke)),
+                emphasize(circle_at(r.cx(), r.cy(), 10, None, Some(NativeRole::Stroke)), node.emphasis),
             );
             sc.push(
                 Chrome::default(),
@@ -3892,16 +6233,13 @@ fn scene_state(nodes: &[StateNode], transitions: &[StateTransition]) -> SceneBui
             );
             sc.close_group();
         } else {
+            let outline = rect_at(r.x, r.y, r.w, r.h, 12, NativeRole::Surface, NativeRole::Stroke);
+            sc.open_link(node.link.as_ref());
             sc.open_group("surfdoc-diagram-state");
-            sc.push(
-                Chrome::default(),
-                rect_at(r.x, r.y, r.w, r.h, 12, NativeRole::Surface, NativeRole::Stroke),
-            );
-            sc.push(
-                Chrome::default(),
-                text_at(r.cx(), r.cy() + 4, &node.label, NativeRole::TextPrimary, 13, false, NativeAnchor::Middle),
-            );
+            sc.push(Chrome::default(), with_href(emphasize(outline, node.emphasis), node.link.as_ref()));
+            push_label_as(&mut sc, r.cx(), r.cy(), &node.label, emphasis_text(node.emphasis));
             sc.close_group();
+            sc.close_link(node.link.as_ref());
         }
     }
 
@@ -3961,7 +6299,7 @@ fn scene_mindmap(nodes: &[MindNode]) -> SceneBuild {
     let max_depth = nodes.iter().map(|n| n.depth).max().unwrap_or(0);
     let mut col_w = vec![0i64; max_depth + 1];
     for n in nodes {
-        let w = label_width(&n.label);
+        let w = line_width(&n.label);
         if w > col_w[n.depth] {
             col_w[n.depth] = w;
         }
@@ -4050,14 +6388,14 @@ fn class_title_h(class: &ClassBox) -> i64 {
 /// Box width for a class: widest of name / `«stereotype»` / member rows,
 /// min 120 (same sizing rule as ERD entities).
 fn class_width(class: &ClassBox) -> i64 {
-    let mut chars = class.name.chars().count();
+    let mut text_w = LABEL_FONT.bold().width(&class.name);
     if let Some(st) = &class.stereotype {
-        chars = chars.max(st.chars().count() + 2); // « »
+        text_w = text_w.max(TINY_FONT.width(&format!("\u{ab}{st}\u{bb}")));
     }
     for member in class.fields.iter().chain(&class.methods) {
-        chars = chars.max(class_member_text(member).chars().count());
+        text_w = text_w.max(SMALL_FONT.width(&class_member_text(member)));
     }
-    (chars as i64 * CHAR_W + 24).max(120)
+    (text_w + 24).max(120)
 }
 
 /// Box height for a class: title bar + one row per field and method.
@@ -4065,13 +6403,28 @@ fn class_height(class: &ClassBox) -> i64 {
     class_title_h(class) + (class.fields.len() + class.methods.len()) as i64 * ERD_ROW_H
 }
 
-fn scene_class(classes: &[ClassBox], relations: &[ClassRelation]) -> SceneBuild {
-    // Same uniform grid as ERD, reusing its cell gap and row metrics.
+fn scene_class(classes: &[ClassBox], relations: &[ClassRelation], direction: DiagramDirection) -> SceneBuild {
     let sizes: Vec<(i64, i64)> = classes
         .iter()
         .map(|c| (class_width(c), class_height(c)))
         .collect();
-    let (rects, total_w, total_h) = grid_layout(&sizes, ERD_PER_ROW, ERD_GAP, ERD_TITLE_H);
+    // Related classes lay out in layers — supertypes and wholes above their
+    // subtypes and parts. Unrelated classes keep the ERD grid (its cell gap
+    // and row metrics): there is no hierarchy to show.
+    let (rects, total_w, total_h) = if relations.is_empty() {
+        grid_layout(&sizes, ERD_PER_ROW, ERD_GAP, ERD_TITLE_H)
+    } else {
+        let edge_idx: Vec<(usize, usize)> = relations
+            .iter()
+            .filter_map(|rel| {
+                let f = classes.iter().position(|c| c.name == rel.from)?;
+                let t = classes.iter().position(|c| c.name == rel.to)?;
+                Some(if rel.kind == ClassRelationKind::Inheritance { (t, f) } else { (f, t) })
+            })
+            .collect();
+        let placed = layered_layout(&sizes, ERD_GAP, ERD_GAP / 2, &edge_idx, &[], direction);
+        (placed.rects, placed.w, placed.h)
+    };
 
     let mut sc = SceneBuild::new(total_w, total_h);
     sc.items.push(SvgItem::ClassDefs);
@@ -4228,9 +6581,9 @@ fn scene_timeline(events: &[TimelineEvent]) -> SceneBuild {
     let slot = events
         .iter()
         .map(|e| {
-            let lw = label_width(&e.label);
+            let lw = line_width(&e.label);
             match &e.marker {
-                Some(m) => lw.max(label_width(m)),
+                Some(m) => lw.max(line_width(m)),
                 None => lw,
             }
         })
@@ -4324,7 +6677,7 @@ fn scene_journey(tasks: &[JourneyTask]) -> SceneBuild {
     let chart_x = MARGIN + 16;
 
     // Column x positions (per-task widths, declaration order).
-    let widths: Vec<i64> = tasks.iter().map(|t| label_width(&t.label)).collect();
+    let widths: Vec<i64> = tasks.iter().map(|t| line_width(&t.label)).collect();
     let mut xs = Vec::with_capacity(tasks.len());
     let mut x = chart_x;
     for w in &widths {
@@ -4440,8 +6793,7 @@ fn scene_quadrant(
     // Left gutter fits the y-axis end labels (drawn beside the frame).
     let gutter = y_axis
         .map(|(low, high)| {
-            let chars = low.chars().count().max(high.chars().count()) as i64;
-            chars * 7 + 12
+            NOTE_FONT.width(low).max(NOTE_FONT.width(high)) + 12
         })
         .unwrap_or(16);
 
@@ -4557,9 +6909,9 @@ fn scene_kanban(columns: &[KanbanColumn]) -> SceneBuild {
     let widths: Vec<i64> = columns
         .iter()
         .map(|col| {
-            let mut w = label_width(&col.name);
+            let mut w = line_width(&col.name);
             for card in &col.cards {
-                w = w.max(label_width(card) + 16);
+                w = w.max(line_width(card) + 16);
             }
             w.max(140)
         })
@@ -4638,12 +6990,12 @@ fn scene_usecase(actors: &[UcActor], cases: &[UcCase], edges: &[UcEdge]) -> Scen
     }
 
     // Actor column on the left, sized by the widest actor name.
-    let actor_col_w = actors.iter().map(|a| label_width(&a.label)).max().unwrap_or(80);
+    let actor_col_w = actors.iter().map(|a| line_width(&a.label)).max().unwrap_or(80);
     let ax = MARGIN + actor_col_w / 2;
     let actor_top = |k: usize| MARGIN + k as i64 * UC_ACTOR_SLOT;
 
     // System boundary on the right, ellipses stacked inside.
-    let ellipse_rx = |c: &UcCase| label_width(&c.label) / 2 + 8;
+    let ellipse_rx = |c: &UcCase| line_width(&c.label) / 2 + 8;
     let max_rx = cases.iter().map(ellipse_rx).max().unwrap_or(40);
     let boundary_x = if actors.is_empty() { MARGIN } else { MARGIN + actor_col_w + 50 };
     let boundary_w = 2 * max_rx + 48;
@@ -4816,7 +7168,7 @@ fn scene_gitgraph(branches: &[String], commits: &[GitCommit]) -> SceneBuild {
     // Left gutter fits the widest branch name.
     let name_col = branches
         .iter()
-        .map(|b| b.chars().count() as i64 * CHAR_W)
+        .map(|b| SMALL_FONT.bold().width(b))
         .max()
         .unwrap_or(32)
         .max(32);
@@ -4921,9 +7273,9 @@ fn c4_node_height(node: &C4Node) -> i64 {
 
 /// Width of one C4 node box (fits label and tech annotation).
 fn c4_node_width(node: &C4Node) -> i64 {
-    let mut w = label_width(&node.label);
+    let mut w = line_width(&node.label);
     if let Some(tech) = &node.tech {
-        w = w.max(tech.chars().count() as i64 * 7 + 32);
+        w = w.max(TINY_FONT.width(&format!("[{tech}]")) + 24);
     }
     w
 }
@@ -5134,11 +7486,11 @@ fn req_stereotype(node: &ReqNode) -> &'static str {
 
 /// Box width for a requirement node: widest of stereotype / label / text.
 fn req_width(node: &ReqNode) -> i64 {
-    let mut chars = node.label.chars().count().max(13); // «requirement»
+    let mut text_w = LABEL_FONT.bold().width(&node.label).max(TINY_FONT.width(req_stereotype(node)));
     if let Some(text) = &node.text {
-        chars = chars.max(text.chars().count());
+        text_w = text_w.max(NOTE_FONT.width(text));
     }
-    (chars as i64 * CHAR_W + 24).max(140)
+    (text_w + 24).max(140)
 }
 
 /// Box height for a requirement node: title zone + one row of body text.
@@ -5313,7 +7665,7 @@ fn scene_sankey(nodes: &[String], flows: &[SankeyFlow]) -> SceneBuild {
     // Gutters for the outer labels (first column left, last column right).
     let gutter = |col: &[usize]| {
         col.iter()
-            .map(|&i| nodes[i].chars().count() as i64 * 7 + 10)
+            .map(|&i| SMALL_FONT.width(&nodes[i]) + 10)
             .max()
             .unwrap_or(10)
     };
@@ -5366,29 +7718,193 @@ fn scene_sankey(nodes: &[String], flows: &[SankeyFlow]) -> SceneBuild {
                 ],
                 fill: NativeRole::AccentSoft,
                 stroke: NativeRole::AccentSoft,
+                href: None,
+            },
+        );
+        out_off[f] += h;
+        in_off[t] += h;
+    }
+
+    // Node bars + labels: first column labels sit left of the bar, the last
+    // column right of it, and middle columns above it (clear of the bands).
+    for (i, name) in nodes.iter().enumerate() {
+        let r = &bars[i];
+        sc.open_group("surfdoc-diagram-sankey-node");
+        sc.push(
+            Chrome::default(),
+            rect_at(r.x, r.y, r.w, r.h, 0, NativeRole::Accent, NativeRole::Stroke),
+        );
+        let label = if layer[i] == 0 {
+            text_at(r.x - 6, r.cy() + 4, name, NativeRole::TextPrimary, 12, false, NativeAnchor::End)
+        } else if layer[i] == n_cols - 1 {
+            text_at(r.x + r.w + 6, r.cy() + 4, name, NativeRole::TextPrimary, 12, false, NativeAnchor::Start)
+        } else {
+            text_at(r.cx(), r.y - 6, name, NativeRole::TextPrimary, 12, false, NativeAnchor::Middle)
+        };
+        sc.push(Chrome::default(), label);
+        sc.close_group();
+    }
+
+    sc
+}
+
+// ------------------------------------------------------------------
+// Scene assembly — packet
+// ------------------------------------------------------------------
+
+/// Bits per packet row.
+const PK_ROW_BITS: i64 = 32;
+/// Width of one bit.
+const PK_BIT_W: i64 = 20;
+/// Field row height.
+const PK_ROW_H: i64 = 34;
+/// Bit-number band above each row.
+const PK_NUM_H: i64 = 14;
+
+fn scene_packet(fields: &[PacketField]) -> SceneBuild {
+    let total = fields.last().map_or(0, |f| f.start + f.bits);
+    if total == 0 {
+        return SceneBuild::new(2 * MARGIN, 2 * MARGIN);
+    }
+    let rows = (total + PK_ROW_BITS - 1) / PK_ROW_BITS;
+    let pitch = PK_NUM_H + PK_ROW_H;
+    let mut sc = SceneBuild::new(2 * MARGIN + total.min(PK_ROW_BITS) * PK_BIT_W, 2 * MARGIN + rows * pitch);
+
+    for field in fields {
+        sc.open_group("surfdoc-diagram-packet-field");
+        // A field that crosses a row boundary draws one segment per row,
+        // each labelled and numbered on its own.
+        let end = field.start + field.bits;
+        let mut bit = field.start;
+        while bit < end {
+            let row = bit / PK_ROW_BITS;
+            let seg_end = end.min((row + 1) * PK_ROW_BITS);
+            let x = MARGIN + (bit % PK_ROW_BITS) * PK_BIT_W;
+            let y = MARGIN + row * pitch + PK_NUM_H;
+            let w = (seg_end - bit) * PK_BIT_W;
+            sc.push(
+                Chrome::default(),
+                rect_at(x, y, w, PK_ROW_H, 0, NativeRole::Surface, NativeRole::Stroke),
+            );
+            let label = SMALL_FONT.truncate(&field.label, w - 8);
+            sc.push(
+                Chrome::default(),
+                text_at(x + w / 2, y + PK_ROW_H / 2 + 4, &label, NativeRole::TextPrimary, 12, false, NativeAnchor::Middle),
+            );
+            if seg_end - bit == 1 {
+                sc.push(
+                    Chrome::class("surfdoc-diagram-bit"),
+                    text_at(x + w / 2, y - 3, &bit.to_string(), NativeRole::TextSecondary, 10, false, NativeAnchor::Middle),
+                );
+            } else {
+                sc.push(
+                    Chrome::class("surfdoc-diagram-bit"),
+                    text_at(x + 2, y - 3, &bit.to_string(), NativeRole::TextSecondary, 10, false, NativeAnchor::Start),
+                );
+                sc.push(
+                    Chrome::class("surfdoc-diagram-bit"),
+                    text_at(x + w - 2, y - 3, &(seg_end - 1).to_string(), NativeRole::TextSecondary, 10, false, NativeAnchor::End),
+                );
+            }
+            bit = seg_end;
+        }
+        sc.close_group();
+    }
+
+    sc
+}
+
+// ------------------------------------------------------------------
+// Scene assembly — block
+// ------------------------------------------------------------------
+
+/// Block box height (one label line).
+const BLOCK_H: i64 = 40;
+/// Gap between grid columns and rows — room for routed edges.
+const BLOCK_GAP: i64 = 32;
+
+fn scene_block(columns: Option<usize>, cells: &[BlockCell], edges: &[BlockEdge]) -> SceneBuild {
+    if cells.is_empty() {
+        return SceneBuild::new(2 * MARGIN, 2 * MARGIN);
+    }
+    // Place cells row by row; a cell that overflows its row wraps.
+    let cols = columns.unwrap_or_else(|| cells.iter().map(|c| c.span).sum());
+    let mut slots: Vec<(i64, i64, i64)> = Vec::with_capacity(cells.len());
+    let (mut row, mut col) = (0usize, 0usize);
+    for cell in cells {
+        let span = cell.span.min(cols);
+        if col + span > cols {
+            row += 1;
+            col = 0;
+        }
+        slots.push((row as i64, col as i64, span as i64));
+        col += span;
+    }
+
+    // One column width and one row height for the whole grid: the widest
+    // label (shared across a spanning block's columns) and the tallest.
+    let blocks: Vec<(&BlockCell, &(i64, i64, i64))> = cells.iter().zip(&slots).filter(|(c, _)| c.id.is_some()).collect();
+    let col_w = blocks
+        .iter()
+        .map(|&(c, &(_, _, span))| (label_width(&c.label) - (span - 1) * BLOCK_GAP + span - 1) / span)
+        .max()
+        .unwrap_or(0)
+        .max(80);
+    let row_h = blocks.iter().map(|(c, _)| label_height(&c.label, BLOCK_H)).max().unwrap_or(BLOCK_H);
+    let rects: Vec<Rect> = blocks
+        .iter()
+        .map(|&(_, &(row, col, span))| Rect {
+            x: MARGIN + col * (col_w + BLOCK_GAP),
+            y: MARGIN + row * (row_h + BLOCK_GAP),
+            w: span * col_w + (span - 1) * BLOCK_GAP,
+            h: row_h,
+        })
+        .collect();
+    let grid_cols = slots.iter().map(|&(_, col, span)| col + span).max().unwrap_or(1);
+    let grid_rows = row as i64 + 1;
+    let mut sc = SceneBuild::new(
+        2 * MARGIN + grid_cols * col_w + (grid_cols - 1) * BLOCK_GAP,
+        2 * MARGIN + grid_rows * row_h + (grid_rows - 1) * BLOCK_GAP,
+    );
+    sc.items.push(SvgItem::ArrowDefs);
+
+    let index = |id: &str| blocks.iter().position(|(c, _)| c.id.as_deref() == Some(id));
+    let (routed, reqs): (Vec<&BlockEdge>, Vec<RouteReq>) = edges
+        .iter()
+        .filter_map(|edge| {
+            let f = index(&edge.from)?;
+            let t = index(&edge.to)?;
+            let (exit, entry) = vert_sides(&rects[f], &rects[t]);
+            Some((edge, RouteReq { from: f, to: t, exit, entry }))
+        })
+        .unzip();
+    let routes = route_edges(&rects, &[], &reqs);
+    let mut labels_taken: Vec<Rect> = Vec::new();
+    for (k, (edge, route)) in routed.iter().zip(&routes).enumerate() {
+        sc.push(
+            Chrome {
+                class: Some("surfdoc-diagram-edge"),
+                fill_none: true,
+                dash: None,
             },
+            route_line(route, NativeMarker::None),
         );
-        out_off[f] += h;
-        in_off[t] += h;
+        if let Some(label) = &edge.label {
+            let (lx, ly, anchor) = place_edge_label(&routes, k, label, &rects, (sc.w, sc.h), &mut labels_taken);
+            sc.push(
+                Chrome::class("surfdoc-diagram-edge-label"),
+                text_at(lx, ly, label, NativeRole::TextSecondary, 11, false, anchor),
+            );
+        }
     }
 
-    // Node bars + labels: first column labels sit left of the bar, the last
-    // column right of it, and middle columns above it (clear of the bands).
-    for (i, name) in nodes.iter().enumerate() {
-        let r = &bars[i];
-        sc.open_group("surfdoc-diagram-sankey-node");
+    for ((cell, _), r) in blocks.iter().zip(&rects) {
+        sc.open_group("surfdoc-diagram-block");
         sc.push(
             Chrome::default(),
-            rect_at(r.x, r.y, r.w, r.h, 0, NativeRole::Accent, NativeRole::Stroke),
+            rect_at(r.x, r.y, r.w, r.h, 4, NativeRole::Surface, NativeRole::Stroke),
         );
-        let label = if layer[i] == 0 {
-            text_at(r.x - 6, r.cy() + 4, name, NativeRole::TextPrimary, 12, false, NativeAnchor::End)
-        } else if layer[i] == n_cols - 1 {
-            text_at(r.x + r.w + 6, r.cy() + 4, name, NativeRole::TextPrimary, 12, false, NativeAnchor::Start)
-        } else {
-            text_at(r.cx(), r.y - 6, name, NativeRole::TextPrimary, 12, false, NativeAnchor::Middle)
-        };
-        sc.push(Chrome::default(), label);
+        push_label(&mut sc, r.cx(), r.cy(), &cell.label);
         sc.close_group();
     }
 
@@ -5415,7 +7931,7 @@ mod tests {
 
     #[test]
     fn arch_nodes_and_edges() {
-        let DiagramModel::Architecture { nodes, edges } = arch(
+        let DiagramModel::Architecture { nodes, edges, .. } = arch(
             "web: Web Frontend\napi: API Server\n\nweb -> api: HTTPS",
         ) else {
             panic!("expected Architecture model");
@@ -5432,7 +7948,7 @@ mod tests {
 
     #[test]
     fn arch_auto_declares_edge_ids() {
-        let DiagramModel::Architecture { nodes, edges } = arch("a -> b\nb -> c") else {
+        let DiagramModel::Architecture { nodes, edges, .. } = arch("a -> b\nb -> c") else {
             panic!("expected Architecture model");
         };
         // a, b, c all auto-declared with label = id, in first-reference order.
@@ -5454,13 +7970,93 @@ mod tests {
     #[test]
     fn arch_node_label_may_contain_arrow_text() {
         // `:` immediately after the id wins the classification — free label text.
-        let DiagramModel::Architecture { nodes, edges } = arch("a: Sends -> downstream") else {
+        let DiagramModel::Architecture { nodes, edges, .. } = arch("a: Sends -> downstream") else {
             panic!("expected Architecture model");
         };
         assert_eq!(nodes[0].label, "Sends -> downstream");
         assert!(edges.is_empty());
     }
 
+    #[test]
+    fn arch_groups_collect_members() {
+        let DiagramModel::Architecture { nodes, groups, .. } = arch(
+            "web: Web\ngroup vpc: Private VPC {\napi: API\ndb\n}\ngroup ops {\nweb\n}\nweb -> api\napi -> db\napi -> mail",
+        ) else {
+            panic!("expected Architecture model");
+        };
+        assert_eq!(groups.len(), 2);
+        assert_eq!((groups[0].id.as_str(), groups[0].label.as_str()), ("vpc", "Private VPC"));
+        // Label defaults to the id.
+        assert_eq!(groups[1].label, "ops");
+        let membership: Vec<(&str, Option<usize>)> = nodes.iter().map(|n| (n.id.as_str(), n.group)).collect();
+        // `db` is auto-declared by its bare member line, `web` joins later,
+        // `mail` is auto-declared by an edge outside any group.
+        assert_eq!(
+            membership,
+            vec![("web", Some(1)), ("api", Some(0)), ("db", Some(0)), ("mail", None)]
+        );
+    }
+
+    #[test]
+    fn group_keyword_stays_a_valid_node_id() {
+        let DiagramModel::Architecture { nodes, groups, edges } = arch("group: Gateway\ngroup -> b") else {
+            panic!("expected Architecture model");
+        };
+        assert!(groups.is_empty());
+        assert_eq!(nodes[0].label, "Gateway");
+        assert_eq!(edges.len(), 1);
+    }
+
+    #[test]
+    fn group_errors_report_line_numbers() {
+        for (body, line) in [
+            ("group a {\ngroup b {\n}\n}", 2),
+            ("a: A\n}", 2),
+            ("group a {\n}\ngroup a {\n}", 3),
+            ("group a: Missing brace", 1),
+            ("group a junk {", 1),
+            ("group a {\nx\n}\ngroup b {\nx\n}", 5),
+        ] {
+            let e = parse_diagram_source("architecture", body).expect_err(body);
+            assert_eq!(e.line, line, "{body}");
+            let e = parse_diagram_source("flowchart", body).expect_err(body);
+            assert_eq!(e.line, line, "{body}");
+        }
+    }
+
+    #[test]
+    fn arch_group_clusters_never_overlap_other_nodes() {
+        let model = arch(
+            "group edge: Edge {\nlb: Load Balancer\n}\ngroup core: Core {\napi: API\nworker: Worker\n}\ndb: Postgres\nlb -> api\nlb -> worker\napi -> db\nworker -> db",
+        );
+        let scene = build_scene(&model, None);
+        let mut clusters = Vec::new();
+        let mut nodes = Vec::new();
+        for item in &scene.items {
+            if let SvgItem::Shape { shape: NativeShape::Rect { x, y, w, h, fill, .. }, .. } = item {
+                let r = (*x as i64, *y as i64, (*x + *w) as i64, (*y + *h) as i64);
+                if *fill == NativeRole::Cluster { clusters.push(r) } else { nodes.push(r) }
+            }
+        }
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(nodes.len(), 4);
+        let inside = |c: &(i64, i64, i64, i64), n: &(i64, i64, i64, i64)| {
+            n.0 >= c.0 && n.1 >= c.1 && n.2 <= c.2 && n.3 <= c.3
+        };
+        let overlaps = |c: &(i64, i64, i64, i64), n: &(i64, i64, i64, i64)| {
+            n.0 < c.2 && c.0 < n.2 && n.1 < c.3 && c.1 < n.3
+        };
+        // lb in `edge`; api + worker in `core`; db in no cluster.
+        assert!(inside(&clusters[0], &nodes[0]));
+        assert!(inside(&clusters[1], &nodes[1]) && inside(&clusters[1], &nodes[2]));
+        assert!(!overlaps(&clusters[0], &nodes[1]) && !overlaps(&clusters[0], &nodes[2]));
+        assert!(clusters.iter().all(|c| !overlaps(c, &nodes[3])));
+        let svg = render_svg(&model, None, None);
+        assert!(svg.contains("surfdoc-diagram-cluster"));
+        assert!(svg.contains(">Core</text>"));
+        assert_eq!(svg, render_svg(&model, None, None));
+    }
+
     #[test]
     fn arch_malformed_line_reports_line_number() {
         let e = parse_diagram_source("architecture", "a: Fine\n\nnot a statement at all")
@@ -5563,7 +8159,7 @@ mod tests {
     #[test]
     fn arch_svg_structure() {
         let model = arch("web: Web\napi: API\nweb -> api: HTTPS\napi <-> web");
-        let svg = render_svg(&model, Some("System"));
+        let svg = render_svg(&model, Some("System"), None);
         assert!(svg.starts_with("<svg class=\"surfdoc-diagram-svg\""));
         assert!(svg.contains("viewBox=\"0 0 "));
         assert!(svg.contains("role=\"img\""));
@@ -5580,21 +8176,21 @@ mod tests {
 
     #[test]
     fn arch_svg_no_title_omits_title_element() {
-        let svg = render_svg(&arch("a -> b"), None);
+        let svg = render_svg(&arch("a -> b"), None, None);
         assert!(!svg.contains("<title>"));
     }
 
     #[test]
     fn arch_svg_cycle_never_loops() {
         // a -> b -> c -> a closes a cycle; layering must terminate.
-        let svg = render_svg(&arch("a -> b\nb -> c\nc -> a"), None);
+        let svg = render_svg(&arch("a -> b\nb -> c\nc -> a"), None, None);
         assert!(svg.contains("surfdoc-diagram-node"));
     }
 
     #[test]
     fn erd_svg_structure() {
         let model = erd("users: id pk, email unique\norders: id pk, user_id fk\nusers 1--* orders: places");
-        let svg = render_svg(&model, Some("Data Model"));
+        let svg = render_svg(&model, Some("Data Model"), None);
         assert!(svg.starts_with("<svg class=\"surfdoc-diagram-svg\""));
         assert!(svg.contains("<title>Data Model</title>"));
         assert!(svg.contains("surfdoc-diagram-entity"));
@@ -5612,16 +8208,16 @@ mod tests {
     #[test]
     fn svg_is_deterministic() {
         let arch_model = arch("a: Alpha\nb: Beta\nc: Gamma\na -> b\nb -> c: link\na <-> c");
-        assert_eq!(render_svg(&arch_model, Some("t")), render_svg(&arch_model, Some("t")));
+        assert_eq!(render_svg(&arch_model, Some("t"), None), render_svg(&arch_model, Some("t"), None));
 
         let erd_model = erd("u: id pk\no: id pk, u_id fk\nu 1--* o");
-        assert_eq!(render_svg(&erd_model, None), render_svg(&erd_model, None));
+        assert_eq!(render_svg(&erd_model, None, None), render_svg(&erd_model, None, None));
     }
 
     #[test]
     fn svg_escapes_user_labels() {
         let model = arch("a: <script>alert(1)</script>\na -> b: <img onerror=x>");
-        let svg = render_svg(&model, Some("<script>t</script>"));
+        let svg = render_svg(&model, Some("<script>t</script>"), None);
         assert!(!svg.contains("<script>"));
         assert!(svg.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
         assert!(svg.contains("&lt;img onerror=x&gt;"));
@@ -5637,7 +8233,7 @@ mod tests {
         };
         entities[0].name = "<b>users</b>".to_string();
         entities[0].fields[0].name = "<i>id</i>".to_string();
-        let svg = render_svg(&DiagramModel::Erd { entities, relations }, None);
+        let svg = render_svg(&DiagramModel::Erd { entities, relations }, None, None);
         assert!(!svg.contains("<b>"));
         assert!(svg.contains("&lt;b&gt;users&lt;/b&gt;"));
         assert!(svg.contains("&lt;i&gt;id&lt;/i&gt;"));
@@ -5645,12 +8241,12 @@ mod tests {
 
     #[test]
     fn empty_body_parses_to_empty_model() {
-        let DiagramModel::Architecture { nodes, edges } = arch("") else {
+        let DiagramModel::Architecture { nodes, groups, edges } = arch("") else {
             panic!("expected Architecture model");
         };
         assert!(nodes.is_empty() && edges.is_empty());
         // And still renders a (trivial) svg without panicking.
-        let svg = render_svg(&DiagramModel::Architecture { nodes, edges }, None);
+        let svg = render_svg(&DiagramModel::Architecture { nodes, groups, edges }, None, None);
         assert!(svg.starts_with("<svg"));
     }
 
@@ -5662,7 +8258,7 @@ mod tests {
 
     #[test]
     fn flowchart_parses_shapes_and_edges() {
-        let DiagramModel::Flowchart { nodes, edges } = flow(
+        let DiagramModel::Flowchart { nodes, edges, .. } = flow(
             "start [terminator]: Start\ncheck [diamond]: Valid?\nsave: Persist\nstart -> check\ncheck -> save: yes\ncheck -> start: no",
         ) else {
             panic!("expected Flowchart");
@@ -5688,7 +8284,7 @@ mod tests {
     #[test]
     fn flowchart_svg_structure_and_determinism() {
         let model = flow("start [rounded]: Start\nd [diamond]: OK?\nend: Done\nstart -> d\nd -> end: yes");
-        let svg = render_svg(&model, Some("Flow"));
+        let svg = render_svg(&model, Some("Flow"), None);
         assert!(svg.starts_with("<svg class=\"surfdoc-diagram-svg\""));
         assert!(svg.contains("<title>Flow</title>"));
         assert!(svg.contains("surfdoc-diagram-node"));
@@ -5696,7 +8292,191 @@ mod tests {
         assert!(svg.contains("marker-end=\"url(#surfdoc-arrow)\""));
         assert!(svg.contains(">yes</text>"));
         assert!(svg.ends_with("</svg>"));
-        assert_eq!(render_svg(&model, Some("Flow")), render_svg(&model, Some("Flow")));
+        assert_eq!(render_svg(&model, Some("Flow"), None), render_svg(&model, Some("Flow"), None));
+    }
+
+    #[test]
+    fn layered_layout_keeps_group_members_contiguous() {
+        // Three nodes in one row; the middle one is ungrouped.
+        let sizes = [(80, 40); 3];
+        let placed = layered_layout(&sizes, 50, 36, &[], &[Some(0), None, Some(0)], DiagramDirection::TopBottom);
+        let xs: Vec<i64> = placed.rects.iter().map(|r| r.x).collect();
+        // Group 0 is placed first (its band starts at node 0), the ungrouped
+        // node after it — never between the two members.
+        assert!(xs[0] < xs[2] && xs[2] < xs[1], "{xs:?}");
+        assert_eq!(placed.clusters.len(), 1);
+        let (g, c) = placed.clusters[0];
+        assert_eq!(g, 0);
+        assert!(c.x <= xs[0] && xs[2] + 80 <= c.x + c.w);
+        assert!(c.x + c.w < xs[1]);
+        // Without groups the layout is the plain centered rows.
+        let plain = layered_layout(&sizes, 50, 36, &[], &[], DiagramDirection::TopBottom);
+        assert!(plain.clusters.is_empty());
+        assert_eq!(plain.rects[0].y, MARGIN);
+    }
+
+    #[test]
+    fn layered_layout_orders_layers_to_avoid_crossings() {
+        // Declaration order would cross a -> d and b -> c; the sweep swaps
+        // c and d so each child sits under its parent.
+        let sizes = [(80, 40); 4];
+        let placed = layered_layout(&sizes, 50, 36, &[(0, 3), (1, 2)], &[], DiagramDirection::TopBottom);
+        let r = &placed.rects;
+        assert_eq!((r[3].x, r[2].x), (r[0].x, r[1].x), "{r:?}");
+    }
+
+    #[test]
+    fn layered_layout_centers_parents_over_children() {
+        let sizes = [(80, 40); 4];
+        let placed = layered_layout(&sizes, 50, 36, &[(0, 1), (0, 2), (0, 3)], &[], DiagramDirection::TopBottom);
+        let r = &placed.rects;
+        assert_eq!(r[0].cx(), r[2].cx());
+        assert_eq!(r[0].cx() * 2, r[1].cx() + r[3].cx());
+    }
+
+    #[test]
+    fn layered_layout_reserves_a_slot_for_long_edges() {
+        // a -> c skips b's layer: a dummy claims a slot beside b, so c does
+        // not sit straight under b and the edge keeps a clear channel.
+        let sizes = [(80, 40); 3];
+        let placed = layered_layout(&sizes, 50, 36, &[(0, 1), (1, 2), (0, 2)], &[], DiagramDirection::TopBottom);
+        assert!(placed.w > 2 * MARGIN + 80, "{}", placed.w);
+        assert_ne!(placed.rects[2].x, placed.rects[1].x);
+    }
+
+    #[test]
+    fn left_right_direction_runs_layers_across() {
+        let sizes = [(80, 40), (120, 40), (80, 40)];
+        let placed = layered_layout(&sizes, 50, 36, &[(0, 1), (1, 2)], &[], DiagramDirection::LeftRight);
+        let r = &placed.rects;
+        assert!(r[0].x + r[0].w < r[1].x && r[1].x + r[1].w < r[2].x, "{r:?}");
+        assert_eq!((r[0].cy(), r[1].cy()), (r[2].cy(), r[2].cy()));
+        assert!(placed.w > placed.h);
+        // The same chart, top to bottom, is taller than wide.
+        let model = flow("a: A\nb: B\nc: C\na -> b\nb -> c");
+        let tall = build_scene(&model, None);
+        let wide = build_scene(&model, Some(DiagramDirection::LeftRight));
+        assert!(tall.h > tall.w && wide.w > wide.h);
+    }
+
+    #[test]
+    fn flowchart_group_svg_and_scene() {
+        let model = flow(
+            "start [rounded]: Start\ngroup review: Review {\ncheck [diamond]: OK?\nfix: Fix\n}\nstart -> check\ncheck -> fix: no\nfix -> check",
+        );
+        let DiagramModel::Flowchart { nodes, groups, .. } = &model else {
+            panic!("expected Flowchart");
+        };
+        assert_eq!(groups[0].label, "Review");
+        assert_eq!(nodes[1].group, Some(0));
+        let svg = render_svg(&model, None, None);
+        assert!(svg.contains("class=\"surfdoc-diagram-cluster\""));
+        assert!(svg.contains("fill=\"#f1f5f9\""));
+        assert!(svg.contains(">Review</text>"));
+        assert_eq!(svg, render_svg(&model, None, None));
+        // The cluster is the first shape of the scene, under everything else.
+        let scene = build_scene(&model, None);
+        let first_shape = scene.items.iter().find_map(|item| match item {
+            SvgItem::Shape { shape, .. } => Some(shape),
+            _ => None,
+        });
+        assert!(matches!(first_shape, Some(NativeShape::Rect { fill: NativeRole::Cluster, .. })));
+    }
+
+    #[test]
+    fn flowchart_style_sets_node_and_edge_emphasis() {
+        let DiagramModel::Flowchart { nodes, edges, .. } =
+            flow("style a, c: danger\na: A\nstyle: Style node\na -> b\nb -> c\nb -> c: again\nstyle b -> c: success")
+        else {
+            panic!("expected Flowchart");
+        };
+        let emphasis: Vec<(&str, Option<Emphasis>)> = nodes.iter().map(|n| (n.id.as_str(), n.emphasis)).collect();
+        assert_eq!(
+            emphasis,
+            vec![("a", Some(Emphasis::Danger)), ("style", None), ("b", None), ("c", Some(Emphasis::Danger))]
+        );
+        // An edge reference styles every parallel edge.
+        assert_eq!(edges[0].emphasis, None);
+        assert_eq!(edges[1].emphasis, Some(Emphasis::Success));
+        assert_eq!(edges[2].emphasis, Some(Emphasis::Success));
+    }
+
+    #[test]
+    fn style_rejects_unknown_targets_and_roles() {
+        let e = parse_diagram_source("flowchart", "a -> b\nstyle x: danger").expect_err("unknown node");
+        assert_eq!(e.line, 2);
+        let e = parse_diagram_source("flowchart", "a -> b\nstyle b -> a: muted").expect_err("unknown edge");
+        assert!(e.message.contains("b -> a"), "{}", e.message);
+        let e = parse_diagram_source("state", "a -> b\nstyle a: red").expect_err("unknown emphasis");
+        assert!(e.message.contains("highlight, muted, danger or success"), "{}", e.message);
+    }
+
+    #[test]
+    fn emphasis_paints_through_semantic_roles() {
+        let model = flow("a: A\nb [diamond]: B\nc: C\na -> b\nb -> c\nstyle a: highlight\nstyle b: muted\nstyle a -> b: danger");
+        let scene = build_scene(&model, None);
+        let shapes: Vec<&NativeShape> = scene
+            .items
+            .iter()
+            .filter_map(|i| match i {
+                SvgItem::Shape { shape, .. } => Some(shape),
+                _ => None,
+            })
+            .collect();
+        // Edges paint first: a -> b is danger at width 2, b -> c plain.
+        assert!(matches!(shapes[0], NativeShape::Line { stroke: NativeRole::Danger, stroke_width, .. } if *stroke_width == 2.0));
+        assert!(matches!(shapes[1], NativeShape::Line { stroke: NativeRole::Stroke, .. }));
+        assert!(shapes.iter().any(|s| matches!(s, NativeShape::Rect { stroke: NativeRole::Highlight, stroke_width, .. } if *stroke_width == 2.0)));
+        // Muted keeps the plain width and dims its label.
+        assert!(shapes.iter().any(|s| matches!(s, NativeShape::Polygon { stroke: NativeRole::Muted, .. })));
+        assert!(shapes.iter().any(|s| matches!(s, NativeShape::Label { text, role: NativeRole::TextSecondary, .. } if text == "B")));
+        let svg = render_svg(&model, None, None);
+        assert!(svg.contains("stroke=\"#d97706\" stroke-width=\"2\""));
+        assert!(svg.contains("stroke=\"#dc2626\" stroke-width=\"2\""));
+    }
+
+    #[test]
+    fn flowchart_link_wraps_node_in_anchor() {
+        let model = flow("a: Docs\nb: Plain\na -> b\nlink a: https://example.com/?q=1&x=\"y\" \"Open <docs>\"\nlink: Node named link");
+        let DiagramModel::Flowchart { nodes, .. } = &model else {
+            panic!("expected Flowchart");
+        };
+        let link = nodes[0].link.as_ref().expect("linked");
+        assert_eq!(link.url, "https://example.com/?q=1&x=\"y\"");
+        assert_eq!(link.tooltip.as_deref(), Some("Open <docs>"));
+        assert!(nodes.iter().any(|n| n.id == "link"), "`link: Label` stays a node line");
+        let svg = render_svg(&model, None, None);
+        assert!(svg.contains(
+            "<a href=\"https://example.com/?q=1&amp;x=&quot;y&quot;\" class=\"surfdoc-diagram-link\"><title>Open &lt;docs&gt;</title><g class=\"surfdoc-diagram-node\">"
+        ));
+        assert_eq!(svg.matches("<a ").count(), 1);
+        // Native clients get the url on the node outline only.
+        let scene = build_scene(&model, None);
+        let hrefs: Vec<Option<&str>> = scene
+            .items
+            .iter()
+            .filter_map(|i| match i {
+                SvgItem::Shape { shape: NativeShape::Rect { href, .. }, .. } => Some(href.as_deref()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(hrefs.iter().filter(|h| h.is_some()).count(), 1);
+    }
+
+    #[test]
+    fn link_rejects_unsafe_urls_and_unknown_nodes() {
+        for url in ["javascript:alert(1)", "JavaScript:alert(1)", "data:text/html,x", "vbscript:x"] {
+            let e = parse_diagram_source("architecture", &format!("a: A\nlink a: {url}")).expect_err(url);
+            assert_eq!(e.line, 2);
+            assert!(e.message.contains("http, https, mailto or a relative path"), "{}", e.message);
+        }
+        for url in ["/docs/auth", "#setup", "mailto:ops@example.com", "HTTPS://example.com"] {
+            assert!(parse_diagram_source("architecture", &format!("a: A\nlink a: {url}")).is_ok(), "{url}");
+        }
+        let e = parse_diagram_source("state", "a -> b\nlink c: /c").expect_err("unknown node");
+        assert!(e.message.contains("unknown node \"c\""), "{}", e.message);
+        let e = parse_diagram_source("flowchart", "a -> b\nlink a: /a trailing").expect_err("unquoted tooltip");
+        assert!(e.message.contains("quoted tooltip"), "{}", e.message);
     }
 
     // ── sequence ────────────────────────────────────────────────────
@@ -5733,7 +8513,7 @@ mod tests {
     #[test]
     fn sequence_svg_structure_and_determinism() {
         let model = seq("actor u: User\nactor s: Server\nu -> s: req\nactivate s\ns --> u: resp\ndeactivate s");
-        let svg = render_svg(&model, Some("Seq"));
+        let svg = render_svg(&model, Some("Seq"), None);
         assert!(svg.starts_with("<svg class=\"surfdoc-diagram-svg\""));
         assert!(svg.contains("<title>Seq</title>"));
         assert!(svg.contains("surfdoc-diagram-lifeline"));
@@ -5742,7 +8522,105 @@ mod tests {
         assert!(svg.contains("stroke-dasharray=\"6 4\"")); // dashed return message
         assert!(svg.contains(">req</text>"));
         assert!(svg.ends_with("</svg>"));
-        assert_eq!(render_svg(&model, None), render_svg(&model, None));
+        assert_eq!(render_svg(&model, None, None), render_svg(&model, None, None));
+    }
+
+    #[test]
+    fn sequence_parses_nested_fragments_and_notes() {
+        let DiagramModel::Sequence { actors, events } = seq(
+            "user -> api: login\nalt valid {\n  loop {\n    api -> db: read\n  }\n  api --> user: 200\nelse\n  note right of user: retry\n}\nnote over api, db: done",
+        ) else {
+            panic!("expected Sequence");
+        };
+        // `db` is declared from inside the nested loop, in reference order.
+        let ids: Vec<&str> = actors.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, vec!["user", "api", "db"]);
+        assert_eq!(events.len(), 3);
+        let SeqEvent::Fragment { kind, sections } = &events[1] else {
+            panic!("expected a fragment");
+        };
+        assert_eq!(*kind, FragmentKind::Alt);
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].guard.as_deref(), Some("valid"));
+        assert_eq!(sections[1].guard, None);
+        assert!(matches!(&sections[0].events[0], SeqEvent::Fragment { kind: FragmentKind::Loop, .. }));
+        assert!(matches!(
+            &sections[1].events[0],
+            SeqEvent::Note { placement: NotePlacement::RightOf, actors, .. } if actors == &["user"]
+        ));
+        assert!(matches!(&events[2], SeqEvent::Note { placement: NotePlacement::Over, actors, .. } if actors.len() == 2));
+    }
+
+    #[test]
+    fn sequence_frame_keywords_stay_valid_actor_ids() {
+        let DiagramModel::Sequence { events, .. } = seq("loop -> note: x\nelse --> and") else {
+            panic!("expected Sequence");
+        };
+        assert!(events.iter().all(|e| matches!(e, SeqEvent::Message { .. })));
+    }
+
+    #[test]
+    fn sequence_fragment_errors_report_line_numbers() {
+        for (body, line) in [
+            ("a -> b\nalt x {\na -> b", 2),
+            ("}", 1),
+            ("else", 1),
+            ("loop {\nelse\n}", 2),
+            ("par {\nand\noption\n}", 3),
+            ("note left of a, b: two", 1),
+            ("note beside a: x", 1),
+            ("note over a", 1),
+        ] {
+            let e = parse_diagram_source("sequence", body).expect_err(body);
+            assert_eq!(e.line, line, "{body}");
+        }
+    }
+
+    #[test]
+    fn sequence_fragments_draw_frames_and_notes() {
+        let model = seq(
+            "actor u: User\nactor s: Server\nnote left of u: start\nalt ok {\nu -> s: req\nelse failed\ns --> u: err\n}\nrect {\ns -> s: tick\n}",
+        );
+        let svg = render_svg(&model, None, None);
+        assert!(svg.contains("surfdoc-diagram-frame"));
+        assert!(svg.contains(">alt</text>"));
+        assert!(svg.contains(">[ok]</text>"));
+        assert!(svg.contains(">[failed]</text>"));
+        assert!(svg.contains("surfdoc-diagram-frame-divider"));
+        assert!(svg.contains("surfdoc-diagram-highlight"));
+        assert!(svg.contains("surfdoc-diagram-note"));
+        assert_eq!(svg, render_svg(&model, None, None));
+
+        // The left note shifts the diagram so it stays on the canvas.
+        let scene = build_scene(&model, None);
+        for item in &scene.items {
+            if let SvgItem::Shape { shape: NativeShape::Rect { x, .. }, .. } = item {
+                assert!(*x >= MARGIN as f64, "shape at x={x} leaves the margin");
+            }
+        }
+        // The alt frame spans both lifelines; its divider sits between the
+        // two messages.
+        let frame = scene
+            .items
+            .iter()
+            .find_map(|item| match item {
+                SvgItem::Shape { shape: NativeShape::Line { points, .. }, chrome } if chrome.class == Some("surfdoc-diagram-frame") => {
+                    Some(points.clone())
+                }
+                _ => None,
+            })
+            .expect("frame outline");
+        let messages: Vec<f64> = scene
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                SvgItem::Shape { shape: NativeShape::Line { points, .. }, chrome } if chrome.class == Some("surfdoc-diagram-msg") => {
+                    Some(points[0].y)
+                }
+                _ => None,
+            })
+            .collect();
+        assert!(frame[0].y < messages[0] && messages[1] < frame[2].y);
     }
 
     // ── gantt ───────────────────────────────────────────────────────
@@ -5753,7 +8631,7 @@ mod tests {
 
     #[test]
     fn gantt_parses_numeric_and_sections() {
-        let DiagramModel::Gantt { tasks, dated } = gantt(
+        let DiagramModel::Gantt { tasks, dated, .. } = gantt(
             "section Plan\nResearch: 0, 3\nDesign: 3, 2\nsection Build\nImpl: 5, 4",
         ) else {
             panic!("expected Gantt");
@@ -5768,7 +8646,7 @@ mod tests {
 
     #[test]
     fn gantt_parses_dates() {
-        let DiagramModel::Gantt { tasks, dated } = gantt("A: 2026-01-01, 5\nB: 2026-01-06, 3") else {
+        let DiagramModel::Gantt { tasks, dated, .. } = gantt("A: 2026-01-01, 5\nB: 2026-01-06, 3") else {
             panic!("expected Gantt");
         };
         assert!(dated);
@@ -5784,7 +8662,7 @@ mod tests {
     #[test]
     fn gantt_svg_structure_and_determinism() {
         let model = gantt("section Plan\nResearch: 0, 3\nDesign: 3, 2");
-        let svg = render_svg(&model, Some("Plan"));
+        let svg = render_svg(&model, Some("Plan"), None);
         assert!(svg.starts_with("<svg class=\"surfdoc-diagram-svg\""));
         assert!(svg.contains("<title>Plan</title>"));
         assert!(svg.contains("surfdoc-diagram-bar"));
@@ -5792,7 +8670,7 @@ mod tests {
         assert!(svg.contains("surfdoc-diagram-section"));
         assert!(svg.contains(">Research</text>"));
         assert!(svg.ends_with("</svg>"));
-        assert_eq!(render_svg(&model, Some("Plan")), render_svg(&model, Some("Plan")));
+        assert_eq!(render_svg(&model, Some("Plan"), None), render_svg(&model, Some("Plan"), None));
     }
 
     #[test]
@@ -5812,10 +8690,10 @@ mod tests {
         }
         // Values at the bound still parse and render.
         let model = gantt("A: 1000000000, 1000000000\nB: -1000000000, 1");
-        let svg = render_svg(&model, None);
+        let svg = render_svg(&model, None, None);
         assert!(svg.starts_with("<svg"));
         let model = gantt("A: 9999-12-31, 5");
-        assert!(render_svg(&model, None).starts_with("<svg"));
+        assert!(render_svg(&model, None, None).starts_with("<svg"));
     }
 
     #[test]
@@ -5826,6 +8704,103 @@ mod tests {
         assert_eq!(days_from_civil(1970, 1, 1), 0);
     }
 
+    #[test]
+    fn gantt_parses_ids_tags_and_dependencies() {
+        let DiagramModel::Gantt { tasks, today, .. } = gantt(
+            "Build: crit, done, build, 2026-01-01, 4\n\
+             Test: active, test, after build, 3\n\
+             Docs: docs, 2026-01-02, 2\n\
+             Ship: milestone, after test docs, 5\n\
+             today 2026-01-03",
+        ) else {
+            panic!("expected Gantt");
+        };
+        assert_eq!(tasks[0].id.as_deref(), Some("build"));
+        assert_eq!(tasks[0].status, Some(GanttStatus::Crit), "crit outranks done");
+        assert_eq!(tasks[1].after, vec!["build"]);
+        assert_eq!(tasks[1].status, Some(GanttStatus::Active));
+        // `after` resolves to the latest end of the dependencies.
+        assert_eq!(tasks[1].start, tasks[0].start + 4);
+        assert_eq!(tasks[3].start, tasks[1].start + 3);
+        assert!(tasks[3].milestone);
+        assert_eq!(tasks[3].duration, 0, "milestones are zero-duration");
+        assert_eq!(today, Some(tasks[0].start + 2));
+        // Dependencies may point forward; plain forms are unchanged.
+        let DiagramModel::Gantt { tasks, .. } = gantt("B: after a, 2\nA: a, 5, 3") else {
+            panic!("expected Gantt");
+        };
+        assert_eq!((tasks[0].start, tasks[1].start), (8, 5));
+    }
+
+    #[test]
+    fn gantt_dependency_errors() {
+        let e = parse_diagram_source("gantt", "A: a, 0, 2\nB: b, after zzz, 1").unwrap_err();
+        assert_eq!(e.line, 2);
+        assert!(e.message.contains("unknown task id \"zzz\""), "{}", e.message);
+        let e = parse_diagram_source("gantt", "R: 0, 1\nA: a, after b, 2\nB: b, after a, 1").unwrap_err();
+        assert_eq!(e.line, 2);
+        assert_eq!(e.message, "dependency cycle: a after b after a");
+        let e = parse_diagram_source("gantt", "A: a, after a, 1").unwrap_err();
+        assert!(e.message.starts_with("dependency cycle"), "{}", e.message);
+        for body in ["A: a, 0, 1\nB: a, 1, 1", "A: after, 1", "A: 0, 1\ntoday 2026-01-01", "today 1\ntoday 2"] {
+            assert!(parse_diagram_source("gantt", body).is_err(), "{body:?} must be rejected");
+        }
+        // A label starting with "today" is still a task.
+        assert!(parse_diagram_source("gantt", "today standup: 0, 1").is_ok());
+    }
+
+    #[test]
+    fn gantt_scene_draws_dependencies_milestones_and_today() {
+        let model = gantt("A: done, a, 0, 4\nB: crit, b, after a, 3\nM: milestone, after b, 0\ntoday 2");
+        let scene = build_scene(&model, None);
+        let shapes: Vec<&NativeShape> = scene
+            .items
+            .iter()
+            .filter_map(|i| match i {
+                SvgItem::Shape { shape, .. } => Some(shape),
+                _ => None,
+            })
+            .collect();
+        assert!(shapes.iter().any(|s| matches!(s, NativeShape::Rect { fill: NativeRole::Success, .. })));
+        assert!(shapes.iter().any(|s| matches!(s, NativeShape::Rect { fill: NativeRole::Danger, .. })));
+        assert_eq!(shapes.iter().filter(|s| matches!(s, NativeShape::Polygon { .. })).count(), 1);
+        let arrows = shapes
+            .iter()
+            .filter(|s| matches!(s, NativeShape::Line { marker_end: NativeMarker::Arrow, .. }))
+            .count();
+        assert_eq!(arrows, 2);
+        assert!(shapes.iter().any(|s| matches!(s, NativeShape::Line { stroke: NativeRole::Danger, dashed: true, .. })));
+
+        let svg = render_svg(&model, None, None);
+        assert!(svg.contains("surfdoc-diagram-milestone"));
+        assert!(svg.contains("surfdoc-diagram-dependency"));
+        assert!(svg.contains("surfdoc-diagram-today"));
+        assert_eq!(svg, render_svg(&model, None, None));
+        // A today outside the axis draws nothing; no dependencies, no arrow defs.
+        let svg = render_svg(&gantt("A: 0, 4\ntoday 40"), None, None);
+        assert!(!svg.contains("surfdoc-diagram-today"));
+        assert!(!svg.contains("<marker"));
+    }
+
+    #[test]
+    fn gantt_today_comes_from_the_caller_unless_the_source_sets_it() {
+        let dated = gantt("A: 2026-01-01, 10");
+        let overridden = gantt("A: 2026-01-01, 10\ntoday 2026-01-08");
+        let with_line = render_svg(&gantt("A: 2026-01-01, 10\ntoday 2026-01-04"), None, None);
+        let overridden_svg = render_svg(&overridden, None, None);
+        assert!(!render_svg(&dated, None, None).contains("surfdoc-diagram-today"));
+        {
+            let _today = install_diagram_today("2026-01-04").expect("valid date");
+            assert_eq!(render_svg(&dated, None, None), with_line);
+            assert_eq!(render_svg(&overridden, None, None), overridden_svg);
+            // A calendar date means nothing on a numeric axis.
+            assert!(!render_svg(&gantt("A: 0, 4"), None, None).contains("surfdoc-diagram-today"));
+        }
+        assert!(!render_svg(&dated, None, None).contains("surfdoc-diagram-today"));
+        assert!(install_diagram_today("2026-13-01").is_none());
+        assert!(install_diagram_today("3").is_none());
+    }
+
     // ── state ───────────────────────────────────────────────────────
 
     fn state(content: &str) -> DiagramModel {
@@ -5849,7 +8824,7 @@ mod tests {
     #[test]
     fn state_svg_structure_and_determinism() {
         let model = state("[*] -> Idle\nIdle -> Done: go\nDone -> [*]");
-        let svg = render_svg(&model, Some("SM"));
+        let svg = render_svg(&model, Some("SM"), None);
         assert!(svg.starts_with("<svg class=\"surfdoc-diagram-svg\""));
         assert!(svg.contains("<title>SM</title>"));
         assert!(svg.contains("surfdoc-diagram-initial"));
@@ -5857,7 +8832,31 @@ mod tests {
         assert!(svg.contains("surfdoc-diagram-state"));
         assert!(svg.contains(">go</text>"));
         assert!(svg.ends_with("</svg>"));
-        assert_eq!(render_svg(&model, Some("SM")), render_svg(&model, Some("SM")));
+        assert_eq!(render_svg(&model, Some("SM"), None), render_svg(&model, Some("SM"), None));
+    }
+
+    #[test]
+    fn state_style_sets_emphasis() {
+        let DiagramModel::State { nodes, transitions } =
+            state("[*] -> Idle\nIdle -> Failed: crash\nstyle Failed: danger\nstyle Idle -> Failed: danger")
+        else {
+            panic!("expected State");
+        };
+        let failed = nodes.iter().find(|n| n.id == "Failed").expect("declared");
+        assert_eq!(failed.emphasis, Some(Emphasis::Danger));
+        assert_eq!(transitions[1].emphasis, Some(Emphasis::Danger));
+        let svg = render_svg(&state("a -> b\nstyle b: success"), None, None);
+        assert!(svg.contains("stroke=\"#16a34a\" stroke-width=\"2\""));
+    }
+
+    #[test]
+    fn state_and_architecture_links_render_anchors() {
+        let svg = render_svg(&state("[*] -> Idle\nlink Idle: /states/idle"), None, None);
+        assert!(svg.contains("<a href=\"/states/idle\" class=\"surfdoc-diagram-link\"><title>/states/idle</title><g class=\"surfdoc-diagram-state\">"));
+        let DiagramModel::Architecture { nodes, .. } = arch("api: API\nlink api: https://api.example.com \"API docs\"") else {
+            panic!("expected Architecture");
+        };
+        assert_eq!(nodes[0].link.as_ref().map(|l| l.url.as_str()), Some("https://api.example.com"));
     }
 
     // ── mindmap ─────────────────────────────────────────────────────
@@ -5882,19 +8881,19 @@ mod tests {
     #[test]
     fn mindmap_svg_structure_and_determinism() {
         let model = mind("Product\n  Web\n    Landing\n    Pricing\n  Mobile\n  API");
-        let svg = render_svg(&model, Some("Map"));
+        let svg = render_svg(&model, Some("Map"), None);
         assert!(svg.starts_with("<svg class=\"surfdoc-diagram-svg\""));
         assert!(svg.contains("<title>Map</title>"));
         assert!(svg.contains("surfdoc-diagram-mind-node"));
         assert!(svg.contains("surfdoc-diagram-branch"));
         assert!(svg.contains(">Landing</text>"));
         assert!(svg.ends_with("</svg>"));
-        assert_eq!(render_svg(&model, Some("Map")), render_svg(&model, Some("Map")));
+        assert_eq!(render_svg(&model, Some("Map"), None), render_svg(&model, Some("Map"), None));
     }
 
     #[test]
     fn new_kinds_escape_user_text() {
-        let svg = render_svg(&flow("a: <script>x</script>\na -> b"), None);
+        let svg = render_svg(&flow("a: <script>x</script>\na -> b"), None, None);
         assert!(!svg.contains("<script>"));
         assert!(svg.contains("&lt;script&gt;"));
         // Stage-2 kinds escape through the same emitter.
@@ -5906,7 +8905,7 @@ mod tests {
             ("usecase", "usecase a: <b>Case</b>"),
         ] {
             let model = parse_diagram_source(kind, body).expect("body parses");
-            let svg = render_svg(&model, None);
+            let svg = render_svg(&model, None, None);
             assert!(!svg.contains("<b>"), "{kind} must escape labels");
             assert!(svg.contains("&lt;b&gt;"), "{kind} must escape labels");
         }
@@ -5917,10 +8916,10 @@ mod tests {
         for kind in [
             "flowchart", "sequence", "gantt", "state", "mindmap", "class",
             "timeline", "journey", "quadrant", "kanban", "usecase",
-            "gitgraph", "c4", "requirement", "sankey",
+            "gitgraph", "c4", "requirement", "sankey", "packet", "block",
         ] {
             let model = parse_diagram_source(kind, "").expect("empty body parses");
-            let svg = render_svg(&model, None);
+            let svg = render_svg(&model, None, None);
             assert!(svg.starts_with("<svg"), "{kind} should render an svg");
             assert!(svg.ends_with("</svg>"));
         }
@@ -6028,7 +9027,7 @@ mod tests {
         let model = class(
             "User: +id, -email, +save()\nRole: enum, Admin, Member\nProfile: bio\nTeam: +name\nAdmin ^-> User\nUser *-> Profile: owns\nTeam o-> User: members\nUser -> Role",
         );
-        let svg = render_svg(&model, Some("Domain"));
+        let svg = render_svg(&model, Some("Domain"), None);
         assert!(svg.starts_with("<svg class=\"surfdoc-diagram-svg\""));
         assert!(svg.contains("<title>Domain</title>"));
         // Three-compartment boxes.
@@ -6054,12 +9053,12 @@ mod tests {
         // Relation label.
         assert!(svg.contains(">owns</text>"));
         assert!(svg.ends_with("</svg>"));
-        assert_eq!(render_svg(&model, Some("Domain")), render_svg(&model, Some("Domain")));
+        assert_eq!(render_svg(&model, Some("Domain"), None), render_svg(&model, Some("Domain"), None));
     }
 
     #[test]
     fn class_svg_escapes_user_text() {
-        let svg = render_svg(&class("A: <b>x</b>\nA -> B: <i>l</i>"), None);
+        let svg = render_svg(&class("A: <b>x</b>\nA -> B: <i>l</i>"), None, None);
         assert!(!svg.contains("<b>"));
         assert!(svg.contains("&lt;b&gt;x&lt;/b&gt;"));
         assert!(svg.contains("&lt;i&gt;l&lt;/i&gt;"));
@@ -6118,7 +9117,7 @@ mod tests {
     #[test]
     fn timeline_svg_structure_and_determinism() {
         let model = timeline("2026-01: Kickoff\n2026-03: Beta\n2026-06: Launch");
-        let svg = render_svg(&model, Some("Roadmap"));
+        let svg = render_svg(&model, Some("Roadmap"), None);
         assert!(svg.starts_with("<svg class=\"surfdoc-diagram-svg\""));
         assert!(svg.contains("<title>Roadmap</title>"));
         assert!(svg.contains("surfdoc-diagram-spine"));
@@ -6128,7 +9127,7 @@ mod tests {
         assert!(svg.contains(">2026-01</text>"));
         assert!(svg.contains(">Kickoff</text>"));
         assert!(svg.ends_with("</svg>"));
-        assert_eq!(render_svg(&model, Some("Roadmap")), render_svg(&model, Some("Roadmap")));
+        assert_eq!(render_svg(&model, Some("Roadmap"), None), render_svg(&model, Some("Roadmap"), None));
     }
 
     #[test]
@@ -6191,7 +9190,7 @@ mod tests {
     #[test]
     fn journey_svg_structure_and_determinism() {
         let model = journey("section On\nSign up: 3\nVerify: 2\nsection Use\nOpen: 5");
-        let svg = render_svg(&model, Some("Journey"));
+        let svg = render_svg(&model, Some("Journey"), None);
         assert!(svg.starts_with("<svg class=\"surfdoc-diagram-svg\""));
         assert!(svg.contains("<title>Journey</title>"));
         assert!(svg.contains("surfdoc-diagram-lane"));
@@ -6202,7 +9201,7 @@ mod tests {
         assert!(svg.contains(">Sign up</text>"));
         assert!(svg.contains(">5</text>")); // score axis tick
         assert!(svg.ends_with("</svg>"));
-        assert_eq!(render_svg(&model, None), render_svg(&model, None));
+        assert_eq!(render_svg(&model, None, None), render_svg(&model, None, None));
     }
 
     #[test]
@@ -6271,7 +9270,7 @@ mod tests {
         let model = quadrant(
             "x-axis Low --> High\ny-axis Cold --> Hot\nquadrant-1: Invest\nA: 0.75, 0.75",
         );
-        let svg = render_svg(&model, Some("Q"));
+        let svg = render_svg(&model, Some("Q"), None);
         assert!(svg.starts_with("<svg class=\"surfdoc-diagram-svg\""));
         assert!(svg.contains("<title>Q</title>"));
         assert!(svg.contains("surfdoc-diagram-frame"));
@@ -6282,7 +9281,7 @@ mod tests {
         assert!(svg.contains(">Invest</text>"));
         assert!(svg.contains(">A</text>"));
         assert!(svg.ends_with("</svg>"));
-        assert_eq!(render_svg(&model, Some("Q")), render_svg(&model, Some("Q")));
+        assert_eq!(render_svg(&model, Some("Q"), None), render_svg(&model, Some("Q"), None));
     }
 
     #[test]
@@ -6344,7 +9343,7 @@ mod tests {
     #[test]
     fn kanban_svg_structure_and_determinism() {
         let model = kanban("column To do\n  Spec\nDoing:\n  Parser\nDone:\n  v1");
-        let svg = render_svg(&model, Some("Board"));
+        let svg = render_svg(&model, Some("Board"), None);
         assert!(svg.starts_with("<svg class=\"surfdoc-diagram-svg\""));
         assert!(svg.contains("<title>Board</title>"));
         assert!(svg.contains("surfdoc-diagram-column"));
@@ -6353,14 +9352,14 @@ mod tests {
         assert!(svg.contains(">To do</text>"));
         assert!(svg.contains(">Parser</text>"));
         assert!(svg.ends_with("</svg>"));
-        assert_eq!(render_svg(&model, Some("Board")), render_svg(&model, Some("Board")));
+        assert_eq!(render_svg(&model, Some("Board"), None), render_svg(&model, Some("Board"), None));
     }
 
     #[test]
     fn kanban_overflow_note_after_eight_cards() {
         let cards: Vec<String> = (1..=11).map(|i| format!("  Card {i}")).collect();
         let body = format!("column Busy\n{}", cards.join("\n"));
-        let svg = render_svg(&kanban(&body), None);
+        let svg = render_svg(&kanban(&body), None, None);
         assert!(svg.contains(">Card 8</text>"));
         assert!(!svg.contains(">Card 9</text>"));
         assert!(svg.contains("surfdoc-diagram-more"));
@@ -6446,7 +9445,7 @@ mod tests {
         let model = usecase(
             "actor customer: Customer\nusecase browse: Browse\nusecase pay: Pay\ncustomer -> browse\nbrowse ^-> pay: includes",
         );
-        let svg = render_svg(&model, Some("Shop"));
+        let svg = render_svg(&model, Some("Shop"), None);
         assert!(svg.starts_with("<svg class=\"surfdoc-diagram-svg\""));
         assert!(svg.contains("<title>Shop</title>"));
         assert!(svg.contains("surfdoc-diagram-boundary"));
@@ -6459,7 +9458,7 @@ mod tests {
         assert!(svg.contains("<ellipse")); // use-case bubbles
         assert!(svg.contains(">Customer</text>"));
         assert!(svg.ends_with("</svg>"));
-        assert_eq!(render_svg(&model, Some("Shop")), render_svg(&model, Some("Shop")));
+        assert_eq!(render_svg(&model, Some("Shop"), None), render_svg(&model, Some("Shop"), None));
     }
 
     #[test]
@@ -6501,6 +9500,7 @@ mod tests {
         for kind in [
             "architecture", "erd", "flowchart", "sequence", "gantt", "state",
             "mindmap", "class", "timeline", "journey", "quadrant", "kanban", "usecase",
+            "packet", "block",
         ] {
             assert_eq!(chart_alias(kind), None, "{kind} must not alias to a chart");
         }
@@ -6534,9 +9534,11 @@ mod tests {
             ("c4", "person u: User\nboundary Core {\ncontainer api: API: Rust\n}\nu -> api: Uses"),
             ("requirement", "requirement r1: Fast: under 5ms\nelement p: parser\np -> r1: satisfies"),
             ("sankey", "Wind -> Grid: 40\nGrid -> Homes: 25.5"),
+            ("packet", "0-15: Port\n16-47: Sequence"),
+            ("block", "columns 2\na: A\nspace\nb [2]: B\na -> b: x"),
         ] {
             let model = parse_diagram_source(kind, body).expect("body parses");
-            let scene = build_scene(&model);
+            let scene = build_scene(&model, None);
             assert!(scene.w > 0 && scene.h > 0, "{kind} scene has a canvas");
             let shapes: Vec<&NativeShape> = scene
                 .items
@@ -6604,7 +9606,7 @@ mod tests {
             "commit: init\nbranch dev\ncommit\ncheckout main\nmerge dev",
         )
         .expect("parses");
-        let svg = render_svg(&model, Some("History"));
+        let svg = render_svg(&model, Some("History"), None);
         assert!(svg.starts_with("<svg class=\"surfdoc-diagram-svg\""));
         assert!(svg.contains("<title>History</title>"));
         assert!(svg.contains("surfdoc-diagram-lane"));
@@ -6670,7 +9672,7 @@ mod tests {
             "person u: Customer\nboundary Core {\ncontainer api: API: Rust\n}\nsystem ext1: Billing [ext]\nu -> api: Uses\napi -> ext1",
         )
         .expect("parses");
-        let svg = render_svg(&model, None);
+        let svg = render_svg(&model, None, None);
         assert!(svg.contains("surfdoc-diagram-boundary"));
         assert!(svg.contains(">Core</text>"));
         assert!(svg.contains("stroke-dasharray=\"6 4\""));
@@ -6723,7 +9725,7 @@ mod tests {
             "requirement r1: Fast: under 5ms\nelement p: parser\np -> r1: satisfies",
         )
         .expect("parses");
-        let svg = render_svg(&model, None);
+        let svg = render_svg(&model, None, None);
         assert!(svg.contains("surfdoc-diagram-requirement"));
         assert!(svg.contains("\u{ab}requirement\u{bb}"));
         assert!(svg.contains("\u{ab}element\u{bb}"));
@@ -6769,7 +9771,7 @@ mod tests {
             "Wind -> Grid: 40\nSolar -> Grid: 30\nGrid -> Homes: 50\nGrid -> Industry: 20",
         )
         .expect("parses");
-        let svg = render_svg(&model, Some("Energy"));
+        let svg = render_svg(&model, Some("Energy"), None);
         assert!(svg.contains("<title>Energy</title>"));
         // One trapezoid band per flow.
         assert_eq!(svg.matches("surfdoc-diagram-flow").count(), 4);
@@ -6789,7 +9791,7 @@ mod tests {
             "A -> B: 99999999999999999999\nB -> C: 92233720368547758.07",
         )
         .expect("parses");
-        let svg = render_svg(&model, None);
+        let svg = render_svg(&model, None, None);
         assert!(svg.starts_with("<svg"));
         assert_eq!(svg.matches("surfdoc-diagram-flow").count(), 2);
     }
@@ -6800,7 +9802,7 @@ mod tests {
         // (single global scale, integer arithmetic).
         let model = parse_diagram_source("sankey", "A -> C: 30\nB -> C: 10\nC -> D: 40")
             .expect("parses");
-        let scene = build_scene(&model);
+        let scene = build_scene(&model, None);
         let polys: Vec<&NativeShape> = scene
             .items
             .iter()
@@ -6815,4 +9817,217 @@ mod tests {
             assert_eq!(points.len(), 4, "bands are straight-edged trapezoids");
         }
     }
+
+    // ── DSL parsing: packet ─────────────────────────────────────────
+
+    #[test]
+    fn packet_fields_tile_from_bit_zero() {
+        let DiagramModel::Packet { fields } =
+            parse_diagram_source("packet", "0-15: Source port\n16-31: Destination port\n32: SYN\n+7: Flags")
+                .expect("packet should parse")
+        else {
+            panic!("expected Packet model");
+        };
+        let spans: Vec<(i64, i64)> = fields.iter().map(|f| (f.start, f.bits)).collect();
+        assert_eq!(spans, vec![(0, 16), (16, 16), (32, 1), (33, 7)]);
+        assert_eq!(fields[3].label, "Flags");
+    }
+
+    #[test]
+    fn packet_gap_reports_line_number() {
+        let e = parse_diagram_source("packet", "0-7: A\n9-15: B").expect_err("gap");
+        assert_eq!(e.line, 2);
+        assert!(e.message.contains("expected bit 8"), "{}", e.message);
+        assert!(parse_diagram_source("packet", "0-7: A\n4-15: B").is_err(), "overlap");
+        assert!(parse_diagram_source("packet", "7-0: Backwards").is_err());
+        assert!(parse_diagram_source("packet", "0-4096: Huge").is_err());
+    }
+
+    #[test]
+    fn packet_svg_wraps_fields_across_rows() {
+        // A 16-bit field starting at bit 24 straddles the 32-bit row edge.
+        let model = parse_diagram_source("packet", "0-23: Header\n24-39: Length").expect("parses");
+        let svg = render_svg(&model, Some("Frame"), None);
+        assert!(svg.contains("<title>Frame</title>"));
+        assert_eq!(svg.matches("class=\"surfdoc-diagram-packet-field\"").count(), 2);
+        // Three segments: Header, then Length split over two rows.
+        assert_eq!(svg.matches("<rect").count(), 3);
+        assert!(svg.contains(">Length</text>"));
+        assert!(svg.contains(">39</text>"));
+    }
+
+    // ── DSL parsing: block ──────────────────────────────────────────
+
+    #[test]
+    fn block_cells_spans_and_spaces() {
+        let DiagramModel::Block { columns, cells, edges } =
+            parse_diagram_source("block", "columns 3\na: Client\nspace\nb [2]: Server\nspace [2]\na -> b: calls")
+                .expect("block should parse")
+        else {
+            panic!("expected Block model");
+        };
+        assert_eq!(columns, Some(3));
+        let shape: Vec<(Option<&str>, usize)> = cells.iter().map(|c| (c.id.as_deref(), c.span)).collect();
+        assert_eq!(shape, vec![(Some("a"), 1), (None, 1), (Some("b"), 2), (None, 2)]);
+        assert_eq!(cells[2].label, "Server");
+        assert_eq!(edges[0].label.as_deref(), Some("calls"));
+    }
+
+    #[test]
+    fn block_rejects_duplicates_and_declares_edge_endpoints() {
+        let e = parse_diagram_source("block", "a: A\na: Again").expect_err("duplicate id");
+        assert_eq!(e.line, 2);
+        assert!(parse_diagram_source("block", "columns 2\ncolumns 3").is_err());
+        let DiagramModel::Block { cells, .. } = parse_diagram_source("block", "a -> b").expect("parses") else {
+            panic!("expected Block model");
+        };
+        let ids: Vec<Option<&str>> = cells.iter().map(|c| c.id.as_deref()).collect();
+        assert_eq!(ids, vec![Some("a"), Some("b")]);
+    }
+
+    #[test]
+    fn block_grid_wraps_rows_at_the_column_count() {
+        let model = parse_diagram_source("block", "columns 2\na\nb\nc [2]").expect("parses");
+        let scene = build_scene(&model, None);
+        let rects: Vec<(f64, f64, f64)> = scene
+            .items
+            .iter()
+            .filter_map(|i| match i {
+                SvgItem::Shape { shape: NativeShape::Rect { x, y, w, .. }, .. } => Some((*x, *y, *w)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(rects.len(), 3);
+        // a and b share the first row; c fills the whole second row.
+        assert_eq!(rects[0].1, rects[1].1);
+        assert!(rects[2].1 > rects[0].1);
+        assert_eq!(rects[2].0, rects[0].0);
+        assert!(rects[2].2 > rects[0].2 + rects[1].2);
+    }
+
+    #[test]
+    fn block_svg_structure() {
+        let model = parse_diagram_source("block", "columns 3\nfe: Frontend\nspace\nbe: Backend\nfe -> be: REST")
+            .expect("parses");
+        let svg = render_svg(&model, None, None);
+        assert_eq!(svg.matches("class=\"surfdoc-diagram-block\"").count(), 2);
+        assert_eq!(svg.matches("class=\"surfdoc-diagram-edge\"").count(), 1);
+        assert!(svg.contains(">Frontend</text>"));
+        assert!(svg.contains(">REST</text>"));
+    }
+
+    // ── text measurement ────────────────────────────────────────────
+
+    /// Node rects and label texts of an architecture scene, in push order.
+    fn arch_boxes(content: &str) -> (Vec<(i64, i64)>, Vec<String>) {
+        let scene = build_scene(&arch(content), None);
+        let mut rects = Vec::new();
+        let mut labels = Vec::new();
+        for item in &scene.items {
+            match item {
+                SvgItem::Shape { shape: NativeShape::Rect { w, h, .. }, .. } => rects.push((*w as i64, *h as i64)),
+                SvgItem::Shape { shape: NativeShape::Label { text, .. }, .. } => labels.push(text.clone()),
+                _ => {}
+            }
+        }
+        (rects, labels)
+    }
+
+    #[test]
+    fn box_widths_follow_measured_text() {
+        // Same char count: wide glyphs get a wider box than narrow Latin.
+        let (latin, _) = arch_boxes("a: illicit little lilies");
+        let (cjk, _) = arch_boxes("a: 東京都庁舎東京都庁舎東京都庁舎東京都庁舎東");
+        assert!(cjk[0].0 > latin[0].0, "{cjk:?} vs {latin:?}");
+        // Narrow Latin is sized per glyph, well under a flat 8px per char.
+        assert!(latin[0].0 < 24 * 8 + 24);
+    }
+
+    #[test]
+    fn long_labels_wrap_onto_taller_boxes() {
+        let (rects, labels) = arch_boxes("a: Authentication and authorization gateway service
+b: Db
+a -> b");
+        assert_eq!(labels, vec!["Authentication and authorization", "gateway service", "Db"]);
+        // Every node shares the wrapped row height; widths stay within the cap.
+        assert_eq!(rects[0].1, NODE_H + LABEL_LINE_H);
+        assert_eq!(rects[1].1, rects[0].1);
+        assert!(rects[0].0 <= LABEL_MAX_W + 24);
+        // Short labels render exactly as before: one line on the cy + 4 baseline.
+        let svg = render_svg(&arch("a: Db"), None, None);
+        assert_eq!(svg.matches("<text").count(), 1);
+    }
+
+    // ── edge routing ────────────────────────────────────────────────
+
+    /// Edge polylines (integer points) of a scene, in paint order.
+    fn edge_routes(model: &DiagramModel) -> Vec<Vec<(i64, i64)>> {
+        build_scene(model, None)
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                SvgItem::Shape {
+                    shape: NativeShape::Line { points, .. },
+                    chrome: Chrome { class: Some("surfdoc-diagram-edge" | "surfdoc-diagram-transition"), .. },
+                } => Some(points.iter().map(|p| (p.x as i64, p.y as i64)).collect()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn routes_turn_at_right_angles_around_nodes_in_the_way() {
+        // Three boxes stacked in one column: the 0 -> 2 edge must go around 1.
+        let rects = [
+            Rect { x: 20, y: 20, w: 80, h: 40 },
+            Rect { x: 20, y: 110, w: 80, h: 40 },
+            Rect { x: 20, y: 200, w: 80, h: 40 },
+        ];
+        let reqs = [RouteReq { from: 0, to: 2, exit: Side::Bottom, entry: Side::Top }];
+        let skip = &route_edges(&rects, &[], &reqs)[0];
+        assert!(skip.len() > 2, "{skip:?}");
+        for seg in skip.windows(2) {
+            assert!(seg[0].0 == seg[1].0 || seg[0].1 == seg[1].1, "diagonal segment {seg:?}");
+            let (x0, x1) = (seg[0].0.min(seg[1].0), seg[0].0.max(seg[1].0));
+            let (y0, y1) = (seg[0].1.min(seg[1].1), seg[0].1.max(seg[1].1));
+            assert!(x1 < 20 || x0 > 100 || y1 < 110 || y0 > 150, "{seg:?} runs through box 1");
+        }
+    }
+
+    #[test]
+    fn opposite_edges_leave_from_separate_ports() {
+        let routes = edge_routes(&state("Idle -> Running: start\nRunning -> Idle: stop"));
+        assert_eq!(routes.len(), 2);
+        let (down, up) = (&routes[0], &routes[1]);
+        assert_ne!(down[0].0, up[up.len() - 1].0, "{down:?} / {up:?}");
+        assert!(down.iter().all(|p| !up.contains(p)), "{down:?} / {up:?}");
+    }
+
+    #[test]
+    fn edge_labels_avoid_nodes_and_each_other() {
+        let rects = [Rect { x: 20, y: 20, w: 80, h: 40 }, Rect { x: 20, y: 110, w: 80, h: 40 }];
+        let reqs = [
+            RouteReq { from: 0, to: 1, exit: Side::Bottom, entry: Side::Top },
+            RouteReq { from: 1, to: 0, exit: Side::Top, entry: Side::Bottom },
+            RouteReq { from: 0, to: 1, exit: Side::Bottom, entry: Side::Top },
+        ];
+        let routes = route_edges(&rects, &[], &reqs);
+        let mut taken = Vec::new();
+        for k in 0..routes.len() {
+            place_edge_label(&routes, k, "label", &rects, (200, 200), &mut taken);
+        }
+        let overlaps = |a: &Rect, b: &Rect| a.x < b.x + b.w && b.x < a.x + a.w && a.y < b.y + b.h && b.y < a.y + a.h;
+        for (i, a) in taken.iter().enumerate() {
+            assert!(rects.iter().all(|r| !overlaps(a, r)), "label {i} on a node: {a:?}");
+            assert!(taken[i + 1..].iter().all(|b| !overlaps(a, b)), "labels collide: {taken:?}");
+        }
+    }
+
+    #[test]
+    fn routing_is_deterministic() {
+        let body = "a: A\nb [diamond]: B?\nc: C\nd: D\na -> b\nb -> c: yes\nb -> d: no\nd -> a\nc -> a: retry\nc -> c";
+        let first = render_svg(&flow(body), None, None);
+        assert_eq!(render_svg(&flow(body), None, None), first);
+        assert!(first.contains("<path class=\"surfdoc-diagram-edge\""));
+    }
 }
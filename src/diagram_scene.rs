@@ -46,8 +46,8 @@ pub enum NativeRole {
     /// Emphasized surface — title bars, activation bars, roots, and the
     /// gantt axis gridlines (SVG: `#e2e8f0`).
     SurfaceAlt,
-    /// Theme accent, reserved for highlighted shapes (SVG: `#2563eb`;
-    /// unused by the built-in diagram types today).
+    /// Theme accent (SVG: `#2563eb`; unused by the built-in diagram types
+    /// today).
     Accent,
     /// Soft accent — de-emphasized guide lines such as sequence lifelines
     /// (SVG: `#cbd5e1`).
@@ -55,8 +55,8 @@ pub enum NativeRole {
     /// Primary outline/edge color, also arrowheads and state markers
     /// (SVG: `#64748b`).
     Stroke,
-    /// Muted solid fill — gantt bars, mindmap branch connectors
-    /// (SVG: `#94a3b8`).
+    /// Muted solid fill — gantt bars, mindmap branch connectors — and the
+    /// outline of nodes/edges styled `muted` (SVG: `#94a3b8`).
     Muted,
     /// Primary text (SVG: `currentColor`, i.e. the page text color).
     TextPrimary,
@@ -65,6 +65,17 @@ pub enum NativeRole {
     /// Paint on top of / inside accent or paper surfaces — also the plain
     /// paper fill of table-style boxes (SVG: `#ffffff`).
     OnAccent,
+    /// Group cluster background behind member nodes in architecture and
+    /// flowchart diagrams (SVG: `#f1f5f9`).
+    Cluster,
+    /// Outline of nodes/edges styled `highlight` (SVG: `#d97706`).
+    Highlight,
+    /// Outline of nodes/edges styled `danger` — failures, blockers
+    /// (SVG: `#dc2626`).
+    Danger,
+    /// Outline of nodes/edges styled `success` — happy paths, done states
+    /// (SVG: `#16a34a`).
+    Success,
 }
 
 /// Horizontal text anchoring relative to a label's `x`.
@@ -97,6 +108,7 @@ pub enum NativeMarker {
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum NativeShape {
     /// Axis-aligned rectangle. `corner` is the corner radius (0 = square).
+    /// `href` is the target of a linked diagram node (`link id: url`).
     Rect {
         x: f64,
         y: f64,
@@ -106,6 +118,8 @@ pub enum NativeShape {
         fill: NativeRole,
         stroke: NativeRole,
         stroke_width: f64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        href: Option<String>,
     },
     /// Open polyline through `points` (2 points = straight segment). Never
     /// filled. `dashed` marks async/guide styling; markers sit on the first
@@ -118,11 +132,14 @@ pub enum NativeShape {
         marker_start: NativeMarker,
         marker_end: NativeMarker,
     },
-    /// Closed filled polygon (e.g. flowchart diamonds).
+    /// Closed filled polygon (e.g. flowchart diamonds). `href` as for
+    /// [`NativeShape::Rect`].
     Polygon {
         points: Vec<NativePoint>,
         fill: NativeRole,
         stroke: NativeRole,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        href: Option<String>,
     },
     /// Ellipse/circle. `fill: None` paints a hollow ring; `stroke: None`
     /// draws no outline.
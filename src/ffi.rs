@@ -11,6 +11,7 @@
 //! cdylib/staticlib that links us, so the generated Swift/Kotlin module for
 //! this crate is named `surf_parse`.
 
+use crate::parse::ParseOptions;
 use crate::render_native::{self, NativeBlock, NativeDoc, NativeTheme, NATIVE_DOC_SCHEMA_VERSION};
 use crate::resolve;
 
@@ -31,6 +32,35 @@ pub enum SurfDocError {
 
     #[error("Internal error: {msg}")]
     Internal { msg: String },
+
+    /// A [`ParseLimits`] bound was hit (P007); the document is rejected.
+    #[error("Parse limit exceeded: {msg}")]
+    LimitExceeded { msg: String },
+}
+
+/// FFI mirror of [`crate::parse::ParseOptions`] (UniFFI has no `usize`).
+/// `None` leaves a bound unset; use the same values the publish server
+/// passes to `surf-lint check` (`[limits]`) so both reject alike.
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct ParseLimits {
+    pub max_depth: Option<u32>,
+    pub max_blocks: Option<u32>,
+    pub max_source_bytes: Option<u32>,
+    pub max_attr_len: Option<u32>,
+}
+
+impl From<&ParseLimits> for crate::parse::ParseOptions {
+    fn from(limits: &ParseLimits) -> Self {
+        let widen = |v: Option<u32>| v.map(|n| n as usize);
+        Self {
+            max_depth: widen(limits.max_depth),
+            max_blocks: widen(limits.max_blocks),
+            max_source_bytes: widen(limits.max_source_bytes),
+            max_attr_len: widen(limits.max_attr_len),
+            max_include_depth: None,
+            max_includes: None,
+        }
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════
@@ -44,7 +74,7 @@ pub enum SurfDocError {
 /// Non-fatal diagnostics are dropped at this layer.
 #[uniffi::export]
 pub fn parse_surfdoc(source: String) -> Result<Vec<NativeBlock>, SurfDocError> {
-    let doc = parse_checked(&source)?;
+    let doc = parse_checked(&source, &ParseOptions::default())?;
     Ok(render_native::to_native_blocks(&doc))
 }
 
@@ -61,6 +91,13 @@ pub fn parse_to_native(source: String) -> Result<NativeDoc, SurfDocError> {
     parse_to_native_styled(source, None, None, None)
 }
 
+/// [`parse_to_native`] under parse resource bounds (web-runtime spec §4.4).
+/// A bound that is hit fails with [`SurfDocError::LimitExceeded`].
+#[uniffi::export]
+pub fn parse_to_native_with(source: String, limits: ParseLimits) -> Result<NativeDoc, SurfDocError> {
+    native_doc(&source, &ParseOptions::from(&limits), None, None, None)
+}
+
 /// [`parse_to_native`] with host-supplied theme inputs. Explicit arguments
 /// win over document-derived values; `None` falls through to the document,
 /// then to platform defaults. Unknown pack keys resolve to Surf Simple so a
@@ -72,7 +109,17 @@ pub fn parse_to_native_styled(
     accent: Option<String>,
     font: Option<String>,
 ) -> Result<NativeDoc, SurfDocError> {
-    let doc = parse_checked(&source)?;
+    native_doc(&source, &ParseOptions::default(), style_pack, accent, font)
+}
+
+fn native_doc(
+    source: &str,
+    options: &ParseOptions,
+    style_pack: Option<String>,
+    accent: Option<String>,
+    font: Option<String>,
+) -> Result<NativeDoc, SurfDocError> {
+    let doc = parse_checked(source, options)?;
 
     // Document-derived theme inputs from the ::site block, if any.
     let (site, _pages, _loose) = crate::render_html::extract_site(&doc);
@@ -99,8 +146,21 @@ pub fn parse_to_native_styled(
 }
 
 /// Shared parse + fatal-diagnostic routing for the FFI entry points.
-fn parse_checked(source: &str) -> Result<crate::types::SurfDoc, SurfDocError> {
-    let result = crate::parse(source);
+fn parse_checked(
+    source: &str,
+    options: &ParseOptions,
+) -> Result<crate::types::SurfDoc, SurfDocError> {
+    let result = crate::parse::parse_with(source, options);
+
+    if let Some(d) = result
+        .diagnostics
+        .iter()
+        .find(|d| d.code.as_deref() == Some("P007"))
+    {
+        return Err(SurfDocError::LimitExceeded {
+            msg: d.message.clone(),
+        });
+    }
 
     let fatals: Vec<&crate::error::Diagnostic> = result
         .diagnostics
@@ -200,6 +260,18 @@ mod tests {
         assert_eq!(doc.theme.pack_id, "surf");
     }
 
+    #[test]
+    fn parse_to_native_with_rejects_over_limit() {
+        let limits = ParseLimits {
+            max_depth: Some(1),
+            ..ParseLimits::default()
+        };
+        let nested = "::columns\n:::column\nA\n:::\n::\n";
+        let err = parse_to_native_with(nested.into(), limits.clone()).unwrap_err();
+        assert!(matches!(err, SurfDocError::LimitExceeded { .. }), "{err}");
+        assert!(parse_to_native_with("::summary\nS\n::\n".into(), limits).is_ok());
+    }
+
     #[test]
     fn section_preserves_children() {
         let blocks = parse_surfdoc(
@@ -0,0 +1,241 @@
+//! Advance widths of the bundled Liberation Sans faces (GENERATED table -
+//! do not hand-edit entries).
+//!
+//! Source: `assets/fonts/LiberationSans-Regular.ttf` and
+//! `LiberationSans-Bold.ttf` (metric-compatible with Arial, so the widths also
+//! hold for the common `system-ui, sans-serif` fallbacks). Each table holds the
+//! `hmtx` advance, in font units (2048 per em), of the glyph the Windows
+//! Unicode `cmap` subtable maps each code point to; `0` marks a code point the
+//! face has no glyph for. Regenerate by re-reading both fonts over the same
+//! ranges; [`crate::text_metrics`] owns the fallbacks for everything else.
+
+/// Font units per em of both faces.
+pub(crate) const UNITS_PER_EM: i64 = 2048;
+
+/// A contiguous run of code points starting at `first`, one advance each.
+pub(crate) struct AdvanceRange {
+    pub(crate) first: u32,
+    pub(crate) advances: &'static [u16],
+}
+
+/// Liberation Sans Regular: Latin (U+0020–U+024F), Greek and Cyrillic
+/// (U+0370–U+04FF), General Punctuation (U+2000–U+206F).
+pub(crate) static REGULAR: &[AdvanceRange] = &[
+    AdvanceRange {
+        first: 0x0020,
+        advances: &[
+            569, 569, 727, 1139, 1139, 1821, 1366, 391, 682, 682, 797, 1196, // U+0020
+            569, 682, 569, 569, 1139, 1139, 1139, 1139, 1139, 1139, 1139, 1139, // U+002C
+            1139, 1139, 569, 569, 1196, 1196, 1196, 1139, 2079, 1366, 1366, 1479, // U+0038
+            1479, 1366, 1251, 1593, 1479, 569, 1024, 1366, 1139, 1706, 1479, 1593, // U+0044
+            1366, 1593, 1479, 1366, 1251, 1479, 1366, 1933, 1366, 1366, 1251, 569, // U+0050
+            569, 569, 961, 1139, 682, 1139, 1139, 1024, 1139, 1139, 569, 1139, // U+005C
+            1139, 455, 455, 1024, 455, 1706, 1139, 1139, 1139, 1139, 682, 1024, // U+0068
+            569, 1139, 1024, 1479, 1024, 1024, 1024, 684, 532, 684, 1196, 0, // U+0074
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0080
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+008C
+            0, 0, 0, 0, 0, 0, 0, 0, 569, 682, 1139, 1139, // U+0098
+            1139, 1139, 532, 1139, 682, 1509, 758, 1139, 1196, 682, 1509, 1131, // U+00A4
+            819, 1124, 682, 682, 682, 1180, 1100, 569, 682, 682, 748, 1139, // U+00B0
+            1708, 1708, 1708, 1251, 1366, 1366, 1366, 1366, 1366, 1366, 2048, 1479, // U+00BC
+            1366, 1366, 1366, 1366, 569, 569, 569, 569, 1479, 1479, 1593, 1593, // U+00C8
+            1593, 1593, 1593, 1196, 1593, 1479, 1479, 1479, 1479, 1366, 1366, 1251, // U+00D4
+            1139, 1139, 1139, 1139, 1139, 1139, 1821, 1024, 1139, 1139, 1139, 1139, // U+00E0
+            569, 569, 569, 569, 1139, 1139, 1139, 1139, 1139, 1139, 1139, 1124, // U+00EC
+            1251, 1139, 1139, 1139, 1139, 1024, 1139, 1024, 1366, 1139, 1366, 1139, // U+00F8
+            1366, 1139, 1479, 1024, 1479, 1024, 1479, 1024, 1479, 1024, 1479, 1259, // U+0104
+            1479, 1139, 1366, 1139, 1366, 1139, 1366, 1139, 1366, 1139, 1366, 1139, // U+0110
+            1593, 1139, 1593, 1139, 1593, 1139, 1593, 1139, 1479, 1139, 1479, 1139, // U+011C
+            569, 569, 569, 569, 569, 569, 569, 455, 569, 569, 1505, 909, // U+0128
+            1024, 455, 1366, 1024, 1024, 1139, 455, 1139, 455, 1139, 597, 1139, // U+0134
+            684, 1139, 455, 1479, 1139, 1479, 1139, 1479, 1139, 1237, 1481, 1139, // U+0140
+            1593, 1139, 1593, 1139, 1593, 1139, 2048, 1933, 1479, 682, 1479, 682, // U+014C
+            1479, 682, 1366, 1024, 1366, 1024, 1366, 1024, 1366, 1024, 1251, 569, // U+0158
+            1251, 768, 1251, 569, 1479, 1139, 1479, 1139, 1479, 1139, 1479, 1139, // U+0164
+            1479, 1139, 1479, 1139, 1933, 1479, 1366, 1024, 1366, 1251, 1024, 1251, // U+0170
+            1024, 1251, 1024, 455, 0, 0, 0, 0, 0, 0, 0, 0, // U+017C
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1139, 0, // U+0188
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0194
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+01A0
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+01AC
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+01B8
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+01C4
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+01D0
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+01DC
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+01E8
+            0, 0, 0, 0, 0, 0, 1366, 1139, 2048, 1821, 1593, 1251, // U+01F4
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0200
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+020C
+            1366, 1024, 1251, 569, 0, 0, 0, 0, 0, 0, 0, 0, // U+0218
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0224
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0230
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+023C
+            0, 0, 0, 0, 0, 0, 0, 0, // U+0248
+        ],
+    },
+    AdvanceRange {
+        first: 0x0370,
+        advances: &[
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0370
+            0, 0, 569, 0, 0, 0, 0, 0, 682, 682, 1367, 569, // U+037C
+            1606, 1716, 786, 0, 1586, 0, 1752, 1541, 455, 1366, 1366, 1128, // U+0388
+            1368, 1366, 1251, 1479, 1593, 569, 1366, 1368, 1706, 1479, 1331, 1593, // U+0394
+            1479, 1366, 0, 1266, 1251, 1366, 1634, 1366, 1711, 1531, 569, 1366, // U+03A0
+            1184, 913, 1139, 455, 1120, 1184, 1178, 1024, 1140, 913, 903, 1139, // U+03AC
+            1139, 455, 1024, 1024, 1180, 1024, 917, 1139, 1413, 1165, 987, 1264, // U+03B8
+            809, 1120, 1328, 1075, 1460, 1599, 455, 1120, 1139, 1120, 1599, 0, // U+03C4
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+03D0
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+03DC
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+03E8
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+03F4
+            1366, 1367, 1771, 1109, 1472, 1366, 569, 569, 1024, 2165, 2069, 1749, // U+0400
+            1193, 1479, 1301, 1472, 1366, 1344, 1366, 1109, 1387, 1366, 1891, 1237, // U+040C
+            1472, 1472, 1193, 1344, 1706, 1479, 1593, 1472, 1366, 1479, 1251, 1301, // U+0418
+            1557, 1366, 1515, 1365, 1877, 1920, 1621, 1813, 1344, 1472, 2069, 1479, // U+0424
+            1139, 1173, 1088, 747, 1195, 1139, 1370, 939, 1144, 1144, 896, 1195, // U+0430
+            1408, 1131, 1139, 1109, 1139, 1024, 938, 1024, 1685, 1024, 1173, 1067, // U+043C
+            1643, 1685, 1280, 1472, 1067, 1045, 1536, 1109, 1139, 1139, 1139, 747, // U+0448
+            1045, 1024, 455, 569, 455, 1856, 1664, 1139, 896, 1144, 1024, 1131, // U+0454
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0460
+            0, 0, 0, 0, 0, 0, 1593, 1139, 0, 0, 0, 0, // U+046C
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0478
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0484
+            1001, 842, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0490
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+049C
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+04A8
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+04B4
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+04C0
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+04CC
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+04D8
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+04E4
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+04F0
+            0, 0, 0, 0, // U+04FC
+        ],
+    },
+    AdvanceRange {
+        first: 0x2000,
+        advances: &[
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+2000
+            0, 0, 0, 0, 682, 682, 0, 1139, 2048, 2048, 0, 1131, // U+200C
+            455, 455, 455, 455, 682, 682, 682, 0, 1139, 1139, 717, 0, // U+2018
+            0, 0, 2048, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+2024
+            2048, 0, 384, 725, 0, 0, 0, 0, 0, 682, 682, 0, // U+2030
+            1024, 0, 682, 0, 0, 0, 0, 0, 342, 0, 0, 0, // U+203C
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+2048
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+2054
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+2060
+            0, 0, 0, 0, // U+206C
+        ],
+    },
+];
+
+/// Liberation Sans Bold: Latin (U+0020–U+024F), Greek and Cyrillic
+/// (U+0370–U+04FF), General Punctuation (U+2000–U+206F).
+pub(crate) static BOLD: &[AdvanceRange] = &[
+    AdvanceRange {
+        first: 0x0020,
+        advances: &[
+            569, 682, 971, 1139, 1139, 1821, 1479, 487, 682, 682, 797, 1196, // U+0020
+            569, 682, 569, 569, 1139, 1139, 1139, 1139, 1139, 1139, 1139, 1139, // U+002C
+            1139, 1139, 682, 682, 1196, 1196, 1196, 1251, 1997, 1479, 1479, 1479, // U+0038
+            1479, 1366, 1251, 1593, 1479, 569, 1139, 1479, 1251, 1706, 1479, 1593, // U+0044
+            1366, 1593, 1479, 1366, 1251, 1479, 1366, 1933, 1366, 1366, 1251, 682, // U+0050
+            569, 682, 1196, 1139, 682, 1139, 1251, 1139, 1251, 1139, 682, 1251, // U+005C
+            1251, 569, 569, 1139, 569, 1821, 1251, 1251, 1251, 1251, 797, 1139, // U+0068
+            682, 1251, 1139, 1593, 1139, 1139, 1024, 797, 573, 797, 1196, 0, // U+0074
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0080
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+008C
+            0, 0, 0, 0, 0, 0, 0, 0, 569, 682, 1139, 1139, // U+0098
+            1139, 1139, 573, 1139, 682, 1509, 758, 1139, 1196, 682, 1509, 1131, // U+00A4
+            819, 1124, 682, 682, 682, 1180, 1139, 569, 682, 682, 748, 1139, // U+00B0
+            1708, 1708, 1708, 1251, 1479, 1479, 1479, 1479, 1479, 1479, 2048, 1479, // U+00BC
+            1366, 1366, 1366, 1366, 569, 569, 569, 569, 1479, 1479, 1593, 1593, // U+00C8
+            1593, 1593, 1593, 1196, 1593, 1479, 1479, 1479, 1479, 1366, 1366, 1251, // U+00D4
+            1139, 1139, 1139, 1139, 1139, 1139, 1821, 1139, 1139, 1139, 1139, 1139, // U+00E0
+            569, 569, 569, 569, 1251, 1251, 1251, 1251, 1251, 1251, 1251, 1124, // U+00EC
+            1251, 1251, 1251, 1251, 1251, 1139, 1251, 1139, 1479, 1139, 1479, 1139, // U+00F8
+            1479, 1139, 1479, 1139, 1479, 1139, 1479, 1139, 1479, 1139, 1479, 1472, // U+0104
+            1479, 1251, 1366, 1139, 1366, 1139, 1366, 1139, 1366, 1139, 1366, 1139, // U+0110
+            1593, 1251, 1593, 1251, 1593, 1251, 1593, 1251, 1479, 1251, 1479, 1251, // U+011C
+            569, 569, 569, 569, 569, 569, 569, 569, 569, 569, 1607, 1139, // U+0128
+            1139, 569, 1479, 1139, 1139, 1251, 569, 1251, 569, 1251, 789, 1251, // U+0134
+            981, 1251, 569, 1479, 1251, 1479, 1251, 1479, 1251, 1451, 1481, 1251, // U+0140
+            1593, 1251, 1593, 1251, 1593, 1251, 2048, 1933, 1479, 797, 1479, 797, // U+014C
+            1479, 797, 1366, 1139, 1366, 1139, 1366, 1139, 1366, 1139, 1251, 682, // U+0158
+            1251, 981, 1251, 682, 1479, 1251, 1479, 1251, 1479, 1251, 1479, 1251, // U+0164
+            1479, 1251, 1479, 1251, 1933, 1593, 1366, 1139, 1366, 1251, 1024, 1251, // U+0170
+            1024, 1251, 1024, 569, 0, 0, 0, 0, 0, 0, 0, 0, // U+017C
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1139, 0, // U+0188
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0194
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+01A0
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+01AC
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+01B8
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+01C4
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+01D0
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+01DC
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+01E8
+            0, 0, 0, 0, 0, 0, 1479, 1139, 2048, 1821, 1593, 1251, // U+01F4
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0200
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+020C
+            1366, 1139, 1251, 682, 0, 0, 0, 0, 0, 0, 0, 0, // U+0218
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0224
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0230
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+023C
+            0, 0, 0, 0, 0, 0, 0, 0, // U+0248
+        ],
+    },
+    AdvanceRange {
+        first: 0x0370,
+        advances: &[
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0370
+            0, 0, 682, 0, 0, 0, 0, 0, 682, 952, 1479, 682, // U+037C
+            1747, 1855, 970, 0, 1689, 0, 1899, 1716, 569, 1479, 1479, 1231, // U+0388
+            1472, 1366, 1251, 1479, 1593, 569, 1479, 1366, 1706, 1479, 1318, 1593, // U+0394
+            1479, 1366, 0, 1229, 1251, 1366, 1681, 1366, 1657, 1642, 569, 1366, // U+03A0
+            1259, 924, 1251, 569, 1192, 1259, 1250, 1139, 1242, 972, 943, 1251, // U+03AC
+            1107, 569, 1142, 1139, 1253, 1139, 912, 1251, 1568, 1267, 1065, 1401, // U+03B8
+            914, 1192, 1465, 1179, 1543, 1730, 569, 1192, 1251, 1192, 1730, 0, // U+03C4
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+03D0
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+03DC
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+03E8
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+03F4
+            1366, 1370, 1813, 1161, 1457, 1366, 569, 565, 1139, 2240, 2176, 1792, // U+0400
+            1250, 1479, 1274, 1472, 1479, 1472, 1479, 1161, 1459, 1366, 1851, 1283, // U+040C
+            1472, 1472, 1250, 1437, 1706, 1479, 1593, 1472, 1366, 1479, 1251, 1274, // U+0418
+            1748, 1366, 1496, 1439, 2058, 2087, 1781, 2005, 1472, 1457, 2112, 1472, // U+0424
+            1139, 1265, 1259, 853, 1300, 1139, 1452, 1018, 1259, 1259, 1025, 1301, // U+0430
+            1515, 1237, 1251, 1237, 1251, 1139, 1003, 1139, 1792, 1139, 1259, 1189, // U+043C
+            1707, 1728, 1493, 1749, 1259, 1131, 1749, 1195, 1139, 1139, 1251, 853, // U+0448
+            1131, 1139, 569, 576, 569, 1984, 1856, 1251, 1025, 1259, 1139, 1237, // U+0454
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0460
+            0, 0, 0, 0, 0, 0, 1593, 1251, 0, 0, 0, 0, // U+046C
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0478
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0484
+            997, 915, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+0490
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+049C
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+04A8
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+04B4
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+04C0
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+04CC
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+04D8
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+04E4
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+04F0
+            0, 0, 0, 0, // U+04FC
+        ],
+    },
+    AdvanceRange {
+        first: 0x2000,
+        advances: &[
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+2000
+            0, 0, 0, 0, 682, 682, 0, 1139, 2048, 2048, 0, 1131, // U+200C
+            569, 569, 569, 569, 1024, 1024, 1024, 0, 1139, 1139, 717, 0, // U+2018
+            0, 0, 2048, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+2024
+            2048, 0, 491, 981, 0, 0, 0, 0, 0, 682, 682, 0, // U+2030
+            1237, 0, 682, 0, 0, 0, 0, 0, 342, 0, 0, 0, // U+203C
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+2048
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+2054
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // U+2060
+            0, 0, 0, 0, // U+206C
+        ],
+    },
+];
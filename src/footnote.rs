@@ -0,0 +1,308 @@
+//! Footnotes: `[^label]` references and `[^label]: text` definitions.
+//!
+//! The syntax is GFM's. A definition may sit in any prose block of the
+//! document and continues over the following lines indented four spaces (or
+//! a tab). References are numbered per *document*, in the order renderers
+//! reach them, so the numbering does not depend on how prose is split into
+//! blocks. A reference with no definition stays literal text; a definition
+//! that is never referenced renders nothing.
+//!
+//! Under a note-class CSL style ([`crate::csl`]), inline `[@key]` citations
+//! become footnotes too, numbered in the same sequence: the citation
+//! renderers add the formatted note with [`Notes::add`] and emit a reference
+//! to it in place of the citation.
+//!
+//! The [`Notes`] for a document live in the ambient
+//! [`CiteContext`](crate::citation::CiteContext) that every renderer
+//! installs. HTML collects the notes into an endnote section, LaTeX and
+//! Typst emit native `\footnote{…}` / `#footnote[…]`, the terminal and
+//! markdown renderers append a numbered list, and the native renderer a
+//! `NativeBlock::Footnotes`.
+
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::citation::{cite_text_of, with_active};
+use crate::inline::find_footnote_refs;
+use crate::types::Block;
+
+/// A numbered footnote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Note {
+    /// Number in the document's footnote sequence, from 1.
+    pub number: usize,
+    /// Authored label (`1`, `long-note`); `@n` for a citation note.
+    pub label: String,
+    /// Note text as markdown.
+    pub body: String,
+}
+
+/// A document's footnote definitions plus the numbers assigned so far.
+#[derive(Debug, Clone, Default)]
+pub struct Notes {
+    definitions: BTreeMap<String, String>,
+    /// Bodies of citation notes; note `i` has label `@{i + 1}`.
+    generated: RefCell<Vec<String>>,
+    /// Notes in number order.
+    numbered: RefCell<Vec<Note>>,
+    /// Notes whose first reference has been rendered.
+    anchored: RefCell<BTreeSet<usize>>,
+    in_note: Cell<bool>,
+}
+
+impl Notes {
+    /// Collect every footnote definition in the document's prose blocks
+    /// (recursing into containers). The first definition of a label wins.
+    pub fn collect(blocks: &[Block]) -> Notes {
+        let mut notes = Notes::default();
+        collect_rec(blocks, &mut notes.definitions);
+        notes
+    }
+
+    /// Add a citation note, returning its label for a `[^label]` reference.
+    pub fn add(&self, body: String) -> String {
+        let mut generated = self.generated.borrow_mut();
+        generated.push(body);
+        format!("@{}", generated.len())
+    }
+
+    /// Resolve a reference: the note for `label`, numbered on its first
+    /// reference. `None` for an undefined label, and for any reference
+    /// inside a note body (notes do not nest).
+    pub fn reference(&self, label: &str) -> Option<Note> {
+        if self.in_note.get() {
+            return None;
+        }
+        if let Some(note) = self.numbered.borrow().iter().find(|n| n.label == label) {
+            return Some(note.clone());
+        }
+        let body = match label.strip_prefix('@') {
+            Some(i) => {
+                let i = i.parse::<usize>().ok()?.checked_sub(1)?;
+                self.generated.borrow().get(i)?.clone()
+            }
+            None => self.definitions.get(label)?.clone(),
+        };
+        let mut numbered = self.numbered.borrow_mut();
+        let note = Note {
+            number: numbered.len() + 1,
+            label: label.to_string(),
+            body,
+        };
+        numbered.push(note.clone());
+        Some(note)
+    }
+
+    /// Whether this is the first rendered reference to note `number`, for
+    /// renderers that give it a back-link anchor.
+    pub fn first_reference(&self, number: usize) -> bool {
+        self.anchored.borrow_mut().insert(number)
+    }
+
+    /// The notes referenced so far, in number order.
+    pub fn numbered(&self) -> Vec<Note> {
+        self.numbered.borrow().clone()
+    }
+
+    /// Run `f` while rendering a note body: references inside stay literal
+    /// and citations render in line rather than as further notes.
+    pub fn within_note<R>(&self, f: impl FnOnce() -> R) -> R {
+        let outer = self.in_note.replace(true);
+        let result = f();
+        self.in_note.set(outer);
+        result
+    }
+
+    /// A note body is being rendered.
+    pub fn in_note(&self) -> bool {
+        self.in_note.get()
+    }
+}
+
+fn collect_rec(blocks: &[Block], out: &mut BTreeMap<String, String>) {
+    for b in blocks {
+        if let Some(text) = cite_text_of(b) {
+            for (label, body) in split_definitions(text).1 {
+                out.entry(label).or_insert(body);
+            }
+        }
+        if let Some(children) = b.children() {
+            collect_rec(children, out);
+        }
+    }
+}
+
+/// The label of a definition line (`[^label]: …`, up to three spaces of
+/// indent) and the text after the colon.
+fn definition_start(line: &str) -> Option<(&str, &str)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    let rest = line[indent..].strip_prefix("[^").filter(|_| indent <= 3)?;
+    let (label, body) = rest.split_once("]:")?;
+    let valid = !label.is_empty()
+        && !label.starts_with('@')
+        && !label.contains(|c: char| c.is_whitespace() || c == '[' || c == ']');
+    valid.then(|| (label, body.trim()))
+}
+
+/// A continuation line of a definition, dedented.
+fn continuation(line: &str) -> Option<&str> {
+    line.strip_prefix("    ")
+        .or_else(|| line.strip_prefix('\t'))
+}
+
+/// Split prose into the text without footnote definitions and the
+/// `(label, body)` definitions it held, in source order. Fenced code blocks
+/// are left alone.
+pub fn split_definitions(text: &str) -> (String, Vec<(String, String)>) {
+    if !text.contains("[^") {
+        return (text.to_string(), Vec::new());
+    }
+    let lines: Vec<&str> = text.split('\n').collect();
+    let mut kept: Vec<&str> = Vec::with_capacity(lines.len());
+    let mut defs = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+        } else if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(&trimmed[..3]);
+        } else if let Some((label, first)) = definition_start(line) {
+            let mut body = first.to_string();
+            let mut j = i + 1;
+            loop {
+                // Blank lines continue the note only when an indented line
+                // follows them.
+                let blanks = lines[j.min(lines.len())..]
+                    .iter()
+                    .take_while(|l| l.trim().is_empty())
+                    .count();
+                let Some(next) = lines.get(j + blanks).and_then(|l| continuation(l)) else {
+                    break;
+                };
+                body.push_str(if blanks > 0 { "\n\n" } else { "\n" });
+                body.push_str(next);
+                j += blanks + 1;
+            }
+            defs.push((label.to_string(), body));
+            i = j;
+            continue;
+        }
+        kept.push(line);
+        i += 1;
+    }
+    (kept.join("\n"), defs)
+}
+
+/// Prose without its footnote definitions.
+pub fn strip_definitions(text: &str) -> String {
+    split_definitions(text).0
+}
+
+/// Replace `[^label]` references in `text` with `render(note)` against the
+/// ambient context's notes. Unresolved references stay as they are.
+pub fn substitute_refs(text: &str, render: impl Fn(&Note) -> String) -> String {
+    let refs = find_footnote_refs(text);
+    if refs.is_empty() {
+        return text.to_string();
+    }
+    with_active(|ctx| {
+        let Some(ctx) = ctx else {
+            return text.to_string();
+        };
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        for (s, e, label) in refs {
+            out.push_str(&text[last..s]);
+            match ctx.notes.reference(&label) {
+                Some(note) => out.push_str(&render(&note)),
+                None => out.push_str(&text[s..e]),
+            }
+            last = e;
+        }
+        out.push_str(&text[last..]);
+        out
+    })
+}
+
+/// Prose pipeline for the text renderers (markdown, terminal, native): drop
+/// footnote definitions, substitute inline citations (note-style ones become
+/// references), then render each reference with `render`.
+pub fn substitute_text_notes(text: &str, render: impl Fn(&Note) -> String) -> String {
+    let text = strip_definitions(text);
+    let text = crate::citation::substitute_text_cites(&text);
+    substitute_refs(&text, render)
+}
+
+/// The ambient document's notes referenced so far, each body passed through
+/// `render` (which runs inside [`Notes::within_note`]). Empty without an
+/// installed context.
+pub fn document_notes(render: impl Fn(&str) -> String) -> Vec<(usize, String)> {
+    with_active(|ctx| {
+        let Some(ctx) = ctx else {
+            return Vec::new();
+        };
+        ctx.notes
+            .numbered()
+            .into_iter()
+            .map(|n| (n.number, ctx.notes.within_note(|| render(&n.body))))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Span;
+
+    #[test]
+    fn split_definitions_with_continuations() {
+        let text = "Intro[^a].\n\n[^a]: First line\n    second line\n\n    second paragraph\n[^b]: Other.\nAfter.\n```\n[^c]: code\n```";
+        let (kept, defs) = split_definitions(text);
+        assert_eq!(kept, "Intro[^a].\n\nAfter.\n```\n[^c]: code\n```");
+        assert_eq!(
+            defs,
+            vec![
+                (
+                    "a".to_string(),
+                    "First line\nsecond line\n\nsecond paragraph".to_string()
+                ),
+                ("b".to_string(), "Other.".to_string()),
+            ]
+        );
+        // Four spaces of indent is code, not a definition.
+        assert!(split_definitions("    [^x]: no").1.is_empty());
+    }
+
+    #[test]
+    fn notes_number_in_reference_order() {
+        let blocks = vec![
+            Block::Markdown {
+                content: "[^b]: Bee.\n\n[^a]: Ay.".into(),
+                span: Span::SYNTHETIC,
+            },
+            Block::Markdown {
+                content: "[^zz]: Never cited.".into(),
+                span: Span::SYNTHETIC,
+            },
+        ];
+        let notes = Notes::collect(&blocks);
+        assert_eq!(notes.reference("a").map(|n| n.number), Some(1));
+        let cite = notes.add("Smith, *Title*.".into());
+        assert_eq!(cite, "@1");
+        assert_eq!(notes.reference("b").map(|n| n.number), Some(2));
+        assert_eq!(notes.reference("a").map(|n| n.number), Some(1));
+        assert_eq!(
+            notes.reference(&cite).map(|n| n.body),
+            Some("Smith, *Title*.".into())
+        );
+        assert_eq!(notes.reference("missing"), None);
+        assert_eq!(notes.within_note(|| notes.reference("zz")), None);
+        let labels: Vec<String> = notes.numbered().into_iter().map(|n| n.label).collect();
+        assert_eq!(labels, ["a", "b", "@1"]);
+    }
+}
@@ -0,0 +1,556 @@
+//! Canonical formatter (`surf-lint fmt`).
+//!
+//! [`format`] rewrites a document's layout without changing what it parses
+//! to. It walks the lossless [`Cst`] and re-emits:
+//!
+//! - directive colons from the actual nesting: two at top level and one more
+//!   than the parent for a nested container. A closer-less leaf keeps its
+//!   colons (`::cta` inside `::page` is the L005 convention; only two-colon
+//!   leaves resolve to blocks);
+//! - opener attributes in `spec/blocks.toml` order, unregistered keys after
+//!   them alphabetically; `true` flags as bare keys, string values bare when
+//!   they read back unchanged unquoted and double-quoted otherwise, numbers
+//!   and other literals as written;
+//! - exactly one blank line between top-level blocks and after the front
+//!   matter, none at the start, and one trailing newline;
+//! - `::data` pipe tables with padded cells, so the pipes line up.
+//!
+//! Markdown, front matter, `::code`/`::output` bodies, fenced code and every
+//! other body line are copied verbatim.
+//!
+//! Guarantees (pinned by `tests/format_properties.rs`):
+//!
+//! 1. **Idempotent** — `format(format(x)) == format(x)`.
+//! 2. **Meaning-preserving** — the parsed `SurfDoc` is unchanged apart from
+//!    spans and the verbatim source copies a layout change has to touch:
+//!    `Data::raw_content`, the raw `content` of blocks that also carry
+//!    parsed `children`, and blank lines around a markdown gap. This holds
+//!    by construction: every rewritten top-level block is re-parsed and kept
+//!    only when it is equivalent to the original and keeps its directive
+//!    nesting, otherwise it is kept as written; and a document whose
+//!    formatted whole still parses differently is returned as written.
+//!
+//! A document that exceeds its [`ParseOptions`] bounds (P007) is returned
+//! unchanged apart from CRLF normalisation.
+
+use serde_json::Value;
+
+use crate::attrs::parse_attrs_spanned;
+use crate::cst::{Cst, CstKind, CstNode};
+use crate::parse::{ParseOptions, parse_with};
+use crate::types::{AttrValue, Block, SurfDoc};
+
+/// Knobs for [`format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Pad `::data` pipe-table cells so the pipes line up.
+    pub align_tables: bool,
+    /// Parse bounds; a document that exceeds them is left as written.
+    pub parse_options: ParseOptions,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            align_tables: true,
+            parse_options: ParseOptions::default(),
+        }
+    }
+}
+
+/// Format `source` into canonical layout (see the module docs). The output
+/// is LF-only and ends in exactly one newline unless it is empty.
+pub fn format(source: &str, options: &FormatOptions) -> String {
+    let result = parse_with(source, &options.parse_options);
+    if result
+        .diagnostics
+        .iter()
+        .any(|d| d.code.as_deref() == Some("P007"))
+    {
+        return source.replace("\r\n", "\n");
+    }
+    let doc = &result.doc;
+    let formatter = Formatter {
+        source: &doc.source,
+        options,
+    };
+
+    let mut front_matter = None;
+    let mut items = Vec::new();
+    // Start offset and fence-line count of a top-level fence the parser cut
+    // into several nodes (strict `::name` lines split a markdown fence);
+    // the whole fenced region is kept verbatim.
+    let mut open_fence: Option<(usize, usize)> = None;
+    for node in &Cst::from_doc(doc).root().children {
+        let text = node.text(&doc.source);
+        if let Some((start, fences)) = open_fence {
+            let fences = fences + fence_lines(text);
+            if fences.is_multiple_of(2) {
+                items.push(trim_blank_lines(&doc.source[start..node.span.end_offset]).to_string());
+                open_fence = None;
+            } else {
+                open_fence = Some((start, fences));
+            }
+            continue;
+        }
+        match node.kind {
+            CstKind::FrontMatter => front_matter = Some(text.trim_end()),
+            CstKind::Directive => items.push(formatter.top_level(node)),
+            CstKind::OrphanCloser => items.push(text.trim().to_string()),
+            _ if !fence_lines(text).is_multiple_of(2) => {
+                open_fence = Some((node.span.start_offset, fence_lines(text)));
+            }
+            // Markdown, and trivia that is more than blank lines (bytes no
+            // block claimed): verbatim, minus surrounding blank lines.
+            _ => {
+                let trimmed = trim_blank_lines(text);
+                if !trimmed.is_empty() {
+                    items.push(trimmed.to_string());
+                }
+            }
+        }
+    }
+    if let Some((start, _)) = open_fence {
+        items.push(trim_blank_lines(&doc.source[start..]).to_string());
+    }
+
+    let mut out = front_matter.map(str::to_string).unwrap_or_default();
+    for item in items {
+        if !out.is_empty() {
+            out.push_str(if out.ends_with('\n') { "\n" } else { "\n\n" });
+        }
+        out.push_str(&item);
+    }
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    // Block boundaries can still move as a whole (an unclosed block at end
+    // of input takes the trailing blank lines into its content).
+    let reparsed = parse_with(&out, &options.parse_options).doc;
+    if serde_json::to_value(&reparsed.front_matter).ok()
+        != serde_json::to_value(&doc.front_matter).ok()
+        || !same_blocks(&reparsed.blocks, &doc.blocks)
+    {
+        return doc.source.clone();
+    }
+    out
+}
+
+/// Whether two block lists are equal modulo layout (see [`layout_free`]).
+fn same_blocks(a: &[Block], b: &[Block]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(x, y)| layout_free(x) == layout_free(y))
+}
+
+/// Number of code-fence lines (```` ``` ```` or `~~~`) in `text`, counted the
+/// way the parser toggles its fence state.
+fn fence_lines(text: &str) -> usize {
+    text.lines()
+        .filter(|line| {
+            let trimmed = line.trim();
+            trimmed.starts_with("```") || trimmed.starts_with("~~~")
+        })
+        .count()
+}
+
+/// `text` without leading whitespace-only lines and without trailing
+/// whitespace. Leading spaces on the first real line are kept (they can be
+/// an indented code block).
+fn trim_blank_lines(text: &str) -> &str {
+    let mut start = 0;
+    for line in text.split_inclusive('\n') {
+        if !line.trim().is_empty() || !line.ends_with('\n') {
+            break;
+        }
+        start += line.len();
+    }
+    text[start..].trim_end()
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    options: &'a FormatOptions,
+}
+
+impl Formatter<'_> {
+    /// A top-level directive: the canonical rewrite when it parses to the
+    /// same block, the original text otherwise.
+    fn top_level(&self, node: &CstNode) -> String {
+        let original = node.text(self.source);
+        let mut candidate = String::new();
+        self.directive(node, 2, &mut candidate);
+        let candidate = candidate.trim_end();
+        if candidate == original.trim_end() || self.equivalent(original, candidate) {
+            candidate.to_string()
+        } else {
+            original.trim_end().to_string()
+        }
+    }
+
+    /// Whether `candidate` parses to the same blocks as `original` and to
+    /// the same directive nesting, so that formatting it again recomputes
+    /// the same colon depths.
+    fn equivalent(&self, original: &str, candidate: &str) -> bool {
+        let a = parse_with(original, &self.options.parse_options).doc;
+        let b = parse_with(candidate, &self.options.parse_options).doc;
+        same_blocks(&a.blocks, &b.blocks) && shape(&a) == shape(&b)
+    }
+
+    fn directive(&self, node: &CstNode, depth: usize, out: &mut String) {
+        for child in &node.children {
+            match child.kind {
+                CstKind::Opener => self.opener(child, depth, out),
+                CstKind::Body => self.body(node, child, depth, out),
+                CstKind::Closer => out.push_str(&":".repeat(depth)),
+                _ => out.push_str(child.text(self.source)),
+            }
+        }
+    }
+
+    fn opener(&self, opener: &CstNode, depth: usize, out: &mut String) {
+        let Some(name) = opener.child(CstKind::Name) else {
+            out.push_str(opener.text(self.source));
+            return;
+        };
+        let name = name.text(self.source);
+        out.push_str(&":".repeat(depth));
+        out.push_str(name);
+        if let Some(list) = opener.child(CstKind::AttrList) {
+            let raw = list.text(self.source);
+            out.push_str(&canonical_attrs(name, raw).unwrap_or_else(|| raw.to_string()));
+        }
+        // Same-line trailing content (L004) stays, one space off the opener
+        // when it was separated at all.
+        if let Some(pos) = opener.children.iter().position(|c| c.kind == CstKind::Text) {
+            if pos > 0 && opener.children[pos - 1].kind == CstKind::Whitespace {
+                out.push(' ');
+            }
+            out.push_str(opener.children[pos].text(self.source));
+        }
+    }
+
+    fn body(&self, directive: &CstNode, body: &CstNode, depth: usize, out: &mut String) {
+        if self.options.align_tables
+            && directive.directive_name(self.source) == Some("data")
+            && is_table_data(directive, self.source)
+            && body.children.iter().all(|c| c.kind == CstKind::Text)
+        {
+            out.push_str(&align_table(body.text(self.source)));
+            return;
+        }
+        for child in &body.children {
+            match child.kind {
+                // A container nests one level deeper than its parent. A
+                // closer-less leaf keeps its colons: only a two-colon leaf
+                // resolves to a block, any other count is body text.
+                CstKind::Directive => {
+                    let depth = match child.child(CstKind::Closer) {
+                        Some(_) => depth + 1,
+                        None => child.directive_depth().unwrap_or(depth),
+                    };
+                    self.directive(child, depth, out);
+                }
+                _ => out.push_str(child.text(self.source)),
+            }
+        }
+    }
+}
+
+/// The directive nesting of `doc`: each directive's name with its body
+/// bracketed (`{}` when it owns a closer, `<>` for a leaf), and `.` for
+/// every run of non-blank text. Colon counts and attribute spelling are
+/// left out.
+fn shape(doc: &SurfDoc) -> String {
+    fn walk(node: &CstNode, source: &str, out: &mut String) {
+        match node.kind {
+            CstKind::Directive => {
+                let container = node.child(CstKind::Closer).is_some();
+                out.push_str(node.directive_name(source).unwrap_or_default());
+                out.push(if container { '{' } else { '<' });
+                if let Some(body) = node.child(CstKind::Body) {
+                    for child in &body.children {
+                        walk(child, source, out);
+                    }
+                }
+                out.push(if container { '}' } else { '>' });
+            }
+            _ if node.text(source).trim().is_empty() => {}
+            _ => out.push('.'),
+        }
+    }
+    let mut out = String::new();
+    for node in &Cst::from_doc(doc).root().children {
+        walk(node, &doc.source, &mut out);
+    }
+    out
+}
+
+/// `block` as JSON without spans and without the verbatim source copies a
+/// layout change has to touch: what [`format`] must preserve.
+fn layout_free(block: &Block) -> Value {
+    fn strip(value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                map.remove("span");
+                map.remove("raw_content");
+                if map.contains_key("children") {
+                    map.remove("content");
+                }
+                map.values_mut().for_each(strip);
+            }
+            Value::Array(items) => items.iter_mut().for_each(strip),
+            _ => {}
+        }
+    }
+    let mut value = serde_json::to_value(block).unwrap_or(Value::Null);
+    strip(&mut value);
+    // A markdown gap's content starts at the first line after the previous
+    // block, so it carries the blank lines the formatter normalises.
+    if let Block::Markdown { content, .. } = block {
+        value["content"] = Value::from(trim_blank_lines(content));
+    }
+    value
+}
+
+// ------------------------------------------------------------------
+// Attributes
+// ------------------------------------------------------------------
+
+/// Canonical spelling of an opener's `[...]` string; `None` when
+/// `parse_attrs` rejects it (left as written).
+fn canonical_attrs(name: &str, raw: &str) -> Option<String> {
+    let (attrs, spans) = parse_attrs_spanned(raw).ok()?;
+    if attrs.is_empty() {
+        return Some(String::new());
+    }
+    let spec = crate::lint::block_attribute_names(name).unwrap_or_default();
+    let mut keys: Vec<&String> = attrs.keys().collect();
+    // Stable: unregistered keys keep the map's alphabetical order.
+    keys.sort_by_key(|key| spec.iter().position(|a| a == *key).unwrap_or(usize::MAX));
+    let entries: Vec<String> = keys
+        .into_iter()
+        .map(|key| match &attrs[key] {
+            AttrValue::Bool(true) => key.clone(),
+            AttrValue::String(s) if reads_back_bare(s) => format!("{key}={s}"),
+            AttrValue::String(s) => {
+                format!("{key}=\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+            // Numbers, `false` and `null` were unquoted: keep the literal.
+            _ => match spans[key].value {
+                Some(span) => format!("{key}={}", &raw[span.start_offset..span.end_offset]),
+                None => key.clone(),
+            },
+        })
+        .collect();
+    Some(format!("[{}]", entries.join(" ")))
+}
+
+/// Whether `value` written unquoted parses back to the same string.
+fn reads_back_bare(value: &str) -> bool {
+    !value.is_empty()
+        && !value
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | '\\' | '[' | ']'))
+        && !matches!(value, "true" | "false" | "null")
+        && value.parse::<f64>().is_err()
+}
+
+// ------------------------------------------------------------------
+// `::data` tables
+// ------------------------------------------------------------------
+
+/// Whether a `::data` directive holds a pipe table: `parse_data` reads every
+/// `format=` other than `csv` and `json` (or none at all) as a table.
+fn is_table_data(directive: &CstNode, source: &str) -> bool {
+    let attrs = directive
+        .child(CstKind::Opener)
+        .and_then(|o| o.child(CstKind::AttrList))
+        .and_then(|list| crate::attrs::parse_attrs(list.text(source)).ok())
+        .unwrap_or_default();
+    !matches!(attrs.get("format"), Some(AttrValue::String(f)) if f == "csv" || f == "json")
+}
+
+/// One line of a pipe table.
+enum TableLine {
+    Blank,
+    /// `|---|:--:|` — per cell, whether it carries a left/right colon.
+    Separator(Vec<(bool, bool)>),
+    Row(Vec<String>),
+}
+
+/// Re-emit `text` (a `::data` body) with every row as `| a | b |`, cells
+/// padded to their column's width and separator dashes to match.
+fn align_table(text: &str) -> String {
+    let lines: Vec<TableLine> = text
+        .split('\n')
+        .map(|line| {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                TableLine::Blank
+            } else if crate::blocks::is_table_separator(trimmed) {
+                TableLine::Separator(
+                    trimmed
+                        .trim_matches('|')
+                        .split('|')
+                        .map(|cell| {
+                            let cell = cell.trim();
+                            (cell.starts_with(':'), cell.ends_with(':'))
+                        })
+                        .collect(),
+                )
+            } else {
+                TableLine::Row(crate::blocks::split_pipe_row(trimmed))
+            }
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = Vec::new();
+    for line in &lines {
+        if let TableLine::Row(cells) = line {
+            for (i, cell) in cells.iter().enumerate() {
+                let width = cell.chars().count().max(3);
+                match widths.get_mut(i) {
+                    Some(w) => *w = (*w).max(width),
+                    None => widths.push(width),
+                }
+            }
+        }
+    }
+    let width = |i: usize| widths.get(i).copied().unwrap_or(3);
+
+    let rendered: Vec<String> = lines
+        .iter()
+        .map(|line| match line {
+            TableLine::Blank => String::new(),
+            TableLine::Row(cells) => {
+                let cells: Vec<String> = cells
+                    .iter()
+                    .enumerate()
+                    .map(|(i, cell)| format!("{cell:<w$}", w = width(i)))
+                    .collect();
+                format!("| {} |", cells.join(" | "))
+            }
+            TableLine::Separator(cells) => {
+                let cells: Vec<String> = cells
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &(left, right))| {
+                        format!(
+                            "{}{}{}",
+                            if left { ':' } else { '-' },
+                            "-".repeat(width(i) - 2),
+                            if right { ':' } else { '-' }
+                        )
+                    })
+                    .collect();
+                format!("| {} |", cells.join(" | "))
+            }
+        })
+        .collect();
+    rendered.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmt(source: &str) -> String {
+        format(source, &FormatOptions::default())
+    }
+
+    #[test]
+    fn blank_lines_between_blocks_are_normalised() {
+        let src = "\n\n# Title\n::callout[type=info]\nHi\n::\n\n\n\nAfter.\n\n";
+        assert_eq!(
+            fmt(src),
+            "# Title\n\n::callout[type=info]\nHi\n::\n\nAfter.\n"
+        );
+    }
+
+    #[test]
+    fn front_matter_is_kept_and_followed_by_one_blank_line() {
+        let src = "---\ntitle: T\n---\n# Body\n";
+        assert_eq!(fmt(src), "---\ntitle: T\n---\n\n# Body\n");
+    }
+
+    #[test]
+    fn attributes_follow_spec_order_and_canonical_quoting() {
+        // blocks.toml registers callout as ["type", "title"].
+        let src =
+            "::callout[zeta=\"z\" title=\"Heads up\" type=\"warning\" flag=true n=\"42\"]\nX\n::\n";
+        assert_eq!(
+            fmt(src),
+            "::callout[type=warning title=\"Heads up\" flag n=\"42\" zeta=z]\nX\n::\n"
+        );
+    }
+
+    #[test]
+    fn numeric_and_escaped_values_keep_their_meaning() {
+        let src = "::metric[label=\"a \\\"b\\\" c\" value=1.50]\n";
+        let out = fmt(src);
+        assert_eq!(out, "::metric[label=\"a \\\"b\\\" c\" value=1.50]\n");
+    }
+
+    #[test]
+    fn colon_depth_follows_nesting() {
+        let src = "::app-shell[layout=sidebar]\n  ::sidebar[position=left]\n::nav-tree[source=files]\n::\n::\n::\n";
+        assert_eq!(
+            fmt(src),
+            "::app-shell[layout=sidebar]\n:::sidebar[position=left]\n::::nav-tree[source=files]\n::::\n:::\n::\n"
+        );
+    }
+
+    #[test]
+    fn over_deep_top_level_opener_is_reduced() {
+        assert_eq!(
+            fmt(":::callout[type=tip]\nHi\n:::\n"),
+            "::callout[type=tip]\nHi\n::\n"
+        );
+    }
+
+    #[test]
+    fn data_tables_are_aligned() {
+        let src = "::data[format=table]\n|Name|Age|\n|:---|--:|\n|Alice|30|\n|Bob|4|\n::\n";
+        assert_eq!(
+            fmt(src),
+            "::data[format=table]\n| Name  | Age |\n| :---- | --: |\n| Alice | 30  |\n| Bob   | 4   |\n::\n"
+        );
+    }
+
+    #[test]
+    fn csv_data_is_left_alone() {
+        let src = "::data[format=csv]\na,b\n1,2\n::\n";
+        assert_eq!(fmt(src), src);
+    }
+
+    #[test]
+    fn code_bodies_and_fences_are_untouched() {
+        let src = "::code[lang=rust]\nfn main() {\n    let x  =  1;\n\n\n}\n::\n\n```md\n:::weird[ b=1 ]\n|a|b|\n```\n";
+        let out = fmt(src);
+        assert_eq!(out, src);
+    }
+
+    #[test]
+    fn block_that_would_change_meaning_is_kept_as_written() {
+        // Re-quoting the page's nested opener would change the page's raw
+        // children parse; nothing here may change what the page parses to.
+        let src = "::page[route=/ title=\"Home\"]\n::cta[label=\"Go\" href=/x]\n::\n";
+        let out = fmt(src);
+        let before = parse_with(src, &ParseOptions::default()).doc.blocks;
+        let after = parse_with(&out, &ParseOptions::default()).doc.blocks;
+        assert_eq!(layout_free(&before[0]), layout_free(&after[0]));
+    }
+
+    #[test]
+    fn crlf_is_normalised() {
+        assert_eq!(fmt("# T\r\n\r\nBody\r\n"), "# T\n\nBody\n");
+    }
+
+    #[test]
+    fn empty_source_stays_empty() {
+        assert_eq!(fmt(""), "");
+        assert_eq!(fmt("\n\n"), "");
+    }
+}
@@ -0,0 +1,615 @@
+//! Document transclusion: `::include[src=path section=anchor]`, closed with
+//! `::` like any other directive (its body is ignored).
+//!
+//! [`crate::parse`] leaves an `::include` directive as a `Block::Unknown`.
+//! [`parse_with_includes`] parses a document and splices every include, at
+//! any container depth, with the blocks of the file a caller-supplied
+//! [`IncludeResolver`] returns. The resolver is the only I/O: wasm and FFI
+//! callers without a filesystem hand in their own.
+//!
+//! - `section=anchor` keeps only the markdown heading whose anchor (explicit
+//!   `{#slug}` or the slugified text) matches, through the next heading of
+//!   the same or a higher level. Headings inside directive bodies do not
+//!   count.
+//! - Included front matter is dropped; includes inside included files are
+//!   expanded relative to that file.
+//! - Every spliced span carries the [`FileId`] of the file it came from;
+//!   [`ParseResult::files`] names the ids.
+//!
+//! An include that cannot be spliced is dropped with a diagnostic instead of
+//! failing the parse: P008 (unresolved), P009 (cycle), or P007 (deeper than
+//! [`ParseOptions::max_include_depth`], past [`ParseOptions::max_includes`]
+//! includes in all, or splicing more blocks than
+//! [`ParseOptions::max_blocks`] allows across the whole expansion).
+
+use std::collections::BTreeMap;
+
+use crate::error::{Diagnostic, Severity};
+use crate::parse::{self, ParseOptions, ParseResult};
+use crate::types::{AttrValue, Attrs, Block, FileId, Span};
+
+/// Include depth used when [`ParseOptions::max_include_depth`] is `None`.
+pub const DEFAULT_MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Include count used when [`ParseOptions::max_includes`] is `None`.
+pub const DEFAULT_MAX_INCLUDES: usize = 256;
+
+/// A file returned by an [`IncludeResolver`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncludedFile {
+    /// Canonical name of the file. Cycle detection compares names, and
+    /// spliced spans report it through [`ParseResult::files`].
+    pub name: String,
+    /// The file's SurfDoc source.
+    pub source: String,
+}
+
+/// Loads the files `::include` directives name.
+pub trait IncludeResolver {
+    /// Load `src` (the attribute as written) for an include inside the file
+    /// named `from`. An `Err` message becomes a P008 diagnostic.
+    fn resolve(&self, src: &str, from: &str) -> Result<IncludedFile, String>;
+}
+
+impl<F> IncludeResolver for F
+where
+    F: Fn(&str, &str) -> Result<IncludedFile, String>,
+{
+    fn resolve(&self, src: &str, from: &str) -> Result<IncludedFile, String> {
+        self(src, from)
+    }
+}
+
+/// In-memory files keyed by name; `src` must match a key exactly.
+impl IncludeResolver for BTreeMap<String, String> {
+    fn resolve(&self, src: &str, _from: &str) -> Result<IncludedFile, String> {
+        let source = self.get(src).ok_or_else(|| "no such file".to_string())?;
+        Ok(IncludedFile {
+            name: src.to_string(),
+            source: source.clone(),
+        })
+    }
+}
+
+/// Reads includes from disk, resolving `src` against the directory of the
+/// including file. Names are canonical paths.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FsResolver;
+
+impl IncludeResolver for FsResolver {
+    fn resolve(&self, src: &str, from: &str) -> Result<IncludedFile, String> {
+        let base = std::path::Path::new(from)
+            .parent()
+            .unwrap_or(std::path::Path::new(""));
+        let path = base.join(src);
+        let source = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        let name = std::fs::canonicalize(&path).unwrap_or(path);
+        Ok(IncludedFile {
+            name: name.display().to_string(),
+            source,
+        })
+    }
+}
+
+/// [`parse::parse_with`] `input` (a file called `name`), then splice every
+/// `::include` through `resolver`. The result's `files[0]` is `name`.
+pub fn parse_with_includes(
+    input: &str,
+    name: &str,
+    options: &ParseOptions,
+    resolver: &dyn IncludeResolver,
+) -> ParseResult {
+    let mut result = parse::parse_with(input, options);
+    let mut expander = Expander {
+        resolver,
+        options,
+        max_depth: options
+            .max_include_depth
+            .unwrap_or(DEFAULT_MAX_INCLUDE_DEPTH),
+        max_includes: options.max_includes.unwrap_or(DEFAULT_MAX_INCLUDES),
+        includes: 0,
+        blocks: count_blocks(&result.doc.blocks),
+        files: vec![name.to_string()],
+        diagnostics: Vec::new(),
+    };
+    let blocks = std::mem::take(&mut result.doc.blocks);
+    let mut chain = vec![name.to_string()];
+    result.doc.blocks = expander.expand(blocks, &mut chain);
+    // Included files may supply the `::data` a chart binds to.
+    crate::chart::bind_chart_data(&mut result.doc.blocks);
+    result.diagnostics.extend(expander.diagnostics);
+    result.files = expander.files;
+    result
+}
+
+struct Expander<'a> {
+    resolver: &'a dyn IncludeResolver,
+    options: &'a ParseOptions,
+    max_depth: usize,
+    max_includes: usize,
+    /// Includes attempted so far, against `max_includes`.
+    includes: usize,
+    /// Blocks in the document so far (any depth), against
+    /// `options.max_blocks`.
+    blocks: usize,
+    files: Vec<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Expander<'_> {
+    /// Splice the includes in `blocks`, which belong to the last file in
+    /// `chain`.
+    fn expand(&mut self, blocks: Vec<Block>, chain: &mut Vec<String>) -> Vec<Block> {
+        let mut out = Vec::with_capacity(blocks.len());
+        for mut block in blocks {
+            if let Block::Unknown { name, .. } = &block
+                && name == "include"
+            {
+                out.extend(self.splice(&block, chain));
+                continue;
+            }
+            if let Some(children) = block.children_mut() {
+                *children = self.expand(std::mem::take(children), chain);
+            }
+            out.push(block);
+        }
+        out
+    }
+
+    fn splice(&mut self, include: &Block, chain: &mut Vec<String>) -> Vec<Block> {
+        let Block::Unknown { attrs, span, .. } = include else {
+            return Vec::new();
+        };
+        let span = *span;
+        let Some(src) = attr_text(attrs, "src") else {
+            self.report(
+                "P008",
+                unresolved_message("", "missing src attribute"),
+                span,
+            );
+            return Vec::new();
+        };
+        if chain.len() > self.max_depth {
+            let detail = format!(
+                "'{src}' is nested {} deep (limit {})",
+                chain.len(),
+                self.max_depth
+            );
+            self.report(
+                "P007",
+                parse::p007_message("max_include_depth", &detail),
+                span,
+            );
+            return Vec::new();
+        }
+        self.includes += 1;
+        if self.includes > self.max_includes {
+            let detail = format!(
+                "'{src}' is include #{} (limit {})",
+                self.includes, self.max_includes
+            );
+            self.report("P007", parse::p007_message("max_includes", &detail), span);
+            return Vec::new();
+        }
+        let from = chain.last().map_or("", String::as_str);
+        let file = match self.resolver.resolve(&src, from) {
+            Ok(file) => file,
+            Err(reason) => {
+                self.report("P008", unresolved_message(&src, &reason), span);
+                return Vec::new();
+            }
+        };
+        if chain.contains(&file.name) {
+            let mut cycle = chain.clone();
+            cycle.push(file.name);
+            self.report(
+                "P009",
+                format!("Include cycle: {}", cycle.join(" → ")),
+                span,
+            );
+            return Vec::new();
+        }
+
+        let id = self.file_id(&file.name);
+        let parsed = parse::parse_with(&file.source, self.options);
+        let mut blocks = parsed.doc.blocks;
+        if let Some(anchor) = attr_text(attrs, "section") {
+            match select_section(&parsed.doc.source, blocks, &anchor) {
+                Some(section) => blocks = section,
+                None => {
+                    let reason = format!("no section '{anchor}' in {}", file.name);
+                    self.report("P008", unresolved_message(&src, &reason), span);
+                    return Vec::new();
+                }
+            }
+        }
+
+        let count = count_blocks(&blocks);
+        if let Some(max) = self
+            .options
+            .max_blocks
+            .filter(|max| self.blocks + count > *max)
+        {
+            let detail = format!(
+                "'{src}' adds {count} blocks to {} (limit {max})",
+                self.blocks
+            );
+            self.report("P007", parse::p007_message("max_blocks", &detail), span);
+            return Vec::new();
+        }
+        self.blocks += count;
+
+        for block in &mut blocks {
+            stamp(block, id);
+        }
+        self.diagnostics
+            .extend(parsed.diagnostics.into_iter().map(|mut d| {
+                if let Some(span) = d.span.as_mut() {
+                    span.file = id;
+                }
+                // Fixes edit the root source; these spans point elsewhere.
+                d.fix = None;
+                d
+            }));
+
+        chain.push(file.name);
+        let blocks = self.expand(blocks, chain);
+        chain.pop();
+        blocks
+    }
+
+    /// The id of `name`, registering it on first use.
+    fn file_id(&mut self, name: &str) -> FileId {
+        let index = match self.files.iter().position(|f| f == name) {
+            Some(index) => index,
+            None => {
+                self.files.push(name.to_string());
+                self.files.len() - 1
+            }
+        };
+        FileId(index as u32)
+    }
+
+    fn report(&mut self, code: &str, message: String, span: Span) {
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            message,
+            span: Some(span),
+            code: Some(code.into()),
+            fix: None,
+        });
+    }
+}
+
+/// An attribute as text; unquoted values like `src=1` coerce to numbers.
+fn attr_text(attrs: &Attrs, key: &str) -> Option<String> {
+    match attrs.get(key)? {
+        AttrValue::String(s) => Some(s.clone()),
+        AttrValue::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Message for a P008 diagnostic.
+fn unresolved_message(src: &str, reason: &str) -> String {
+    format!("Unresolved include '{src}': {reason}")
+}
+
+/// Number of blocks in `blocks`, counting container children.
+fn count_blocks(blocks: &[Block]) -> usize {
+    blocks
+        .iter()
+        .map(|b| 1 + b.children().map_or(0, count_blocks))
+        .sum()
+}
+
+/// Point every span in `block` (children and list items included) at `file`.
+fn stamp(block: &mut Block, file: FileId) {
+    block.span_mut().file = file;
+    for span in block.inner_spans_mut() {
+        span.file = file;
+    }
+    let nested: Vec<&mut Block> = match block {
+        Block::SplitPane { left, right, .. } => left.iter_mut().chain(right).collect(),
+        _ => block
+            .children_mut()
+            .map(|c| c.iter_mut().collect())
+            .unwrap_or_default(),
+    };
+    for child in nested {
+        stamp(child, file);
+    }
+}
+
+/// The blocks of `source` under the top-level heading anchored `anchor`,
+/// through the next heading of the same or a higher level. Markdown blocks
+/// straddling either edge are cut to it. `None` when no heading matches.
+fn select_section(source: &str, blocks: Vec<Block>, anchor: &str) -> Option<Vec<Block>> {
+    let headings = markdown_headings(source, &blocks);
+    let (i, &(start, level, _)) = headings
+        .iter()
+        .enumerate()
+        .find(|(_, (_, _, slug))| slug == anchor)?;
+    let end = headings[i + 1..]
+        .iter()
+        .find(|(_, l, _)| *l <= level)
+        .map_or(source.len(), |(offset, _, _)| *offset);
+
+    let section = blocks
+        .into_iter()
+        .filter_map(|block| match block {
+            Block::Markdown { span, .. } => {
+                let raw = source.get(span.start_offset.max(start)..span.end_offset.min(end))?;
+                let text = raw.trim();
+                if text.is_empty() {
+                    return None;
+                }
+                let from = span.start_offset.max(start) + raw.len() - raw.trim_start().len();
+                let to = from + text.len();
+                Some(Block::Markdown {
+                    content: text.to_string(),
+                    span: Span {
+                        start_line: line_at(source, from),
+                        end_line: line_at(source, to - 1),
+                        start_offset: from,
+                        end_offset: to,
+                        file: span.file,
+                    },
+                })
+            }
+            other => {
+                let offset = other.span().start_offset;
+                (start <= offset && offset < end).then_some(other)
+            }
+        })
+        .collect();
+    Some(section)
+}
+
+/// `(offset, level, anchor)` of every ATX heading in the top-level markdown
+/// blocks, skipping fenced code.
+fn markdown_headings(source: &str, blocks: &[Block]) -> Vec<(usize, usize, String)> {
+    let mut headings = Vec::new();
+    for block in blocks {
+        let Block::Markdown { span, .. } = block else {
+            continue;
+        };
+        let Some(text) = source.get(span.start_offset..span.end_offset) else {
+            continue;
+        };
+        let mut offset = span.start_offset;
+        let mut in_fence = false;
+        for line in text.split_inclusive('\n') {
+            let trimmed = line.trim();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+            }
+            let level = trimmed.chars().take_while(|&c| c == '#').count();
+            if !in_fence && (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+                let title = trimmed[level..].trim();
+                let slug = match crate::render_html::split_explicit_anchor(title) {
+                    Some((_, slug)) => slug.to_string(),
+                    None => crate::render_html::slugify(title),
+                };
+                headings.push((offset, level, slug));
+            }
+            offset += line.len();
+        }
+    }
+    headings
+}
+
+/// 1-based line number of byte `offset`.
+fn line_at(source: &str, offset: usize) -> usize {
+    source[..offset].matches('\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(name, source)| (name.to_string(), source.to_string()))
+            .collect()
+    }
+
+    fn codes(result: &ParseResult) -> Vec<&str> {
+        result
+            .diagnostics
+            .iter()
+            .filter_map(|d| d.code.as_deref())
+            .collect()
+    }
+
+    #[test]
+    fn include_splices_blocks_with_provenance() {
+        let resolver = files(&[("part.surf", "::callout[type=info]\nShared\n::\n")]);
+        let input = "# Top\n\n::include[src=part.surf]\n::\n\nTail.\n";
+        let result = parse_with_includes(input, "main.surf", &ParseOptions::default(), &resolver);
+
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+        assert_eq!(result.doc.blocks.len(), 3);
+        let callout = &result.doc.blocks[1];
+        assert!(matches!(callout, Block::Callout { .. }), "{callout:?}");
+        let span = callout.span();
+        assert_eq!(result.file_name(span.file), Some("part.surf"));
+        assert_eq!((span.start_line, span.start_offset), (1, 0));
+        assert_eq!(result.doc.blocks[2].span().file, FileId::ROOT);
+        assert_eq!(result.file_name(FileId::ROOT), Some("main.surf"));
+    }
+
+    #[test]
+    fn include_inside_container_and_nested_files() {
+        let resolver = files(&[
+            ("a.surf", "A.\n\n::include[src=b.surf]\n::\n"),
+            ("b.surf", "::tasks\n- [ ] B\n::\n"),
+        ]);
+        let input = "::section\n:::include[src=a.surf]\n:::\n::\n";
+        let result = parse_with_includes(input, "main.surf", &ParseOptions::default(), &resolver);
+
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+        let Block::Section { children, .. } = &result.doc.blocks[0] else {
+            panic!("Expected Section, got {:?}", result.doc.blocks[0]);
+        };
+        assert_eq!(children.len(), 2);
+        assert_eq!(result.file_name(children[0].span().file), Some("a.surf"));
+        let Block::Tasks { items, span } = &children[1] else {
+            panic!("Expected Tasks, got {:?}", children[1]);
+        };
+        assert_eq!(result.file_name(span.file), Some("b.surf"));
+        assert_eq!(items[0].span.map(|s| s.file), Some(span.file));
+    }
+
+    #[test]
+    fn include_section_selects_heading_range() {
+        let part = "# Guide\n\nIntro.\n\n## Setup {#install}\n\nRun it.\n\n::callout[type=tip]\nHint\n::\n\n### Detail\n\nMore.\n\n## Usage\n\nLater.\n";
+        let resolver = files(&[("guide.surf", part)]);
+        let input = "::include[src=guide.surf section=install]\n::\n";
+        let result = parse_with_includes(input, "main.surf", &ParseOptions::default(), &resolver);
+
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+        let blocks = &result.doc.blocks;
+        assert_eq!(blocks.len(), 3, "{blocks:?}");
+        let Block::Markdown { content, span } = &blocks[0] else {
+            panic!("Expected Markdown, got {:?}", blocks[0]);
+        };
+        assert_eq!(content, "## Setup {#install}\n\nRun it.");
+        assert_eq!(&part[span.start_offset..span.end_offset], content);
+        assert_eq!(span.start_line, 5);
+        assert!(matches!(blocks[1], Block::Callout { .. }));
+        let Block::Markdown { content, .. } = &blocks[2] else {
+            panic!("Expected Markdown, got {:?}", blocks[2]);
+        };
+        assert_eq!(content, "### Detail\n\nMore.");
+    }
+
+    #[test]
+    fn unresolved_include_is_dropped_with_p008() {
+        let resolver = files(&[("guide.surf", "# Guide\n")]);
+        let input = "::include[src=missing.surf]\n::\n\n::include[src=guide.surf section=nope]\n::\n\n::include\n::\n\nBody.\n";
+        let result = parse_with_includes(input, "main.surf", &ParseOptions::default(), &resolver);
+
+        assert_eq!(codes(&result), ["P008", "P008", "P008"]);
+        assert_eq!(result.diagnostics[0].span.unwrap().start_line, 1);
+        assert!(result.diagnostics[0].message.contains("missing.surf"));
+        assert_eq!(result.doc.blocks.len(), 1);
+        assert!(result.doc.to_html_fragment().contains("Body."));
+    }
+
+    #[test]
+    fn include_cycle_reports_p009() {
+        let resolver = files(&[
+            ("a.surf", "A.\n\n::include[src=b.surf]\n::\n"),
+            ("b.surf", "B.\n\n::include[src=a.surf]\n::\n"),
+        ]);
+        let result = parse_with_includes(
+            "::include[src=a.surf]\n::\n",
+            "main.surf",
+            &ParseOptions::default(),
+            &resolver,
+        );
+
+        assert_eq!(codes(&result), ["P009"]);
+        let diagnostic = &result.diagnostics[0];
+        assert_eq!(
+            diagnostic.message,
+            "Include cycle: main.surf → a.surf → b.surf → a.surf"
+        );
+        assert_eq!(
+            result.file_name(diagnostic.span.unwrap().file),
+            Some("b.surf")
+        );
+        assert_eq!(result.doc.blocks.len(), 2);
+    }
+
+    #[test]
+    fn include_depth_is_bounded() {
+        // Every level names a fresh file, so only the depth limit stops it.
+        let resolver = |src: &str, _from: &str| -> Result<IncludedFile, String> {
+            let n: usize = src.parse().map_err(|_| "bad name".to_string())?;
+            Ok(IncludedFile {
+                name: src.to_string(),
+                source: format!("Level {n}.\n\n::include[src={}]\n::\n", n + 1),
+            })
+        };
+        let options = ParseOptions {
+            max_include_depth: Some(3),
+            ..ParseOptions::default()
+        };
+        let result =
+            parse_with_includes("::include[src=1]\n::\n", "main.surf", &options, &resolver);
+
+        assert_eq!(codes(&result), ["P007"]);
+        assert!(result.diagnostics[0].message.contains("max_include_depth"));
+        assert_eq!(result.doc.blocks.len(), 3);
+        assert_eq!(result.files, ["main.surf", "1", "2", "3"]);
+    }
+
+    /// Every file includes the next level four times: 4^depth splices
+    /// without a global budget.
+    fn fan_out(src: &str, _from: &str) -> Result<IncludedFile, String> {
+        let n: usize = src.parse().map_err(|_| "bad name".to_string())?;
+        let include = format!("::include[src={}]\n::\n\n", n + 1);
+        Ok(IncludedFile {
+            name: src.to_string(),
+            source: format!("Level {n}.\n\n{}", include.repeat(4)),
+        })
+    }
+
+    #[test]
+    fn spliced_blocks_count_against_max_blocks() {
+        let options = ParseOptions {
+            max_blocks: Some(100),
+            max_include_depth: Some(10),
+            ..ParseOptions::default()
+        };
+        let result = parse_with_includes("::include[src=1]\n::\n", "main.surf", &options, &fan_out);
+
+        assert!(count_blocks(&result.doc.blocks) <= 100);
+        assert!(codes(&result).iter().all(|c| *c == "P007"));
+        assert!(result.diagnostics.len() < 100, "{:?}", result.diagnostics);
+        assert!(
+            result
+                .diagnostics
+                .iter()
+                .any(|d| d.message.contains("max_blocks"))
+        );
+    }
+
+    #[test]
+    fn include_count_is_bounded() {
+        let options = ParseOptions {
+            max_includes: Some(5),
+            max_include_depth: Some(10),
+            ..ParseOptions::default()
+        };
+        let result = parse_with_includes("::include[src=1]\n::\n", "main.surf", &options, &fan_out);
+
+        // Five splices of one markdown block each; the next attempt is P007.
+        assert_eq!(result.doc.blocks.len(), 5);
+        assert!(!result.diagnostics.is_empty());
+        assert!(codes(&result).iter().all(|c| *c == "P007"));
+        assert!(result.diagnostics[0].message.contains("max_includes"));
+    }
+
+    #[test]
+    fn included_diagnostics_point_into_their_file() {
+        let resolver = files(&[("part.surf", "::callout[type=info]\nNever closed\n")]);
+        let result = parse_with_includes(
+            "::include[src=part.surf]\n::\n",
+            "main.surf",
+            &ParseOptions::default(),
+            &resolver,
+        );
+
+        assert_eq!(codes(&result), ["P001"]);
+        let diagnostic = &result.diagnostics[0];
+        assert_eq!(
+            result.file_name(diagnostic.span.unwrap().file),
+            Some("part.surf")
+        );
+        assert!(diagnostic.fix.is_none());
+    }
+}
@@ -0,0 +1,527 @@
+//! Incremental reparsing for editor buffers.
+//!
+//! [`parse`](crate::parse::parse) re-splits and rescans the whole source on
+//! every call. An editor reparsing on each keystroke changes a few bytes at a
+//! time, so [`reparse_edit`] takes the previous [`ParseResult`] plus a
+//! byte-range [`TextEdit`] and reuses every top-level block the edit cannot
+//! affect:
+//!
+//! - **Prefix** — blocks ending on a line before the edit are kept as-is,
+//!   after re-checking each directive's leaf/container look-ahead (that
+//!   look-ahead reads towards EOF, so a later edit can flip it).
+//! - **Rescan** — the block scanner resumes on the line after the prefix.
+//! - **Suffix** — once the scanner reaches a top-level opener that started an
+//!   old block after the edit, in the same code-fence state, the remaining old
+//!   blocks and their diagnostics are spliced back with shifted [`Span`]s.
+//!
+//! The result is always identical to a full parse of the edited text (pinned
+//! by a property test in `tests/properties.rs`). Edits the reparser cannot
+//! reason about locally — anything touching front matter, or introducing a
+//! `\r` that CRLF normalisation could pair up — fall back to a full parse.
+
+use std::collections::BTreeMap;
+
+use crate::error::{Diagnostic, TextEdit};
+use crate::parse::{self, ParseOptions, ParseResult};
+use crate::types::{Block, Span, SurfDoc};
+
+/// How much of the previous tree the last [`IncrementalParser::edit`] reused.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReuseStats {
+    /// Leading top-level blocks kept unchanged.
+    pub prefix: usize,
+    /// Trailing top-level blocks kept with shifted spans.
+    pub suffix: usize,
+    /// Top-level blocks produced by rescanning the edited region.
+    pub rescanned: usize,
+    /// The edit could not be handled locally and the whole text was reparsed.
+    pub full: bool,
+}
+
+/// A parse tree kept in sync with an editor buffer through byte-range edits.
+///
+/// ```
+/// use surf_parse::incremental::IncrementalParser;
+/// use surf_parse::{Span, TextEdit};
+///
+/// let mut p = IncrementalParser::new("::callout[type=info]\nHi\n::\n\nText.\n");
+/// // Replace "Hi" (bytes 21..23) with "Hello".
+/// let edit = TextEdit {
+///     span: Span::new(2, 2, 21, 23),
+///     replacement: "Hello".into(),
+/// };
+/// let result = p.edit(&edit);
+/// assert_eq!(result.doc.source, "::callout[type=info]\nHello\n::\n\nText.\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct IncrementalParser {
+    result: ParseResult,
+    stats: ReuseStats,
+}
+
+impl IncrementalParser {
+    /// Parse `input` in full and start tracking it.
+    pub fn new(input: &str) -> Self {
+        Self::from_result(parse::parse(input))
+    }
+
+    /// Start tracking an existing parse result (e.g. one produced by
+    /// [`parse`](crate::parse::parse) elsewhere).
+    pub fn from_result(result: ParseResult) -> Self {
+        Self {
+            result,
+            stats: ReuseStats::default(),
+        }
+    }
+
+    /// The current parse result.
+    pub fn result(&self) -> &ParseResult {
+        &self.result
+    }
+
+    /// The current (CRLF-normalised) source text.
+    pub fn source(&self) -> &str {
+        &self.result.doc.source
+    }
+
+    /// Consume the parser, returning the current parse result.
+    pub fn into_result(self) -> ParseResult {
+        self.result
+    }
+
+    /// Reuse statistics for the most recent [`Self::edit`].
+    pub fn last_reuse(&self) -> ReuseStats {
+        self.stats
+    }
+
+    /// Apply `edit` (byte offsets into [`Self::source`]) and reparse
+    /// incrementally. Offsets past the end are clamped and offsets inside a
+    /// UTF-8 character snap back to its start, so this never panics.
+    pub fn edit(&mut self, edit: &TextEdit) -> &ParseResult {
+        let previous = std::mem::replace(&mut self.result, empty_result());
+        let (result, stats) = reparse(previous, edit);
+        self.result = result;
+        self.stats = stats;
+        &self.result
+    }
+}
+
+/// Reparse `previous` after applying `edit` to its source, reusing the
+/// top-level blocks the edit cannot affect. Equivalent to calling
+/// [`parse`](crate::parse::parse) on the edited text.
+pub fn reparse_edit(previous: &ParseResult, edit: &TextEdit) -> ParseResult {
+    reparse(previous.clone(), edit).0
+}
+
+fn empty_result() -> ParseResult {
+    ParseResult {
+        doc: SurfDoc {
+            front_matter: None,
+            blocks: Vec::new(),
+            source: String::new(),
+        },
+        diagnostics: Vec::new(),
+        files: Vec::new(),
+    }
+}
+
+fn reparse(previous: ParseResult, edit: &TextEdit) -> (ParseResult, ReuseStats) {
+    let ParseResult {
+        doc,
+        diagnostics: old_diagnostics,
+        ..
+    } = previous;
+    let old = doc.source.as_str();
+    let (start, end) = clamp_edit(old, &edit.span);
+
+    let mut source = String::with_capacity(old.len() - (end - start) + edit.replacement.len());
+    source.push_str(&old[..start]);
+    source.push_str(&edit.replacement);
+    source.push_str(&old[end..]);
+
+    let full = |source: &str| {
+        let stats = ReuseStats {
+            full: true,
+            ..ReuseStats::default()
+        };
+        (parse::parse(source), stats)
+    };
+
+    // `parse` normalises CRLF over the whole text; a `\r` at the seam could
+    // pair with a `\n` on the other side and change bytes outside the edit.
+    if edit.replacement.contains('\r') || old[..start].ends_with('\r') {
+        return full(&source);
+    }
+
+    let old_lines: Vec<&str> = old.split('\n').collect();
+    let lines: Vec<&str> = source.split('\n').collect();
+    let edit_line = old[..start].matches('\n').count();
+    let Some(body_start) = unchanged_body_start(&old_lines, edit_line) else {
+        return full(&source);
+    };
+
+    let old_starts = line_starts(&old_lines);
+    let byte_delta = edit.replacement.len() as isize - (end - start) as isize;
+    let line_delta = edit.replacement.matches('\n').count() as isize
+        - old[start..end].matches('\n').count() as isize;
+
+    // Fence parity before each old line, as the scanner carries it.
+    let mut fence_before = Vec::with_capacity(old_lines.len() + 1);
+    let mut in_fence = false;
+    for (idx, line) in old_lines.iter().enumerate() {
+        fence_before.push(in_fence);
+        if idx >= body_start && is_fence_line(line) {
+            in_fence = !in_fence;
+        }
+    }
+    fence_before.push(in_fence);
+
+    let mut old_blocks = doc.blocks;
+
+    // ---------------------------------------------------------------
+    // Prefix: blocks whose last line precedes the edited line.
+    // ---------------------------------------------------------------
+    let mut keep = old_blocks
+        .iter()
+        .take_while(|b| b.span().end_line <= edit_line)
+        .count();
+    if let Some(i) = old_blocks[..keep]
+        .iter()
+        .position(|b| !lookahead_agrees(&lines, b))
+    {
+        keep = i;
+    }
+    // A trailing markdown gap may run on into the edited lines.
+    while keep > 0 && matches!(old_blocks[keep - 1], Block::Markdown { .. }) {
+        keep -= 1;
+    }
+    let resume = match keep.checked_sub(1).map(|i| old_blocks[i].span()) {
+        None => body_start,
+        // A `::page` force-closed by the next `::page` ends at the START of
+        // that opener's line; the scan resumes on the opener itself.
+        Some(span)
+            if span.end_line > span.start_line
+                && span.end_offset == old_starts[span.end_line - 1] =>
+        {
+            span.end_line - 1
+        }
+        Some(span) => span.end_line,
+    };
+    let resume_offset = old_starts.get(resume).copied().unwrap_or(old.len());
+
+    // ---------------------------------------------------------------
+    // Suffix candidates: old directive blocks wholly after the edit whose
+    // opener still starts a line, keyed by their line in the new text.
+    // ---------------------------------------------------------------
+    let mut candidates: BTreeMap<usize, usize> = BTreeMap::new();
+    for (j, block) in old_blocks.iter().enumerate().skip(keep) {
+        let span = block.span();
+        if matches!(block, Block::Markdown { .. }) || span.start_offset < end {
+            continue;
+        }
+        let new_offset = span.start_offset.saturating_add_signed(byte_delta);
+        if new_offset != 0 && source.as_bytes().get(new_offset - 1) != Some(&b'\n') {
+            continue;
+        }
+        let new_line = (span.start_line - 1).saturating_add_signed(line_delta);
+        candidates.insert(new_line, j);
+    }
+
+    // ---------------------------------------------------------------
+    // Rescan from `resume` until the scan state re-joins the old tree.
+    // ---------------------------------------------------------------
+    let mut rescan_diagnostics = Vec::new();
+    let mut synced: Option<usize> = None;
+    let (scanned, _) = parse::scan_blocks_from(
+        &lines,
+        resume,
+        fence_before[resume.min(old_lines.len())],
+        &source,
+        &ParseOptions::default(),
+        &mut rescan_diagnostics,
+        &mut |idx, fence| match candidates.get(&idx) {
+            Some(&j) if fence_before[old_blocks[j].span().start_line - 1] == fence => {
+                synced = Some(j);
+                true
+            }
+            _ => false,
+        },
+    );
+    let scanned = parse::resolve_blocks(scanned, &source);
+
+    let suffix_offset = synced.map(|j| old_blocks[j].span().start_offset);
+    let mut diagnostics = Vec::with_capacity(old_diagnostics.len());
+    let mut suffix_diagnostics = Vec::new();
+    for d in old_diagnostics {
+        let offset = d.span.map_or(0, |s| s.start_offset);
+        if offset < resume_offset {
+            diagnostics.push(d);
+        } else if suffix_offset.is_some_and(|s| offset >= s) {
+            suffix_diagnostics.push(d);
+        }
+    }
+    diagnostics.append(&mut rescan_diagnostics);
+    for d in suffix_diagnostics {
+        diagnostics.push(shift_diagnostic(d, byte_delta, line_delta, &lines));
+    }
+
+    let suffix: Vec<Block> = match synced {
+        Some(j) => old_blocks
+            .drain(j..)
+            .map(|mut b| {
+                shift_block(&mut b, byte_delta, line_delta);
+                b
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+    old_blocks.truncate(keep);
+
+    let stats = ReuseStats {
+        prefix: keep,
+        suffix: suffix.len(),
+        rescanned: scanned.len(),
+        full: false,
+    };
+    let mut blocks = old_blocks;
+    blocks.extend(scanned);
+    blocks.extend(suffix);
+    // Reused charts may reference a `::data` block the edit changed.
+    crate::chart::bind_chart_data(&mut blocks);
+
+    let result = ParseResult {
+        doc: SurfDoc {
+            front_matter: doc.front_matter,
+            blocks,
+            source,
+        },
+        diagnostics,
+        files: Vec::new(),
+    };
+    (result, stats)
+}
+
+/// Clamp `span` to `source` and snap both ends onto character boundaries.
+fn clamp_edit(source: &str, span: &Span) -> (usize, usize) {
+    let mut end = span.end_offset.min(source.len());
+    let mut start = span.start_offset.min(end);
+    while !source.is_char_boundary(start) {
+        start -= 1;
+    }
+    while !source.is_char_boundary(end) {
+        end -= 1;
+    }
+    (start, end.max(start))
+}
+
+/// First body line when the edit on `edit_line` cannot change the front
+/// matter, `None` when it might (or the front matter is unterminated, where
+/// any later `---` would close it).
+fn unchanged_body_start(old_lines: &[&str], edit_line: usize) -> Option<usize> {
+    if old_lines[0].trim() != "---" {
+        return (edit_line >= 1).then_some(0);
+    }
+    let close = old_lines.iter().skip(1).position(|l| l.trim() == "---")? + 1;
+    (edit_line > close).then_some(close + 1)
+}
+
+fn line_starts(lines: &[&str]) -> Vec<usize> {
+    let mut starts = Vec::with_capacity(lines.len());
+    let mut offset = 0;
+    for line in lines {
+        starts.push(offset);
+        offset += line.len() + 1;
+    }
+    starts
+}
+
+fn is_fence_line(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.starts_with("```") || trimmed.starts_with("~~~")
+}
+
+/// Whether a prefix block's leaf/container classification still holds in the
+/// edited text. Top-level openers look ahead for their closer (see
+/// `parse::is_leaf_before_sibling`), so a later edit can turn a leaf into a
+/// container or vice versa; `::page`/`::footer` never take that path.
+fn lookahead_agrees(lines: &[&str], block: &Block) -> bool {
+    if matches!(block, Block::Markdown { .. }) {
+        return true;
+    }
+    let span = block.span();
+    let idx = span.start_line - 1;
+    let Some((depth, name, _)) = parse::opening_directive(lines[idx].trim()) else {
+        return false;
+    };
+    if name == "page" || name == "footer" {
+        return true;
+    }
+    let was_leaf = span.start_line == span.end_line;
+    parse::is_leaf_before_sibling(lines, idx + 1, depth) == was_leaf
+}
+
+/// Shift `block`'s span and every span inside it.
+fn shift_block(block: &mut Block, bytes: isize, lines: isize) {
+    shift_span(block.span_mut(), bytes, lines);
+    shift_inner_spans(block, bytes, lines);
+}
+
+/// Shift the attribute and list-item spans of `block` and of every block
+/// nested in it. Nested blocks only have a placeholder span of their own.
+fn shift_inner_spans(block: &mut Block, bytes: isize, lines: isize) {
+    for span in block.inner_spans_mut() {
+        shift_span(span, bytes, lines);
+    }
+    let nested: Vec<&mut Block> = match block {
+        Block::SplitPane { left, right, .. } => left.iter_mut().chain(right).collect(),
+        _ => block
+            .children_mut()
+            .map(|c| c.iter_mut().collect())
+            .unwrap_or_default(),
+    };
+    for child in nested {
+        shift_inner_spans(child, bytes, lines);
+    }
+}
+
+fn shift_span(span: &mut Span, bytes: isize, lines: isize) {
+    span.start_offset = span.start_offset.saturating_add_signed(bytes);
+    span.end_offset = span.end_offset.saturating_add_signed(bytes);
+    span.start_line = span.start_line.saturating_add_signed(lines);
+    span.end_line = span.end_line.saturating_add_signed(lines);
+}
+
+/// Shift a reused diagnostic, re-rendering messages that quote a line number.
+fn shift_diagnostic(
+    mut d: Diagnostic,
+    bytes: isize,
+    lines: isize,
+    new_lines: &[&str],
+) -> Diagnostic {
+    let Some(span) = d.span.as_mut() else {
+        return d;
+    };
+    shift_span(span, bytes, lines);
+    if lines != 0 {
+        match d.code.as_deref() {
+            Some("P001") => {
+                if let Some((_, name, _)) = new_lines
+                    .get(span.start_line - 1)
+                    .and_then(|l| parse::opening_directive(l.trim()))
+                {
+                    d.message = parse::p001_message(&name, span.start_line);
+                }
+            }
+            Some("P006") => d.message = parse::p006_message(span.start_line),
+            _ => {}
+        }
+    }
+    d
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FileId;
+
+    fn edit(start: usize, end: usize, replacement: &str) -> TextEdit {
+        TextEdit {
+            span: Span {
+                start_line: 0,
+                end_line: 0,
+                start_offset: start,
+                end_offset: end,
+                file: FileId::ROOT,
+            },
+            replacement: replacement.into(),
+        }
+    }
+
+    fn assert_matches_full(p: &IncrementalParser) {
+        let full = parse::parse(p.source());
+        assert_eq!(
+            serde_json::to_value(&p.result().doc).unwrap(),
+            serde_json::to_value(&full.doc).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_value(&p.result().diagnostics).unwrap(),
+            serde_json::to_value(&full.diagnostics).unwrap()
+        );
+    }
+
+    #[test]
+    fn edit_inside_middle_block_reuses_neighbours() {
+        let src = "::callout[type=info]\nA\n::\n\n::callout[type=tip]\nB\n::\n\n::metric[label=x value=1]\n\n::summary\nS\n::\n";
+        let mut p = IncrementalParser::new(src);
+        let at = src.find("B\n").unwrap();
+        p.edit(&edit(at, at + 1, "Bee\nmore"));
+        assert_matches_full(&p);
+        let stats = p.last_reuse();
+        assert!(!stats.full);
+        assert_eq!(stats.prefix, 1);
+        assert_eq!(stats.rescanned, 1);
+        assert_eq!(stats.suffix, 2);
+        match &p.result().doc.blocks[3] {
+            Block::Summary { span, .. } => assert_eq!(span.start_line, 12),
+            other => panic!("expected Summary, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn reused_blocks_shift_their_item_spans() {
+        let src = "::callout[type=info]\nA\n::\n\n::tasks\n- [ ] One\n::\n";
+        let mut p = IncrementalParser::new(src);
+        p.edit(&edit(21, 22, "Longer\ntext"));
+        assert_matches_full(&p);
+        assert_eq!(p.last_reuse().suffix, 1);
+        match &p.result().doc.blocks[1] {
+            Block::Tasks { items, .. } => assert_eq!(items[0].span.unwrap().start_line, 7),
+            other => panic!("expected Tasks, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn edit_that_adds_a_closer_reclassifies_an_earlier_leaf() {
+        // `::metric` is a leaf until a surplus `::` closer appears later.
+        let src = "::metric[label=a value=1]\nText\n\nMore\n";
+        let mut p = IncrementalParser::new(src);
+        p.edit(&edit(src.len(), src.len(), "::\n"));
+        assert_matches_full(&p);
+        assert_eq!(p.last_reuse().prefix, 0);
+    }
+
+    #[test]
+    fn front_matter_edit_falls_back_to_full_parse() {
+        let src = "---\ntitle: A\n---\n# Hi\n";
+        let mut p = IncrementalParser::new(src);
+        p.edit(&edit(11, 12, "B"));
+        assert!(p.last_reuse().full);
+        assert_eq!(
+            p.result()
+                .doc
+                .front_matter
+                .as_ref()
+                .unwrap()
+                .title
+                .as_deref(),
+            Some("B")
+        );
+    }
+
+    #[test]
+    fn shifted_p001_message_names_new_line() {
+        let src = "Intro\n\n::callout[type=info]\nunclosed\n";
+        let mut p = IncrementalParser::new(src);
+        p.edit(&edit(0, 0, "# Title\n\n"));
+        assert_matches_full(&p);
+        assert!(p.result().diagnostics[0].message.ends_with("line 5"));
+    }
+
+    #[test]
+    fn out_of_range_edit_is_clamped() {
+        let mut p = IncrementalParser::new("héllo\n");
+        p.edit(&edit(2, 99, "!"));
+        assert_eq!(p.source(), "h!");
+        assert_matches_full(&p);
+    }
+}
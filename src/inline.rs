@@ -39,6 +39,62 @@ pub fn find_inline_cites(text: &str) -> Vec<(usize, usize, CiteRef)> {
     out
 }
 
+/// Scan `text` for footnote references: `[^label]`.
+///
+/// Returns `(start_byte, end_byte, label)` tuples in document order. Labels
+/// are non-empty and contain no whitespace or brackets. A reference directly
+/// followed by `:` is a definition (`[^label]: text`) and is skipped.
+pub fn find_footnote_refs(text: &str) -> Vec<(usize, usize, String)> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    while let Some(rel) = text[pos..].find("[^") {
+        let start = pos + rel;
+        let label_start = start + 2;
+        let label_len = text[label_start..]
+            .find(|c: char| c.is_whitespace() || c == '[' || c == ']')
+            .unwrap_or(text.len() - label_start);
+        let end = label_start + label_len + 1;
+        if label_len > 0
+            && text[label_start + label_len..].starts_with(']')
+            && !text[end..].starts_with(':')
+        {
+            out.push((start, end, text[label_start..end - 1].to_string()));
+            pos = end;
+        } else {
+            pos = label_start;
+        }
+    }
+    out
+}
+
+/// Scan `text` for cross-references: `[#label]`.
+///
+/// Returns `(start_byte, end_byte, label)` tuples in document order. Labels
+/// are runs of `[A-Za-z0-9_:.-]`, like citation keys. A bracket followed by
+/// `(` or `[` is link text (`[#tag](url)`) and is skipped.
+pub fn find_xrefs(text: &str) -> Vec<(usize, usize, String)> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    while let Some(rel) = text[pos..].find("[#") {
+        let start = pos + rel;
+        let label_start = start + 2;
+        let label_len = text[label_start..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '_' | ':' | '.' | '-')))
+            .unwrap_or(text.len() - label_start);
+        let end = label_start + label_len + 1;
+        if label_len > 0
+            && text[label_start + label_len..].starts_with(']')
+            && !text[end..].starts_with(['(', '['])
+        {
+            out.push((start, end, text[label_start..end - 1].to_string()));
+            pos = end;
+        } else {
+            pos = label_start;
+        }
+    }
+    out
+}
+
 /// Parse the inside of a `[...]` citation group into a [`CiteRef`].
 fn parse_cite_group(inner: &str) -> Option<CiteRef> {
     let mut items = Vec::new();
@@ -269,4 +325,22 @@ mod tests {
     fn non_cite_brackets_ignored() {
         assert!(find_inline_cites("a [link](url) and [ref][1] and [ ] box").is_empty());
     }
+
+    #[test]
+    fn find_footnote_refs_skips_definitions() {
+        let refs = find_footnote_refs("See[^1] and [^long-note].\n[^1]: The note. [^ x] [^]");
+        let labels: Vec<&str> = refs.iter().map(|r| r.2.as_str()).collect();
+        assert_eq!(labels, vec!["1", "long-note"]);
+        assert_eq!((refs[0].0, refs[0].1), (3, 7));
+        assert!(find_footnote_refs("[@cite] and [link](url) and [^open").is_empty());
+    }
+
+    #[test]
+    fn find_xrefs_skips_links() {
+        let refs =
+            find_xrefs("See [#fig:arch] and [#tbl.sales-2].\n[#tag](url) [#ref][1] [#] [#a b]");
+        let labels: Vec<&str> = refs.iter().map(|r| r.2.as_str()).collect();
+        assert_eq!(labels, vec!["fig:arch", "tbl.sales-2"]);
+        assert_eq!((refs[0].0, refs[0].1), (4, 15));
+    }
 }
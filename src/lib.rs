@@ -18,11 +18,18 @@ pub mod blocks;
 pub mod builder;
 pub(crate) mod chart;
 pub mod citation;
+pub mod csl;
+pub mod cst;
 pub(crate) mod diagram;
 pub mod diagram_scene;
 pub mod error;
+pub mod footnote;
+pub mod format;
+mod font_metrics_vendored;
 pub mod icons;
 mod icons_vendored;
+pub mod include;
+pub mod incremental;
 pub mod inline;
 pub mod lint;
 pub(crate) mod mermaid_compat;
@@ -32,6 +39,7 @@ pub mod render_latex;
 pub mod render_md;
 pub mod resolve;
 pub mod slots;
+pub mod streaming;
 #[cfg(feature = "pdf")]
 pub mod render_pdf;
 pub mod render_typst;
@@ -52,8 +60,10 @@ pub mod wasm;
 #[cfg(feature = "dom")]
 pub mod render_dom;
 pub mod template;
+pub(crate) mod text_metrics;
 pub mod types;
 pub mod validate;
+pub mod xref;
 
 /// Unified CSS for app chrome and SurfDoc content rendering.
 ///
@@ -68,18 +78,23 @@ pub const SURFDOC_CSS: &str = include_str!("../assets/surfdoc.css");
 pub use blocks::{parse_schema_field_type, parse_schema_constraint};
 pub use builder::SurfDocBuilder;
 pub use citation::{
-    active_style, bibliography_heading, build_context, format_in_text, format_reference,
-    parse_author, parse_authors, reference_list, reference_list_keyed, Author, CiteContext,
-    CiteItem, CiteRef, Reference, RefType,
+    active_style, bibliography_heading, build_context, document_context, format_in_text,
+    format_reference, parse_author, parse_authors, reference_list, reference_list_keyed, Author,
+    BibliographyList, CiteContext, CiteItem, CiteRef, Reference, RefType,
 };
+pub use csl::{CslError, CslProcessor, CslStyle};
 pub use error::*;
 pub use lint::{
-    AppliedFix, CheckReport, FixOutcome, LintConfig, LintRule, SkippedFix, apply_fixes,
-    apply_fixes_once, check, check_with,
+    AppliedFix, CheckReport, CustomRule, FixOutcome, LintConfig, LintRule, SkippedFix,
+    apply_fixes, apply_fixes_once, check, check_with,
 };
-pub use parse::parse;
+pub use format::{FormatOptions, format};
+pub use include::{IncludeResolver, IncludedFile, parse_with_includes};
+pub use incremental::{IncrementalParser, reparse_edit};
+pub use parse::{ParseOptions, parse, parse_with};
 pub use template::TemplateContext;
 pub use types::*;
+pub use diagram::{TodayScope, install_diagram_today};
 pub use diagram_scene::{
     NativeAnchor, NativeDiagramScene, NativeMarker, NativePoint, NativeRole, NativeShape,
 };
@@ -97,6 +112,12 @@ pub use render_slides::{DeckConfig, SlideEntry, extract_deck, render_deck_html};
 pub use render_pdf::{collect_image_srcs, PdfConfig, PdfError};
 
 impl SurfDoc {
+    /// Lossless concrete syntax tree of this document's source, linked to
+    /// its blocks, nested ones included (see [`cst`]).
+    pub fn cst(&self) -> cst::Cst {
+        cst::Cst::from_doc(self)
+    }
+
     /// Render this document as standard CommonMark markdown (no `::` markers).
     pub fn to_markdown(&self) -> String {
         render_md::to_markdown(self)
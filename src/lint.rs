@@ -18,9 +18,10 @@ use std::sync::LazyLock;
 use serde::{Deserialize, Serialize};
 
 use crate::attrs::parse_attrs;
+use crate::cst::{Cst, CstKind, CstNode, LITERAL_BLOCK_NAMES};
 use crate::error::{Diagnostic, Fix, FixSafety, Severity, TextEdit};
 use crate::parse::{closing_directive_depth, directive_name_start, opening_directive};
-use crate::types::{AttrValue, Block, DocType, FrontMatter, Span, SurfDoc};
+use crate::types::{AttrValue, Block, DocType, FileId, FrontMatter, Span, SurfDoc};
 
 // ------------------------------------------------------------------
 // Rule registry (spec/rules.toml)
@@ -76,7 +77,9 @@ pub fn rule_registry_total() -> usize {
 
 /// Rule ids emitted by the parse layer (`src/parse.rs`), pinned for the
 /// registry drift test in `tests/spec_compliance.rs`.
-pub const PARSE_RULE_IDS: &[&str] = &["P001", "P002", "P003", "P005", "P006"];
+pub const PARSE_RULE_IDS: &[&str] = &[
+    "P001", "P002", "P003", "P005", "P006", "P007", "P008", "P009",
+];
 
 /// Default severity for a rule id, falling back to `Warning` for ids that are
 /// not in the registry (defensive; the drift test makes this unreachable).
@@ -125,15 +128,28 @@ fn rule_fix(id: &str, description: String, edits: Vec<TextEdit>) -> Fix {
 
 #[derive(Debug, Deserialize)]
 struct BlocksSpecFile {
-    blocks: BTreeMap<String, toml::Value>,
+    blocks: BTreeMap<String, BlockSpecEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockSpecEntry {
+    #[serde(default)]
+    attributes: Vec<String>,
 }
 
-static KNOWN_BLOCK_NAMES: LazyLock<BTreeSet<String>> = LazyLock::new(|| {
+/// Block name → registered attribute names, in spec order.
+static BLOCK_ATTRIBUTES: LazyLock<BTreeMap<String, Vec<String>>> = LazyLock::new(|| {
     let spec: BlocksSpecFile = toml::from_str(include_str!("../spec/blocks.toml"))
         .expect("spec/blocks.toml is embedded at compile time and must parse as valid TOML — covered by spec_compliance tests");
-    spec.blocks.into_keys().collect()
+    spec.blocks
+        .into_iter()
+        .map(|(name, entry)| (name, entry.attributes))
+        .collect()
 });
 
+static KNOWN_BLOCK_NAMES: LazyLock<BTreeSet<String>> =
+    LazyLock::new(|| BLOCK_ATTRIBUTES.keys().cloned().collect());
+
 /// All block names registered in `spec/blocks.toml` (any status).
 ///
 /// L020 keys off this set — the spec, not the `Block` enum, is the law.
@@ -141,9 +157,16 @@ pub fn known_block_names() -> &'static BTreeSet<String> {
     &KNOWN_BLOCK_NAMES
 }
 
+/// Attribute names `spec/blocks.toml` registers for block `name`, in spec
+/// order; `None` for a name the spec does not register.
+pub fn block_attribute_names(name: &str) -> Option<&'static [String]> {
+    BLOCK_ATTRIBUTES.get(name).map(Vec::as_slice)
+}
+
 /// Parser-accepted names that are deliberately NOT in `spec/blocks.toml`:
 /// sub-directives (`column`) and aliases/renderer families the parser resolves
-/// (`action-items` → tasks, `deck`/`slide`, `deploy_urls`, `info-card`).
+/// (`action-items` → tasks, `deck`/`slide`, `deploy_urls`, `info-card`), and
+/// `include`, which `include::parse_with_includes` splices away.
 /// L020 must not flag these. The spec_compliance test asserts none of them is
 /// in blocks.toml — once the spec registers one, remove it from this list.
 pub const EXTRA_KNOWN_BLOCK_NAMES: &[&str] = &[
@@ -151,6 +174,7 @@ pub const EXTRA_KNOWN_BLOCK_NAMES: &[&str] = &[
     "column",
     "deck",
     "deploy_urls",
+    "include",
     "info-card",
     "slide",
 ];
@@ -161,8 +185,9 @@ pub const EXTRA_KNOWN_BLOCK_NAMES: &[&str] = &[
 
 /// A single style-layer lint rule.
 pub trait LintRule {
-    /// Stable rule id, e.g. `"L001"`. Must have an entry in `spec/rules.toml`.
-    fn id(&self) -> &'static str;
+    /// Stable rule id, e.g. `"L001"`. Built-in rules must have an entry in
+    /// `spec/rules.toml`; a [`CustomRule`] uses its configured id.
+    fn id(&self) -> &str;
     /// Check a parsed document against this rule.
     ///
     /// `source` is the CRLF-normalised source text the spans refer to
@@ -184,6 +209,11 @@ pub fn all_rules() -> Vec<Box<dyn LintRule>> {
         Box::new(MissingRequiredFrontMatter),
         Box::new(FrontMatterEnumCase),
         Box::new(MermaidConstructSkipped),
+        Box::new(DiagramParseFailed),
+        Box::new(ChartBindingUnresolved),
+        Box::new(CslStyleInvalid),
+        Box::new(XrefUnresolved),
+        Box::new(XrefDuplicateLabel),
     ]
 }
 
@@ -213,6 +243,7 @@ impl ScanLine<'_> {
             end_line: self.idx + 1,
             start_offset: self.offset,
             end_offset: self.offset + self.raw.len(),
+            file: FileId::ROOT,
         }
     }
 
@@ -229,6 +260,7 @@ impl ScanLine<'_> {
             end_line: self.idx + 1,
             start_offset: self.offset + start,
             end_offset: self.offset + end,
+            file: FileId::ROOT,
         }
     }
 
@@ -250,6 +282,7 @@ impl ScanLine<'_> {
                 end_line: self.idx + 1,
                 start_offset: self.offset,
                 end_offset: end,
+                file: FileId::ROOT,
             },
             replacement: String::new(),
         }
@@ -271,10 +304,6 @@ fn front_matter_close_line(lines: &[&str]) -> Option<usize> {
         .map(|(i, _)| i)
 }
 
-/// Block names whose body content is literal text (directive-looking lines
-/// inside them must not trigger source-scan rules).
-const LITERAL_BLOCK_NAMES: &[&str] = &["code", "output"];
-
 /// Classify every source line for the source-scan rules.
 fn scan_lines(source: &str) -> Vec<ScanLine<'_>> {
     let lines: Vec<&str> = source.split('\n').collect();
@@ -465,34 +494,46 @@ impl LintRule for SectionBlockUsed {
 /// L002: `::table` block used — prefer a markdown pipe table.
 ///
 /// Fix (safe): strip the opener and closer lines, keeping the content.
+/// Walks the [`Cst`] directives, so `::table` lines inside literal bodies and
+/// fenced code never match and the closer is the one the parser paired.
 struct TableBlockUsed;
 
+/// Edit deleting a line-sized CST node (an opener or closer) together with
+/// its trailing newline, if any.
+fn delete_node_line_edit(node: &CstNode, source_len: usize) -> TextEdit {
+    let mut span = node.span;
+    if span.end_offset < source_len {
+        span.end_offset += 1;
+    }
+    TextEdit {
+        span,
+        replacement: String::new(),
+    }
+}
+
 impl LintRule for TableBlockUsed {
     fn id(&self) -> &'static str {
         "L002"
     }
 
-    fn check(&self, _doc: &SurfDoc, source: &str) -> Vec<Diagnostic> {
-        let scan = scan_lines(source);
+    fn check(&self, doc: &SurfDoc, source: &str) -> Vec<Diagnostic> {
+        let cst = Cst::from_doc(doc);
         let mut out = Vec::new();
-        for (i, line) in scan.iter().enumerate() {
-            if line.literal {
+        for directive in cst.directives() {
+            if directive.directive_name(source) != Some("table") {
                 continue;
             }
-            let Some((depth, name, _)) = opening_directive(line.trimmed) else {
+            let Some(opener) = directive.child(CstKind::Opener) else {
                 continue;
             };
-            if name != "table" {
-                continue;
-            }
-            let mut edits = vec![line.delete_edit(source.len())];
-            if let Some(ci) = matching_closer_idx(&scan, i, depth) {
-                edits.push(scan[ci].delete_edit(source.len()));
+            let mut edits = vec![delete_node_line_edit(opener, source.len())];
+            if let Some(closer) = directive.child(CstKind::Closer) {
+                edits.push(delete_node_line_edit(closer, source.len()));
             }
             out.push(diag_fix(
                 "L002",
                 "'::table' block used — prefer a markdown pipe table".to_string(),
-                Some(line.span()),
+                Some(opener.span),
                 Some(rule_fix(
                     "L002",
                     "remove the '::table' wrapper, keeping the table content".to_string(),
@@ -905,6 +946,7 @@ fn summary_stub_fix(source: &str) -> Option<Fix> {
                 end_line: anchor + 2,
                 start_offset: offset,
                 end_offset: offset,
+                file: FileId::ROOT,
             },
             replacement: text,
         }],
@@ -994,9 +1036,9 @@ fn levenshtein(a: &str, b: &str) -> usize {
     prev[b.len()]
 }
 
-/// Unique spec block name within edit distance 2 of `name`, if any.
+/// Unique candidate within edit distance 2 of `name`, if any.
 /// Returns `None` on a tie at the minimum distance.
-fn did_you_mean<'a>(name: &str, known: &'a BTreeSet<String>) -> Option<&'a str> {
+fn did_you_mean<'a>(name: &str, known: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
     let mut best: Option<(&str, usize)> = None;
     let mut tie = false;
     for cand in known {
@@ -1044,7 +1086,8 @@ impl LintRule for UnknownBlockName {
                     continue;
                 }
                 // Fix (suggested): rename to the unique did-you-mean match.
-                let (suggestion, fix) = match did_you_mean(&name, known) {
+                let (suggestion, fix) = match did_you_mean(&name, known.iter().map(String::as_str))
+                {
                     Some(s) => {
                         let name_start = line.indent() + depth;
                         let fix = rule_fix(
@@ -1109,6 +1152,7 @@ fn derived_updated_fix(source: &str) -> Option<Fix> {
                     end_line: line_no,
                     start_offset: insert_at,
                     end_offset: insert_at,
+                    file: FileId::ROOT,
                 },
                 replacement: format!("updated:{value}\n"),
             }],
@@ -1280,6 +1324,7 @@ impl LintRule for FrontMatterEnumCase {
                             end_line: idx + 1,
                             start_offset: val_start,
                             end_offset: val_end,
+                            file: FileId::ROOT,
                         },
                         replacement: (*expected).to_string(),
                     }],
@@ -1294,6 +1339,7 @@ impl LintRule for FrontMatterEnumCase {
                         end_line: idx + 1,
                         start_offset: line_offset,
                         end_offset: line_offset + raw.len(),
+                        file: FileId::ROOT,
                     }),
                     Some(fix),
                 ));
@@ -1329,31 +1375,13 @@ impl MermaidConstructSkipped {
                     }
                 }
             }
-            if let Some(children) = container_children(block) {
+            if let Some(children) = block.children() {
                 Self::walk(children, out);
             }
         }
     }
 }
 
-/// Child-block accessor for the container variants a `::diagram` can nest
-/// inside (mirrors the citation walker's container set).
-fn container_children(b: &Block) -> Option<&[Block]> {
-    match b {
-        Block::Page { children, .. }
-        | Block::Section { children, .. }
-        | Block::Slide { children, .. }
-        | Block::App { children, .. }
-        | Block::AppShell { children, .. }
-        | Block::Sidebar { children, .. }
-        | Block::Panel { children, .. }
-        | Block::TabContent { children, .. }
-        | Block::Drawer { children, .. }
-        | Block::Modal { children, .. } => Some(children),
-        _ => None,
-    }
-}
-
 impl LintRule for MermaidConstructSkipped {
     fn id(&self) -> &'static str {
         "L040"
@@ -1366,6 +1394,682 @@ impl LintRule for MermaidConstructSkipped {
     }
 }
 
+// ------------------------------------------------------------------
+// L041 — diagram body does not parse
+// ------------------------------------------------------------------
+
+/// L041: a `::diagram` body the DSL parser rejects — the block silently
+/// renders as a preformatted code block. Reported at the offending source
+/// line with the parser's message and, for "expected …" errors, the closest
+/// valid statement form for the diagram type.
+struct DiagramParseFailed;
+
+impl DiagramParseFailed {
+    fn walk(blocks: &[Block], source: &str, out: &mut Vec<Diagnostic>) {
+        for block in blocks {
+            if let Block::Diagram {
+                diagram_type,
+                content,
+                span,
+                ..
+            } = block
+                && let Some(d) = Self::check_block(diagram_type, content, span, source)
+            {
+                out.push(d);
+            }
+            if let Some(children) = block.children() {
+                Self::walk(children, source, out);
+            }
+        }
+    }
+
+    fn check_block(
+        diagram_type: &str,
+        content: &str,
+        span: &Span,
+        source: &str,
+    ) -> Option<Diagnostic> {
+        // Mirror the renderer: mermaid bodies translate first, chart aliases
+        // go to the `::chart` pipeline (which reports nothing line-level).
+        let translated = crate::mermaid_compat::translate(diagram_type, content);
+        let (eff_type, eff_content) = match &translated {
+            Some(t) => (t.diagram_type, t.content.as_str()),
+            None => (diagram_type, content),
+        };
+        if crate::diagram::chart_alias(eff_type).is_some() {
+            return None;
+        }
+        let err = crate::diagram::parse_diagram_source(eff_type, eff_content).err()?;
+        if translated.is_some() {
+            // Translated line numbers do not map back to the author's source.
+            return Some(diag(
+                "L041",
+                format!(
+                    "Diagram did not parse after mermaid translation: {}",
+                    err.message
+                ),
+                Some(*span),
+            ));
+        }
+        if err.line == 0 {
+            let suggestion =
+                match did_you_mean(diagram_type, crate::diagram::DIAGRAM_TYPES.iter().copied()) {
+                    Some(t) => format!(" — did you mean type={t}?"),
+                    None => String::new(),
+                };
+            return Some(diag(
+                "L041",
+                format!("Diagram did not parse: {}{suggestion}", err.message),
+                Some(*span),
+            ));
+        }
+        let line = content.lines().nth(err.line - 1).unwrap_or("");
+        let suggestion = match closest_statement_form(diagram_type, line) {
+            Some(form) if err.message.starts_with("expected") => {
+                format!(" — closest statement form: `{form}`")
+            }
+            _ => String::new(),
+        };
+        Some(diag(
+            "L041",
+            format!(
+                "Diagram did not parse (diagram line {}): {}{suggestion}",
+                err.line, err.message
+            ),
+            Some(body_line_span(span, content, err.line, source).unwrap_or(*span)),
+        ))
+    }
+}
+
+/// Span of 1-based body line `n` of a diagram block whose body is
+/// `content`. The body starts on the line after the opener; blocks nested in
+/// a container carry no source span, so their body is located by its first
+/// occurrence in `source` instead. `None` when neither lands on the body.
+fn body_line_span(span: &Span, content: &str, n: usize, source: &str) -> Option<Span> {
+    if span.file != FileId::ROOT || content.is_empty() {
+        return None;
+    }
+    let body = if span.start_line > 0 {
+        source
+            .split_inclusive('\n')
+            .take(span.start_line)
+            .map(str::len)
+            .sum()
+    } else {
+        source.find(content)?
+    };
+    if !source.get(body..)?.starts_with(content) {
+        return None;
+    }
+    let offset = body
+        + content
+            .split_inclusive('\n')
+            .take(n - 1)
+            .map(str::len)
+            .sum::<usize>();
+    let raw = content.lines().nth(n - 1)?;
+    let line = source[..offset].matches('\n').count() + 1;
+    Some(Span {
+        start_line: line,
+        end_line: line,
+        start_offset: offset,
+        end_offset: offset + raw.len(),
+        file: span.file,
+    })
+}
+
+/// The statement form of `diagram_type` closest to the rejected `line`: an
+/// exact leading keyword, then the edge form for arrow-shaped lines, then a
+/// keyword within edit distance 2, then the type's plain node form.
+fn closest_statement_form(diagram_type: &str, line: &str) -> Option<&'static str> {
+    let forms = crate::diagram::statement_forms(diagram_type);
+    let word = line
+        .trim()
+        .split(|c: char| c.is_whitespace() || c == ':')
+        .next()
+        .unwrap_or("");
+    let word = word.to_ascii_lowercase();
+    let keyed = |kw: &str| forms.iter().find(|(k, _)| *k == kw).map(|(_, f)| *f);
+    if !word.is_empty()
+        && let Some(form) = keyed(&word)
+    {
+        return Some(form);
+    }
+    let is_edge = |s: &str| {
+        ["->", "--", "=>", "<-", "→"]
+            .iter()
+            .any(|arrow| s.contains(arrow))
+    };
+    if is_edge(line)
+        && let Some((_, form)) = forms.iter().find(|(k, f)| k.is_empty() && is_edge(f))
+    {
+        return Some(form);
+    }
+    if word.chars().count() >= 3
+        && let Some(kw) = did_you_mean(
+            &word,
+            forms.iter().map(|(k, _)| *k).filter(|k| !k.is_empty()),
+        )
+    {
+        return keyed(kw);
+    }
+    forms
+        .iter()
+        .find(|(k, f)| k.is_empty() && !is_edge(f))
+        .or_else(|| forms.first())
+        .map(|(_, f)| *f)
+}
+
+impl LintRule for DiagramParseFailed {
+    fn id(&self) -> &'static str {
+        "L041"
+    }
+
+    fn check(&self, doc: &SurfDoc, source: &str) -> Vec<Diagnostic> {
+        let mut out = Vec::new();
+        Self::walk(&doc.blocks, source, &mut out);
+        out
+    }
+}
+
+// ------------------------------------------------------------------
+// L042 — chart data binding does not resolve
+// ------------------------------------------------------------------
+
+/// L042: a `::chart[data=#id]` whose binding fails — no `::data` block with
+/// that id, an unknown column, or a sort key that is not charted. The chart
+/// silently renders its placeholder, so say why.
+struct ChartBindingUnresolved;
+
+impl ChartBindingUnresolved {
+    fn walk(blocks: &[Block], all: &[Block], out: &mut Vec<Diagnostic>) {
+        for block in blocks {
+            if let Block::Chart {
+                binding: Some(binding),
+                span,
+                ..
+            } = block
+                && let Err(reason) = crate::chart::resolve_binding(binding, all)
+            {
+                out.push(diag(
+                    "L042",
+                    format!("Chart data binding did not resolve: {reason}"),
+                    Some(*span),
+                ));
+            }
+            if let Some(children) = block.children() {
+                Self::walk(children, all, out);
+            }
+        }
+    }
+}
+
+impl LintRule for ChartBindingUnresolved {
+    fn id(&self) -> &'static str {
+        "L042"
+    }
+
+    fn check(&self, doc: &SurfDoc, _source: &str) -> Vec<Diagnostic> {
+        let mut out = Vec::new();
+        Self::walk(&doc.blocks, &doc.blocks, &mut out);
+        out
+    }
+}
+
+// ------------------------------------------------------------------
+// L043 — front matter CSL style does not parse
+// ------------------------------------------------------------------
+
+/// L043: a front matter `csl:` style that is not valid CSL 1.0. Renderers
+/// fall back to the built-in `format:` style, so say why. The span points
+/// at the offending style line inside a `csl: |` block.
+struct CslStyleInvalid;
+
+impl LintRule for CslStyleInvalid {
+    fn id(&self) -> &'static str {
+        "L043"
+    }
+
+    fn check(&self, doc: &SurfDoc, source: &str) -> Vec<Diagnostic> {
+        let Some(xml) = doc.front_matter.as_ref().and_then(|fm| fm.csl.as_deref()) else {
+            return Vec::new();
+        };
+        let Err(err) = crate::csl::CslStyle::parse(xml) else {
+            return Vec::new();
+        };
+        let lines: Vec<&str> = source.split('\n').collect();
+        let fm_end = front_matter_close_line(&lines).unwrap_or(0);
+        let span = lines
+            .iter()
+            .take(fm_end)
+            .position(|l| l.starts_with("csl:"))
+            .map(|idx| {
+                let block = lines[idx]["csl:".len()..]
+                    .trim_start()
+                    .starts_with(['|', '>']);
+                let line = if block {
+                    (idx + err.line).min(fm_end - 1)
+                } else {
+                    idx
+                };
+                let start_offset: usize = lines[..line].iter().map(|l| l.len() + 1).sum();
+                Span {
+                    start_line: line + 1,
+                    end_line: line + 1,
+                    start_offset,
+                    end_offset: start_offset + lines[line].len(),
+                    file: FileId::ROOT,
+                }
+            });
+        vec![diag(
+            "L043",
+            format!("CSL style did not parse: {err}"),
+            span,
+        )]
+    }
+}
+
+// ------------------------------------------------------------------
+// L044 — cross-reference names no target
+// ------------------------------------------------------------------
+
+/// L044: a `[#label]` reference with no figure, table, equation, listing or
+/// heading labelled `label`. It renders as literal text, so say so.
+struct XrefUnresolved;
+
+impl LintRule for XrefUnresolved {
+    fn id(&self) -> &'static str {
+        "L044"
+    }
+
+    fn check(&self, doc: &SurfDoc, source: &str) -> Vec<Diagnostic> {
+        let xrefs = crate::xref::Xrefs::collect(&doc.blocks);
+        crate::xref::unresolved(&doc.blocks, &xrefs, source)
+            .into_iter()
+            .map(|(label, span)| {
+                diag(
+                    "L044",
+                    format!("Cross-reference target not found: [#{label}]"),
+                    Some(span),
+                )
+            })
+            .collect()
+    }
+}
+
+// ------------------------------------------------------------------
+// L045 — cross-reference label defined twice
+// ------------------------------------------------------------------
+
+/// L045: a second target with an existing cross-reference label.
+/// References resolve to the first; reported at the later definition.
+struct XrefDuplicateLabel;
+
+impl LintRule for XrefDuplicateLabel {
+    fn id(&self) -> &'static str {
+        "L045"
+    }
+
+    fn check(&self, doc: &SurfDoc, source: &str) -> Vec<Diagnostic> {
+        let xrefs = crate::xref::Xrefs::collect_in(&doc.blocks, source);
+        xrefs
+            .duplicates()
+            .iter()
+            .map(|dup| {
+                let line = xrefs
+                    .get(&dup.label)
+                    .map_or(0, |first| first.span.start_line);
+                diag(
+                    "L045",
+                    format!(
+                        "Duplicate cross-reference label '{}' (first defined on line {line})",
+                        dup.label
+                    ),
+                    Some(dup.span),
+                )
+            })
+            .collect()
+    }
+}
+
+// ------------------------------------------------------------------
+// Custom (declarative) rules — `[[rules]]` in .surflint.toml
+// ------------------------------------------------------------------
+
+/// A project-local declarative lint rule, carried in
+/// [`LintConfig::custom_rules`] (`[[rules]]` in `.surflint.toml`).
+///
+/// With `block` set the rule applies to every `::block` directive at any
+/// nesting depth; without it, to the whole document. `front_matter` narrows
+/// it to documents whose raw front matter has the given field values, so it
+/// also matches values the typed schema rejects. Every failed predicate
+/// yields one diagnostic with the rule's `id`, `severity` and `message`.
+///
+/// `message` is a template: `{block}`, `{attr}`, `{value}`, `{child}`,
+/// `{depth}` and `{max}` are filled in where the failing predicate has them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomRule {
+    /// Diagnostic code, e.g. `"X001"`. Must not use a built-in prefix
+    /// (`P`, `V`, `L` followed by digits).
+    pub id: String,
+    /// Severity of every diagnostic the rule emits.
+    #[serde(default = "custom_rule_default_severity")]
+    pub severity: Severity,
+    /// Message template (see the type docs).
+    pub message: String,
+    /// Directive name the rule applies to; `None` for a document rule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block: Option<String>,
+    /// Only check documents whose front matter has these field values.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub front_matter: BTreeMap<String, String>,
+    /// Attributes the block must carry.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub require_attrs: Vec<String>,
+    /// Attributes the block must not carry.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forbid_attrs: Vec<String>,
+    /// Attribute → required value prefix, checked when the attribute is set
+    /// (`src = "https://"`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attr_prefix: BTreeMap<String, String>,
+    /// Attribute → allowed values, checked when the attribute is set.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attr_values: BTreeMap<String, Vec<String>>,
+    /// Directives that must appear: directly inside the block, or anywhere
+    /// in a document rule.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub require_children: Vec<String>,
+    /// Directives that must not appear (same scope as `require_children`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forbid_children: Vec<String>,
+    /// Deepest markdown heading allowed (`3` rejects `####`), inside the
+    /// block or anywhere in a document rule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_heading_depth: Option<usize>,
+}
+
+fn custom_rule_default_severity() -> Severity {
+    Severity::Warning
+}
+
+/// Check a set of custom rules for mistakes the rule engine cannot recover
+/// from: reserved or duplicate ids, empty messages, rules without a
+/// predicate, and attribute predicates on a document rule. The error names
+/// the offending rule.
+pub fn validate_custom_rules(rules: &[CustomRule]) -> Result<(), String> {
+    let mut seen = BTreeSet::new();
+    for rule in rules {
+        let id = rule.id.as_str();
+        let reserved = id.len() > 1
+            && id.starts_with(['P', 'V', 'L'])
+            && id[1..].bytes().all(|b| b.is_ascii_digit());
+        if id.is_empty() || reserved || rule_registry().contains_key(id) {
+            return Err(format!(
+                "custom rule id '{id}' is empty or uses a built-in prefix (P, V, L + digits)"
+            ));
+        }
+        if !seen.insert(id) {
+            return Err(format!("custom rule id '{id}' is defined twice"));
+        }
+        if rule.message.trim().is_empty() {
+            return Err(format!("custom rule '{id}' has an empty message"));
+        }
+        let attr_predicates = !rule.require_attrs.is_empty()
+            || !rule.forbid_attrs.is_empty()
+            || !rule.attr_prefix.is_empty()
+            || !rule.attr_values.is_empty();
+        if attr_predicates && rule.block.is_none() {
+            return Err(format!(
+                "custom rule '{id}' checks attributes but names no `block`"
+            ));
+        }
+        if !attr_predicates
+            && rule.require_children.is_empty()
+            && rule.forbid_children.is_empty()
+            && rule.max_heading_depth.is_none()
+        {
+            return Err(format!("custom rule '{id}' has no predicate"));
+        }
+    }
+    Ok(())
+}
+
+/// Values a message template can refer to.
+#[derive(Default)]
+struct TemplateArgs<'a> {
+    block: Option<&'a str>,
+    attr: Option<&'a str>,
+    value: Option<&'a str>,
+    child: Option<&'a str>,
+    depth: Option<usize>,
+    max: Option<usize>,
+}
+
+impl CustomRule {
+    fn diagnostic(&self, args: TemplateArgs<'_>, span: Option<Span>) -> Diagnostic {
+        let mut message = self.message.clone();
+        let depth = args.depth.map(|d| d.to_string());
+        let max = args.max.map(|m| m.to_string());
+        for (slot, value) in [
+            ("{block}", args.block),
+            ("{attr}", args.attr),
+            ("{value}", args.value),
+            ("{child}", args.child),
+            ("{depth}", depth.as_deref()),
+            ("{max}", max.as_deref()),
+        ] {
+            if let Some(value) = value {
+                message = message.replace(slot, value);
+            }
+        }
+        Diagnostic {
+            severity: self.severity,
+            message,
+            span,
+            code: Some(self.id.clone()),
+            fix: None,
+        }
+    }
+
+    /// Whether the document's raw front matter has every `front_matter`
+    /// value (scalars compared as written).
+    fn front_matter_matches(&self, source: &str) -> bool {
+        if self.front_matter.is_empty() {
+            return true;
+        }
+        let lines: Vec<&str> = source.split('\n').collect();
+        let Some(end) = front_matter_close_line(&lines) else {
+            return false;
+        };
+        let Ok(serde_yaml::Value::Mapping(map)) =
+            serde_yaml::from_str::<serde_yaml::Value>(&lines[1..end].join("\n"))
+        else {
+            return false;
+        };
+        self.front_matter.iter().all(|(field, expected)| {
+            let value = match map.get(field.as_str()) {
+                Some(serde_yaml::Value::String(s)) => s.clone(),
+                Some(serde_yaml::Value::Number(n)) => n.to_string(),
+                Some(serde_yaml::Value::Bool(b)) => b.to_string(),
+                _ => return false,
+            };
+            &value == expected
+        })
+    }
+
+    fn check_directive(
+        &self,
+        directive: &CstNode,
+        name: &str,
+        source: &str,
+        out: &mut Vec<Diagnostic>,
+    ) {
+        let Some(opener) = directive.child(CstKind::Opener) else {
+            return;
+        };
+        let attrs = opener
+            .child(CstKind::AttrList)
+            .and_then(|list| parse_attrs(list.text(source)).ok())
+            .unwrap_or_default();
+        let args = |attr| TemplateArgs {
+            block: Some(name),
+            attr: Some(attr),
+            ..TemplateArgs::default()
+        };
+        let attr_span = |key: &str| directive.attr(key, source).map_or(opener.span, |a| a.span);
+
+        for key in &self.require_attrs {
+            if !attrs.contains_key(key) {
+                out.push(self.diagnostic(args(key), Some(opener.span)));
+            }
+        }
+        for key in &self.forbid_attrs {
+            if attrs.contains_key(key) {
+                out.push(self.diagnostic(args(key), Some(attr_span(key))));
+            }
+        }
+        for (key, value) in &attrs {
+            let written = match value {
+                AttrValue::String(s) => s.clone(),
+                AttrValue::Number(n) => n.to_string(),
+                AttrValue::Bool(b) => b.to_string(),
+                AttrValue::Null => "null".to_string(),
+            };
+            let prefix_ok = self
+                .attr_prefix
+                .get(key)
+                .is_none_or(|prefix| written.starts_with(prefix.as_str()));
+            let value_ok = self
+                .attr_values
+                .get(key)
+                .is_none_or(|allowed| allowed.contains(&written));
+            if !prefix_ok || !value_ok {
+                let args = TemplateArgs {
+                    value: Some(&written),
+                    ..args(key)
+                };
+                out.push(self.diagnostic(args, Some(attr_span(key))));
+            }
+        }
+
+        let children: Vec<(&str, Span)> = directive
+            .child(CstKind::Body)
+            .map(|body| {
+                body.children
+                    .iter()
+                    .filter(|c| c.kind == CstKind::Directive)
+                    .filter_map(|c| Some((c.directive_name(source)?, c.span)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.check_children(&children, Some(name), Some(opener.span), out);
+
+        if let Some(body) = directive.child(CstKind::Body) {
+            self.check_headings(
+                source,
+                body.span.start_offset,
+                body.span.end_offset,
+                Some(name),
+                out,
+            );
+        }
+    }
+
+    fn check_children(
+        &self,
+        children: &[(&str, Span)],
+        block: Option<&str>,
+        span: Option<Span>,
+        out: &mut Vec<Diagnostic>,
+    ) {
+        let args = |child| TemplateArgs {
+            block,
+            child: Some(child),
+            ..TemplateArgs::default()
+        };
+        for required in &self.require_children {
+            if !children.iter().any(|(name, _)| name == required) {
+                out.push(self.diagnostic(args(required), span));
+            }
+        }
+        for (name, child_span) in children {
+            if self.forbid_children.iter().any(|f| f == name) {
+                out.push(self.diagnostic(args(name), Some(*child_span)));
+            }
+        }
+    }
+
+    /// Markdown headings between byte offsets `start..end` deeper than
+    /// `max_heading_depth`. Literal lines (front matter, `::code` bodies,
+    /// fences) are skipped.
+    fn check_headings(
+        &self,
+        source: &str,
+        start: usize,
+        end: usize,
+        block: Option<&str>,
+        out: &mut Vec<Diagnostic>,
+    ) {
+        let Some(max) = self.max_heading_depth else {
+            return;
+        };
+        for line in scan_lines(source) {
+            if line.literal || line.offset < start || line.offset >= end {
+                continue;
+            }
+            let depth = line.trimmed.bytes().take_while(|&b| b == b'#').count();
+            let is_heading = (1..=6).contains(&depth)
+                && line.trimmed[depth..]
+                    .chars()
+                    .next()
+                    .is_none_or(char::is_whitespace);
+            if is_heading && depth > max {
+                let args = TemplateArgs {
+                    block,
+                    depth: Some(depth),
+                    max: Some(max),
+                    ..TemplateArgs::default()
+                };
+                out.push(self.diagnostic(args, Some(line.span())));
+            }
+        }
+    }
+}
+
+impl LintRule for CustomRule {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn check(&self, doc: &SurfDoc, source: &str) -> Vec<Diagnostic> {
+        if !self.front_matter_matches(source) {
+            return Vec::new();
+        }
+        let cst = Cst::from_doc(doc);
+        let mut out = Vec::new();
+        match &self.block {
+            Some(block) => {
+                for directive in cst.directives() {
+                    if directive.directive_name(source) == Some(block.as_str()) {
+                        self.check_directive(directive, block, source, &mut out);
+                    }
+                }
+            }
+            None => {
+                let directives: Vec<(&str, Span)> = cst
+                    .directives()
+                    .filter_map(|d| Some((d.directive_name(source)?, d.span)))
+                    .collect();
+                self.check_children(&directives, None, None, &mut out);
+                self.check_headings(source, 0, source.len(), None, &mut out);
+            }
+        }
+        out
+    }
+}
+
 // ------------------------------------------------------------------
 // Unified check entry point
 // ------------------------------------------------------------------
@@ -1399,6 +2103,16 @@ pub struct LintConfig {
     /// (the typed schema is unchanged) — this only silences the diagnostic.
     #[serde(default)]
     pub extra_frontmatter_values: BTreeMap<String, BTreeSet<String>>,
+    /// Resource bounds for the parse layer; a bound that is hit surfaces as
+    /// P007. Unbounded by default.
+    #[serde(default)]
+    pub parse_options: crate::parse::ParseOptions,
+    /// Project-local declarative rules, run after the built-in style rules.
+    /// Their ids obey `disabled_rules` and `severity_overrides` like any other.
+    /// A set that fails [`validate_custom_rules`] is not run; [`check_with`]
+    /// reports the problem as an error diagnostic instead.
+    #[serde(default)]
+    pub custom_rules: Vec<CustomRule>,
 }
 
 /// Run all three diagnostic layers (parse, validate, lint) over `input`.
@@ -1406,9 +2120,10 @@ pub fn check(input: &str) -> CheckReport {
     check_with(input, &LintConfig::default())
 }
 
-/// [`check`] with severity overrides and disabled rules applied.
+/// [`check`] with severity overrides, disabled rules, parse bounds, and
+/// custom rules applied.
 pub fn check_with(input: &str, cfg: &LintConfig) -> CheckReport {
-    let result = crate::parse::parse(input);
+    let result = crate::parse::parse_with(input, &cfg.parse_options);
     let mut diagnostics = result.diagnostics;
     attach_parse_fixes(&mut diagnostics, &result.doc.source);
     // The front matter failed to parse (P002 broken YAML / P005 unknown
@@ -1438,7 +2153,24 @@ pub fn check_with(input: &str, cfg: &LintConfig) -> CheckReport {
                 })
         });
     }
-    for rule in all_rules() {
+    let builtin = all_rules();
+    // An invalid rule set could shadow built-in ids or never fire; run none
+    // of it and say why rather than guessing which rules are usable.
+    let custom_rules = match validate_custom_rules(&cfg.custom_rules) {
+        Ok(()) => cfg.custom_rules.as_slice(),
+        Err(e) => {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                message: format!("invalid custom rules: {e}"),
+                span: None,
+                code: None,
+                fix: None,
+            });
+            &[]
+        }
+    };
+    let custom = custom_rules.iter().map(|r| r as &dyn LintRule);
+    for rule in builtin.iter().map(Box::as_ref).chain(custom) {
         if cfg.disabled_rules.contains(rule.id()) {
             continue;
         }
@@ -1572,6 +2304,7 @@ fn p001_eof_closer_fix(
                 end_line: line_no,
                 start_offset: source.len(),
                 end_offset: source.len(),
+                file: FileId::ROOT,
             },
             replacement: text,
         }],
@@ -1612,6 +2345,7 @@ fn p002_close_front_matter_fix(d: &Diagnostic, source: &str, lines: &[&str]) ->
                 end_line: blank + 1,
                 start_offset: offset,
                 end_offset: offset,
+                file: FileId::ROOT,
             },
             replacement: "---\n".to_string(),
         }],
@@ -1899,6 +2633,227 @@ pub fn reports_to_json(files: &[(Option<&str>, &CheckReport)]) -> serde_json::Va
     })
 }
 
+// ------------------------------------------------------------------
+// SARIF 2.1.0 and JUnit XML (CI dashboards)
+// ------------------------------------------------------------------
+
+/// One checked file for [`reports_to_sarif`] / [`reports_to_junit`]: its
+/// label (used as the artifact URI / suite name), the CRLF-normalised source
+/// the report's spans point into, and the report.
+pub type LabeledReport<'a> = (&'a str, &'a str, &'a CheckReport);
+
+/// SARIF `level` for a severity.
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "note",
+    }
+}
+
+/// 1-based line and column (in Unicode code points) of byte `offset`.
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let line = before.matches('\n').count() + 1;
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// SARIF region for a span: line/column (code points, end exclusive) plus
+/// the exact byte range.
+fn sarif_region(source: &str, span: &Span) -> serde_json::Value {
+    let (start_line, start_column) = line_column(source, span.start_offset);
+    let (end_line, end_column) = line_column(source, span.end_offset);
+    serde_json::json!({
+        "startLine": start_line,
+        "startColumn": start_column,
+        "endLine": end_line,
+        "endColumn": end_column,
+        "byteOffset": span.start_offset,
+        "byteLength": span.end_offset.saturating_sub(span.start_offset),
+    })
+}
+
+/// SARIF 2.1.0 log over checked files: one run whose `tool.driver.rules`
+/// is the `spec/rules.toml` registry (description, default level,
+/// fixability) followed by `custom_rules` (layer `"custom"`; the first rule
+/// with a given id wins), one result per diagnostic, and each [`Fix`] as a
+/// SARIF `fix` with byte-exact replacements. Diagnostics whose code has no
+/// descriptor (V-codes) carry a `ruleId` without a `ruleIndex`.
+pub fn reports_to_sarif(
+    files: &[LabeledReport<'_>],
+    custom_rules: &[CustomRule],
+) -> serde_json::Value {
+    let registry = rule_registry();
+    let mut rule_ids: Vec<&str> = registry.keys().map(String::as_str).collect();
+    let mut rules: Vec<serde_json::Value> = registry
+        .iter()
+        .map(|(id, meta)| {
+            let mut properties = serde_json::json!({
+                "layer": meta.layer,
+                "fixable": meta.fixable,
+                "messageTemplate": meta.message,
+            });
+            if let Some(safety) = &meta.fix_safety {
+                properties["fixSafety"] = safety.as_str().into();
+            }
+            serde_json::json!({
+                "id": id,
+                "shortDescription": { "text": meta.description },
+                "defaultConfiguration": { "level": sarif_level(meta.severity) },
+                "properties": properties,
+            })
+        })
+        .collect();
+    for rule in custom_rules {
+        if rule_ids.contains(&rule.id.as_str()) {
+            continue;
+        }
+        rule_ids.push(&rule.id);
+        rules.push(serde_json::json!({
+            "id": rule.id,
+            "shortDescription": { "text": rule.message },
+            "defaultConfiguration": { "level": sarif_level(rule.severity) },
+            "properties": {
+                "layer": "custom",
+                "fixable": false,
+                "messageTemplate": rule.message,
+            },
+        }));
+    }
+
+    let mut results = Vec::new();
+    for (path, source, report) in files {
+        let artifact = serde_json::json!({ "uri": path.replace('\\', "/") });
+        for d in &report.diagnostics {
+            let mut location = serde_json::json!({ "artifactLocation": artifact });
+            if let Some(span) = &d.span {
+                location["region"] = sarif_region(source, span);
+            }
+            let mut result = serde_json::json!({
+                "level": sarif_level(d.severity),
+                "message": { "text": d.message },
+                "locations": [{ "physicalLocation": location }],
+            });
+            if let Some(code) = &d.code {
+                result["ruleId"] = code.as_str().into();
+                if let Some(index) = rule_ids.iter().position(|id| id == code) {
+                    result["ruleIndex"] = index.into();
+                }
+            }
+            if let Some(fix) = &d.fix {
+                let replacements: Vec<serde_json::Value> = fix
+                    .edits
+                    .iter()
+                    .map(|edit| {
+                        serde_json::json!({
+                            "deletedRegion": sarif_region(source, &edit.span),
+                            "insertedContent": { "text": edit.replacement },
+                        })
+                    })
+                    .collect();
+                result["fixes"] = serde_json::json!([{
+                    "description": { "text": fix.description },
+                    "artifactChanges": [{
+                        "artifactLocation": artifact,
+                        "replacements": replacements,
+                    }],
+                    "properties": { "safety": fix.safety },
+                }]);
+            }
+            results.push(result);
+        }
+    }
+
+    serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "surf-lint",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    })
+}
+
+/// Escape text for an XML attribute value or element body.
+fn xml_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// JUnit XML report over checked files: one `<testsuite>` per file and one
+/// `<testcase>` per diagnostic. Errors and warnings are `<failure>`s (typed
+/// by severity); info diagnostics pass with the message in `<system-out>`.
+/// A file with no diagnostics gets a single passing test case, so every
+/// file shows up in test-report widgets.
+pub fn reports_to_junit(files: &[LabeledReport<'_>]) -> String {
+    let failures_in = |report: &CheckReport| report.error_count + report.warning_count;
+    let tests_in = |report: &CheckReport| report.diagnostics.len().max(1);
+    let total_tests: usize = files.iter().map(|(_, _, r)| tests_in(r)).sum();
+    let total_failures: usize = files.iter().map(|(_, _, r)| failures_in(r)).sum();
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<testsuites name=\"surf-lint\" tests=\"{total_tests}\" failures=\"{total_failures}\" errors=\"0\">\n"
+    ));
+    for (path, _, report) in files {
+        let path = xml_escape(path);
+        out.push_str(&format!(
+            "  <testsuite name=\"{path}\" tests=\"{}\" failures=\"{}\" errors=\"0\">\n",
+            tests_in(report),
+            failures_in(report),
+        ));
+        if report.diagnostics.is_empty() {
+            out.push_str(&format!(
+                "    <testcase name=\"surf-lint\" classname=\"{path}\"/>\n"
+            ));
+        }
+        for d in &report.diagnostics {
+            let code = d.code.as_deref().unwrap_or("----");
+            let line = d.span.map_or(0, |s| s.start_line);
+            let name = xml_escape(&format!("{code} (line {line})"));
+            let message = xml_escape(&d.message);
+            out.push_str(&format!(
+                "    <testcase name=\"{name}\" classname=\"{path}\">\n"
+            ));
+            match d.severity {
+                Severity::Error | Severity::Warning => out.push_str(&format!(
+                    "      <failure type=\"{}\" message=\"{message}\">{path}:{line}: {code} {message}</failure>\n",
+                    sarif_level(d.severity),
+                )),
+                Severity::Info => out.push_str(&format!(
+                    "      <system-out>{path}:{line}: {code} {message}</system-out>\n"
+                )),
+            }
+            out.push_str("    </testcase>\n");
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
 // ------------------------------------------------------------------
 // Tests
 // ------------------------------------------------------------------
@@ -1944,6 +2899,14 @@ mod tests {
         assert!(!known.contains("table"));
     }
 
+    #[test]
+    fn block_attribute_names_loaded_from_spec() {
+        let callout = block_attribute_names("callout").unwrap();
+        assert!(callout.iter().any(|a| a == "type"));
+        assert_eq!(block_attribute_names("alternatives"), Some(&[][..]));
+        assert_eq!(block_attribute_names("table"), None);
+    }
+
     // --- L001 / L002 ---
 
     #[test]
@@ -2831,6 +3794,7 @@ mod tests {
                 end_line: 1,
                 start_offset: s,
                 end_offset: t,
+                file: FileId::ROOT,
             },
             replacement: String::new(),
         };
@@ -2945,15 +3909,117 @@ mod tests {
         assert_eq!(envelope["summary"]["error_count"], 0);
     }
 
+    // --- SARIF / JUnit ---
+
+    #[test]
+    fn sarif_log_has_registry_rules_results_and_fixes() {
+        let report = check(JSON_FIXTURE);
+        let sarif = reports_to_sarif(&[("docs/a.surf", JSON_FIXTURE, &report)], &[]);
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        assert_eq!(rules.len(), rule_registry().len());
+        let l001_index = rules.iter().position(|r| r["id"] == "L001").unwrap();
+        let l001_rule = &rules[l001_index];
+        assert_eq!(
+            l001_rule["shortDescription"]["text"],
+            rule_registry()["L001"].description
+        );
+        assert_eq!(l001_rule["defaultConfiguration"]["level"], "warning");
+        assert_eq!(l001_rule["properties"]["fixable"], true);
+
+        let result = run["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|r| r["ruleId"] == "L001")
+            .expect("L001 result");
+        assert_eq!(result["ruleIndex"], l001_index);
+        assert_eq!(result["level"], "warning");
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "docs/a.surf");
+        assert_eq!(location["region"]["startLine"], 9);
+        assert_eq!(location["region"]["startColumn"], 1);
+        let change = &result["fixes"][0]["artifactChanges"][0];
+        let replacement = &change["replacements"][0];
+        assert_eq!(replacement["insertedContent"]["text"], "## Background");
+        assert!(replacement["deletedRegion"]["byteLength"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    fn sarif_columns_count_code_points() {
+        let source = "# Café ☕\n::callout{type=info}\nx\n::\n";
+        let report = check(source);
+        let sarif = reports_to_sarif(&[("a.surf", source, &report)], &[]);
+        let l003 = sarif["runs"][0]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|r| r["ruleId"] == "L003")
+            .expect("L003 result");
+        let region = &l003["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 2);
+        assert_eq!(region["byteOffset"], "# Café ☕\n".len());
+        assert_eq!(line_column("é☕x", "é☕".len()), (1, 3));
+    }
+
+    #[test]
+    fn sarif_lists_custom_rules_after_the_registry() {
+        let input = "::decision\nUse Rust.\n::\n";
+        let rule = custom(
+            "id = \"X001\"\nseverity = \"error\"\nblock = \"decision\"\nrequire_attrs = [\"deciders\"]\nmessage = \"missing {attr}\"",
+        );
+        let cfg = LintConfig {
+            custom_rules: vec![rule.clone()],
+            ..LintConfig::default()
+        };
+        let report = check_with(input, &cfg);
+        let sarif = reports_to_sarif(&[("a.surf", input, &report)], &[rule.clone(), rule]);
+        let run = &sarif["runs"][0];
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        assert_eq!(rules.len(), rule_registry().len() + 1);
+        let x001 = &rules[rule_registry().len()];
+        assert_eq!(x001["id"], "X001");
+        assert_eq!(x001["shortDescription"]["text"], "missing {attr}");
+        assert_eq!(x001["defaultConfiguration"]["level"], "error");
+        assert_eq!(x001["properties"]["layer"], "custom");
+
+        let result = run["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|r| r["ruleId"] == "X001")
+            .expect("X001 result");
+        assert_eq!(result["ruleIndex"], rule_registry().len());
+    }
+
+    #[test]
+    fn junit_report_has_a_suite_per_file_and_failures_per_diagnostic() {
+        let with_warning = check(JSON_FIXTURE);
+        let clean =
+            check("---\ntitle: \"T\"\ntype: report\nstatus: active\n---\n\n# Doc\n\nClean body.\n");
+        let xml = reports_to_junit(&[
+            ("a.surf", JSON_FIXTURE, &with_warning),
+            ("b&c.surf", "", &clean),
+        ]);
+        assert!(xml.starts_with("<?xml"));
+        assert!(xml.contains("<testsuites name=\"surf-lint\" tests=\"2\" failures=\"1\""));
+        assert!(xml.contains("<testsuite name=\"a.surf\" tests=\"1\" failures=\"1\""));
+        assert!(xml.contains("<testcase name=\"L001 (line 9)\" classname=\"a.surf\">"));
+        assert!(xml.contains("<failure type=\"warning\""));
+        assert!(xml.contains("<testsuite name=\"b&amp;c.surf\" tests=\"1\" failures=\"0\""));
+        assert!(xml.contains("&apos;::section&apos;"));
+    }
+
     // --- L040 ---
 
     #[test]
     fn l040_flags_skipped_mermaid_constructs() {
-        let input = "---\ntitle: T\ntype: doc\n---\n\n::diagram\nflowchart LR\nsubgraph One\nA --> B\nend\n::\n";
+        let input = "---\ntitle: T\ntype: doc\n---\n\n::diagram\nflowchart LR\nclassDef plain stroke-width:2px\nA --> B\nclick A cb\n::\n";
         let diags = run_rule(&MermaidConstructSkipped, input);
-        assert_eq!(codes(&diags), vec!["L040", "L040"]); // subgraph + end
+        assert_eq!(codes(&diags), vec!["L040", "L040"]); // role-less classDef + click
         assert_eq!(diags[0].severity, Severity::Info);
-        assert!(diags[0].message.contains("subgraph"));
+        assert!(diags[0].message.contains("classDef"));
         assert!(diags[0].fix.is_none());
     }
 
@@ -2966,4 +4032,377 @@ mod tests {
         let clean = "---\ntitle: T\ntype: doc\n---\n\n::diagram\nsequenceDiagram\nA->>B: hi\n::\n";
         assert!(run_rule(&MermaidConstructSkipped, clean).is_empty());
     }
+
+    // --- L041 ---
+
+    #[test]
+    fn l041_points_at_the_offending_body_line() {
+        let input = "---\ntitle: T\ntype: doc\n---\n\n::diagram[type=sequence]\nactor a: A\nactr b: B\n::\n";
+        let diags = run_rule(&DiagramParseFailed, input);
+        assert_eq!(codes(&diags), vec!["L041"]);
+        assert_eq!(diags[0].severity, Severity::Warning);
+        assert!(
+            diags[0].message.contains("(diagram line 2)"),
+            "{}",
+            diags[0].message
+        );
+        assert!(
+            diags[0]
+                .message
+                .ends_with("closest statement form: `actor id: Label`"),
+            "{}",
+            diags[0].message
+        );
+        let span = diags[0].span.expect("span");
+        assert_eq!(span.start_line, 8);
+        assert_eq!(&input[span.start_offset..span.end_offset], "actr b: B");
+        assert!(diags[0].fix.is_none());
+    }
+
+    #[test]
+    fn l041_suggests_the_edge_form_for_arrow_lines() {
+        let input = "::section\n::diagram[type=architecture]\nweb: Web\nweb => api\n::\n::\n";
+        let diags = run_rule(&DiagramParseFailed, input);
+        assert_eq!(codes(&diags), vec!["L041"]);
+        assert!(
+            diags[0].message.ends_with("`a -> b: Label`"),
+            "{}",
+            diags[0].message
+        );
+        assert_eq!(diags[0].span.map(|s| s.start_line), Some(4));
+    }
+
+    #[test]
+    fn l041_suggests_the_nearest_diagram_type() {
+        let input = "::diagram[type=flowchat]\na -> b\n::\n";
+        let diags = run_rule(&DiagramParseFailed, input);
+        assert_eq!(codes(&diags), vec!["L041"]);
+        assert!(
+            diags[0].message.ends_with("did you mean type=flowchart?"),
+            "{}",
+            diags[0].message
+        );
+        assert_eq!(diags[0].span.map(|s| s.start_line), Some(1));
+    }
+
+    #[test]
+    fn l041_silent_for_valid_bodies_and_chart_aliases() {
+        let native = "::diagram[type=flowchart]\na: Start\na -> b\n::\n";
+        assert!(run_rule(&DiagramParseFailed, native).is_empty());
+        let mermaid = "::diagram\nsequenceDiagram\nA->>B: hi\n::\n";
+        assert!(run_rule(&DiagramParseFailed, mermaid).is_empty());
+        let pie = "::diagram[type=pie]\nnot a table\n::\n";
+        assert!(run_rule(&DiagramParseFailed, pie).is_empty());
+    }
+
+    // --- L042 ---
+
+    #[test]
+    fn l042_reports_unresolved_chart_bindings() {
+        let input = "::data[id=sales]\n| month | revenue |\n| Jan | 10 |\n::\n\n::chart[type=bar data=#sales y=profit]\n::\n\n::chart[data=#missing]\n::\n\n::chart[data=#sales sort=-revenue]\n::\n";
+        let diags = run_rule(&ChartBindingUnresolved, input);
+        assert_eq!(codes(&diags), vec!["L042", "L042"]);
+        assert_eq!(diags[0].severity, Severity::Warning);
+        assert!(
+            diags[0]
+                .message
+                .ends_with("has no column \"profit\" (columns: month, revenue)"),
+            "{}",
+            diags[0].message
+        );
+        assert_eq!(diags[0].span.map(|s| s.start_line), Some(6));
+        assert!(
+            diags[1]
+                .message
+                .contains("no `::data` block with id \"missing\""),
+            "{}",
+            diags[1].message
+        );
+    }
+
+    // --- L043 ---
+
+    #[test]
+    fn l043_reports_unparseable_csl_style() {
+        let input = "---\ntitle: Paper\ncsl: |\n  <style version=\"1.0\">\n    <citation><layout><text macro=\"author\"/></layout></citation>\n  </style>\n---\n\nBody.\n";
+        let diags = run_rule(&CslStyleInvalid, input);
+        assert_eq!(codes(&diags), vec!["L043"]);
+        assert_eq!(diags[0].severity, Severity::Warning);
+        assert_eq!(
+            diags[0].message,
+            "CSL style did not parse: line 2: undefined macro \"author\""
+        );
+        assert_eq!(diags[0].span.map(|s| s.start_line), Some(5));
+        let valid =
+            "---\ncsl: |\n  <style version=\"1.0\"><citation><layout/></citation></style>\n---\n";
+        assert!(run_rule(&CslStyleInvalid, valid).is_empty());
+    }
+
+    // --- L044 / L045 ---
+
+    #[test]
+    fn l044_reports_unresolved_cross_references() {
+        let input = "## Intro {#sec:intro}\n\n::figure[src=a.png id=fig:a]\n::\n\nSee [#fig:a], [#sec:intro]\nand [#fig:b].\n";
+        let diags = run_rule(&XrefUnresolved, input);
+        assert_eq!(codes(&diags), vec!["L044"]);
+        assert_eq!(diags[0].severity, Severity::Warning);
+        assert_eq!(
+            diags[0].message,
+            "Cross-reference target not found: [#fig:b]"
+        );
+        assert_eq!(diags[0].span.map(|s| s.start_line), Some(7));
+    }
+
+    #[test]
+    fn l045_reports_duplicate_labels() {
+        let input =
+            "::data[id=t]\n| a |\n::\n\n## Totals {#t}\n\n::code[lang=rust id=t]\nfn f() {}\n::\n";
+        let diags = run_rule(&XrefDuplicateLabel, input);
+        assert_eq!(codes(&diags), vec!["L045", "L045"]);
+        assert_eq!(
+            diags[0].message,
+            "Duplicate cross-reference label 't' (first defined on line 1)"
+        );
+        assert_eq!(diags[0].span.map(|s| s.start_line), Some(5));
+        assert_eq!(diags[1].span.map(|s| s.start_line), Some(7));
+    }
+
+    /// The source text a diagnostic's span covers, and its line.
+    fn spanned<'a>(diag: &Diagnostic, input: &'a str) -> (usize, &'a str) {
+        let span = diag.span.expect("diagnostic has a span");
+        (span.start_line, &input[span.start_offset..span.end_offset])
+    }
+
+    #[test]
+    fn l044_spans_the_reference_line() {
+        let input = "First line\nsecond line here [#nope]\n";
+        let diags = run_rule(&XrefUnresolved, input);
+        assert_eq!(spanned(&diags[0], input), (2, "second line here [#nope]"));
+    }
+
+    #[test]
+    fn l044_spans_the_reference_line_in_a_container() {
+        let input = "::callout[type=note]\nFirst.\nSee [#nope] here.\n::\n";
+        let diags = run_rule(&XrefUnresolved, input);
+        assert_eq!(spanned(&diags[0], input), (3, "See [#nope] here."));
+    }
+
+    #[test]
+    fn l044_spans_the_reference_line_after_non_ascii_text() {
+        let input = "\nÜnïcødé ✓ 漢字\nPara with [#fig:a] ref\n";
+        let diags = run_rule(&XrefUnresolved, input);
+        assert_eq!(spanned(&diags[0], input), (3, "Para with [#fig:a] ref"));
+    }
+
+    #[test]
+    fn l045_spans_the_duplicate_heading_line() {
+        let input = "# Top\n\n## A {#a}\n\n## B {#a}\n";
+        let diags = run_rule(&XrefDuplicateLabel, input);
+        assert_eq!(
+            diags[0].message,
+            "Duplicate cross-reference label 'a' (first defined on line 3)"
+        );
+        assert_eq!(spanned(&diags[0], input), (5, "## B {#a}"));
+    }
+
+    #[test]
+    fn l045_spans_the_duplicate_heading_line_in_a_container() {
+        let input = "::section\n## Title\n\n## A {#a}\n\n## B {#a}\n::\n";
+        let diags = run_rule(&XrefDuplicateLabel, input);
+        assert_eq!(
+            diags[0].message,
+            "Duplicate cross-reference label 'a' (first defined on line 4)"
+        );
+        assert_eq!(spanned(&diags[0], input), (6, "## B {#a}"));
+    }
+
+    #[test]
+    fn l045_spans_the_duplicate_heading_line_after_non_ascii_text() {
+        let input = "Ünïcødé ✓ 漢字\n\n## A {#a}\n\n## B {#a}\n";
+        let diags = run_rule(&XrefDuplicateLabel, input);
+        assert_eq!(spanned(&diags[0], input), (5, "## B {#a}"));
+    }
+
+    // --- custom rules ---
+
+    fn custom(rule: &str) -> CustomRule {
+        toml::from_str(rule).expect("custom rule parses")
+    }
+
+    #[test]
+    fn custom_rule_requires_an_attribute() {
+        let rule = custom(
+            "id = \"X001\"\nblock = \"decision\"\nrequire_attrs = [\"deciders\"]\nmessage = \"::{block} must have `{attr}`\"",
+        );
+        let input = "::decision[status=accepted]\nUse Rust.\n::\n\n::decision[status=accepted deciders=\"A, B\"]\nOk.\n::\n";
+        let diags = run_rule(&rule, input);
+        assert_eq!(codes(&diags), vec!["X001"]);
+        assert_eq!(diags[0].message, "::decision must have `deciders`");
+        assert_eq!(diags[0].severity, Severity::Warning);
+        assert_eq!(diags[0].span.map(|s| s.start_line), Some(1));
+    }
+
+    #[test]
+    fn custom_rule_checks_attribute_prefix_at_the_attribute() {
+        let rule = custom(
+            "id = \"X002\"\nseverity = \"error\"\nblock = \"embed\"\nattr_prefix = { src = \"https://\" }\nmessage = \"{attr}={value} is not https\"",
+        );
+        let input = "::embed[src=\"http://a.test\"]\n\n::embed[src=\"https://b.test\"]\n";
+        let diags = run_rule(&rule, input);
+        assert_eq!(codes(&diags), vec!["X002"]);
+        assert_eq!(diags[0].message, "src=http://a.test is not https");
+        assert_eq!(diags[0].severity, Severity::Error);
+        let span = diags[0].span.expect("span");
+        assert_eq!(
+            &input[span.start_offset..span.end_offset],
+            "src=\"http://a.test\""
+        );
+    }
+
+    #[test]
+    fn custom_rule_limits_heading_depth_outside_literals() {
+        let rule = custom(
+            "id = \"X003\"\nmax_heading_depth = 3\nmessage = \"h{depth} is deeper than h{max}\"",
+        );
+        let input = "# A\n\n#### Too deep\n\n```md\n#### fenced\n```\n\n::code[lang=sh]\n##### comment\n::\n";
+        let diags = run_rule(&rule, input);
+        assert_eq!(codes(&diags), vec!["X003"]);
+        assert_eq!(diags[0].message, "h4 is deeper than h3");
+        assert_eq!(diags[0].span.map(|s| s.start_line), Some(3));
+    }
+
+    #[test]
+    fn custom_rule_front_matter_scope_requires_a_block() {
+        let rule = custom(
+            "id = \"X004\"\nfront_matter = { type = \"incident\" }\nrequire_children = [\"tasks\"]\nmessage = \"incident reports need a ::{child} block\"",
+        );
+        let incident = "---\ntitle: Outage\ntype: incident\n---\n\nWhat happened.\n";
+        let diags = run_rule(&rule, incident);
+        assert_eq!(codes(&diags), vec!["X004"]);
+        assert_eq!(diags[0].message, "incident reports need a ::tasks block");
+        let with_tasks = format!("{incident}\n::tasks\n- [ ] Fix it\n::\n");
+        assert!(run_rule(&rule, &with_tasks).is_empty());
+        let other = "---\ntitle: Plan\ntype: plan\n---\n\nNo tasks.\n";
+        assert!(run_rule(&rule, other).is_empty());
+    }
+
+    #[test]
+    fn custom_rule_children_are_direct_body_directives() {
+        let rule = custom(
+            "id = \"X005\"\nblock = \"columns\"\nforbid_children = [\"callout\"]\nmessage = \"no ::{child} in ::{block}\"",
+        );
+        let input = "::columns\n:::column\nA\n:::\n:::callout\nB\n:::\n::\n";
+        let diags = run_rule(&rule, input);
+        assert_eq!(codes(&diags), vec!["X005"]);
+        assert_eq!(diags[0].message, "no ::callout in ::columns");
+    }
+
+    #[test]
+    fn custom_rules_flow_through_check_with_and_obey_config() {
+        let input = "::decision\nUse Rust.\n::\n";
+        let mut cfg = LintConfig {
+            custom_rules: vec![custom(
+                "id = \"X001\"\nblock = \"decision\"\nrequire_attrs = [\"deciders\"]\nmessage = \"missing {attr}\"",
+            )],
+            ..LintConfig::default()
+        };
+        let report = check_with(input, &cfg);
+        assert!(codes(&report.diagnostics).contains(&"X001"));
+
+        cfg.severity_overrides
+            .insert("X001".to_string(), Severity::Error);
+        let report = check_with(input, &cfg);
+        assert!(
+            report
+                .diagnostics
+                .iter()
+                .any(|d| d.code.as_deref() == Some("X001") && d.severity == Severity::Error)
+        );
+
+        cfg.disabled_rules.insert("X001".to_string());
+        assert!(!codes(&check_with(input, &cfg).diagnostics).contains(&"X001"));
+    }
+
+    #[test]
+    fn validate_custom_rules_rejects_unusable_rules() {
+        let ok = custom(
+            "id = \"X001\"\nblock = \"decision\"\nrequire_attrs = [\"deciders\"]\nmessage = \"m\"",
+        );
+        assert!(validate_custom_rules(std::slice::from_ref(&ok)).is_ok());
+        let cases = [
+            (
+                CustomRule {
+                    id: "L001".into(),
+                    ..ok.clone()
+                },
+                "built-in",
+            ),
+            (
+                CustomRule {
+                    id: "V999".into(),
+                    ..ok.clone()
+                },
+                "built-in",
+            ),
+            (
+                CustomRule {
+                    block: None,
+                    ..ok.clone()
+                },
+                "names no `block`",
+            ),
+            (
+                CustomRule {
+                    require_attrs: Vec::new(),
+                    ..ok.clone()
+                },
+                "no predicate",
+            ),
+            (
+                CustomRule {
+                    message: " ".into(),
+                    ..ok.clone()
+                },
+                "empty message",
+            ),
+        ];
+        for (rule, expected) in cases {
+            let err = validate_custom_rules(&[rule]).unwrap_err();
+            assert!(err.contains(expected), "{err}");
+        }
+        let err = validate_custom_rules(&[ok.clone(), ok]).unwrap_err();
+        assert!(err.contains("twice"), "{err}");
+    }
+
+    #[test]
+    fn check_with_reports_invalid_custom_rules_instead_of_running_them() {
+        let input = "::decision\nUse Rust.\n::\n";
+        let ok = custom(
+            "id = \"X001\"\nblock = \"decision\"\nrequire_attrs = [\"deciders\"]\nmessage = \"m\"",
+        );
+        let cfg = LintConfig {
+            custom_rules: vec![
+                ok.clone(),
+                CustomRule {
+                    id: "L001".into(),
+                    ..ok
+                },
+            ],
+            ..LintConfig::default()
+        };
+        let report = check_with(input, &cfg);
+        assert!(!codes(&report.diagnostics).contains(&"X001"));
+        let invalid: Vec<_> = report
+            .diagnostics
+            .iter()
+            .filter(|d| d.message.starts_with("invalid custom rules:"))
+            .collect();
+        assert_eq!(invalid.len(), 1);
+        assert_eq!(invalid[0].severity, Severity::Error);
+        assert!(
+            invalid[0].message.contains("'L001'"),
+            "{}",
+            invalid[0].message
+        );
+    }
 }
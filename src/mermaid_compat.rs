@@ -4,13 +4,15 @@
 //! mermaid header (`flowchart LR`, `sequenceDiagram`, `erDiagram`, …) — or
 //! the block says `type=mermaid` explicitly — the body is translated to the
 //! native diagram DSL and rendered through the normal geometry pipeline.
-//! Thirteen mermaid families are accepted: flowchart/graph, sequence, class,
+//! Twenty mermaid families are accepted: flowchart/graph, sequence, class,
 //! state, er, gantt, mindmap, pie (via the chart pipeline), timeline,
-//! journey, kanban, quadrant and gitGraph.
+//! journey, kanban, quadrant, gitGraph, block, packet, architecture,
+//! requirement, C4, sankey and xychart (via the chart pipeline).
 //!
-//! Translation covers the common core of each family. Constructs outside
-//! that core (subgraphs, notes, loop/alt frames, styling directives, …)
-//! degrade per line: the line is skipped and recorded as a [`MermaidNote`],
+//! Translation covers the common core of each family (flowchart subgraphs
+//! become native `group` blocks, sequence notes and `loop`/`alt`/… frames
+//! become native notes and fragments). Constructs outside that core (nested
+//! subgraphs, participant boxes, styling directives, …) degrade per line: the line is skipped and recorded as a [`MermaidNote`],
 //! which the `L040` lint surfaces. A body that yields no statements at all
 //! translates to nothing (`None`) and falls back to prose like any other
 //! unparseable diagram — a diagram must NEVER fail a render.
@@ -19,6 +21,8 @@
 //! text; the emitted native DSL is rendered by [`crate::diagram`] under its
 //! usual byte-stability guarantees.
 
+use crate::types::DiagramDirection;
+
 /// A skipped mermaid construct: the 1-based line number within the diagram
 /// body and a short description of what was skipped.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -28,12 +32,14 @@ pub(crate) struct MermaidNote {
 }
 
 /// The result of translating a mermaid body: the native diagram type (or
-/// chart-alias type for `pie`), the native DSL body, and per-line notes for
-/// every construct the translation skipped.
+/// chart-alias type for `pie` and `xychart`), the native DSL body, the layout direction
+/// the header asked for, and per-line notes for every construct the
+/// translation skipped.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct MermaidTranslation {
     pub(crate) diagram_type: &'static str,
     pub(crate) content: String,
+    pub(crate) direction: Option<DiagramDirection>,
     pub(crate) notes: Vec<MermaidNote>,
 }
 
@@ -53,6 +59,13 @@ enum Family {
     Kanban,
     Quadrant,
     GitGraph,
+    Block,
+    Packet,
+    Architecture,
+    Requirement,
+    C4,
+    Sankey,
+    XyChart,
 }
 
 /// Translate a mermaid `::diagram` body to the native DSL.
@@ -82,6 +95,13 @@ pub(crate) fn translate(diagram_type: &str, content: &str) -> Option<MermaidTran
         Family::Kanban => kanban(&lines, header_idx, &mut notes),
         Family::Quadrant => quadrant(&lines, header_idx, &mut notes),
         Family::GitGraph => gitgraph(&lines, header_idx, &mut notes),
+        Family::Block => block(&lines, header_idx, &mut notes),
+        Family::Packet => packet(&lines, header_idx, &mut notes),
+        Family::Architecture => architecture(&lines, header_idx, &mut notes),
+        Family::Requirement => requirement(&lines, header_idx, &mut notes),
+        Family::C4 => c4(&lines, header_idx, &mut notes),
+        Family::Sankey => sankey(&lines, header_idx, &mut notes),
+        Family::XyChart => xychart(&lines, header_idx, &mut notes),
     };
     if body.trim().is_empty() {
         return None; // nothing translated — prose fallback shows the source
@@ -101,12 +121,33 @@ pub(crate) fn translate(diagram_type: &str, content: &str) -> Option<MermaidTran
             Family::Kanban => "kanban",
             Family::Quadrant => "quadrant",
             Family::GitGraph => "gitgraph",
+            Family::Block => "block",
+            Family::Packet => "packet",
+            Family::Architecture => "architecture",
+            Family::Requirement => "requirement",
+            Family::C4 => "c4",
+            Family::Sankey => "sankey",
+            Family::XyChart => "xychart",
         },
         content: body,
+        direction: match family {
+            Family::Flowchart => header_direction(lines[header_idx]),
+            _ => None,
+        },
         notes,
     })
 }
 
+/// The layout direction of a `flowchart LR` / `graph TD` header. The native
+/// layout has no reversed axes, so `RL` lays out as `LR` and `BT` as `TB`.
+fn header_direction(header: &str) -> Option<DiagramDirection> {
+    match header.split_whitespace().nth(1)? {
+        "LR" | "RL" => Some(DiagramDirection::LeftRight),
+        "TB" | "TD" | "BT" => Some(DiagramDirection::TopBottom),
+        _ => None,
+    }
+}
+
 /// Find the mermaid header: the first significant line (skipping blanks,
 /// `%%` comments and a leading `---` front-matter fence) must match a known
 /// family header. Returns the family and the header's line index.
@@ -155,6 +196,16 @@ fn header_family(line: &str) -> Option<Family> {
         "kanban" if line == "kanban" => Some(Family::Kanban),
         "quadrantChart" => Some(Family::Quadrant),
         "gitGraph" => Some(Family::GitGraph),
+        "block-beta" if line == "block-beta" => Some(Family::Block),
+        "packet-beta" | "packet" if line == word => Some(Family::Packet),
+        "architecture-beta" if line == "architecture-beta" => Some(Family::Architecture),
+        "requirementDiagram" if line == "requirementDiagram" => Some(Family::Requirement),
+        "C4Context" | "C4Container" | "C4Component" if line == word => Some(Family::C4),
+        "sankey-beta" | "sankey" if line == word => Some(Family::Sankey),
+        "xychart-beta" | "xychart" => {
+            let rest = line[word.len()..].trim();
+            matches!(rest, "" | "vertical" | "horizontal").then_some(Family::XyChart)
+        }
         _ => None,
     }
 }
@@ -239,41 +290,39 @@ const FLOW_BRACKETS: &[(&str, &str, &str)] = &[
     (">", "]", "box"),
 ];
 
-/// One parsed flowchart node term: id, optional (label, shape).
+/// One parsed flowchart node term: id, optional (label, shape), and an
+/// optional `:::class` shorthand.
 struct FlowTerm {
     id: String,
     decl: Option<(String, &'static str)>,
+    class: Option<String>,
 }
 
-/// Parse a node term (`A`, `A[Label]`, `B{Choice?}`, …) off the front of
-/// `s`. Returns the term and the remainder.
+/// Parse a node term (`A`, `A[Label]`, `B{Choice?}:::hot`, …) off the front
+/// of `s`. Returns the term and the remainder.
 fn take_flow_term(s: &str) -> Option<(FlowTerm, &str)> {
     let s = s.trim_start();
-    let (id, rest) = take_id(s);
+    let (id, mut rest) = take_id(s);
     if id.is_empty() {
         return None;
     }
+    let mut decl = None;
     for (open, close, shape) in FLOW_BRACKETS {
         if let Some(inner) = rest.strip_prefix(open) {
             if let Some(pos) = inner.find(close) {
-                let label = clean_label(&inner[..pos]);
-                return Some((
-                    FlowTerm {
-                        id: safe_id(id),
-                        decl: Some((label, shape)),
-                    },
-                    &inner[pos + close.len()..],
-                ));
+                decl = Some((clean_label(&inner[..pos]), *shape));
+                rest = &inner[pos + close.len()..];
+                break;
             }
         }
     }
-    Some((
-        FlowTerm {
-            id: safe_id(id),
-            decl: None,
-        },
-        rest,
-    ))
+    let mut class = None;
+    if let Some(after) = rest.strip_prefix(":::") {
+        let (name, after_name) = take_id(after);
+        class = Some(name.to_string()).filter(|c| !c.is_empty());
+        rest = after_name;
+    }
+    Some((FlowTerm { id: safe_id(id), decl, class }, rest))
 }
 
 /// Parse an arrow/link token off the front of `s`. Returns the optional
@@ -348,19 +397,52 @@ fn take_pipe_label(s: &str) -> (Option<String>, &str) {
     (None, s)
 }
 
+/// Parse a `subgraph` header remainder into `(id, label)`: `id [Label]`,
+/// `id["Label"]`, `"Label"` or bare `Some title` (the id is derived from
+/// the title). `None` for an empty header.
+fn subgraph_header(rest: &str) -> Option<(String, String)> {
+    let rest = rest.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    if let Some(open) = rest.find('[')
+        && let Some(inner) = rest[open + 1..].strip_suffix(']')
+    {
+        let id = rest[..open].trim();
+        let label = clean_label(inner);
+        let id = if id.is_empty() { safe_id(&label) } else { safe_id(id) };
+        return Some((id, label));
+    }
+    let label = clean_label(rest);
+    Some((safe_id(&label), label))
+}
+
 fn flowchart(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> String {
     // Nodes in first-reference order; declarations update label/shape.
-    let mut nodes: Vec<(String, Option<(String, &'static str)>)> = Vec::new();
+    // The group is the first subgraph that references the node, even when
+    // the node was seen outside one before.
+    type NodeEntry = (String, Option<(String, &'static str)>, Option<usize>);
+    let mut nodes: Vec<NodeEntry> = Vec::new();
     let mut edges: Vec<(String, String, Option<String>)> = Vec::new();
-
-    let register = |nodes: &mut Vec<(String, Option<(String, &'static str)>)>, term: FlowTerm| {
-        match nodes.iter_mut().find(|(id, _)| *id == term.id) {
-            Some((_, decl)) => {
+    // Subgraphs `(id, label)` in declaration order, and the open-subgraph
+    // stack (`None` entries are nested subgraphs folded into their parent).
+    let mut groups: Vec<(String, String)> = Vec::new();
+    let mut open: Vec<Option<usize>> = Vec::new();
+    let mut styling = Styling::default();
+    let mut clicks: Vec<(String, String, Option<String>, usize)> = Vec::new();
+
+    let register = |nodes: &mut Vec<NodeEntry>, styling: &mut Styling, term: FlowTerm, group: Option<usize>, idx: usize| {
+        styling.shorthand(&term.id, term.class.as_deref(), idx);
+        match nodes.iter_mut().find(|(id, _, _)| *id == term.id) {
+            Some((_, decl, node_group)) => {
                 if term.decl.is_some() {
                     *decl = term.decl;
                 }
+                if node_group.is_none() {
+                    *node_group = group;
+                }
             }
-            None => nodes.push((term.id, term.decl)),
+            None => nodes.push((term.id, term.decl, group)),
         }
     };
 
@@ -369,14 +451,47 @@ fn flowchart(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> Str
         if is_noise(line) {
             continue;
         }
-        if keyword(line, "subgraph").is_some() || line == "end" {
-            note(notes, idx, "subgraph");
+        // Subgraphs become native groups. The native DSL has one nesting
+        // level, so a nested subgraph's members join the outermost one.
+        if let Some(rest) = keyword(line, "subgraph") {
+            if !open.is_empty() {
+                note(notes, idx, "nested subgraph");
+                open.push(None);
+                continue;
+            }
+            let Some((id, label)) = subgraph_header(rest) else {
+                note(notes, idx, "subgraph");
+                open.push(None);
+                continue;
+            };
+            let g = match groups.iter().position(|(gid, _)| *gid == id) {
+                Some(g) => g,
+                None => {
+                    groups.push((id, label));
+                    groups.len() - 1
+                }
+            };
+            open.push(Some(g));
+            continue;
+        }
+        if line == "end" {
+            if open.pop().is_none() {
+                note(notes, idx, "subgraph");
+            }
             continue;
         }
-        if ["direction", "classDef", "class", "style", "linkStyle", "click"]
-            .iter()
-            .any(|kw| keyword(line, kw).is_some())
-        {
+        let group = open.first().copied().flatten();
+        if styling.directive(line, idx, notes) {
+            continue;
+        }
+        if let Some(rest) = keyword(line, "click") {
+            match click_link(rest) {
+                Some((id, url, tooltip)) => clicks.push((id, url, tooltip, idx)),
+                None => note(notes, idx, "click"),
+            }
+            continue;
+        }
+        if keyword(line, "direction").is_some() {
             note(notes, idx, line.split_whitespace().next().unwrap_or("directive"));
             continue;
         }
@@ -391,7 +506,7 @@ fn flowchart(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> Str
             continue;
         };
         let mut prev = first.id.clone();
-        register(&mut nodes, first);
+        register(&mut nodes, &mut styling, first, group, idx);
         let mut parsed_chain = true;
         loop {
             let t = rest.trim_start();
@@ -412,7 +527,7 @@ fn flowchart(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> Str
             };
             edges.push((prev.clone(), term.id.clone(), label.filter(|l| !l.is_empty())));
             prev = term.id.clone();
-            register(&mut nodes, term);
+            register(&mut nodes, &mut styling, term, group, idx);
             rest = after_term;
         }
         if !parsed_chain {
@@ -420,15 +535,40 @@ fn flowchart(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> Str
         }
     }
 
-    let mut out = String::new();
-    for (id, decl) in &nodes {
-        if let Some((label, shape)) = decl {
-            let label = if label.is_empty() { id.as_str() } else { label.as_str() };
+    // A node line: its declaration, or a bare member id inside a group.
+    let node_line = |id: &str, decl: &Option<(String, &'static str)>| match decl {
+        Some((label, shape)) => {
+            let label = if label.is_empty() { id } else { label.as_str() };
             if *shape == "box" {
-                out.push_str(&format!("{id}: {label}\n"));
+                format!("{id}: {label}\n")
             } else {
-                out.push_str(&format!("{id} [{shape}]: {label}\n"));
+                format!("{id} [{shape}]: {label}\n")
+            }
+        }
+        None => format!("{id}\n"),
+    };
+
+    // Each group is emitted whole at its first member's position, so node
+    // order stays first-reference order and members stay contiguous.
+    let mut out = String::new();
+    let mut emitted = vec![false; groups.len()];
+    for (id, decl, group) in &nodes {
+        match group {
+            None => {
+                if decl.is_some() {
+                    out.push_str(&node_line(id, decl));
+                }
+            }
+            Some(g) if !emitted[*g] => {
+                emitted[*g] = true;
+                let (gid, label) = &groups[*g];
+                out.push_str(&format!("group {gid}: {label} {{\n"));
+                for (member, member_decl, _) in nodes.iter().filter(|n| n.2 == Some(*g)) {
+                    out.push_str(&format!("  {}", node_line(member, member_decl)));
+                }
+                out.push_str("}\n");
             }
+            Some(_) => {}
         }
     }
     for (from, to, label) in &edges {
@@ -437,9 +577,271 @@ fn flowchart(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> Str
             None => out.push_str(&format!("{from} -> {to}\n")),
         }
     }
+    // Undeclared nodes outside groups exist natively only as edge endpoints.
+    let exists = |id: &str| {
+        nodes.iter().any(|(n, decl, group)| n == id && (decl.is_some() || group.is_some()))
+            || edges.iter().any(|(from, to, _)| from == id || to == id)
+    };
+    let links: Vec<(String, String)> = edges.iter().map(|(from, to, _)| (from.clone(), to.clone())).collect();
+    out.push_str(&styling.emit(exists, &links, notes));
+    for (id, url, tooltip, idx) in &clicks {
+        if !exists(id) {
+            note(notes, *idx, "click");
+            continue;
+        }
+        match tooltip {
+            Some(t) => out.push_str(&format!("link {id}: {url} \"{t}\"\n")),
+            None => out.push_str(&format!("link {id}: {url}\n")),
+        }
+    }
     out
 }
 
+/// Parse a `click` remainder in its url form (`A "url" "tooltip"` or
+/// `A href "url" "tooltip" _blank`) into `(id, url, tooltip)`. Callback
+/// forms and urls the native `link` statement would reject yield `None`.
+/// The link target (`_blank`, …) has no native equivalent and is dropped.
+fn click_link(rest: &str) -> Option<(String, String, Option<String>)> {
+    let (id, after) = take_id(rest.trim());
+    if id.is_empty() {
+        return None;
+    }
+    let after = after.trim_start();
+    let after = keyword(after, "href").unwrap_or(after);
+    let (url, after) = after.strip_prefix('"')?.split_once('"')?;
+    if url.is_empty() || !crate::diagram::is_safe_link(url) {
+        return None;
+    }
+    let after = after.trim_start();
+    let tooltip = after
+        .strip_prefix('"')
+        .and_then(|t| t.split_once('"'))
+        .map(|(t, _)| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+    Some((id.to_string(), url.to_string(), tooltip))
+}
+
+// ------------------------------------------------------------------
+// styling (flowchart, state)
+// ------------------------------------------------------------------
+
+/// Class-name fragments that pick an emphasis outright, checked in order
+/// (`inactive` must read as muted before `active` reads as highlight).
+const EMPHASIS_NAMES: &[(&str, &[&str])] = &[
+    ("muted", &["muted", "disabled", "inactive", "dim", "fade", "skip", "deprecated", "ghost"]),
+    ("danger", &["danger", "error", "fail", "crit", "alert", "bad"]),
+    ("success", &["success", "done", "pass", "complete", "good"]),
+    ("highlight", &["highlight", "important", "active", "focus", "primary", "warn", "hot", "current"]),
+];
+
+/// CSS color names common in mermaid styles, as `#rrggbb`.
+const NAMED_COLORS: &[(&str, &str)] = &[
+    ("red", "#ff0000"),
+    ("darkred", "#8b0000"),
+    ("crimson", "#dc143c"),
+    ("tomato", "#ff6347"),
+    ("orange", "#ffa500"),
+    ("gold", "#ffd700"),
+    ("yellow", "#ffff00"),
+    ("green", "#008000"),
+    ("lime", "#00ff00"),
+    ("lightgreen", "#90ee90"),
+    ("darkgreen", "#006400"),
+    ("blue", "#0000ff"),
+    ("purple", "#800080"),
+    ("pink", "#ffc0cb"),
+    ("gray", "#808080"),
+    ("grey", "#808080"),
+    ("lightgray", "#d3d3d3"),
+    ("lightgrey", "#d3d3d3"),
+    ("silver", "#c0c0c0"),
+    ("white", "#ffffff"),
+    ("black", "#000000"),
+];
+
+/// Parse `#rgb`, `#rrggbb` or a [`NAMED_COLORS`] name into `(r, g, b)`.
+fn parse_color(value: &str) -> Option<(i64, i64, i64)> {
+    let value = value.trim().to_ascii_lowercase();
+    let hex = match NAMED_COLORS.iter().find(|(name, _)| *name == value) {
+        Some((_, hex)) => hex.to_string(),
+        None => value,
+    };
+    let digits = hex.strip_prefix('#')?;
+    let channel = |s: &str| i64::from_str_radix(s, 16).ok();
+    match digits.len() {
+        3 => {
+            let c: Vec<i64> = digits.chars().map(|d| d.to_digit(16).map(|v| v as i64 * 17)).collect::<Option<_>>()?;
+            Some((c[0], c[1], c[2]))
+        }
+        6 => Some((channel(&digits[0..2])?, channel(&digits[2..4])?, channel(&digits[4..6])?)),
+        _ => None,
+    }
+}
+
+/// Emphasis for a color: greys are muted, otherwise by hue — reds are
+/// danger, greens success, and everything else (amber through blue and
+/// pink) highlight.
+fn color_emphasis((r, g, b): (i64, i64, i64)) -> &'static str {
+    let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+    let d = max - min;
+    if d < 48 {
+        return "muted";
+    }
+    let hue = if max == r {
+        (60 * (g - b) / d).rem_euclid(360)
+    } else if max == g {
+        120 + 60 * (b - r) / d
+    } else {
+        240 + 60 * (r - g) / d
+    };
+    match hue {
+        0..15 | 330.. => "danger",
+        75..170 => "success",
+        _ => "highlight",
+    }
+}
+
+/// Semantic emphasis for a mermaid class or `style` line: by class name
+/// first (`error`, `done`, …), then by the color of its `stroke`, `fill` or
+/// `color` property. Hard-coded colors never reach the output — only the
+/// role does, so themes stay in charge.
+fn emphasis_for(name: &str, props: &str) -> Option<&'static str> {
+    let lower = name.to_ascii_lowercase();
+    if let Some((emphasis, _)) = EMPHASIS_NAMES.iter().find(|(_, words)| words.iter().any(|w| lower.contains(w))) {
+        return Some(emphasis);
+    }
+    let props: Vec<(&str, &str)> = props.split(',').filter_map(|p| p.split_once(':')).collect();
+    ["stroke", "fill", "color"].iter().find_map(|key| {
+        let (_, value) = props.iter().find(|(k, _)| k.trim() == *key)?;
+        parse_color(value).map(color_emphasis)
+    })
+}
+
+/// Split a `:::class` shorthand off a node reference: `(node, class)`.
+fn strip_class(s: &str) -> (&str, Option<&str>) {
+    match s.split_once(":::") {
+        Some((node, class)) => {
+            let (class, _) = take_id(class);
+            (node, Some(class).filter(|c| !c.is_empty()))
+        }
+        None => (s, None),
+    }
+}
+
+/// Mermaid styling collected while translating a flowchart or state body,
+/// resolved to native `style` lines once every node and link is known.
+#[derive(Default)]
+struct Styling {
+    /// `classDef` name → emphasis (`None` when nothing maps).
+    classes: Vec<(String, Option<&'static str>)>,
+    /// `(node, class, line)` from `class` lines and `:::` shorthand.
+    node_classes: Vec<(String, String, usize)>,
+    /// Direct node emphasis from `style` lines; applied after classes.
+    node_styles: Vec<(String, &'static str)>,
+    /// `(link index, emphasis, line)` from `linkStyle` lines.
+    link_styles: Vec<(usize, &'static str, usize)>,
+}
+
+impl Styling {
+    /// Record a `classDef` / `class` / `style` / `linkStyle` line. Returns
+    /// `false` when `line` is not a styling directive.
+    fn directive(&mut self, line: &str, idx: usize, notes: &mut Vec<MermaidNote>) -> bool {
+        if let Some(rest) = keyword(line, "classDef") {
+            let (names, props) = rest.trim().split_once(char::is_whitespace).unwrap_or((rest.trim(), ""));
+            for name in names.split(',').map(str::trim) {
+                let emphasis = emphasis_for(name, props);
+                if name == "default" || emphasis.is_none() {
+                    note(notes, idx, format!("classDef {name} (no emphasis role)"));
+                }
+                if name != "default" {
+                    self.classes.push((name.to_string(), emphasis));
+                }
+            }
+        } else if let Some(rest) = keyword(line, "class") {
+            let rest = rest.trim();
+            let Some((ids, class)) = rest.rsplit_once(char::is_whitespace) else {
+                note(notes, idx, "class");
+                return true;
+            };
+            for id in ids.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+                self.node_classes.push((safe_id(id), class.trim().to_string(), idx));
+            }
+        } else if let Some(rest) = keyword(line, "style") {
+            let (id, props) = rest.trim().split_once(char::is_whitespace).unwrap_or((rest.trim(), ""));
+            // A style carries no class name, so only its colors count.
+            match emphasis_for("", props) {
+                Some(emphasis) => self.node_styles.push((safe_id(id), emphasis)),
+                None => note(notes, idx, "style (no emphasis role)"),
+            }
+        } else if let Some(rest) = keyword(line, "linkStyle") {
+            let (indices, props) = rest.trim().split_once(char::is_whitespace).unwrap_or((rest.trim(), ""));
+            let Some(emphasis) = emphasis_for("", props) else {
+                note(notes, idx, "linkStyle (no emphasis role)");
+                return true;
+            };
+            for index in indices.split(',') {
+                match index.trim().parse::<usize>() {
+                    Ok(k) => self.link_styles.push((k, emphasis, idx)),
+                    Err(_) => note(notes, idx, format!("linkStyle {}", index.trim())),
+                }
+            }
+        } else {
+            return false;
+        }
+        true
+    }
+
+    /// Record a `:::class` shorthand on node `id`.
+    fn shorthand(&mut self, id: &str, class: Option<&str>, idx: usize) {
+        if let Some(class) = class {
+            self.node_classes.push((id.to_string(), class.to_string(), idx));
+        }
+    }
+
+    /// Native `style` lines: one per emphasis for nodes (in first-styled
+    /// order), then one per styled link. `exists` says whether the native
+    /// body declares a node; `links` are the translated links in mermaid
+    /// order, which `linkStyle` indexes.
+    fn emit(&self, exists: impl Fn(&str) -> bool, links: &[(String, String)], notes: &mut Vec<MermaidNote>) -> String {
+        let mut styled: Vec<(String, &'static str)> = Vec::new();
+        let mut set = |id: &str, emphasis: &'static str| match styled.iter_mut().find(|(n, _)| n == id) {
+            Some(entry) => entry.1 = emphasis,
+            None => styled.push((id.to_string(), emphasis)),
+        };
+        for (id, class, idx) in &self.node_classes {
+            match self.classes.iter().rev().find(|(name, _)| name == class) {
+                Some((_, Some(emphasis))) if exists(id) => set(id, emphasis),
+                Some((_, Some(_))) => {}
+                Some((_, None)) => {} // already noted at the classDef
+                None => note(notes, *idx, format!("class {class} (no classDef)")),
+            }
+        }
+        for (id, emphasis) in &self.node_styles {
+            if exists(id) {
+                set(id, emphasis);
+            }
+        }
+
+        let mut out = String::new();
+        let mut done: Vec<&str> = Vec::new();
+        for (_, emphasis) in &styled {
+            if done.contains(emphasis) {
+                continue;
+            }
+            done.push(emphasis);
+            let ids: Vec<&str> = styled.iter().filter(|(_, e)| e == emphasis).map(|(id, _)| id.as_str()).collect();
+            out.push_str(&format!("style {}: {emphasis}\n", ids.join(", ")));
+        }
+        for (k, emphasis, idx) in &self.link_styles {
+            match links.get(*k) {
+                Some((from, to)) => out.push_str(&format!("style {from} -> {to}: {emphasis}\n")),
+                None => note(notes, *idx, format!("linkStyle {k} (no such link)")),
+            }
+        }
+        out
+    }
+}
+
 // ------------------------------------------------------------------
 // sequenceDiagram
 // ------------------------------------------------------------------
@@ -457,8 +859,29 @@ const SEQ_ARROWS: &[(&str, bool)] = &[
     ("->", false),
 ];
 
+/// Mermaid fragment openers that map one-to-one onto native fragments.
+const SEQ_FRAGMENTS: &[&str] = &["loop", "alt", "opt", "par", "critical", "break", "rect"];
+
+/// Translate a mermaid `Note …` remainder (`right of A: text`,
+/// `over A,B: text`) to the native note line.
+fn sequence_note(rest: &str) -> Option<String> {
+    let (targets, text) = rest.split_once(':')?;
+    let targets = targets.trim();
+    let lower = targets.to_ascii_lowercase();
+    let placement = ["left of", "right of", "over"]
+        .into_iter()
+        .find(|p| lower.starts_with(p))?;
+    let ids: Vec<String> = targets[placement.len()..]
+        .split(',')
+        .map(|id| safe_id(unquote(id)))
+        .collect();
+    Some(format!("note {placement} {}: {}\n", ids.join(", "), clean_label(text)))
+}
+
 fn sequence(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> String {
     let mut out = String::new();
+    // Open fragments: native braces to close on `end` (or at end of body).
+    let mut depth = 0usize;
 
     for (idx, raw) in lines.iter().enumerate().skip(header + 1) {
         let line = raw.trim();
@@ -492,11 +915,43 @@ fn sequence(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> Stri
             continue;
         }
         let lower = line.to_ascii_lowercase();
-        if ["note", "loop", "alt", "opt", "par", "and", "else", "end", "rect", "box", "critical", "break"]
+        let word = lower.split_whitespace().next().unwrap_or("frame");
+        if word == "note"
+            && let Some(native) = sequence_note(&line[4..])
+        {
+            out.push_str(&native);
+            continue;
+        }
+        if SEQ_FRAGMENTS.contains(&word) {
+            // `rect rgb(…)` carries a color, not a guard.
+            let guard = if word == "rect" { String::new() } else { clean_label(&line[word.len()..]) };
+            if guard.is_empty() {
+                out.push_str(&format!("{word} {{\n"));
+            } else {
+                out.push_str(&format!("{word} {guard} {{\n"));
+            }
+            depth += 1;
+            continue;
+        }
+        if ["else", "and", "option"].contains(&word) && depth > 0 {
+            let guard = clean_label(&line[word.len()..]);
+            if guard.is_empty() {
+                out.push_str(&format!("{word}\n"));
+            } else {
+                out.push_str(&format!("{word} {guard}\n"));
+            }
+            continue;
+        }
+        if lower == "end" && depth > 0 {
+            out.push_str("}\n");
+            depth -= 1;
+            continue;
+        }
+        if ["note", "and", "else", "option", "end", "box"]
             .iter()
             .any(|kw| keyword(&lower, kw).is_some() || lower == *kw)
         {
-            note(notes, idx, lower.split_whitespace().next().unwrap_or("frame").to_string());
+            note(notes, idx, word.to_string());
             continue;
         }
 
@@ -544,6 +999,10 @@ fn sequence(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> Stri
         }
     }
 
+    // Mermaid rejects unclosed fragments; close them so the diagram renders.
+    for _ in 0..depth {
+        out.push_str("}\n");
+    }
     out
 }
 
@@ -748,6 +1207,9 @@ fn class(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> String
 
 fn state(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> String {
     let mut out = String::new();
+    let mut styling = Styling::default();
+    // Native state ids in emission order; `style` lines may only name these.
+    let mut ids: Vec<String> = Vec::new();
 
     for (idx, raw) in lines.iter().enumerate().skip(header + 1) {
         let line = raw.trim();
@@ -757,17 +1219,25 @@ fn state(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> String
         if line == "}" || line == "--" {
             continue; // composite close / concurrency separator
         }
+        // State diagrams have no `linkStyle`; transitions keep the plain stroke.
+        if keyword(line, "linkStyle").is_none() && styling.directive(line, idx, notes) {
+            continue;
+        }
         if let Some(rest) = keyword(line, "state") {
             // `state "Description" as s1` → a native state declaration.
             if let Some((desc, id)) = rest.split_once(" as ") {
-                out.push_str(&format!("{}: {}\n", safe_id(id), clean_label(desc)));
+                let (id, class) = strip_class(id.trim());
+                let id = safe_id(id);
+                styling.shorthand(&id, class, idx);
+                out.push_str(&format!("{id}: {}\n", clean_label(desc)));
+                ids.push(id);
                 continue;
             }
             // `state X {` opens a composite — flatten its transitions.
             note(notes, idx, "composite state");
             continue;
         }
-        if keyword(line, "note").is_some() || keyword(line, "direction").is_some() {
+        if ["note", "direction", "linkStyle"].iter().any(|kw| keyword(line, kw).is_some()) {
             note(notes, idx, line.split_whitespace().next().unwrap_or("directive"));
             continue;
         }
@@ -776,20 +1246,31 @@ fn state(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> String
             continue;
         }
 
-        // Transition: `A --> B : ev` (also `[*]` endpoints).
+        // Transition: `A --> B : ev` (also `[*]` endpoints and `:::class`
+        // shorthand on either state).
         if let Some(pos) = line.find("-->") {
-            let from = line[..pos].trim();
+            let (from, from_class) = strip_class(line[..pos].trim());
             let rest = line[pos + 3..].trim();
-            let (to, label) = match rest.split_once(':') {
-                Some((t, l)) => (t.trim(), Some(clean_label(l))),
-                None => (rest, None),
+            let (to, to_class, label) = match rest.split_once(":::") {
+                Some((to, after)) => {
+                    let (class, after_class) = take_id(after);
+                    let label = after_class.trim_start().strip_prefix(':').map(clean_label);
+                    (to.trim(), Some(class).filter(|c| !c.is_empty()), label)
+                }
+                None => match rest.split_once(':') {
+                    Some((t, l)) => (t.trim(), None, Some(clean_label(l))),
+                    None => (rest, None, None),
+                },
             };
-            let from_t = if from == "[*]" { "[*]".to_string() } else { safe_id(from) };
+            let from_t = if from.trim() == "[*]" { "[*]".to_string() } else { safe_id(from.trim()) };
             let to_t = if to == "[*]" { "[*]".to_string() } else { safe_id(to) };
+            styling.shorthand(&from_t, from_class, idx);
+            styling.shorthand(&to_t, to_class, idx);
             match label.filter(|l| !l.is_empty()) {
                 Some(l) => out.push_str(&format!("{from_t} -> {to_t}: {l}\n")),
                 None => out.push_str(&format!("{from_t} -> {to_t}\n")),
             }
+            ids.extend([from_t, to_t].into_iter().filter(|id| id != "[*]"));
             continue;
         }
 
@@ -798,6 +1279,18 @@ fn state(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> String
             let (name, rest) = take_id(id.trim());
             if !name.is_empty() && rest.trim().is_empty() {
                 out.push_str(&format!("{}: {}\n", safe_id(name), clean_label(label)));
+                ids.push(safe_id(name));
+                continue;
+            }
+        }
+        // Bare state with a class: `A:::class`.
+        if let (id, Some(class)) = strip_class(line) {
+            let (name, rest) = take_id(id.trim());
+            if !name.is_empty() && rest.trim().is_empty() {
+                let id = safe_id(name);
+                styling.shorthand(&id, Some(class), idx);
+                out.push_str(&format!("{id}: {name}\n"));
+                ids.push(id);
                 continue;
             }
         }
@@ -805,6 +1298,7 @@ fn state(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> String
         note(notes, idx, "unrecognized statement");
     }
 
+    out.push_str(&styling.emit(|id| ids.iter().any(|n| n == id), &[], notes));
     out
 }
 
@@ -977,27 +1471,41 @@ fn gantt(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> String
             continue;
         }
 
-        // Task: `Label : [tags,] [id,] start, duration`.
+        // Task: `Label : [tags,] [id,] start, duration` — the native form
+        // takes the same fields, so tags, ids and `after` starts carry over.
         let Some((label, spec)) = line.split_once(':') else {
             note(notes, idx, "unrecognized statement");
             continue;
         };
         let label = label.trim();
-        let tokens: Vec<&str> = spec.split(',').map(str::trim).filter(|t| !t.is_empty()).collect();
-        if tokens.iter().any(|t| t.starts_with("after ") || *t == "after" || t.starts_with("until ")) {
-            note(notes, idx, "`after`/`until` dependency");
+        let mut tokens: Vec<&str> = spec.split(',').map(str::trim).filter(|t| !t.is_empty()).collect();
+        if tokens.iter().any(|t| t.starts_with("until ")) {
+            note(notes, idx, "`until` dependency");
             continue;
         }
-        let milestone = tokens.contains(&"milestone");
-        let start = tokens.iter().find(|t| is_iso_date(t));
-        let duration = tokens
-            .iter()
-            .filter(|t| !is_iso_date(t))
-            .find_map(|t| gantt_duration(t));
-        match (start, duration) {
-            (Some(start), Some(d)) => out.push_str(&format!("{label}: {start}, {d}\n")),
-            (Some(start), None) if milestone => out.push_str(&format!("{label}: {start}, 0\n")),
-            _ => note(notes, idx, "task without a date + duration"),
+        let mut tags: Vec<&str> = Vec::new();
+        while let Some(&tag) = tokens.first()
+            && ["done", "active", "crit", "milestone"].contains(&tag)
+        {
+            tags.push(tag);
+            tokens.remove(0);
+        }
+        let milestone = tags.contains(&"milestone");
+        let is_start = |t: &str| is_iso_date(t) || t.starts_with("after ");
+        let (id, start, duration) = match tokens[..] {
+            [start, dur] if is_start(start) => (None, start, gantt_duration(dur)),
+            [id, start, dur] if is_start(start) => (Some(id), start, gantt_duration(dur)),
+            [id, start] if milestone && is_start(start) => (Some(id), start, None),
+            [start] if milestone && is_start(start) => (None, start, None),
+            _ => (None, "", None),
+        };
+        let duration = duration.or(milestone.then_some(0));
+        match duration {
+            Some(d) if !start.is_empty() => {
+                let fields: Vec<&str> = tags.iter().copied().chain(id).chain([start]).collect();
+                out.push_str(&format!("{label}: {}, {d}\n", fields.join(", ")));
+            }
+            _ => note(notes, idx, "task without a start + duration"),
         }
     }
 
@@ -1363,77 +1871,780 @@ fn gitgraph(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> Stri
 }
 
 // ------------------------------------------------------------------
-// Tests
+// block-beta
 // ------------------------------------------------------------------
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// One block-beta grid cell: a block (id, optional label, span) or a
+/// `space` of some span.
+enum BlockCell {
+    Block(String, Option<String>, usize),
+    Space(usize),
+}
 
-    /// Render a `::diagram` block body to HTML through the full pipeline.
-    fn html_for(body: &str) -> String {
-        let source = format!("::diagram\n{body}\n::\n");
-        let result = crate::parse::parse(&source);
-        result.doc.to_html_fragment()
+/// Optional `:n` column span after a block term or `space`.
+fn take_block_span(s: &str) -> (usize, &str) {
+    if let Some(r) = s.strip_prefix(':') {
+        let end = r.find(|c: char| !c.is_ascii_digit()).unwrap_or(r.len());
+        if let Ok(n) = r[..end].parse::<usize>() {
+            return (n.max(1), &r[end..]);
+        }
     }
+    (1, s)
+}
 
-    fn assert_renders_svg(body: &str, expect_fragment: &str) {
-        let html = html_for(body);
-        assert!(
-            html.contains("<svg"),
-            "mermaid body should render SVG, got: {html}"
-        );
-        assert!(
-            !html.contains("surfdoc-diagram-fallback"),
-            "mermaid body degraded to prose: {html}"
-        );
-        assert!(
-            html.contains(expect_fragment),
-            "expected {expect_fragment:?} in: {html}"
-        );
-    }
+fn block(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> String {
+    let mut cells: Vec<BlockCell> = Vec::new();
+    let mut edges: Vec<(String, String, Option<String>)> = Vec::new();
+    let mut columns: Option<usize> = None;
+    // Depth of open composite `block … end` groups; the native grid is
+    // flat, so their blocks join the outer grid.
+    let mut depth = 0usize;
+
+    let register = |cells: &mut Vec<BlockCell>, term: FlowTerm, span: usize| {
+        let label = term.decl.map(|(label, _)| label).filter(|l| !l.is_empty());
+        let existing = cells.iter_mut().find_map(|c| match c {
+            BlockCell::Block(id, label, _) if *id == term.id => Some(label),
+            _ => None,
+        });
+        match existing {
+            Some(existing) => {
+                if label.is_some() {
+                    *existing = label;
+                }
+            }
+            None => cells.push(BlockCell::Block(term.id, label, span)),
+        }
+    };
 
-    // ── golden sample per family ────────────────────────────────────
+    for (idx, raw) in lines.iter().enumerate().skip(header + 1) {
+        let line = raw.trim();
+        if is_noise(line) {
+            continue;
+        }
+        if let Some(rest) = keyword(line, "columns") {
+            match rest.parse::<usize>() {
+                Ok(n) if depth == 0 && columns.is_none() => columns = Some(n),
+                _ => note(notes, idx, "columns"),
+            }
+            continue;
+        }
+        if line == "block" || line.starts_with("block:") {
+            note(notes, idx, "nested block (its blocks join the outer grid)");
+            depth += 1;
+            continue;
+        }
+        if line == "end" {
+            if depth == 0 {
+                note(notes, idx, "end");
+            } else {
+                depth -= 1;
+            }
+            continue;
+        }
+        if ["classDef", "class", "style"].iter().any(|kw| keyword(line, kw).is_some()) {
+            note(notes, idx, line.split_whitespace().next().unwrap_or("directive"));
+            continue;
+        }
 
-    #[test]
-    fn golden_flowchart_renders_svg() {
-        assert_renders_svg(
-            "flowchart LR\n  A[Start] --> B{Valid?}\n  B -->|yes| C[Save]\n  B -->|no| A",
-            "surfdoc-diagram-flowchart",
-        );
+        // Cells `a["Label"]:2 b space:2`, or a link chain `a --> b`.
+        let mut rest = line;
+        let mut prev: Option<String> = None;
+        let mut parsed = true;
+        loop {
+            let t = rest.trim_start();
+            if t.is_empty() {
+                break;
+            }
+            if let Some(from) = &prev
+                && let Some((mut label, after_arrow)) = take_flow_arrow(t)
+            {
+                let (pipe, after_label) = take_pipe_label(after_arrow);
+                if pipe.is_some() {
+                    label = pipe;
+                }
+                let Some((term, after_term)) = take_flow_term(after_label) else {
+                    parsed = false;
+                    break;
+                };
+                edges.push((from.clone(), term.id.clone(), label.filter(|l| !l.is_empty())));
+                prev = Some(term.id.clone());
+                register(&mut cells, term, 1);
+                rest = after_term;
+                continue;
+            }
+            if let Some(after) = t.strip_prefix("space")
+                && !after.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_')
+            {
+                let (span, after) = take_block_span(after);
+                cells.push(BlockCell::Space(span));
+                prev = None;
+                rest = after;
+                continue;
+            }
+            let Some((term, after_term)) = take_flow_term(t) else {
+                parsed = false;
+                break;
+            };
+            let (span, after_span) = take_block_span(after_term);
+            prev = Some(term.id.clone());
+            register(&mut cells, term, span);
+            rest = after_span;
+        }
+        if !parsed {
+            note(notes, idx, "unrecognized statement");
+        }
     }
 
-    #[test]
-    fn golden_sequence_renders_svg() {
-        assert_renders_svg(
-            "sequenceDiagram\n  participant U as User\n  U->>API: POST /login\n  API-->>U: 200 OK",
-            "surfdoc-diagram-sequence",
-        );
+    let mut out = String::new();
+    if let Some(n) = columns {
+        out.push_str(&format!("columns {n}\n"));
+    }
+    for cell in &cells {
+        match cell {
+            BlockCell::Space(1) => out.push_str("space\n"),
+            BlockCell::Space(n) => out.push_str(&format!("space [{n}]\n")),
+            BlockCell::Block(id, label, span) => {
+                let span = if *span > 1 { format!(" [{span}]") } else { String::new() };
+                match label {
+                    Some(l) => out.push_str(&format!("{id}{span}: {l}\n")),
+                    None => out.push_str(&format!("{id}{span}\n")),
+                }
+            }
+        }
     }
-
-    #[test]
-    fn golden_class_renders_svg() {
-        assert_renders_svg(
-            "classDiagram\n  class Animal {\n    +String name\n    +speak() void\n  }\n  Animal <|-- Dog",
-            "surfdoc-diagram-class",
-        );
+    for (from, to, label) in &edges {
+        match label {
+            Some(l) => out.push_str(&format!("{from} -> {to}: {l}\n")),
+            None => out.push_str(&format!("{from} -> {to}\n")),
+        }
     }
+    out
+}
 
-    #[test]
-    fn golden_state_renders_svg() {
-        assert_renders_svg(
-            "stateDiagram-v2\n  [*] --> Idle\n  Idle --> Running : start\n  Running --> [*]",
-            "surfdoc-diagram-state",
-        );
-    }
+// ------------------------------------------------------------------
+// packet-beta
+// ------------------------------------------------------------------
 
-    #[test]
-    fn golden_er_renders_svg() {
-        assert_renders_svg(
-            "erDiagram\n  CUSTOMER ||--o{ ORDER : places\n  ORDER {\n    int id PK\n    int customer_id FK\n  }",
-            "surfdoc-diagram-erd",
-        );
-    }
+fn packet(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> String {
+    let mut out = String::new();
+
+    for (idx, raw) in lines.iter().enumerate().skip(header + 1) {
+        let line = raw.trim();
+        if is_noise(line) {
+            continue;
+        }
+        if keyword(line, "title").is_some() {
+            note(notes, idx, "title (use the block's title= attribute)");
+            continue;
+        }
+        // Field: `0-15: "Source Port"`, `16: "Flag"` or `+8: "Length"`.
+        let Some((range, label)) = line.split_once(':') else {
+            note(notes, idx, "unrecognized statement");
+            continue;
+        };
+        let range = range.trim();
+        if range.is_empty() || !range.chars().all(|c| c.is_ascii_digit() || c == '-' || c == '+') {
+            note(notes, idx, "unrecognized statement");
+            continue;
+        }
+        out.push_str(&format!("{range}: {}\n", clean_label(label)));
+    }
+
+    out
+}
+
+// ------------------------------------------------------------------
+// architecture-beta
+// ------------------------------------------------------------------
+
+/// Parse a service/group declaration `id(icon)[Label] in parent` (icon,
+/// label and parent all optional) into `(id, label, parent)`.
+fn arch_decl(rest: &str) -> Option<(String, Option<String>, Option<String>)> {
+    let (id, mut rest) = take_id(rest.trim());
+    if id.is_empty() {
+        return None;
+    }
+    if let Some(r) = rest.strip_prefix('(') {
+        rest = r.split_once(')')?.1; // the icon is dropped
+    }
+    let mut label = None;
+    if let Some(r) = rest.strip_prefix('[') {
+        let (l, after) = r.split_once(']')?;
+        label = Some(clean_label(l)).filter(|l| !l.is_empty());
+        rest = after;
+    }
+    let rest = rest.trim();
+    let parent = if rest.is_empty() {
+        None
+    } else {
+        Some(safe_id(keyword(rest, "in")?))
+    };
+    Some((safe_id(id), label, parent))
+}
+
+/// Parse an edge `a:R --> L:b` (sides and `{group}` modifiers optional)
+/// into `(from, to, bidirectional)`. `<--` edges are flipped; undirected
+/// `--` edges point left to right.
+fn arch_edge(line: &str) -> Option<(String, String, bool)> {
+    let pos = line.find("--")?;
+    let (mut lhs, mut rhs) = (&line[..pos], &line[pos + 2..]);
+    let back = lhs.ends_with('<');
+    if back {
+        lhs = &lhs[..lhs.len() - 1];
+    }
+    let fwd = rhs.starts_with('>');
+    if fwd {
+        rhs = &rhs[1..];
+    }
+    let endpoint = |s: &str, side_first: bool| -> Option<String> {
+        let s = s.trim();
+        let s = match (side_first, s.split_once(':'), s.rsplit_once(':')) {
+            (true, Some((_, id)), _) | (false, _, Some((id, _))) => id,
+            _ => s,
+        };
+        let id = s.split('{').next().unwrap_or("").trim();
+        (!id.is_empty()).then(|| safe_id(id))
+    };
+    let (a, b) = (endpoint(lhs, false)?, endpoint(rhs, true)?);
+    Some(if back && !fwd { (b, a, false) } else { (a, b, back && fwd) })
+}
+
+fn architecture(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> String {
+    // Groups `(id, label)` in declaration order; `parent_of[g]` is the
+    // emitted group a group's members join (nested groups fold into their
+    // outermost ancestor).
+    let mut groups: Vec<(String, String)> = Vec::new();
+    let mut parent_of: Vec<usize> = Vec::new();
+    let mut services: Vec<(String, String, Option<usize>)> = Vec::new();
+    let mut edges: Vec<(String, String, bool)> = Vec::new();
+
+    let group_index = |groups: &[(String, String)], parent_of: &[usize], id: &Option<String>| {
+        id.as_ref()
+            .and_then(|id| groups.iter().position(|(g, _)| g == id))
+            .map(|g| parent_of[g])
+    };
+
+    for (idx, raw) in lines.iter().enumerate().skip(header + 1) {
+        let line = raw.trim();
+        if is_noise(line) {
+            continue;
+        }
+        if let Some(rest) = keyword(line, "group") {
+            let Some((id, label, parent)) = arch_decl(rest) else {
+                note(notes, idx, "group");
+                continue;
+            };
+            let outer = match group_index(&groups, &parent_of, &parent) {
+                Some(p) => {
+                    note(notes, idx, "nested group (its services join the outer group)");
+                    p
+                }
+                None => groups.len(),
+            };
+            groups.push((id.clone(), label.unwrap_or(id)));
+            parent_of.push(outer);
+            continue;
+        }
+        if let Some(rest) = keyword(line, "service") {
+            let Some((id, label, parent)) = arch_decl(rest) else {
+                note(notes, idx, "service");
+                continue;
+            };
+            let group = group_index(&groups, &parent_of, &parent);
+            services.push((id.clone(), label.unwrap_or(id), group));
+            continue;
+        }
+        if keyword(line, "junction").is_some() {
+            note(notes, idx, "junction");
+            continue;
+        }
+        match arch_edge(line) {
+            Some(edge) => edges.push(edge),
+            None => note(notes, idx, "unrecognized statement"),
+        }
+    }
+
+    // Each group is emitted whole at its first member's position, so
+    // services stay in declaration order and members stay contiguous.
+    let mut out = String::new();
+    let mut emitted = vec![false; groups.len()];
+    for (id, label, group) in &services {
+        match group {
+            None => out.push_str(&format!("{id}: {label}\n")),
+            Some(g) if !emitted[*g] => {
+                emitted[*g] = true;
+                let (gid, glabel) = &groups[*g];
+                out.push_str(&format!("group {gid}: {glabel} {{\n"));
+                for (member, member_label, _) in services.iter().filter(|s| s.2 == Some(*g)) {
+                    out.push_str(&format!("  {member}: {member_label}\n"));
+                }
+                out.push_str("}\n");
+            }
+            Some(_) => {}
+        }
+    }
+    for (from, to, bidirectional) in &edges {
+        let arrow = if *bidirectional { "<->" } else { "->" };
+        out.push_str(&format!("{from} {arrow} {to}\n"));
+    }
+    out
+}
+
+// ------------------------------------------------------------------
+// requirementDiagram
+// ------------------------------------------------------------------
+
+/// Declaration keywords that open a requirement block.
+const REQ_KINDS: &[&str] = &[
+    "requirement",
+    "functionalRequirement",
+    "interfaceRequirement",
+    "performanceRequirement",
+    "physicalRequirement",
+    "designConstraint",
+];
+
+/// Relation kinds with a native equivalent (`copies` has none).
+const REQ_RELATIONS: &[&str] = &["contains", "derives", "satisfies", "verifies", "refines", "traces"];
+
+/// A label safe for native DSL positions that split at the first colon.
+fn colon_free(label: &str) -> String {
+    label.replace(':', " ").split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn requirement(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> String {
+    // Nodes `(id, label, text, is_requirement)` in declaration order, and
+    // the node whose `{ … }` body is open.
+    let mut nodes: Vec<(String, String, Option<String>, bool)> = Vec::new();
+    let mut edges: Vec<(String, String, &str)> = Vec::new();
+    let mut open: Option<usize> = None;
+
+    for (idx, raw) in lines.iter().enumerate().skip(header + 1) {
+        let line = raw.trim();
+        if is_noise(line) {
+            continue;
+        }
+        if let Some(i) = open {
+            if line == "}" {
+                open = None;
+                continue;
+            }
+            match line.split_once(':') {
+                Some((key, value)) if key.trim() == "text" && nodes[i].3 => {
+                    nodes[i].2 = Some(clean_label(value)).filter(|t| !t.is_empty());
+                }
+                Some((key, _)) => note(notes, idx, format!("{} field", key.trim())),
+                None => note(notes, idx, "unrecognized statement"),
+            }
+            continue;
+        }
+
+        // Declaration: `requirement name {` / `element name {`.
+        let word = line.split_whitespace().next().unwrap_or("");
+        let is_requirement = REQ_KINDS.contains(&word);
+        if is_requirement || word == "element" {
+            let rest = line[word.len()..].trim();
+            let closed = rest.ends_with('}');
+            let opened = rest.contains('{');
+            let name = rest.trim_end_matches('}').trim_end().trim_end_matches('{').trim();
+            if name.is_empty() {
+                note(notes, idx, word);
+                continue;
+            }
+            let label = clean_label(name);
+            nodes.push((safe_id(&label), label, None, is_requirement));
+            if opened && !closed {
+                open = Some(nodes.len() - 1);
+            }
+            continue;
+        }
+
+        // Relation: `src - kind -> dst` or `dst <- kind - src`.
+        let relation = if let Some((lhs, dst)) = line.split_once("->") {
+            lhs.rsplit_once('-').map(|(src, kind)| (src, kind, dst))
+        } else if let Some((dst, rhs)) = line.split_once("<-") {
+            rhs.split_once('-').map(|(kind, src)| (src, kind, dst))
+        } else {
+            None
+        };
+        let Some((src, kind, dst)) = relation else {
+            note(notes, idx, "unrecognized statement");
+            continue;
+        };
+        let kind = kind.trim();
+        match REQ_RELATIONS.iter().find(|k| **k == kind) {
+            Some(kind) => edges.push((safe_id(&clean_label(src)), safe_id(&clean_label(dst)), kind)),
+            None => note(notes, idx, format!("{kind} relation")),
+        }
+    }
+
+    let mut out = String::new();
+    for (id, label, text, is_requirement) in &nodes {
+        let label = colon_free(label);
+        match (is_requirement, text) {
+            (true, Some(text)) => out.push_str(&format!("requirement {id}: {label}: {text}\n")),
+            (true, None) => out.push_str(&format!("requirement {id}: {label}\n")),
+            (false, _) => out.push_str(&format!("element {id}: {label}\n")),
+        }
+    }
+    for (from, to, kind) in &edges {
+        out.push_str(&format!("{from} -> {to}: {kind}\n"));
+    }
+    out
+}
+
+// ------------------------------------------------------------------
+// C4Context / C4Container / C4Component
+// ------------------------------------------------------------------
+
+/// Split a C4 macro call `Name(a, "b, c", $tags="x")` into its name and
+/// unquoted positional arguments (`$key=value` arguments are dropped).
+fn c4_call(line: &str) -> Option<(&str, Vec<String>)> {
+    let open = line.find('(')?;
+    let close = line.rfind(')')?;
+    let name = line[..open].trim();
+    if close < open || name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+    let mut args = Vec::new();
+    let mut arg = String::new();
+    let mut quoted = false;
+    for c in line[open + 1..close].chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => args.push(std::mem::take(&mut arg).trim().to_string()),
+            _ => arg.push(c),
+        }
+    }
+    args.push(arg.trim().to_string());
+    args.retain(|a| !a.starts_with('$'));
+    Some((name, args))
+}
+
+fn c4(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> String {
+    let mut out = String::new();
+    let mut edges = String::new();
+    // Open boundaries: `true` for the emitted outermost one, `false` for
+    // nested ones folded into it (the native DSL has one level).
+    let mut open: Vec<bool> = Vec::new();
+
+    for (idx, raw) in lines.iter().enumerate().skip(header + 1) {
+        let line = raw.trim();
+        if is_noise(line) || line == "{" {
+            continue;
+        }
+        if keyword(line, "title").is_some() {
+            note(notes, idx, "title (use the block's title= attribute)");
+            continue;
+        }
+        if line == "}" {
+            match open.pop() {
+                Some(true) => out.push_str("}\n"),
+                Some(false) => {}
+                None => note(notes, idx, "}"),
+            }
+            continue;
+        }
+        let Some((name, args)) = c4_call(line) else {
+            note(notes, idx, "unrecognized statement");
+            continue;
+        };
+        let arg = |i: usize| args.get(i).map(|a| clean_label(a)).filter(|a| !a.is_empty());
+        let Some(id) = args.first().filter(|a| !a.is_empty()).map(|a| safe_id(a)) else {
+            note(notes, idx, name);
+            continue;
+        };
+        let label = arg(1).unwrap_or_else(|| id.clone());
+        let (base, external) = match name.strip_suffix("_Ext") {
+            Some(base) => (base, " [ext]"),
+            None => (name, ""),
+        };
+        match base {
+            "Person" => out.push_str(&format!("person {id}: {label}{external}\n")),
+            "System" | "SystemDb" | "SystemQueue" => {
+                out.push_str(&format!("system {id}: {label}{external}\n"));
+            }
+            "Container" | "ContainerDb" | "ContainerQueue" | "Component" | "ComponentDb" | "ComponentQueue" => {
+                let label = colon_free(&label);
+                match arg(2) {
+                    Some(tech) => out.push_str(&format!("container {id}: {label}: {tech}{external}\n")),
+                    None => out.push_str(&format!("container {id}: {label}{external}\n")),
+                }
+            }
+            "Boundary" | "Enterprise_Boundary" | "System_Boundary" | "Container_Boundary" => {
+                if open.is_empty() {
+                    out.push_str(&format!("boundary {label} {{\n"));
+                    open.push(true);
+                } else {
+                    note(notes, idx, "nested boundary (its elements join the outer one)");
+                    open.push(false);
+                }
+            }
+            "Rel" | "Rel_U" | "Rel_Up" | "Rel_D" | "Rel_Down" | "Rel_L" | "Rel_Left" | "Rel_R" | "Rel_Right"
+            | "Rel_Back" | "BiRel" => {
+                let Some(to) = args.get(1).filter(|a| !a.is_empty()).map(|a| safe_id(a)) else {
+                    note(notes, idx, name);
+                    continue;
+                };
+                if name == "BiRel" {
+                    note(notes, idx, "BiRel (drawn one-way)");
+                }
+                let (from, to) = if name == "Rel_Back" { (to, id) } else { (id, to) };
+                let label = match (arg(2), arg(3)) {
+                    (Some(label), Some(tech)) => format!(": {label} [{tech}]"),
+                    (Some(label), None) => format!(": {label}"),
+                    _ => String::new(),
+                };
+                edges.push_str(&format!("{from} -> {to}{label}\n"));
+            }
+            _ => note(notes, idx, name),
+        }
+    }
+
+    // Close a boundary the source left open so the body still parses.
+    if open.first() == Some(&true) {
+        out.push_str("}\n");
+    }
+    out.push_str(&edges);
+    out
+}
+
+// ------------------------------------------------------------------
+// sankey-beta
+// ------------------------------------------------------------------
+
+/// Split one CSV record: `,` separated, `"…"` quoting with `""` for a
+/// literal quote.
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+fn sankey(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> String {
+    let mut out = String::new();
+
+    for (idx, raw) in lines.iter().enumerate().skip(header + 1) {
+        let line = raw.trim();
+        if is_noise(line) {
+            continue;
+        }
+        // Flow: `Source,Target,Value` (CSV quoting allowed).
+        let fields = csv_fields(line);
+        let [from, to, value] = fields.as_slice() else {
+            note(notes, idx, "unrecognized statement");
+            continue;
+        };
+        let (from, to, value) = (from.trim(), to.trim(), value.trim());
+        if from.is_empty() || to.is_empty() {
+            note(notes, idx, "unrecognized statement");
+            continue;
+        }
+        if !value.parse::<f64>().is_ok_and(|v| v.is_finite() && v > 0.0) {
+            note(notes, idx, "non-positive or non-numeric value");
+            continue;
+        }
+        // The native form splits at the first `->` and the target's `:`.
+        if from.contains("->") || to.contains(':') {
+            note(notes, idx, "node name containing `->` or `:`");
+            continue;
+        }
+        out.push_str(&format!("{from} -> {to}: {value}\n"));
+    }
+
+    out
+}
+
+// ------------------------------------------------------------------
+// xychart-beta (→ ::chart pipeline)
+// ------------------------------------------------------------------
+
+/// Parse a `[a, "b c", 3]` list into its cleaned items.
+fn bracket_list(s: &str) -> Option<Vec<String>> {
+    let inner = s.trim().strip_prefix('[')?.strip_suffix(']')?;
+    if inner.trim().is_empty() {
+        return Some(Vec::new());
+    }
+    Some(csv_fields(inner).iter().map(|item| clean_label(item).replace('|', "/")).collect())
+}
+
+/// Split an optional axis/series title (`"Quoted title"` or one bare word)
+/// off the front of `s`.
+fn take_chart_title(s: &str) -> (Option<String>, &str) {
+    let s = s.trim_start();
+    if let Some(r) = s.strip_prefix('"')
+        && let Some((title, rest)) = r.split_once('"')
+    {
+        return (Some(clean_label(title).replace('|', "/")), rest);
+    }
+    if s.starts_with(|c: char| c.is_alphabetic()) {
+        let end = s.find(char::is_whitespace).unwrap_or(s.len());
+        return (Some(s[..end].replace('|', "/")), &s[end..]);
+    }
+    (None, s)
+}
+
+fn xychart(lines: &[&str], header: usize, notes: &mut Vec<MermaidNote>) -> String {
+    let mut x_title: Option<String> = None;
+    let mut categories: Option<Vec<String>> = None;
+    let mut series: Vec<(String, Vec<String>)> = Vec::new();
+
+    if lines[header].split_whitespace().nth(1) == Some("horizontal") {
+        note(notes, header, "horizontal orientation");
+    }
+    for (idx, raw) in lines.iter().enumerate().skip(header + 1) {
+        let line = raw.trim();
+        if is_noise(line) {
+            continue;
+        }
+        if keyword(line, "title").is_some() {
+            note(notes, idx, "title (use the block's title= attribute)");
+            continue;
+        }
+        if let Some(rest) = keyword(line, "x-axis") {
+            let (title, rest) = take_chart_title(rest);
+            x_title = title.or(x_title);
+            match bracket_list(rest) {
+                Some(list) => categories = Some(list),
+                None if rest.trim().is_empty() => {}
+                None => note(notes, idx, "numeric x-axis range"),
+            }
+            continue;
+        }
+        if keyword(line, "y-axis").is_some() {
+            note(notes, idx, "y-axis (the chart scales its own axis)");
+            continue;
+        }
+        let (kind, rest) = match (keyword(line, "bar"), keyword(line, "line")) {
+            (Some(rest), _) => ("Bar", rest),
+            (_, Some(rest)) => ("Line", rest),
+            _ => {
+                note(notes, idx, "unrecognized statement");
+                continue;
+            }
+        };
+        let (name, rest) = match rest.trim_start().starts_with('"') {
+            true => take_chart_title(rest),
+            false => (None, rest),
+        };
+        let Some(values) = bracket_list(rest).filter(|v| v.iter().all(|x| x.parse::<f64>().is_ok())) else {
+            note(notes, idx, "non-numeric series");
+            continue;
+        };
+        // Unnamed series are numbered per kind after the first.
+        let mut name = name.unwrap_or_else(|| kind.to_string());
+        let base = name.clone();
+        let mut n = 1;
+        while series.iter().any(|(s, _)| *s == name) {
+            n += 1;
+            name = format!("{base} {n}");
+        }
+        series.push((name, values));
+    }
+
+    if series.is_empty() {
+        return String::new();
+    }
+    let rows = match &categories {
+        Some(c) => c.len(),
+        None => series.iter().map(|(_, v)| v.len()).max().unwrap_or(0),
+    };
+    let names: Vec<&str> = series.iter().map(|(name, _)| name.as_str()).collect();
+    let mut out = format!("{} | {}\n", x_title.as_deref().unwrap_or("X"), names.join(" | "));
+    for i in 0..rows {
+        let category = categories.as_ref().and_then(|c| c.get(i).cloned()).unwrap_or_else(|| (i + 1).to_string());
+        let cells: Vec<&str> = series.iter().map(|(_, v)| v.get(i).map_or("", String::as_str)).collect();
+        out.push_str(&format!("{category} | {}\n", cells.join(" | ")));
+    }
+    out
+}
+
+// ------------------------------------------------------------------
+// Tests
+// ------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Render a `::diagram` block body to HTML through the full pipeline.
+    fn html_for(body: &str) -> String {
+        let source = format!("::diagram\n{body}\n::\n");
+        let result = crate::parse::parse(&source);
+        result.doc.to_html_fragment()
+    }
+
+    fn assert_renders_svg(body: &str, expect_fragment: &str) {
+        let html = html_for(body);
+        assert!(
+            html.contains("<svg"),
+            "mermaid body should render SVG, got: {html}"
+        );
+        assert!(
+            !html.contains("surfdoc-diagram-fallback"),
+            "mermaid body degraded to prose: {html}"
+        );
+        assert!(
+            html.contains(expect_fragment),
+            "expected {expect_fragment:?} in: {html}"
+        );
+    }
+
+    // ── golden sample per family ────────────────────────────────────
+
+    #[test]
+    fn golden_flowchart_renders_svg() {
+        assert_renders_svg(
+            "flowchart LR\n  A[Start] --> B{Valid?}\n  B -->|yes| C[Save]\n  B -->|no| A",
+            "surfdoc-diagram-flowchart",
+        );
+    }
+
+    #[test]
+    fn golden_sequence_renders_svg() {
+        assert_renders_svg(
+            "sequenceDiagram\n  participant U as User\n  U->>API: POST /login\n  API-->>U: 200 OK",
+            "surfdoc-diagram-sequence",
+        );
+    }
+
+    #[test]
+    fn golden_class_renders_svg() {
+        assert_renders_svg(
+            "classDiagram\n  class Animal {\n    +String name\n    +speak() void\n  }\n  Animal <|-- Dog",
+            "surfdoc-diagram-class",
+        );
+    }
+
+    #[test]
+    fn golden_state_renders_svg() {
+        assert_renders_svg(
+            "stateDiagram-v2\n  [*] --> Idle\n  Idle --> Running : start\n  Running --> [*]",
+            "surfdoc-diagram-state",
+        );
+    }
+
+    #[test]
+    fn golden_er_renders_svg() {
+        assert_renders_svg(
+            "erDiagram\n  CUSTOMER ||--o{ ORDER : places\n  ORDER {\n    int id PK\n    int customer_id FK\n  }",
+            "surfdoc-diagram-erd",
+        );
+    }
 
     #[test]
     fn golden_gantt_renders_svg() {
@@ -1501,6 +2712,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn golden_block_renders_svg() {
+        assert_renders_svg(
+            "block-beta\n  columns 3\n  fe[\"Frontend\"] space be[\"Backend\"]\n  fe --> be",
+            "surfdoc-diagram-block",
+        );
+    }
+
+    #[test]
+    fn golden_packet_renders_svg() {
+        assert_renders_svg(
+            "packet-beta\n  0-15: \"Source Port\"\n  16-31: \"Destination Port\"\n  32-63: \"Sequence Number\"",
+            "surfdoc-diagram-packet",
+        );
+    }
+
+    #[test]
+    fn golden_architecture_renders_svg() {
+        assert_renders_svg(
+            "architecture-beta\n  group api(cloud)[API]\n  service db(database)[Database] in api\n  service server(server)[Server] in api\n  db:L -- R:server",
+            "surfdoc-diagram-architecture",
+        );
+    }
+
+    #[test]
+    fn golden_requirement_renders_svg() {
+        assert_renders_svg(
+            "requirementDiagram\n  requirement fast {\n    id: 1\n    text: under 5ms\n  }\n  element parser {\n    type: module\n  }\n  parser - satisfies -> fast",
+            "surfdoc-diagram-requirement",
+        );
+    }
+
+    #[test]
+    fn golden_c4_renders_svg() {
+        assert_renders_svg(
+            "C4Context\n  Person(user, \"User\")\n  System_Boundary(core, \"Core\") {\n    Container(api, \"API\", \"Rust\")\n  }\n  Rel(user, api, \"Uses\", \"HTTPS\")",
+            "surfdoc-diagram-c4",
+        );
+    }
+
+    #[test]
+    fn golden_sankey_renders_svg() {
+        assert_renders_svg(
+            "sankey-beta\n  Wind,Grid,40\n  Solar,Grid,30\n  Grid,Homes,70",
+            "surfdoc-diagram-sankey",
+        );
+    }
+
+    #[test]
+    fn golden_xychart_renders_chart_svg() {
+        assert_renders_svg(
+            "xychart-beta\n  x-axis [jan, feb, mar]\n  bar [5, 7, 9]\n  line [4, 6, 8]",
+            "surfdoc-diagram-xychart",
+        );
+        // xychart forwards to the ::chart pipeline.
+        assert!(html_for("xychart-beta\n  line [1, 2, 3]").contains("surfdoc-chart-svg"));
+    }
+
     // ── explicit type=mermaid ───────────────────────────────────────
 
     #[test]
@@ -1527,12 +2796,37 @@ mod tests {
     }
 
     #[test]
-    fn flowchart_subgraph_is_skipped_with_note() {
-        let t = translate("", "flowchart LR\nsubgraph One\nA --> B\nend\nB --> C").unwrap();
-        assert!(t.content.contains("A -> B"));
-        assert!(t.content.contains("B -> C"));
-        assert_eq!(t.notes.len(), 2); // subgraph + end
-        assert!(t.notes.iter().all(|n| n.construct == "subgraph"));
+    fn flowchart_header_sets_direction() {
+        let lr = translate("", "flowchart LR\nA --> B").unwrap();
+        assert_eq!(lr.direction, Some(DiagramDirection::LeftRight));
+        let td = translate("", "graph TD\nA --> B").unwrap();
+        assert_eq!(td.direction, Some(DiagramDirection::TopBottom));
+        assert_eq!(translate("", "flowchart\nA --> B").unwrap().direction, None);
+        assert_eq!(translate("", "stateDiagram-v2\n[*] --> A").unwrap().direction, None);
+    }
+
+    #[test]
+    fn flowchart_subgraph_becomes_a_group() {
+        let body = "flowchart LR\nX[Client] --> A\nsubgraph vpc [Private VPC]\nA[API] --> B\nend\nsubgraph Workers\nC\nend\nB --> C";
+        let t = translate("", body).unwrap();
+        assert_eq!(
+            t.content,
+            "X: Client\ngroup vpc: Private VPC {\n  A: API\n  B\n}\ngroup Workers: Workers {\n  C\n}\nX -> A\nA -> B\nB -> C\n"
+        );
+        assert!(t.notes.is_empty());
+        let html = html_for(body);
+        assert!(html.contains("surfdoc-diagram-cluster"));
+        assert!(html.contains(">Private VPC</text>"));
+    }
+
+    #[test]
+    fn flowchart_nested_subgraph_folds_into_its_parent() {
+        let t = translate("", "flowchart TD\nsubgraph outer\nsubgraph inner\nA --> B\nend\nend\nend").unwrap();
+        assert!(t.content.contains("group outer: outer {\n  A\n  B\n}"));
+        let constructs: Vec<&str> = t.notes.iter().map(|n| n.construct.as_str()).collect();
+        // The nested opener, then the stray third `end`.
+        assert_eq!(constructs, vec!["nested subgraph", "subgraph"]);
+        assert_eq!(t.notes[0].line, 3);
     }
 
     #[test]
@@ -1552,11 +2846,27 @@ mod tests {
     }
 
     #[test]
-    fn sequence_frames_are_noted() {
-        let t = translate("", "sequenceDiagram\nA->>B: hi\nloop retry\nB->>A: pong\nend\nNote over A: hmm").unwrap();
-        assert!(t.content.contains("A -> B: hi"));
-        assert!(t.content.contains("B -> A: pong"));
-        assert_eq!(t.notes.len(), 3); // loop, end, Note
+    fn sequence_fragments_and_notes_translate() {
+        let t = translate(
+            "",
+            "sequenceDiagram\nA->>B: hi\nloop Every minute\nB->>A: pong\nend\nalt is sick\nB->>A: Not so good\nelse is well\nB->>A: Fine\nend\nNote right of A: Rational<br/>thoughts\nNote over A,B: both\nrect rgb(191, 223, 255)\npar\nA->>B: x\nand Second\nA->>B: y\nend\nend",
+        )
+        .unwrap();
+        assert_eq!(
+            t.content,
+            "A -> B: hi\nloop Every minute {\nB -> A: pong\n}\nalt is sick {\nB -> A: Not so good\nelse is well\nB -> A: Fine\n}\nnote right of A: Rational thoughts\nnote over A, B: both\nrect {\npar {\nA -> B: x\nand Second\nA -> B: y\n}\n}\n"
+        );
+        assert!(t.notes.is_empty());
+        assert!(crate::diagram::parse_diagram_source(t.diagram_type, &t.content).is_ok());
+    }
+
+    #[test]
+    fn sequence_unbalanced_frames_degrade() {
+        // A stray `end` is noted; an unclosed fragment is closed.
+        let t = translate("", "sequenceDiagram\nend\nopt maybe\nA->>B: hi").unwrap();
+        assert_eq!(t.content, "opt maybe {\nA -> B: hi\n}\n");
+        assert_eq!(t.notes.len(), 1);
+        assert_eq!(t.notes[0].construct, "end");
     }
 
     #[test]
@@ -1605,15 +2915,18 @@ mod tests {
     }
 
     #[test]
-    fn gantt_after_dependency_is_noted() {
+    fn gantt_tags_ids_and_after_dependencies_translate() {
         let t = translate(
             "",
-            "gantt\ntitle Plan\nsection S\nA :a1, 2026-01-01, 3d\nB :after a1, 5d",
+            "gantt\ntitle Plan\nsection S\nA :done, a1, 2026-01-01, 3d\nB :crit, after a1, 1w\nShip :milestone, m1, after a1, 0d\nC :after a1, 2026-02-01",
         )
         .unwrap();
-        assert!(t.content.contains("A: 2026-01-01, 3"));
-        assert!(!t.content.contains("after"));
-        assert_eq!(t.notes.len(), 2); // title + after-dependency
+        assert!(t.content.contains("A: done, a1, 2026-01-01, 3"));
+        assert!(t.content.contains("B: crit, after a1, 7"));
+        assert!(t.content.contains("Ship: milestone, m1, after a1, 0"));
+        // title + the end-date task (no duration).
+        assert_eq!(t.notes.len(), 2);
+        assert!(crate::diagram::parse_diagram_source(t.diagram_type, &t.content).is_ok());
     }
 
     #[test]
@@ -1638,6 +2951,63 @@ mod tests {
         assert!(t.content.contains("Point A: 0.25, 0.75"));
     }
 
+    #[test]
+    fn flowchart_styling_maps_to_emphasis_roles() {
+        let t = translate(
+            "",
+            "flowchart LR\nclassDef error fill:#fee,stroke:#c00\nclassDef calm fill:#eee\nclassDef blue fill:#36f\nA:::error --> B --> C\nclass B,C calm\nstyle C stroke:#2a2,stroke-width:2px\nlinkStyle 1 stroke:orange",
+        )
+        .unwrap();
+        assert!(t.content.contains("style A: danger\n"));
+        assert!(t.content.contains("style B: muted\n"));
+        // A direct style wins over the class.
+        assert!(t.content.contains("style C: success\n"));
+        assert!(t.content.contains("style B -> C: highlight\n"));
+        // No hard-coded colors survive translation.
+        assert!(!t.content.contains('#'));
+        assert!(t.notes.is_empty(), "{:?}", t.notes);
+        assert_renders_svg("flowchart LR\nclassDef bad stroke:red\nA:::bad --> B", "#dc2626");
+    }
+
+    #[test]
+    fn flowchart_click_becomes_native_link() {
+        let t = translate(
+            "",
+            "flowchart LR\nA --> B\nclick A \"https://example.com\" \"Open site\"\nclick B href \"/docs\" _blank\nclick A callback \"Tip\"\nclick B \"javascript:alert(1)\"\nclick Z \"/z\"",
+        )
+        .unwrap();
+        assert!(t.content.contains("link A: https://example.com \"Open site\"\n"));
+        assert!(t.content.contains("link B: /docs\n"));
+        // Callback, unsafe url and unknown node.
+        assert_eq!(t.notes.iter().map(|n| n.line).collect::<Vec<_>>(), vec![5, 6, 7]);
+        assert_renders_svg("flowchart LR\nA --> B\nclick A \"/a\"", "<a href=\"/a\"");
+    }
+
+    #[test]
+    fn unmappable_styling_is_noted() {
+        let t = translate(
+            "",
+            "graph TD\nclassDef plain stroke-width:2px\nA --> B\nclass A plain\nclass B missing\nlinkStyle 7 stroke:#f00",
+        )
+        .unwrap();
+        assert!(!t.content.contains("style"));
+        // classDef without a role, class without a classDef, out-of-range link.
+        assert_eq!(t.notes.len(), 3, "{:?}", t.notes);
+    }
+
+    #[test]
+    fn state_classes_map_to_emphasis_roles() {
+        let t = translate(
+            "",
+            "stateDiagram-v2\nclassDef done fill:#cfc\n[*] --> Work\nWork --> Shipped:::done : ship\nclass Work active\nclassDef active stroke:#f90",
+        )
+        .unwrap();
+        assert!(t.content.contains("Work -> Shipped: ship\n"));
+        assert!(t.content.contains("style Shipped: success\n"));
+        assert!(t.content.contains("style Work: highlight\n"));
+        assert!(t.notes.is_empty(), "{:?}", t.notes);
+    }
+
     #[test]
     fn gitgraph_options_map_to_labels() {
         let t = translate(
@@ -1652,6 +3022,94 @@ mod tests {
         assert_eq!(t.notes.len(), 1); // cherry-pick
     }
 
+    #[test]
+    fn block_spans_spaces_and_links() {
+        let t = translate(
+            "",
+            "block-beta\ncolumns 3\na[\"Client\"]:2 space\nblock:inner\n  b c\nend\na --> b\nb -- \"reads\" --> c\nclassDef x fill:#f00",
+        )
+        .unwrap();
+        assert_eq!(t.diagram_type, "block");
+        assert!(t.content.starts_with("columns 3\n"));
+        assert!(t.content.contains("a [2]: Client\nspace\nb\nc\n"));
+        assert!(t.content.contains("a -> b\n"));
+        assert!(t.content.contains("b -> c: reads"));
+        assert_eq!(t.notes.len(), 2); // nested block, classDef
+    }
+
+    #[test]
+    fn packet_fields_keep_their_ranges() {
+        let t = translate("", "packet-beta\ntitle TCP\n0-15: \"Source Port\"\n+16: \"Dest\"\nbogus line").unwrap();
+        assert_eq!(t.diagram_type, "packet");
+        assert_eq!(t.content, "0-15: Source Port\n+16: Dest\n");
+        assert_eq!(t.notes.len(), 2); // title, bogus line
+    }
+
+    #[test]
+    fn architecture_groups_services_and_edge_directions() {
+        let t = translate(
+            "",
+            "architecture-beta\ngroup api(cloud)[API]\ngroup inner[Inner] in api\nservice web(internet)[Web]\nservice db(database)[DB] in inner\nservice disk[Disk] in api\njunction j\nweb:R --> L:db\ndisk{group}:T <-- B:db\nweb:B <--> T:disk",
+        )
+        .unwrap();
+        assert_eq!(t.diagram_type, "architecture");
+        assert!(t.content.contains("web: Web\ngroup api: API {\n  db: DB\n  disk: Disk\n}\n"));
+        assert!(t.content.contains("web -> db\n"));
+        assert!(t.content.contains("db -> disk\n"));
+        assert!(t.content.contains("web <-> disk\n"));
+        assert_eq!(t.notes.len(), 2); // nested group, junction
+    }
+
+    #[test]
+    fn requirement_blocks_and_relations() {
+        let t = translate(
+            "",
+            "requirementDiagram\nperformanceRequirement fast {\n  id: 1\n  text: under 5ms\n  risk: high\n}\nelement parser {\n  type: module\n}\nparser - satisfies -> fast\nfast <- verifies - parser\nparser - copies -> fast",
+        )
+        .unwrap();
+        assert_eq!(t.diagram_type, "requirement");
+        assert!(t.content.contains("requirement fast: fast: under 5ms\n"));
+        assert!(t.content.contains("element parser: parser\n"));
+        assert!(t.content.contains("parser -> fast: satisfies\n"));
+        assert!(t.content.contains("parser -> fast: verifies\n"));
+        // id, risk, type fields and the copies relation.
+        assert_eq!(t.notes.len(), 4);
+    }
+
+    #[test]
+    fn c4_macros_and_boundaries() {
+        let t = translate(
+            "",
+            "C4Container\ntitle Core\nPerson_Ext(u, \"User, admin\", $sprite=\"x\")\nSystem_Boundary(b, \"Core\") {\n  Container_Boundary(c, \"Inner\") {\n    ContainerDb(db, \"Store\", \"Postgres\")\n  }\n}\nRel_Back(db, u, \"Serves\", \"SQL\")\nUpdateLayoutConfig(2)",
+        )
+        .unwrap();
+        assert_eq!(t.diagram_type, "c4");
+        assert!(t.content.contains("person u: User, admin [ext]\n"));
+        assert!(t.content.contains("boundary Core {\ncontainer db: Store: Postgres\n}\n"));
+        assert!(t.content.contains("u -> db: Serves [SQL]"));
+        assert_eq!(t.notes.len(), 3); // title, nested boundary, UpdateLayoutConfig
+    }
+
+    #[test]
+    fn sankey_csv_rows_become_flows() {
+        let t = translate("", "sankey-beta\n\"Bio, fuel\",Grid,12.5\nGrid,Homes,0\nGrid,\"A \"\"B\"\"\",3").unwrap();
+        assert_eq!(t.diagram_type, "sankey");
+        assert_eq!(t.content, "Bio, fuel -> Grid: 12.5\nGrid -> A \"B\": 3\n");
+        assert_eq!(t.notes.len(), 1); // zero value
+    }
+
+    #[test]
+    fn xychart_series_become_a_pipe_table() {
+        let t = translate(
+            "",
+            "xychart-beta horizontal\ntitle \"Sales\"\nx-axis \"Month\" [jan, feb]\ny-axis \"Revenue\" 0 --> 10\nbar [1, 2]\nbar [3, 4]\nline \"Trend\" [2, 3]",
+        )
+        .unwrap();
+        assert_eq!(t.diagram_type, "xychart");
+        assert_eq!(t.content, "Month | Bar | Bar 2 | Trend\njan | 1 | 3 | 2\nfeb | 2 | 4 | 3\n");
+        assert_eq!(t.notes.len(), 3); // horizontal, title, y-axis
+    }
+
     // ── non-mermaid bodies pass through ─────────────────────────────
 
     #[test]
@@ -1,6 +1,7 @@
-use crate::attrs::parse_attrs;
+use crate::attrs::parse_attrs_at;
 use crate::error::{Diagnostic, Severity};
-use crate::types::{Attrs, Block, FrontMatter, Span, SurfDoc};
+use crate::types::{AttrSpans, Attrs, Block, FileId, FrontMatter, Span, SurfDoc};
+use serde::{Deserialize, Serialize};
 
 /// Result of parsing a SurfDoc.
 #[derive(Debug, Clone)]
@@ -9,6 +10,50 @@ pub struct ParseResult {
     pub doc: SurfDoc,
     /// Non-fatal diagnostics collected during parsing.
     pub diagnostics: Vec<Diagnostic>,
+    /// File names indexed by [`FileId`]: entry 0 names the parsed source,
+    /// later entries the files `::include` spliced in. Empty unless the
+    /// document was parsed with [`crate::include::parse_with_includes`].
+    pub files: Vec<String>,
+}
+
+impl ParseResult {
+    /// Name of the file a span's `file` id points into, if known.
+    pub fn file_name(&self, file: FileId) -> Option<&str> {
+        self.files.get(file.0 as usize).map(String::as_str)
+    }
+}
+
+/// Resource bounds for [`parse_with`] (web-runtime spec §4.4). Every bound
+/// defaults to `None` (unbounded), which is exactly [`parse`].
+///
+/// Bounds are checked while scanning, so a client and a publish server that
+/// share one `ParseOptions` reject the same inputs at the same line. Hitting
+/// a bound stops the scan at the offending line with a P007 error: blocks
+/// before it are kept (open containers are closed at that line, without
+/// P001), nothing from that line on is parsed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ParseOptions {
+    /// Maximum directive nesting depth; a top-level block is depth 1.
+    pub max_depth: Option<usize>,
+    /// Maximum number of block directives, counted at every depth.
+    /// [`crate::include::parse_with_includes`] also counts every spliced
+    /// block against it, across the whole expansion.
+    pub max_blocks: Option<usize>,
+    /// Maximum input size in bytes, checked before anything is parsed. An
+    /// oversized input yields an empty document (empty `source` included).
+    pub max_source_bytes: Option<usize>,
+    /// Maximum byte length of one opener's `[attrs]` string.
+    pub max_attr_len: Option<usize>,
+    /// Maximum `::include` nesting depth; a file included by the root is
+    /// depth 1. Only [`crate::include::parse_with_includes`] reads it, and
+    /// `None` there means [`crate::include::DEFAULT_MAX_INCLUDE_DEPTH`]:
+    /// include chains are always bounded.
+    pub max_include_depth: Option<usize>,
+    /// Maximum number of `::include` directives expanded in one document,
+    /// at any depth. Read like `max_include_depth`; `None` means
+    /// [`crate::include::DEFAULT_MAX_INCLUDES`].
+    pub max_includes: Option<usize>,
 }
 
 /// Parse a SurfDoc string into a `ParseResult`.
@@ -16,8 +61,36 @@ pub struct ParseResult {
 /// This function never panics. Malformed input produces diagnostics and a
 /// best-effort `SurfDoc`.
 pub fn parse(input: &str) -> ParseResult {
+    parse_with(input, &ParseOptions::default())
+}
+
+/// [`parse`] under the resource bounds in `options`. A bound that is hit
+/// produces a P007 error diagnostic and stops the scan (see [`ParseOptions`]).
+pub fn parse_with(input: &str, options: &ParseOptions) -> ParseResult {
     let mut diagnostics = Vec::new();
 
+    if let Some(max) = options.max_source_bytes.filter(|max| input.len() > *max) {
+        diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            message: p007_message(
+                "max_source_bytes",
+                &format!("source is {} bytes (limit {max}); nothing was parsed", input.len()),
+            ),
+            span: None,
+            code: Some("P007".into()),
+            fix: None,
+        });
+        return ParseResult {
+            doc: SurfDoc {
+                front_matter: None,
+                blocks: Vec::new(),
+                source: String::new(),
+            },
+            diagnostics,
+            files: Vec::new(),
+        };
+    }
+
     // Normalise CRLF → LF.
     let normalised = input.replace("\r\n", "\n");
     let lines: Vec<&str> = normalised.split('\n').collect();
@@ -30,18 +103,18 @@ pub fn parse(input: &str) -> ParseResult {
     // ---------------------------------------------------------------
     // Pass 1b: Line-by-line block directive scan.
     // ---------------------------------------------------------------
-    let blocks = scan_blocks(&lines, body_start_line, &normalised, &mut diagnostics);
+    let blocks = scan_blocks(&lines, body_start_line, &normalised, options, &mut diagnostics);
 
     // ---------------------------------------------------------------
     // Pass 2: Type resolution — convert Unknown blocks to typed variants.
     // ---------------------------------------------------------------
-    let blocks = blocks
-        .into_iter()
-        .map(|block| match block {
-            Block::Unknown { .. } => crate::blocks::resolve_block(block),
-            other => other,
-        })
-        .collect();
+    let mut blocks = resolve_blocks(blocks, &normalised);
+
+    // ---------------------------------------------------------------
+    // Pass 3: Chart data binding — fill `::chart[data=#id]` datasets from
+    // the referenced `::data` blocks, now that every block is typed.
+    // ---------------------------------------------------------------
+    crate::chart::bind_chart_data(&mut blocks);
 
     ParseResult {
         doc: SurfDoc {
@@ -50,9 +123,21 @@ pub fn parse(input: &str) -> ParseResult {
             source: normalised,
         },
         diagnostics,
+        files: Vec::new(),
     }
 }
 
+/// Pass 2 over scanned blocks: convert `Block::Unknown` to typed variants.
+pub(crate) fn resolve_blocks(blocks: Vec<Block>, source: &str) -> Vec<Block> {
+    blocks
+        .into_iter()
+        .map(|block| match block {
+            Block::Unknown { .. } => crate::blocks::resolve_block_in(block, source),
+            other => other,
+        })
+        .collect()
+}
+
 // ------------------------------------------------------------------
 // Front matter
 // ------------------------------------------------------------------
@@ -60,7 +145,7 @@ pub fn parse(input: &str) -> ParseResult {
 /// Try to extract YAML front matter from the beginning of the document.
 ///
 /// Returns `(Option<FrontMatter>, first_body_line_index)`.
-fn extract_front_matter(
+pub(crate) fn extract_front_matter(
     lines: &[&str],
     source: &str,
     diagnostics: &mut Vec<Diagnostic>,
@@ -99,6 +184,7 @@ fn extract_front_matter(
         end_line: end_idx + 1,
         start_offset: 0,
         end_offset: byte_offset_end_of_line(end_idx, source),
+        file: FileId::ROOT,
     };
 
     match serde_yaml::from_str::<FrontMatter>(&yaml_str) {
@@ -177,6 +263,7 @@ fn render_yaml_value(v: &serde_yaml::Value) -> String {
 struct OpenBlock {
     name: String,
     attrs: Attrs,
+    attr_spans: AttrSpans,
     depth: usize, // number of leading colons (2 = top-level, 3 = nested, …)
     start_line: usize, // 1-based
     start_offset: usize,
@@ -192,8 +279,34 @@ fn scan_blocks(
     lines: &[&str],
     body_start: usize,
     source: &str,
+    options: &ParseOptions,
     diagnostics: &mut Vec<Diagnostic>,
 ) -> Vec<Block> {
+    scan_blocks_from(lines, body_start, false, source, options, diagnostics, &mut |_, _| false).0
+}
+
+/// [`scan_blocks`] resumable at an arbitrary top-level line.
+///
+/// `start` must be a line at which a full scan has an empty nesting stack and
+/// no pending markdown gap (e.g. the line after a top-level block); `in_fence`
+/// is the code-fence state a full scan carries into it. `sync` is consulted at
+/// every top-level opener line with `(line_index, in_fence)`: returning `true`
+/// stops the scan right after the pending markdown gap is flushed (exactly as
+/// the opener would flush it) and returns the line index. The incremental
+/// reparser uses this to splice previously parsed blocks back in once the scan
+/// state provably matches the old tree.
+///
+/// `options.max_blocks` counts directives from `start`, so resumed scans only
+/// agree with a full scan when that bound is unset.
+pub(crate) fn scan_blocks_from(
+    lines: &[&str],
+    start: usize,
+    in_fence: bool,
+    source: &str,
+    options: &ParseOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+    sync: &mut dyn FnMut(usize, bool) -> bool,
+) -> (Vec<Block>, Option<usize>) {
     let mut blocks: Vec<Block> = Vec::new();
     let mut stack: Vec<OpenBlock> = Vec::new();
 
@@ -207,9 +320,14 @@ fn scan_blocks(
     // (space-separated openers, orphan-closer consumption) so fenced SurfDoc
     // examples in prose stay literal; the pre-existing strict-opener path is
     // deliberately left non-fence-aware to avoid behavior churn.
-    let mut in_fence = false;
+    let mut in_fence = in_fence;
 
-    for (idx, &line) in lines.iter().enumerate().skip(body_start) {
+    // Directive openers seen so far (any depth), for `max_blocks`; and the
+    // line a resource bound stopped the scan at.
+    let mut directives = 0usize;
+    let mut stopped: Option<usize> = None;
+
+    for (idx, &line) in lines.iter().enumerate().skip(start) {
         let trimmed = line.trim();
         let line_offset = byte_offset_start_of_line(idx, source);
 
@@ -226,15 +344,13 @@ fn scan_blocks(
                     let orphan = stack.pop().unwrap();
                     diagnostics.push(Diagnostic {
                         severity: Severity::Warning,
-                        message: format!(
-                            "Unclosed block directive '{}' opened at line {}",
-                            orphan.name, orphan.start_line
-                        ),
+                        message: p001_message(&orphan.name, orphan.start_line),
                         span: Some(Span {
                             start_line: orphan.start_line,
                             end_line: idx + 1,
                             start_offset: orphan.start_offset,
                             end_offset: line_offset + line.len(),
+                            file: FileId::ROOT,
                         }),
                         code: Some("P001".into()),
                         fix: None,
@@ -251,12 +367,14 @@ fn scan_blocks(
                     blocks.push(Block::Unknown {
                         name: open.name,
                         attrs: open.attrs,
+                        attr_spans: open.attr_spans,
                         content: content.to_string(),
                         span: Span {
                             start_line: open.start_line,
                             end_line: idx + 1,
                             start_offset: open.start_offset,
                             end_offset: line_offset + line.len(),
+                            file: FileId::ROOT,
                         },
                     });
 
@@ -284,7 +402,7 @@ fn scan_blocks(
                 );
                 diagnostics.push(Diagnostic {
                     severity: Severity::Warning,
-                    message: format!("Closing directive with no open block at line {}", idx + 1),
+                    message: p006_message(idx + 1),
                     span: Some(line_span(idx, idx, source)),
                     code: Some("P006".into()),
                     fix: None,
@@ -325,12 +443,14 @@ fn scan_blocks(
                 blocks.push(Block::Unknown {
                     name: open.name,
                     attrs: open.attrs,
+                    attr_spans: open.attr_spans,
                     content: content.to_string(),
                     span: Span {
                         start_line: open.start_line,
                         end_line: idx + 1,
                         start_offset: open.start_offset,
                         end_offset: line_offset,
+                        file: FileId::ROOT,
                     },
                 });
 
@@ -338,6 +458,43 @@ fn scan_blocks(
                 md_start_offset = None;
             }
 
+            directives += 1;
+            let exceeded = if let Some(max) =
+                options.max_attr_len.filter(|max| attrs_str.len() > *max)
+            {
+                Some((
+                    "max_attr_len",
+                    format!(
+                        "attributes on '::{name}' at line {} are {} bytes (limit {max})",
+                        idx + 1,
+                        attrs_str.len()
+                    ),
+                ))
+            } else if let Some(max) = options.max_depth.filter(|max| stack.len() + 1 > *max) {
+                Some((
+                    "max_depth",
+                    format!("nesting depth {} at line {} (limit {max})", stack.len() + 1, idx + 1),
+                ))
+            } else {
+                options.max_blocks.filter(|max| directives > *max).map(|max| {
+                    (
+                        "max_blocks",
+                        format!("block directive #{directives} at line {} (limit {max})", idx + 1),
+                    )
+                })
+            };
+            if let Some((bound, detail)) = exceeded {
+                diagnostics.push(Diagnostic {
+                    severity: Severity::Error,
+                    message: p007_message(bound, &format!("{detail}; scanning stopped")),
+                    span: Some(line_span(idx, idx, source)),
+                    code: Some("P007".into()),
+                    fix: None,
+                });
+                stopped = Some(idx);
+                break;
+            }
+
             // If we're at top level, flush any accumulated markdown.
             if stack.is_empty() {
                 flush_markdown(
@@ -347,9 +504,14 @@ fn scan_blocks(
                     idx,
                     source,
                 );
+                if sync(idx, in_fence) {
+                    return (blocks, Some(idx));
+                }
 
-                let attrs = match parse_attrs(&attrs_str) {
-                    Ok(a) => a,
+                let indent = line.len() - line.trim_start().len();
+                let at = line_offset + indent + directive_name_start(trimmed, depth) + name.len();
+                let (attrs, attr_spans) = match parse_attrs_at(&attrs_str, at, idx + 1) {
+                    Ok(parsed) => parsed,
                     Err(e) => {
                         diagnostics.push(Diagnostic {
                             severity: Severity::Warning,
@@ -358,7 +520,7 @@ fn scan_blocks(
                             code: Some("P003".into()),
                             fix: None,
                         });
-                        Attrs::new()
+                        (Attrs::new(), AttrSpans::new())
                     }
                 };
 
@@ -382,6 +544,7 @@ fn scan_blocks(
                     blocks.push(Block::Unknown {
                         name,
                         attrs,
+                        attr_spans,
                         content: String::new(),
                         span: line_span(idx, idx, source),
                     });
@@ -397,6 +560,7 @@ fn scan_blocks(
                 stack.push(OpenBlock {
                     name,
                     attrs,
+                    attr_spans,
                     depth,
                     start_line: idx + 1,
                     start_offset: line_offset,
@@ -409,6 +573,7 @@ fn scan_blocks(
                 stack.push(OpenBlock {
                     name,
                     attrs: Attrs::new(),
+                    attr_spans: AttrSpans::new(),
                     depth,
                     start_line: idx + 1,
                     start_offset: line_offset,
@@ -425,36 +590,41 @@ fn scan_blocks(
         }
     }
 
+    // A bound that stopped the scan acts as end of input at the start of
+    // the offending line.
+    let (eof_line, eof_offset) = match stopped {
+        Some(idx) => (idx, byte_offset_start_of_line(idx, source)),
+        None => (lines.len(), source.len()),
+    };
+
     // Flush any remaining markdown.
     flush_markdown(
         &mut blocks,
         &mut md_start_line,
         &mut md_start_offset,
-        lines.len(),
+        eof_line,
         source,
     );
 
     // Force-close any remaining open blocks (unclosed at EOF).
     // Only the outermost (bottom of stack) gets emitted; inner ones just get diagnostics.
     while let Some(open) = stack.pop() {
-        let eof_offset = source.len();
-        let eof_line = lines.len();
-
-        diagnostics.push(Diagnostic {
-            severity: Severity::Warning,
-            message: format!(
-                "Unclosed block directive '{}' opened at line {}",
-                open.name, open.start_line
-            ),
-            span: Some(Span {
-                start_line: open.start_line,
-                end_line: eof_line,
-                start_offset: open.start_offset,
-                end_offset: eof_offset,
-            }),
-            code: Some("P001".into()),
-            fix: None,
-        });
+        // Cut off by a bound, not unclosed: P007 already covers it.
+        if stopped.is_none() {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                message: p001_message(&open.name, open.start_line),
+                span: Some(Span {
+                    start_line: open.start_line,
+                    end_line: eof_line,
+                    start_offset: open.start_offset,
+                    end_offset: eof_offset,
+                    file: FileId::ROOT,
+                }),
+                code: Some("P001".into()),
+                fix: None,
+            });
+        }
 
         // Only emit for the outermost block (stack now empty).
         if stack.is_empty() {
@@ -468,18 +638,37 @@ fn scan_blocks(
             blocks.push(Block::Unknown {
                 name: open.name,
                 attrs: open.attrs,
+                attr_spans: open.attr_spans,
                 content: content.to_string(),
                 span: Span {
                     start_line: open.start_line,
                     end_line: eof_line,
                     start_offset: open.start_offset,
                     end_offset: eof_offset,
+                    file: FileId::ROOT,
                 },
             });
         }
     }
 
-    blocks
+    (blocks, None)
+}
+
+/// Message for a P001 diagnostic (block directive never closed). The line
+/// number is 1-based; the incremental reparser re-renders it when it shifts a
+/// reused diagnostic.
+pub(crate) fn p001_message(name: &str, line: usize) -> String {
+    format!("Unclosed block directive '{name}' opened at line {line}")
+}
+
+/// Message for a P006 diagnostic (closing directive with no open block).
+pub(crate) fn p006_message(line: usize) -> String {
+    format!("Closing directive with no open block at line {line}")
+}
+
+/// Message for a P007 diagnostic (a [`ParseOptions`] bound was hit).
+pub(crate) fn p007_message(bound: &str, detail: &str) -> String {
+    format!("Parse bound '{bound}' exceeded: {detail}")
 }
 
 /// Flush accumulated markdown lines into a `Block::Markdown`.
@@ -513,6 +702,7 @@ fn flush_markdown(
                     end_line: end_idx + 1,
                     start_offset: start_off,
                     end_offset,
+                    file: FileId::ROOT,
                 },
             });
         }
@@ -537,14 +727,33 @@ fn flush_markdown(
 /// container force-close behavior (return false; caller pushes + P001s).
 ///
 /// Mirrors the semantics of `crate::blocks::scan_container_close`.
-fn is_leaf_before_sibling(lines: &[&str], after_idx: usize, depth: usize) -> bool {
+pub(crate) fn is_leaf_before_sibling(lines: &[&str], after_idx: usize, depth: usize) -> bool {
+    match leaf_lookahead(lines, after_idx, depth) {
+        LeafLookahead::Closed { .. } => false,
+        LeafLookahead::Open { saw_same_depth } => saw_same_depth,
+    }
+}
+
+/// Outcome of the [`is_leaf_before_sibling`] look-ahead.
+pub(crate) enum LeafLookahead {
+    /// The surplus closer arrived at line index `closer`: the opener is a
+    /// container. Final — no later line can change it.
+    Closed { closer: usize },
+    /// `lines` ran out first. Only provisional while more input may follow
+    /// (see [`crate::streaming`]): a later surplus closer still flips it.
+    Open { saw_same_depth: bool },
+}
+
+/// The look-ahead behind [`is_leaf_before_sibling`], reporting whether the
+/// decision was reached before the end of `lines`.
+pub(crate) fn leaf_lookahead(lines: &[&str], after_idx: usize, depth: usize) -> LeafLookahead {
     let mut pending = 0usize; // unmatched same-or-deeper openers
     let mut saw_same_depth = false;
-    for line in lines.iter().skip(after_idx) {
+    for (idx, line) in lines.iter().enumerate().skip(after_idx) {
         let trimmed = line.trim();
         if let Some(close_depth) = closing_directive_depth(trimmed) {
             if pending == 0 && close_depth == depth {
-                return false; // surplus closer is ours → container
+                return LeafLookahead::Closed { closer: idx }; // surplus closer is ours → container
             }
             pending = pending.saturating_sub(1);
             continue;
@@ -558,7 +767,8 @@ fn is_leaf_before_sibling(lines: &[&str], after_idx: usize, depth: usize) -> boo
             }
         }
     }
-    saw_same_depth // EOF without our closer: openers were siblings → leaf
+    // EOF without our closer: openers were siblings → leaf.
+    LeafLookahead::Open { saw_same_depth }
 }
 
 /// If the line is a closing directive (`::`, `:::`, …), return the depth (colon count).
@@ -681,7 +891,7 @@ pub(crate) fn find_attr_close(s: &str) -> Option<usize> {
 // ------------------------------------------------------------------
 
 /// Byte offset of the start of line `idx` (0-based) within `source`.
-fn byte_offset_start_of_line(idx: usize, source: &str) -> usize {
+pub(crate) fn byte_offset_start_of_line(idx: usize, source: &str) -> usize {
     let mut offset = 0;
     for (i, line) in source.split('\n').enumerate() {
         if i == idx {
@@ -712,6 +922,7 @@ fn line_span(start_idx: usize, end_idx: usize, source: &str) -> Span {
         end_line: end_idx + 1,
         start_offset: byte_offset_start_of_line(start_idx, source),
         end_offset: byte_offset_end_of_line(end_idx, source),
+        file: FileId::ROOT,
     }
 }
 
@@ -752,7 +963,7 @@ mod tests {
         let line = r#"::figure[src="https://x/y.jpg" caption="HQ in [[Mountain View, California]], 2009"]"#;
         let (_, name, attrs_str) = opening_directive(line).expect("directive parses");
         assert_eq!(name, "figure");
-        let attrs = parse_attrs(&attrs_str).expect("attrs parse");
+        let attrs = crate::attrs::parse_attrs(&attrs_str).expect("attrs parse");
         let str_attr = |k: &str| match attrs.get(k) {
             Some(crate::types::AttrValue::String(s)) => Some(s.clone()),
             _ => None,
@@ -1524,4 +1735,108 @@ Body.
             other => panic!("Expected Markdown block, got {other:?}"),
         }
     }
+
+    fn p007(result: &ParseResult) -> Vec<&Diagnostic> {
+        result
+            .diagnostics
+            .iter()
+            .filter(|d| d.code.as_deref() == Some("P007"))
+            .collect()
+    }
+
+    #[test]
+    fn parse_with_default_options_is_parse() {
+        let input = "# T\n\n::columns\n:::column\nA\n:::\n::\n::metric[label=x value=1]\n";
+        let bounded = parse_with(input, &ParseOptions::default());
+        let plain = parse(input);
+        assert_eq!(
+            serde_json::to_value(&bounded.doc.blocks).unwrap(),
+            serde_json::to_value(&plain.doc.blocks).unwrap()
+        );
+        assert_eq!(bounded.diagnostics.len(), plain.diagnostics.len());
+    }
+
+    #[test]
+    fn max_source_bytes_rejects_before_parsing() {
+        let input = "---\ntitle: T\n---\n# Hello\n";
+        let options = ParseOptions {
+            max_source_bytes: Some(8),
+            ..ParseOptions::default()
+        };
+        let result = parse_with(input, &options);
+        assert!(result.doc.blocks.is_empty());
+        assert!(result.doc.front_matter.is_none());
+        let p007 = p007(&result);
+        assert_eq!(p007.len(), 1);
+        assert_eq!(p007[0].severity, Severity::Error);
+        assert!(p007[0].message.contains("max_source_bytes"), "{}", p007[0].message);
+        // At the limit is fine.
+        let options = ParseOptions {
+            max_source_bytes: Some(input.len()),
+            ..ParseOptions::default()
+        };
+        assert!(parse_with(input, &options).diagnostics.is_empty());
+    }
+
+    #[test]
+    fn max_depth_stops_at_the_offending_line() {
+        let input = "Intro.\n\n::columns\n:::column\nA\n:::\n::\n\nTail.\n";
+        let options = ParseOptions {
+            max_depth: Some(1),
+            ..ParseOptions::default()
+        };
+        let result = parse_with(input, &options);
+        let p007 = p007(&result);
+        assert_eq!(p007.len(), 1, "{:?}", result.diagnostics);
+        assert_eq!(p007[0].span.unwrap().start_line, 4);
+        // The open container is closed at the stop line without a P001, and
+        // nothing after it (the prose tail included) is parsed.
+        assert!(result.diagnostics.iter().all(|d| d.code.as_deref() != Some("P001")));
+        assert_eq!(result.doc.blocks.len(), 2);
+        let span = result.doc.blocks[1].span();
+        assert_eq!((span.start_line, span.end_line), (3, 3));
+        assert!(!result.doc.blocks.iter().any(
+            |b| matches!(b, Block::Markdown { content, .. } if content.contains("Tail"))
+        ));
+    }
+
+    #[test]
+    fn max_blocks_counts_directives_at_every_depth() {
+        let input = "::columns\n:::column\nA\n:::\n::\n::summary\nS\n::\n";
+        let at_limit = ParseOptions {
+            max_blocks: Some(3),
+            ..ParseOptions::default()
+        };
+        assert!(p007(&parse_with(input, &at_limit)).is_empty());
+        let over = ParseOptions {
+            max_blocks: Some(2),
+            ..ParseOptions::default()
+        };
+        let result = parse_with(input, &over);
+        let p007 = p007(&result);
+        assert_eq!(p007.len(), 1);
+        assert_eq!(p007[0].span.unwrap().start_line, 6);
+        assert_eq!(result.doc.blocks.len(), 1);
+    }
+
+    #[test]
+    fn max_attr_len_applies_to_nested_openers() {
+        let input = "::columns\n:::column[width=\"a-very-long-value\"]\nA\n:::\n::\n";
+        let options = ParseOptions {
+            max_attr_len: Some(16),
+            ..ParseOptions::default()
+        };
+        let result = parse_with(input, &options);
+        let p007 = p007(&result);
+        assert_eq!(p007.len(), 1);
+        assert!(p007[0].message.contains("'::column'"), "{}", p007[0].message);
+    }
+
+    #[test]
+    fn parse_options_deserialize_with_defaults() {
+        let options: ParseOptions = serde_json::from_str(r#"{"max_depth": 4}"#).unwrap();
+        assert_eq!(options.max_depth, Some(4));
+        assert_eq!(options.max_blocks, None);
+        assert!(serde_json::from_str::<ParseOptions>(r#"{"max_dpeth": 4}"#).is_err());
+    }
 }
@@ -16,7 +16,11 @@ fn render_markdown(content: &str) -> String {
     options.insert(pulldown_cmark::Options::ENABLE_TABLES);
     options.insert(pulldown_cmark::Options::ENABLE_STRIKETHROUGH);
     options.insert(pulldown_cmark::Options::ENABLE_TASKLISTS);
-    let parser = pulldown_cmark::Parser::new_ext(content, options);
+    // Footnotes are numbered per document, not per block, so pulldown-cmark's
+    // own footnote support stays off: definitions are lifted out here and
+    // references resolved after rendering (see `crate::footnote`).
+    let content = crate::footnote::strip_definitions(content);
+    let parser = pulldown_cmark::Parser::new_ext(&content, options);
     let mut html_output = String::new();
     pulldown_cmark::html::push_html(&mut html_output, parser);
     // Sanitize HTML to prevent XSS — strips dangerous tags (script, iframe,
@@ -26,8 +30,51 @@ fn render_markdown(content: &str) -> String {
     // Wrap bare <table> tags in scroll containers for mobile responsiveness
     let html_output = html_output.replace("<table>", "<div class=\"surfdoc-table-wrap\"><table>");
     let html_output = html_output.replace("</table>", "</table></div>");
-    // Resolve inline `[@key]` citations against the ambient citation context.
-    substitute_cites_html(&html_output)
+    // Resolve inline `[@key]` citations against the ambient citation context,
+    // then footnote references (note-style citations become references), then
+    // `[#label]` cross-references.
+    let html_output = substitute_cites_html(&html_output);
+    let html_output = crate::footnote::substitute_refs(&html_output, render_footnote_ref_html);
+    crate::xref::substitute_xrefs(&html_output, |t| {
+        format!(
+            "<a class=\"surfdoc-xref\" href=\"#{}\">{}</a>",
+            escape_html(&t.label),
+            t.text()
+        )
+    })
+}
+
+/// A footnote reference: a superscript number linking to its endnote. Only
+/// the first reference to a note carries the back-link target id.
+fn render_footnote_ref_html(note: &crate::footnote::Note) -> String {
+    let n = note.number;
+    let first =
+        citation::with_active(|ctx| ctx.is_some_and(|c| c.notes.first_reference(note.number)));
+    let id = if first {
+        format!(" id=\"fnref-{n}\"")
+    } else {
+        String::new()
+    };
+    format!("<sup class=\"surfdoc-footnote-ref\"><a href=\"#fn-{n}\"{id}>{n}</a></sup>")
+}
+
+/// The endnote section for the footnotes referenced during this render;
+/// empty when there are none.
+fn render_footnotes_html() -> String {
+    let notes = crate::footnote::document_notes(render_inline_markdown_phrasing);
+    if notes.is_empty() {
+        return String::new();
+    }
+    let mut out = String::from(
+        "<section class=\"surfdoc-footnotes\" role=\"doc-endnotes\" aria-label=\"Notes\"><ol class=\"surfdoc-footnotes-list\">",
+    );
+    for (n, body) in notes {
+        out.push_str(&format!(
+            "<li id=\"fn-{n}\" class=\"surfdoc-footnote\">{body} <a class=\"surfdoc-footnote-back\" href=\"#fnref-{n}\" aria-label=\"Back to reference {n}\">\u{21a9}</a></li>"
+        ));
+    }
+    out.push_str("</ol></section>");
+    out
 }
 
 /// Replace inline `[@key]` citation tokens in rendered HTML with anchored
@@ -59,11 +106,20 @@ fn substitute_cites_html(html: &str) -> String {
 /// Render a single inline citation group as an anchor linking to its first
 /// reference's bibliography entry. Numbered styles wrap in `<sup>`.
 fn render_cite_html(cr: &CiteRef, ctx: &citation::CiteContext) -> String {
-    let text = citation::format_in_text(&ctx.references, cr, ctx.style, &ctx.numbers);
+    let text = ctx.in_text(cr);
+    if ctx.note_citations() {
+        // Resolved into a footnote reference by `render_markdown`.
+        return format!("[^{}]", ctx.notes.add(text));
+    }
     let first_key = cr.items.first().map(|i| i.key.as_str()).unwrap_or("");
     let href = format!("#ref-{}", escape_html(first_key));
-    let inner = escape_html(&text);
-    if citation::is_numbered(ctx.style) {
+    // CSL styles may italicize (`*Title*`) inside the citation.
+    let inner = if ctx.csl.is_some() {
+        render_inline_markdown_phrasing(&text)
+    } else {
+        escape_html(&text)
+    };
+    if ctx.superscript() {
         format!(
             "<sup class=\"surfdoc-cite\"><a href=\"{href}\">{inner}</a></sup>"
         )
@@ -81,22 +137,16 @@ fn render_bibliography_html(style_override: Option<Format>) -> String {
         if ctx.references.is_empty() {
             return String::new();
         }
-        let style = style_override.unwrap_or(ctx.style);
-        // With no override, use citation-number order for numbered styles; with
-        // an override, pass definition order (the keyed list sorts author styles
-        // and numbers numbered styles by input order).
-        let refs = if style_override.is_some() {
-            ctx.references.clone()
-        } else {
-            citation::ordered_references(ctx)
-        };
-        let entries = citation::reference_list_keyed(&refs, style);
-        let heading = citation::bibliography_heading(style);
-        let style_slug = format_slug(style);
+        // With no override, the document's CSL style or citation-number order
+        // for numbered styles; with an override, definition order (the keyed
+        // list sorts author styles and numbers numbered styles by input order).
+        let list = ctx.bibliography(style_override);
+        let heading = list.heading.as_str();
+        let style_slug = list.style.map_or("csl", format_slug);
         let mut body = String::new();
-        if citation::is_numbered(style) {
+        if list.numbered {
             body.push_str("<ol class=\"surfdoc-bibliography-list\">");
-            for (key, formatted) in &entries {
+            for (key, formatted) in &list.entries {
                 body.push_str(&format!(
                     "<li id=\"ref-{}\" class=\"surfdoc-bibliography-entry\">{}</li>",
                     escape_html(key),
@@ -106,7 +156,7 @@ fn render_bibliography_html(style_override: Option<Format>) -> String {
             body.push_str("</ol>");
         } else {
             body.push_str("<div class=\"surfdoc-bibliography-list\">");
-            for (key, formatted) in &entries {
+            for (key, formatted) in &list.entries {
                 body.push_str(&format!(
                     "<p id=\"ref-{}\" class=\"surfdoc-bibliography-entry\">{}</p>",
                     escape_html(key),
@@ -817,8 +867,9 @@ fn strip_tags(html: &str) -> String {
 /// Split an explicit `{#slug}` anchor suffix off a heading's inner HTML.
 ///
 /// `Our Menu {#our-menu}` → `("Our Menu", "our-menu")`. Slug charset is
-/// `[A-Za-z0-9_-]+`; anything else (prose braces, template syntax) is left
-/// untouched. Returns `None` when no valid trailing anchor exists.
+/// `[A-Za-z0-9_:.-]+` (so cross-reference labels like `sec:intro` work);
+/// anything else (prose braces, template syntax) is left untouched. Returns
+/// `None` when no valid trailing anchor exists.
 pub(crate) fn split_explicit_anchor(inner: &str) -> Option<(&str, &str)> {
     let trimmed = inner.trim_end();
     if !trimmed.ends_with('}') {
@@ -829,7 +880,7 @@ pub(crate) fn split_explicit_anchor(inner: &str) -> Option<(&str, &str)> {
     if slug.is_empty()
         || !slug
             .bytes()
-            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b':' | b'.'))
     {
         return None;
     }
@@ -1025,10 +1076,7 @@ fn toc_nested_ol(items: &[&(u32, String, String)]) -> String {
 pub fn to_html(doc: &SurfDoc) -> String {
     // Install the citation context so inline `[@key]` cites + `::bibliography`
     // resolve during this render (cleared on drop).
-    let _cite_scope = citation::install_context(citation::build_context(
-        &doc.blocks,
-        doc.front_matter.as_ref().and_then(|fm| fm.format),
-    ));
+    let _cite_scope = citation::install_context(citation::document_context(doc));
 
     let mut parts: Vec<String> = Vec::new();
     let mut css_overrides = String::new();
@@ -1121,6 +1169,11 @@ pub fn to_html(doc: &SurfDoc) -> String {
         parts.push("</section>".to_string());
     }
 
+    let footnotes = render_footnotes_html();
+    if !footnotes.is_empty() {
+        parts.push(footnotes);
+    }
+
     wire_headings_and_toc(&parts.join("\n"))
 }
 
@@ -1159,6 +1212,10 @@ pub fn to_html_fragment(blocks: &[Block]) -> String {
     if !cta_group.is_empty() {
         parts.push(format!("<div class=\"surfdoc-cta-group\">{}</div>", cta_group.join("\n")));
     }
+    let footnotes = render_footnotes_html();
+    if !footnotes.is_empty() {
+        parts.push(footnotes);
+    }
     wire_headings_and_toc(&parts.join("\n"))
 }
 
@@ -2445,6 +2502,29 @@ fn render_surfy_panel_children(children: &[Block]) -> String {
 }
 
 pub(crate) fn render_block(block: &Block) -> String {
+    let html = render_block_body(block);
+    match crate::xref::block_label(block) {
+        Some(label) => with_anchor(&html, label),
+        None => html,
+    }
+}
+
+/// Give a cross-reference target's HTML the `id` its `[#label]` references
+/// link to: on the root element, or on a wrapper when the root already has
+/// an id.
+fn with_anchor(html: &str, label: &str) -> String {
+    let id = escape_html(label);
+    let open = html.strip_prefix('<').filter(|o| !o.starts_with('!'));
+    let Some(open) = open.filter(|o| !o[..o.find('>').unwrap_or(o.len())].contains(" id=")) else {
+        return format!("<div id=\"{id}\">{html}</div>");
+    };
+    let name_end = open
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .unwrap_or(open.len());
+    format!("<{} id=\"{id}\"{}", &open[..name_end], &open[name_end..])
+}
+
+fn render_block_body(block: &Block) -> String {
     match block {
         Block::Markdown { content, .. } => render_markdown(content),
 
@@ -2635,8 +2715,9 @@ pub(crate) fn render_block(block: &Block) -> String {
             )
         }
 
-        // `::cite` is a definition only — it renders nothing visible.
-        Block::Cite { .. } => String::new(),
+        // `::cite` and `::bibliography-data` are definitions only — they
+        // render nothing visible.
+        Block::Cite { .. } | Block::BibliographyData { .. } => String::new(),
 
         Block::Bibliography { style, .. } => render_bibliography_html(*style),
 
@@ -2675,6 +2756,7 @@ pub(crate) fn render_block(block: &Block) -> String {
         Block::Diagram {
             diagram_type,
             title,
+            direction,
             content,
             ..
         } => {
@@ -2695,6 +2777,8 @@ pub(crate) fn render_block(block: &Block) -> String {
                 Some(t) => (t.diagram_type, t.content.as_str()),
                 None => (diagram_type.as_str(), content.as_str()),
             };
+            // An authored `direction` beats the mermaid header's.
+            let direction = direction.or(translated.as_ref().and_then(|t| t.direction));
             // Chart-alias types (pie/donut/radar/xychart) forward the body to
             // the `::chart` pipeline — the body is the same pipe-delimited
             // table `::chart` accepts. An unusable body degrades to the same
@@ -2716,7 +2800,7 @@ pub(crate) fn render_block(block: &Block) -> String {
             }
             match crate::diagram::parse_diagram_source(eff_type, eff_content) {
                 Ok(model) => {
-                    let svg = crate::diagram::render_svg(&model, title.as_deref());
+                    let svg = crate::diagram::render_svg(&model, title.as_deref(), direction);
                     format!(
                         "<figure class=\"surfdoc-diagram surfdoc-diagram-{}\">{caption_html}{svg}</figure>",
                         escape_html(eff_type),
@@ -6938,6 +7022,7 @@ mod tests {
             end_line: 1,
             start_offset: 0,
             end_offset: 0,
+            file: FileId::ROOT,
         }
     }
 
@@ -7205,6 +7290,7 @@ mod tests {
     #[test]
     fn html_code() {
         let doc = doc_with(vec![Block::Code {
+            id: None,
             lang: Some("rust".into()),
             file: None,
             highlight: vec![],
@@ -7225,6 +7311,7 @@ mod tests {
     #[test]
     fn html_code_with_file_and_highlight() {
         let doc = doc_with(vec![Block::Code {
+            id: None,
             lang: Some("rust".into()),
             file: Some("main.rs".into()),
             highlight: vec!["2".into(), "4-5".into()],
@@ -7250,11 +7337,13 @@ mod tests {
                     done: true,
                     text: "Done item".into(),
                     assignee: None,
+                    span: None,
                 },
                 TaskItem {
                     done: false,
                     text: "Pending item".into(),
                     assignee: None,
+                    span: None,
                 },
             ],
             span: span(),
@@ -7275,6 +7364,7 @@ mod tests {
                 done: false,
                 text: "Ship it".into(),
                 assignee: Some("brady".into()),
+                span: None,
             }],
             span: span(),
         }]);
@@ -7302,6 +7392,7 @@ mod tests {
     #[test]
     fn html_figure() {
         let doc = doc_with(vec![Block::Figure {
+            id: None,
             src: "arch.png".into(),
             caption: Some("Architecture diagram".into()),
             alt: Some("System architecture".into()),
@@ -7322,8 +7413,10 @@ mod tests {
     #[test]
     fn html_diagram_architecture_success() {
         let doc = doc_with(vec![Block::Diagram {
+            id: None,
             diagram_type: "architecture".into(),
             title: Some("System Map".into()),
+            direction: None,
             content: "web: Web\napi: API\nweb -> api: HTTPS".into(),
             span: span(),
         }]);
@@ -7338,8 +7431,10 @@ mod tests {
     #[test]
     fn html_diagram_erd_success() {
         let doc = doc_with(vec![Block::Diagram {
+            id: None,
             diagram_type: "erd".into(),
             title: None,
+            direction: None,
             content: "users: id pk\norders: id pk, user_id fk\nusers 1--* orders".into(),
             span: span(),
         }]);
@@ -7355,8 +7450,10 @@ mod tests {
         // render_block directly: page chrome has its own inline svg icons,
         // so the no-svg assertion must scope to the block fragment.
         let html = render_block(&Block::Diagram {
+            id: None,
             diagram_type: "venn".into(),
             title: Some("Later".into()),
+            direction: None,
             content: "start => end".into(),
             span: span(),
         });
@@ -7370,8 +7467,10 @@ mod tests {
     fn html_diagram_malformed_dsl_falls_back() {
         // Malformed DSL must NEVER fail a render — prose fallback always.
         let doc = doc_with(vec![Block::Diagram {
+            id: None,
             diagram_type: "architecture".into(),
             title: None,
+            direction: None,
             content: "this is not a node or an edge".into(),
             span: span(),
         }]);
@@ -7392,8 +7491,10 @@ mod tests {
             ("xychart", "surfdoc-chart-svg"),
         ] {
             let html = render_block(&Block::Diagram {
+                id: None,
                 diagram_type: alias.into(),
                 title: Some("Share".into()),
+                direction: None,
                 content: "Segment | Value\nA | 40\nB | 60".into(),
                 span: span(),
             });
@@ -7413,8 +7514,10 @@ mod tests {
         // A body the chart pipeline cannot use degrades to prose, exactly
         // like malformed geometry DSL.
         let html = render_block(&Block::Diagram {
+            id: None,
             diagram_type: "pie".into(),
             title: None,
+            direction: None,
             content: "not a table".into(),
             span: span(),
         });
@@ -7433,8 +7536,10 @@ mod tests {
             ("usecase", "actor u: User\nusecase c: Case\nu -> c", "surfdoc-diagram-boundary"),
         ] {
             let html = render_block(&Block::Diagram {
+                id: None,
                 diagram_type: kind.into(),
                 title: None,
+                direction: None,
                 content: body.into(),
                 span: span(),
             });
@@ -8145,10 +8250,12 @@ mod tests {
                 crate::types::FaqItem {
                     question: "Is it free?".into(),
                     answer: "Yes, the free tier is forever.".into(),
+                    span: None,
                 },
                 crate::types::FaqItem {
                     question: "Can I self-host?".into(),
                     answer: "Docker image available.".into(),
+                    span: None,
                 },
             ],
             span: span(),
@@ -8194,6 +8301,7 @@ mod tests {
             items: vec![crate::types::FaqItem {
                 question: "<script>alert('q')</script>".into(),
                 answer: "<img onerror=alert(1)>".into(),
+                span: None,
             }],
             span: span(),
         }]);
@@ -8237,6 +8345,7 @@ mod tests {
                     span: span(),
                 },
             ],
+            attr_spans: Default::default(),
             span: span(),
         }]);
         let html = to_html(&doc);
@@ -8265,6 +8374,7 @@ mod tests {
                     span: span(),
                 },
             ],
+            attr_spans: Default::default(),
             span: span(),
         }]);
         let html = to_html(&doc);
@@ -8578,6 +8688,7 @@ mod tests {
     #[test]
     fn aria_code_label() {
         let doc = doc_with(vec![Block::Code {
+            id: None,
             lang: Some("python".into()),
             file: None,
             highlight: vec![],
@@ -8595,6 +8706,7 @@ mod tests {
                 done: false,
                 text: "Do thing".into(),
                 assignee: None,
+                span: None,
             }],
             span: span(),
         }]);
@@ -8862,6 +8974,7 @@ mod tests {
             sidebar: false,
             content: String::new(),
             children: vec![],
+            attr_spans: Default::default(),
             span: span(),
         }]);
         let html = to_html(&doc);
@@ -8877,6 +8990,7 @@ mod tests {
             sidebar: false,
             content: String::new(),
             children: vec![],
+            attr_spans: Default::default(),
             span: span(),
         }]);
         let html = to_html(&doc);
@@ -8889,6 +9003,7 @@ mod tests {
             name: "custom".into(),
             attrs: Default::default(),
             content: "stuff".into(),
+            attr_spans: Default::default(),
             span: span(),
         }]);
         let html = to_html(&doc);
@@ -8905,6 +9020,7 @@ mod tests {
             name: "team".into(),
             attrs: Default::default(),
             content: "## Crew\n- Ada\n- Lin".into(),
+            attr_spans: Default::default(),
             span: span(),
         }]);
         let html = to_html(&doc);
@@ -8921,6 +9037,7 @@ mod tests {
             name: "team".into(),
             attrs: Default::default(),
             content: "<script>alert(1)</script>Hi".into(),
+            attr_spans: Default::default(),
             span: span(),
         }]);
         let html = to_html(&doc);
@@ -9003,6 +9120,7 @@ mod tests {
                     content: "# Welcome".into(),
                     span: span(),
                 }],
+                attr_spans: Default::default(),
                 span: span(),
             },
             Block::Page {
@@ -9015,6 +9133,7 @@ mod tests {
                     content: "# About".into(),
                     span: span(),
                 }],
+                attr_spans: Default::default(),
                 span: span(),
             },
         ]);
@@ -9851,8 +9970,8 @@ mod tests {
     fn html_nav_renders_links() {
         let doc = doc_with(vec![Block::Nav {
             items: vec![
-                crate::types::NavItem { label: "Home".into(), href: "/".into(), icon: None, image: None, external: false },
-                crate::types::NavItem { label: "About".into(), href: "#about".into(), icon: None, image: None, external: false },
+                crate::types::NavItem { label: "Home".into(), href: "/".into(), icon: None, image: None, external: false, span: None },
+                crate::types::NavItem { label: "About".into(), href: "#about".into(), icon: None, image: None, external: false, span: None },
             ],
             logo: Some("MySite".into()),
             groups: vec![], brand: None, brand_reg: false, cta: None, drawer: false, minimal: false,
@@ -9885,7 +10004,7 @@ mod tests {
             Block::Markdown { content: "# Section One".into(), span: span() },
             Block::Nav {
                 items: vec![
-                    crate::types::NavItem { label: "Top".into(), href: "#top".into(), icon: None, image: None, external: false },
+                    crate::types::NavItem { label: "Top".into(), href: "#top".into(), icon: None, image: None, external: false, span: None },
                 ],
                 logo: None,
                 groups: vec![], brand: None, brand_reg: false, cta: None, drawer: false, minimal: false,
@@ -9908,7 +10027,7 @@ mod tests {
             },
             Block::Nav {
                 items: vec![
-                    crate::types::NavItem { label: "Docs".into(), href: "/docs".into(), icon: None, image: None, external: false },
+                    crate::types::NavItem { label: "Docs".into(), href: "/docs".into(), icon: None, image: None, external: false, span: None },
                 ],
                 logo: None,
                 groups: vec![], brand: None, brand_reg: false, cta: None, drawer: false, minimal: false,
@@ -9930,6 +10049,7 @@ mod tests {
                     icon: Some("github".into()),
                     image: None,
                     external: false,
+                    span: None,
                 },
             ],
             logo: None,
@@ -9954,6 +10074,7 @@ mod tests {
                     icon: None,
                     image: None,
                     external: false,
+                    span: None,
                 },
             ],
             logo: Some("<img onerror=alert(1)>".into()),
@@ -9986,6 +10107,7 @@ mod tests {
             icon: icon.map(|s| s.into()),
             image: None,
             external: false,
+            span: None,
         }
     }
 
@@ -10007,6 +10129,7 @@ mod tests {
                         icon: None,
                         image: Some("/surf.png".into()),
                         external: true,
+                        span: None,
                     }],
                 },
             ],
@@ -10702,6 +10825,7 @@ About
     #[test]
     fn html_figure_missing_alt_renders_empty() {
         let doc = doc_with(vec![Block::Figure {
+            id: None,
             src: "photo.jpg".into(),
             caption: Some("A photo".into()),
             alt: None,
@@ -10757,6 +10881,7 @@ About
     fn html_uploaded_image_relative_path() {
         // User-uploaded images use /uploads/ paths — verify they render as-is
         let doc = doc_with(vec![Block::Figure {
+            id: None,
             src: "/uploads/abc123-photo.jpg".into(),
             caption: Some("My uploaded photo".into()),
             alt: Some("Photo".into()),
@@ -11100,6 +11225,7 @@ About
     #[test]
     fn fragment_single_block() {
         let blocks = vec![Block::Code {
+            id: None,
             lang: Some("rust".into()),
             file: None,
             highlight: vec![],
@@ -11138,6 +11264,7 @@ About
                     href: "/".into(),
                     icon: None,
                     image: None, external: false,
+                    span: None,
                 }],
                 logo: Some("MySite".into()),
                 groups: vec![], brand: None, brand_reg: false, cta: None, drawer: false, minimal: false,
@@ -13599,6 +13726,7 @@ About
                 done: false,
                 text: "Ship the **bold** fix".into(),
                 assignee: None,
+                span: None,
             }],
             span: span(),
         }]);
@@ -13617,6 +13745,7 @@ About
                 done: true,
                 text: "Reviewed *carefully*".into(),
                 assignee: None,
+                span: None,
             }],
             span: span(),
         }]);
@@ -13634,6 +13763,7 @@ About
                 done: false,
                 text: "<script>alert(1)</script>".into(),
                 assignee: None,
+                span: None,
             }],
             span: span(),
         }]);
@@ -13684,6 +13814,7 @@ mod proptest_summary {
             end_line: 1,
             start_offset: 0,
             end_offset: 0,
+            file: FileId::ROOT,
         }
     }
 
@@ -18,19 +18,16 @@
 //! Pure / deterministic: same input → byte-identical `.tex`. No new heavy deps —
 //! the `.tex` is built as strings.
 
-use crate::citation::{
-    bibliography_heading, format_in_text, is_numbered, ordered_references, reference_list,
-    with_active, CiteContext, CiteRef,
-};
+use crate::citation::{is_numbered, with_active, CiteContext, CiteRef};
 use crate::render_typst::{parse_backtick_code, parse_delimited, parse_link, split_ordered_list};
 use crate::types::*;
+use crate::xref::{Target, XrefKind};
 
 /// Render a [`SurfDoc`] to a complete LaTeX (`.tex`) document string.
 pub fn to_latex(doc: &SurfDoc) -> String {
     let format = doc.front_matter.as_ref().and_then(|fm| fm.format);
     let doc_type = doc.front_matter.as_ref().and_then(|fm| fm.doc_type);
-    let _cite_scope =
-        crate::citation::install_context(crate::citation::build_context(&doc.blocks, format));
+    let _cite_scope = crate::citation::install_context(crate::citation::document_context(doc));
 
     match crate::types::render_profile(doc_type, format) {
         RenderProfile::Paper(f) => latex_paper(doc, f),
@@ -296,7 +293,10 @@ fn push_common_preamble(out: &mut String) {
 fn latex_body(blocks: &[Block], style: Format, out: &mut String) {
     for b in blocks {
         match b {
-            Block::Cite { .. } | Block::Site { .. } | Block::Style { .. } => {}
+            Block::Cite { .. }
+            | Block::BibliographyData { .. }
+            | Block::Site { .. }
+            | Block::Style { .. } => {}
             Block::Bibliography { style: bstyle, .. } => {
                 latex_bibliography(bstyle.unwrap_or(style), out);
             }
@@ -306,13 +306,26 @@ fn latex_body(blocks: &[Block], style: Format, out: &mut String) {
 }
 
 fn latex_block(b: &Block, out: &mut String) {
+    let label = crate::xref::block_label(b);
     match b {
         Block::Markdown { content, .. } => {
             out.push_str(&render_prose_latex(content));
             out.push_str("\n\n");
         }
         Block::Code { lang, content, .. } => {
-            let _ = lang;
+            if crate::xref::is_equation(lang.as_deref()) {
+                out.push_str("\\begin{equation}\n");
+                out.push_str(content.trim());
+                out.push('\n');
+                if let Some(label) = label {
+                    out.push_str(&format!("\\label{{{label}}}\n"));
+                }
+                out.push_str("\\end{equation}\n\n");
+                return;
+            }
+            if let Some(label) = label {
+                out.push_str(&format!("\\hypertarget{{{label}}}{{}}%\n"));
+            }
             out.push_str("\\begin{verbatim}\n");
             out.push_str(content);
             out.push_str("\n\\end{verbatim}\n\n");
@@ -344,6 +357,12 @@ fn latex_block(b: &Block, out: &mut String) {
             out.push_str(&render_prose_latex(content));
             out.push_str("\n\\end{quote}\n\n");
         }
+        Block::Data { headers, rows, .. } if let Some(label) = label => {
+            out.push_str("\\begin{table}[h]\n\\centering\n");
+            latex_table(headers, rows, out);
+            out.push_str(&latex_label(label, false, "table"));
+            out.push_str("\\end{table}\n\n");
+        }
         Block::Data { headers, rows, .. } | Block::PricingTable { headers, rows, .. } => {
             latex_table(headers, rows, out);
         }
@@ -378,6 +397,9 @@ fn latex_block(b: &Block, out: &mut String) {
             if let Some(c) = caption {
                 out.push_str(&format!("\\caption{{{}}}\n", md_inline_to_latex(c)));
             }
+            if let Some(label) = label {
+                out.push_str(&latex_label(label, caption.is_some(), "figure"));
+            }
             out.push_str("\\end{figure}\n\n");
         }
         Block::Section {
@@ -439,6 +461,9 @@ fn latex_block(b: &Block, out: &mut String) {
             if let Some(t) = title {
                 out.push_str(&format!("\\caption{{{}}}\n", escape_latex(t)));
             }
+            if let Some(label) = label {
+                out.push_str(&latex_label(label, title.is_some(), "figure"));
+            }
             out.push_str("\\end{figure}\n\n");
         }
         Block::Diagram { title, content, .. } => {
@@ -448,6 +473,9 @@ fn latex_block(b: &Block, out: &mut String) {
             if let Some(t) = title {
                 out.push_str(&format!("\\caption{{{}}}\n", escape_latex(t)));
             }
+            if let Some(label) = label {
+                out.push_str(&latex_label(label, title.is_some(), "figure"));
+            }
             out.push_str("\\end{figure}\n\n");
         }
         Block::Divider { .. } => {
@@ -465,6 +493,16 @@ fn latex_block(b: &Block, out: &mut String) {
     }
 }
 
+/// `\label{…}` for a labelled float. An uncaptioned float steps its
+/// counter itself so the label still resolves to its number.
+fn latex_label(id: &str, captioned: bool, counter: &str) -> String {
+    if captioned {
+        format!("\\label{{{id}}}\n")
+    } else {
+        format!("\\refstepcounter{{{counter}}}\\label{{{id}}}\n")
+    }
+}
+
 fn latex_table(headers: &[String], rows: &[Vec<String>], out: &mut String) {
     let ncols = if !headers.is_empty() {
         headers.len()
@@ -492,21 +530,16 @@ fn latex_table(headers: &[String], rows: &[Vec<String>], out: &mut String) {
 
 fn latex_bibliography(style: Format, out: &mut String) {
     with_active(|ctx| {
-        let refs = match ctx {
+        let list = match ctx {
             Some(c) if !c.references.is_empty() => {
-                if style == c.style {
-                    ordered_references(c)
-                } else {
-                    c.references.clone()
-                }
+                c.bibliography((style != c.style).then_some(style))
             }
             _ => return,
         };
-        if is_numbered(style) {
+        if list.numbered {
             out.push_str("\\begin{thebibliography}{99}\n");
-            for (i, line) in reference_list(&refs, style).iter().enumerate() {
+            for (key, line) in &list.entries {
                 let body = line.splitn(2, "] ").nth(1).unwrap_or(line.as_str());
-                let key = refs.get(i).map(|r| r.key.as_str()).unwrap_or("ref");
                 out.push_str(&format!(
                     "\\bibitem{{{}}} {}\n",
                     key,
@@ -517,12 +550,12 @@ fn latex_bibliography(style: Format, out: &mut String) {
         } else {
             out.push_str(&format!(
                 "\\section*{{{}}}\n",
-                escape_latex(bibliography_heading(style))
+                escape_latex(&list.heading)
             ));
-            for line in reference_list(&refs, style) {
+            for (_, line) in &list.entries {
                 out.push_str(&format!(
                     "\\noindent\\hangindent=0.5in\\hangafter=1 {}\\par\\medskip\n",
-                    md_inline_to_latex(&line)
+                    md_inline_to_latex(line)
                 ));
             }
         }
@@ -535,28 +568,45 @@ fn latex_bibliography(style: Format, out: &mut String) {
 // ───────────────────────────────────────────────────────────────────────────
 
 /// Convert markdown prose to LaTeX, first substituting inline `[@key]` cites
-/// (numbered styles → `\cite{…}`; author styles → the formatted in-text string).
+/// (numbered styles → `\cite{…}`; author styles → the formatted in-text string;
+/// note styles → `\footnote{…}`), `[^label]` footnote references and
+/// `[#label]` cross-references.
 fn render_prose_latex(text: &str) -> String {
+    let text = crate::footnote::strip_definitions(text);
     with_active(|ctx| {
-        let cites = crate::inline::find_inline_cites(text);
-        if cites.is_empty() {
-            return md_block_to_latex(text);
+        let mut spans: Vec<(usize, usize, String)> = crate::inline::find_inline_cites(&text)
+            .into_iter()
+            .map(|(s, e, cr)| (s, e, ctx.map(|c| latex_in_text(&cr, c)).unwrap_or_default()))
+            .collect();
+        if let Some(c) = ctx {
+            // Unresolved references stay literal.
+            for (s, e, label) in crate::inline::find_footnote_refs(&text) {
+                if let Some(note) = c.notes.reference(&label) {
+                    let body = c.notes.within_note(|| render_prose_latex(&note.body));
+                    spans.push((s, e, format!("\\footnote{{{}}}", body.trim())));
+                }
+            }
+            for (s, e, label) in crate::inline::find_xrefs(&text) {
+                if let Some(target) = c.xrefs.get(&label) {
+                    spans.push((s, e, latex_xref(target)));
+                }
+            }
+        }
+        if spans.is_empty() {
+            return md_block_to_latex(&text);
         }
-        // Splice citation placeholders that survive the markdown→LaTeX pass,
-        // then substitute the rendered cite commands back in.
+        spans.sort_by_key(|(s, _, _)| *s);
+        // Splice placeholders that survive the markdown→LaTeX pass, then
+        // substitute the rendered commands back in.
         let mut prepared = String::with_capacity(text.len());
         let mut replacements: Vec<(String, String)> = Vec::new();
         let mut last = 0;
-        for (idx, (s, e, cr)) in cites.iter().enumerate() {
-            prepared.push_str(&text[last..*s]);
+        for (idx, (s, e, rep)) in spans.into_iter().enumerate() {
+            prepared.push_str(&text[last..s]);
             let token = format!("\u{1}CITE{idx}\u{2}");
-            let rep = match ctx {
-                Some(c) => latex_in_text(cr, c),
-                None => String::new(),
-            };
             prepared.push_str(&token);
             replacements.push((token, rep));
-            last = *e;
+            last = e;
         }
         prepared.push_str(&text[last..]);
         let mut converted = md_block_to_latex(&prepared);
@@ -567,8 +617,18 @@ fn render_prose_latex(text: &str) -> String {
     })
 }
 
+/// A cross-reference: `Figure~\ref{…}` against LaTeX's own numbering.
+/// Listings have no LaTeX counter here, so they link with the document's
+/// number instead.
+fn latex_xref(target: &Target) -> String {
+    match target.kind {
+        XrefKind::Listing => format!("\\hyperlink{{{}}}{{{}}}", target.label, target.text()),
+        kind => format!("{}~\\ref{{{}}}", kind.name(), target.label),
+    }
+}
+
 fn latex_in_text(cr: &CiteRef, ctx: &CiteContext) -> String {
-    if is_numbered(ctx.style) {
+    if ctx.csl.is_none() && is_numbered(ctx.style) {
         if cr.items.len() == 1 {
             let it = &cr.items[0];
             match &it.locator {
@@ -580,7 +640,25 @@ fn latex_in_text(cr: &CiteRef, ctx: &CiteContext) -> String {
             format!("\\cite{{{}}}", keys.join(","))
         }
     } else {
-        escape_latex(&format_in_text(&ctx.references, cr, ctx.style, &ctx.numbers))
+        let text = ctx.in_text(cr);
+        if ctx.note_citations() {
+            return format!("\\footnote{{{}}}", md_inline_to_latex(&text));
+        }
+        // CSL styles may italicize (`*Title*`) inside the citation.
+        if ctx.csl.is_some() {
+            md_inline_to_latex(&text)
+        } else {
+            escape_latex(&text)
+        }
+    }
+}
+
+/// A heading's LaTeX title and, for an explicit `{#label}` anchor, the
+/// `\label{…}` that cross-references point at.
+fn heading_latex(text: &str) -> (String, String) {
+    match crate::render_html::split_explicit_anchor(text) {
+        Some((title, label)) => (md_inline_to_latex(title), format!("\\label{{{label}}}")),
+        None => (md_inline_to_latex(text), String::new()),
     }
 }
 
@@ -617,22 +695,26 @@ fn md_block_to_latex(md: &str) -> String {
 
         // Headings
         if let Some(rest) = line.strip_prefix("#### ") {
-            out.push_str(&format!("\\paragraph{{{}}} ", md_inline_to_latex(rest)));
+            let (title, label) = heading_latex(rest);
+            out.push_str(&format!("\\paragraph{{{title}}}{label} "));
             i += 1;
             continue;
         }
         if let Some(rest) = line.strip_prefix("### ") {
-            out.push_str(&format!("\\subsubsection{{{}}}\n", md_inline_to_latex(rest)));
+            let (title, label) = heading_latex(rest);
+            out.push_str(&format!("\\subsubsection{{{title}}}{label}\n"));
             i += 1;
             continue;
         }
         if let Some(rest) = line.strip_prefix("## ") {
-            out.push_str(&format!("\\subsection{{{}}}\n", md_inline_to_latex(rest)));
+            let (title, label) = heading_latex(rest);
+            out.push_str(&format!("\\subsection{{{title}}}{label}\n"));
             i += 1;
             continue;
         }
         if let Some(rest) = line.strip_prefix("# ") {
-            out.push_str(&format!("\\section{{{}}}\n", md_inline_to_latex(rest)));
+            let (title, label) = heading_latex(rest);
+            out.push_str(&format!("\\section{{{title}}}{label}\n"));
             i += 1;
             continue;
         }
@@ -4,26 +4,44 @@
 //! Each block type is degraded to the nearest Markdown equivalent.
 
 use crate::citation;
+use crate::footnote;
 use crate::types::{Block, CalloutType, ChartType, DecisionStatus, Format, HttpMethod, ListDisplay, SurfDoc, Trend};
+use crate::xref::{self, Target};
 
 /// Render a `SurfDoc` as standard CommonMark markdown.
 ///
 /// The output contains no `::` directive markers. Each SurfDoc block type is
 /// degraded to its closest CommonMark equivalent.
 pub fn to_markdown(doc: &SurfDoc) -> String {
-    let _cite_scope = citation::install_context(citation::build_context(
-        &doc.blocks,
-        doc.front_matter.as_ref().and_then(|fm| fm.format),
-    ));
+    let _cite_scope = citation::install_context(citation::document_context(doc));
     let mut parts: Vec<String> = Vec::new();
 
     for block in &doc.blocks {
         parts.push(render_block(block));
     }
+    let notes = render_footnotes_md();
+    if !notes.is_empty() {
+        parts.push(notes);
+    }
 
     parts.join("\n\n")
 }
 
+/// Footnote definitions for the notes referenced in the document, renumbered
+/// in reference order. Continuation lines are indented four spaces.
+fn render_footnotes_md() -> String {
+    footnote::document_notes(citation::substitute_text_cites)
+        .into_iter()
+        .map(|(n, body)| {
+            format!(
+                "[^{n}]: {}",
+                body.replace('\n', "\n    ").replace("\n    \n", "\n\n")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Render a `::bibliography` as a markdown reference list (heading + entries).
 fn render_bibliography_md(style_override: Option<Format>) -> String {
     citation::with_active(|ctx| {
@@ -33,19 +51,12 @@ fn render_bibliography_md(style_override: Option<Format>) -> String {
         if ctx.references.is_empty() {
             return String::new();
         }
-        let style = style_override.unwrap_or(ctx.style);
-        let refs = if style_override.is_some() {
-            ctx.references.clone()
-        } else {
-            citation::ordered_references(ctx)
-        };
-        let mut lines = vec![format!("## {}", citation::bibliography_heading(style))];
-        if citation::is_numbered(style) {
-            for line in citation::reference_list(&refs, style) {
+        let list = ctx.bibliography(style_override);
+        let mut lines = vec![format!("## {}", list.heading)];
+        for (_, line) in list.entries {
+            if list.numbered {
                 lines.push(line);
-            }
-        } else {
-            for line in citation::reference_list(&refs, style) {
+            } else {
                 lines.push(format!("- {line}"));
             }
         }
@@ -55,10 +66,14 @@ fn render_bibliography_md(style_override: Option<Format>) -> String {
 
 pub(crate) fn render_block(block: &Block) -> String {
     match block {
-        Block::Markdown { content, .. } => citation::substitute_text_cites(content),
+        Block::Markdown { content, .. } => {
+            let text = footnote::substitute_text_notes(content, |n| format!("[^{}]", n.number));
+            xref::substitute_xrefs(&text, Target::text)
+        }
 
-        // `::cite` is a definition only — emits nothing in markdown output.
-        Block::Cite { .. } => String::new(),
+        // `::cite` and `::bibliography-data` are definitions only — they emit
+        // nothing in markdown output.
+        Block::Cite { .. } | Block::BibliographyData { .. } => String::new(),
 
         Block::Bibliography { style, .. } => render_bibliography_md(*style),
 
@@ -1336,6 +1351,7 @@ mod tests {
             end_line: 1,
             start_offset: 0,
             end_offset: 0,
+            file: FileId::ROOT,
         }
     }
 
@@ -1363,8 +1379,10 @@ mod tests {
     #[test]
     fn md_diagram_degrades_to_fenced_block() {
         let doc = doc_with(vec![Block::Diagram {
+            id: None,
             diagram_type: "architecture".into(),
             title: Some("System Map".into()),
+            direction: None,
             content: "web -> api".into(),
             span: span(),
         }]);
@@ -1374,8 +1392,10 @@ mod tests {
 
         // No title + empty type -> bare `diagram` info string, no bold line.
         let doc = doc_with(vec![Block::Diagram {
+            id: None,
             diagram_type: String::new(),
             title: None,
+            direction: None,
             content: "a -> b".into(),
             span: span(),
         }]);
@@ -1403,6 +1423,7 @@ mod tests {
     #[test]
     fn md_code_block() {
         let doc = doc_with(vec![Block::Code {
+            id: None,
             lang: Some("rust".into()),
             file: None,
             highlight: vec![],
@@ -1423,11 +1444,13 @@ mod tests {
                     done: false,
                     text: "Write tests".into(),
                     assignee: None,
+                    span: None,
                 },
                 TaskItem {
                     done: true,
                     text: "Write parser".into(),
                     assignee: Some("brady".into()),
+                    span: None,
                 },
             ],
             span: span(),
@@ -1478,6 +1501,7 @@ mod tests {
     #[test]
     fn md_figure() {
         let doc = doc_with(vec![Block::Figure {
+            id: None,
             src: "diagram.png".into(),
             caption: Some("Architecture".into()),
             alt: Some("Diagram".into()),
@@ -1549,10 +1573,12 @@ mod tests {
                 crate::types::FaqItem {
                     question: "Is it free?".into(),
                     answer: "Yes.".into(),
+                    span: None,
                 },
                 crate::types::FaqItem {
                     question: "Can I export?".into(),
                     answer: "PDF and HTML.".into(),
+                    span: None,
                 },
             ],
             span: span(),
@@ -1601,6 +1627,7 @@ mod tests {
             sidebar: false,
             content: "Welcome to our site.".into(),
             children: vec![],
+            attr_spans: Default::default(),
             span: span(),
         }]);
         let md = to_markdown(&doc);
@@ -1617,6 +1644,7 @@ mod tests {
             sidebar: false,
             content: "# About Us\n\nWe build things.".into(),
             children: vec![],
+            attr_spans: Default::default(),
             span: span(),
         }]);
         let md = to_markdown(&doc);
@@ -1634,6 +1662,7 @@ mod tests {
                 span: span(),
             },
             Block::Code {
+                id: None,
                 lang: Some("rust".into()),
                 file: None,
                 highlight: vec![],
@@ -574,6 +574,14 @@ pub enum NativeBlock {
         entries: Vec<NativeReferenceEntry>,
     },
 
+    /// The document's footnotes, appended after the last block when any are
+    /// referenced. Prose carries `[^n]` markers matching `number`; note text
+    /// is markdown, with note-style citations already formatted in Rust.
+    Footnotes {
+        heading: String,
+        notes: Vec<NativeFootnote>,
+    },
+
     /// Access-code card (::gate) — password field + submit button. The
     /// native app controls submission (like `Form`); `action` names the
     /// POST target for the client to bind.
@@ -867,6 +875,14 @@ pub struct NativeReferenceEntry {
     pub formatted: String,
 }
 
+/// A single numbered note within a native `Footnotes` block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct NativeFootnote {
+    pub number: u32,
+    pub text: String,
+}
+
 /// A labelled group of product cards within a native `ProductGrid`.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
@@ -1179,11 +1195,40 @@ pub struct NativeDoc {
 
 /// Convert a parsed SurfDoc into a Vec<NativeBlock> for native rendering.
 pub fn to_native_blocks(doc: &SurfDoc) -> Vec<NativeBlock> {
-    let _cite_scope = crate::citation::install_context(crate::citation::build_context(
-        &doc.blocks,
-        doc.front_matter.as_ref().and_then(|fm| fm.format),
-    ));
-    doc.blocks.iter().flat_map(|b| convert_block_flat(b, 0)).collect()
+    let _cite_scope = crate::citation::install_context(crate::citation::document_context(doc));
+    let mut blocks: Vec<NativeBlock> = doc
+        .blocks
+        .iter()
+        .flat_map(|b| convert_block_flat(b, 0))
+        .collect();
+    let notes = crate::footnote::document_notes(crate::citation::substitute_text_cites);
+    if !notes.is_empty() {
+        blocks.push(NativeBlock::Footnotes {
+            heading: "Notes".to_string(),
+            notes: notes
+                .into_iter()
+                .map(|(number, text)| NativeFootnote {
+                    number: number as u32,
+                    text,
+                })
+                .collect(),
+        });
+    }
+    blocks
+}
+
+/// Markdown prose with footnote definitions lifted out, references
+/// renumbered to `[^n]` and `[#label]` cross-references resolved to their
+/// text ("Figure 3"). Under a note-class CSL style, inline citations become
+/// footnotes too; other citations are left for the client.
+fn native_prose(content: &str) -> String {
+    let render = |n: &crate::footnote::Note| format!("[^{}]", n.number);
+    let text = if crate::citation::with_active(|ctx| ctx.is_some_and(|c| c.note_citations())) {
+        crate::footnote::substitute_text_notes(content, render)
+    } else {
+        crate::footnote::substitute_refs(&crate::footnote::strip_definitions(content), render)
+    };
+    crate::xref::substitute_xrefs(&text, crate::xref::Target::text)
 }
 
 /// Convert a list of child blocks, expanding GFM pipe tables that live inside
@@ -1202,7 +1247,7 @@ fn convert_children(children: &[Block], depth: u32) -> Vec<NativeBlock> {
 /// 1:1 via [`convert_block`].
 fn convert_block_flat(block: &Block, depth: u32) -> Vec<NativeBlock> {
     match block {
-        Block::Markdown { content, .. } => expand_markdown_tables(content),
+        Block::Markdown { content, .. } => expand_markdown_tables(&native_prose(content)),
         other => vec![convert_block(other, depth)],
     }
 }
@@ -1482,13 +1527,19 @@ fn convert_block(block: &Block, depth: u32) -> NativeBlock {
         Block::Diagram {
             diagram_type,
             title,
+            direction,
             content,
             ..
         } => NativeBlock::Diagram {
             diagram_type: diagram_type.clone(),
             title: title.clone(),
             content: content.clone(),
-            scene: crate::diagram::native_scene(diagram_type, content, title.as_deref()),
+            scene: crate::diagram::native_scene(
+                diagram_type,
+                content,
+                title.as_deref(),
+                *direction,
+            ),
         },
 
         Block::ProductCard {
@@ -2381,12 +2432,17 @@ fn convert_block(block: &Block, depth: u32) -> NativeBlock {
         // Formatted with the document's active citation style — the context
         // is installed by `to_native_blocks` before conversion runs.
         Block::Cite { reference, .. } => {
-            let style = crate::citation::with_active(|ctx| {
-                crate::citation::active_style(ctx.map(|c| c.style))
+            let formatted = crate::citation::with_active(|ctx| match ctx {
+                Some(ctx) => ctx.format_entry(reference),
+                None => crate::citation::format_reference(
+                    reference,
+                    crate::citation::active_style(None),
+                    None,
+                ),
             });
             NativeBlock::Cite {
                 key: reference.key.clone(),
-                formatted: crate::citation::format_reference(reference, style, None),
+                formatted,
             }
         }
 
@@ -2398,22 +2454,16 @@ fn convert_block(block: &Block, depth: u32) -> NativeBlock {
                 if ctx.references.is_empty() {
                     return (String::new(), Vec::new());
                 }
-                let active = style.unwrap_or(ctx.style);
-                // Mirrors render_bibliography_html: citation-number order for
-                // the document style, definition order under an override.
-                let refs = if style.is_some() {
-                    ctx.references.clone()
-                } else {
-                    crate::citation::ordered_references(ctx)
-                };
-                let entries = crate::citation::reference_list_keyed(&refs, active)
+                // Mirrors render_bibliography_html: the CSL style or
+                // citation-number order for the document style, definition
+                // order under an override.
+                let list = ctx.bibliography(*style);
+                let entries = list
+                    .entries
                     .into_iter()
                     .map(|(key, formatted)| NativeReferenceEntry { key, formatted })
                     .collect();
-                (
-                    crate::citation::bibliography_heading(active).to_string(),
-                    entries,
-                )
+                (list.heading, entries)
             });
             NativeBlock::Bibliography { heading, entries }
         }
@@ -2486,6 +2536,7 @@ fn convert_block(block: &Block, depth: u32) -> NativeBlock {
 
         Block::Unknown { .. }
         | Block::Style { .. }
+        | Block::BibliographyData { .. }
         | Block::Logo { .. }
         | Block::Action { .. }
         | Block::Dashboard { .. }
@@ -2727,6 +2778,7 @@ pub fn block_tier(block: &Block) -> BlockTier {
         | Block::Metric { .. }
         | Block::Summary { .. }
         | Block::Cite { .. }
+        | Block::BibliographyData { .. }
         | Block::Bibliography { .. }
         // Reader-content blocks promoted from Degraded → native structural
         // rendering (A-04 / BR-APP-7).
@@ -3339,6 +3391,7 @@ mod tests {
     #[test]
     fn native_code_with_lang() {
         let block = Block::Code {
+            id: None,
             lang: Some("rust".to_string()),
             file: Some("main.rs".to_string()),
             highlight: vec![],
@@ -3358,6 +3411,7 @@ mod tests {
     #[test]
     fn native_code_no_lang() {
         let block = Block::Code {
+            id: None,
             lang: None,
             file: None,
             highlight: vec![],
@@ -3424,11 +3478,13 @@ mod tests {
                     done: false,
                     text: "Write tests".to_string(),
                     assignee: None,
+                    span: None,
                 },
                 TaskItem {
                     done: true,
                     text: "Ship".to_string(),
                     assignee: Some("brady".to_string()),
+                    span: None,
                 },
             ],
             span: syn(),
@@ -3529,6 +3585,7 @@ mod tests {
     #[test]
     fn native_figure() {
         let block = Block::Figure {
+            id: None,
             src: "diagram.png".to_string(),
             caption: Some("Arch".to_string()),
             alt: Some("Diagram".to_string()),
@@ -3655,6 +3712,7 @@ mod tests {
             items: vec![FaqItem {
                 question: "Free?".to_string(),
                 answer: "Yes.".to_string(),
+                span: None,
             }],
             span: syn(),
         };
@@ -3919,6 +3977,7 @@ mod tests {
             name: "custom".to_string(),
             attrs: BTreeMap::new(),
             content: "some content".to_string(),
+            attr_spans: Default::default(),
             span: syn(),
         };
         match convert_block(&block, 0) {
@@ -3940,6 +3999,7 @@ mod tests {
                 href: "/".to_string(),
                 icon: None,
                 image: None, external: false,
+                span: None,
             }],
             logo: Some("Acme".to_string()),
             groups: vec![], brand: None, brand_reg: false, cta: None, drawer: false, minimal: false,
@@ -4039,6 +4099,7 @@ mod tests {
                     span: syn(),
                 },
             ],
+            attr_spans: Default::default(),
             span: syn(),
         };
         match convert_block(&block, 0) {
@@ -4687,4 +4748,32 @@ mod tests {
             other => panic!("expected SectionContainer, got {other:?}"),
         }
     }
+
+    #[test]
+    fn footnotes_become_a_trailing_block() {
+        let src = "Text[^x] and[^y].\n\n[^y]: Why.\n[^x]: Ex *note*.\n";
+        let blocks = to_native_blocks(&crate::parse(src).doc);
+        assert_eq!(
+            blocks[0],
+            NativeBlock::Markdown {
+                content: "Text[^1] and[^2].\n".to_string()
+            }
+        );
+        assert_eq!(
+            blocks.last(),
+            Some(&NativeBlock::Footnotes {
+                heading: "Notes".to_string(),
+                notes: vec![
+                    NativeFootnote {
+                        number: 1,
+                        text: "Ex *note*.".to_string()
+                    },
+                    NativeFootnote {
+                        number: 2,
+                        text: "Why.".to_string()
+                    },
+                ],
+            })
+        );
+    }
 }
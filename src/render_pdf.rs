@@ -315,17 +315,11 @@ pub fn collect_image_srcs(doc: &SurfDoc) -> Vec<String> {
                         push(&item.src, out, seen);
                     }
                 }
-                Block::Page { children, .. }
-                | Block::Slide { children, .. }
-                | Block::Section { children, .. }
-                | Block::App { children, .. }
-                | Block::AppShell { children, .. }
-                | Block::Sidebar { children, .. }
-                | Block::Panel { children, .. }
-                | Block::TabContent { children, .. }
-                | Block::Drawer { children, .. }
-                | Block::Modal { children, .. } => walk(children, out, seen),
-                _ => {}
+                _ => {
+                    if let Some(children) = b.children() {
+                        walk(children, out, seen);
+                    }
+                }
             }
         }
     }
@@ -9,15 +9,23 @@ use crate::types::{Block, CalloutType, DecisionStatus, SurfDoc, Trend};
 
 /// Render a `SurfDoc` as ANSI-colored terminal text.
 pub fn to_terminal(doc: &SurfDoc) -> String {
-    let _cite_scope = crate::citation::install_context(crate::citation::build_context(
-        &doc.blocks,
-        doc.front_matter.as_ref().and_then(|fm| fm.format),
-    ));
+    let _cite_scope = crate::citation::install_context(crate::citation::document_context(doc));
     let mut parts: Vec<String> = Vec::new();
 
     for block in &doc.blocks {
         parts.push(render_block(block));
     }
+    let notes = crate::footnote::document_notes(crate::citation::substitute_text_cites);
+    if !notes.is_empty() {
+        let mut list = vec!["## Notes".to_string(), String::new()];
+        for (n, body) in notes {
+            list.push(format!(
+                "{n}. {}",
+                body.split_whitespace().collect::<Vec<_>>().join(" ")
+            ));
+        }
+        parts.push(render_markdown_content(&list.join("\n")));
+    }
 
     parts.join("\n\n")
 }
@@ -25,7 +33,12 @@ pub fn to_terminal(doc: &SurfDoc) -> String {
 fn render_block(block: &Block) -> String {
     match block {
         Block::Markdown { content, .. } => {
-            render_markdown_content(&crate::citation::substitute_text_cites(content))
+            let text =
+                crate::footnote::substitute_text_notes(content, |n| format!("[{}]", n.number));
+            render_markdown_content(&crate::xref::substitute_xrefs(
+                &text,
+                crate::xref::Target::text,
+            ))
         }
 
         Block::Callout {
@@ -739,6 +752,7 @@ fn render_block(block: &Block) -> String {
         | Block::ProblemList { .. }
         | Block::PostGrid { .. }
         | Block::Cite { .. }
+        | Block::BibliographyData { .. }
         | Block::Bibliography { .. }
         | Block::Gate { .. } => {
             crate::render_md::render_block(block)
@@ -990,6 +1004,7 @@ mod tests {
             end_line: 1,
             start_offset: 0,
             end_offset: 0,
+            file: FileId::ROOT,
         }
     }
 
@@ -1031,11 +1046,13 @@ mod tests {
                     done: true,
                     text: "Done".into(),
                     assignee: None,
+                    span: None,
                 },
                 TaskItem {
                     done: false,
                     text: "Pending".into(),
                     assignee: None,
+                    span: None,
                 },
             ],
             span: span(),
@@ -8,6 +8,7 @@
 //! injection.
 
 use crate::types::*;
+use crate::xref::XrefKind;
 
 /// Base Typst template with page setup, colors, and reusable components.
 pub(crate) const SURFDOC_TEMPLATE: &str = include_str!("../assets/surfdoc.typ");
@@ -64,8 +65,7 @@ fn resolved_image(src: &str) -> Option<String> {
 pub fn to_typst(doc: &SurfDoc) -> String {
     let format = doc.front_matter.as_ref().and_then(|fm| fm.format);
     let doc_type = doc.front_matter.as_ref().and_then(|fm| fm.doc_type);
-    let _cite_scope =
-        crate::citation::install_context(crate::citation::build_context(&doc.blocks, format));
+    let _cite_scope = crate::citation::install_context(crate::citation::document_context(doc));
 
     // Document-type render profile (Chunk 1) selects an academic template for
     // papers/reports; everything else uses the generic SurfDoc layout.
@@ -220,6 +220,7 @@ fn render_academic_body(blocks: &[Block], style: Format, out: &mut String) {
     for b in blocks {
         match b {
             Block::Cite { .. }
+            | Block::BibliographyData { .. }
             | Block::Site { .. }
             | Block::Style { .. }
             | Block::Page { .. } => {}
@@ -231,7 +232,7 @@ fn render_academic_body(blocks: &[Block], style: Format, out: &mut String) {
             // other src degrades to a self-contained, deterministic
             // placeholder (a numbered `#figure` / a captioned data table)
             // instead of an `image()` call that would fail Typst compilation.
-            Block::Figure { src, caption, alt, .. } => match resolved_image(src) {
+            Block::Figure { src, caption, alt, .. } => labelled(b, out, |out| match resolved_image(src) {
                 Some(path) => {
                     out.push_str(&format!("#figure(\n  image(\"{}\")", escape_typst(&path)));
                     if let Some(c) = caption.as_deref() {
@@ -241,10 +242,10 @@ fn render_academic_body(blocks: &[Block], style: Format, out: &mut String) {
                     let _ = alt;
                 }
                 None => render_academic_figure(caption.as_deref(), alt.as_deref(), out),
-            },
-            Block::Chart { title, data, .. } => {
-                render_academic_chart(title.as_deref(), data.as_ref(), out);
-            }
+            }),
+            Block::Chart { title, data, .. } => labelled(b, out, |out| {
+                render_chart_table(title.as_deref(), data.as_ref(), out);
+            }),
             _ => {
                 render_block(&substitute_block_cites(b), out);
                 out.push('\n');
@@ -268,7 +269,7 @@ fn render_academic_figure(caption: Option<&str>, alt: Option<&str>, out: &mut St
 }
 
 /// A chart rendered as a captioned data table (deterministic, no SVG embed).
-fn render_academic_chart(title: Option<&str>, data: Option<&ChartData>, out: &mut String) {
+fn render_chart_table(title: Option<&str>, data: Option<&ChartData>, out: &mut String) {
     let Some(d) = data else { return };
     if let Some(t) = title {
         out.push_str(&format!(
@@ -298,33 +299,23 @@ fn render_academic_chart(title: Option<&str>, data: Option<&ChartData>, out: &mu
 /// (so it is excluded from the body's section numbering).
 fn render_academic_bibliography(style: Format, out: &mut String) {
     crate::citation::with_active(|ctx| {
-        let refs = match ctx {
+        let list = match ctx {
             Some(c) if !c.references.is_empty() => {
-                if style == c.style {
-                    crate::citation::ordered_references(c)
-                } else {
-                    c.references.clone()
-                }
+                c.bibliography((style != c.style).then_some(style))
             }
             _ => return,
         };
-        let heading = crate::citation::bibliography_heading(style);
         out.push_str(&format!(
             "\n#heading(numbering: none)[{}]\n\n",
-            escape_typst(heading)
+            escape_typst(&list.heading)
         ));
-        if crate::citation::is_numbered(style) {
-            for (i, line) in crate::citation::reference_list(&refs, style)
-                .iter()
-                .enumerate()
-            {
+        for (i, (_, line)) in list.entries.iter().enumerate() {
+            if list.numbered {
                 // Strip the leading "[n] " — Typst numbers the list itself.
                 let body = line.splitn(2, "] ").nth(1).unwrap_or(line.as_str());
                 out.push_str(&format!("{}. {}\n\n", i + 1, md_to_typst_inline(body)));
-            }
-        } else {
-            for line in crate::citation::reference_list(&refs, style) {
-                out.push_str(&md_to_typst_inline(&line));
+            } else {
+                out.push_str(&md_to_typst_inline(line));
                 out.push_str("\n\n");
             }
         }
@@ -571,6 +562,37 @@ fn render_front_matter(fm: &FrontMatter, out: &mut String) {
 
 /// Render a single block to Typst markup, appending to `out`.
 fn render_block(block: &Block, out: &mut String) {
+    labelled(block, out, |out| render_block_body(block, out));
+}
+
+/// Render a block through `render`, attaching the `<label>` that `@label`
+/// cross-references point at when the block has an `id`. A `#figure(…)` takes
+/// the label directly; other output is wrapped in a figure of the target's
+/// kind, so Typst numbers it and `@label` prints "Table 2".
+fn labelled(block: &Block, out: &mut String, render: impl FnOnce(&mut String)) {
+    let Some(label) = crate::xref::block_label(block) else {
+        render(out);
+        return;
+    };
+    let mut body = String::new();
+    render(&mut body);
+    let body = body.trim_end();
+    if body.starts_with("#figure(") && body.ends_with(')') {
+        out.push_str(&format!("{body} <{label}>\n"));
+        return;
+    }
+    let (kind, supplement) = match crate::xref::block_kind(block) {
+        Some(XrefKind::Table) => ("table", "Table"),
+        Some(XrefKind::Equation) => ("\"equation\"", "Equation"),
+        Some(XrefKind::Listing) => ("raw", "Listing"),
+        _ => ("image", "Figure"),
+    };
+    out.push_str(&format!(
+        "#figure(kind: {kind}, supplement: [{supplement}])[\n{body}\n] <{label}>\n"
+    ));
+}
+
+fn render_block_body(block: &Block, out: &mut String) {
     match block {
         Block::Markdown { content, .. } => {
             out.push_str(&md_to_typst(content));
@@ -1272,9 +1294,14 @@ fn render_block(block: &Block, out: &mut String) {
         }
         Block::FilterBar { .. } | Block::Search { .. } | Block::Dashboard { .. }
         | Block::ChatInput { .. } | Block::Feed { .. } | Block::Editor { .. }
-        | Block::Chart { .. } | Block::SplitPane { .. } => {
+        | Block::SplitPane { .. } => {
             // Interactive widgets — no meaningful PDF representation
         }
+        Block::Chart { title, data, .. } => {
+            // Inline or `data=#id`-bound datasets print as a table; a chart
+            // fed only by a live `source` has nothing to show on paper.
+            render_chart_table(title.as_deref(), data.as_ref(), out);
+        }
 
         Block::Diagram { title, content, .. } => {
             // No vector rendering in PDF yet — emit the bold title plus the
@@ -1293,8 +1320,9 @@ fn render_block(block: &Block, out: &mut String) {
             ));
         }
 
-        // `::cite` is a definition only — renders nothing.
-        Block::Cite { .. } => {}
+        // `::cite` and `::bibliography-data` are definitions only — they
+        // render nothing.
+        Block::Cite { .. } | Block::BibliographyData { .. } => {}
 
         Block::Bibliography { style, .. } => {
             let style = *style;
@@ -1303,18 +1331,10 @@ fn render_block(block: &Block, out: &mut String) {
                 if ctx.references.is_empty() {
                     return;
                 }
-                let style = style.unwrap_or(ctx.style);
-                let refs = if style == ctx.style {
-                    crate::citation::ordered_references(ctx)
-                } else {
-                    ctx.references.clone()
-                };
-                out.push_str(&format!(
-                    "\n== {}\n\n",
-                    escape_typst(crate::citation::bibliography_heading(style))
-                ));
-                for line in crate::citation::reference_list(&refs, style) {
-                    out.push_str(&md_to_typst(&line));
+                let list = ctx.bibliography(style.filter(|&s| s != ctx.style));
+                out.push_str(&format!("\n== {}\n\n", escape_typst(&list.heading)));
+                for (_, line) in &list.entries {
+                    out.push_str(&md_to_typst(line));
                     out.push_str(" \\\n");
                 }
                 out.push('\n');
@@ -1414,6 +1434,7 @@ fn render_comparison_table(
 ///
 /// Handles headings, bold, italic, links, images, code, lists, and tables.
 pub fn md_to_typst(md: &str) -> String {
+    let md = &crate::footnote::strip_definitions(md);
     let mut out = String::with_capacity(md.len());
     let mut in_code_block = false;
     let mut code_lang = String::new();
@@ -1461,27 +1482,27 @@ pub fn md_to_typst(md: &str) -> String {
 
         // Headings
         if let Some(rest) = line.strip_prefix("# ") {
-            out.push_str(&format!("= {}\n", md_to_typst_inline(rest)));
+            out.push_str(&format!("= {}\n", typst_heading(rest)));
             continue;
         }
         if let Some(rest) = line.strip_prefix("## ") {
-            out.push_str(&format!("== {}\n", md_to_typst_inline(rest)));
+            out.push_str(&format!("== {}\n", typst_heading(rest)));
             continue;
         }
         if let Some(rest) = line.strip_prefix("### ") {
-            out.push_str(&format!("=== {}\n", md_to_typst_inline(rest)));
+            out.push_str(&format!("=== {}\n", typst_heading(rest)));
             continue;
         }
         if let Some(rest) = line.strip_prefix("#### ") {
-            out.push_str(&format!("==== {}\n", md_to_typst_inline(rest)));
+            out.push_str(&format!("==== {}\n", typst_heading(rest)));
             continue;
         }
         if let Some(rest) = line.strip_prefix("##### ") {
-            out.push_str(&format!("===== {}\n", md_to_typst_inline(rest)));
+            out.push_str(&format!("===== {}\n", typst_heading(rest)));
             continue;
         }
         if let Some(rest) = line.strip_prefix("###### ") {
-            out.push_str(&format!("====== {}\n", md_to_typst_inline(rest)));
+            out.push_str(&format!("====== {}\n", typst_heading(rest)));
             continue;
         }
 
@@ -1601,6 +1622,60 @@ fn flush_md_table(out: &mut String, buf: &mut Vec<Vec<String>>, has_header: &mut
     *has_header = false;
 }
 
+/// Heading text with an explicit `{#label}` anchor turned into a Typst
+/// `<label>` for cross-references.
+fn typst_heading(text: &str) -> String {
+    match crate::render_html::split_explicit_anchor(text) {
+        Some((title, label)) => format!("{} <{label}>", md_to_typst_inline(title)),
+        None => md_to_typst_inline(text),
+    }
+}
+
+/// Resolve a `[^label]` reference at `start` against the ambient document's
+/// notes, returning the `#footnote[…]` call and the index past the `]`.
+fn footnote_ref(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let close = chars[start + 2..]
+        .iter()
+        .position(|c| c.is_whitespace() || *c == '[' || *c == ']')?
+        + start
+        + 2;
+    if close == start + 2 || chars[close] != ']' || chars.get(close + 1) == Some(&':') {
+        return None;
+    }
+    let label: String = chars[start + 2..close].iter().collect();
+    crate::citation::with_active(|ctx| {
+        let notes = &ctx?.notes;
+        let note = notes.reference(&label)?;
+        let body = notes.within_note(|| md_to_typst_inline(&note.body.replace('\n', " ")));
+        Some((format!("#footnote[{body}]"), close + 1))
+    })
+}
+
+/// Resolve a `[#label]` cross-reference at `start` against the ambient
+/// document's targets, returning the Typst reference and the index past the
+/// `]`. Sections link with the document's number, since Typst only
+/// references numbered headings; `#ref(<label>)` stands in for `@label`
+/// where the shorthand would not parse (after a word character, or a label
+/// ending in `.` or `:`).
+fn xref_ref(chars: &[char], start: usize, prev: Option<char>) -> Option<(String, usize)> {
+    let rest: String = chars[start..].iter().take(256).collect();
+    let (0, end, label) = crate::inline::find_xrefs(&rest).into_iter().next()? else {
+        return None;
+    };
+    crate::citation::with_active(|ctx| {
+        let target = ctx?.xrefs.get(&label)?;
+        let reference = if target.kind == XrefKind::Section {
+            format!("#link(<{label}>)[{}]", target.text())
+        } else if label.ends_with(['.', ':']) || prev.is_some_and(char::is_alphanumeric) {
+            format!("#ref(<{label}>)")
+        } else {
+            format!("@{label}")
+        };
+        // Labels are ASCII, so byte and char offsets agree.
+        Some((reference, start + end))
+    })
+}
+
 /// Convert inline markdown to Typst inline markup.
 ///
 /// Handles: `**bold**` → `*bold*`, `*italic*` → `_italic_`,
@@ -1642,6 +1717,28 @@ pub fn md_to_typst_inline(text: &str) -> String {
             }
         }
 
+        // Footnote reference: [^label] → #footnote[…]; unresolved stays literal.
+        if chars[i] == '['
+            && i + 1 < len
+            && chars[i + 1] == '^'
+            && let Some((note, end)) = footnote_ref(&chars, i)
+        {
+            out.push_str(&note);
+            i = end;
+            continue;
+        }
+
+        // Cross-reference: [#label] → @label; unresolved stays literal.
+        if chars[i] == '['
+            && i + 1 < len
+            && chars[i + 1] == '#'
+            && let Some((reference, end)) = xref_ref(&chars, i, out.chars().last())
+        {
+            out.push_str(&reference);
+            i = end;
+            continue;
+        }
+
         // Link: [text](url)
         if chars[i] == '[' {
             if let Some((text_content, href, end)) = parse_link(&chars, i) {
@@ -2113,6 +2210,7 @@ mod tests {
         let doc = SurfDoc {
             front_matter: None,
             blocks: vec![Block::Code {
+                id: None,
                 lang: Some("rust".to_string()),
                 file: None,
                 highlight: vec![],
@@ -2132,8 +2230,8 @@ mod tests {
             front_matter: None,
             blocks: vec![Block::Tasks {
                 items: vec![
-                    TaskItem { done: true, text: "Done task".to_string(), assignee: None },
-                    TaskItem { done: false, text: "Open task".to_string(), assignee: Some("brady".to_string()) },
+                    TaskItem { done: true, text: "Done task".to_string(), assignee: None, span: None },
+                    TaskItem { done: false, text: "Open task".to_string(), assignee: Some("brady".to_string()), span: None },
                 ],
                 span: Span::SYNTHETIC,
             }],
@@ -2347,6 +2445,7 @@ mod tests {
                 name: "widget".to_string(),
                 attrs: Default::default(),
                 content: "some content".to_string(),
+                attr_spans: Default::default(),
                 span: Span::SYNTHETIC,
             }],
             source: String::new(),
@@ -2408,6 +2507,7 @@ mod tests {
                 items: vec![FaqItem {
                     question: "What is SurfDoc?".into(),
                     answer: "A typed document format.".into(),
+                    span: None,
                 }],
                 span: Span::SYNTHETIC,
             }],
@@ -2462,8 +2562,8 @@ mod tests {
             front_matter: None,
             blocks: vec![Block::Nav {
                 items: vec![
-                    NavItem { label: "Home".into(), href: "/".into(), icon: None, image: None, external: false },
-                    NavItem { label: "About".into(), href: "/about".into(), icon: None, image: None, external: false },
+                    NavItem { label: "Home".into(), href: "/".into(), icon: None, image: None, external: false, span: None },
+                    NavItem { label: "About".into(), href: "/about".into(), icon: None, image: None, external: false, span: None },
                 ],
                 logo: None,
                 groups: vec![], brand: None, brand_reg: false, cta: None, drawer: false, minimal: false,
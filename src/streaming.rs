@@ -0,0 +1,389 @@
+//! Push-based streaming parser (web-runtime spec §4.5).
+//!
+//! [`StreamingParser`] accepts source text in arbitrary chunks — e.g. LLM
+//! output as it arrives — and hands back top-level blocks only once they are
+//! *committed*: no continuation of the stream can change them any more. The
+//! uncommitted tail is described by an inert [`Placeholder`] that carries no
+//! content, so a half-open container can never capture what comes after it.
+//!
+//! Commit points follow the grammar exactly:
+//!
+//! - A container commits when its `::` closer line is complete and its
+//!   leaf/container look-ahead has seen that surplus closer.
+//! - A `::page` commits at its closer or at the next `::page`/`::footer`.
+//! - A markdown gap commits when the directive line that ends it is complete
+//!   (the whole gap is one `Block::Markdown`, as in [`parse`]).
+//! - A closer-less directive (`::metric`, `::cta`, …) is only known to be a
+//!   leaf at end of input — a later surplus closer would make it a container
+//!   — so it, and everything after it, waits for [`StreamingParser::finish`].
+//!
+//! Gate (never-weaken): the committed blocks are always a prefix of a full
+//! [`parse`] of the accumulated source, and after `finish` the streamed tree
+//! equals it, diagnostics included.
+//!
+//! [`parse`]: crate::parse::parse
+
+use crate::error::Diagnostic;
+use crate::parse::{self, LeafLookahead, ParseOptions, ParseResult};
+use crate::types::{Block, FrontMatter, SurfDoc};
+
+/// A top-level block that no later input can change.
+#[derive(Debug, Clone)]
+pub struct CommittedBlock {
+    /// Position in the document's top-level block list.
+    pub index: usize,
+    /// The typed block, identical to the one a full parse produces.
+    pub block: Block,
+}
+
+/// Inert stand-in for the uncommitted tail of the stream. It deliberately
+/// carries no content: render it as an empty pending marker and replace it
+/// once the blocks it stands for commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placeholder {
+    /// Directive name of the first uncommitted block (`"callout"` for an open
+    /// `::callout`); `None` for prose or front matter still being received.
+    pub name: Option<String>,
+    /// Byte offset in the accumulated (CRLF-normalised) source where the
+    /// uncommitted content starts.
+    pub start_offset: usize,
+}
+
+/// Incremental, push-based SurfDoc parser.
+///
+/// ```
+/// use surf_parse::streaming::StreamingParser;
+///
+/// let mut s = StreamingParser::new();
+/// assert!(s.feed("::callout[type=info]\nHel").is_empty());
+/// assert_eq!(s.pending().unwrap().name.as_deref(), Some("callout"));
+/// let committed = s.feed("lo\n::\n\n::summary\n");
+/// assert_eq!(committed.len(), 1); // the callout; the summary is still open
+/// let (rest, result) = s.finish();
+/// assert_eq!(rest.len(), 1);
+/// assert_eq!(result.doc.blocks.len(), 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StreamingParser {
+    /// CRLF-normalised complete lines (empty, or ending in `\n`).
+    source: String,
+    /// Raw bytes after the last newline received.
+    tail: String,
+    /// Front matter and first body line, once the front matter is settled.
+    body: Option<(Option<FrontMatter>, usize)>,
+    committed: Vec<Block>,
+    diagnostics: Vec<Diagnostic>,
+    /// Line the next scan resumes at: a top-level boundary after the last
+    /// committed block.
+    resume: usize,
+    /// Code-fence state the scanner carries into `resume`.
+    in_fence: bool,
+    pending: Option<Placeholder>,
+}
+
+impl StreamingParser {
+    /// An empty stream.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `chunk` and return the blocks it committed, in document order.
+    pub fn feed(&mut self, chunk: &str) -> Vec<CommittedBlock> {
+        self.tail.push_str(chunk);
+        if let Some(nl) = self.tail.rfind('\n') {
+            let complete: String = self.tail.drain(..=nl).collect();
+            self.source.push_str(&complete.replace("\r\n", "\n"));
+        }
+        self.advance(false)
+    }
+
+    /// End the stream: commit everything still pending and return those
+    /// blocks together with the complete parse result.
+    pub fn finish(mut self) -> (Vec<CommittedBlock>, ParseResult) {
+        let tail = std::mem::take(&mut self.tail);
+        self.source.push_str(&tail.replace("\r\n", "\n"));
+        let rest = self.advance(true);
+        let result = ParseResult {
+            doc: SurfDoc {
+                front_matter: self.body.and_then(|(fm, _)| fm),
+                blocks: self.committed,
+                source: self.source,
+            },
+            diagnostics: self.diagnostics,
+            files: Vec::new(),
+        };
+        (rest, result)
+    }
+
+    /// The uncommitted tail, if any content is waiting on a commit point.
+    pub fn pending(&self) -> Option<&Placeholder> {
+        self.pending.as_ref()
+    }
+
+    /// Blocks committed so far.
+    pub fn committed(&self) -> &[Block] {
+        &self.committed
+    }
+
+    /// Everything received so far, CRLF-normalised.
+    pub fn source(&self) -> String {
+        format!("{}{}", self.source, self.tail.replace("\r\n", "\n"))
+    }
+
+    fn advance(&mut self, at_eof: bool) -> Vec<CommittedBlock> {
+        let source = self.source.as_str();
+        let all_lines: Vec<&str> = source.split('\n').collect();
+        // Mid-stream the final element is the line still being received.
+        let lines = if at_eof {
+            &all_lines[..]
+        } else {
+            &all_lines[..all_lines.len() - 1]
+        };
+
+        if self.body.is_none() {
+            let settled = at_eof
+                || lines.first().is_some_and(|first| {
+                    first.trim() != "---" || lines.iter().skip(1).any(|l| l.trim() == "---")
+                });
+            if !settled {
+                self.pending =
+                    (!source.is_empty() || !self.tail.is_empty()).then_some(Placeholder {
+                        name: None,
+                        start_offset: 0,
+                    });
+                return Vec::new();
+            }
+            let (front_matter, body_start) =
+                parse::extract_front_matter(lines, source, &mut self.diagnostics);
+            self.body = Some((front_matter, body_start));
+            self.resume = body_start;
+        }
+
+        let mut scan_diagnostics = Vec::new();
+        let (blocks, _) = parse::scan_blocks_from(
+            lines,
+            self.resume,
+            self.in_fence,
+            source,
+            &ParseOptions::default(),
+            &mut scan_diagnostics,
+            &mut |_, _| false,
+        );
+
+        let mut final_count = if at_eof {
+            blocks.len()
+        } else {
+            blocks
+                .iter()
+                .enumerate()
+                .take_while(|(i, b)| is_committed(b, lines, source, *i + 1 < blocks.len()))
+                .count()
+        };
+        // A chart bound to a `::data` id the stream has not delivered yet
+        // waits (with everything after it) until the table arrives or the
+        // input ends, so it binds exactly as in a full parse.
+        let mut resolved = parse::resolve_blocks(blocks[..final_count].to_vec(), source);
+        if !at_eof {
+            final_count = crate::chart::bindable_prefix(&self.committed, &resolved);
+            resolved.truncate(final_count);
+        }
+
+        let next_resume = if at_eof {
+            lines.len()
+        } else {
+            match final_count.checked_sub(1).map(|i| blocks[i].span()) {
+                None => self.resume,
+                // A `::page` closed by the next `::page` ends at the START of
+                // that opener's line; the next scan resumes on the opener.
+                Some(span)
+                    if span.end_line > span.start_line
+                        && span.end_offset
+                            == parse::byte_offset_start_of_line(span.end_line - 1, source) =>
+                {
+                    span.end_line - 1
+                }
+                Some(span) => span.end_line,
+            }
+        };
+        let resume_offset = parse::byte_offset_start_of_line(next_resume, source);
+
+        self.pending = blocks
+            .get(final_count)
+            .map(|b| Placeholder {
+                name: match b {
+                    Block::Unknown { name, .. } => Some(name.clone()),
+                    _ => None,
+                },
+                start_offset: b.span().start_offset,
+            })
+            .or_else(|| {
+                (!at_eof && (resume_offset < source.len() || !self.tail.is_empty())).then_some(
+                    Placeholder {
+                        name: None,
+                        start_offset: resume_offset,
+                    },
+                )
+            });
+
+        self.diagnostics.extend(
+            scan_diagnostics
+                .into_iter()
+                .filter(|d| at_eof || d.span.is_some_and(|s| s.start_offset < resume_offset)),
+        );
+        for line in &lines[self.resume.min(next_resume)..next_resume] {
+            let trimmed = line.trim();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                self.in_fence = !self.in_fence;
+            }
+        }
+        self.resume = next_resume;
+
+        let first_index = self.committed.len();
+        crate::chart::bind_after(&self.committed, &mut resolved);
+        self.committed.extend(resolved.iter().cloned());
+        resolved
+            .into_iter()
+            .enumerate()
+            .map(|(i, block)| CommittedBlock {
+                index: first_index + i,
+                block,
+            })
+            .collect()
+    }
+}
+
+/// Whether a block scanned from the complete lines so far is final, i.e.
+/// identical under every continuation of the stream. `followed` is true when
+/// the scan emitted another block after it.
+fn is_committed(block: &Block, lines: &[&str], source: &str, followed: bool) -> bool {
+    let Block::Unknown { name, span, .. } = block else {
+        // A markdown gap is final once the directive line that ended it
+        // is complete; the last gap may still grow.
+        return followed;
+    };
+    // Force-closed at end of input: its closer may still arrive.
+    if span.end_offset == source.len() {
+        return false;
+    }
+    if name == "page" || name == "footer" {
+        return true;
+    }
+    // Single-line: a provisional leaf.
+    if span.start_line == span.end_line {
+        return false;
+    }
+    let opener = lines[span.start_line - 1].trim();
+    match parse::opening_directive(opener) {
+        Some((depth, _, _)) => matches!(
+            parse::leaf_lookahead(lines, span.start_line, depth),
+            LeafLookahead::Closed { .. }
+        ),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn container_commits_only_at_its_closer() {
+        let mut s = StreamingParser::new();
+        assert!(s.feed("::callout[type=warning]\n").is_empty());
+        assert!(s.feed("Careful\n").is_empty());
+        assert_eq!(
+            s.pending(),
+            Some(&Placeholder {
+                name: Some("callout".into()),
+                start_offset: 0
+            })
+        );
+        // The closer line is not complete until its newline arrives.
+        assert!(s.feed("::").is_empty());
+        let committed = s.feed("\n");
+        assert_eq!(committed.len(), 1);
+        assert!(matches!(committed[0].block, Block::Callout { .. }));
+        assert_eq!(s.pending(), None);
+    }
+
+    #[test]
+    fn markdown_commits_when_the_next_directive_arrives() {
+        let mut s = StreamingParser::new();
+        assert!(s.feed("# Title\n\nFirst paragraph.\n\n").is_empty());
+        assert_eq!(s.pending().unwrap().name, None);
+        let committed = s.feed("::summary\nS\n::\n");
+        assert_eq!(committed.len(), 2);
+        assert!(matches!(committed[0].block, Block::Markdown { .. }));
+        assert!(matches!(committed[1].block, Block::Summary { .. }));
+    }
+
+    #[test]
+    fn leaf_directive_holds_back_until_finish() {
+        // A later surplus `::` would turn the metric into a container that
+        // swallows the prose, so nothing after it may commit mid-stream.
+        let mut s = StreamingParser::new();
+        let src = "::metric[label=a value=1]\n::metric[label=b value=2]\n\nProse.\n";
+        assert!(s.feed(src).is_empty());
+        assert_eq!(s.pending().unwrap().name.as_deref(), Some("metric"));
+        let (rest, result) = s.finish();
+        assert_eq!(rest.len(), result.doc.blocks.len());
+        assert!(matches!(result.doc.blocks[0], Block::Metric { .. }));
+    }
+
+    #[test]
+    fn front_matter_waits_for_its_closing_fence() {
+        let mut s = StreamingParser::new();
+        assert!(s.feed("---\ntitle: T\n").is_empty());
+        assert_eq!(s.pending().unwrap().name, None);
+        assert!(s.feed("---\n::summary\nS\n::\n").len() == 1);
+        let (_, result) = s.finish();
+        assert_eq!(result.doc.front_matter.unwrap().title.as_deref(), Some("T"));
+    }
+
+    #[test]
+    fn chart_waits_for_data_that_comes_later() {
+        let mut s = StreamingParser::new();
+        let held = s.feed("::chart[type=bar data=#t]\n::\n\nText.\n\n");
+        assert!(held.is_empty());
+        let committed = s.feed("::data[id=t]\n| a | b |\n| x | 1 |\n::\n\n");
+        assert_eq!(committed.len(), 3);
+        assert!(matches!(
+            committed[0].block,
+            Block::Chart { data: Some(_), .. }
+        ));
+    }
+
+    #[test]
+    fn chart_with_unknown_data_id_waits_for_finish() {
+        let mut s = StreamingParser::new();
+        assert!(s.feed("::chart[type=bar data=#missing]\n::\n\n").is_empty());
+        let (rest, result) = s.finish();
+        assert_eq!(rest.len(), 1);
+        assert!(matches!(
+            result.doc.blocks[0],
+            Block::Chart { data: None, .. }
+        ));
+    }
+
+    #[test]
+    fn chart_binds_to_data_committed_in_an_earlier_chunk() {
+        let mut s = StreamingParser::new();
+        assert_eq!(s.feed("::data[id=t]\n| a | b |\n| x | 1 |\n::\n").len(), 1);
+        let committed = s.feed("::chart[type=bar data=#t]\n::\n");
+        assert_eq!(committed.len(), 1);
+        assert!(matches!(
+            committed[0].block,
+            Block::Chart { data: Some(_), .. }
+        ));
+    }
+
+    #[test]
+    fn crlf_split_across_chunks_is_normalised() {
+        let mut s = StreamingParser::new();
+        s.feed("::summary\r");
+        s.feed("\nS\r\n::\r\n");
+        assert_eq!(s.committed().len(), 1);
+        let (_, result) = s.finish();
+        assert_eq!(result.doc.source, "::summary\nS\n::\n");
+    }
+}
@@ -0,0 +1,268 @@
+//! Deterministic text measurement for diagram and chart layout.
+//!
+//! SVG text is shaped by the viewer, but boxes, legends and columns must be
+//! sized when the SVG is written. [`TextMeasurer`] sums per-glyph advance
+//! widths from the bundled Liberation Sans faces (see
+//! [`crate::font_metrics_vendored`]); code points those faces do not cover
+//! fall back by class — East Asian wide characters and emoji take a full em,
+//! combining marks and zero-width characters take nothing, anything else
+//! takes the width of a digit.
+//!
+//! DETERMINISM: measurement is integer arithmetic over static tables (font
+//! units × px size, rounded up to whole px), so layouts built on it stay
+//! byte-stable across runs and hosts.
+
+use crate::font_metrics_vendored::{AdvanceRange, BOLD, REGULAR, UNITS_PER_EM};
+
+/// Advance of a code point neither face covers and no class fallback claims:
+/// the Liberation Sans digit width (0.556 em).
+const DEFAULT_ADVANCE: i64 = 1139;
+
+/// Measures and wraps single-line text at one font size and weight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TextMeasurer {
+    size: i64,
+    bold: bool,
+}
+
+impl TextMeasurer {
+    /// A measurer for regular-weight text at `size` px.
+    pub(crate) const fn new(size: i64) -> Self {
+        Self { size, bold: false }
+    }
+
+    /// The same size in the bold face.
+    pub(crate) const fn bold(self) -> Self {
+        Self {
+            size: self.size,
+            bold: true,
+        }
+    }
+
+    /// Rendered width of `text` in whole px (rounded up).
+    pub(crate) fn width(&self, text: &str) -> i64 {
+        self.px(text.chars().map(|c| self.advance(c)).sum())
+    }
+
+    /// Font units to whole px at this size, rounded up.
+    fn px(&self, units: i64) -> i64 {
+        (units * self.size + UNITS_PER_EM - 1) / UNITS_PER_EM
+    }
+
+    /// Break `text` into lines no wider than `max_w` px.
+    ///
+    /// Text that already fits is returned as one unchanged line. Otherwise
+    /// lines break greedily at whitespace (collapsed to single spaces) and
+    /// between East Asian wide characters; a single word wider than `max_w`
+    /// is split between characters. Always returns at least one line.
+    pub(crate) fn wrap(&self, text: &str, max_w: i64) -> Vec<String> {
+        if self.width(text) <= max_w {
+            return vec![text.to_string()];
+        }
+        let mut lines: Vec<String> = Vec::new();
+        let mut line = String::new();
+        for (spaced, token) in tokens(text) {
+            let candidate = if line.is_empty() {
+                token.to_string()
+            } else if spaced {
+                format!("{line} {token}")
+            } else {
+                format!("{line}{token}")
+            };
+            if self.width(&candidate) <= max_w {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            // The token starts a fresh line; split it while it overflows.
+            let mut rest = token;
+            while self.width(rest) > max_w {
+                let cut = self.fit(rest, max_w);
+                lines.push(rest[..cut].to_string());
+                rest = &rest[cut..];
+            }
+            line = rest.to_string();
+        }
+        if !line.is_empty() || lines.is_empty() {
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// `text` cut down to fit `max_w` px, with a trailing `…` when anything
+    /// was dropped. Text that already fits is returned unchanged.
+    pub(crate) fn truncate(&self, text: &str, max_w: i64) -> String {
+        if self.width(text) <= max_w {
+            return text.to_string();
+        }
+        let budget = max_w - self.width("\u{2026}");
+        let cut = if budget > 0 {
+            self.fit(text, budget)
+        } else {
+            0
+        };
+        format!("{}\u{2026}", text[..cut].trim_end())
+    }
+
+    /// Byte length of the longest prefix of `s` that fits in `max_w` px —
+    /// at least one character, so splitting always makes progress.
+    fn fit(&self, s: &str, max_w: i64) -> usize {
+        let mut units = 0;
+        for (i, c) in s.char_indices() {
+            units += self.advance(c);
+            if i > 0 && self.px(units) > max_w {
+                return i;
+            }
+        }
+        s.len()
+    }
+
+    /// Advance of `c` in font units.
+    fn advance(&self, c: char) -> i64 {
+        let table = if self.bold { BOLD } else { REGULAR };
+        lookup(table, c).unwrap_or_else(|| fallback_advance(c))
+    }
+}
+
+/// Table advance of `c`, `None` when the face has no glyph for it.
+fn lookup(table: &[AdvanceRange], c: char) -> Option<i64> {
+    table.iter().find_map(|range| {
+        let i = (c as u32).checked_sub(range.first)?;
+        match range.advances.get(i as usize) {
+            Some(&a) if a > 0 => Some(i64::from(a)),
+            _ => None,
+        }
+    })
+}
+
+/// Advance by character class for code points outside the tables.
+fn fallback_advance(c: char) -> i64 {
+    if c.is_control() || is_zero_width(c) {
+        0
+    } else if is_wide(c) {
+        UNITS_PER_EM
+    } else {
+        DEFAULT_ADVANCE
+    }
+}
+
+/// Combining marks, zero-width spaces/joiners and variation selectors.
+fn is_zero_width(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036F}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{FE20}'..='\u{FE2F}'
+            | '\u{E0100}'..='\u{E01EF}'
+    )
+}
+
+/// East Asian wide/fullwidth characters and emoji — one em each.
+fn is_wide(c: char) -> bool {
+    matches!(
+        c,
+        '\u{1100}'..='\u{115F}'
+            | '\u{2600}'..='\u{27BF}'
+            | '\u{2E80}'..='\u{303E}'
+            | '\u{3041}'..='\u{33FF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{A000}'..='\u{A4CF}'
+            | '\u{AC00}'..='\u{D7A3}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{FE30}'..='\u{FE4F}'
+            | '\u{FF00}'..='\u{FF60}'
+            | '\u{FFE0}'..='\u{FFE6}'
+            | '\u{1F000}'..='\u{1FAFF}'
+            | '\u{20000}'..='\u{3FFFD}'
+    )
+}
+
+/// Split `text` into wrap tokens: whitespace-separated words, with every
+/// wide character its own token. Each token carries whether whitespace
+/// preceded it (so the line joins it back with a space).
+fn tokens(text: &str) -> Vec<(bool, &str)> {
+    let mut out = Vec::new();
+    for word in text.split_whitespace() {
+        let mut spaced = true;
+        let mut start = 0;
+        for (i, c) in word.char_indices() {
+            if is_wide(c) {
+                if start < i {
+                    out.push((spaced, &word[start..i]));
+                    spaced = false;
+                }
+                let end = i + c.len_utf8();
+                out.push((spaced, &word[i..end]));
+                spaced = false;
+                start = end;
+            }
+        }
+        if start < word.len() {
+            out.push((spaced, &word[start..]));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UI: TextMeasurer = TextMeasurer::new(13);
+
+    #[test]
+    fn latin_widths_follow_the_font_tables() {
+        // "i" (455 units) is far narrower than "W" (1933 units).
+        assert_eq!(UI.width("i"), 3);
+        assert_eq!(UI.width("W"), 13);
+        assert!(UI.width("iiii") < UI.width("WWWW"));
+        assert!(UI.bold().width("Order") > UI.width("Order"));
+        assert_eq!(UI.width(""), 0);
+    }
+
+    #[test]
+    fn wide_glyphs_take_a_full_em_and_marks_take_none() {
+        assert_eq!(UI.width("東京"), 26);
+        assert_eq!(UI.width("🚀"), 13);
+        assert_eq!(UI.width("e\u{301}"), UI.width("e"));
+        assert_eq!(UI.width("a\u{200D}b"), UI.width("ab"));
+    }
+
+    #[test]
+    fn wrap_keeps_fitting_text_verbatim() {
+        assert_eq!(UI.wrap("Web  Server", 200), vec!["Web  Server"]);
+        assert_eq!(UI.wrap("", 10), vec![""]);
+    }
+
+    #[test]
+    fn wrap_breaks_at_spaces_and_between_wide_glyphs() {
+        let lines = UI.wrap("Authentication and authorization service", 120);
+        assert_eq!(lines, vec!["Authentication and", "authorization service"]);
+        assert!(lines.iter().all(|l| UI.width(l) <= 120));
+        assert_eq!(UI.wrap("東京都庁舎", 30), vec!["東京", "都庁", "舎"]);
+        assert_eq!(UI.wrap("Go 東京", 30), vec!["Go", "東京"]);
+    }
+
+    #[test]
+    fn wrap_splits_overlong_words_between_characters() {
+        let lines = UI.wrap("Supercalifragilistic", 40);
+        assert!(lines.len() > 1);
+        assert_eq!(lines.concat(), "Supercalifragilistic");
+        assert!(lines.iter().all(|l| UI.width(l) <= 40));
+        // A single glyph wider than the limit still makes progress.
+        assert_eq!(UI.wrap("WW", 5), vec!["W", "W"]);
+    }
+
+    #[test]
+    fn truncate_ends_with_an_ellipsis_only_when_cut() {
+        assert_eq!(UI.truncate("Revenue", 100), "Revenue");
+        let cut = UI.truncate("Quarterly revenue by region", 80);
+        assert!(cut.ends_with('\u{2026}') && UI.width(&cut) <= 80, "{cut}");
+    }
+}
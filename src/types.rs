@@ -2,7 +2,7 @@ use std::collections::{BTreeMap, HashMap};
 
 use serde::{Deserialize, Serialize};
 
-use crate::citation::Reference;
+use crate::citation::{BibFormat, Reference};
 
 /// A parsed SurfDoc document.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +32,11 @@ pub struct FrontMatter {
     #[serde(rename = "format", skip_serializing_if = "Option::is_none")]
     pub format: Option<Format>,
 
+    /// CSL 1.0 style XML (usually a YAML `|` block) for citations and the
+    /// bibliography; overrides `format`'s built-in citation style.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub csl: Option<String>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub status: Option<DocStatus>,
 
@@ -251,6 +256,10 @@ pub enum Block {
         name: String,
         attrs: Attrs,
         content: String,
+        /// Source spans of the attributes this block was parsed from, keyed
+        /// like its attributes. Empty for built blocks.
+        #[serde(default, skip_serializing_if = "AttrSpans::is_empty")]
+        attr_spans: AttrSpans,
         span: Span,
     },
     /// Plain markdown content between directives.
@@ -277,6 +286,9 @@ pub enum Block {
     },
     /// Code block with optional language and file path.
     Code {
+        /// Cross-reference label from the `id` attr (`[#id]` in prose).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
         lang: Option<String>,
         file: Option<String>,
         highlight: Vec<String>,
@@ -317,6 +329,18 @@ pub enum Block {
         reference: Reference,
         span: Span,
     },
+    /// Imported reference data (`::bibliography-data[format=bibtex]`, or a
+    /// `::cite` whose body is BibTeX, CSL-JSON or RIS). Renders nothing; every
+    /// parsed [`Reference`] joins the document's references like a `::cite`.
+    BibliographyData {
+        /// Interchange format of the body.
+        format: BibFormat,
+        /// References parsed from the body, in source order.
+        references: Vec<Reference>,
+        /// The body as authored, kept for lossless re-serialization.
+        raw_content: String,
+        span: Span,
+    },
     /// A rendered reference list (`::bibliography` / `::references`). Collects
     /// every `::cite`-defined reference and formats them per the active citation
     /// style (or the optional per-block `style=` override).
@@ -327,6 +351,9 @@ pub enum Block {
     },
     /// Figure with image source and caption.
     Figure {
+        /// Cross-reference label from the `id` attr (`[#id]` in prose).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
         src: String,
         caption: Option<String>,
         alt: Option<String>,
@@ -337,9 +364,16 @@ pub enum Block {
     /// `content` is the raw DSL source, preserved verbatim for lossless
     /// round-trip; unknown `diagram_type` values degrade to prose at render.
     Diagram {
+        /// Cross-reference label from the `id` attr (`[#id]` in prose).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
         /// Raw `type` attr value, lowercased; `""` if absent.
         diagram_type: String,
         title: Option<String>,
+        /// Layout axis from the `direction` attr (`TB` / `LR`); `None` keeps
+        /// the diagram type's default.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        direction: Option<DiagramDirection>,
         content: String,
         span: Span,
     },
@@ -444,6 +478,10 @@ pub enum Block {
         content: String,
         /// Parsed child blocks (leaf directives resolved, rest as Markdown).
         children: Vec<Block>,
+        /// Source spans of the attributes this block was parsed from, keyed
+        /// like its attributes. Empty for built blocks.
+        #[serde(default, skip_serializing_if = "AttrSpans::is_empty")]
+        attr_spans: AttrSpans,
         span: Span,
     },
     /// Deck-level configuration (one per document) — the `::deck` block.
@@ -762,6 +800,9 @@ pub enum Block {
     },
     /// Data visualization mount point.
     Chart {
+        /// Cross-reference label from the `id` attr (`[#id]` in prose).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
         chart_type: ChartType,
         source: String,
         period: Option<String>,
@@ -771,6 +812,10 @@ pub enum Block {
         /// is rendered as real deterministic SVG; when `None` it falls back to
         /// the `source=` live-data mount point / static placeholder.
         data: Option<ChartData>,
+        /// `data=#id` binding to a `::data` block. Resolved into `data` at
+        /// parse time; kept so the block round-trips as a reference.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        binding: Option<ChartBinding>,
         span: Span,
     },
     /// Resizable side-by-side layout mount point. Authored `::pane[side=left]`
@@ -835,12 +880,20 @@ pub enum Block {
         min_machines: Option<u32>,
         strategy: Option<String>,
         properties: Vec<StyleProperty>,
+        /// Source spans of the attributes this block was parsed from, keyed
+        /// like its attributes. Empty for built blocks.
+        #[serde(default, skip_serializing_if = "AttrSpans::is_empty")]
+        attr_spans: AttrSpans,
         span: Span,
     },
     /// Environment variable group (required/recommended/optional/defaults).
     InfraEnv {
         tier: Option<String>,
         entries: Vec<EnvEntry>,
+        /// Source spans of the attributes this block was parsed from, keyed
+        /// like its attributes. Empty for built blocks.
+        #[serde(default, skip_serializing_if = "AttrSpans::is_empty")]
+        attr_spans: AttrSpans,
         span: Span,
     },
     /// Health check configuration.
@@ -858,6 +911,10 @@ pub enum Block {
         hard_limit: Option<u32>,
         soft_limit: Option<u32>,
         force_https: bool,
+        /// Source spans of the attributes this block was parsed from, keyed
+        /// like its attributes. Empty for built blocks.
+        #[serde(default, skip_serializing_if = "AttrSpans::is_empty")]
+        attr_spans: AttrSpans,
         span: Span,
     },
     /// CI/CD pipeline configuration.
@@ -910,6 +967,10 @@ pub enum Block {
         body: Option<String>,
         handler: Option<String>,
         content: String,
+        /// Source spans of the attributes this block was parsed from, keyed
+        /// like its attributes. Empty for built blocks.
+        #[serde(default, skip_serializing_if = "AttrSpans::is_empty")]
+        attr_spans: AttrSpans,
         span: Span,
     },
     /// Authentication configuration.
@@ -1232,6 +1293,324 @@ pub enum Block {
     },
 }
 
+impl Block {
+    /// Source location of this block. Programmatically built blocks carry
+    /// [`Span::SYNTHETIC`].
+    pub fn span(&self) -> Span {
+        match self {
+            Block::Unknown { span, .. }
+            | Block::Markdown { span, .. }
+            | Block::Callout { span, .. }
+            | Block::Data { span, .. }
+            | Block::Code { span, .. }
+            | Block::Tasks { span, .. }
+            | Block::Decision { span, .. }
+            | Block::Metric { span, .. }
+            | Block::Summary { span, .. }
+            | Block::Cite { span, .. }
+            | Block::BibliographyData { span, .. }
+            | Block::Bibliography { span, .. }
+            | Block::Figure { span, .. }
+            | Block::Diagram { span, .. }
+            | Block::Tabs { span, .. }
+            | Block::Columns { span, .. }
+            | Block::Quote { span, .. }
+            | Block::Cta { span, .. }
+            | Block::Nav { span, .. }
+            | Block::HeroImage { span, .. }
+            | Block::Testimonial { span, .. }
+            | Block::Style { span, .. }
+            | Block::Faq { span, .. }
+            | Block::PricingTable { span, .. }
+            | Block::Site { span, .. }
+            | Block::Page { span, .. }
+            | Block::Deck { span, .. }
+            | Block::Slide { span, .. }
+            | Block::Embed { span, .. }
+            | Block::Form { span, .. }
+            | Block::Banner { span, .. }
+            | Block::ProductGrid { span, .. }
+            | Block::PostGrid { span, .. }
+            | Block::Gate { span, .. }
+            | Block::Gallery { span, .. }
+            | Block::Footer { span, .. }
+            | Block::Details { span, .. }
+            | Block::Divider { span, .. }
+            | Block::Hero { span, .. }
+            | Block::Features { span, .. }
+            | Block::Steps { span, .. }
+            | Block::Stats { span, .. }
+            | Block::Comparison { span, .. }
+            | Block::Logo { span, .. }
+            | Block::Toc { span, .. }
+            | Block::BeforeAfter { span, .. }
+            | Block::Pipeline { span, .. }
+            | Block::Section { span, .. }
+            | Block::ProductCard { span, .. }
+            | Block::List { span, .. }
+            | Block::Board { span, .. }
+            | Block::Action { span, .. }
+            | Block::FilterBar { span, .. }
+            | Block::Search { span, .. }
+            | Block::Dashboard { span, .. }
+            | Block::ChatInput { span, .. }
+            | Block::Feed { span, .. }
+            | Block::Store { span, .. }
+            | Block::Booking { span, .. }
+            | Block::Editor { span, .. }
+            | Block::Chart { span, .. }
+            | Block::SplitPane { span, .. }
+            | Block::App { span, .. }
+            | Block::Build { span, .. }
+            | Block::InfraDatabase { span, .. }
+            | Block::Deploy { span, .. }
+            | Block::InfraEnv { span, .. }
+            | Block::Health { span, .. }
+            | Block::Concurrency { span, .. }
+            | Block::Cicd { span, .. }
+            | Block::Smoke { span, .. }
+            | Block::Domains { span, .. }
+            | Block::Crates { span, .. }
+            | Block::DeployUrls { span, .. }
+            | Block::Volumes { span, .. }
+            | Block::Model { span, .. }
+            | Block::Route { span, .. }
+            | Block::Auth { span, .. }
+            | Block::Binding { span, .. }
+            | Block::Schema { span, .. }
+            | Block::Use { span, .. }
+            | Block::AppEnv { span, .. }
+            | Block::AppDeploy { span, .. }
+            | Block::Row { span, .. }
+            | Block::InfoCard { span, .. }
+            | Block::AppShell { span, .. }
+            | Block::Sidebar { span, .. }
+            | Block::Panel { span, .. }
+            | Block::TabBar { span, .. }
+            | Block::TabContent { span, .. }
+            | Block::Toolbar { span, .. }
+            | Block::Drawer { span, .. }
+            | Block::Modal { span, .. }
+            | Block::CommandPalette { span, .. }
+            | Block::SegmentedControl { span, .. }
+            | Block::DropdownSelect { span, .. }
+            | Block::CodeEditor { span, .. }
+            | Block::BlockEditor { span, .. }
+            | Block::Terminal { span, .. }
+            | Block::NavTree { span, .. }
+            | Block::Badge { span, .. }
+            | Block::SuggestionChips { span, .. }
+            | Block::ChatThread { span, .. }
+            | Block::ChipInput { span, .. }
+            | Block::ChatInputSimple { span, .. }
+            | Block::Progress { span, .. }
+            | Block::LogStream { span, .. }
+            | Block::ProblemList { span, .. }
+            | Block::RecipientPicker { span, .. }
+            | Block::Qr { span, .. } => *span,
+        }
+    }
+
+    /// Mutable access to this block's [`Span`], e.g. to shift a reused block
+    /// after an edit earlier in the source (see [`crate::incremental`]).
+    pub fn span_mut(&mut self) -> &mut Span {
+        match self {
+            Block::Unknown { span, .. }
+            | Block::Markdown { span, .. }
+            | Block::Callout { span, .. }
+            | Block::Data { span, .. }
+            | Block::Code { span, .. }
+            | Block::Tasks { span, .. }
+            | Block::Decision { span, .. }
+            | Block::Metric { span, .. }
+            | Block::Summary { span, .. }
+            | Block::Cite { span, .. }
+            | Block::BibliographyData { span, .. }
+            | Block::Bibliography { span, .. }
+            | Block::Figure { span, .. }
+            | Block::Diagram { span, .. }
+            | Block::Tabs { span, .. }
+            | Block::Columns { span, .. }
+            | Block::Quote { span, .. }
+            | Block::Cta { span, .. }
+            | Block::Nav { span, .. }
+            | Block::HeroImage { span, .. }
+            | Block::Testimonial { span, .. }
+            | Block::Style { span, .. }
+            | Block::Faq { span, .. }
+            | Block::PricingTable { span, .. }
+            | Block::Site { span, .. }
+            | Block::Page { span, .. }
+            | Block::Deck { span, .. }
+            | Block::Slide { span, .. }
+            | Block::Embed { span, .. }
+            | Block::Form { span, .. }
+            | Block::Banner { span, .. }
+            | Block::ProductGrid { span, .. }
+            | Block::PostGrid { span, .. }
+            | Block::Gate { span, .. }
+            | Block::Gallery { span, .. }
+            | Block::Footer { span, .. }
+            | Block::Details { span, .. }
+            | Block::Divider { span, .. }
+            | Block::Hero { span, .. }
+            | Block::Features { span, .. }
+            | Block::Steps { span, .. }
+            | Block::Stats { span, .. }
+            | Block::Comparison { span, .. }
+            | Block::Logo { span, .. }
+            | Block::Toc { span, .. }
+            | Block::BeforeAfter { span, .. }
+            | Block::Pipeline { span, .. }
+            | Block::Section { span, .. }
+            | Block::ProductCard { span, .. }
+            | Block::List { span, .. }
+            | Block::Board { span, .. }
+            | Block::Action { span, .. }
+            | Block::FilterBar { span, .. }
+            | Block::Search { span, .. }
+            | Block::Dashboard { span, .. }
+            | Block::ChatInput { span, .. }
+            | Block::Feed { span, .. }
+            | Block::Store { span, .. }
+            | Block::Booking { span, .. }
+            | Block::Editor { span, .. }
+            | Block::Chart { span, .. }
+            | Block::SplitPane { span, .. }
+            | Block::App { span, .. }
+            | Block::Build { span, .. }
+            | Block::InfraDatabase { span, .. }
+            | Block::Deploy { span, .. }
+            | Block::InfraEnv { span, .. }
+            | Block::Health { span, .. }
+            | Block::Concurrency { span, .. }
+            | Block::Cicd { span, .. }
+            | Block::Smoke { span, .. }
+            | Block::Domains { span, .. }
+            | Block::Crates { span, .. }
+            | Block::DeployUrls { span, .. }
+            | Block::Volumes { span, .. }
+            | Block::Model { span, .. }
+            | Block::Route { span, .. }
+            | Block::Auth { span, .. }
+            | Block::Binding { span, .. }
+            | Block::Schema { span, .. }
+            | Block::Use { span, .. }
+            | Block::AppEnv { span, .. }
+            | Block::AppDeploy { span, .. }
+            | Block::Row { span, .. }
+            | Block::InfoCard { span, .. }
+            | Block::AppShell { span, .. }
+            | Block::Sidebar { span, .. }
+            | Block::Panel { span, .. }
+            | Block::TabBar { span, .. }
+            | Block::TabContent { span, .. }
+            | Block::Toolbar { span, .. }
+            | Block::Drawer { span, .. }
+            | Block::Modal { span, .. }
+            | Block::CommandPalette { span, .. }
+            | Block::SegmentedControl { span, .. }
+            | Block::DropdownSelect { span, .. }
+            | Block::CodeEditor { span, .. }
+            | Block::BlockEditor { span, .. }
+            | Block::Terminal { span, .. }
+            | Block::NavTree { span, .. }
+            | Block::Badge { span, .. }
+            | Block::SuggestionChips { span, .. }
+            | Block::ChatThread { span, .. }
+            | Block::ChipInput { span, .. }
+            | Block::ChatInputSimple { span, .. }
+            | Block::Progress { span, .. }
+            | Block::LogStream { span, .. }
+            | Block::ProblemList { span, .. }
+            | Block::RecipientPicker { span, .. }
+            | Block::Qr { span, .. } => span,
+        }
+    }
+
+    /// Source spans of this block's attributes, for the variants that record
+    /// them (unresolved directives and the blocks whose attribute values are
+    /// validated: `::page`, `::route`, `::deploy`, `::env`, `::concurrency`).
+    pub fn attr_spans(&self) -> Option<&AttrSpans> {
+        match self {
+            Block::Unknown { attr_spans, .. }
+            | Block::Page { attr_spans, .. }
+            | Block::Deploy { attr_spans, .. }
+            | Block::InfraEnv { attr_spans, .. }
+            | Block::Concurrency { attr_spans, .. }
+            | Block::Route { attr_spans, .. } => Some(attr_spans),
+            _ => None,
+        }
+    }
+
+    /// Mutable access to the source spans inside this block — attribute
+    /// keys and values, list items (task lines, FAQ entries, nav links) — for
+    /// moving them along with [`Block::span_mut`]. Child blocks are not
+    /// included; walk [`Block::children_mut`] for those.
+    pub fn inner_spans_mut(&mut self) -> Vec<&mut Span> {
+        let items: Vec<&mut Option<Span>> = match self {
+            Block::Tasks { items, .. } => items.iter_mut().map(|i| &mut i.span).collect(),
+            Block::Faq { items, .. } => items.iter_mut().map(|i| &mut i.span).collect(),
+            Block::Nav { items, groups, .. } => items
+                .iter_mut()
+                .chain(groups.iter_mut().flat_map(|g| g.items.iter_mut()))
+                .map(|i| &mut i.span)
+                .collect(),
+            Block::Unknown { attr_spans, .. }
+            | Block::Page { attr_spans, .. }
+            | Block::Deploy { attr_spans, .. }
+            | Block::InfraEnv { attr_spans, .. }
+            | Block::Concurrency { attr_spans, .. }
+            | Block::Route { attr_spans, .. } => {
+                return attr_spans
+                    .values_mut()
+                    .flat_map(|a| std::iter::once(&mut a.key).chain(a.value.as_mut()))
+                    .collect();
+            }
+            _ => Vec::new(),
+        };
+        items.into_iter().flatten().collect()
+    }
+
+    /// Child blocks of a container (`::page`, `::section`, `::slide`, …),
+    /// `None` for every other block. Walkers that recurse into containers go
+    /// through this (and [`Block::children_mut`]), so a new container
+    /// variant only has to be added here.
+    pub fn children(&self) -> Option<&[Block]> {
+        match self {
+            Block::Page { children, .. }
+            | Block::Section { children, .. }
+            | Block::Slide { children, .. }
+            | Block::App { children, .. }
+            | Block::AppShell { children, .. }
+            | Block::Sidebar { children, .. }
+            | Block::Panel { children, .. }
+            | Block::TabContent { children, .. }
+            | Block::Drawer { children, .. }
+            | Block::Modal { children, .. } => Some(children),
+            _ => None,
+        }
+    }
+
+    /// Mutable access to the child blocks of a container; see
+    /// [`Block::children`].
+    pub fn children_mut(&mut self) -> Option<&mut Vec<Block>> {
+        match self {
+            Block::Page { children, .. }
+            | Block::Section { children, .. }
+            | Block::Slide { children, .. }
+            | Block::App { children, .. }
+            | Block::AppShell { children, .. }
+            | Block::Sidebar { children, .. }
+            | Block::Panel { children, .. }
+            | Block::TabContent { children, .. }
+            | Block::Drawer { children, .. }
+            | Block::Modal { children, .. } => Some(children),
+            _ => None,
+        }
+    }
+}
+
 /// State for Row and InfoCard blocks.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -1390,12 +1769,46 @@ pub enum DataFormat {
     Json,
 }
 
+/// Layout axis of a layered diagram (`::diagram[direction=…]`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiagramDirection {
+    /// `TB` (alias `TD`): layers stack top to bottom.
+    #[serde(rename = "TB")]
+    TopBottom,
+    /// `LR`: layers run left to right.
+    #[serde(rename = "LR")]
+    LeftRight,
+}
+
+impl DiagramDirection {
+    /// Parse a `direction` attribute value (case-insensitive).
+    pub fn from_name(s: &str) -> Option<DiagramDirection> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "TB" | "TD" => Some(DiagramDirection::TopBottom),
+            "LR" => Some(DiagramDirection::LeftRight),
+            _ => None,
+        }
+    }
+
+    /// The attribute spelling: `TB` or `LR`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiagramDirection::TopBottom => "TB",
+            DiagramDirection::LeftRight => "LR",
+        }
+    }
+}
+
 /// A single task item within a `Tasks` block.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskItem {
     pub done: bool,
     pub text: String,
     pub assignee: Option<String>,
+    /// Source location of this item's line (trimmed). `None` for items that
+    /// were built programmatically or parsed inside a nested block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
 }
 
 /// Decision record status.
@@ -1442,6 +1855,10 @@ pub struct StyleProperty {
 pub struct FaqItem {
     pub question: String,
     pub answer: String,
+    /// Source location from the `###` question line through the last answer
+    /// line. `None` when built programmatically or nested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
 }
 
 /// A navigation link within a `Nav` block.
@@ -1458,6 +1875,10 @@ pub struct NavItem {
     /// product links in the rich shell nav.
     #[serde(default)]
     pub external: bool,
+    /// Source location of this link's line (trimmed). `None` for links that
+    /// were built programmatically, nested, or synthesized from attributes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
 }
 
 /// A labelled group of navigation links within a `Nav` block (rich shell).
@@ -1883,6 +2304,39 @@ pub struct ChartData {
     pub series: Vec<ChartSeries>,
 }
 
+/// A `::chart[data=#sales x=month y=revenue,cost]` reference to a
+/// `::data[id=sales]` table in the same document. `x` names the category
+/// column (default: the first), `y` the series columns (default: every other
+/// column). Rows sharing an `x` value are merged by `aggregate` when set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChartBinding {
+    /// The referenced `::data` block's `id` (without the `#`).
+    pub data: String,
+    pub x: Option<String>,
+    pub y: Vec<String>,
+    pub aggregate: Option<ChartAggregate>,
+    pub sort: Option<ChartSort>,
+}
+
+/// How a [`ChartBinding`] merges rows that share a category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartAggregate {
+    Sum,
+    Avg,
+    /// Rows per category with a non-empty cell (or all rows when no `y`
+    /// column is named, as a single `count` series).
+    Count,
+}
+
+/// Category order of a bound chart: by the `x` column or one series
+/// (`sort=revenue`), descending with a leading `-` (`sort=-revenue`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChartSort {
+    pub column: String,
+    pub descending: bool,
+}
+
 // ----- App spec supporting types -----
 
 /// A field within a `Model` block.
@@ -1994,6 +2448,18 @@ pub enum InlineExt {
 /// Ordered map of attribute key-value pairs.
 pub type Attrs = BTreeMap<String, AttrValue>;
 
+/// Source location of one attribute entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttrSpan {
+    /// The key as written.
+    pub key: Span,
+    /// The value as written (quotes included); `None` for bare flags.
+    pub value: Option<Span>,
+}
+
+/// Attribute spans keyed like [`Attrs`].
+pub type AttrSpans = BTreeMap<String, AttrSpan>;
+
 /// A value inside a block directive attribute.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -2005,7 +2471,11 @@ pub enum AttrValue {
 }
 
 /// Source location of a block in the original document.
+///
+/// Non-exhaustive so fields can be added without breaking callers: build one
+/// with [`Span::new`] and destructure with `..`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Span {
     /// 1-based starting line number.
     pub start_line: usize,
@@ -2015,9 +2485,29 @@ pub struct Span {
     pub start_offset: usize,
     /// 0-based byte offset past the last character.
     pub end_offset: usize,
+    /// File the offsets point into: [`FileId::ROOT`] unless the block was
+    /// spliced in by an `::include`.
+    #[serde(default, skip_serializing_if = "FileId::is_root")]
+    pub file: FileId,
 }
 
 impl Span {
+    /// A span in the root file ([`FileId::ROOT`]).
+    pub const fn new(
+        start_line: usize,
+        end_line: usize,
+        start_offset: usize,
+        end_offset: usize,
+    ) -> Span {
+        Span {
+            start_line,
+            end_line,
+            start_offset,
+            end_offset,
+            file: FileId::ROOT,
+        }
+    }
+
     /// A zero-valued span for programmatically constructed blocks that have no
     /// source location.
     pub const SYNTHETIC: Span = Span {
@@ -2025,9 +2515,27 @@ impl Span {
         end_line: 0,
         start_offset: 0,
         end_offset: 0,
+        file: FileId::ROOT,
     };
 }
 
+/// The file a [`Span`] points into. Ids other than [`FileId::ROOT`] index
+/// the `files` table of the [`ParseResult`](crate::parse::ParseResult) that
+/// spliced them in (see [`crate::include`]).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FileId(pub u32);
+
+impl FileId {
+    /// The source that was parsed.
+    pub const ROOT: FileId = FileId(0);
+
+    /// True for [`FileId::ROOT`].
+    pub fn is_root(&self) -> bool {
+        *self == FileId::ROOT
+    }
+}
+
 #[cfg(test)]
 mod doc_type_format_tests {
     use super::*;
@@ -4,7 +4,7 @@
 //! Returns a list of `Diagnostic` items (non-fatal).
 
 use crate::error::{Diagnostic, Severity};
-use crate::types::{Block, SurfDoc};
+use crate::types::{Block, Span, SurfDoc};
 
 /// Validate a parsed `SurfDoc` and return any diagnostics.
 ///
@@ -41,7 +41,8 @@ pub fn validate(doc: &SurfDoc) -> Vec<Diagnostic> {
     diagnostics
 }
 
-/// Check for duplicate `::page[route=...]` values within a document.
+/// Check for duplicate `::page[route=...]` values within a document. The
+/// diagnostic underlines the repeated `route=` value when it can be located.
 fn validate_unique_page_routes(blocks: &[Block], diagnostics: &mut Vec<Diagnostic>) {
     let mut seen: Vec<(&str, &crate::types::Span)> = Vec::new();
     for block in blocks {
@@ -53,7 +54,7 @@ fn validate_unique_page_routes(blocks: &[Block], diagnostics: &mut Vec<Diagnosti
                         "Duplicate page route \"{}\": first defined at line {}",
                         route, first_span.start_line
                     ),
-                    span: Some(*span),
+                    span: Some(attr_value_span(block, "route")),
                     code: Some("V141".into()),
                     fix: None,
                 });
@@ -64,6 +65,16 @@ fn validate_unique_page_routes(blocks: &[Block], diagnostics: &mut Vec<Diagnosti
     }
 }
 
+/// Where `block`'s `key=` value was written, or the whole block when the
+/// attribute was not recorded (built blocks, missing attributes).
+fn attr_value_span(block: &Block, key: &str) -> Span {
+    block
+        .attr_spans()
+        .and_then(|spans| spans.get(key))
+        .and_then(|attr| attr.value)
+        .unwrap_or_else(|| block.span())
+}
+
 fn validate_front_matter(doc: &SurfDoc, diagnostics: &mut Vec<Diagnostic>) {
     match &doc.front_matter {
         None => {
@@ -292,6 +303,15 @@ fn validate_block(block: &Block, diagnostics: &mut Vec<Diagnostic>) {
                     fix: None,
                 });
             }
+            for item in items.iter().filter(|i| i.answer.trim().is_empty()) {
+                diagnostics.push(Diagnostic {
+                    severity: Severity::Warning,
+                    message: format!("Faq question \"{}\" has no answer", item.question),
+                    span: Some(item.span.unwrap_or(*span)),
+                    code: Some("V121".into()),
+                    fix: None,
+                });
+            }
         }
 
         Block::PricingTable {
@@ -332,7 +352,12 @@ fn validate_block(block: &Block, diagnostics: &mut Vec<Diagnostic>) {
             }
         }
 
-        Block::Nav { items, span, .. } => {
+        Block::Nav {
+            items,
+            groups,
+            span,
+            ..
+        } => {
             if items.is_empty() {
                 diagnostics.push(Diagnostic {
                     severity: Severity::Warning,
@@ -342,6 +367,28 @@ fn validate_block(block: &Block, diagnostics: &mut Vec<Diagnostic>) {
                     fix: None,
                 });
             }
+            let links = items.iter().chain(groups.iter().flat_map(|g| &g.items));
+            for item in links.filter(|i| i.href.trim().is_empty()) {
+                diagnostics.push(Diagnostic {
+                    severity: Severity::Warning,
+                    message: format!("Nav link \"{}\" has an empty href", item.label),
+                    span: Some(item.span.unwrap_or(*span)),
+                    code: Some("V151".into()),
+                    fix: None,
+                });
+            }
+        }
+
+        Block::Tasks { items, span } => {
+            for item in items.iter().filter(|i| i.text.trim().is_empty()) {
+                diagnostics.push(Diagnostic {
+                    severity: Severity::Warning,
+                    message: "Task item has no text".into(),
+                    span: Some(item.span.unwrap_or(*span)),
+                    code: Some("V160".into()),
+                    fix: None,
+                });
+            }
         }
 
         Block::App { name, span, .. } => {
@@ -370,7 +417,7 @@ fn validate_block(block: &Block, diagnostics: &mut Vec<Diagnostic>) {
                     diagnostics.push(Diagnostic {
                         severity: Severity::Warning,
                         message: format!("Deploy env \"{}\" is not one of: develop, staging, production", e),
-                        span: Some(*span),
+                        span: Some(attr_value_span(block, "env")),
                         code: Some("V202".into()),
                         fix: None,
                     });
@@ -392,7 +439,7 @@ fn validate_block(block: &Block, diagnostics: &mut Vec<Diagnostic>) {
                     diagnostics.push(Diagnostic {
                         severity: Severity::Warning,
                         message: format!("Env tier \"{}\" is not one of: required, recommended, optional, defaults", t),
-                        span: Some(*span),
+                        span: Some(attr_value_span(block, "tier")),
                         code: Some("V204".into()),
                         fix: None,
                     });
@@ -426,13 +473,13 @@ fn validate_block(block: &Block, diagnostics: &mut Vec<Diagnostic>) {
             }
         }
 
-        Block::Concurrency { hard_limit, soft_limit, span, .. } => {
+        Block::Concurrency { hard_limit, soft_limit, .. } => {
             if let (Some(hard), Some(soft)) = (hard_limit, soft_limit) {
                 if hard < soft {
                     diagnostics.push(Diagnostic {
                         severity: Severity::Warning,
                         message: format!("Concurrency hard_limit ({}) should be >= soft_limit ({})", hard, soft),
-                        span: Some(*span),
+                        span: Some(attr_value_span(block, "hard_limit")),
                         code: Some("V207".into()),
                         fix: None,
                     });
@@ -504,7 +551,7 @@ fn validate_block(block: &Block, diagnostics: &mut Vec<Diagnostic>) {
                 diagnostics.push(Diagnostic {
                     severity: Severity::Warning,
                     message: format!("Route path \"{}\" should start with /", path),
-                    span: Some(*span),
+                    span: Some(attr_value_span(block, "path")),
                     code: Some("V311".into()),
                     fix: None,
                 });
@@ -563,6 +610,7 @@ mod tests {
             end_line: 1,
             start_offset: 0,
             end_offset: 0,
+            file: FileId::ROOT,
         }
     }
 
@@ -641,6 +689,7 @@ mod tests {
                 ..FrontMatter::default()
             }),
             blocks: vec![Block::Figure {
+                id: None,
                 src: String::new(),
                 caption: Some("Photo".into()),
                 alt: None,
@@ -674,7 +723,8 @@ mod tests {
                     sidebar: false,
                     content: String::new(),
                     children: vec![],
-                    span: Span { start_line: 1, end_line: 3, start_offset: 0, end_offset: 30 },
+                    attr_spans: Default::default(),
+                    span: Span { start_line: 1, end_line: 3, start_offset: 0, end_offset: 30, file: FileId::ROOT },
                 },
                 Block::Page {
                     route: "/about".into(),
@@ -683,7 +733,8 @@ mod tests {
                     sidebar: false,
                     content: String::new(),
                     children: vec![],
-                    span: Span { start_line: 4, end_line: 6, start_offset: 31, end_offset: 60 },
+                    attr_spans: Default::default(),
+                    span: Span { start_line: 4, end_line: 6, start_offset: 31, end_offset: 60, file: FileId::ROOT },
                 },
                 Block::Page {
                     route: "/".into(),
@@ -692,7 +743,8 @@ mod tests {
                     sidebar: false,
                     content: String::new(),
                     children: vec![],
-                    span: Span { start_line: 7, end_line: 9, start_offset: 61, end_offset: 90 },
+                    attr_spans: Default::default(),
+                    span: Span { start_line: 7, end_line: 9, start_offset: 61, end_offset: 90, file: FileId::ROOT },
                 },
             ],
             source: String::new(),
@@ -707,6 +759,55 @@ mod tests {
         assert_eq!(dup_diags[0].severity, Severity::Error);
     }
 
+    #[test]
+    fn validate_duplicate_page_route_points_at_value() {
+        let source = "::page[route=/]\nHome\n::\n\n::page[title=Again route=\"/\"]\nAgain\n::\n";
+        let doc = crate::parse(source).doc;
+        let diags = validate(&doc);
+        let dup = diags
+            .iter()
+            .find(|d| d.code.as_deref() == Some("V141"))
+            .expect("duplicate route diagnostic");
+        let span = dup.span.unwrap();
+        assert_eq!(&source[span.start_offset..span.end_offset], "\"/\"");
+        assert_eq!(span.start_line, 5);
+    }
+
+    #[test]
+    fn validate_attribute_values_point_at_value() {
+        let source = "::route[method=GET path=users]\n::\n\n::deploy[env=prod]\n::\n";
+        let doc = crate::parse(source).doc;
+        let diags = validate(&doc);
+        let text = |code: &str| {
+            let d = diags
+                .iter()
+                .find(|d| d.code.as_deref() == Some(code))
+                .unwrap();
+            let span = d.span.unwrap();
+            &source[span.start_offset..span.end_offset]
+        };
+        assert_eq!(text("V311"), "users");
+        assert_eq!(text("V202"), "prod");
+    }
+
+    #[test]
+    fn validate_list_items_point_at_item() {
+        let source = "::faq\n### Open?\n\n### Free?\nYes.\n::\n\n::nav\n- [Home]()\n::\n\n::tasks\n- [ ] Ship\n- [ ]  @ann\n::\n";
+        let doc = crate::parse(source).doc;
+        let diags = validate(&doc);
+        let text = |code: &str| {
+            let d = diags
+                .iter()
+                .find(|d| d.code.as_deref() == Some(code))
+                .unwrap();
+            let span = d.span.unwrap();
+            &source[span.start_offset..span.end_offset]
+        };
+        assert_eq!(text("V121"), "### Open?");
+        assert_eq!(text("V151"), "- [Home]()");
+        assert_eq!(text("V160"), "- [ ]  @ann");
+    }
+
     #[test]
     fn validate_unique_page_routes_no_false_positive() {
         let doc = SurfDoc {
@@ -723,6 +824,7 @@ mod tests {
                     sidebar: false,
                     content: String::new(),
                     children: vec![],
+                    attr_spans: Default::default(),
                     span: span(),
                 },
                 Block::Page {
@@ -732,6 +834,7 @@ mod tests {
                     sidebar: false,
                     content: String::new(),
                     children: vec![],
+                    attr_spans: Default::default(),
                     span: span(),
                 },
                 Block::Page {
@@ -741,6 +844,7 @@ mod tests {
                     sidebar: false,
                     content: String::new(),
                     children: vec![],
+                    attr_spans: Default::default(),
                     span: span(),
                 },
             ],
@@ -763,6 +867,7 @@ mod tests {
                 ..FrontMatter::default()
             }),
             blocks: vec![Block::Code {
+                id: None,
                 lang: Some("rust".into()),
                 file: None,
                 highlight: vec![],
@@ -1,6 +1,8 @@
 //! WebAssembly bindings for client-side SurfDoc rendering.
 //!
-//! Exposes `render_surfdoc` and `parse_metadata` to JavaScript via wasm-bindgen.
+//! Exposes `render_surfdoc` (with its bounded twin `render_surfdoc_with` and
+//! include-resolving twin `render_surfdoc_with_includes`) and
+//! `parse_metadata` to JavaScript via wasm-bindgen.
 //! Build with: `wasm-pack build --target web --features wasm --no-default-features`
 
 use wasm_bindgen::prelude::*;
@@ -15,6 +17,59 @@ pub fn render_surfdoc(source: &str) -> String {
     result.doc.to_html_fragment()
 }
 
+/// [`render_surfdoc`] under parse resource bounds (web-runtime spec §4.4).
+///
+/// `options_json` is a `ParseOptions` object, e.g.
+/// `{"max_depth": 16, "max_source_bytes": 1048576}`; omitted bounds are
+/// unbounded. Throws when the options are malformed or a bound is hit (the
+/// P007 message), so the caller falls back exactly where `surf-lint check`
+/// with the same `[limits]` rejects at publish.
+#[wasm_bindgen]
+pub fn render_surfdoc_with(source: &str, options_json: &str) -> Result<String, JsError> {
+    let options: crate::parse::ParseOptions = serde_json::from_str(options_json)
+        .map_err(|e| JsError::new(&format!("invalid parse options: {e}")))?;
+    let result = crate::parse::parse_with(source, &options);
+    if let Some(d) = result
+        .diagnostics
+        .iter()
+        .find(|d| d.code.as_deref() == Some("P007"))
+    {
+        return Err(JsError::new(&d.message));
+    }
+    Ok(result.doc.to_html_fragment())
+}
+
+/// [`render_surfdoc_with`] with `::include` directives spliced from an
+/// in-memory file set, for callers without a filesystem.
+///
+/// `files_json` maps include names to sources, e.g.
+/// `{"intro.surf": "# Welcome"}`; an include's `src` must match a key
+/// exactly. `options_json` is a `ParseOptions` object as for
+/// [`render_surfdoc_with`] (`"{}"` for the defaults); its bounds apply to
+/// every included file and to the expansion as a whole. Unresolvable
+/// includes are dropped from the output. Throws when either JSON argument
+/// is malformed or a bound is hit (the P007 message).
+#[wasm_bindgen]
+pub fn render_surfdoc_with_includes(
+    source: &str,
+    files_json: &str,
+    options_json: &str,
+) -> Result<String, JsError> {
+    let files: std::collections::BTreeMap<String, String> = serde_json::from_str(files_json)
+        .map_err(|e| JsError::new(&format!("invalid include files: {e}")))?;
+    let options: crate::parse::ParseOptions = serde_json::from_str(options_json)
+        .map_err(|e| JsError::new(&format!("invalid parse options: {e}")))?;
+    let result = crate::include::parse_with_includes(source, "", &options, &files);
+    if let Some(d) = result
+        .diagnostics
+        .iter()
+        .find(|d| d.code.as_deref() == Some("P007"))
+    {
+        return Err(JsError::new(&d.message));
+    }
+    Ok(result.doc.to_html_fragment())
+}
+
 /// Render a SurfDoc source string to a full HTML document (with page chrome).
 ///
 /// Uses `to_html_page`, which embeds the full SURFDOC_CSS stylesheet so the
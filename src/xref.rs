@@ -0,0 +1,385 @@
+//! Cross-references: numbered figures, tables, equations, listings and
+//! sections, referenced from prose as `[#label]`.
+//!
+//! A block becomes a target through its `id` attribute: `::figure`,
+//! `::chart` and `::diagram` are figures, `::data` is a table, and
+//! `::code[lang=math]` is an equation (any other `::code` is a listing). A
+//! markdown heading becomes a section target through an explicit
+//! `{#label}` anchor. Each kind is numbered in document order, counting
+//! unlabelled blocks too; sections are numbered by heading level (`2`,
+//! `2.1`) from the document's top heading level.
+//!
+//! [`Xrefs`] for a document live in the ambient
+//! [`CiteContext`](crate::citation::CiteContext). HTML renders a reference
+//! as a "Figure 3" link to the target's anchor, LaTeX as `Figure~\ref{…}`,
+//! Typst as `@label`, and the text renderers as plain "Figure 3". A
+//! reference to an unknown label stays literal; lint L044 reports it, and
+//! L045 a label defined twice.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::citation::{cite_text_of, with_active};
+use crate::inline::find_xrefs;
+use crate::render_html::split_explicit_anchor;
+use crate::types::{Block, Span};
+
+/// What a cross-reference target is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XrefKind {
+    Figure,
+    Table,
+    Equation,
+    Listing,
+    Section,
+}
+
+impl XrefKind {
+    /// Display name used in resolved references.
+    pub fn name(self) -> &'static str {
+        match self {
+            XrefKind::Figure => "Figure",
+            XrefKind::Table => "Table",
+            XrefKind::Equation => "Equation",
+            XrefKind::Listing => "Listing",
+            XrefKind::Section => "Section",
+        }
+    }
+}
+
+/// A labelled, numbered target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    pub label: String,
+    pub kind: XrefKind,
+    /// `3` for blocks, `2.1` for sections.
+    pub number: String,
+    /// The block, or the heading line.
+    pub span: Span,
+}
+
+impl Target {
+    /// Reference text: `Figure 3`.
+    pub fn text(&self) -> String {
+        format!("{} {}", self.kind.name(), self.number)
+    }
+}
+
+/// A document's cross-reference targets.
+#[derive(Debug, Clone, Default)]
+pub struct Xrefs {
+    /// First definition of each label, in document order.
+    targets: Vec<Target>,
+    /// Later definitions of an already defined label.
+    duplicates: Vec<Target>,
+}
+
+impl Xrefs {
+    /// Number every figure, table, equation, listing and heading in the
+    /// document (recursing into containers) and keep the labelled ones.
+    pub fn collect(blocks: &[Block]) -> Xrefs {
+        Self::walk(Walk::default(), blocks)
+    }
+
+    /// [`collect`](Self::collect), with heading spans located in the
+    /// document's `source` so diagnostics point at the heading line.
+    pub fn collect_in(blocks: &[Block], source: &str) -> Xrefs {
+        let walk = Walk {
+            source: Some(source),
+            ..Walk::default()
+        };
+        Self::walk(walk, blocks)
+    }
+
+    fn walk(mut walk: Walk<'_>, blocks: &[Block]) -> Xrefs {
+        walk.blocks(blocks);
+        let top = walk.headings.iter().map(|h| h.0).min().unwrap_or(1);
+        let mut levels = [0usize; 6];
+        for (level, label, span) in walk.headings {
+            levels[level - 1] += 1;
+            levels[level..].fill(0);
+            if let Some(label) = label {
+                let number: Vec<String> = levels[top - 1..level]
+                    .iter()
+                    .map(|n| n.to_string())
+                    .collect();
+                walk.labelled.push(Target {
+                    label,
+                    kind: XrefKind::Section,
+                    number: number.join("."),
+                    span,
+                });
+            }
+        }
+        walk.labelled
+            .sort_by_key(|t| (t.span.start_line, t.span.start_offset));
+
+        let mut xrefs = Xrefs::default();
+        let mut seen = HashSet::new();
+        for target in walk.labelled {
+            if seen.insert(target.label.clone()) {
+                xrefs.targets.push(target);
+            } else {
+                xrefs.duplicates.push(target);
+            }
+        }
+        xrefs
+    }
+
+    /// The target labelled `label`.
+    pub fn get(&self, label: &str) -> Option<&Target> {
+        self.targets.iter().find(|t| t.label == label)
+    }
+
+    /// Every target, in document order.
+    pub fn targets(&self) -> &[Target] {
+        &self.targets
+    }
+
+    /// Redefinitions of a label, in document order; references resolve to
+    /// the first definition.
+    pub fn duplicates(&self) -> &[Target] {
+        &self.duplicates
+    }
+}
+
+#[derive(Default)]
+struct Walk<'a> {
+    /// Document source, when heading spans should be located in it.
+    source: Option<&'a str>,
+    counts: HashMap<XrefKind, usize>,
+    labelled: Vec<Target>,
+    /// `(level, label, span)` for every markdown heading.
+    headings: Vec<(usize, Option<String>, Span)>,
+}
+
+impl Walk<'_> {
+    fn blocks(&mut self, blocks: &[Block]) {
+        for b in blocks {
+            if let Some(kind) = block_kind(b) {
+                let n = self.counts.entry(kind).or_default();
+                *n += 1;
+                if let Some(label) = block_label(b) {
+                    self.labelled.push(Target {
+                        label: label.to_string(),
+                        kind,
+                        number: n.to_string(),
+                        span: b.span(),
+                    });
+                }
+            }
+            if let Block::Markdown { content, span } = b {
+                let origin = self
+                    .source
+                    .and_then(|source| content_origin(b, content, source))
+                    .unwrap_or(*span);
+                for (offset, level, label) in headings(content) {
+                    let span = line_span(origin, content, offset);
+                    self.headings.push((level, label.map(str::to_string), span));
+                }
+            }
+            if let Some(children) = b.children() {
+                self.blocks(children);
+            }
+        }
+    }
+}
+
+/// The kind a block is numbered as, labelled or not.
+pub fn block_kind(b: &Block) -> Option<XrefKind> {
+    match b {
+        Block::Figure { .. } | Block::Chart { .. } | Block::Diagram { .. } => {
+            Some(XrefKind::Figure)
+        }
+        Block::Data { .. } => Some(XrefKind::Table),
+        Block::Code { lang, .. } if is_equation(lang.as_deref()) => Some(XrefKind::Equation),
+        Block::Code { .. } => Some(XrefKind::Listing),
+        _ => None,
+    }
+}
+
+/// A block's cross-reference label (its `id` attribute).
+pub fn block_label(b: &Block) -> Option<&str> {
+    match b {
+        Block::Figure { id, .. }
+        | Block::Chart { id, .. }
+        | Block::Diagram { id, .. }
+        | Block::Data { id, .. }
+        | Block::Code { id, .. } => id.as_deref().filter(|id| !id.is_empty()),
+        _ => None,
+    }
+}
+
+/// `::code[lang=math]` holds a (LaTeX-syntax) display equation.
+pub fn is_equation(lang: Option<&str>) -> bool {
+    lang == Some("math")
+}
+
+/// ATX headings in markdown prose, outside fenced code: `(byte offset,
+/// level, explicit {#label})`.
+fn headings(content: &str) -> Vec<(usize, usize, Option<&str>)> {
+    let mut out = Vec::new();
+    let mut offset = 0;
+    let mut in_fence = false;
+    for line in content.split('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        } else if !in_fence && let Some((level, text)) = atx_heading(line) {
+            out.push((offset, level, split_explicit_anchor(text).map(|(_, l)| l)));
+        }
+        offset += line.len() + 1;
+    }
+    out
+}
+
+/// `## Title` → `(2, "Title")`.
+fn atx_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.bytes().take_while(|b| *b == b'#').count();
+    let rest = &line[level..];
+    ((1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' ')))
+        .then(|| (level, rest.trim()))
+}
+
+/// The span of the line at byte `offset` of `content`, where `origin`
+/// starts at the content's first byte.
+pub(crate) fn line_span(origin: Span, content: &str, offset: usize) -> Span {
+    let line = content[..offset].matches('\n').count();
+    let start = content[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let len = content[start..].find('\n').unwrap_or(content.len() - start);
+    Span {
+        start_line: origin.start_line + line,
+        end_line: origin.start_line + line,
+        start_offset: origin.start_offset + start,
+        end_offset: origin.start_offset + start + len,
+        file: origin.file,
+    }
+}
+
+/// Where a block's `content` starts in `source`. Markdown content is the
+/// block's own source slice, a container's body follows its opener line;
+/// blocks nested in a container carry no source span, so their content is
+/// located by its first occurrence instead.
+fn content_origin(b: &Block, content: &str, source: &str) -> Option<Span> {
+    let span = b.span();
+    let from = if span.start_line == 0 {
+        0
+    } else {
+        span.start_offset
+    };
+    let start = from + source.get(from..)?.find(content)?;
+    let line = source[..start].matches('\n').count() + 1;
+    Some(Span {
+        start_line: line,
+        end_line: line,
+        start_offset: start,
+        end_offset: start + content.len(),
+        file: span.file,
+    })
+}
+
+/// `[#label]` references in the document's prose that name no target, with
+/// the span of the line each sits on in `source`.
+pub fn unresolved(blocks: &[Block], xrefs: &Xrefs, source: &str) -> Vec<(String, Span)> {
+    let mut out = Vec::new();
+    unresolved_rec(blocks, xrefs, source, &mut out);
+    out
+}
+
+fn unresolved_rec(blocks: &[Block], xrefs: &Xrefs, source: &str, out: &mut Vec<(String, Span)>) {
+    for b in blocks {
+        if let Some(text) = cite_text_of(b) {
+            let origin = content_origin(b, text, source);
+            for (s, _, label) in find_xrefs(text) {
+                if xrefs.get(&label).is_none() {
+                    let span = origin.map_or(b.span(), |o| line_span(o, text, s));
+                    // A section's children repeat its content.
+                    let entry = (label, span);
+                    if !out.contains(&entry) {
+                        out.push(entry);
+                    }
+                }
+            }
+        }
+        if let Some(children) = b.children() {
+            unresolved_rec(children, xrefs, source, out);
+        }
+    }
+}
+
+/// Replace `[#label]` references in `text` with `render(target)` against
+/// the ambient context's targets. Unresolved references stay as they are.
+pub fn substitute_xrefs(text: &str, render: impl Fn(&Target) -> String) -> String {
+    let refs = find_xrefs(text);
+    if refs.is_empty() {
+        return text.to_string();
+    }
+    with_active(|ctx| {
+        let Some(ctx) = ctx else {
+            return text.to_string();
+        };
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        for (s, e, label) in refs {
+            out.push_str(&text[last..s]);
+            match ctx.xrefs.get(&label) {
+                Some(target) => out.push_str(&render(target)),
+                None => out.push_str(&text[s..e]),
+            }
+            last = e;
+        }
+        out.push_str(&text[last..]);
+        out
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(xrefs: &Xrefs) -> Vec<(String, String)> {
+        xrefs
+            .targets()
+            .iter()
+            .map(|t| (t.label.clone(), t.text()))
+            .collect()
+    }
+
+    #[test]
+    fn numbers_each_kind_in_document_order() {
+        let src = "## Intro {#sec:intro}\n\n::figure[src=a.png]\n::\n\n::chart[type=bar id=fig:chart]\n| | A |\n| x | 1 |\n::\n\n::data[id=tbl:one]\n| a |\n| 1 |\n::\n\n### Method {#sec:method}\n\n```\n# not a heading {#nope}\n```\n\n::code[lang=math id=eq:e]\nE = mc^2\n::\n\n::code[lang=rust id=lst:main]\nfn main() {}\n::\n\n## Results {#sec:results}\n";
+        let doc = crate::parse(src).doc;
+        let xrefs = Xrefs::collect(&doc.blocks);
+        assert_eq!(
+            labels(&xrefs),
+            [
+                ("sec:intro", "Section 1"),
+                ("fig:chart", "Figure 2"),
+                ("tbl:one", "Table 1"),
+                ("sec:method", "Section 1.1"),
+                ("eq:e", "Equation 1"),
+                ("lst:main", "Listing 1"),
+                ("sec:results", "Section 2"),
+            ]
+            .map(|(l, t)| (l.to_string(), t.to_string()))
+        );
+        assert_eq!(xrefs.get("sec:method").map(|t| t.span.start_line), Some(16));
+        assert!(xrefs.get("nope").is_none());
+    }
+
+    #[test]
+    fn duplicates_and_unresolved_references() {
+        let src = "::figure[src=a.png id=dup]\n::\n\n::data[id=dup]\n| a |\n::\n\nSee [#dup] and\n[#missing].\n";
+        let doc = crate::parse(src).doc;
+        let xrefs = Xrefs::collect(&doc.blocks);
+        assert_eq!(
+            xrefs.get("dup").map(Target::text).as_deref(),
+            Some("Figure 1")
+        );
+        assert_eq!(xrefs.duplicates().len(), 1);
+        assert_eq!(xrefs.duplicates()[0].kind, XrefKind::Table);
+        let missing = unresolved(&doc.blocks, &xrefs, src);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].0, "missing");
+        assert_eq!(missing[0].1.start_line, 9);
+    }
+}
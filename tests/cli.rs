@@ -211,6 +211,46 @@ fn github_format_uses_error_kind_for_errors() {
     assert!(stdout_str(&out).contains(&format!("::error file={path},line=1::P002: ")));
 }
 
+#[test]
+fn sarif_format_emits_rules_results_and_fixes() {
+    let path = fixture("l001-section.surf");
+    let out = run(&["check", &path, "--format", "sarif"]);
+    assert_eq!(exit_code(&out), 0);
+    let v: serde_json::Value =
+        serde_json::from_str(&stdout_str(&out)).expect("stdout is valid JSON");
+    assert_eq!(v["version"], "2.1.0");
+    let run = &v["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "surf-lint");
+    let rules = run["tool"]["driver"]["rules"].as_array().expect("rules");
+    assert!(rules.iter().any(|r| r["id"] == "L001"));
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"], "L001");
+    assert_eq!(
+        result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+        path.as_str()
+    );
+    assert!(result["fixes"][0]["artifactChanges"][0]["replacements"].is_array());
+}
+
+#[test]
+fn junit_format_emits_a_suite_per_file() {
+    let out = run(&[
+        "check",
+        &fixture("clean.surf"),
+        &fixture("p002-unclosed-frontmatter.surf"),
+        "--format",
+        "junit",
+    ]);
+    assert_eq!(exit_code(&out), 1);
+    let stdout = stdout_str(&out);
+    assert!(stdout.starts_with("<?xml"), "stdout: {stdout}");
+    assert_eq!(stdout.matches("<testsuite ").count(), 2);
+    assert!(
+        stdout.contains("<failure type=\"error\""),
+        "stdout: {stdout}"
+    );
+}
+
 #[test]
 fn human_format_shows_excerpt_caret_and_fix_hint() {
     let out = run(&["check", &fixture("l001-section.surf")]);
@@ -343,6 +383,65 @@ fn fix_stdin_writes_fixed_source_to_stdout() {
     );
 }
 
+// ------------------------------------------------------------------
+// fmt
+// ------------------------------------------------------------------
+
+const UNFORMATTED: &str = "# Title\n::callout[ title=\"Hi\"  type=\"info\" ]\nBody.\n::\n\n\n\n::data\n|a|bb|\n|-|-|\n|1|2|\n::\n";
+const FORMATTED: &str = "# Title\n\n::callout[type=info title=Hi]\nBody.\n::\n\n::data\n| a   | bb  |\n| --- | --- |\n| 1   | 2   |\n::\n";
+
+#[test]
+fn fmt_rewrites_file_in_place() {
+    let dir = temp_dir("fmt-write");
+    let file = dir.join("doc.surf");
+    fs::write(&file, UNFORMATTED).expect("write doc");
+    let out = run(&["fmt", file.to_str().unwrap()]);
+    assert_eq!(exit_code(&out), 0);
+    assert!(
+        stdout_str(&out).contains("formatted"),
+        "stdout: {}",
+        stdout_str(&out)
+    );
+    assert_eq!(fs::read_to_string(&file).expect("read back"), FORMATTED);
+
+    // Already canonical: second run is silent and leaves the file alone.
+    let out = run(&["fmt", file.to_str().unwrap()]);
+    assert_eq!(exit_code(&out), 0);
+    assert_eq!(stdout_str(&out), "");
+}
+
+#[test]
+fn fmt_check_reports_diff_and_exits_1_without_writing() {
+    let dir = temp_dir("fmt-check");
+    let file = dir.join("doc.surf");
+    fs::write(&file, UNFORMATTED).expect("write doc");
+    let out = run(&["fmt", "--check", file.to_str().unwrap()]);
+    assert_eq!(exit_code(&out), 1);
+    let stdout = stdout_str(&out);
+    assert!(
+        stdout.contains("+::callout[type=info title=Hi]"),
+        "stdout: {stdout}"
+    );
+    assert_eq!(fs::read_to_string(&file).expect("read back"), UNFORMATTED);
+}
+
+#[test]
+fn fmt_check_on_formatted_file_exits_0() {
+    let dir = temp_dir("fmt-clean");
+    let file = dir.join("doc.surf");
+    fs::write(&file, FORMATTED).expect("write doc");
+    let out = run(&["fmt", "--check", file.to_str().unwrap()]);
+    assert_eq!(exit_code(&out), 0);
+    assert_eq!(stdout_str(&out), "");
+}
+
+#[test]
+fn fmt_stdin_writes_formatted_source_to_stdout() {
+    let out = run_stdin(&["fmt", "-"], UNFORMATTED);
+    assert_eq!(exit_code(&out), 0);
+    assert_eq!(stdout_str(&out), FORMATTED);
+}
+
 // ------------------------------------------------------------------
 // rules
 // ------------------------------------------------------------------
@@ -424,6 +523,24 @@ fn config_discovery_walks_up_to_a_parent_directory() {
     assert_eq!(exit_code(&out), 1, "config found in grandparent dir");
 }
 
+#[test]
+fn config_limits_reject_with_p007() {
+    let dir = temp_dir("cfg-limits");
+    let file = copy_fixture_to(&dir, "clean.surf");
+    write_config(&dir, "[limits]\nmax_blocks = 2\n");
+    let out = run(&["check", file.to_str().unwrap()]);
+    assert_eq!(exit_code(&out), 1, "P007 is an error → exit 1");
+    assert!(
+        stdout_str(&out).contains("error[P007]"),
+        "{}",
+        stdout_str(&out)
+    );
+
+    write_config(&dir, "[limits]\nmax_blocks = 3\n");
+    let out = run(&["check", file.to_str().unwrap()]);
+    assert_eq!(exit_code(&out), 0, "{}", stdout_str(&out));
+}
+
 #[test]
 fn config_off_disables_a_rule() {
     let dir = temp_dir("cfg-off");
@@ -520,3 +637,80 @@ fn config_applies_to_fix_as_well() {
     let after = fs::read_to_string(&file).expect("read back");
     assert_eq!(after, original, "disabled rule must not be fixed");
 }
+
+#[test]
+fn config_custom_rule_reaches_every_output_format() {
+    let dir = temp_dir("cfg-custom-rule");
+    let file = dir.join("doc.surf");
+    fs::write(
+        &file,
+        "---\ntitle: T\ntype: doc\n---\n\n::decision[status=accepted]\nUse Rust.\n::\n",
+    )
+    .expect("write doc");
+    write_config(
+        &dir,
+        "[[rules]]\nid = \"X001\"\nseverity = \"error\"\nblock = \"decision\"\n\
+         require_attrs = [\"deciders\"]\nmessage = \"::{block} must have `{attr}`\"\n",
+    );
+    let path = file.to_str().unwrap();
+
+    let out = run(&["check", path]);
+    assert_eq!(exit_code(&out), 1);
+    assert!(
+        stdout_str(&out).contains("error[X001] ::decision must have `deciders`"),
+        "stdout: {}",
+        stdout_str(&out)
+    );
+
+    let out = run(&["check", path, "--format", "json"]);
+    let v: serde_json::Value =
+        serde_json::from_str(&stdout_str(&out)).expect("stdout is valid JSON");
+    let diags = v["files"][0]["diagnostics"]
+        .as_array()
+        .expect("diagnostics");
+    assert!(
+        diags
+            .iter()
+            .any(|d| d["code"] == "X001" && d["severity"] == "error")
+    );
+
+    let out = run(&["check", path, "--format", "github"]);
+    assert!(
+        stdout_str(&out).contains("::error file="),
+        "stdout: {}",
+        stdout_str(&out)
+    );
+    assert!(stdout_str(&out).contains("X001"));
+
+    let out = run(&["check", path, "--format", "sarif"]);
+    let v: serde_json::Value =
+        serde_json::from_str(&stdout_str(&out)).expect("stdout is valid JSON");
+    let run = &v["runs"][0];
+    let rules = run["tool"]["driver"]["rules"].as_array().expect("rules");
+    let index = rules
+        .iter()
+        .position(|r| r["id"] == "X001")
+        .expect("X001 descriptor");
+    assert_eq!(rules[index]["defaultConfiguration"]["level"], "error");
+    let result = run["results"]
+        .as_array()
+        .expect("results")
+        .iter()
+        .find(|r| r["ruleId"] == "X001")
+        .expect("X001 result");
+    assert_eq!(result["ruleIndex"], index);
+}
+
+#[test]
+fn config_custom_rule_with_reserved_id_exits_3() {
+    let dir = temp_dir("cfg-custom-reserved");
+    let file = copy_fixture_to(&dir, "clean.surf");
+    write_config(
+        &dir,
+        "[[rules]]\nid = \"L001\"\nmax_heading_depth = 3\nmessage = \"too deep\"\n",
+    );
+    let out = run(&["check", file.to_str().unwrap()]);
+    assert_eq!(exit_code(&out), 3);
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("built-in prefix"), "stderr: {stderr}");
+}
@@ -60,6 +60,7 @@ const ALLOWLIST: &[&str] = &[
 /// implemented of 112 total). When a kind is added to the registry, the
 /// companion completeness check below fails until it gets a snippet here.
 const SNIPPETS: &[(&str, &str)] = &[
+    ("bibliography-data", "::bibliography-data[format=bibtex]\n@book{jones2019, author = {Jones, Alice}, year = 2019}\n::"),
     ("callout", "::callout[type=warning title=\"Heads up\"]\nBody\n::"),
     ("chart", "::chart[type=line source=\"/api/metrics\" period=weekly title=\"WAU\"]\n::"),
     ("code", "::code[lang=rust file=src/main.rs]\nfn main() {}\n::"),
@@ -0,0 +1,212 @@
+//! Property-based tests for the canonical formatter (`surf_parse::format`).
+//!
+//! The two guarantees `surf-lint fmt` relies on, plus the usual crash sweep:
+//!
+//! 1. **Idempotent** — `format(format(x)) == format(x)`.
+//! 2. **Meaning-preserving** — `parse(format(x))` equals `parse(x)` modulo
+//!    spans and verbatim raw copies of the source.
+//! 3. **Never panics** — arbitrary input never crashes the formatter.
+
+use proptest::prelude::*;
+use serde_json::Value;
+
+use surf_parse::FormatOptions;
+
+// ------------------------------------------------------------------
+// Generators: fixture-style documents with layout noise
+// ------------------------------------------------------------------
+
+/// A segment with something for the formatter to normalise, plus filler.
+fn arb_segment() -> impl Strategy<Value = String> {
+    prop_oneof![
+        // Clean filler.
+        Just("Plain paragraph text.\n".to_string()),
+        Just("## Heading\n\nMore text here.\n".to_string()),
+        Just("::callout[type=info]\nAll good.\n::\n".to_string()),
+        // Attributes out of spec order, over-quoted, padded.
+        "[A-Za-z]{1,8}"
+            .prop_map(|t| format!("::callout[ title=\"{t}\"  type=\"warning\" ]\nBody.\n::\n")),
+        Just("::metric[value=\"42\" label=\"Users\" trend=up]\n".to_string()),
+        Just("::cta[primary label=\"Go now\" href=\"/go\"]\n".to_string()),
+        // Quoted values that must stay quoted.
+        Just("::callout[title=\"true\" type=note]\nQuoted literal.\n::\n".to_string()),
+        // Nesting at the wrong colon depth.
+        Just("::app-shell\n::sidebar\n::nav-item[label=Home href=/]\n::\n::\n".to_string()),
+        Just("::columns\n::::column\nDeep.\n::::\n::\n".to_string()),
+        // Ragged data tables.
+        Just("::data[format=table]\n|a|bb|\n|-|:-:|\n|1|2|\n::\n".to_string()),
+        Just(
+            "::data\n| Name | Score |\n|---|---|\n| Ada | 9 |\n| Grace Hopper | 10 |\n::\n"
+                .to_string()
+        ),
+        Just("::data[format=csv]\na,b\n1,2\n::\n".to_string()),
+        // Verbatim content.
+        Just("::code[lang=rust]\nfn main() {\n    let x  =  1;\n\n\n}\n::\n".to_string()),
+        Just("```md\n:::weird[ b=1 ]\n|a|b|\n```\n".to_string()),
+        // Multi-byte content.
+        Just("# Café ☕ 日本語 🚀\n\n::callout[type=tip]\n東京 emoji 🎉 body.\n::\n".to_string()),
+    ]
+}
+
+/// Separator between segments: none, one blank line, or several.
+fn arb_gap() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just(String::new()),
+        Just("\n".to_string()),
+        Just("\n\n\n".to_string()),
+    ]
+}
+
+/// Optional front matter, with varying space before the body.
+fn arb_front_matter() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just(String::new()),
+        Just("---\ntitle: T\ntype: report\n---\n".to_string()),
+        Just("---\ntitle: \"日本語 🚀\"\ntype: guide\n---\n\n\n".to_string()),
+    ]
+}
+
+/// A document: front matter + segments separated by random gaps.
+fn arb_document() -> impl Strategy<Value = String> {
+    (
+        arb_front_matter(),
+        proptest::collection::vec((arb_segment(), arb_gap()), 0..6),
+    )
+        .prop_map(|(fm, segments)| {
+            let body: String = segments
+                .into_iter()
+                .map(|(segment, gap)| format!("{segment}{gap}"))
+                .collect();
+            format!("{fm}{body}")
+        })
+}
+
+// ------------------------------------------------------------------
+// Oracle: the parse tree with layout stripped
+// ------------------------------------------------------------------
+
+/// Drop spans and the fields that copy source text verbatim (`raw_content`,
+/// and `content` wherever the block also carries parsed `children`), and
+/// the blank lines around markdown gaps.
+fn strip_layout(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.remove("span");
+            map.remove("raw_content");
+            if map.contains_key("children") {
+                map.remove("content");
+            }
+            // Blank lines around a markdown gap are layout.
+            if map.get("kind").and_then(Value::as_str) == Some("Markdown")
+                && let Some(Value::String(content)) = map.get_mut("content")
+            {
+                let lines: Vec<&str> = content
+                    .lines()
+                    .skip_while(|line| line.trim().is_empty())
+                    .collect();
+                *content = lines.join("\n").trim_end().to_string();
+            }
+            map.values_mut().for_each(strip_layout);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_layout),
+        _ => {}
+    }
+}
+
+fn tree(source: &str) -> Value {
+    let doc = surf_parse::parse(source).doc;
+    let mut value = serde_json::json!({
+        "front_matter": doc.front_matter,
+        "blocks": doc.blocks,
+    });
+    strip_layout(&mut value);
+    value
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(128))]
+
+    // ------------------------------------------------------------------
+    // Property 1: idempotency
+    // ------------------------------------------------------------------
+
+    /// format(format(x)) == format(x).
+    #[test]
+    fn format_is_idempotent(doc in arb_document()) {
+        let options = FormatOptions::default();
+        let once = surf_parse::format(&doc, &options);
+        let twice = surf_parse::format(&once, &options);
+        prop_assert_eq!(twice, once);
+    }
+
+    // ------------------------------------------------------------------
+    // Property 2: meaning preservation
+    // ------------------------------------------------------------------
+
+    /// The parse tree survives formatting, modulo spans and raw copies.
+    #[test]
+    fn format_preserves_the_parse_tree(doc in arb_document()) {
+        let formatted = surf_parse::format(&doc, &FormatOptions::default());
+        prop_assert_eq!(tree(&formatted), tree(&doc), "formatted:\n{}", formatted);
+    }
+
+    // ------------------------------------------------------------------
+    // Property 3: never panics
+    // ------------------------------------------------------------------
+
+    /// Arbitrary printable strings never panic the formatter.
+    #[test]
+    fn format_never_panics(input in "\\PC{0,400}") {
+        let _ = surf_parse::format(&input, &FormatOptions::default());
+    }
+
+    /// Directive-ish multi-line fragments never panic the formatter, and the
+    /// output is always a fixpoint.
+    #[test]
+    fn format_never_panics_on_directive_fragments(
+        lines in proptest::collection::vec(
+            prop_oneof![
+                "\\PC{0,20}",
+                Just("::".to_string()),
+                Just(":::".to_string()),
+                Just("::data".to_string()),
+                Just("|a|b|".to_string()),
+                Just("|-|-|".to_string()),
+                Just("```".to_string()),
+                "::[a-z-]{1,8}\\[[a-z= \"]{0,12}\\]?",
+                ":::[a-z-]{1,8}",
+            ],
+            0..20,
+        )
+    ) {
+        let input = lines.join("\n");
+        let options = FormatOptions::default();
+        let once = surf_parse::format(&input, &options);
+        prop_assert_eq!(surf_parse::format(&once, &options), once);
+    }
+}
+
+// ------------------------------------------------------------------
+// Regressions: colon depth of leaf and closer-less children
+// ------------------------------------------------------------------
+
+/// Nesting shapes whose colon depth used to change on every pass.
+#[test]
+fn format_is_idempotent_on_leaf_and_closerless_children() {
+    let options = FormatOptions::default();
+    for doc in [
+        "::footer\nmore text\n:::column\nref\n::\n",
+        "::page[route=/]\n::cta[label=Go href=/x]\n::metric[label=x value=1]\n:::\n::\n",
+        "::faq\n### Why?\n::cta[label=Go href=/x]\nBecause.\n::\n### Next?\nYes.\n::\n",
+    ] {
+        let once = surf_parse::format(doc, &options);
+        assert_eq!(surf_parse::format(&once, &options), once, "input:\n{doc}");
+    }
+}
+
+/// A `::faq` whose `::cta` owns a closer keeps both closers at two colons.
+#[test]
+fn format_keeps_closers_of_a_faq_around_a_cta() {
+    let doc = "::faq\n### Why?\n::cta[label=Go href=/x]\nBecause.\n::\n### Next?\nYes.\n::\n";
+    assert_eq!(surf_parse::format(doc, &FormatOptions::default()), doc);
+}
@@ -1029,6 +1029,8 @@ fn diagrams_showcase_renders_all_kinds() {
         "c4",
         "requirement",
         "sankey",
+        "packet",
+        "block",
         // Chart aliases render as diagram figures wrapping chart SVG.
         "pie",
         "donut",
@@ -1045,11 +1047,12 @@ fn diagrams_showcase_renders_all_kinds() {
         !html.contains("surfdoc-diagram-fallback"),
         "a diagram fell back to prose instead of rendering SVG"
     );
-    // Each geometry kind produced a diagram <svg> (17 native types + the
-    // mermaid-syntax block, which translates to a native flowchart); each
-    // chart alias produced a chart <svg> through the ::chart pipeline.
-    assert_eq!(html.matches("<svg class=\"surfdoc-diagram-svg\"").count(), 18);
-    assert_eq!(html.matches("<svg class=\"surfdoc-chart-svg\"").count(), 4);
+    // Each geometry kind produced a diagram <svg> (19 native types + the
+    // seven mermaid-syntax blocks that translate to native kinds); each
+    // chart alias and the mermaid xychart produced a chart <svg> through the
+    // ::chart pipeline.
+    assert_eq!(html.matches("<svg class=\"surfdoc-diagram-svg\"").count(), 26);
+    assert_eq!(html.matches("<svg class=\"surfdoc-chart-svg\"").count(), 5);
 
     // Determinism: byte-identical across two renders.
     assert_eq!(html, result.doc.to_html());
@@ -1077,9 +1080,9 @@ fn diagrams_reference_doc_renders_clean() {
         !html.contains("surfdoc-diagram-fallback"),
         "a reference diagram fell back to prose"
     );
-    // 17 native examples + the mermaid example render as diagram SVG; the
+    // 19 native examples + the mermaid example render as diagram SVG; the
     // pie chart-alias example renders as chart SVG.
-    assert_eq!(html.matches("<svg class=\"surfdoc-diagram-svg\"").count(), 18);
+    assert_eq!(html.matches("<svg class=\"surfdoc-diagram-svg\"").count(), 20);
     assert_eq!(html.matches("<svg class=\"surfdoc-chart-svg\"").count(), 1);
     assert_eq!(html, result.doc.to_html());
 }
@@ -1117,11 +1120,12 @@ fn charts_showcase_renders_all_types() {
             "missing rendered figure for chart type {kind}"
         );
     }
-    // Eight inline-data charts each produced a real <svg> (role="img"); the
-    // static preview placeholder has no role, so this counts only real charts.
+    // Eight inline-data and two `data=#id`-bound charts each produced a real
+    // <svg> (role="img"); the static preview placeholder has no role, so this
+    // counts only real charts.
     assert_eq!(
         html.matches("<svg class=\"surfdoc-chart-svg\" xmlns").count(),
-        8
+        10
     );
     // The trailing data-less chart fell back to the static preview mount point.
     assert!(html.contains("surfdoc-chart-preview"));
@@ -1166,6 +1170,9 @@ fn citations_showcase_renders_apa() {
     // Inline APA author-date citation resolved.
     assert!(html.contains("(Smith, 2020)"), "missing inline APA cite");
     assert!(html.contains("(Lee, 2021, p. 4)"), "missing inline cite w/ locator");
+    // Imported BibTeX reference resolved, accents decoded.
+    assert!(html.contains("(Gödel, 1931)"), "missing imported cite");
+    assert!(html.contains("id=\"ref-godel1931\""), "missing imported bib anchor");
     // Bibliography heading + anchors.
     assert!(html.contains(">References<"), "missing References heading");
     assert!(html.contains("id=\"ref-smith2020\""), "missing bib anchor");
@@ -1293,6 +1300,247 @@ author = Smith, John\ntitle = A Title\njournal = A Journal\nyear = 2020\npages =
     assert_eq!(bib_style, Some(Some(surf_parse::Format::Mla)));
 }
 
+/// `::bibliography-data` imports BibTeX references that inline citations and
+/// `::bibliography` resolve like `::cite` definitions, and re-serializes
+/// its body verbatim.
+#[test]
+fn bibliography_data_bibtex_import() {
+    let src = "---\nformat: apa\n---\n\n\
+::bibliography-data[format=bibtex]\n\
+@article{godel1931,\n  author = {G{\\\"o}del, Kurt},\n  title = {{\\\"U}ber formal unentscheidbare S{\\\"a}tze},\n  journal = {Monatshefte f{\\\"u}r Mathematik},\n  year = 1931,\n  month = dec,\n  pages = {173--198},\n}\n::\n\n\
+As shown by [@godel1931].\n\n\
+::bibliography\n::\n";
+    let result = surf_parse::parse(src);
+    let html = result.doc.to_html();
+    assert!(html.contains("Gödel, 1931"), "{html}");
+    assert!(html.contains("Über formal unentscheidbare Sätze"));
+    assert!(html.contains("Monatshefte für Mathematik"));
+    assert!(html.contains("173-198"));
+
+    let serialized = surf_parse::builder::to_surf_source(&result.doc);
+    assert!(serialized.contains("::bibliography-data[format=bibtex]\n@article{godel1931,"));
+    let reparsed = surf_parse::parse(&serialized).doc;
+    let refs = surf_parse::citation::collect_references(&reparsed.blocks);
+    assert_eq!(refs.len(), 1);
+    assert_eq!(refs[0].month, Some(12));
+}
+
+/// A front matter `csl:` style replaces the built-in `format:` style for
+/// in-text citations and the reference list, across renderers.
+#[test]
+fn csl_front_matter_style() {
+    let style = r#"<style xmlns="http://purl.org/net/xbiblio/csl" class="in-text" version="1.0">
+  <citation disambiguate-add-year-suffix="true">
+    <layout prefix="(" suffix=")" delimiter="; ">
+      <names variable="author"><name form="short"/></names>
+      <date variable="issued" prefix=" "><date-part name="year"/></date>
+    </layout>
+  </citation>
+  <bibliography>
+    <sort><key variable="author"/><key variable="title"/></sort>
+    <layout suffix=".">
+      <names variable="author"><name name-as-sort-order="all" initialize-with="."/></names>
+      <date variable="issued" prefix=" (" suffix=")"><date-part name="year"/></date>
+      <text variable="title" font-style="italic" prefix=" "/>
+    </layout>
+  </bibliography>
+</style>"#;
+    let indented: String = style.lines().map(|l| format!("  {l}\n")).collect();
+    let src = format!(
+        "---\nformat: apa\ncsl: |\n{indented}---\n\n\
+::cite[key=b type=book]\nauthor = Young, Ben\ntitle = Second Book\nyear = 2019\n::\n\n\
+::cite[key=a type=book]\nauthor = Young, Ben\ntitle = First Book\nyear = 2019\n::\n\n\
+Compare [@b] with [@a].\n\n\
+::bibliography\n::\n"
+    );
+    let doc = surf_parse::parse(&src).doc;
+    let html = doc.to_html();
+    assert!(html.contains(">(Young 2019b)</a>"), "{html}");
+    assert!(html.contains(">(Young 2019a)</a>"), "{html}");
+    assert!(html.contains("surfdoc-bib-csl"));
+    assert!(
+        html.contains("Young, B. (2019a) <em>First Book</em>."),
+        "{html}"
+    );
+
+    let md = doc.to_markdown();
+    assert!(
+        md.contains("Compare (Young 2019b) with (Young 2019a)."),
+        "{md}"
+    );
+    assert!(
+        md.contains(
+            "## References\n- Young, B. (2019a) *First Book*.\n- Young, B. (2019b) *Second Book*."
+        ),
+        "{md}"
+    );
+
+    // An invalid style falls back to `format:` (lint L043 reports it).
+    let broken = src.replace(
+        "<names variable=\"author\"><name form=\"short\"/></names>",
+        "<text macro=\"nope\"/>",
+    );
+    let fallback = surf_parse::parse(&broken).doc.to_markdown();
+    assert!(
+        fallback.contains("Compare (Young, 2019) with (Young, 2019)."),
+        "{fallback}"
+    );
+}
+
+#[test]
+fn footnotes_across_renderers() {
+    let src = "# Notes\n\nFirst[^b] then second[^a] and again[^b].\n\n\
+[^a]: Alpha note.\n[^b]: Beta note\n    continued.\n\n\
+Unknown[^zz] stays.\n";
+    let doc = surf_parse::parse(src).doc;
+
+    let html = doc.to_html();
+    assert!(
+        html.contains(
+            "First<sup class=\"surfdoc-footnote-ref\"><a href=\"#fn-1\" id=\"fnref-1\">1</a></sup>"
+        ),
+        "{html}"
+    );
+    assert!(
+        html.contains("again<sup class=\"surfdoc-footnote-ref\"><a href=\"#fn-1\">1</a></sup>")
+    );
+    assert!(
+        html.contains("<li id=\"fn-1\" class=\"surfdoc-footnote\">Beta note\ncontinued."),
+        "{html}"
+    );
+    assert!(html.contains("<li id=\"fn-2\" class=\"surfdoc-footnote\">Alpha note."));
+    assert!(html.contains("Unknown[^zz] stays."));
+    assert!(!html.contains("[^a]:"));
+
+    let md = doc.to_markdown();
+    assert!(
+        md.contains("First[^1] then second[^2] and again[^1]."),
+        "{md}"
+    );
+    assert!(
+        md.ends_with("[^1]: Beta note\n    continued.\n[^2]: Alpha note."),
+        "{md}"
+    );
+
+    let tex = doc.to_latex();
+    assert!(
+        tex.contains("First\\footnote{Beta note\ncontinued.} then second\\footnote{Alpha note.}"),
+        "{tex}"
+    );
+
+    let ts = doc.to_typst();
+    assert!(
+        ts.contains("First#footnote[Beta note continued.] then second#footnote[Alpha note.]"),
+        "{ts}"
+    );
+
+    let term = doc.to_terminal();
+    assert!(
+        term.contains("First[1] then second[2] and again[1]."),
+        "{term}"
+    );
+    assert!(term.contains("1. Beta note continued."), "{term}");
+}
+
+#[test]
+fn note_style_csl_citations_become_footnotes() {
+    let style = r#"<style xmlns="http://purl.org/net/xbiblio/csl" class="note" version="1.0">
+  <citation>
+    <layout suffix="." delimiter="; ">
+      <names variable="author"><name/></names>
+      <text variable="title" font-style="italic" prefix=", "/>
+    </layout>
+  </citation>
+</style>"#;
+    let indented: String = style.lines().map(|l| format!("  {l}\n")).collect();
+    let src = format!(
+        "---\ncsl: |\n{indented}---\n\n\
+::cite[key=a type=book]\nauthor = Young, Ben\ntitle = First Book\nyear = 2019\n::\n\n\
+Claim[^n] and cite [@a].\n\n[^n]: A plain note.\n"
+    );
+    let doc = surf_parse::parse(&src).doc;
+
+    let md = doc.to_markdown();
+    assert!(md.contains("Claim[^1] and cite [^2]."), "{md}");
+    assert!(md.contains("[^2]: Ben Young, *First Book*."), "{md}");
+
+    let html = doc.to_html();
+    assert!(
+        html.contains("<li id=\"fn-2\" class=\"surfdoc-footnote\">Ben Young, <em>First Book</em>."),
+        "{html}"
+    );
+
+    let tex = doc.to_latex();
+    assert!(
+        tex.contains("cite \\footnote{Ben Young, \\textit{First Book}.}"),
+        "{tex}"
+    );
+}
+
+#[test]
+fn cross_references_across_renderers() {
+    let src = "# Design {#sec:design}\n\n\
+See [#fig:arch], [#tbl:costs], [#eq:energy] and [#sec:design]; [#fig:nope] stays.\n\n\
+::figure[src=intro.png]\n::\n\n\
+::figure[src=arch.png caption=\"Architecture\" id=fig:arch]\n::\n\n\
+::data[id=tbl:costs]\n| item | cost |\n|------|------|\n| A | 1 |\n::\n\n\
+::code[lang=math id=eq:energy]\nE = mc^2\n::\n";
+    let doc = surf_parse::parse(src).doc;
+
+    let html = doc.to_html();
+    assert!(html.contains("See <a class=\"surfdoc-xref\" href=\"#fig:arch\">Figure 2</a>, <a class=\"surfdoc-xref\" href=\"#tbl:costs\">Table 1</a>, <a class=\"surfdoc-xref\" href=\"#eq:energy\">Equation 1</a> and <a class=\"surfdoc-xref\" href=\"#sec:design\">Section 1</a>; [#fig:nope] stays."), "{html}");
+    assert!(
+        html.contains("<figure id=\"fig:arch\" class=\"surfdoc-figure\">"),
+        "{html}"
+    );
+    assert!(
+        html.contains("<div id=\"tbl:costs\" class=\"surfdoc-table-wrap\">"),
+        "{html}"
+    );
+    assert!(html.contains("<h1 id=\"sec:design\">Design</h1>"), "{html}");
+
+    let tex = doc.to_latex();
+    assert!(
+        tex.contains("\\section{Design}\\label{sec:design}"),
+        "{tex}"
+    );
+    assert!(tex.contains("See Figure~\\ref{fig:arch}, Table~\\ref{tbl:costs}, Equation~\\ref{eq:energy} and Section~\\ref{sec:design}"), "{tex}");
+    assert!(
+        tex.contains("\\caption{Architecture}\n\\label{fig:arch}"),
+        "{tex}"
+    );
+    assert!(
+        tex.contains("\\refstepcounter{table}\\label{tbl:costs}\n\\end{table}"),
+        "{tex}"
+    );
+    assert!(
+        tex.contains("\\begin{equation}\nE = mc^2\n\\label{eq:energy}\n\\end{equation}"),
+        "{tex}"
+    );
+
+    let ts = doc.to_typst();
+    assert!(ts.contains("= Design <sec:design>"), "{ts}");
+    assert!(
+        ts.contains("See @fig:arch, @tbl:costs, @eq:energy and #link(<sec:design>)[Section 1]"),
+        "{ts}"
+    );
+    assert!(ts.contains(") <fig:arch>\n"), "{ts}");
+    assert!(
+        ts.contains("#figure(kind: table, supplement: [Table])[\n#table("),
+        "{ts}"
+    );
+    assert!(ts.contains("] <tbl:costs>\n"), "{ts}");
+
+    let md = doc.to_markdown();
+    assert!(
+        md.contains("See Figure 2, Table 1, Equation 1 and Section 1; [#fig:nope] stays."),
+        "{md}"
+    );
+
+    let term = doc.to_terminal();
+    assert!(term.contains("See Figure 2, Table 1"), "{term}");
+}
+
 // ── Chunk 6: paper/report rendering (Typst markup + LaTeX) ──────────────────
 
 fn showcase(name: &str) -> surf_parse::SurfDoc {
@@ -59,6 +59,11 @@ const LINT_CORPUS: &[(&str, &[&str])] = &[
     ("l030-derivable-updated.surf", &["L030"]),
     ("l030-missing-fields.surf", &["L030", "L030"]),
     ("l031-enum-case.surf", &["L031"]),
+    ("l041-diagram-parse.surf", &["L041"]),
+    ("l042-chart-binding.surf", &["L042"]),
+    ("l043-csl-style.surf", &["L043"]),
+    ("l044-xref-unresolved.surf", &["L044"]),
+    ("l045-xref-duplicate.surf", &["L045"]),
     ("p001-unclosed.surf", &[]),
     ("p002-unclosed-frontmatter.surf", &[]),
 ];
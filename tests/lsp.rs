@@ -0,0 +1,406 @@
+//! Integration tests for the `surf-lsp` language server binary.
+//!
+//! These run ONLY under `cargo test --features lsp` — the bin target has
+//! `required-features = ["lsp"]`, so `CARGO_BIN_EXE_surf-lsp` only exists
+//! when the feature is on. Each test drives a real server process over stdio
+//! with Content-Length framed JSON-RPC.
+#![cfg(feature = "lsp")]
+
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde_json::{Value, json};
+
+const BIN: &str = env!("CARGO_BIN_EXE_surf-lsp");
+
+/// A running server plus the client half of its stdio.
+struct Client {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: i64,
+    /// Notifications read while waiting for a response.
+    notifications: Vec<Value>,
+}
+
+impl Client {
+    /// Spawn the server and complete the initialize handshake.
+    fn start() -> (Self, Value) {
+        let mut child = Command::new(BIN)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("surf-lsp binary should spawn");
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        let mut client = Client {
+            child,
+            stdin,
+            stdout,
+            next_id: 0,
+            notifications: Vec::new(),
+        };
+        let init = client.request("initialize", json!({ "capabilities": {} }));
+        client.notify("initialized", json!({}));
+        (client, init)
+    }
+
+    fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{body}", body.len())
+            .expect("write to server");
+        self.stdin.flush().expect("flush server stdin");
+    }
+
+    fn receive(&mut self) -> Value {
+        let mut length = None;
+        loop {
+            let mut header = String::new();
+            let read = self.stdout.read_line(&mut header).expect("read header");
+            assert!(read > 0, "server closed stdout mid-session");
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length: ") {
+                length = Some(value.parse::<usize>().expect("numeric Content-Length"));
+            }
+        }
+        let mut body = vec![0; length.expect("Content-Length header")];
+        self.stdout.read_exact(&mut body).expect("read body");
+        serde_json::from_slice(&body).expect("body is JSON")
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    /// Send a request and return its `result`, buffering notifications.
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+        loop {
+            let message = self.receive();
+            if message["id"] == json!(id) {
+                assert!(message.get("error").is_none(), "{method} failed: {message}");
+                return message["result"].clone();
+            }
+            self.notifications.push(message);
+        }
+    }
+
+    /// Next notification named `method`, from the buffer or the wire.
+    fn notification(&mut self, method: &str) -> Value {
+        if let Some(i) = self
+            .notifications
+            .iter()
+            .position(|n| n["method"] == method)
+        {
+            return self.notifications.remove(i)["params"].clone();
+        }
+        loop {
+            let message = self.receive();
+            if message["method"] == method {
+                return message["params"].clone();
+            }
+            self.notifications.push(message);
+        }
+    }
+
+    fn open(&mut self, uri: &str, text: &str) -> Value {
+        self.notify(
+            "textDocument/didOpen",
+            json!({ "textDocument": {
+                "uri": uri, "languageId": "surfdoc", "version": 1, "text": text,
+            } }),
+        );
+        self.notification("textDocument/publishDiagnostics")
+    }
+
+    /// `shutdown` + `exit`; returns the process exit code.
+    fn stop(mut self) -> i32 {
+        self.request("shutdown", Value::Null);
+        self.notify("exit", Value::Null);
+        drop(self.stdin);
+        let status = self.child.wait().expect("server exits");
+        status.code().expect("process not killed by signal")
+    }
+}
+
+/// Unique temp dir per test invocation (no tempfile dependency).
+fn temp_dir(tag: &str) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "surf-lsp-{tag}-{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    ));
+    fs::create_dir_all(&dir).expect("create temp dir");
+    dir
+}
+
+fn file_uri(path: &Path) -> String {
+    format!("file://{}", path.display())
+}
+
+fn codes(published: &Value) -> Vec<String> {
+    published["diagnostics"]
+        .as_array()
+        .expect("diagnostics array")
+        .iter()
+        .filter_map(|d| d["code"].as_str().map(str::to_string))
+        .collect()
+}
+
+const SECTION_DOC: &str = "::section[title=Intro]\nText.\n::\n";
+
+// ------------------------------------------------------------------
+// Lifecycle
+// ------------------------------------------------------------------
+
+#[test]
+fn initialize_advertises_capabilities_and_shutdown_exits_0() {
+    let (client, init) = Client::start();
+    let caps = &init["capabilities"];
+    assert_eq!(caps["textDocumentSync"], json!(1), "full sync");
+    assert_eq!(caps["hoverProvider"], json!(true));
+    assert_eq!(caps["documentSymbolProvider"], json!(true));
+    assert_eq!(
+        caps["codeActionProvider"]["codeActionKinds"],
+        json!(["quickfix"])
+    );
+    assert!(caps["completionProvider"]["triggerCharacters"].is_array());
+    assert_eq!(init["serverInfo"]["name"], json!("surf-lsp"));
+    assert_eq!(client.stop(), 0);
+}
+
+// ------------------------------------------------------------------
+// Diagnostics and code actions
+// ------------------------------------------------------------------
+
+#[test]
+fn did_open_publishes_check_diagnostics() {
+    let (mut client, _) = Client::start();
+    let published = client.open("untitled:doc.surf", SECTION_DOC);
+    assert_eq!(published["uri"], json!("untitled:doc.surf"));
+    assert_eq!(published["version"], json!(1));
+    let l001 = published["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["code"] == "L001")
+        .expect("L001 published");
+    assert_eq!(l001["severity"], json!(2), "warning");
+    assert_eq!(l001["source"], json!("surf-lint"));
+    assert_eq!(l001["range"]["start"], json!({ "line": 0, "character": 0 }));
+
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": "untitled:doc.surf", "version": 2 },
+            "contentChanges": [{ "text": "## Intro\n\nText.\n" }],
+        }),
+    );
+    let published = client.notification("textDocument/publishDiagnostics");
+    assert_eq!(published["version"], json!(2));
+    assert!(!codes(&published).contains(&"L001".to_string()));
+    client.stop();
+}
+
+#[test]
+fn code_action_offers_the_diagnostic_fix() {
+    let (mut client, _) = Client::start();
+    client.open("untitled:doc.surf", SECTION_DOC);
+    let actions = client.request(
+        "textDocument/codeAction",
+        json!({
+            "textDocument": { "uri": "untitled:doc.surf" },
+            "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 0 } },
+            "context": { "diagnostics": [] },
+        }),
+    );
+    let action = actions
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|a| a["diagnostics"][0]["code"] == "L001")
+        .expect("quickfix for L001");
+    assert_eq!(action["kind"], json!("quickfix"));
+    assert_eq!(action["isPreferred"], json!(true), "safe fix");
+    let edits = &action["edit"]["changes"]["untitled:doc.surf"];
+    assert!(
+        edits[0]["newText"].as_str().unwrap().contains("## Intro"),
+        "{edits}"
+    );
+    client.stop();
+}
+
+#[test]
+fn positions_count_utf16_code_units() {
+    let (mut client, _) = Client::start();
+    // "😀" is one char, two UTF-16 units, four UTF-8 bytes.
+    client.open(
+        "untitled:emoji.surf",
+        "# 😀 Hi
+",
+    );
+    let symbols = client.request(
+        "textDocument/documentSymbol",
+        json!({ "textDocument": { "uri": "untitled:emoji.surf" } }),
+    );
+    assert_eq!(symbols[0]["name"], json!("😀 Hi"));
+    assert_eq!(
+        symbols[0]["range"]["end"],
+        json!({ "line": 0, "character": 7 })
+    );
+    client.stop();
+}
+
+// ------------------------------------------------------------------
+// Completion and hover
+// ------------------------------------------------------------------
+
+fn labels(completion: &Value) -> Vec<String> {
+    completion
+        .as_array()
+        .expect("completion array")
+        .iter()
+        .map(|item| item["label"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn complete(client: &mut Client, uri: &str, line: u32, character: u32) -> Value {
+    client.request(
+        "textDocument/completion",
+        json!({
+            "textDocument": { "uri": uri },
+            "position": { "line": line, "character": character },
+        }),
+    )
+}
+
+#[test]
+fn completion_offers_block_names_after_colons() {
+    let (mut client, _) = Client::start();
+    client.open("untitled:c.surf", "Intro\n\n::cal\n");
+    let names = labels(&complete(&mut client, "untitled:c.surf", 2, 5));
+    assert!(names.contains(&"callout".to_string()));
+    assert!(names.contains(&"page".to_string()));
+    assert!(labels(&complete(&mut client, "untitled:c.surf", 0, 3)).is_empty());
+    client.stop();
+}
+
+#[test]
+fn completion_offers_spec_attributes_inside_brackets() {
+    let (mut client, _) = Client::start();
+    client.open("untitled:c.surf", "::callout[type=info \n");
+    let attrs = labels(&complete(&mut client, "untitled:c.surf", 0, 20));
+    assert_eq!(attrs, vec!["title"], "'type' is already present");
+
+    client.open("untitled:d.surf", "::callout[\n");
+    let items = complete(&mut client, "untitled:d.surf", 0, 10);
+    assert_eq!(labels(&items), vec!["type", "title"]);
+    assert_eq!(items[0]["insertText"], json!("type="));
+    // Inside a value nothing is offered.
+    client.open("untitled:e.surf", "::callout[type=\n");
+    assert!(labels(&complete(&mut client, "untitled:e.surf", 0, 15)).is_empty());
+    client.stop();
+}
+
+#[test]
+fn hover_shows_the_rule_description() {
+    let (mut client, _) = Client::start();
+    client.open("untitled:h.surf", SECTION_DOC);
+    let hover = client.request(
+        "textDocument/hover",
+        json!({
+            "textDocument": { "uri": "untitled:h.surf" },
+            "position": { "line": 0, "character": 4 },
+        }),
+    );
+    let text = hover["contents"]["value"].as_str().expect("markdown hover");
+    assert!(
+        text.contains("**L001** — Sections should be expressed as markdown headings"),
+        "{text}"
+    );
+    client.stop();
+}
+
+// ------------------------------------------------------------------
+// Document symbols
+// ------------------------------------------------------------------
+
+#[test]
+fn document_symbols_list_headings_and_page_routes() {
+    let (mut client, _) = Client::start();
+    let doc = "# Site\n\n::page[route=/about title=About]\n## Team\n\nPeople.\n::\n";
+    client.open("untitled:s.surf", doc);
+    let symbols = client.request(
+        "textDocument/documentSymbol",
+        json!({ "textDocument": { "uri": "untitled:s.surf" } }),
+    );
+    let symbols = symbols.as_array().expect("nested symbols");
+    assert_eq!(symbols[0]["name"], json!("Site"));
+    assert_eq!(symbols[1]["name"], json!("/about"));
+    assert_eq!(symbols[1]["detail"], json!("About"));
+    assert_eq!(symbols[1]["range"]["start"]["line"], json!(2));
+    assert_eq!(symbols[1]["children"][0]["name"], json!("Team"));
+    assert_eq!(
+        symbols[1]["children"][0]["range"]["start"]["line"],
+        json!(3)
+    );
+    client.stop();
+}
+
+// ------------------------------------------------------------------
+// .surflint.toml discovery (same rules as the CLI)
+// ------------------------------------------------------------------
+
+#[test]
+fn config_is_discovered_from_the_document_directory_upwards() {
+    let dir = temp_dir("cfg-walkup");
+    let sub = dir.join("nested");
+    fs::create_dir_all(&sub).expect("create nested dir");
+    fs::write(dir.join(".surflint.toml"), "[severity]\nL001 = \"error\"\n").unwrap();
+    let (mut client, _) = Client::start();
+    let published = client.open(&file_uri(&sub.join("doc.surf")), SECTION_DOC);
+    let l001 = published["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["code"] == "L001")
+        .expect("L001 published");
+    assert_eq!(l001["severity"], json!(1), "promoted to error by config");
+
+    // The config is re-read on every check.
+    fs::write(dir.join(".surflint.toml"), "[severity]\nL001 = \"off\"\n").unwrap();
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": file_uri(&sub.join("doc.surf")), "version": 2 },
+            "contentChanges": [{ "text": SECTION_DOC }],
+        }),
+    );
+    let published = client.notification("textDocument/publishDiagnostics");
+    assert!(!codes(&published).contains(&"L001".to_string()));
+    client.stop();
+}
+
+#[test]
+fn malformed_config_is_reported_and_clears_diagnostics() {
+    let dir = temp_dir("cfg-bad");
+    fs::write(dir.join(".surflint.toml"), "[severity]\nL001 = \"loud\"\n").unwrap();
+    let (mut client, _) = Client::start();
+    let published = client.open(&file_uri(&dir.join("doc.surf")), SECTION_DOC);
+    assert_eq!(published["diagnostics"], json!([]));
+    let shown = client.notification("window/showMessage");
+    assert_eq!(shown["type"], json!(1), "error");
+    let message = shown["message"].as_str().unwrap();
+    assert!(message.contains(".surflint.toml"), "{message}");
+    client.stop();
+}
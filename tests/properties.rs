@@ -78,6 +78,7 @@ use surf_parse::types::*;
 /// Helper: build a `Block::Code` with SYNTHETIC span.
 fn synth_code(lang: Option<&str>, file: Option<&str>, content: &str) -> Block {
     Block::Code {
+        id: None,
         lang: lang.map(|s| s.to_string()),
         file: file.map(|s| s.to_string()),
         highlight: vec![],
@@ -242,6 +243,7 @@ fn arb_block() -> impl Strategy<Value = Block> {
             proptest::option::of("[a-zA-Z ]{1,30}"),
         )
             .prop_map(|(src, caption, alt)| Block::Figure {
+                id: None,
                 src,
                 caption,
                 alt,
@@ -255,8 +257,10 @@ fn arb_block() -> impl Strategy<Value = Block> {
             "\\PC{0,100}",
         )
             .prop_map(|(diagram_type, title, content)| Block::Diagram {
+                id: None,
                 diagram_type,
                 title,
+                direction: None,
                 content,
                 span: Span::SYNTHETIC,
             }),
@@ -567,3 +571,222 @@ fn all_simple_model_field_type_variants_reachable() {
         assert_eq!(result, expected, "Variant mismatch for {input:?}");
     }
 }
+
+// ─── incremental reparse ≡ full parse ───────────────────────────────────────
+//
+// `IncrementalParser::edit` must produce exactly what `parse` produces for the
+// edited text: same blocks (spans included), front matter, source and
+// diagnostics. Documents are stitched from fragments that exercise every scan
+// path the reparser reasons about — leaves, containers, nesting, orphan
+// closers, greedy `::page` boundaries, code fences and front matter.
+
+use surf_parse::incremental::IncrementalParser;
+use surf_parse::TextEdit;
+
+const DOC_FRAGMENTS: &[&str] = &[
+    "# Heading\n",
+    "Paragraph text.\n",
+    "\n",
+    "::callout[type=info]\nBody\n::\n",
+    "::metric[label=a value=1]\n",
+    "::\n",
+    ":::\n",
+    "::columns\n:::column\nL\n:::\n:::column\nR\n:::\n::\n",
+    "```\n",
+    "::page[route=/a]\n",
+    "::footer\n",
+    "---\n",
+    "title: T\n",
+    ":: hero[x=1]\n",
+    "::tasks\n- [ ] a\n::\n",
+    "::code[lang=rust]\nfn x() {}\n::\n",
+    "::summary\n",
+    "Ünïcødé ✓\n",
+    "::data\n| a | b |\n| 1 | 2 |\n::\n",
+];
+
+const EDIT_FRAGMENTS: &[&str] = &["", "x", "\n", "::", ":::", "::\n", "```", "---", "é", "::metric[label=b value=2]\n"];
+
+fn arb_surf_doc() -> impl Strategy<Value = String> {
+    proptest::collection::vec(proptest::sample::select(DOC_FRAGMENTS), 0..14)
+        .prop_map(|parts| parts.concat())
+}
+
+fn arb_replacement() -> impl Strategy<Value = String> {
+    prop_oneof![
+        proptest::sample::select(EDIT_FRAGMENTS).prop_map(str::to_string),
+        proptest::sample::select(DOC_FRAGMENTS).prop_map(str::to_string),
+    ]
+}
+
+/// Char-boundary byte offsets of `s`, including `s.len()`.
+fn boundaries(s: &str) -> Vec<usize> {
+    s.char_indices().map(|(i, _)| i).chain(std::iter::once(s.len())).collect()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(500))]
+
+    #[test]
+    fn incremental_reparse_matches_full_parse(
+        doc in arb_surf_doc(),
+        edits in proptest::collection::vec(
+            (any::<proptest::sample::Index>(), any::<proptest::sample::Index>(), arb_replacement()),
+            1..4,
+        ),
+    ) {
+        let mut inc = IncrementalParser::new(&doc);
+        for (a, b, replacement) in edits {
+            let source = inc.source().to_string();
+            let bounds = boundaries(&source);
+            let (x, y) = (*a.get(&bounds), *b.get(&bounds));
+            let (start, end) = (x.min(y), x.max(y));
+            let expected_source = format!("{}{}{}", &source[..start], replacement, &source[end..]);
+            let edit = TextEdit {
+                span: Span::new(0, 0, start, end),
+                replacement,
+            };
+            let got = inc.edit(&edit).clone();
+            let full = surf_parse::parse(&expected_source);
+            prop_assert_eq!(&got.doc.source, &full.doc.source);
+            prop_assert_eq!(
+                serde_json::to_value(&got.doc).unwrap(),
+                serde_json::to_value(&full.doc).unwrap()
+            );
+            prop_assert_eq!(
+                serde_json::to_value(&got.diagnostics).unwrap(),
+                serde_json::to_value(&full.diagnostics).unwrap()
+            );
+        }
+    }
+}
+
+// ─── streamed tree ≡ full parse (web-runtime spec §4.5, never-weaken) ───────
+//
+// However the source is chunked, every block `StreamingParser` commits must be
+// final: a prefix of the full parse of the accumulated source at that moment
+// AND of the final source. After `finish`, the streamed tree — blocks, spans,
+// front matter and diagnostics — equals a full `parse`.
+
+use surf_parse::streaming::StreamingParser;
+
+fn block_json(blocks: &[Block]) -> Vec<serde_json::Value> {
+    blocks.iter().map(|b| serde_json::to_value(b).unwrap()).collect()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(500))]
+
+    #[test]
+    fn streamed_tree_matches_full_parse(
+        doc in prop_oneof![arb_surf_doc(), arb_surf_doc().prop_map(|d| d.replace('\n', "\r\n"))],
+        cuts in proptest::collection::vec(any::<proptest::sample::Index>(), 0..8),
+    ) {
+        let bounds = boundaries(&doc);
+        let mut cuts: Vec<usize> = cuts.iter().map(|i| *i.get(&bounds)).collect();
+        cuts.push(doc.len());
+        cuts.sort_unstable();
+        cuts.dedup();
+
+        let full = surf_parse::parse(&doc);
+        let full_blocks = block_json(&full.doc.blocks);
+        let mut stream = StreamingParser::new();
+        let mut streamed = Vec::new();
+        let mut fed = 0;
+        for cut in cuts {
+            for c in stream.feed(&doc[fed..cut]) {
+                prop_assert_eq!(c.index, streamed.len());
+                streamed.push(serde_json::to_value(&c.block).unwrap());
+            }
+            fed = cut;
+            let so_far = block_json(&surf_parse::parse(&doc[..fed]).doc.blocks);
+            prop_assert!(streamed.len() <= so_far.len() && streamed[..] == so_far[..streamed.len()]);
+            prop_assert!(streamed.len() <= full_blocks.len() && streamed[..] == full_blocks[..streamed.len()]);
+        }
+        let (rest, result) = stream.finish();
+        streamed.extend(rest.iter().map(|c| serde_json::to_value(&c.block).unwrap()));
+        prop_assert_eq!(&streamed, &full_blocks);
+        prop_assert_eq!(
+            serde_json::to_value(&result.doc).unwrap(),
+            serde_json::to_value(&full.doc).unwrap()
+        );
+        prop_assert_eq!(
+            serde_json::to_value(&result.diagnostics).unwrap(),
+            serde_json::to_value(&full.diagnostics).unwrap()
+        );
+    }
+}
+
+// ── Lossless CST: leaves tile the source; typed blocks link to their nodes ──
+
+use surf_parse::cst::CstKind;
+
+const CST_FRAGMENTS: &[&str] = &[
+    "::callout[type=warning title=\"Mind \\\"it\\\"\" dismissible] trailing\nX\n::\n",
+    "  ::summary  \nIndented opener\n  ::  \n",
+    "::figure[src=a.png caption=\"[[link]]\"]\n",
+    "::callout[type=\"open]\nBad attrs\n::\n",
+    "::callout[\n",
+    "::::\n",
+    "::page[route=/b]\n::hero-image[src=x.png]\n::cta[label=Go href=/]\n::\n",
+];
+
+fn arb_cst_doc() -> impl Strategy<Value = String> {
+    let fragment = prop_oneof![
+        proptest::sample::select(DOC_FRAGMENTS),
+        proptest::sample::select(CST_FRAGMENTS),
+    ];
+    proptest::collection::vec(fragment, 0..14).prop_map(|parts| parts.concat())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(500))]
+
+    #[test]
+    fn cst_is_lossless_and_linked(doc in prop_oneof![
+        arb_cst_doc(),
+        arb_cst_doc().prop_map(|d| d.replace('\n', "\r\n")),
+    ]) {
+        let parsed = surf_parse::parse(&doc).doc;
+        let cst = parsed.cst();
+        let source = &parsed.source;
+
+        let rebuilt: String = cst.leaves().map(|l| l.text(source)).collect();
+        prop_assert_eq!(&rebuilt, source);
+
+        // Children tile their parent exactly.
+        for node in cst.root().descendants() {
+            let mut at = node.span.start_offset;
+            for child in &node.children {
+                prop_assert_eq!(child.span.start_offset, at, "{:?} in {:?}", child.kind, node.kind);
+                at = child.span.end_offset;
+            }
+            if !node.children.is_empty() {
+                prop_assert_eq!(at, node.span.end_offset);
+            }
+        }
+
+        for (i, block) in parsed.blocks.iter().enumerate() {
+            let node = cst.block_node(i);
+            prop_assert!(node.is_some(), "block {} unlinked", i);
+            let node = node.unwrap();
+            prop_assert_eq!(node.span, block.span());
+            let expected = if matches!(block, Block::Markdown { .. }) {
+                CstKind::Markdown
+            } else {
+                CstKind::Directive
+            };
+            prop_assert_eq!(node.kind, expected);
+        }
+
+        // Nested blocks link through their containers' children.
+        let mut stack: Vec<(Vec<usize>, &Block)> =
+            parsed.blocks.iter().enumerate().map(|(i, b)| (vec![i], b)).collect();
+        while let Some((path, block)) = stack.pop() {
+            prop_assert!(cst.node_at(&path).is_some(), "block {:?} unlinked", path);
+            for (i, child) in block.children().unwrap_or_default().iter().enumerate() {
+                stack.push(([path.as_slice(), &[i]].concat(), child));
+            }
+        }
+    }
+}
@@ -35,6 +35,8 @@ const ENUM_VARIANTS: &[&str] = &[
     "SuggestionChips", "TabBar", "TabContent", "Terminal", "Toolbar",
     // Messages/Contacts vocabulary (0.12)
     "RecipientPicker", "Qr",
+    // Citations
+    "BibliographyData",
 ];
 
 #[derive(Debug, Deserialize)]
@@ -138,10 +140,8 @@ fn lint_rule_registry_matches_implementation() {
 
     // CHECK 1: every style-layer registry entry has an implemented rule, and
     // every implemented rule is registered (bidirectional).
-    let implemented: BTreeSet<&str> = surf_parse::lint::all_rules()
-        .iter()
-        .map(|r| r.id())
-        .collect();
+    let rules = surf_parse::lint::all_rules();
+    let implemented: BTreeSet<&str> = rules.iter().map(|r| r.id()).collect();
     let style_ids: BTreeSet<&str> = registry
         .iter()
         .filter(|(_, m)| m.layer == "style")